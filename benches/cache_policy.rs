@@ -0,0 +1,85 @@
+//! Compare `MemoryCache` hit rates across eviction policies.
+//!
+//! Run with `cargo bench --bench cache_policy`. Each workload replays the
+//! same synthetic key sequence against every policy and reports the
+//! embedding-cache hit rate and wall time.
+
+use openagent::memory::cache::{CachePolicy, MemoryCache};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
+
+const CAPACITY: u64 = 200;
+const REQUESTS: usize = 50_000;
+
+/// Generates the key sequence for one workload
+type Workload = (&'static str, fn(&mut StdRng) -> Vec<u64>);
+
+/// Skewed (read-heavy) pattern: 80% of requests hit 10% of the key space.
+fn hot_set(rng: &mut StdRng) -> Vec<u64> {
+    (0..REQUESTS)
+        .map(|_| {
+            if rng.random_bool(0.8) {
+                rng.random_range(0..100)
+            } else {
+                rng.random_range(100..1_000)
+            }
+        })
+        .collect()
+}
+
+/// Shifting (write-heavy) pattern: the hot window slides through the key space.
+fn sliding_window(rng: &mut StdRng) -> Vec<u64> {
+    (0..REQUESTS)
+        .map(|i| (i as u64 / 50) + rng.random_range(0..150))
+        .collect()
+}
+
+/// Uniform scan over a key space larger than the cache.
+fn uniform(rng: &mut StdRng) -> Vec<u64> {
+    (0..REQUESTS).map(|_| rng.random_range(0..1_000)).collect()
+}
+
+async fn hit_rate(policy: CachePolicy, keys: &[u64]) -> f64 {
+    let cache = MemoryCache::new(policy);
+    let mut hits = 0usize;
+    for key in keys {
+        let text = key.to_string();
+        if cache.get_embedding(&text).await.is_some() {
+            hits += 1;
+        } else {
+            cache.put_embedding(&text, vec![0.0; 8]).await;
+        }
+    }
+    hits as f64 / keys.len() as f64
+}
+
+#[tokio::main]
+async fn main() {
+    let policies = [
+        ("lru", CachePolicy::Lru { max_capacity: CAPACITY }),
+        ("lfu", CachePolicy::Lfu { max_capacity: CAPACITY }),
+        ("ttl", CachePolicy::Ttl { max_capacity: CAPACITY, entry_ttl_secs: 1800 }),
+    ];
+    let workloads: [Workload; 3] = [
+        ("hot_set", hot_set),
+        ("sliding_window", sliding_window),
+        ("uniform", uniform),
+    ];
+
+    println!("{:<16} {:<6} {:>9} {:>10}", "workload", "policy", "hit rate", "time");
+    for (workload, generate) in workloads {
+        let keys = generate(&mut StdRng::seed_from_u64(42));
+        for (name, policy) in policies {
+            let start = Instant::now();
+            let rate = hit_rate(policy, &keys).await;
+            println!(
+                "{:<16} {:<6} {:>8.1}% {:>8}ms",
+                workload,
+                name,
+                rate * 100.0,
+                start.elapsed().as_millis()
+            );
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Compare JSON and CBOR encoding of gateway frames.
+//!
+//! Run with `cargo bench --bench frame_encoding`. Reports encoded size and
+//! average encode/decode time for a representative `AgentResponse` frame.
+
+use openagent::gateway::protocol::schema::ResponseFrame;
+use openagent::gateway::{AgentResponse, GatewayFrame, ProtocolEncoding, UsageStats};
+use std::hint::black_box;
+use std::time::Instant;
+
+const ITERATIONS: u32 = 20_000;
+
+fn sample_frame() -> GatewayFrame {
+    let response = AgentResponse {
+        session_id: "6f1c2d9e-7a43-4c1b-9e0f-2b8d5a7c3e11".to_string(),
+        content: "Here is a summary of today's tasks:\n\n".repeat(8)
+            + "1. Review the pull request\n2. Reply to the design thread\n3. Deploy the gateway",
+        model: "anthropic/claude-sonnet-4".to_string(),
+        finish_reason: Some("stop".to_string()),
+        usage: Some(UsageStats {
+            prompt_tokens: 1843,
+            completion_tokens: 412,
+            total_tokens: 2255,
+            cost: Some(0.0123),
+        }),
+        continuation_token: None,
+    };
+    GatewayFrame::Response(ResponseFrame::success(
+        "req-42",
+        serde_json::to_value(response).unwrap(),
+    ))
+}
+
+fn main() {
+    let frame = sample_frame();
+
+    println!("{:<6} {:>8} {:>12} {:>12}", "format", "bytes", "encode (ns)", "decode (ns)");
+    for encoding in [ProtocolEncoding::Json, ProtocolEncoding::Cbor] {
+        let encoded = frame.encode(encoding).unwrap();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(black_box(&frame).encode(encoding).unwrap());
+        }
+        let encode_ns = start.elapsed().as_nanos() / ITERATIONS as u128;
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            black_box(GatewayFrame::decode(black_box(&encoded), encoding).unwrap());
+        }
+        let decode_ns = start.elapsed().as_nanos() / ITERATIONS as u128;
+
+        println!(
+            "{:<6} {:>8} {:>12} {:>12}",
+            format!("{:?}", encoding).to_lowercase(),
+            encoded.len(),
+            encode_ns,
+            decode_ns
+        );
+    }
+}
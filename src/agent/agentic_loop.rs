@@ -4,22 +4,30 @@
 //! and scheduler into a single reusable function with configurable callbacks,
 //! limits, and structured tracing.
 
-use crate::agent::loop_guard::LoopGuard;
+use crate::agent::loop_guard::{default_stuck_loop_backoff, LoopGuard, LoopGuardAction};
+use crate::agent::model_metadata::estimate_tokens;
+use crate::agent::streaming::StreamAccumulator;
 use crate::agent::types::*;
 use crate::agent::OpenRouterClient;
-use crate::error::Result;
-use crate::tools::{ToolCall, ToolRegistry};
+use crate::error::{Error, Result};
+use crate::tools::{ToolCall, ToolRegistry, ToolResult};
 
 use async_trait::async_trait;
-use std::time::Instant;
-use tracing::{debug, info, warn};
+use futures::StreamExt;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, field, info, info_span, warn, Instrument, Span};
 
 // ---------------------------------------------------------------------------
 // Configuration
 // ---------------------------------------------------------------------------
 
 /// Configurable limits for the agentic loop.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopConfig {
     /// Maximum LLM round-trips before the loop is forcefully stopped.
     pub max_iterations: u32,
@@ -29,10 +37,95 @@ pub struct LoopConfig {
     pub generation_options: GenerationOptions,
     /// If true, inject a planning system message before the first iteration.
     pub enable_planning_prompt: bool,
+    /// Model that writes the plan when `enable_planning_prompt` is set. Its
+    /// plan is injected as a system message; without one the primary model
+    /// is just told to plan first.
+    #[serde(default)]
+    pub planning_model: Option<String>,
     /// If true, inject a reflection system message after each tool-result batch.
     pub enable_reflection_prompt: bool,
     /// Fallback text returned when the loop exits without a final response.
     pub fallback_message: String,
+    /// Delays slept before the next LLM call on successive stuck-loop
+    /// detections. Once exhausted, the loop stops with `LoopOutcome::StuckLoop`.
+    pub stuck_loop_backoff: Vec<Duration>,
+    /// Estimated token budget for tool results. Once exceeded, tools are
+    /// withdrawn and the LLM is asked to summarize and conclude.
+    pub token_budget: Option<u32>,
+    /// JSON schema the final response must match. When set, the LLM is told
+    /// to answer with a single JSON object conforming to it.
+    #[serde(default)]
+    pub structured_output: Option<serde_json::Value>,
+    /// Event sources on the tool registry's `ToolEventBus` to listen on.
+    /// Events pushed during the run are added as system messages before the
+    /// next LLM call.
+    #[serde(default)]
+    pub event_sources: Vec<String>,
+    /// Soul hints keyed by tool name. Each is added as a system message
+    /// ahead of that tool's results.
+    #[serde(default)]
+    pub tool_preferences: HashMap<String, String>,
+    /// Stream LLM responses, reporting text to `LoopCallback::on_content_delta`
+    /// as it is generated.
+    #[serde(default)]
+    pub stream: bool,
+    /// Longest tool result, in characters, added to the context. Longer
+    /// results are cut down with `tool_result_compression_strategy`.
+    #[serde(default)]
+    pub tool_result_max_chars: Option<usize>,
+    /// Which part of an oversized tool result to keep.
+    #[serde(default)]
+    pub tool_result_compression_strategy: TruncationStrategy,
+    /// Most tool calls from one LLM response run at the same time (see
+    /// `ToolRegistry::execute_batch`); per-tool limits still apply. The
+    /// default of 1 keeps calls that depend on each other in order.
+    #[serde(default = "default_parallel_tool_calls")]
+    pub max_parallel_tool_calls: usize,
+}
+
+fn default_parallel_tool_calls() -> usize {
+    1
+}
+
+/// Which part of an oversized tool result is kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TruncationStrategy {
+    /// Keep the beginning and drop the tail.
+    #[default]
+    Head,
+    /// Keep the end, e.g. for logs where the latest lines matter.
+    Tail,
+    /// Keep the beginning and the end and drop the middle.
+    Middle,
+}
+
+impl TruncationStrategy {
+    /// `text` cut to `max_chars` characters plus a truncation note, or
+    /// `None` if it already fits.
+    pub fn truncate(self, text: &str, max_chars: usize) -> Option<String> {
+        let total = text.chars().count();
+        if total <= max_chars {
+            return None;
+        }
+        // Byte offset of the `n`th character
+        let offset = |n: usize| text.char_indices().nth(n).map_or(text.len(), |(i, _)| i);
+        let note = format!("[truncated to {} chars; total {} chars]", max_chars, total);
+        Some(match self {
+            TruncationStrategy::Head => format!("{}\n...{}", &text[..offset(max_chars)], note),
+            TruncationStrategy::Tail => format!("{}...\n{}", note, &text[offset(total - max_chars)..]),
+            TruncationStrategy::Middle => {
+                let head = max_chars.div_ceil(2);
+                let tail = max_chars - head;
+                format!(
+                    "{}\n...{}...\n{}",
+                    &text[..offset(head)],
+                    note,
+                    &text[offset(total - tail)..]
+                )
+            }
+        })
+    }
 }
 
 impl LoopConfig {
@@ -43,8 +136,18 @@ impl LoopConfig {
             max_tool_calls: 30,
             generation_options: GenerationOptions::balanced(),
             enable_planning_prompt: false,
+            planning_model: None,
             enable_reflection_prompt: false,
             fallback_message: "I searched for information but couldn't find specific results. Please try a more specific query.".into(),
+            stuck_loop_backoff: default_stuck_loop_backoff(),
+            token_budget: None,
+            structured_output: None,
+            event_sources: Vec::new(),
+            tool_preferences: HashMap::new(),
+            stream: false,
+            tool_result_max_chars: None,
+            tool_result_compression_strategy: TruncationStrategy::default(),
+            max_parallel_tool_calls: default_parallel_tool_calls(),
         }
     }
 
@@ -55,8 +158,18 @@ impl LoopConfig {
             max_tool_calls: 20,
             generation_options: GenerationOptions::balanced(),
             enable_planning_prompt: false,
+            planning_model: None,
             enable_reflection_prompt: false,
             fallback_message: "I reached the maximum number of iterations. Please try a more specific request.".into(),
+            stuck_loop_backoff: default_stuck_loop_backoff(),
+            token_budget: None,
+            structured_output: None,
+            event_sources: Vec::new(),
+            tool_preferences: HashMap::new(),
+            stream: false,
+            tool_result_max_chars: None,
+            tool_result_compression_strategy: TruncationStrategy::default(),
+            max_parallel_tool_calls: default_parallel_tool_calls(),
         }
     }
 
@@ -67,10 +180,67 @@ impl LoopConfig {
             max_tool_calls: 20,
             generation_options: GenerationOptions::balanced(),
             enable_planning_prompt: false,
+            planning_model: None,
             enable_reflection_prompt: false,
             fallback_message: String::new(),
+            stuck_loop_backoff: default_stuck_loop_backoff(),
+            token_budget: None,
+            structured_output: None,
+            event_sources: Vec::new(),
+            tool_preferences: HashMap::new(),
+            stream: false,
+            tool_result_max_chars: None,
+            tool_result_compression_strategy: TruncationStrategy::default(),
+            max_parallel_tool_calls: default_parallel_tool_calls(),
         }
     }
+
+    /// Plan with `model` (typically a faster, cheaper one) before running
+    /// the tool loop on the primary model. Enables the planning prompt.
+    pub fn with_planning_model(mut self, model: &str) -> Self {
+        self.enable_planning_prompt = true;
+        self.planning_model = Some(model.to_string());
+        self
+    }
+
+    /// Ask for a final response that is a JSON object matching `T`'s schema.
+    /// Use `run_agentic_loop_typed` to get the parsed value.
+    pub fn with_structured_output<T: JsonSchema + DeserializeOwned>(mut self) -> Self {
+        self.structured_output = Some(schemars::schema_for!(T).to_value());
+        self
+    }
+
+    /// Add events pushed on `source` (e.g. an MCP subscription) to the
+    /// context as they arrive
+    pub fn with_event_source(mut self, source: impl Into<String>) -> Self {
+        self.event_sources.push(source.into());
+        self
+    }
+
+    /// Remind the LLM of the soul's per-tool preferences (see
+    /// `Soul::tool_preferences`) when it reads those tools' results
+    pub fn with_tool_preferences<'a>(mut self, preferences: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        self.tool_preferences.extend(
+            preferences
+                .into_iter()
+                .map(|(tool, preference)| (tool.to_string(), preference.to_string())),
+        );
+        self
+    }
+
+    /// Stream LLM responses so callbacks can show them as they arrive
+    pub fn with_streaming(mut self) -> Self {
+        self.stream = true;
+        self
+    }
+
+    /// Cut tool results longer than `max_chars` characters before they are
+    /// added to the context, keeping the part chosen by
+    /// `tool_result_compression_strategy`
+    pub fn with_tool_result_compression(mut self, max_chars: usize) -> Self {
+        self.tool_result_max_chars = Some(max_chars);
+        self
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -78,24 +248,30 @@ impl LoopConfig {
 // ---------------------------------------------------------------------------
 
 /// A recorded action (tool call) and its observation (result).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolAction {
     pub tool_name: String,
     pub arguments: String,
     pub observation: ToolObservation,
+    /// ID (conversation checkpoint) of the user message that started the
+    /// run; `None` when the message was never added to a conversation.
+    #[serde(default)]
+    pub parent_message_id: Option<String>,
 }
 
 /// The result of executing a single tool call.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolObservation {
     pub success: bool,
     pub content: String,
     pub duration_ms: u64,
     pub loop_guard_triggered: bool,
+    /// Structured metadata reported by the tool (`ToolResult::metadata`)
+    pub metadata: HashMap<String, serde_json::Value>,
 }
 
 /// One iteration of the agentic loop.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopStep {
     pub iteration: u32,
     /// Text content produced by the LLM in this iteration (may be empty).
@@ -104,19 +280,108 @@ pub struct LoopStep {
     pub actions: Vec<ToolAction>,
     /// The LLM's finish_reason for this iteration.
     pub finish_reason: String,
+    /// Not persisted; a deserialized step gets the time it was loaded.
+    #[serde(skip, default = "Instant::now")]
     pub timestamp: Instant,
 }
 
 /// Full trace of a loop execution.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopTrace {
     pub steps: Vec<LoopStep>,
     pub outcome: LoopOutcome,
     pub total_duration_ms: u64,
+    /// Inputs and LLM responses, for `LoopTrace::replay`.
+    pub recording: LoopRecording,
+}
+
+/// What a loop run started from and what the LLM answered — enough to
+/// re-run it without calling the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopRecording {
+    /// Messages passed in, before any planning prompt was injected.
+    pub messages: Vec<Message>,
+    pub config: LoopConfig,
+    pub user_id: Option<String>,
+    pub chat_id: Option<i64>,
+    /// Every successful LLM response of the tool loop, in call order.
+    pub responses: Vec<ChatCompletionResponse>,
+    /// The planning model's response, if one was used and answered.
+    #[serde(default)]
+    pub planning_response: Option<ChatCompletionResponse>,
+}
+
+impl LoopTrace {
+    /// Re-run the recorded loop against `tools`, answering every LLM call
+    /// with the recorded response instead of calling the API.
+    ///
+    /// Tools are executed for real, so differences in tool behaviour show
+    /// up in the new trace. Loop guard backoffs are not slept. A recorded
+    /// LLM error is reproduced as "no recorded response left".
+    pub async fn replay(
+        &self,
+        client: &OpenRouterClient,
+        tools: &ToolRegistry,
+    ) -> Result<AgentLoopOutput> {
+        let recording = &self.recording;
+        let mut responses = recording.responses.clone();
+        let mut config = recording.config.clone();
+        match recording.planning_response {
+            Some(ref plan) => responses.insert(0, plan.clone()),
+            // The planning call failed (or never ran): fall back the same way
+            None => config.planning_model = None,
+        }
+        let llm_client = client.replaying(responses);
+        config.stuck_loop_backoff = vec![Duration::ZERO; config.stuck_loop_backoff.len()];
+
+        run_agentic_loop(AgentLoopInput {
+            messages: recording.messages.clone(),
+            llm_client: &llm_client,
+            tools,
+            tool_definitions: tools.definitions(),
+            config,
+            user_id: recording.user_id.clone(),
+            chat_id: recording.chat_id,
+            callback: NoOpCallback::new(),
+        })
+        .await
+    }
+
+    /// Panic unless `other` took the same path: same outcome, and per
+    /// iteration the same finish reason, text and tool calls (name,
+    /// arguments, success). Tool output and timings are not compared, and
+    /// any two `LlmError`s match since replay cannot reproduce the message.
+    pub fn assert_same_outcome(&self, other: &LoopTrace) {
+        match (&self.outcome, &other.outcome) {
+            (LoopOutcome::LlmError(_), LoopOutcome::LlmError(_)) => {}
+            (a, b) => assert_eq!(a, b, "loop outcome differs"),
+        }
+        assert_eq!(
+            self.steps.len(),
+            other.steps.len(),
+            "number of iterations differs"
+        );
+
+        let calls = |step: &LoopStep| -> Vec<(String, String, bool)> {
+            step.actions
+                .iter()
+                .map(|a| (a.tool_name.clone(), a.arguments.clone(), a.observation.success))
+                .collect()
+        };
+        for (a, b) in self.steps.iter().zip(&other.steps) {
+            assert_eq!(
+                a.finish_reason, b.finish_reason,
+                "finish_reason differs in iteration {}",
+                a.iteration
+            );
+            assert_eq!(a.thought, b.thought, "LLM text differs in iteration {}", a.iteration);
+            assert_eq!(calls(a), calls(b), "tool calls differ in iteration {}", a.iteration);
+        }
+    }
 }
 
 /// How the loop finished.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LoopOutcome {
     /// LLM returned finish_reason "stop" / "end_turn".
     Completed,
@@ -128,6 +393,10 @@ pub enum LoopOutcome {
     EmptyResponse,
     /// LLM API returned an error.
     LlmError(String),
+    /// The loop guard kept firing after its hint and backoff stages.
+    StuckLoop,
+    /// Exceeded `token_budget`; final response taken from content-only call.
+    TokenBudgetExceeded,
 }
 
 // ---------------------------------------------------------------------------
@@ -140,12 +409,18 @@ pub enum LoopOutcome {
 pub trait LoopCallback: Send + Sync {
     /// Called at the start of each iteration, before the LLM call.
     async fn on_iteration_start(&self, _iteration: u32) {}
+    /// Called with each piece of response text when `LoopConfig::stream` is set.
+    async fn on_content_delta(&self, _delta: &str) {}
     /// Called after each individual tool has been executed.
     async fn on_tool_executed(&self, _tool_name: &str, _observation: &ToolObservation) {}
     /// Called at the end of each iteration, after all tool results are collected.
     async fn on_iteration_end(&self, _step: &LoopStep) {}
     /// Called once after the loop terminates.
     async fn on_loop_complete(&self, _trace: &LoopTrace) {}
+    /// Called when an LLM call or a tool execution returns an error, before
+    /// the loop reacts to it. Tool failures reported as `ToolResult`s are
+    /// not errors.
+    async fn on_error(&self, _err: &Error, _iteration: u32) {}
 }
 
 /// Default no-op callback.
@@ -158,6 +433,95 @@ impl NoOpCallback {
 #[async_trait]
 impl LoopCallback for NoOpCallback {}
 
+/// Lets one callback observe several runs, e.g. the retries of
+/// `run_agentic_loop_typed`.
+#[async_trait]
+impl<C: LoopCallback + ?Sized> LoopCallback for &C {
+    async fn on_iteration_start(&self, iteration: u32) {
+        (**self).on_iteration_start(iteration).await
+    }
+    async fn on_content_delta(&self, delta: &str) {
+        (**self).on_content_delta(delta).await
+    }
+    async fn on_tool_executed(&self, tool_name: &str, observation: &ToolObservation) {
+        (**self).on_tool_executed(tool_name, observation).await
+    }
+    async fn on_iteration_end(&self, step: &LoopStep) {
+        (**self).on_iteration_end(step).await
+    }
+    async fn on_loop_complete(&self, trace: &LoopTrace) {
+        (**self).on_loop_complete(trace).await
+    }
+    async fn on_error(&self, err: &Error, iteration: u32) {
+        (**self).on_error(err, iteration).await
+    }
+}
+
+/// Disabled callbacks (`None`) ignore every event.
+#[async_trait]
+impl<C: LoopCallback> LoopCallback for Option<C> {
+    async fn on_iteration_start(&self, iteration: u32) {
+        if let Some(c) = self {
+            c.on_iteration_start(iteration).await
+        }
+    }
+    async fn on_content_delta(&self, delta: &str) {
+        if let Some(c) = self {
+            c.on_content_delta(delta).await
+        }
+    }
+    async fn on_tool_executed(&self, tool_name: &str, observation: &ToolObservation) {
+        if let Some(c) = self {
+            c.on_tool_executed(tool_name, observation).await
+        }
+    }
+    async fn on_iteration_end(&self, step: &LoopStep) {
+        if let Some(c) = self {
+            c.on_iteration_end(step).await
+        }
+    }
+    async fn on_loop_complete(&self, trace: &LoopTrace) {
+        if let Some(c) = self {
+            c.on_loop_complete(trace).await
+        }
+    }
+    async fn on_error(&self, err: &Error, iteration: u32) {
+        if let Some(c) = self {
+            c.on_error(err, iteration).await
+        }
+    }
+}
+
+/// Runs two callbacks, first `.0` then `.1`, e.g. a channel's typing
+/// indicator plus error reporting.
+#[async_trait]
+impl<A: LoopCallback, B: LoopCallback> LoopCallback for (A, B) {
+    async fn on_iteration_start(&self, iteration: u32) {
+        self.0.on_iteration_start(iteration).await;
+        self.1.on_iteration_start(iteration).await;
+    }
+    async fn on_content_delta(&self, delta: &str) {
+        self.0.on_content_delta(delta).await;
+        self.1.on_content_delta(delta).await;
+    }
+    async fn on_tool_executed(&self, tool_name: &str, observation: &ToolObservation) {
+        self.0.on_tool_executed(tool_name, observation).await;
+        self.1.on_tool_executed(tool_name, observation).await;
+    }
+    async fn on_iteration_end(&self, step: &LoopStep) {
+        self.0.on_iteration_end(step).await;
+        self.1.on_iteration_end(step).await;
+    }
+    async fn on_loop_complete(&self, trace: &LoopTrace) {
+        self.0.on_loop_complete(trace).await;
+        self.1.on_loop_complete(trace).await;
+    }
+    async fn on_error(&self, err: &Error, iteration: u32) {
+        self.0.on_error(err, iteration).await;
+        self.1.on_error(err, iteration).await;
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Input / Output
 // ---------------------------------------------------------------------------
@@ -192,6 +556,8 @@ pub struct AgentLoopOutput {
     pub final_messages: Vec<Message>,
     /// Accumulated token usage across all iterations.
     pub total_usage: Usage,
+    /// Token usage of the planning model call, not included in `total_usage`.
+    pub planning_usage: Option<Usage>,
 }
 
 // ---------------------------------------------------------------------------
@@ -201,33 +567,170 @@ pub struct AgentLoopOutput {
 /// Run the unified agentic loop.
 ///
 /// Calls the LLM, executes tool calls, feeds results back, and repeats until
-/// the LLM stops requesting tools or limits are hit.
+/// the LLM stops requesting tools or limits are hit. The whole run is wrapped
+/// in an `agent.loop` span with one `agent.llm_call` child per LLM request.
 pub async fn run_agentic_loop<C: LoopCallback>(
     input: AgentLoopInput<'_, C>,
 ) -> Result<AgentLoopOutput> {
+    let span = info_span!(
+        "agent.loop",
+        model = %input.llm_client.default_model(),
+        user_id = input.user_id.as_deref().unwrap_or(""),
+        iterations = field::Empty,
+        tool_calls = field::Empty,
+        estimated_tokens = field::Empty,
+        prompt_tokens = field::Empty,
+        completion_tokens = field::Empty,
+        total_tokens = field::Empty,
+        outcome = field::Empty,
+        duration_ms = field::Empty,
+    );
+    run_loop(input).instrument(span).await
+}
+
+/// Correction rounds `run_agentic_loop_typed` allows after the first answer.
+pub const STRUCTURED_OUTPUT_RETRIES: u32 = 3;
+
+/// Run the loop and parse its final response into `T`.
+///
+/// The LLM is asked for a JSON object matching `T`'s schema (or the schema
+/// already set with `LoopConfig::with_structured_output`). If the response
+/// does not deserialize, the conversation continues with the parse error as
+/// a correction prompt, up to `STRUCTURED_OUTPUT_RETRIES` times. The
+/// returned trace is that of the last run.
+pub async fn run_agentic_loop_typed<T, C>(input: AgentLoopInput<'_, C>) -> Result<(T, LoopTrace)>
+where
+    T: JsonSchema + DeserializeOwned,
+    C: LoopCallback,
+{
     let AgentLoopInput {
         mut messages,
         llm_client,
         tools,
         tool_definitions,
-        config,
+        mut config,
+        user_id,
+        chat_id,
+        callback,
+    } = input;
+    if config.structured_output.is_none() {
+        config = config.with_structured_output::<T>();
+    }
+
+    let mut retries = 0;
+    loop {
+        let output = run_agentic_loop(AgentLoopInput {
+            messages,
+            llm_client,
+            tools,
+            tool_definitions: tool_definitions.clone(),
+            config: config.clone(),
+            user_id: user_id.clone(),
+            chat_id,
+            callback: &callback,
+        })
+        .await?;
+        if let LoopOutcome::LlmError(ref e) = output.trace.outcome {
+            return Err(Error::Provider(e.clone()));
+        }
+
+        let error = match parse_structured_output::<T>(&output.response) {
+            Ok(value) => return Ok((value, output.trace)),
+            Err(e) => e,
+        };
+        if retries == STRUCTURED_OUTPUT_RETRIES {
+            return Err(Error::Validation(format!(
+                "Agent response did not match the expected schema after {} retries: {}",
+                retries, error
+            )));
+        }
+        retries += 1;
+        warn!("Structured response failed to parse (retry {}): {}", retries, error);
+
+        // The schema instruction (and any plan) is already in the context
+        messages = output.final_messages;
+        messages.push(Message::assistant(&output.response));
+        messages.push(Message::user(format!(
+            "Your response could not be parsed: {}. Reply with only a JSON object \
+matching the schema, without any other text.",
+            error
+        )));
+        config.structured_output = None;
+        config.enable_planning_prompt = false;
+    }
+}
+
+/// Checkpoint ID of the last user message, if it belongs to a conversation
+fn current_user_message_id(messages: &[Message]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User)
+        .filter(|m| !m.checkpoint_id.is_nil())
+        .map(|m| m.checkpoint_id.to_string())
+}
+
+async fn run_loop<C: LoopCallback>(input: AgentLoopInput<'_, C>) -> Result<AgentLoopOutput> {
+    let AgentLoopInput {
+        mut messages,
+        llm_client,
+        tools,
+        tool_definitions,
+        mut config,
         user_id,
         chat_id,
         callback,
     } = input;
 
     let loop_start = Instant::now();
+    let parent_message_id = current_user_message_id(&messages);
+    let mut events: Vec<(String, broadcast::Receiver<ToolResult>)> = config
+        .event_sources
+        .iter()
+        .map(|source| (source.clone(), tools.events().subscribe(source)))
+        .collect();
+    let mut recording = LoopRecording {
+        messages: messages.clone(),
+        config: config.clone(),
+        user_id: user_id.clone(),
+        chat_id,
+        responses: Vec::new(),
+        planning_response: None,
+    };
 
-    // Optionally inject planning instructions
+    // Optionally plan first, with a separate model if one is configured
+    let mut planning_usage = None;
     if config.enable_planning_prompt {
-        inject_planning_instructions(&mut messages);
+        let plan = match config.planning_model {
+            Some(ref model) => request_plan(llm_client, model, &messages, &config).await,
+            None => None,
+        };
+        match plan {
+            Some((plan, response)) => {
+                planning_usage = response.usage.clone();
+                recording.planning_response = Some(response);
+                messages.push(Message::system(format!("Plan for this request:\n{}", plan)));
+            }
+            None => inject_planning_instructions(&mut messages),
+        }
+    }
+    if let Some(ref schema) = config.structured_output {
+        inject_structured_output_instructions(&mut messages, schema);
     }
 
     let mut iteration: u32 = 0;
     let mut tool_calls_made: u32 = 0;
     let mut final_response = String::new();
-    let mut loop_guard = LoopGuard::default();
+    let mut loop_guard = LoopGuard::with_backoff(3, config.stuck_loop_backoff.clone());
+    if let Some(schedule) = config.generation_options.adaptive_temperature {
+        loop_guard = loop_guard.with_adaptive_temperature(schedule);
+    }
+    let mut pending_backoff: Option<Duration> = None;
     let mut steps: Vec<LoopStep> = Vec::new();
+    // Usage is only reported once a response completes, so track tool-result
+    // tokens ourselves as they are added to the context.
+    let mut estimated_tokens: u32 = 0;
+    let mut budget_exceeded = false;
     let mut total_usage = Usage {
         prompt_tokens: 0,
         completion_tokens: 0,
@@ -255,29 +758,75 @@ pub async fn run_agentic_loop<C: LoopCallback>(
             break;
         }
 
+        // Honour a backoff requested by the loop guard in the previous iteration
+        if let Some(wait) = pending_backoff.take() {
+            info!("Loop guard backoff: sleeping {:?} before next LLM call", wait);
+            tokio::time::sleep(wait).await;
+        }
+
+        if let Some(temperature) = loop_guard.temperature() {
+            config.generation_options.temperature = Some(temperature);
+        }
+
+        inject_pushed_events(&mut messages, &mut events);
+
         // Decide whether to send tool definitions
-        let use_tools = tool_calls_made < config.max_tool_calls && !tool_definitions.is_empty();
+        let use_tools = !budget_exceeded
+            && tool_calls_made < config.max_tool_calls
+            && !tool_definitions.is_empty();
 
         // Call LLM
-        let response = if use_tools {
-            llm_client
-                .chat_with_tools(
-                    messages.clone(),
-                    tool_definitions.clone(),
-                    config.generation_options.clone(),
-                )
-                .await
-        } else {
-            llm_client
-                .chat(messages.clone(), config.generation_options.clone())
-                .await
-        };
+        let llm_span = info_span!(
+            "agent.llm_call",
+            model = %llm_client.default_model(),
+            iteration,
+            with_tools = use_tools,
+            prompt_tokens = field::Empty,
+            completion_tokens = field::Empty,
+            total_tokens = field::Empty,
+            duration_ms = field::Empty,
+        );
+        let llm_start = Instant::now();
+        let response = async {
+            if config.stream {
+                let tools = use_tools.then(|| tool_definitions.clone());
+                stream_completion(llm_client, messages.clone(), tools, config.generation_options.clone(), &callback)
+                    .await
+            } else if use_tools {
+                llm_client
+                    .chat_with_tools(
+                        messages.clone(),
+                        tool_definitions.clone(),
+                        config.generation_options.clone(),
+                    )
+                    .await
+            } else {
+                llm_client
+                    .chat(messages.clone(), config.generation_options.clone())
+                    .await
+            }
+        }
+        .instrument(llm_span.clone())
+        .await;
+
+        llm_span.record("duration_ms", llm_start.elapsed().as_millis() as u64);
+        if let Ok(resp) = &response {
+            llm_span.record("model", resp.model.as_str());
+            if let Some(usage) = &resp.usage {
+                llm_span.record("prompt_tokens", usage.prompt_tokens);
+                llm_span.record("completion_tokens", usage.completion_tokens);
+                llm_span.record("total_tokens", usage.total_tokens);
+            }
+        }
 
         let response = match response {
-            Ok(resp) => resp,
+            Ok(resp) => {
+                recording.responses.push(resp.clone());
+                resp
+            }
             Err(e) => {
-                let err_str = e.to_string();
-                outcome = LoopOutcome::LlmError(err_str);
+                callback.on_error(&e, iteration).await;
+                outcome = LoopOutcome::LlmError(e.to_string());
                 break;
             }
         };
@@ -332,7 +881,11 @@ pub async fn run_agentic_loop<C: LoopCallback>(
             };
             callback.on_iteration_end(&step).await;
             steps.push(step);
-            outcome = LoopOutcome::Completed;
+            outcome = if budget_exceeded {
+                LoopOutcome::TokenBudgetExceeded
+            } else {
+                LoopOutcome::Completed
+            };
             break;
         }
 
@@ -350,7 +903,14 @@ pub async fn run_agentic_loop<C: LoopCallback>(
                     messages.push(choice.message.clone());
 
                     let mut actions = Vec::new();
-
+                    let mut stuck = false;
+                    // Preferences and loop guard hints wait until every tool
+                    // call has its result, so the tool replies directly
+                    // follow the assistant message that requested them
+                    let mut preferences: Vec<String> = Vec::new();
+                    let mut hints: Vec<Message> = Vec::new();
+
+                    let mut calls = Vec::with_capacity(tool_calls_list.len());
                     for tc in tool_calls_list.iter() {
                         tool_calls_made += 1;
 
@@ -378,17 +938,22 @@ pub async fn run_agentic_loop<C: LoopCallback>(
                         // Inject _user_id / _chat_id for memory and task tools
                         let call_args = inject_user_context(args, &user_id, &chat_id, tool_name);
 
-                        let call = ToolCall {
+                        calls.push(ToolCall {
                             id: tc.id.clone(),
                             name: tool_name.clone(),
                             arguments: call_args,
-                        };
+                            parent_message_id: parent_message_id.clone(),
+                        });
+                    }
 
-                        let tool_start = Instant::now();
-                        let result = tools.execute(&call).await;
-                        let duration_ms = tool_start.elapsed().as_millis() as u64;
+                    let results = tools
+                        .execute_batch_timed(&calls, config.max_parallel_tool_calls)
+                        .await;
 
-                        let result_content = match result {
+                    for ((tc, call), (result, elapsed)) in tool_calls_list.iter().zip(&calls).zip(results) {
+                        let duration_ms = elapsed.as_millis() as u64;
+                        let tool_name = &tc.function.name;
+                        let tool_result = match result {
                             Ok(r) => {
                                 let s = r.to_string();
                                 info!(
@@ -401,40 +966,75 @@ pub async fn run_agentic_loop<C: LoopCallback>(
                                     tool_name,
                                     &s[..s.len().min(1000)]
                                 );
-                                s
+                                r
                             }
                             Err(e) => {
-                                let err = format!("Tool error: {}", e);
-                                warn!("Tool {} failed: {}", tool_name, err);
-                                err
+                                callback.on_error(&e, iteration).await;
+                                warn!("Tool {} failed: {}", tool_name, e);
+                                ToolResult::failure(e.to_string())
                             }
                         };
-
-                        // Add tool result to messages
-                        messages.push(Message::tool(&tc.id, &result_content));
+                        let tool_result = match config.tool_result_max_chars {
+                            Some(max_chars) => compress_tool_result(
+                                tool_result,
+                                max_chars,
+                                config.tool_result_compression_strategy,
+                            ),
+                            None => tool_result,
+                        };
+                        let result_content = tool_result.to_string();
+
+                        // Add tool result to messages and note the soul's
+                        // preference for this tool
+                        if let Some(preference) = config.tool_preferences.get(tool_name) {
+                            let line = format!("Tool preference for {}: {}", tool_name, preference);
+                            if !preferences.contains(&line) {
+                                preferences.push(line);
+                            }
+                        }
+                        messages.push(Message::tool_result(call, &tool_result));
+                        estimated_tokens = estimated_tokens
+                            .saturating_add(estimate_tokens(&result_content) as u32);
 
                         // Check for stuck loops
-                        let loop_guard_triggered = if let Some(hint) = loop_guard.record(
+                        let guard_action = loop_guard.record(
                             tool_name,
                             &tc.function.arguments,
                             &result_content,
-                        ) {
-                            warn!(
-                                "Loop guard triggered for tool '{}', injecting hint",
-                                tool_name
-                            );
-                            messages.push(Message::user(&hint));
-                            true
-                        } else {
-                            false
-                        };
+                        );
+                        match &guard_action {
+                            Some(LoopGuardAction::Hint(_)) => {
+                                warn!(
+                                    "Loop guard triggered for tool '{}', injecting hint",
+                                    tool_name
+                                );
+                            }
+                            Some(LoopGuardAction::Backoff { wait, .. }) => {
+                                warn!(
+                                    "Loop guard triggered again for tool '{}', backing off {:?}",
+                                    tool_name, wait
+                                );
+                                pending_backoff = Some(*wait);
+                            }
+                            Some(LoopGuardAction::Abort(reason)) => {
+                                warn!("{}", reason);
+                                stuck = true;
+                            }
+                            None => {}
+                        }
+                        // The abort reason is recorded too, so the history
+                        // shows why the loop stopped
+                        if let Some(action) = &guard_action {
+                            hints.push(Message::user(action.message()));
+                        }
+                        let loop_guard_triggered = guard_action.is_some();
 
                         let observation = ToolObservation {
-                            success: !result_content.starts_with("Tool error:")
-                                && !result_content.starts_with("Error:"),
+                            success: tool_result.success,
                             content: result_content,
                             duration_ms,
                             loop_guard_triggered,
+                            metadata: tool_result.metadata,
                         };
 
                         callback.on_tool_executed(tool_name, &observation).await;
@@ -443,14 +1043,35 @@ pub async fn run_agentic_loop<C: LoopCallback>(
                             tool_name: tool_name.clone(),
                             arguments: tc.function.arguments.clone(),
                             observation,
+                            parent_message_id: parent_message_id.clone(),
                         });
+
+                        if stuck {
+                            break;
+                        }
+                    }
+
+                    if !preferences.is_empty() {
+                        messages.push(Message::system(preferences.join("\n")));
                     }
+                    messages.extend(hints);
 
                     // Optionally inject reflection prompt after tool results
                     if config.enable_reflection_prompt {
                         inject_reflection_prompt(&mut messages);
                     }
 
+                    if let Some(budget) = config.token_budget {
+                        if !budget_exceeded && estimated_tokens > budget {
+                            warn!(
+                                "Token budget exceeded ({} > {}), disabling tools",
+                                estimated_tokens, budget
+                            );
+                            budget_exceeded = true;
+                            inject_budget_prompt(&mut messages);
+                        }
+                    }
+
                     let step = LoopStep {
                         iteration,
                         thought: choice.message.content.clone(),
@@ -461,6 +1082,14 @@ pub async fn run_agentic_loop<C: LoopCallback>(
                     callback.on_iteration_end(&step).await;
                     steps.push(step);
 
+                    if stuck {
+                        if final_response.is_empty() {
+                            final_response = config.fallback_message.clone();
+                        }
+                        outcome = LoopOutcome::StuckLoop;
+                        break;
+                    }
+
                     // Continue loop — LLM will process tool results
                     continue;
                 }
@@ -488,7 +1117,9 @@ pub async fn run_agentic_loop<C: LoopCallback>(
             callback.on_iteration_end(&step).await;
             steps.push(step);
 
-            if !use_tools && tool_calls_made >= config.max_tool_calls {
+            if budget_exceeded {
+                outcome = LoopOutcome::TokenBudgetExceeded;
+            } else if !use_tools && tool_calls_made >= config.max_tool_calls {
                 outcome = LoopOutcome::ToolLimitReached;
             } else {
                 outcome = LoopOutcome::Completed;
@@ -519,10 +1150,21 @@ pub async fn run_agentic_loop<C: LoopCallback>(
         steps,
         outcome: outcome.clone(),
         total_duration_ms,
+        recording,
     };
 
     callback.on_loop_complete(&trace).await;
 
+    let span = Span::current();
+    span.record("iterations", iteration.min(config.max_iterations));
+    span.record("tool_calls", tool_calls_made);
+    span.record("estimated_tokens", estimated_tokens);
+    span.record("prompt_tokens", total_usage.prompt_tokens);
+    span.record("completion_tokens", total_usage.completion_tokens);
+    span.record("total_tokens", total_usage.total_tokens);
+    span.record("outcome", field::debug(&outcome));
+    span.record("duration_ms", total_duration_ms);
+
     info!(
         "Agentic loop finished: outcome={:?}, iterations={}, tool_calls={}, duration={}ms",
         outcome,
@@ -536,6 +1178,7 @@ pub async fn run_agentic_loop<C: LoopCallback>(
         trace,
         final_messages: messages,
         total_usage,
+        planning_usage,
     })
 }
 
@@ -552,7 +1195,123 @@ In what order should you proceed?";
     messages.push(Message::system(planning_prompt));
 }
 
+/// Tell the LLM to answer with a JSON object matching `schema`.
+fn inject_structured_output_instructions(messages: &mut Vec<Message>, schema: &serde_json::Value) {
+    messages.push(Message::system(format!(
+        "Your final response must be a single JSON object matching this JSON schema, \
+with no other text:\n{}",
+        schema
+    )));
+}
+
+/// Parse a final response as `T`, tolerating a Markdown code fence or text
+/// around the JSON object.
+fn parse_structured_output<T: DeserializeOwned>(response: &str) -> serde_json::Result<T> {
+    let text = response.trim();
+    let text = text
+        .strip_prefix("```json")
+        .or_else(|| text.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(str::trim)
+        .unwrap_or(text);
+    serde_json::from_str(text).or_else(|e| match (text.find('{'), text.rfind('}')) {
+        (Some(start), Some(end)) if start < end => serde_json::from_str(&text[start..=end]),
+        _ => Err(e),
+    })
+}
+
+/// Ask `model` for a plan for the latest user message. Returns the plan and
+/// the raw response, or `None` if the call fails or the plan is empty.
+async fn request_plan(
+    llm_client: &OpenRouterClient,
+    model: &str,
+    messages: &[Message],
+    config: &LoopConfig,
+) -> Option<(String, ChatCompletionResponse)> {
+    let query = messages.iter().rev().find(|m| m.role == Role::User)?;
+    let planning_messages = vec![
+        Message::system(
+            "You plan how an assistant with tools should handle the user's request. \
+Reply with a short numbered list of steps: what information or actions are \
+needed and in what order. Do not answer the request itself.",
+        ),
+        Message::user(&query.content),
+    ];
+
+    let span = info_span!(
+        "agent.planning_call",
+        model = %model,
+        total_tokens = field::Empty,
+        duration_ms = field::Empty,
+    );
+    let start = Instant::now();
+    let response = llm_client
+        .chat_with_model(model, planning_messages, config.generation_options.clone())
+        .instrument(span.clone())
+        .await;
+    span.record("duration_ms", start.elapsed().as_millis() as u64);
+
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Planning model {} failed, planning with the primary model: {}", model, e);
+            return None;
+        }
+    };
+    if let Some(ref usage) = response.usage {
+        span.record("total_tokens", usage.total_tokens);
+    }
+    let plan = response.choices.first()?.message.content.trim().to_string();
+    if plan.is_empty() {
+        return None;
+    }
+    info!("Planning model {} produced a {} char plan", model, plan.len());
+    Some((plan, response))
+}
+
+/// Stream one LLM call, passing text to `callback` as it arrives, and join
+/// the chunks into a response
+async fn stream_completion<C: LoopCallback>(
+    llm_client: &OpenRouterClient,
+    messages: Vec<Message>,
+    tools: Option<Vec<ToolDefinition>>,
+    options: GenerationOptions,
+    callback: &C,
+) -> Result<ChatCompletionResponse> {
+    let mut chunks = match tools {
+        Some(tools) => llm_client.stream_chat_with_tools(messages, tools, options).boxed(),
+        None => llm_client.stream_chat(messages, options).boxed(),
+    };
+    let mut accumulator = StreamAccumulator::default();
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        if !chunk.delta.is_empty() {
+            callback.on_content_delta(&chunk.delta).await;
+        }
+        accumulator.push(chunk);
+    }
+    Ok(accumulator.into_response(llm_client.default_model()))
+}
+
 /// Inject a reflection system message after a batch of tool results.
+/// Add events pushed since the last LLM call, one system message each
+fn inject_pushed_events(messages: &mut Vec<Message>, events: &mut [(String, broadcast::Receiver<ToolResult>)]) {
+    for (source, receiver) in events.iter_mut() {
+        loop {
+            match receiver.try_recv() {
+                Ok(event) => {
+                    debug!("Injecting event from {}", source);
+                    messages.push(Message::system(format!("Event from {}:\n{}", source, event.to_string())));
+                }
+                Err(broadcast::error::TryRecvError::Lagged(missed)) => {
+                    warn!("Missed {} events from {}", missed, source);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+}
+
 fn inject_reflection_prompt(messages: &mut Vec<Message>) {
     let reflection_prompt = "\
 Review the results above. Have you gathered enough information to answer the \
@@ -561,6 +1320,14 @@ explain what's still needed and continue.";
     messages.push(Message::system(reflection_prompt));
 }
 
+/// Tell the LLM to stop using tools once the token budget is spent.
+fn inject_budget_prompt(messages: &mut Vec<Message>) {
+    let budget_prompt = "\
+The token budget for this request has been reached and tools are no longer \
+available. Summarize what you have found so far and give your final answer.";
+    messages.push(Message::system(budget_prompt));
+}
+
 /// Inject `_user_id` and `_chat_id` into tool arguments for memory/task tools.
 fn inject_user_context(
     mut args: serde_json::Value,
@@ -581,6 +1348,21 @@ fn inject_user_context(
     args
 }
 
+/// Truncate the content (or error) of an oversized tool result.
+fn compress_tool_result(mut result: ToolResult, max_chars: usize, strategy: TruncationStrategy) -> ToolResult {
+    let text = if result.success { &mut result.content } else { &mut result.error };
+    let Some(original) = text.as_deref() else {
+        return result;
+    };
+    let Some(truncated) = strategy.truncate(original, max_chars) else {
+        return result;
+    };
+    let original_chars = original.chars().count();
+    debug!("Truncated tool result from {} to {} chars", original_chars, max_chars);
+    *text = Some(truncated);
+    result.with_metadata("original_chars", original_chars.into())
+}
+
 /// Sum token usage from one response into an accumulator.
 fn accumulate_usage(total: &mut Usage, delta: &Usage) {
     total.prompt_tokens += delta.prompt_tokens;
@@ -609,6 +1391,38 @@ mod tests {
         let sched = LoopConfig::scheduler();
         assert_eq!(sched.max_iterations, 20);
         assert_eq!(sched.max_tool_calls, 20);
+        assert_eq!(sched.stuck_loop_backoff, default_stuck_loop_backoff());
+    }
+
+    #[test]
+    fn test_tool_result_truncation_strategies() {
+        let text = "abcdefghij";
+        assert_eq!(TruncationStrategy::Head.truncate(text, 10), None);
+        assert_eq!(
+            TruncationStrategy::Head.truncate(text, 4).unwrap(),
+            "abcd\n...[truncated to 4 chars; total 10 chars]"
+        );
+        assert_eq!(
+            TruncationStrategy::Tail.truncate(text, 4).unwrap(),
+            "[truncated to 4 chars; total 10 chars]...\nghij"
+        );
+        assert_eq!(
+            TruncationStrategy::Middle.truncate(text, 5).unwrap(),
+            "abc\n...[truncated to 5 chars; total 10 chars]...\nij"
+        );
+        // Cuts on character boundaries
+        assert!(TruncationStrategy::Head.truncate("ééé", 1).unwrap().starts_with("é\n"));
+
+        let config = LoopConfig::tui().with_tool_result_compression(4);
+        let result = compress_tool_result(
+            ToolResult::success(text),
+            config.tool_result_max_chars.unwrap(),
+            config.tool_result_compression_strategy,
+        );
+        assert!(result.content.unwrap().starts_with("abcd\n..."));
+        assert_eq!(result.metadata["original_chars"], 10);
+        let failure = compress_tool_result(ToolResult::failure(text), 4, TruncationStrategy::Tail);
+        assert!(failure.error.unwrap().ends_with("ghij"));
     }
 
     #[test]
@@ -654,4 +1468,514 @@ mod tests {
         assert_eq!(total.completion_tokens, 15);
         assert_eq!(total.total_tokens, 45);
     }
+
+    struct LargeOutputTool;
+
+    #[async_trait]
+    impl crate::tools::Tool for LargeOutputTool {
+        fn name(&self) -> &str {
+            "large_output"
+        }
+
+        fn description(&self) -> &str {
+            "Returns a large result"
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({"type": "object", "properties": {}})
+        }
+
+        async fn execute(&self, _args: serde_json::Value) -> Result<crate::tools::ToolResult> {
+            Ok(crate::tools::ToolResult::success("x".repeat(4000)))
+        }
+    }
+
+    /// Client for `base_url` without retries
+    fn client_at(base_url: &str) -> OpenRouterClient {
+        OpenRouterClient::new(crate::config::OpenRouterConfig {
+            api_key: secrecy::SecretString::from("test-key"),
+            default_model: "test/model".to_string(),
+            site_url: None,
+            site_name: None,
+            base_url: base_url.to_string(),
+            timeout_secs: 5,
+            max_retries: 0,
+        })
+        .unwrap()
+    }
+
+    /// Client that talks to a wiremock server
+    fn test_client(server: &wiremock::MockServer) -> OpenRouterClient {
+        client_at(&server.uri())
+    }
+
+    fn completion(message: serde_json::Value, finish_reason: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": "gen-1",
+            "object": "chat.completion",
+            "created": 0,
+            "model": "test/model",
+            "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_token_budget_disables_tools() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // Keeps asking for the tool whenever tools are offered
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(wiremock::matchers::body_string_contains("\"tools\""))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "large_output", "arguments": "{}"}
+                    }]
+                }),
+                "tool_calls",
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "Summary"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let mut tools = ToolRegistry::new();
+        tools.register(LargeOutputTool);
+
+        let config = LoopConfig {
+            token_budget: Some(2500),
+            ..LoopConfig::tui()
+        };
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![Message::user("go")],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config,
+            user_id: None,
+            chat_id: None,
+            callback: NoOpCallback::new(),
+        })
+        .await
+        .unwrap();
+
+        // 1000 estimated tokens per call: the third result crosses the budget
+        assert_eq!(output.trace.outcome, LoopOutcome::TokenBudgetExceeded);
+        assert_eq!(output.response, "Summary");
+        let tool_results = output
+            .final_messages
+            .iter()
+            .filter(|m| m.role == Role::Tool)
+            .count();
+        assert_eq!(tool_results, 3);
+        assert!(output
+            .final_messages
+            .last()
+            .is_some_and(|m| m.role == Role::System && m.content.contains("token budget")));
+    }
+
+    #[tokio::test]
+    async fn test_tool_preference_follows_results() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        // Mounted first so it wins once the preference is in the context
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("Tool preference for large_output: skim it"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "Done"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "large_output", "arguments": "{}"}
+                    }]
+                }),
+                "tool_calls",
+            )))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let mut tools = ToolRegistry::new();
+        tools.register(LargeOutputTool);
+
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![Message::user("go")],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config: LoopConfig::tui().with_tool_preferences([("large_output", "skim it"), ("other", "x")]),
+            user_id: None,
+            chat_id: None,
+            callback: NoOpCallback::new(),
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(output.response, "Done");
+        // The tool reply directly follows the assistant's tool call, and the
+        // preference comes after it
+        let tool_idx = output.final_messages.iter().position(|m| m.role == Role::Tool).unwrap();
+        assert!(output.final_messages[tool_idx - 1].tool_calls.is_some());
+        let hint = &output.final_messages[tool_idx + 1];
+        assert_eq!(hint.role, Role::System);
+        assert_eq!(hint.content, "Tool preference for large_output: skim it");
+    }
+
+    #[tokio::test]
+    async fn test_replay_reproduces_trace() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({
+                    "role": "assistant",
+                    "content": "Let me look",
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "large_output", "arguments": "{\"n\":1}"}
+                    }]
+                }),
+                "tool_calls",
+            )))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "Done"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let mut tools = ToolRegistry::new();
+        tools.register(LargeOutputTool);
+
+        let mut user_message = Message::user("go");
+        user_message.checkpoint_id = uuid::Uuid::new_v4();
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![user_message.clone()],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config: LoopConfig::tui(),
+            user_id: Some("user-1".into()),
+            chat_id: None,
+            callback: NoOpCallback::new(),
+        })
+        .await
+        .unwrap();
+        assert_eq!(output.trace.recording.responses.len(), 2);
+        assert_eq!(
+            output.trace.steps[0].actions[0].parent_message_id,
+            Some(user_message.checkpoint_id.to_string())
+        );
+
+        // Round-trip through JSON as the trace store does, then replay
+        // without the server
+        drop(server);
+        let stored: LoopTrace =
+            serde_json::from_value(serde_json::to_value(&output.trace).unwrap()).unwrap();
+        let replayed = stored.replay(&llm_client, &tools).await.unwrap();
+
+        assert_eq!(replayed.response, "Done");
+        output.trace.assert_same_outcome(&replayed.trace);
+    }
+
+    #[tokio::test]
+    async fn test_planning_model() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let mut plan = completion(
+            serde_json::json!({"role": "assistant", "content": "1. Read the file"}),
+            "stop",
+        );
+        plan["usage"] = serde_json::json!({"prompt_tokens": 7, "completion_tokens": 3, "total_tokens": 10});
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("test/planner"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(plan))
+            .expect(1)
+            .mount(&server)
+            .await;
+        // The primary model must see the plan
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("1. Read the file"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "Done"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let tools = ToolRegistry::new();
+
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![Message::user("summarize notes.txt")],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config: LoopConfig::tui().with_planning_model("test/planner"),
+            user_id: None,
+            chat_id: None,
+            callback: NoOpCallback::new(),
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(output.response, "Done");
+        assert_eq!(output.planning_usage.map(|u| u.total_tokens), Some(10));
+        assert_eq!(output.total_usage.total_tokens, 0);
+        assert!(output
+            .final_messages
+            .iter()
+            .any(|m| m.role == Role::System && m.content.contains("1. Read the file")));
+
+        drop(server);
+        let replayed = output.trace.replay(&llm_client, &tools).await.unwrap();
+        assert_eq!(replayed.response, "Done");
+        output.trace.assert_same_outcome(&replayed.trace);
+    }
+
+    #[test]
+    #[should_panic(expected = "tool calls differ")]
+    fn test_assert_same_outcome_detects_divergence() {
+        let step = |arguments: &str| LoopStep {
+            iteration: 1,
+            thought: String::new(),
+            actions: vec![ToolAction {
+                tool_name: "read_file".into(),
+                arguments: arguments.into(),
+                observation: ToolObservation {
+                    success: true,
+                    content: String::new(),
+                    duration_ms: 0,
+                    loop_guard_triggered: false,
+                    metadata: HashMap::new(),
+                },
+                parent_message_id: None,
+            }],
+            finish_reason: "tool_calls".into(),
+            timestamp: Instant::now(),
+        };
+        let trace = |arguments: &str| LoopTrace {
+            steps: vec![step(arguments)],
+            outcome: LoopOutcome::MaxIterationsExceeded,
+            total_duration_ms: 0,
+            recording: LoopRecording {
+                messages: vec![],
+                config: LoopConfig::tui(),
+                user_id: None,
+                chat_id: None,
+                responses: vec![],
+                planning_response: None,
+            },
+        };
+
+        trace("{\"path\":\"a\"}").assert_same_outcome(&trace("{\"path\":\"a\"}"));
+        trace("{\"path\":\"a\"}").assert_same_outcome(&trace("{\"path\":\"b\"}"));
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, JsonSchema)]
+    struct City {
+        name: String,
+        population: u64,
+    }
+
+    #[test]
+    fn test_parse_structured_output() {
+        let city = City { name: "Oslo".into(), population: 700_000 };
+        let parse = |s: &str| parse_structured_output::<City>(s).ok();
+        assert_eq!(parse(r#"{"name":"Oslo","population":700000}"#).as_ref(), Some(&city));
+        assert_eq!(parse("```json\n{\"name\":\"Oslo\",\"population\":700000}\n```").as_ref(), Some(&city));
+        assert_eq!(parse("Here you go: {\"name\":\"Oslo\",\"population\":700000}").as_ref(), Some(&city));
+        assert!(parse("{\"name\":\"Oslo\"}").is_none());
+        assert!(parse("Oslo has 700k people").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_typed_loop_retries_with_parse_error() {
+        use wiremock::matchers::{body_string_contains, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("could not be parsed"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "{\"name\":\"Oslo\",\"population\":700000}"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("population"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "Oslo, about 700k people"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let tools = ToolRegistry::new();
+        let input = || AgentLoopInput {
+            messages: vec![Message::user("Largest city in Norway?")],
+            llm_client: &llm_client,
+            tool_definitions: vec![],
+            tools: &tools,
+            config: LoopConfig::tui(),
+            user_id: None,
+            chat_id: None,
+            callback: NoOpCallback::new(),
+        };
+
+        let (city, trace) = run_agentic_loop_typed::<City, _>(input()).await.unwrap();
+        assert_eq!(city, City { name: "Oslo".into(), population: 700_000 });
+        assert_eq!(trace.outcome, LoopOutcome::Completed);
+        // The retry carries the failed answer and the schema instruction only once
+        let retry = &trace.recording.messages;
+        assert_eq!(retry.iter().filter(|m| m.content.contains("JSON schema")).count(), 1);
+        assert!(retry.iter().any(|m| m.role == Role::Assistant && m.content.starts_with("Oslo,")));
+        assert!(trace.recording.config.structured_output.is_none());
+
+        // A response that never parses gives up after the retries
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion(
+                serde_json::json!({"role": "assistant", "content": "no idea"}),
+                "stop",
+            )))
+            .mount(&server)
+            .await;
+        let err = run_agentic_loop_typed::<City, _>(input()).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(_)));
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len() as u32, STRUCTURED_OUTPUT_RETRIES + 1);
+    }
+
+    /// Records `(iteration, category)` of every reported error
+    #[derive(Default)]
+    struct ErrorRecorder(std::sync::Mutex<Vec<(u32, &'static str)>>);
+
+    #[async_trait]
+    impl LoopCallback for ErrorRecorder {
+        async fn on_error(&self, err: &Error, iteration: u32) {
+            self.0.lock().unwrap().push((iteration, err.category()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_error_reported_to_callback() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("bad request"))
+            .mount(&server)
+            .await;
+
+        let llm_client = test_client(&server);
+        let tools = ToolRegistry::new();
+        let recorder = ErrorRecorder::default();
+
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![Message::user("go")],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config: LoopConfig::tui(),
+            user_id: None,
+            chat_id: None,
+            // Both combinators forward errors; the disabled half ignores them
+            callback: (&recorder, None::<NoOpCallback>),
+        })
+        .await
+        .unwrap();
+
+        assert!(matches!(output.trace.outcome, LoopOutcome::LlmError(_)));
+        let reported = recorder.0.lock().unwrap().clone();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].0, 1);
+    }
+
+    /// Collects streamed response text
+    #[derive(Default)]
+    struct DeltaRecorder(std::sync::Mutex<String>);
+
+    #[async_trait]
+    impl LoopCallback for DeltaRecorder {
+        async fn on_content_delta(&self, delta: &str) {
+            self.0.lock().unwrap().push_str(delta);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_reports_content_deltas() {
+        let response: ChatCompletionResponse = serde_json::from_value(completion(
+            serde_json::json!({"role": "assistant", "content": "Streamed answer"}),
+            "stop",
+        ))
+        .unwrap();
+        let llm_client = client_at("http://127.0.0.1:9").replaying(vec![response]);
+        let tools = ToolRegistry::new();
+        let recorder = DeltaRecorder::default();
+
+        let output = run_agentic_loop(AgentLoopInput {
+            messages: vec![Message::user("go")],
+            llm_client: &llm_client,
+            tool_definitions: tools.definitions(),
+            tools: &tools,
+            config: LoopConfig::tui().with_streaming(),
+            user_id: None,
+            chat_id: None,
+            callback: &recorder,
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(output.response, "Streamed answer");
+        assert_eq!(*recorder.0.lock().unwrap(), "Streamed answer");
+    }
 }
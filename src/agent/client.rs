@@ -1,12 +1,19 @@
 //! OpenRouter API client
 
-use crate::config::OpenRouterConfig;
+use crate::config::{OpenRouterConfig, ProviderConfig};
+use crate::core::LlmProvider;
 use crate::error::{Error, Result};
+use crate::providers::{FailoverLlmProvider, OpenRouterProvider};
+use crate::agent::streaming::{response_chunks, SseDecoder};
 use crate::agent::types::*;
+use crate::core::StreamingChunk;
+use futures::stream::{self, Stream, StreamExt};
 use reqwest::{Client, header};
 use secrecy::ExposeSecret;
+use std::collections::VecDeque;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info, warn};
 
 /// OpenRouter API client
@@ -18,8 +25,18 @@ pub struct OpenRouterClient {
     config: OpenRouterConfig,
     /// Rate limit state
     rate_limit: Arc<RwLock<RateLimitState>>,
+    /// Cached `/models` response (shared across clones)
+    model_cache: Arc<RwLock<ModelCache>>,
+    /// Recorded responses served instead of calling the API (see `replaying`)
+    replay: Option<Arc<Mutex<VecDeque<ChatCompletionResponse>>>>,
+    /// Fallback model chain for requests to the default model (see
+    /// `from_provider_config`)
+    failover: Option<Arc<FailoverLlmProvider>>,
 }
 
+/// Model list and when it was fetched
+type ModelCache = Option<(Instant, Vec<ModelInfo>)>;
+
 /// Rate limit tracking
 #[derive(Debug, Default)]
 struct RateLimitState {
@@ -65,14 +82,70 @@ impl OpenRouterClient {
             client,
             config,
             rate_limit: Arc::new(RwLock::new(RateLimitState::default())),
+            model_cache: Arc::new(RwLock::new(None)),
+            replay: None,
+            failover: None,
         })
     }
 
+    /// Create a client from the provider config
+    ///
+    /// With `failover.enabled`, requests to the default model that fail
+    /// with a 5xx or connection error are retried on each of
+    /// `failover.fallback_models` in turn, through a [`FailoverLlmProvider`].
+    pub fn from_provider_config(config: &ProviderConfig) -> Result<Self> {
+        let openrouter = config.openrouter.clone().ok_or_else(|| {
+            Error::Config("OpenRouter not configured. Set OPENROUTER_API_KEY environment variable.".into())
+        })?;
+        let mut client = Self::new(openrouter)?;
+
+        if config.failover.enabled && !config.failover.fallback_models.is_empty() {
+            let fallbacks = config
+                .failover
+                .fallback_models
+                .iter()
+                .map(|model| Arc::new(OpenRouterProvider::new(client.with_model(model))) as Arc<dyn LlmProvider>)
+                .collect();
+            let chain = FailoverLlmProvider::new(
+                Arc::new(OpenRouterProvider::new(client.clone())),
+                fallbacks,
+                config.failover.clone(),
+            );
+            client.failover = Some(Arc::new(chain));
+        }
+        Ok(client)
+    }
+
+    /// Clone of this client with a different default model and no failover
+    pub fn with_model(&self, model: &str) -> Self {
+        let mut config = self.config.clone();
+        config.default_model = model.to_string();
+        OpenRouterClient {
+            config,
+            failover: None,
+            ..self.clone()
+        }
+    }
+
+    /// Clone of this client that answers chat requests with `responses`, in
+    /// order, instead of calling the API. Once they run out, requests fail.
+    pub fn replaying(&self, responses: Vec<ChatCompletionResponse>) -> Self {
+        OpenRouterClient {
+            replay: Some(Arc::new(Mutex::new(responses.into()))),
+            ..self.clone()
+        }
+    }
+
     /// Get the default model
     pub fn default_model(&self) -> &str {
         &self.config.default_model
     }
 
+    /// API base URL
+    pub fn base_url(&self) -> &str {
+        &self.config.base_url
+    }
+
     /// Create a chat completion
     pub async fn chat(
         &self,
@@ -127,19 +200,248 @@ impl OpenRouterClient {
         self.send_request(request).await
     }
 
+    /// Create a chat completion whose reply starts with `prefix`
+    ///
+    /// Anthropic models support prefill: `prefix` is sent as a trailing
+    /// assistant message and the model continues from it. Other providers
+    /// don't, so the prefix is left out of the request. Either way the
+    /// first choice's content is returned with `prefix` in front (unless a
+    /// non-Anthropic model already started with it).
+    pub async fn chat_with_prefix_fill(
+        &self,
+        mut messages: Vec<Message>,
+        prefix: &str,
+    ) -> Result<ChatCompletionResponse> {
+        let model = self.config.default_model.clone();
+        let prefill = supports_prefill(&model);
+        if prefill {
+            messages.push(Message::assistant(prefix));
+        }
+
+        let mut response = self
+            .chat_with_model(&model, messages, GenerationOptions::default())
+            .await?;
+        if let Some(choice) = response.choices.first_mut() {
+            let content = &mut choice.message.content;
+            if prefill || !content.starts_with(prefix) {
+                content.insert_str(0, prefix);
+            }
+        }
+        Ok(response)
+    }
+
+    /// Create a chat completion with images attached to the last user message
+    ///
+    /// Images are sent as `image_url` content parts after the message text,
+    /// so the default model must accept image input.
+    pub async fn chat_with_vision(
+        &self,
+        messages: Vec<Message>,
+        images: Vec<ImageInput>,
+    ) -> Result<ChatCompletionResponse> {
+        let request = ChatCompletionRequest {
+            model: self.config.default_model.clone(),
+            messages,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            stop: None,
+            stream: Some(false),
+            tools: None,
+            tool_choice: None,
+        };
+
+        let body = attach_images(serde_json::to_value(&request)?, &images)?;
+        self.send_body(&request.model, &body).await
+    }
+
+    /// Stream a chat completion as it is generated
+    ///
+    /// Sends the request with `"stream": true` and yields a chunk per
+    /// server-sent event, ending after the chunk with a `finish_reason`.
+    /// Connection failures, 429s and 5xx are retried like [`chat`](Self::chat)
+    /// until the response starts; an error mid-stream ends it with `Err`.
+    pub fn stream_chat(
+        &self,
+        messages: Vec<Message>,
+        options: GenerationOptions,
+    ) -> impl Stream<Item = Result<StreamingChunk>> + Send + 'static {
+        self.stream_request(ChatCompletionRequest {
+            model: self.config.default_model.clone(),
+            messages,
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop: options.stop,
+            stream: Some(true),
+            tools: None,
+            tool_choice: None,
+        })
+    }
+
+    /// Stream a chat completion with tools/functions
+    ///
+    /// Tool calls arrive as `StreamingChunk::tool_calls` fragments, joined
+    /// by their `index`.
+    pub fn stream_chat_with_tools(
+        &self,
+        messages: Vec<Message>,
+        tools: Vec<ToolDefinition>,
+        options: GenerationOptions,
+    ) -> impl Stream<Item = Result<StreamingChunk>> + Send + 'static {
+        self.stream_request(ChatCompletionRequest {
+            model: self.config.default_model.clone(),
+            messages,
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            top_p: options.top_p,
+            stop: options.stop,
+            stream: Some(true),
+            tools: Some(tools),
+            tool_choice: Some(ToolChoice::Auto("auto".to_string())),
+        })
+    }
+
+    /// Open the stream lazily, on first poll
+    fn stream_request(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> impl Stream<Item = Result<StreamingChunk>> + Send + 'static {
+        let client = self.clone();
+        stream::once(async move {
+            if let Some(ref replay) = client.replay {
+                debug!("Replaying recorded response as a stream: model={}", request.model);
+                return match replay.lock().await.pop_front() {
+                    Some(response) => stream::iter(response_chunks(response).into_iter().map(Ok)).boxed(),
+                    None => stream::once(async {
+                        Err(Error::OpenRouter("No recorded response left to replay".to_string()))
+                    })
+                    .boxed(),
+                };
+            }
+            let opened = match client.failover {
+                Some(ref chain) if request.model == client.config.default_model => {
+                    chain
+                        .with_failover(|provider| {
+                            let request = ChatCompletionRequest {
+                                model: provider.default_model().to_string(),
+                                ..request.clone()
+                            };
+                            let client = &client;
+                            async move { client.open_stream(&request).await }
+                        })
+                        .await
+                }
+                _ => client.open_stream(&request).await,
+            };
+            match opened {
+                Ok(response) => sse_chunks(response).boxed(),
+                Err(e) => stream::once(async move { Err(e) }).boxed(),
+            }
+        })
+        .flatten()
+    }
+
+    /// POST a streaming request, retrying transient errors until the body starts
+    async fn open_stream(&self, request: &ChatCompletionRequest) -> Result<reqwest::Response> {
+        let url = format!("{}/chat/completions", self.config.base_url);
+        let max_retries = self.config.max_retries;
+
+        for attempt in 0..=max_retries {
+            debug!("Opening stream to OpenRouter: model={} (attempt {}/{})",
+                request.model, attempt + 1, max_retries + 1);
+
+            let response = match self.client.post(&url).json(request).send().await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    if attempt < max_retries && (e.is_timeout() || e.is_connect()) {
+                        let wait = Duration::from_secs(2u64.pow(attempt));
+                        warn!("Stream request failed (attempt {}/{}): {}. Retrying in {:?}...",
+                            attempt + 1, max_retries + 1, e, wait);
+                        tokio::time::sleep(wait).await;
+                        continue;
+                    }
+                    return Err(Error::OpenRouter(format!("HTTP error: {}", e)));
+                }
+            };
+
+            self.update_rate_limit(&response).await;
+
+            let status = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+            let error_text = response.text().await.unwrap_or_default();
+            let retryable = status.as_u16() == 429 || status.is_server_error();
+            if retryable && attempt < max_retries {
+                let wait = Duration::from_secs(2u64.pow(attempt));
+                warn!("Stream request got {} (attempt {}/{}): {}. Retrying in {:?}...",
+                    status, attempt + 1, max_retries + 1, error_text, wait);
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+            return Err(match status.as_u16() {
+                429 => Error::RateLimit(error_text),
+                401 => Error::Unauthorized("Invalid API key".to_string()),
+                _ => Error::OpenRouter(format!("API error ({}): {}", status, error_text)),
+            });
+        }
+
+        Err(Error::OpenRouter("Max retries exceeded".to_string()))
+    }
+
     /// Send a request to the OpenRouter API (with retries for transient errors)
     async fn send_request(&self, request: ChatCompletionRequest) -> Result<ChatCompletionResponse> {
+        self.send_body(&request.model, &request).await
+    }
+
+    /// POST a chat completion body, falling back to other models if the
+    /// client has a failover chain
+    async fn send_body<T: serde::Serialize + ?Sized>(
+        &self,
+        model: &str,
+        body: &T,
+    ) -> Result<ChatCompletionResponse> {
+        if let Some(ref replay) = self.replay {
+            debug!("Replaying recorded response: model={}", model);
+            return replay.lock().await.pop_front().ok_or_else(|| {
+                Error::OpenRouter("No recorded response left to replay".to_string())
+            });
+        }
+
+        match self.failover {
+            Some(ref chain) if model == self.config.default_model => {
+                let body = serde_json::to_value(body)?;
+                chain
+                    .with_failover(|provider| {
+                        let model = provider.default_model();
+                        let mut body = body.clone();
+                        body["model"] = model.into();
+                        async move { self.post_body(model, &body).await }
+                    })
+                    .await
+            }
+            _ => self.post_body(model, body).await,
+        }
+    }
+
+    /// POST a chat completion body, retrying transient errors
+    async fn post_body<T: serde::Serialize + ?Sized>(
+        &self,
+        model: &str,
+        body: &T,
+    ) -> Result<ChatCompletionResponse> {
         let url = format!("{}/chat/completions", self.config.base_url);
         let max_retries = self.config.max_retries;
 
         for attempt in 0..=max_retries {
             debug!("Sending request to OpenRouter: model={} (attempt {}/{})",
-                request.model, attempt + 1, max_retries + 1);
+                model, attempt + 1, max_retries + 1);
 
             let result = self
                 .client
                 .post(&url)
-                .json(&request)
+                .json(body)
                 .send()
                 .await;
 
@@ -278,6 +580,19 @@ impl OpenRouterClient {
             Err(Error::OpenRouter(format!("Failed to list models: {}", error)))
         }
     }
+
+    /// List available models, reusing the last result if it is younger than `ttl`
+    pub async fn list_models_cached(&self, ttl: Duration) -> Result<Vec<ModelInfo>> {
+        if let Some((fetched_at, models)) = self.model_cache.read().await.as_ref() {
+            if fetched_at.elapsed() < ttl {
+                return Ok(models.clone());
+            }
+        }
+
+        let models = self.list_models().await?;
+        *self.model_cache.write().await = Some((Instant::now(), models.clone()));
+        Ok(models)
+    }
 }
 
 /// Response from /models endpoint
@@ -288,6 +603,7 @@ struct ModelsResponse {
 
 /// Information about an available model
 #[derive(Debug, Clone, serde::Deserialize)]
+#[serde(from = "RawModelInfo")]
 pub struct ModelInfo {
     /// Model ID (e.g., "anthropic/claude-3.5-sonnet")
     pub id: String,
@@ -300,6 +616,45 @@ pub struct ModelInfo {
     pub context_length: u32,
     /// Pricing info
     pub pricing: ModelPricing,
+    /// Accepts `tools` in requests
+    pub supports_tools: bool,
+    /// Accepts image input
+    pub supports_vision: bool,
+}
+
+/// `/models` entry as returned by OpenRouter
+#[derive(serde::Deserialize)]
+struct RawModelInfo {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    context_length: u32,
+    pricing: ModelPricing,
+    #[serde(default)]
+    supported_parameters: Vec<String>,
+    #[serde(default)]
+    architecture: ModelArchitecture,
+}
+
+#[derive(Default, serde::Deserialize)]
+struct ModelArchitecture {
+    #[serde(default)]
+    input_modalities: Vec<String>,
+}
+
+impl From<RawModelInfo> for ModelInfo {
+    fn from(raw: RawModelInfo) -> Self {
+        ModelInfo {
+            supports_tools: raw.supported_parameters.iter().any(|p| p == "tools"),
+            supports_vision: raw.architecture.input_modalities.iter().any(|m| m == "image"),
+            id: raw.id,
+            name: raw.name,
+            description: raw.description,
+            context_length: raw.context_length,
+            pricing: raw.pricing,
+        }
+    }
 }
 
 /// Model pricing information
@@ -311,6 +666,65 @@ pub struct ModelPricing {
     pub completion: String,
 }
 
+/// Replace the content of the last user message in a serialized request
+/// with a content array: the text part (if any) followed by one `image_url`
+/// part per image, per the OpenRouter vision format
+fn attach_images(mut body: serde_json::Value, images: &[ImageInput]) -> Result<serde_json::Value> {
+    let mut parts = Vec::with_capacity(images.len() + 1);
+    let message = body["messages"]
+        .as_array_mut()
+        .and_then(|messages| messages.iter_mut().rev().find(|m| m["role"] == "user"))
+        .ok_or_else(|| Error::InvalidInput("Vision request needs a user message".to_string()))?;
+
+    if let Some(text) = message["content"].as_str().filter(|t| !t.is_empty()) {
+        parts.push(serde_json::json!({ "type": "text", "text": text }));
+    }
+    for image in images {
+        let url = image.to_data_url().ok_or_else(|| {
+            Error::InvalidInput("Image needs a url or base64 data".to_string())
+        })?;
+        parts.push(serde_json::json!({ "type": "image_url", "image_url": { "url": url } }));
+    }
+
+    message["content"] = serde_json::Value::Array(parts);
+    Ok(body)
+}
+
+/// Decode the server-sent events of a streaming response
+fn sse_chunks(response: reqwest::Response) -> impl Stream<Item = Result<StreamingChunk>> + Send + 'static {
+    let state = (response.bytes_stream().boxed(), SseDecoder::default(), VecDeque::new());
+    stream::unfold(state, |(mut body, mut decoder, mut ready)| async move {
+        loop {
+            if let Some(chunk) = ready.pop_front() {
+                return Some((chunk, (body, decoder, ready)));
+            }
+            if decoder.is_done() {
+                return None;
+            }
+            match body.next().await {
+                Some(Ok(bytes)) => ready.extend(decoder.push(&bytes)),
+                Some(Err(e)) => {
+                    // Nothing after a broken body can be trusted
+                    let chunk = Err(Error::OpenRouter(format!("Stream interrupted: {}", e)));
+                    return Some((chunk, (stream::empty().boxed(), SseDecoder::default(), ready)));
+                }
+                None => {
+                    ready.extend(decoder.finish());
+                    if ready.is_empty() {
+                        return None;
+                    }
+                    body = stream::empty().boxed();
+                }
+            }
+        }
+    })
+}
+
+/// Whether `model` continues a trailing assistant message (prefill)
+fn supports_prefill(model: &str) -> bool {
+    model.starts_with("anthropic/")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -328,6 +742,16 @@ mod tests {
         }
     }
 
+    /// Client that talks to a wiremock server, without retries
+    fn test_client(server: &wiremock::MockServer) -> OpenRouterClient {
+        OpenRouterClient::new(OpenRouterConfig {
+            base_url: server.uri(),
+            max_retries: 0,
+            ..test_config()
+        })
+        .unwrap()
+    }
+
     #[test]
     fn test_client_creation() {
         let config = test_config();
@@ -343,4 +767,244 @@ mod tests {
         let creative = GenerationOptions::creative();
         assert_eq!(creative.temperature, Some(0.8));
     }
+
+    #[test]
+    fn test_attach_images() {
+        let request = serde_json::json!({
+            "model": "openai/gpt-4o",
+            "messages": [
+                { "role": "system", "content": "Be brief" },
+                { "role": "user", "content": "What is this?" },
+            ],
+        });
+        let images = vec![
+            ImageInput::from_url("https://example.com/cat.png", "image/png"),
+            ImageInput::from_bytes(b"jpeg", "image/jpeg"),
+        ];
+
+        let body = attach_images(request, &images).unwrap();
+        assert_eq!(body["messages"][0]["content"], "Be brief");
+        assert_eq!(
+            body["messages"][1]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "What is this?" },
+                { "type": "image_url", "image_url": { "url": "https://example.com/cat.png" } },
+                { "type": "image_url", "image_url": { "url": "data:image/jpeg;base64,anBlZw==" } },
+            ])
+        );
+
+        let empty = ImageInput { url: None, base64: None, media_type: "image/png".to_string() };
+        let request = serde_json::json!({ "messages": [{ "role": "user", "content": "" }] });
+        assert!(attach_images(request, &[empty]).is_err());
+        let request = serde_json::json!({ "messages": [{ "role": "system", "content": "x" }] });
+        assert!(attach_images(request, &images).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_prefix_fill() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+        let reply = |content: &str| {
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "gen-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "test/model",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": "stop"
+                }]
+            }))
+        };
+        let ends_with_prefill = |request: &Request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            body["messages"].as_array().and_then(|m| m.last()).map(|m| m["role"] == "assistant")
+                == Some(true)
+        };
+        let messages = vec![Message::user("Is the sky blue?")];
+
+        // Anthropic continues from the prefill
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(ends_with_prefill)
+            .respond_with(reply(" yes"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = test_client(&server);
+        let response = client.chat_with_prefix_fill(messages.clone(), "Verdict:").await.unwrap();
+        assert_eq!(response.choices[0].message.content, "Verdict: yes");
+
+        // Other models get no prefill; the prefix is added to the reply
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(move |request: &Request| !ends_with_prefill(request))
+            .respond_with(reply(" yes"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = OpenRouterClient::new(OpenRouterConfig {
+            base_url: server.uri(),
+            default_model: "openai/gpt-4o".to_string(),
+            max_retries: 0,
+            ..test_config()
+        })
+        .unwrap();
+        let response = client.chat_with_prefix_fill(messages, "Verdict:").await.unwrap();
+        assert!(response.choices[0].message.content.starts_with("Verdict:"));
+        assert_eq!(response.choices[0].message.content, "Verdict: yes");
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_fallback_model() {
+        use crate::config::FailoverConfig;
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "model": "anthropic/claude-3.5-sonnet" })))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "model": "openai/gpt-4o" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "gen-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "openai/gpt-4o",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "from fallback" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OpenRouterClient::from_provider_config(&ProviderConfig {
+            openrouter: Some(OpenRouterConfig {
+                base_url: server.uri(),
+                max_retries: 0,
+                ..test_config()
+            }),
+            failover: FailoverConfig {
+                enabled: true,
+                fallback_models: vec!["openai/gpt-4o".to_string()],
+                max_failures: 3,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        let response = client
+            .chat(vec![Message::user("Hi")], GenerationOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(response.model, "openai/gpt-4o");
+        assert_eq!(response.choices[0].message.content, "from fallback");
+    }
+
+    #[tokio::test]
+    async fn test_list_models_cached() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "id": "openai/gpt-4o",
+                    "name": "GPT-4o",
+                    "context_length": 128000,
+                    "pricing": {"prompt": "0.0000025", "completion": "0.00001"},
+                    "supported_parameters": ["tools", "tool_choice", "temperature"],
+                    "architecture": {"input_modalities": ["text", "image"], "output_modalities": ["text"]}
+                }, {
+                    "id": "meta-llama/llama-3-8b",
+                    "name": "Llama 3 8B",
+                    "context_length": 8192,
+                    "pricing": {"prompt": "0", "completion": "0"}
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = test_client(&server);
+
+        let models = client.list_models_cached(Duration::from_secs(60)).await.unwrap();
+        assert!(models[0].supports_tools && models[0].supports_vision);
+        assert!(!models[1].supports_tools && !models[1].supports_vision);
+
+        // Served from cache; the mock verifies only one request on drop
+        let again = client.list_models_cached(Duration::from_secs(60)).await.unwrap();
+        assert_eq!(again.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let event = |content: &str, finish: Option<&str>| {
+            let chunk = serde_json::json!({
+                "id": "gen-1",
+                "object": "chat.completion.chunk",
+                "created": 0,
+                "model": "test/model",
+                "choices": [{ "index": 0, "delta": { "content": content }, "finish_reason": finish }]
+            });
+            format!("data: {}\n\n", chunk)
+        };
+        let body = format!(
+            ": OPENROUTER PROCESSING\n\n{}{}{}data: [DONE]\n\n",
+            event("Hel", None),
+            event("lo", None),
+            event("", Some("stop"))
+        );
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "stream": true })))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = test_client(&server);
+
+        let chunks: Vec<StreamingChunk> = client
+            .stream_chat(vec![Message::user("Hi")], GenerationOptions::default())
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let text: String = chunks.iter().map(|c| c.delta.as_str()).collect();
+        assert_eq!(text, "Hello");
+        assert!(chunks.last().unwrap().is_final);
+
+        // Errors before the stream starts end it with one `Err`
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        let client = test_client(&server);
+        let results: Vec<Result<StreamingChunk>> = client
+            .stream_chat(vec![Message::user("Hi")], GenerationOptions::default())
+            .collect()
+            .await;
+        assert!(matches!(results.as_slice(), [Err(Error::Unauthorized(_))]));
+    }
 }
@@ -1,15 +1,80 @@
 //! Conversation management
 
+use crate::agent::model_metadata::{CharTokenEstimator, TokenEstimator};
 use crate::agent::types::{Message, Role};
+use crate::tools::{ToolCall, ToolResult};
+use crate::database::ConversationStore;
+use crate::error::Result;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Channel of conversations created without one; Telegram was the only
+/// conversation channel before sessions were namespaced
+pub const DEFAULT_CHANNEL: &str = "telegram";
+
+/// Conversations idle longer than this are neither restored nor kept in the database
+pub const PERSISTENCE_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Start of the persistence window, relative to now
+fn persistence_cutoff() -> DateTime<Utc> {
+    let window = chrono::Duration::from_std(PERSISTENCE_WINDOW).unwrap_or(chrono::Duration::MAX);
+    Utc::now().checked_sub_signed(window).unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// Key of a conversation: the channel plus the user's ID on that channel,
+/// so Telegram user 123 and Slack user 123 get separate sessions
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SessionNamespace {
+    /// Channel ID (e.g. "telegram", "slack")
+    pub channel: String,
+    /// User ID on that channel
+    pub user_id: String,
+}
+
+impl SessionNamespace {
+    pub fn new(channel: impl Into<String>, user_id: impl Into<String>) -> Self {
+        SessionNamespace {
+            channel: channel.into(),
+            user_id: user_id.into(),
+        }
+    }
+
+    /// Session of a Telegram user
+    pub fn telegram(user_id: impl ToString) -> Self {
+        Self::new(DEFAULT_CHANNEL, user_id.to_string())
+    }
+
+    /// User key for per-user stores (memories, loop traces)
+    ///
+    /// Telegram IDs stay bare so data saved before namespacing still
+    /// matches; other channels are prefixed as `channel:user_id`.
+    pub fn user_key(&self) -> String {
+        if self.channel == DEFAULT_CHANNEL {
+            self.user_id.clone()
+        } else {
+            self.to_string()
+        }
+    }
+}
+
+impl fmt::Display for SessionNamespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.channel, self.user_id)
+    }
+}
+
 /// A conversation session
 #[derive(Debug, Clone)]
 pub struct Conversation {
     /// Unique conversation ID
     pub id: Uuid,
-    /// User ID (e.g., Telegram user ID)
+    /// Channel the conversation happens on
+    pub channel: String,
+    /// User ID on that channel (e.g., Telegram user ID)
     pub user_id: String,
     /// Messages in the conversation
     pub messages: Vec<Message>,
@@ -31,6 +96,7 @@ impl Conversation {
         let now = Utc::now();
         Conversation {
             id: Uuid::new_v4(),
+            channel: DEFAULT_CHANNEL.to_string(),
             user_id: user_id.into(),
             messages: Vec::new(),
             system_prompt: None,
@@ -41,14 +107,53 @@ impl Conversation {
         }
     }
 
+    /// Create a new conversation for a channel session
+    pub fn for_session(session: &SessionNamespace, model: impl Into<String>) -> Self {
+        let mut conv = Self::new(&session.user_id, model);
+        conv.channel = session.channel.clone();
+        conv
+    }
+
     /// Set the system prompt
     pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
         self.system_prompt = Some(prompt.into());
         self
     }
 
-    /// Add a message to the conversation
-    pub fn add_message(&mut self, message: Message) {
+    /// Session key of this conversation
+    pub fn session(&self) -> SessionNamespace {
+        SessionNamespace::new(&self.channel, &self.user_id)
+    }
+
+    /// Unique conversation ID
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Copy of this conversation ending at the message with `message_id`
+    ///
+    /// The fork gets a new ID and starts with no token usage; messages keep
+    /// their checkpoint IDs so both branches can be compared. Returns `None`
+    /// if no message has that checkpoint.
+    pub fn fork_at_message(&self, message_id: Uuid) -> Option<Conversation> {
+        let end = self.messages.iter().position(|m| m.checkpoint_id == message_id)?;
+        let now = Utc::now();
+        Some(Conversation {
+            id: Uuid::new_v4(),
+            channel: self.channel.clone(),
+            user_id: self.user_id.clone(),
+            messages: self.messages[..=end].to_vec(),
+            system_prompt: self.system_prompt.clone(),
+            created_at: now,
+            updated_at: now,
+            model: self.model.clone(),
+            total_tokens: 0,
+        })
+    }
+
+    /// Add a message to the conversation, assigning it a fresh checkpoint ID
+    pub fn add_message(&mut self, mut message: Message) {
+        message.checkpoint_id = Uuid::new_v4();
         self.messages.push(message);
         self.updated_at = Utc::now();
     }
@@ -63,6 +168,26 @@ impl Conversation {
         self.add_message(Message::assistant(content));
     }
 
+    /// Add the result of a tool call, keeping the call and the result's
+    /// metadata alongside the message
+    pub fn add_tool_call_result(&mut self, call: &ToolCall, result: &ToolResult) {
+        self.add_message(Message::tool_result(call, result));
+    }
+
+    /// Every tool call and its result, in order, for messages added with
+    /// [`add_tool_call_result`](Self::add_tool_call_result) or
+    /// [`Message::tool_result`]
+    ///
+    /// Bare `Message::tool` messages and messages restored from the
+    /// database carry no typed record and are skipped.
+    pub fn get_tool_results(&self) -> Vec<(&ToolCall, &ToolResult)> {
+        self.messages
+            .iter()
+            .filter_map(|m| m.tool_record.as_deref())
+            .map(|record| (&record.call, &record.result))
+            .collect()
+    }
+
     /// Get messages formatted for API request (includes system prompt)
     pub fn get_api_messages(&self) -> Vec<Message> {
         let mut messages = Vec::with_capacity(self.messages.len() + 1);
@@ -81,34 +206,77 @@ impl Conversation {
         self.messages[start..].to_vec()
     }
 
-    /// Truncate conversation to fit within token limit
-    /// Keeps system prompt and most recent messages
-    pub fn truncate_to_tokens(&mut self, max_tokens: u32) {
-        // Simple approximation: ~4 chars per token
-        let max_chars = (max_tokens * 4) as usize;
-
-        let mut total_chars: usize = self
-            .system_prompt
-            .as_ref()
-            .map(|s| s.len())
-            .unwrap_or(0);
+    /// The last `n` messages of the history, without the system prompt
+    ///
+    /// With `skip_system`, system messages leading the history (such as a
+    /// system prompt persisted with older conversations) are never included.
+    pub fn get_last_n_messages(&self, n: usize, skip_system: bool) -> &[Message] {
+        let first = if skip_system {
+            self.messages
+                .iter()
+                .position(|m| m.role != Role::System)
+                .unwrap_or(self.messages.len())
+        } else {
+            0
+        };
+        let start = self.messages.len().saturating_sub(n).max(first);
+        &self.messages[start..]
+    }
 
-        // Start from the end and keep messages that fit
-        let mut keep_from = self.messages.len();
+    /// The longest tail of the history that fits in `n_tokens`, as counted
+    /// by `estimator`
+    ///
+    /// The system prompt is not counted; callers subtract it from the budget.
+    pub fn get_last_n_tokens(&self, n_tokens: u32, estimator: &dyn TokenEstimator) -> &[Message] {
+        let mut used: u32 = 0;
+        let mut start = self.messages.len();
         for (i, msg) in self.messages.iter().enumerate().rev() {
-            let msg_chars = msg.content.len();
-            if total_chars + msg_chars > max_chars {
-                keep_from = i + 1;
+            used = used.saturating_add(estimator.estimate(&msg.content));
+            if used > n_tokens {
                 break;
             }
-            total_chars += msg_chars;
+            start = i;
         }
+        &self.messages[start..]
+    }
 
-        if keep_from > 0 {
-            self.messages = self.messages[keep_from..].to_vec();
+    /// Get all messages added after the given checkpoint.
+    ///
+    /// Returns every message if the checkpoint is unknown (e.g. truncated away),
+    /// so the client falls back to a full resync.
+    pub fn get_messages_since(&self, checkpoint_id: Uuid) -> &[Message] {
+        match self
+            .messages
+            .iter()
+            .position(|m| m.checkpoint_id == checkpoint_id)
+        {
+            Some(i) => &self.messages[i + 1..],
+            None => &self.messages,
         }
     }
 
+    /// Checkpoint ID of the most recent message, if any
+    pub fn latest_checkpoint(&self) -> Option<Uuid> {
+        self.messages.last().map(|m| m.checkpoint_id)
+    }
+
+    /// Truncate conversation to fit within token limit
+    /// Keeps system prompt and most recent messages
+    pub fn truncate_to_tokens(&mut self, max_tokens: u32) {
+        let estimator = CharTokenEstimator;
+        let system_tokens = self
+            .system_prompt
+            .as_deref()
+            .map(|s| estimator.estimate(s))
+            .unwrap_or(0);
+
+        let keep = self
+            .get_last_n_tokens(max_tokens.saturating_sub(system_tokens), &estimator)
+            .len();
+        let drop = self.messages.len() - keep;
+        self.messages.drain(..drop);
+    }
+
     /// Clear all messages
     pub fn clear(&mut self) {
         self.messages.clear();
@@ -136,21 +304,27 @@ impl Conversation {
 
 /// Manages multiple conversations
 pub struct ConversationManager {
-    /// Active conversations by user ID
-    conversations: std::collections::HashMap<String, Conversation>,
+    /// Active conversations by session
+    conversations: HashMap<SessionNamespace, Conversation>,
+    /// Forks created by `fork`, by conversation ID
+    forks: HashMap<Uuid, Conversation>,
     /// Default model for new conversations
     default_model: String,
     /// Default system prompt
     default_system_prompt: Option<String>,
+    /// Total conversations removed by `prune_inactive` since startup
+    pruned_total: u64,
 }
 
 impl ConversationManager {
     /// Create a new conversation manager
     pub fn new(default_model: impl Into<String>) -> Self {
         ConversationManager {
-            conversations: std::collections::HashMap::new(),
+            conversations: HashMap::new(),
+            forks: HashMap::new(),
             default_model: default_model.into(),
             default_system_prompt: None,
+            pruned_total: 0,
         }
     }
 
@@ -160,55 +334,190 @@ impl ConversationManager {
         self
     }
 
-    /// Get or create a conversation for a user
-    pub fn get_or_create(&mut self, user_id: &str) -> &mut Conversation {
-        if !self.conversations.contains_key(user_id) {
-            let mut conv = Conversation::new(user_id, &self.default_model);
-            if let Some(ref prompt) = self.default_system_prompt {
-                conv = conv.with_system_prompt(prompt);
-            }
-            self.conversations.insert(user_id.to_string(), conv);
+    /// Replace the default system prompt used for new conversations
+    pub fn set_system_prompt(&mut self, prompt: impl Into<String>) {
+        self.default_system_prompt = Some(prompt.into());
+    }
+
+    /// Drop the system prompt of every active conversation so callers
+    /// re-render it on the next turn (e.g. after the soul changed)
+    pub fn reset_system_prompts(&mut self) {
+        for conv in self.conversations.values_mut() {
+            conv.system_prompt = None;
         }
-        self.conversations.get_mut(user_id).unwrap()
     }
 
-    /// Get a conversation by user ID
-    pub fn get(&self, user_id: &str) -> Option<&Conversation> {
-        self.conversations.get(user_id)
+    /// Get or create the conversation for a session
+    pub fn get_or_create(&mut self, session: &SessionNamespace) -> &mut Conversation {
+        self.conversations.entry(session.clone()).or_insert_with(|| {
+            let conv = Conversation::for_session(session, &self.default_model);
+            match self.default_system_prompt {
+                Some(ref prompt) => conv.with_system_prompt(prompt),
+                None => conv,
+            }
+        })
+    }
+
+    /// Get a conversation by session
+    pub fn get(&self, session: &SessionNamespace) -> Option<&Conversation> {
+        self.conversations.get(session)
     }
 
-    /// Get a mutable conversation by user ID
-    pub fn get_mut(&mut self, user_id: &str) -> Option<&mut Conversation> {
-        self.conversations.get_mut(user_id)
+    /// Get a mutable conversation by session
+    pub fn get_mut(&mut self, session: &SessionNamespace) -> Option<&mut Conversation> {
+        self.conversations.get_mut(session)
+    }
+
+    /// Get the latest message checkpoint for a session's conversation
+    pub fn get_latest_checkpoint(&self, session: &SessionNamespace) -> Option<Uuid> {
+        self.conversations.get(session)?.latest_checkpoint()
     }
 
     /// Remove a conversation
-    pub fn remove(&mut self, user_id: &str) -> Option<Conversation> {
-        self.conversations.remove(user_id)
+    pub fn remove(&mut self, session: &SessionNamespace) -> Option<Conversation> {
+        self.conversations.remove(session)
     }
 
-    /// Clear a user's conversation (but keep the entry)
-    pub fn clear_conversation(&mut self, user_id: &str) {
-        if let Some(conv) = self.conversations.get_mut(user_id) {
+    /// Clear a session's conversation (but keep the entry)
+    pub fn clear_conversation(&mut self, session: &SessionNamespace) {
+        if let Some(conv) = self.conversations.get_mut(session) {
             conv.clear();
         }
     }
 
-    /// Get all active conversation IDs
-    pub fn active_users(&self) -> Vec<&str> {
-        self.conversations.keys().map(|s| s.as_str()).collect()
+    /// Fork a session's conversation at `message_id` and keep the fork
+    ///
+    /// Returns the fork's ID, or `None` if the session or message is unknown.
+    pub fn fork(&mut self, session: &SessionNamespace, message_id: Uuid) -> Option<Uuid> {
+        let fork = self.conversations.get(session)?.fork_at_message(message_id)?;
+        let id = fork.id();
+        self.forks.insert(id, fork);
+        Some(id)
+    }
+
+    /// Fork a kept fork again, e.g. to branch deeper in a search tree
+    pub fn fork_fork(&mut self, fork_id: Uuid, message_id: Uuid) -> Option<Uuid> {
+        let fork = self.forks.get(&fork_id)?.fork_at_message(message_id)?;
+        let id = fork.id();
+        self.forks.insert(id, fork);
+        Some(id)
+    }
+
+    /// Get a fork by ID
+    pub fn get_fork(&self, fork_id: Uuid) -> Option<&Conversation> {
+        self.forks.get(&fork_id)
+    }
+
+    /// Get a mutable fork by ID
+    pub fn get_fork_mut(&mut self, fork_id: Uuid) -> Option<&mut Conversation> {
+        self.forks.get_mut(&fork_id)
+    }
+
+    /// Discard a fork
+    pub fn remove_fork(&mut self, fork_id: Uuid) -> Option<Conversation> {
+        self.forks.remove(&fork_id)
+    }
+
+    /// IDs of the living forks of `user_id`'s conversations, oldest first
+    pub fn list_forks(&self, user_id: &str) -> Vec<Uuid> {
+        let mut forks: Vec<&Conversation> = self.forks.values().filter(|c| c.user_id == user_id).collect();
+        forks.sort_by_key(|c| (c.created_at, c.id));
+        forks.into_iter().map(Conversation::id).collect()
+    }
+
+    /// Get all active sessions, sorted by channel then user ID
+    pub fn get_all_sessions(&self) -> Vec<SessionNamespace> {
+        let mut sessions: Vec<SessionNamespace> = self.conversations.keys().cloned().collect();
+        sessions.sort();
+        sessions
+    }
+
+    /// Count active conversations per channel
+    pub fn session_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for session in self.conversations.keys() {
+            *counts.entry(session.channel.clone()).or_insert(0) += 1;
+        }
+        counts
     }
 
     /// Count active conversations
     pub fn conversation_count(&self) -> usize {
         self.conversations.len()
     }
+
+    /// Remove conversations whose last activity is older than `older_than`.
+    /// Returns the number of conversations removed; stale forks are dropped
+    /// too but not counted.
+    pub fn prune_inactive(&mut self, older_than: Duration) -> usize {
+        let max_age = chrono::Duration::from_std(older_than).unwrap_or(chrono::Duration::MAX);
+        let cutoff = Utc::now().checked_sub_signed(max_age).unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        let before = self.conversations.len();
+        self.conversations.retain(|_, conv| conv.updated_at >= cutoff);
+        let pruned = before - self.conversations.len();
+        self.forks.retain(|_, conv| conv.updated_at >= cutoff);
+
+        self.pruned_total += pruned as u64;
+        pruned
+    }
+
+    /// Restore conversations active within [`PERSISTENCE_WINDOW`] from `store`
+    ///
+    /// Restored conversations have no system prompt; callers render one on
+    /// the next message.
+    pub async fn load_from_db(mut self, store: &ConversationStore) -> Result<Self> {
+        for conv in store.load_recent(persistence_cutoff()).await? {
+            self.conversations.insert(conv.session(), conv);
+        }
+        Ok(self)
+    }
+
+    /// Save conversations active within [`PERSISTENCE_WINDOW`] to `store` and
+    /// delete older ones from it
+    pub async fn save_to_db(&self, store: &ConversationStore) -> Result<()> {
+        let cutoff = persistence_cutoff();
+        for conv in self.conversations.values().filter(|c| c.updated_at >= cutoff) {
+            store.save(conv).await?;
+        }
+        store.delete_older_than(cutoff).await?;
+        Ok(())
+    }
+
+    /// Total number of conversations pruned since this manager was created
+    pub fn pruned_total(&self) -> u64 {
+        self.pruned_total
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_tool_call_results() {
+        let mut conv = Conversation::new("user123", "gpt-4");
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "read_file".to_string(),
+            arguments: serde_json::json!({ "path": "a.txt" }),
+            parent_message_id: None,
+        };
+        let result = ToolResult::success("hello").with_metadata("bytes", 5.into());
+        conv.add_tool_call_result(&call, &result);
+        conv.add_message(Message::tool("call_2", "untyped"));
+
+        let message = &conv.messages[0];
+        assert_eq!(message.role, Role::Tool);
+        assert_eq!(message.tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(message.content, "hello");
+
+        let results = conv.get_tool_results();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "read_file");
+        assert_eq!(results[0].1.metadata("bytes"), Some(&serde_json::json!(5)));
+    }
+
     #[test]
     fn test_conversation_creation() {
         let conv = Conversation::new("user123", "gpt-4");
@@ -238,16 +547,158 @@ mod tests {
         assert_eq!(api_messages[0].role, Role::System);
     }
 
+    #[test]
+    fn test_last_n_messages_and_tokens() {
+        let mut conv = Conversation::new("user123", "gpt-4").with_system_prompt("soul");
+        conv.messages.push(Message::system("persisted prompt"));
+        conv.add_user_message("a".repeat(40)); // 10 tokens
+        conv.add_assistant_message("b".repeat(20)); // 5 tokens
+        conv.add_user_message("c".repeat(8)); // 2 tokens
+
+        assert_eq!(conv.get_last_n_messages(2, true).len(), 2);
+        assert_eq!(conv.get_last_n_messages(10, true)[0].role, Role::User);
+        assert_eq!(conv.get_last_n_messages(10, false).len(), 4);
+        assert!(conv.get_last_n_messages(0, false).is_empty());
+
+        let estimator = CharTokenEstimator;
+        assert_eq!(conv.get_last_n_tokens(7, &estimator).len(), 2);
+        assert_eq!(conv.get_last_n_tokens(16, &estimator).len(), 2);
+        assert_eq!(conv.get_last_n_tokens(17, &estimator).len(), 3);
+        assert!(conv.get_last_n_tokens(1, &estimator).is_empty());
+
+        // The system prompt ("soul", 1 token) counts against the budget
+        conv.truncate_to_tokens(8);
+        assert_eq!(conv.messages.len(), 2);
+        assert_eq!(conv.messages[0].content, "b".repeat(20));
+        conv.truncate_to_tokens(1000);
+        assert_eq!(conv.messages.len(), 2);
+    }
+
     #[test]
     fn test_conversation_manager() {
         let mut manager = ConversationManager::new("gpt-4")
             .with_system_prompt("Test system prompt");
 
-        let conv = manager.get_or_create("user1");
+        let conv = manager.get_or_create(&SessionNamespace::telegram("user1"));
         conv.add_user_message("Hello");
 
-        assert!(manager.get("user1").is_some());
-        assert!(manager.get("user2").is_none());
+        assert!(manager.get(&SessionNamespace::telegram("user1")).is_some());
+        assert!(manager.get(&SessionNamespace::telegram("user2")).is_none());
         assert_eq!(manager.conversation_count(), 1);
     }
+
+    #[test]
+    fn test_get_messages_since() {
+        let mut manager = ConversationManager::new("gpt-4");
+        let user1 = SessionNamespace::telegram("user1");
+        assert!(manager.get_latest_checkpoint(&user1).is_none());
+
+        let conv = manager.get_or_create(&user1);
+        conv.add_user_message("Hello");
+        conv.add_assistant_message("Hi there!");
+        let cursor = manager.get_latest_checkpoint(&user1).unwrap();
+
+        let conv = manager.get_mut(&user1).unwrap();
+        assert!(conv.get_messages_since(cursor).is_empty());
+
+        conv.add_user_message("How are you?");
+        let new = conv.get_messages_since(cursor);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].content, "How are you?");
+
+        // Unknown cursor falls back to the full history
+        assert_eq!(conv.get_messages_since(Uuid::new_v4()).len(), 3);
+    }
+
+    #[test]
+    fn test_fork_at_message() {
+        let mut manager = ConversationManager::new("gpt-4").with_system_prompt("soul");
+        let user1 = SessionNamespace::telegram("user1");
+        let conv = manager.get_or_create(&user1);
+        conv.add_user_message("Plan a trip");
+        conv.add_assistant_message("Option A");
+        let branch_point = conv.messages[0].checkpoint_id;
+        conv.total_tokens = 50;
+
+        let fork = conv.fork_at_message(branch_point).unwrap();
+        assert_ne!(fork.id(), conv.id());
+        assert_eq!(fork.messages.len(), 1);
+        assert_eq!(fork.messages[0].checkpoint_id, branch_point);
+        assert_eq!(fork.system_prompt.as_deref(), Some("soul"));
+        assert_eq!(fork.total_tokens, 0);
+        assert!(conv.fork_at_message(Uuid::new_v4()).is_none());
+
+        let fork_id = manager.fork(&user1, branch_point).unwrap();
+        manager.get_fork_mut(fork_id).unwrap().add_assistant_message("Option B");
+        assert_eq!(manager.get(&user1).unwrap().messages[1].content, "Option A");
+        assert_eq!(manager.get_fork(fork_id).unwrap().messages[1].content, "Option B");
+
+        let deeper = manager.fork_fork(fork_id, branch_point).unwrap();
+        let forks = manager.list_forks("user1");
+        assert_eq!(forks.len(), 2);
+        assert!(forks.contains(&fork_id) && forks.contains(&deeper));
+        assert!(manager.list_forks("user2").is_empty());
+        assert!(manager.fork(&SessionNamespace::telegram("user2"), branch_point).is_none());
+
+        manager.remove_fork(fork_id);
+        assert_eq!(manager.list_forks("user1"), vec![deeper]);
+    }
+
+    #[test]
+    fn test_prune_inactive() {
+        let mut manager = ConversationManager::new("gpt-4");
+        let (stale, fresh) = (SessionNamespace::telegram("stale"), SessionNamespace::telegram("fresh"));
+        manager.get_or_create(&stale).updated_at = Utc::now() - chrono::Duration::hours(2);
+        manager.get_or_create(&fresh).add_user_message("Hello");
+
+        assert_eq!(manager.prune_inactive(Duration::from_secs(3600)), 1);
+        assert!(manager.get(&stale).is_none());
+        assert!(manager.get(&fresh).is_some());
+        assert_eq!(manager.pruned_total(), 1);
+
+        assert_eq!(manager.prune_inactive(Duration::from_secs(3600)), 0);
+        assert_eq!(manager.pruned_total(), 1);
+    }
+
+    #[test]
+    fn test_reset_system_prompts() {
+        let mut manager = ConversationManager::new("gpt-4").with_system_prompt("old soul");
+        let user1 = SessionNamespace::telegram("user1");
+        manager.get_or_create(&user1).add_user_message("Hello");
+
+        manager.set_system_prompt("new soul");
+        manager.reset_system_prompts();
+        assert!(manager.get(&user1).unwrap().system_prompt.is_none());
+        assert_eq!(manager.get(&user1).unwrap().messages.len(), 1);
+        assert_eq!(
+            manager.get_or_create(&SessionNamespace::telegram("user2")).system_prompt.as_deref(),
+            Some("new soul")
+        );
+    }
+
+    #[test]
+    fn test_sessions_isolated_by_channel() {
+        let mut manager = ConversationManager::new("gpt-4");
+        let telegram = SessionNamespace::telegram(123);
+        let slack = SessionNamespace::new("slack", "123");
+        manager.get_or_create(&telegram).add_user_message("from telegram");
+        manager.get_or_create(&slack).add_user_message("from slack");
+        manager.get_or_create(&SessionNamespace::telegram(456));
+
+        assert_eq!(manager.conversation_count(), 3);
+        assert_eq!(manager.get(&telegram).unwrap().messages[0].content, "from telegram");
+        assert_eq!(manager.get(&slack).unwrap().messages[0].content, "from slack");
+        assert_eq!(manager.get(&slack).unwrap().session(), slack);
+
+        assert_eq!(
+            manager.get_all_sessions(),
+            vec![slack.clone(), telegram.clone(), SessionNamespace::telegram(456)]
+        );
+        let counts = manager.session_counts();
+        assert_eq!(counts["telegram"], 2);
+        assert_eq!(counts["slack"], 1);
+
+        assert_eq!(telegram.user_key(), "123");
+        assert_eq!(slack.user_key(), "slack:123");
+    }
 }
@@ -0,0 +1,105 @@
+//! Sentry error reporting for the agentic loop
+//!
+//! With the `sentry` feature and `SENTRY_DSN` set, [`init_error_reporting`]
+//! starts the Sentry client and [`SentryLoopCallback::from_env`] returns a
+//! callback that captures every LLM and tool error of a loop run, tagged with
+//! the iteration, model and user. Otherwise both are no-ops, so callers don't
+//! need their own feature checks.
+
+use async_trait::async_trait;
+
+use super::agentic_loop::LoopCallback;
+use crate::error::Error;
+
+/// Environment variable holding the Sentry DSN
+pub const SENTRY_DSN_ENV: &str = "SENTRY_DSN";
+
+/// The configured DSN, if any
+fn sentry_dsn() -> Option<String> {
+    std::env::var(SENTRY_DSN_ENV).ok().filter(|dsn| !dsn.trim().is_empty())
+}
+
+/// Flushes pending Sentry events when dropped. Keep it alive for the life of
+/// the process.
+#[must_use = "dropping the guard stops error reporting"]
+pub struct ErrorReportingGuard {
+    #[cfg(feature = "sentry")]
+    _client: Option<sentry::ClientInitGuard>,
+}
+
+/// Start the Sentry client if `SENTRY_DSN` is set
+pub fn init_error_reporting() -> ErrorReportingGuard {
+    #[cfg(feature = "sentry")]
+    {
+        let client = sentry_dsn().map(|dsn| {
+            let guard = sentry::init((
+                dsn,
+                sentry::ClientOptions {
+                    release: sentry::release_name!(),
+                    ..Default::default()
+                },
+            ));
+            tracing::info!("Sentry error reporting enabled");
+            guard
+        });
+        ErrorReportingGuard { _client: client }
+    }
+
+    #[cfg(not(feature = "sentry"))]
+    {
+        if sentry_dsn().is_some() {
+            tracing::warn!("SENTRY_DSN is set but OpenAgent was built without the `sentry` feature");
+        }
+        ErrorReportingGuard {}
+    }
+}
+
+/// Loop callback that reports errors to Sentry
+#[cfg_attr(not(feature = "sentry"), allow(dead_code))]
+pub struct SentryLoopCallback {
+    model: String,
+    user_id: Option<String>,
+}
+
+impl SentryLoopCallback {
+    /// Callback for one loop run, or `None` when error reporting is off
+    /// (no `SENTRY_DSN`, or built without the `sentry` feature)
+    pub fn from_env(model: impl Into<String>, user_id: Option<&str>) -> Option<Self> {
+        if !cfg!(feature = "sentry") || sentry_dsn().is_none() {
+            return None;
+        }
+        Some(SentryLoopCallback {
+            model: model.into(),
+            user_id: user_id.map(str::to_string),
+        })
+    }
+}
+
+#[async_trait]
+impl LoopCallback for SentryLoopCallback {
+    #[cfg(feature = "sentry")]
+    async fn on_error(&self, err: &Error, iteration: u32) {
+        use sentry::protocol::{Context, Map};
+
+        sentry::with_scope(
+            |scope| {
+                scope.set_tag("model", &self.model);
+                scope.set_tag("error_category", err.category());
+                if let Some(ref user_id) = self.user_id {
+                    scope.set_user(Some(sentry::User {
+                        id: Some(user_id.clone()),
+                        ..Default::default()
+                    }));
+                }
+                let mut context = Map::new();
+                context.insert("iteration".to_string(), iteration.into());
+                context.insert("model".to_string(), self.model.clone().into());
+                scope.set_context("agent_loop", Context::Other(context));
+            },
+            || sentry::capture_error(err),
+        );
+    }
+
+    #[cfg(not(feature = "sentry"))]
+    async fn on_error(&self, _err: &Error, _iteration: u32) {}
+}
@@ -1,9 +1,43 @@
 //! Loop guard for agentic tool-calling loops.
 //!
 //! Detects when the LLM is stuck calling the same tool repeatedly with
-//! similar arguments/results and injects a hint to force reconsideration.
+//! similar arguments/results and escalates its intervention each time:
+//! first a gentle hint, then hints paired with a backoff delay, and finally
+//! an instruction to abort the loop. With an [`AdaptiveTemperature`]
+//! schedule it also raises the sampling temperature on each detection, so
+//! the LLM is less likely to repeat the same call.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::agent::types::AdaptiveTemperature;
+
+/// Default backoff delays applied after the first stuck-loop hint.
+pub fn default_stuck_loop_backoff() -> Vec<Duration> {
+    vec![Duration::from_secs(2)]
+}
+
+/// What the agentic loop should do after a stuck loop is detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopGuardAction {
+    /// Inject the hint message and continue.
+    Hint(String),
+    /// Inject the hint message and sleep for `wait` before the next LLM call.
+    Backoff { hint: String, wait: Duration },
+    /// Stop the loop; the tool keeps being retried despite earlier hints.
+    Abort(String),
+}
+
+impl LoopGuardAction {
+    /// The message to surface to the LLM (or log, for `Abort`).
+    pub fn message(&self) -> &str {
+        match self {
+            LoopGuardAction::Hint(hint) => hint,
+            LoopGuardAction::Backoff { hint, .. } => hint,
+            LoopGuardAction::Abort(reason) => reason,
+        }
+    }
+}
 
 /// Tracks recent tool calls and detects stuck loops.
 pub struct LoopGuard {
@@ -11,24 +45,77 @@ pub struct LoopGuard {
     recent: VecDeque<(String, u64, String)>,
     /// How many consecutive same-tool-same-result calls trigger intervention.
     threshold: usize,
+    /// Delays applied on successive detections after the first hint.
+    backoff: Vec<Duration>,
+    /// How many times a stuck loop has been detected since the last reset,
+    /// per (tool_name, arguments_hash), so one stuck call does not escalate
+    /// the intervention for another.
+    strikes: HashMap<(String, u64), usize>,
+    /// (tool_name, result_snippet) of the previous call; unlike `recent`,
+    /// not cleared when the guard fires.
+    last_call: Option<(String, String)>,
+    /// Temperature schedule and the current temperature, if adaptive.
+    adaptive: Option<(AdaptiveTemperature, f32)>,
 }
 
 impl LoopGuard {
     /// Create a new guard. `threshold` is how many consecutive identical
     /// results from the same tool trigger a hint (default: 3).
     pub fn new(threshold: usize) -> Self {
+        Self::with_backoff(threshold, default_stuck_loop_backoff())
+    }
+
+    /// Create a guard with explicit backoff stages.
+    ///
+    /// The first detection yields a [`LoopGuardAction::Hint`], each following
+    /// detection consumes one entry of `backoff` as a
+    /// [`LoopGuardAction::Backoff`], and the detection after that yields
+    /// [`LoopGuardAction::Abort`].
+    pub fn with_backoff(threshold: usize, backoff: Vec<Duration>) -> Self {
         Self {
             recent: VecDeque::with_capacity(threshold + 1),
             threshold,
+            backoff,
+            strikes: HashMap::new(),
+            last_call: None,
+            adaptive: None,
         }
     }
 
-    /// Record a tool call and its result. Returns `Some(hint)` if the LLM
-    /// appears stuck and should be told to stop retrying.
-    pub fn record(&mut self, tool_name: &str, arguments: &str, result: &str) -> Option<String> {
+    /// Raise the temperature on every detection according to `schedule`.
+    pub fn with_adaptive_temperature(mut self, schedule: AdaptiveTemperature) -> Self {
+        self.adaptive = Some((schedule, schedule.initial));
+        self
+    }
+
+    /// Temperature the next LLM call should use, if adaptive.
+    pub fn temperature(&self) -> Option<f32> {
+        self.adaptive.map(|(_, current)| current)
+    }
+
+    /// Record a tool call and its result. Returns `Some(action)` if the LLM
+    /// appears stuck and the loop should intervene.
+    pub fn record(
+        &mut self,
+        tool_name: &str,
+        arguments: &str,
+        result: &str,
+    ) -> Option<LoopGuardAction> {
         let arg_hash = Self::simple_hash(arguments);
         let result_snippet = Self::snippet(result);
 
+        // A call that breaks the streak restores the initial temperature
+        let repeated = self
+            .last_call
+            .as_ref()
+            .is_some_and(|(name, snip)| name == tool_name && *snip == result_snippet);
+        if !repeated {
+            if let Some((schedule, current)) = &mut self.adaptive {
+                *current = schedule.initial;
+            }
+        }
+        self.last_call = Some((tool_name.to_string(), result_snippet.clone()));
+
         self.recent.push_back((tool_name.to_string(), arg_hash, result_snippet.clone()));
 
         // Keep only the last `threshold` entries
@@ -44,13 +131,13 @@ impl LoopGuard {
 
             if all_same {
                 self.recent.clear(); // Reset so we don't keep firing
-                return Some(format!(
-                    "[SYSTEM] The tool '{}' has returned the same result {} times in a row. \
-                     Do NOT call this tool again with a similar query. \
-                     Instead, respond to the user with what you already know, \
-                     or try a completely different approach.",
-                    tool_name, self.threshold
-                ));
+                let strikes = self.strikes.entry((tool_name.to_string(), arg_hash)).or_insert(0);
+                *strikes += 1;
+                let strikes = *strikes;
+                if let Some((schedule, current)) = &mut self.adaptive {
+                    *current = (*current + schedule.increment).min(schedule.max);
+                }
+                return Some(self.escalate(tool_name, strikes));
             }
         }
 
@@ -60,6 +147,42 @@ impl LoopGuard {
     /// Reset the guard (e.g., between conversations).
     pub fn reset(&mut self) {
         self.recent.clear();
+        self.strikes.clear();
+        self.last_call = None;
+        if let Some((schedule, current)) = &mut self.adaptive {
+            *current = schedule.initial;
+        }
+    }
+
+    /// Pick the intervention for the call's strike count.
+    fn escalate(&self, tool_name: &str, strikes: usize) -> LoopGuardAction {
+        if strikes <= 1 {
+            return LoopGuardAction::Hint(format!(
+                "[SYSTEM] The tool '{}' has returned the same result {} times in a row. \
+                 Do NOT call this tool again with a similar query. \
+                 Instead, respond to the user with what you already know, \
+                 or try a completely different approach.",
+                tool_name, self.threshold
+            ));
+        }
+
+        match self.backoff.get(strikes - 2) {
+            Some(&wait) => LoopGuardAction::Backoff {
+                hint: format!(
+                    "[SYSTEM] The tool '{}' is still returning the same result. \
+                     wait_seconds={}: pausing before the next attempt. \
+                     Stop retrying this tool and answer with what you already know.",
+                    tool_name,
+                    wait.as_secs()
+                ),
+                wait,
+            },
+            None => LoopGuardAction::Abort(format!(
+                "The tool '{}' kept returning the same result after {} warnings; aborting the loop.",
+                tool_name,
+                strikes - 1
+            )),
+        }
     }
 
     /// Simple non-cryptographic hash for argument deduplication.
@@ -126,4 +249,75 @@ mod tests {
         // After trigger, internal state is cleared
         assert!(guard.record("t", "{}", result).is_none());
     }
+
+    #[test]
+    fn escalates_hint_backoff_abort() {
+        let wait = Duration::from_secs(5);
+        let mut guard = LoopGuard::with_backoff(1, vec![wait]);
+
+        assert!(matches!(
+            guard.record("t", "{}", "same"),
+            Some(LoopGuardAction::Hint(_))
+        ));
+        match guard.record("t", "{}", "same") {
+            Some(LoopGuardAction::Backoff { hint, wait: w }) => {
+                assert_eq!(w, wait);
+                assert!(hint.contains("wait_seconds=5"));
+            }
+            other => panic!("expected backoff, got {:?}", other),
+        }
+        assert!(matches!(
+            guard.record("t", "{}", "same"),
+            Some(LoopGuardAction::Abort(_))
+        ));
+
+        guard.reset();
+        assert!(matches!(
+            guard.record("t", "{}", "same"),
+            Some(LoopGuardAction::Hint(_))
+        ));
+    }
+
+    #[test]
+    fn strikes_are_kept_per_tool_and_arguments() {
+        let mut guard = LoopGuard::with_backoff(1, vec![Duration::from_secs(5)]);
+
+        assert!(matches!(guard.record("a", "{}", "same"), Some(LoopGuardAction::Hint(_))));
+        // A different tool, or the same tool with other arguments, starts over
+        assert!(matches!(guard.record("b", "{}", "same"), Some(LoopGuardAction::Hint(_))));
+        assert!(matches!(
+            guard.record("a", r#"{"q":1}"#, "same"),
+            Some(LoopGuardAction::Hint(_))
+        ));
+        assert!(matches!(
+            guard.record("a", "{}", "same"),
+            Some(LoopGuardAction::Backoff { .. })
+        ));
+    }
+
+    #[test]
+    fn adaptive_temperature_rises_and_resets() {
+        let schedule = AdaptiveTemperature {
+            initial: 0.5,
+            increment: 0.25,
+            max: 1.0,
+        };
+        let mut guard = LoopGuard::with_backoff(2, vec![Duration::ZERO; 3])
+            .with_adaptive_temperature(schedule);
+        assert_eq!(guard.temperature(), Some(0.5));
+
+        guard.record("t", "{}", "same");
+        assert!(guard.record("t", "{}", "same").is_some());
+        assert_eq!(guard.temperature(), Some(0.75));
+
+        guard.record("t", "{}", "same");
+        assert!(guard.record("t", "{}", "same").is_some());
+        assert_eq!(guard.temperature(), Some(1.0));
+
+        // A different result breaks the streak
+        assert!(guard.record("t", "{}", "other").is_none());
+        assert_eq!(guard.temperature(), Some(0.5));
+
+        assert_eq!(LoopGuard::new(3).temperature(), None);
+    }
 }
@@ -13,26 +13,35 @@
 pub mod agentic_loop;
 mod client;
 mod conversation;
+mod streaming;
+pub mod error_reporting;
 pub mod loop_guard;
+pub mod model_metadata;
 pub mod prompts;
 pub(crate) mod types;
 
 pub use agentic_loop::{
-    run_agentic_loop, AgentLoopInput, AgentLoopOutput, LoopCallback, LoopConfig, LoopOutcome,
-    LoopTrace, NoOpCallback,
+    run_agentic_loop, run_agentic_loop_typed, AgentLoopInput, AgentLoopOutput, LoopCallback,
+    LoopConfig, LoopOutcome, LoopRecording, LoopTrace, NoOpCallback, TruncationStrategy,
+    STRUCTURED_OUTPUT_RETRIES,
 };
 pub use client::OpenRouterClient;
-pub use conversation::{Conversation, ConversationManager};
-pub use loop_guard::LoopGuard;
+pub use conversation::{Conversation, ConversationManager, SessionNamespace, DEFAULT_CHANNEL};
+pub use error_reporting::{init_error_reporting, ErrorReportingGuard, SentryLoopCallback};
+pub use loop_guard::{LoopGuard, LoopGuardAction};
+pub use model_metadata::{estimate_tokens, CharTokenEstimator, ModelMetadata, TokenEstimator};
 pub use prompts::PromptTemplate;
 pub use types::*;
 
 // Re-export tools from the new location for backward compatibility
 pub use crate::tools::{
-    Tool, ToolCall, ToolResult, ToolRegistry,
-    ReadFileTool, WriteFileTool, SystemCommandTool,
-    DuckDuckGoSearchTool, BraveSearchTool, BraveSearchConfig,
+    Tool, ToolCall, ToolResult, ToolRegistry, ToolRegistrySnapshot, ToolDryRunResult,
+    Permission, PermissionSet,
+    MetricsRecorder, PrometheusMetricsRecorder,
+    ReadFileTool, WriteFileTool, DiffTool, SystemCommandTool, FileWatcherTool, FileWatcherPollTool, GitTool,
+    DuckDuckGoSearchTool, FetchPageTool, BraveSearchTool, BraveSearchConfig, BraveImageSearchTool,
     PerplexitySearchTool, PerplexityConfig, SearchResult,
     MemorySaveTool, MemorySearchTool, MemoryListTool, MemoryDeleteTool,
     TaskCreateTool, TaskListTool, TaskUpdateTool,
+    CalendarTool, NotionTool,
 };
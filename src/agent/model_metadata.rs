@@ -0,0 +1,115 @@
+//! Built-in model metadata (context windows) and token estimation
+
+/// Context window used for models missing from the table
+pub const DEFAULT_CONTEXT_WINDOW: u32 = 32_768;
+
+/// Static metadata for a known model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelMetadata {
+    /// OpenRouter model ID (`provider/name`)
+    pub id: &'static str,
+    /// Maximum context window in tokens
+    pub context_window: u32,
+}
+
+const MODELS: &[ModelMetadata] = &[
+    ModelMetadata { id: "anthropic/claude-opus-4", context_window: 200_000 },
+    ModelMetadata { id: "anthropic/claude-sonnet-4", context_window: 200_000 },
+    ModelMetadata { id: "anthropic/claude-3.7-sonnet", context_window: 200_000 },
+    ModelMetadata { id: "anthropic/claude-3.5-sonnet", context_window: 200_000 },
+    ModelMetadata { id: "anthropic/claude-3.5-haiku", context_window: 200_000 },
+    ModelMetadata { id: "anthropic/claude-3-haiku", context_window: 200_000 },
+    ModelMetadata { id: "openai/gpt-4.1", context_window: 1_047_576 },
+    ModelMetadata { id: "openai/gpt-4o", context_window: 128_000 },
+    ModelMetadata { id: "openai/gpt-4o-mini", context_window: 128_000 },
+    ModelMetadata { id: "openai/gpt-4-turbo", context_window: 128_000 },
+    ModelMetadata { id: "openai/gpt-4", context_window: 8_192 },
+    ModelMetadata { id: "openai/gpt-3.5-turbo", context_window: 16_385 },
+    ModelMetadata { id: "openai/o1", context_window: 200_000 },
+    ModelMetadata { id: "openai/o3-mini", context_window: 200_000 },
+    ModelMetadata { id: "google/gemini-2.5-pro", context_window: 1_048_576 },
+    ModelMetadata { id: "google/gemini-2.0-flash-001", context_window: 1_048_576 },
+    ModelMetadata { id: "google/gemini-pro-1.5", context_window: 2_000_000 },
+    ModelMetadata { id: "meta-llama/llama-3.1-8b-instruct", context_window: 131_072 },
+    ModelMetadata { id: "meta-llama/llama-3.1-70b-instruct", context_window: 131_072 },
+    ModelMetadata { id: "meta-llama/llama-3.3-70b-instruct", context_window: 131_072 },
+    ModelMetadata { id: "mistralai/mistral-7b-instruct", context_window: 32_768 },
+    ModelMetadata { id: "mistralai/mixtral-8x7b-instruct", context_window: 32_768 },
+    ModelMetadata { id: "deepseek/deepseek-chat", context_window: 64_000 },
+    ModelMetadata { id: "deepseek/deepseek-r1", context_window: 64_000 },
+    ModelMetadata { id: "qwen/qwen-2.5-72b-instruct", context_window: 32_768 },
+];
+
+/// Look up a model by ID.
+///
+/// Variant suffixes (`:free`, `:beta`) are ignored, dated releases match their
+/// base ID (longest prefix wins), and a bare name without the provider prefix
+/// is accepted.
+pub fn lookup(model: &str) -> Option<&'static ModelMetadata> {
+    let model = model.split(':').next().unwrap_or(model).trim();
+    let has_provider = model.contains('/');
+
+    MODELS
+        .iter()
+        .filter(|m| {
+            let id = if has_provider {
+                m.id
+            } else {
+                m.id.split_once('/').map_or(m.id, |(_, name)| name)
+            };
+            model.starts_with(id)
+        })
+        .max_by_key(|m| m.id.len())
+}
+
+/// Context window for a model, falling back to [`DEFAULT_CONTEXT_WINDOW`]
+pub fn context_window(model: &str) -> u32 {
+    lookup(model)
+        .map(|m| m.context_window)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW)
+}
+
+/// Rough token count for `text` (~4 characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Token counter used to fit messages into a context window
+pub trait TokenEstimator: Send + Sync {
+    /// Estimated tokens in `text`
+    fn estimate(&self, text: &str) -> u32;
+}
+
+/// [`estimate_tokens`] as a [`TokenEstimator`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharTokenEstimator;
+
+impl TokenEstimator for CharTokenEstimator {
+    fn estimate(&self, text: &str) -> u32 {
+        estimate_tokens(text) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(context_window("anthropic/claude-sonnet-4"), 200_000);
+        assert_eq!(context_window("openai/gpt-4o-mini"), 128_000);
+        // Longest prefix wins over `openai/gpt-4`
+        assert_eq!(context_window("openai/gpt-4-turbo-preview"), 128_000);
+        assert_eq!(context_window("openai/gpt-4"), 8_192);
+        assert_eq!(context_window("meta-llama/llama-3.1-8b-instruct:free"), 131_072);
+        assert_eq!(context_window("claude-3.5-sonnet"), 200_000);
+        assert_eq!(context_window("someone/unknown-model"), DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+}
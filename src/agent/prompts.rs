@@ -2,14 +2,29 @@
 
 use handlebars::Handlebars;
 use serde::Serialize;
+use crate::agent::model_metadata::{context_window, estimate_tokens};
 use crate::error::{Error, Result};
 use std::path::Path;
 use chrono::Utc;
+use tracing::{info, warn};
 
 /// Default path for the SOUL.md file
 pub const SOUL_FILE_PATH: &str = "SOUL.md";
 
+/// Share of a model's context window the rendered soul may occupy
+const SOUL_CONTEXT_SHARE: f64 = 0.10;
+
+/// Soul section mapping tool names to behavioral hints
+pub const TOOL_PREFERENCES_SECTION: &str = "Tool Preferences";
+
+/// Workspace subdirectory holding operator `*.hbs` templates
+pub const TEMPLATES_DIR: &str = "templates";
+
 /// A prompt template using Handlebars syntax
+///
+/// Supports `{{variable}}`, `{{#if condition}}...{{/if}}` and
+/// `{{#each items}}...{{/each}}`. Output is plain text, so values are
+/// inserted without HTML escaping.
 pub struct PromptTemplate {
     /// Template name
     name: String,
@@ -22,6 +37,7 @@ impl PromptTemplate {
     pub fn new(name: impl Into<String>, template: &str) -> Result<Self> {
         let name = name.into();
         let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
 
         registry
             .register_template_string(&name, template)
@@ -30,7 +46,47 @@ impl PromptTemplate {
         Ok(PromptTemplate { name, registry })
     }
 
+    /// Load template `name` from `workspace/templates/`, falling back to `default`
+    ///
+    /// Every `*.hbs` file in the directory is registered under its file stem,
+    /// so templates can include each other as partials (`{{> footer}}`).
+    /// `workspace/templates/<name>.hbs` replaces the built-in default.
+    pub fn load(name: &str, workspace: &Path, default: &str) -> Result<Self> {
+        let mut template = Self::new(name, default)?;
+
+        let dir = workspace.join(TEMPLATES_DIR);
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(template),
+        };
+
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("hbs") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            match template.registry.register_template_file(stem, &path) {
+                Ok(()) if stem == name => info!("Using prompt template {}", path.display()),
+                Ok(()) => {}
+                Err(e) => {
+                    warn!("Skipping prompt template {}: {}", path.display(), e);
+                    // A broken override must not leave `name` unregistered
+                    if stem == name {
+                        template = Self::new(name, default)?;
+                    }
+                }
+            }
+        }
+
+        Ok(template)
+    }
+
     /// Render the template with given data
+    ///
+    /// Any `Serialize` value works, e.g. a `HashMap<&str, &str>` of variables
+    /// or a `serde_json::json!` object when `{{#each}}` needs a list.
     pub fn render<T: Serialize>(&self, data: &T) -> Result<String> {
         self.registry
             .render(&self.name, data)
@@ -85,10 +141,55 @@ impl Soul {
         format!(
             "{}\n\n---\n\n## Agent Soul\n\n{}",
             DEFAULT_SYSTEM_PROMPT,
-            self.content
+            split_front_matter(&self.content).1
         )
     }
 
+    /// Get the soul as a system prompt for `model`, rendered with
+    /// [`render_for_model`](Self::render_for_model)
+    pub fn as_system_prompt_for_model(&self, model: &str) -> String {
+        format!(
+            "{}\n\n---\n\n## Agent Soul\n\n{}",
+            DEFAULT_SYSTEM_PROMPT,
+            self.render_for_model(model)
+        )
+    }
+
+    /// Render the soul to fit a model's context window.
+    ///
+    /// If the soul takes more than 10% of the model's context, `## ` sections
+    /// marked low priority in the front matter are dropped, last first, until
+    /// it fits or none remain. The stored content is never modified.
+    ///
+    /// ```text
+    /// ---
+    /// priority:
+    ///   Areas of Expertise: low
+    ///   Memory & Learning: low
+    /// ---
+    /// ```
+    pub fn render_for_model(&self, model: &str) -> String {
+        let budget = (context_window(model) as f64 * SOUL_CONTEXT_SHARE) as usize;
+        let (front_matter, body) = split_front_matter(&self.content);
+        let low_priority = low_priority_sections(front_matter);
+
+        let mut sections = split_soul_sections(body);
+        let mut rendered: String = sections.iter().map(|(_, text)| *text).collect();
+
+        while estimate_tokens(&rendered) > budget {
+            let Some(idx) = sections
+                .iter()
+                .rposition(|(name, _)| name.is_some_and(|n| low_priority.contains(&n)))
+            else {
+                break;
+            };
+            sections.remove(idx);
+            rendered = sections.iter().map(|(_, text)| *text).collect();
+        }
+
+        rendered
+    }
+
     /// Update a specific section in the soul
     pub fn update_section(&mut self, section: &str, new_content: &str) -> Result<()> {
         // Find the section header
@@ -132,6 +233,85 @@ impl Soul {
         self.update_section("User Preferences", &new_content)
     }
 
+    /// Hint for interpreting `tool_name`'s output, from the
+    /// `## Tool Preferences` section
+    pub fn get_tool_preference(&self, tool_name: &str) -> Option<&str> {
+        parse_tool_preferences(&self.content)
+            .into_iter()
+            .find(|(name, _)| *name == tool_name)
+            .map(|(_, preference)| preference)
+    }
+
+    /// All `(tool name, preference)` pairs from the `## Tool Preferences` section
+    pub fn tool_preferences(&self) -> Vec<(&str, &str)> {
+        parse_tool_preferences(&self.content)
+    }
+
+    /// Set the preference for `tool_name`, replacing any existing one
+    ///
+    /// The `## Tool Preferences` section is created before the
+    /// `*Last updated*` footer if missing.
+    pub fn add_tool_preference(&mut self, tool_name: &str, preference: &str) -> Result<()> {
+        let tool_name = tool_name.trim();
+        if tool_name.is_empty() || tool_name.contains([':', '\n']) {
+            return Err(Error::InvalidInput(format!("Invalid tool name '{}'", tool_name)));
+        }
+        let preference = preference.split_whitespace().collect::<Vec<_>>().join(" ");
+        if preference.is_empty() {
+            return Err(Error::InvalidInput("Tool preference must not be empty".to_string()));
+        }
+        let entry = format!("- {}: {}", tool_name, preference);
+
+        let header = format!("## {}", TOOL_PREFERENCES_SECTION);
+        let mut lines: Vec<String> = self.content.lines().map(str::to_string).collect();
+        match lines.iter().position(|l| l.trim_end() == header) {
+            Some(start) => {
+                let end = lines[start + 1..]
+                    .iter()
+                    .position(|l| l.starts_with("## ") || l.trim() == "---")
+                    .map_or(lines.len(), |pos| start + 1 + pos);
+                let existing = (start + 1..end)
+                    .find(|&i| parse_preference_line(&lines[i]).is_some_and(|(name, _)| name == tool_name));
+                let last = (start + 1..end).rev().find(|&i| !lines[i].trim().is_empty());
+                match (existing, last) {
+                    (Some(i), _) => lines[i] = entry,
+                    // Replace the `_No tool preferences set._` placeholder
+                    (None, Some(i)) if lines[i].starts_with('_') => lines[i] = entry,
+                    (None, Some(i)) => lines.insert(i + 1, entry),
+                    (None, None) => {
+                        lines.insert(start + 1, entry);
+                        lines.insert(start + 1, String::new());
+                    }
+                }
+                let trailing_newline = self.content.ends_with('\n');
+                self.content = lines.join("\n");
+                if trailing_newline {
+                    self.content.push('\n');
+                }
+            }
+            None => {
+                let section = format!("{}\n\n{}\n\n", header, entry);
+                let footer = self
+                    .content
+                    .rfind("\n---\n")
+                    .filter(|&pos| self.content[pos..].contains("*Last updated:"));
+                match footer {
+                    Some(pos) => self.content.insert_str(pos + 1, &section),
+                    None => {
+                        if !self.content.is_empty() && !self.content.ends_with("\n\n") {
+                            self.content.push_str(if self.content.ends_with('\n') { "\n" } else { "\n\n" });
+                        }
+                        self.content.push_str(section.trim_end());
+                        self.content.push('\n');
+                    }
+                }
+            }
+        }
+
+        self.update_timestamp();
+        self.save()
+    }
+
     /// Add a frequently asked topic
     pub fn add_topic(&mut self, topic: &str) -> Result<()> {
         let current = self.get_section_content("Frequently Asked Topics");
@@ -192,6 +372,83 @@ impl Soul {
     }
 }
 
+/// `(tool name, preference)` pairs listed as `- tool: preference` under the
+/// `## Tool Preferences` section of a soul
+pub fn parse_tool_preferences(content: &str) -> Vec<(&str, &str)> {
+    split_soul_sections(split_front_matter(content).1)
+        .into_iter()
+        .filter(|(name, _)| *name == Some(TOOL_PREFERENCES_SECTION))
+        .flat_map(|(_, text)| text.lines().filter_map(parse_preference_line))
+        .collect()
+}
+
+/// Parse one `- tool: preference` list item
+fn parse_preference_line(line: &str) -> Option<(&str, &str)> {
+    let item = line.trim().strip_prefix("- ").or_else(|| line.trim().strip_prefix("* "))?;
+    let (name, preference) = item.split_once(':')?;
+    let name = name.trim().trim_matches('`');
+    let preference = preference.trim();
+    (!name.is_empty() && !preference.is_empty()).then_some((name, preference))
+}
+
+/// Split optional `---`-delimited front matter from the soul body
+fn split_front_matter(content: &str) -> (&str, &str) {
+    let Some(rest) = content.strip_prefix("---\n") else {
+        return ("", content);
+    };
+    match rest.find("\n---\n") {
+        Some(end) => (&rest[..end], &rest[end + 5..]),
+        None => ("", content),
+    }
+}
+
+/// Section names listed as `Name: low` under the front matter `priority:` key
+fn low_priority_sections(front_matter: &str) -> Vec<&str> {
+    let mut in_priority = false;
+    let mut sections = Vec::new();
+
+    for line in front_matter.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') {
+            in_priority = line.trim_end() == "priority:";
+            continue;
+        }
+        if in_priority {
+            if let Some((name, level)) = line.trim().rsplit_once(':') {
+                if level.trim().eq_ignore_ascii_case("low") {
+                    sections.push(name.trim());
+                }
+            }
+        }
+    }
+
+    sections
+}
+
+/// Split the soul body into `## ` sections, keeping the exact text so that
+/// concatenating the pieces reproduces the input. The preamble has no name.
+fn split_soul_sections(body: &str) -> Vec<(Option<&str>, &str)> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    let mut name = None;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("## ") {
+            if offset > start {
+                sections.push((name, &body[start..offset]));
+            }
+            start = offset;
+            name = Some(header.trim());
+        }
+        offset += line.len();
+    }
+    if offset > start {
+        sections.push((name, &body[start..offset]));
+    }
+
+    sections
+}
+
 impl Default for Soul {
     fn default() -> Self {
         Soul {
@@ -237,6 +494,7 @@ You have access to these tools and SHOULD USE THEM when asked:
 ### Search Tools
 - `duckduckgo_search`: Search the web for current information
 - `brave_search`: Search using Brave Search API (if configured)
+- `brave_image_search`: Find images with Brave Search (if configured)
 - `perplexity_search`: AI-powered search with synthesized answers (if configured)
 
 ### Memory Tools (if available)
@@ -276,6 +534,35 @@ You are running inside a real environment with real system access via the system
 - When installing software, show the commands you're running
 "#;
 
+
+/// Built-in system prompt template; `workspace/templates/system.hbs` overrides it
+///
+/// Variables: `base_prompt`, `soul`, and the per-user `username`,
+/// `timezone` and `language` (each may be absent).
+pub const SYSTEM_PROMPT_TEMPLATE: &str = r#"{{base_prompt}}
+{{#if soul}}
+
+---
+
+## Agent Soul
+
+{{soul}}
+{{/if}}
+{{#if username}}
+
+---
+
+## Current User
+
+- Name: {{username}}
+{{#if timezone}}
+- Timezone: {{timezone}}
+{{/if}}
+{{#if language}}
+- Preferred language: {{language}} (reply in this language unless asked otherwise)
+{{/if}}
+{{/if}}"#;
+
 /// Code execution prompt template
 pub const CODE_EXECUTION_PROMPT: &str = r#"You have been asked to execute code. Here is the context:
 
@@ -385,6 +672,7 @@ impl PromptBuilder {
 mod tests {
     use super::*;
     use serde_json::json;
+    use std::collections::HashMap;
 
     #[test]
     fn test_prompt_template() {
@@ -393,6 +681,48 @@ mod tests {
         assert_eq!(result, "Hello, World!");
     }
 
+    #[test]
+    fn test_system_prompt_template() {
+        let template = PromptTemplate::new("system", SYSTEM_PROMPT_TEMPLATE).unwrap();
+
+        let soul_only = template
+            .render(&HashMap::from([("base_prompt", "Base"), ("soul", "Be kind & brief")]))
+            .unwrap();
+        assert_eq!(soul_only, "Base\n\n---\n\n## Agent Soul\n\nBe kind & brief\n");
+
+        let per_user = template
+            .render(&HashMap::from([
+                ("base_prompt", "Base"),
+                ("username", "alice"),
+                ("language", "ja"),
+            ]))
+            .unwrap();
+        assert!(per_user.ends_with("## Current User\n\n- Name: alice\n- Preferred language: ja (reply in this language unless asked otherwise)\n"));
+        assert!(!per_user.contains("Timezone"));
+    }
+
+    #[test]
+    fn test_prompt_template_each() {
+        let template = PromptTemplate::new("list", "{{#each items}}- {{this}}\n{{/each}}").unwrap();
+        let result = template.render(&json!({"items": ["a", "b"]})).unwrap();
+        assert_eq!(result, "- a\n- b\n");
+    }
+
+    #[test]
+    fn test_prompt_template_load_override() {
+        let workspace = tempfile::tempdir().unwrap();
+        let default = PromptTemplate::load("system", workspace.path(), "Default {{name}}").unwrap();
+        assert_eq!(default.render(&HashMap::from([("name", "x")])).unwrap(), "Default x");
+
+        let dir = workspace.path().join(TEMPLATES_DIR);
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("system.hbs"), "Hi {{name}}{{> sig}}").unwrap();
+        std::fs::write(dir.join("sig.hbs"), " -- ops").unwrap();
+
+        let custom = PromptTemplate::load("system", workspace.path(), "Default {{name}}").unwrap();
+        assert_eq!(custom.render(&HashMap::from([("name", "x")])).unwrap(), "Hi x -- ops");
+    }
+
     #[test]
     fn test_prompt_builder() {
         let prompt = PromptBuilder::new()
@@ -405,4 +735,70 @@ mod tests {
         assert!(prompt.contains("```python"));
         assert!(prompt.contains("- Item 1"));
     }
+
+    #[test]
+    fn test_render_for_model_drops_low_priority_sections() {
+        let filler = "word ".repeat(4000);
+        let soul = Soul {
+            content: format!(
+                "---\npriority:\n  Expertise: low\n  Memory: low\n---\n# Soul\n\n## Identity\n\nOpenAgent\n\n## Expertise\n\n{filler}\n\n## Memory\n\n{filler}\n"
+            ),
+            path: String::new(),
+        };
+
+        // Plenty of room: everything but the front matter is kept
+        let full = soul.render_for_model("anthropic/claude-sonnet-4");
+        assert!(full.starts_with("# Soul"));
+        assert!(full.contains("## Expertise") && full.contains("## Memory"));
+        assert!(!full.contains("priority:"));
+
+        // 8k context → 819 token budget: both low-priority sections must go
+        let small = soul.render_for_model("openai/gpt-4");
+        assert!(small.contains("## Identity"));
+        assert!(!small.contains("## Expertise") && !small.contains("## Memory"));
+
+        // The stored soul is untouched
+        assert!(soul.content.contains("## Memory"));
+    }
+
+    #[test]
+    fn test_tool_preferences() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut soul = Soul {
+            path: dir.path().join("SOUL.md").to_string_lossy().to_string(),
+            ..Soul::default()
+        };
+        assert!(soul.tool_preferences().is_empty());
+
+        soul.add_tool_preference("web_search", "prefer academic sources").unwrap();
+        soul.add_tool_preference("read_file", "quote line numbers").unwrap();
+        soul.add_tool_preference("web_search", "prefer  primary\nsources").unwrap();
+        assert_eq!(soul.get_tool_preference("web_search"), Some("prefer primary sources"));
+        assert_eq!(soul.tool_preferences().len(), 2);
+        assert!(!soul.content.contains("_No tool preferences set._"));
+        assert!(soul.add_tool_preference("bad:name", "x").is_err());
+
+        // Saved to disk and the footer is still last
+        let saved = Soul::load_from(&soul.path).unwrap();
+        assert_eq!(saved.get_tool_preference("read_file"), Some("quote line numbers"));
+        assert!(!saved.content.contains("*Last updated: Never*"));
+
+        // A soul without the section gets one before the footer
+        let mut bare = Soul {
+            content: "## Identity\n\nOpenAgent\n\n---\n\n*Last updated: Never*\n".to_string(),
+            path: dir.path().join("BARE.md").to_string_lossy().to_string(),
+        };
+        bare.add_tool_preference("git", "`git log` before `git diff`").unwrap();
+        assert!(bare.content.starts_with("## Identity\n\nOpenAgent\n\n## Tool Preferences\n\n- git: `git log` before `git diff`\n\n---\n"));
+        assert_eq!(bare.get_tool_preference("git"), Some("`git log` before `git diff`"));
+    }
+
+    #[test]
+    fn test_split_soul_sections_round_trip() {
+        let body = "# Title\n\n## A\n\na\n\n## B\nb";
+        let sections = split_soul_sections(body);
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[1].0, Some("A"));
+        assert_eq!(sections.iter().map(|(_, t)| *t).collect::<String>(), body);
+    }
 }
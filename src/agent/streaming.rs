@@ -0,0 +1,260 @@
+//! Server-sent event decoding for streamed chat completions
+//!
+//! With `"stream": true`, OpenRouter answers with `text/event-stream`: one
+//! `data: {chunk}` line per [`ChatCompletionChunk`], `: ...` keep-alive
+//! comments while the model is queued, and `data: [DONE]` at the end.
+//! [`SseDecoder`] turns the raw body into [`StreamingChunk`]s for
+//! [`OpenRouterClient::stream_chat`](super::OpenRouterClient::stream_chat),
+//! and [`StreamAccumulator`] joins them back into a full response.
+
+use crate::agent::types::{
+    AssistantToolCall, ChatCompletionChunk, ChatCompletionResponse, Choice, FunctionCall, Message,
+};
+use crate::core::{StreamingChunk, StreamingToolCall};
+use crate::error::{Error, Result};
+
+/// Splits an SSE body into lines and decodes its `data:` payloads
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    /// Bytes after the last newline; a chunk may end mid-line or mid-character
+    pending: Vec<u8>,
+    done: bool,
+}
+
+impl SseDecoder {
+    /// Whether `[DONE]` or an error ended the stream
+    pub(crate) fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Decode the complete lines in `bytes`
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<Result<StreamingChunk>> {
+        self.pending.extend_from_slice(bytes);
+        let mut chunks = Vec::new();
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            if self.done {
+                continue;
+            }
+            if let Some(chunk) = self.decode_line(&String::from_utf8_lossy(&line)) {
+                self.done |= chunk.is_err();
+                chunks.push(chunk);
+            }
+        }
+        chunks
+    }
+
+    /// Decode a trailing line left without a newline when the body ends
+    pub(crate) fn finish(&mut self) -> Vec<Result<StreamingChunk>> {
+        if self.pending.is_empty() {
+            return Vec::new();
+        }
+        self.push(b"\n")
+    }
+
+    fn decode_line(&mut self, line: &str) -> Option<Result<StreamingChunk>> {
+        let data = line.trim_end_matches(['\r', '\n']).strip_prefix("data:")?.trim_start();
+        if data == "[DONE]" {
+            self.done = true;
+            return None;
+        }
+
+        let value: serde_json::Value = match serde_json::from_str(data) {
+            Ok(value) => value,
+            Err(e) => return Some(Err(Error::OpenRouter(format!("Invalid stream chunk: {}", e)))),
+        };
+        // Errors after the response started arrive as a chunk
+        if let Some(error) = value.get("error") {
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+            return Some(Err(Error::OpenRouter(format!("Stream error: {}", message))));
+        }
+        match serde_json::from_value::<ChatCompletionChunk>(value) {
+            Ok(chunk) => streaming_chunk(chunk).map(Ok),
+            Err(e) => Some(Err(Error::OpenRouter(format!("Invalid stream chunk: {}", e)))),
+        }
+    }
+}
+
+/// The first choice of `chunk`, if it has one
+fn streaming_chunk(chunk: ChatCompletionChunk) -> Option<StreamingChunk> {
+    let choice = chunk.choices.into_iter().next()?;
+    let tool_calls = choice
+        .delta
+        .tool_calls
+        .unwrap_or_default()
+        .into_iter()
+        .map(|call| {
+            let (name, arguments) = call.function.map_or((None, None), |f| (f.name, f.arguments));
+            StreamingToolCall {
+                index: call.index,
+                id: call.id,
+                name,
+                arguments: arguments.unwrap_or_default(),
+            }
+        })
+        .collect();
+    Some(StreamingChunk {
+        id: chunk.id,
+        delta: choice.delta.content.unwrap_or_default(),
+        is_final: choice.finish_reason.is_some(),
+        finish_reason: choice.finish_reason,
+        tool_calls,
+    })
+}
+
+/// Chunks for a complete response, used when replaying recordings
+pub(crate) fn response_chunks(response: ChatCompletionResponse) -> Vec<StreamingChunk> {
+    let Some(choice) = response.choices.into_iter().next() else {
+        return Vec::new();
+    };
+    let tool_calls = choice
+        .message
+        .tool_calls
+        .unwrap_or_default()
+        .into_iter()
+        .enumerate()
+        .map(|(index, call)| StreamingToolCall {
+            index: index as u32,
+            id: Some(call.id),
+            name: Some(call.function.name),
+            arguments: call.function.arguments,
+        })
+        .collect();
+    vec![StreamingChunk {
+        id: response.id,
+        delta: choice.message.content,
+        is_final: true,
+        finish_reason: choice.finish_reason.or_else(|| Some("stop".to_string())),
+        tool_calls,
+    }]
+}
+
+/// Joins streamed chunks into the response a non-streaming call returns
+#[derive(Debug, Default)]
+pub(crate) struct StreamAccumulator {
+    id: String,
+    content: String,
+    tool_calls: Vec<AssistantToolCall>,
+    finish_reason: Option<String>,
+}
+
+impl StreamAccumulator {
+    pub(crate) fn push(&mut self, chunk: StreamingChunk) {
+        if self.id.is_empty() {
+            self.id = chunk.id;
+        }
+        self.content.push_str(&chunk.delta);
+        for part in chunk.tool_calls {
+            let index = part.index as usize;
+            while self.tool_calls.len() <= index {
+                self.tool_calls.push(AssistantToolCall {
+                    id: String::new(),
+                    call_type: "function".to_string(),
+                    function: FunctionCall {
+                        name: String::new(),
+                        arguments: String::new(),
+                    },
+                });
+            }
+            let call = &mut self.tool_calls[index];
+            if let Some(id) = part.id {
+                call.id = id;
+            }
+            if let Some(name) = part.name {
+                call.function.name.push_str(&name);
+            }
+            call.function.arguments.push_str(&part.arguments);
+        }
+        if chunk.finish_reason.is_some() {
+            self.finish_reason = chunk.finish_reason;
+        }
+    }
+
+    /// The joined response; streams carry no usage statistics
+    pub(crate) fn into_response(self, model: &str) -> ChatCompletionResponse {
+        let mut message = Message::assistant(self.content);
+        if !self.tool_calls.is_empty() {
+            message.tool_calls = Some(self.tool_calls);
+        }
+        ChatCompletionResponse {
+            id: self.id,
+            object: "chat.completion".to_string(),
+            created: chrono::Utc::now().timestamp().max(0) as u64,
+            model: model.to_string(),
+            choices: vec![Choice {
+                index: 0,
+                message,
+                finish_reason: self.finish_reason,
+            }],
+            usage: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(json: &str) -> String {
+        format!("data: {}\n\n", json)
+    }
+
+    #[test]
+    fn test_decoder_splits_lines_and_stops_at_done() {
+        let first = data(r#"{"id":"gen-1","object":"chat.completion.chunk","created":0,"model":"m","choices":[{"index":0,"delta":{"role":"assistant","content":"Héllo"},"finish_reason":null}]}"#);
+        let last = data(r#"{"id":"gen-1","object":"chat.completion.chunk","created":0,"model":"m","choices":[{"index":0,"delta":{"content":"!"},"finish_reason":"stop"}]}"#);
+        let body = format!(": OPENROUTER PROCESSING\n\n{}{}data: [DONE]\n\n{}", first, last, first);
+        let bytes = body.as_bytes();
+
+        // Split inside the multi-byte character
+        let split = body.find('é').unwrap() + 1;
+        let mut decoder = SseDecoder::default();
+        let mut chunks = decoder.push(&bytes[..split]);
+        assert!(chunks.is_empty());
+        chunks.extend(decoder.push(&bytes[split..]));
+        chunks.extend(decoder.finish());
+        assert!(decoder.is_done());
+
+        let chunks: Vec<StreamingChunk> = chunks.into_iter().map(|c| c.unwrap()).collect();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].delta, "Héllo");
+        assert!(!chunks[0].is_final);
+        assert_eq!(chunks[1].finish_reason.as_deref(), Some("stop"));
+
+        let mut decoder = SseDecoder::default();
+        let errors = decoder.push(data(r#"{"error":{"code":502,"message":"Provider disconnected"}}"#).as_bytes());
+        assert!(errors[0].as_ref().unwrap_err().to_string().contains("Provider disconnected"));
+        assert!(decoder.is_done());
+    }
+
+    #[test]
+    fn test_accumulator_joins_tool_call_fragments() {
+        let chunk = |delta: &str, tool_calls: Vec<StreamingToolCall>, finish: Option<&str>| StreamingChunk {
+            id: "gen-1".to_string(),
+            delta: delta.to_string(),
+            is_final: finish.is_some(),
+            finish_reason: finish.map(str::to_string),
+            tool_calls,
+        };
+        let part = |id: Option<&str>, name: Option<&str>, arguments: &str| StreamingToolCall {
+            index: 0,
+            id: id.map(str::to_string),
+            name: name.map(str::to_string),
+            arguments: arguments.to_string(),
+        };
+
+        let mut accumulator = StreamAccumulator::default();
+        accumulator.push(chunk("Let me look.", vec![], None));
+        accumulator.push(chunk("", vec![part(Some("call_1"), Some("read_file"), "{\"pa")], None));
+        accumulator.push(chunk("", vec![part(None, None, "th\":\"a.txt\"}")], Some("tool_calls")));
+
+        let response = accumulator.into_response("test/model");
+        let choice = &response.choices[0];
+        assert_eq!(choice.message.content, "Let me look.");
+        assert_eq!(choice.finish_reason.as_deref(), Some("tool_calls"));
+        let calls = choice.message.tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a.txt"}"#);
+    }
+}
@@ -1,6 +1,9 @@
 //! Type definitions for the agent module
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::tools::{ToolCall, ToolResult};
 
 /// Role of a message in a conversation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -43,6 +46,22 @@ pub struct Message {
     /// Optional tool calls made by assistant
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<AssistantToolCall>>,
+    /// Sync checkpoint assigned when the message is added to a conversation
+    /// (nil until then). Never sent to the LLM.
+    #[serde(skip)]
+    pub checkpoint_id: Uuid,
+    /// Call and result behind a tool message built with
+    /// [`Message::tool_result`]. Never sent to the LLM or persisted.
+    #[serde(skip)]
+    pub tool_record: Option<Box<ToolCallRecord>>,
+}
+
+/// A tool call together with its result
+#[derive(Debug, Clone)]
+pub struct ToolCallRecord {
+    pub call: ToolCall,
+    /// The result, including its `metadata`
+    pub result: ToolResult,
 }
 
 impl Message {
@@ -54,6 +73,8 @@ impl Message {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            checkpoint_id: Uuid::nil(),
+            tool_record: None,
         }
     }
 
@@ -65,6 +86,8 @@ impl Message {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            checkpoint_id: Uuid::nil(),
+            tool_record: None,
         }
     }
 
@@ -76,6 +99,20 @@ impl Message {
             name: None,
             tool_call_id: None,
             tool_calls: None,
+            checkpoint_id: Uuid::nil(),
+            tool_record: None,
+        }
+    }
+
+    /// Create a tool result message for `call`, keeping the typed call and
+    /// result (see [`Conversation::get_tool_results`](crate::agent::Conversation::get_tool_results))
+    pub fn tool_result(call: &ToolCall, result: &ToolResult) -> Self {
+        Message {
+            tool_record: Some(Box::new(ToolCallRecord {
+                call: call.clone(),
+                result: result.clone(),
+            })),
+            ..Self::tool(&call.id, result.to_string())
         }
     }
 
@@ -87,6 +124,51 @@ impl Message {
             name: None,
             tool_call_id: Some(tool_call_id.into()),
             tool_calls: None,
+            checkpoint_id: Uuid::nil(),
+            tool_record: None,
+        }
+    }
+}
+
+/// Image attached to a vision request, by URL or inline base64 data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInput {
+    /// Publicly reachable image URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Base64-encoded image data (used when `url` is not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    /// MIME type, e.g. `image/jpeg`
+    pub media_type: String,
+}
+
+impl ImageInput {
+    /// Image served from a URL
+    pub fn from_url(url: impl Into<String>, media_type: impl Into<String>) -> Self {
+        ImageInput {
+            url: Some(url.into()),
+            base64: None,
+            media_type: media_type.into(),
+        }
+    }
+
+    /// Image from raw bytes, base64-encoded for the request
+    pub fn from_bytes(data: &[u8], media_type: impl Into<String>) -> Self {
+        use base64::Engine;
+        ImageInput {
+            url: None,
+            base64: Some(base64::engine::general_purpose::STANDARD.encode(data)),
+            media_type: media_type.into(),
+        }
+    }
+
+    /// URL for the `image_url` content part: the URL itself, or a data URL
+    pub fn to_data_url(&self) -> Option<String> {
+        match (&self.url, &self.base64) {
+            (Some(url), _) => Some(url.clone()),
+            (None, Some(data)) => Some(format!("data:{};base64,{}", self.media_type, data)),
+            (None, None) => None,
         }
     }
 }
@@ -187,7 +269,7 @@ pub struct FunctionName {
 }
 
 /// Response from the OpenRouter API
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatCompletionResponse {
     /// Unique ID for this completion
     pub id: String,
@@ -204,7 +286,7 @@ pub struct ChatCompletionResponse {
 }
 
 /// A completion choice
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Choice {
     /// Index of this choice
     pub index: u32,
@@ -215,7 +297,7 @@ pub struct Choice {
 }
 
 /// Token usage statistics
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Usage {
     /// Tokens in the prompt
     pub prompt_tokens: u32,
@@ -294,7 +376,7 @@ pub struct FunctionDelta {
 }
 
 /// Generation options for chat completions
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GenerationOptions {
     /// Maximum tokens to generate
     pub max_tokens: Option<u32>,
@@ -306,6 +388,20 @@ pub struct GenerationOptions {
     pub stop: Option<Vec<String>>,
     /// Whether to stream the response
     pub stream: bool,
+    /// Raise the temperature while the loop guard reports a stuck loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptive_temperature: Option<AdaptiveTemperature>,
+}
+
+/// Temperature schedule applied by the agentic loop's loop guard
+///
+/// Each stuck-loop detection raises the temperature by `increment`, capped
+/// at `max`; a tool call that breaks the streak drops it back to `initial`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveTemperature {
+    pub initial: f32,
+    pub increment: f32,
+    pub max: f32,
 }
 
 impl GenerationOptions {
@@ -333,4 +429,16 @@ impl GenerationOptions {
             ..Default::default()
         }
     }
+
+    /// Start at `initial` temperature and raise it by `increment` (up to
+    /// `max`) each time the loop guard detects a stuck tool-call loop
+    pub fn with_adaptive_temperature(mut self, initial: f32, increment: f32, max: f32) -> Self {
+        self.temperature = Some(initial);
+        self.adaptive_temperature = Some(AdaptiveTemperature {
+            initial,
+            increment,
+            max,
+        });
+        self
+    }
 }
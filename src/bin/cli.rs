@@ -5,12 +5,18 @@
 use clap::{Parser, Subcommand};
 use console::{style, Term};
 use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, MultiSelect, Password, Select};
-use openagent::config::{Config, ExecutionEnv};
+use openagent::config::{config_path, read_config_snapshot, Config, ConfigSnapshot, ExecutionEnv};
 use openagent::database::{
-    init_pool, init_pool_for_migrations, migrations,
-    ConfigParamStore, ConfigValueType, PostgresPool, SoulStore,
+    count_user_data, init_pool, init_pool_for_migrations, merge_users, migrations,
+    ConfigParamStore, ConfigValueType, MemoryStore, PostgresPool, SoulStore, TaskStore,
+};
+use openagent::memory::{EmbeddingService, MemoryCache, MemoryRetriever};
+use openagent::tools::{
+    BraveSearchTool, DiffTool, DuckDuckGoSearchTool, FetchPageTool, FileWatcherTool, GitTool,
+    PerplexitySearchTool, ReadFileTool, SystemCommandTool, ToolRegistry, WriteFileTool,
 };
 use openagent::{Error, Result, VERSION};
+use prettytable::{Cell, Row, Table};
 use rand::seq::SliceRandom;
 use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
@@ -65,10 +71,13 @@ enum Commands {
 
     /// Execute code in the sandbox
     Run {
-        /// Programming language
+        /// Programming language (python, javascript, typescript, bash, go, kotlin, java, ruby)
         language: String,
         /// Code to execute
         code: String,
+        /// Check the language and executor without running the code
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// List available models
@@ -89,6 +98,150 @@ enum Commands {
         #[command(subcommand)]
         action: Option<SoulAction>,
     },
+
+    /// Manage autonomous tasks
+    Tasks {
+        #[command(subcommand)]
+        action: TasksAction,
+    },
+
+    /// Back up or restore long-term memories
+    Memory {
+        #[command(subcommand)]
+        action: MemoryAction,
+    },
+
+    /// Inspect the effective configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Document the agent's tools
+    Tools {
+        #[command(subcommand)]
+        action: ToolsAction,
+    },
+
+    /// Migrate data between user accounts
+    Users {
+        #[command(subcommand)]
+        action: UsersAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum UsersAction {
+    /// Move a user's memories, tasks, conversations and settings to another user ID, e.g. after a Telegram account change
+    Merge {
+        /// User ID to move data from
+        #[arg(long)]
+        from: String,
+        /// User ID to move data to
+        #[arg(long)]
+        to: String,
+        /// Count what would be moved without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ToolsAction {
+    /// Show a tool's parameters and an example call
+    Explain {
+        /// Tool name, e.g. `git` or `read_file`
+        tool_name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Show fields where the live config (file + environment) differs from the config file
+    Diff,
+}
+
+#[derive(Subcommand)]
+enum MemoryAction {
+    /// Export a user's memories as JSONL
+    Export {
+        /// User whose memories to export
+        #[arg(long)]
+        user: String,
+        /// Output file (defaults to stdout)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Import memories from a JSONL export (existing IDs are overwritten)
+    Import {
+        /// Input file (defaults to stdin)
+        #[arg(short, long)]
+        input: Option<String>,
+    },
+    /// Rebuild the full-text search index of stored memories
+    Reindex {
+        /// Only reindex this user's memories (defaults to all users)
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Recompute a user's embeddings with the current embedding model
+    Reembed {
+        /// User whose memories to re-embed
+        #[arg(long)]
+        user: String,
+    },
+    /// Show memory count, storage size and importance distribution for a user
+    Stats {
+        /// User whose memories to summarize
+        #[arg(long)]
+        user: String,
+    },
+    /// Delete a user's old, unimportant memories
+    Vacuum {
+        /// User whose memories to prune
+        #[arg(long)]
+        user: String,
+        /// Only memories created longer ago than this, e.g. `30d` or `12h`
+        #[arg(long, value_parser = humantime_serde::re::humantime::parse_duration)]
+        older_than: std::time::Duration,
+        /// Only memories with importance below this (0.0 - 1.0)
+        #[arg(long)]
+        below_importance: f32,
+        /// Count the memories that would be deleted without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Group a user's memories into themes by embedding similarity
+    Cluster {
+        /// User whose memories to cluster
+        #[arg(long)]
+        user: String,
+        /// Maximum number of clusters
+        #[arg(long, default_value_t = 10)]
+        clusters: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum TasksAction {
+    /// Schedule a recurring task, e.g. `tasks schedule "0 9 * * *" "Send daily summary"`
+    Schedule {
+        /// Cron expression (5-field, or 6-field with leading seconds), in UTC
+        cron: String,
+        /// What the agent should do each time the task fires
+        description: String,
+        /// User the task runs on behalf of
+        #[arg(long, default_value = "cli")]
+        user: String,
+        /// Telegram chat to associate with the task
+        #[arg(long)]
+        chat_id: Option<i64>,
+        /// Task priority (higher runs first)
+        #[arg(long, default_value_t = 0)]
+        priority: i32,
+    },
+    /// List recurring tasks
+    Recurring,
 }
 
 #[derive(Subcommand)]
@@ -126,11 +279,16 @@ async fn main() -> Result<()> {
         Some(Commands::Status) => check_status().await,
         Some(Commands::Migrate) => run_migrations().await,
         Some(Commands::TestLlm { model }) => test_llm(model).await,
-        Some(Commands::Run { language, code }) => run_code(&language, &code).await,
+        Some(Commands::Run { language, code, dry_run }) => run_code(&language, &code, dry_run).await,
         Some(Commands::Models) => list_models().await,
         Some(Commands::InitConfig) => init_config(),
         Some(Commands::Chat { model }) => interactive_chat(model).await,
         Some(Commands::Soul { action }) => manage_soul(action).await,
+        Some(Commands::Tasks { action }) => manage_tasks(action).await,
+        Some(Commands::Memory { action }) => manage_memory(action).await,
+        Some(Commands::Config { action }) => manage_config(action),
+        Some(Commands::Tools { action }) => manage_tools(action),
+        Some(Commands::Users { action }) => manage_users(action).await,
         None => interactive_main_menu().await,
     }
 }
@@ -1528,6 +1686,8 @@ WantedBy=multi-user.target
 
 /// Check status of all services
 async fn check_status() -> Result<()> {
+    use secrecy::ExposeSecret;
+
     println!("🔍 OpenAgent Status\n");
 
     let config = match Config::from_env() {
@@ -1545,6 +1705,22 @@ async fn check_status() -> Result<()> {
     println!("  Model: {}", default_model);
     println!("  Execution: {}", config.sandbox.execution_env);
 
+    let redacted = config.to_redacted();
+    if let Some(openrouter) = redacted.provider.openrouter.as_ref() {
+        println!("  OpenRouter key: {}", openrouter.api_key.expose_secret());
+    }
+    if let Some(postgres) = redacted.storage.postgres.as_ref() {
+        println!("  Database URL: {}", postgres.url.expose_secret());
+    }
+    let auth = &redacted.gateway.auth;
+    println!("  Gateway auth: {:?}", auth.mode);
+    if let Some(password) = auth.password.as_deref() {
+        println!("    Password: {}", password);
+    }
+    for token in &auth.tokens {
+        println!("    Token: {}", token);
+    }
+
     // Check OpenRouter
     match test_openrouter(&config).await {
         Ok(_) => println!("OpenRouter: ✅ Connected"),
@@ -1610,7 +1786,7 @@ async fn test_llm(model: Option<String>) -> Result<()> {
 }
 
 /// Run code in sandbox
-async fn run_code(language: &str, code: &str) -> Result<()> {
+async fn run_code(language: &str, code: &str, dry_run: bool) -> Result<()> {
     use openagent::sandbox::{create_executor, ExecutionRequest, Language};
 
     let config = Config::from_env()?;
@@ -1619,6 +1795,23 @@ async fn run_code(language: &str, code: &str) -> Result<()> {
     let language: Language = language.parse()?;
     let request = ExecutionRequest::new(code, language);
 
+    if dry_run {
+        if !executor.supports_language(language) {
+            return Err(Error::Sandbox(format!(
+                "{} executor does not support {}",
+                executor.name(),
+                language
+            )));
+        }
+        println!(
+            "✓ {} code ({} bytes) would run on the {} executor",
+            language,
+            request.code.len(),
+            executor.name()
+        );
+        return Ok(());
+    }
+
     println!("Executing {} code...\n", language);
 
     let result = executor.execute(request).await?;
@@ -1785,7 +1978,7 @@ async fn interactive_chat(model: Option<String>) -> Result<()> {
     let config = Config::from_env()?;
     let openrouter_config = config.provider.openrouter.clone()
         .ok_or_else(|| Error::Config("OpenRouter not configured".into()))?;
-    let client = OpenRouterClient::new(openrouter_config.clone())?;
+    let client = OpenRouterClient::from_provider_config(&config.provider)?;
 
     println!();
     println!("{}", style("╔══════════════════════════════════════════════════╗").cyan());
@@ -1839,7 +2032,7 @@ async fn interactive_chat(model: Option<String>) -> Result<()> {
 
     // Load the agent's soul for personality
     let soul = openagent::agent::prompts::Soul::load_or_default();
-    let system_prompt = soul.as_system_prompt();
+    let system_prompt = soul.as_system_prompt_for_model(&model);
 
     let mut conversation = Conversation::new("cli-user", &model)
         .with_system_prompt(&system_prompt);
@@ -1879,7 +2072,7 @@ async fn interactive_chat(model: Option<String>) -> Result<()> {
                         // Reload soul in case it was edited
                         let soul = openagent::agent::prompts::Soul::load_or_default();
                         conversation = Conversation::new("cli-user", &current_model)
-                            .with_system_prompt(soul.as_system_prompt());
+                            .with_system_prompt(soul.as_system_prompt_for_model(&current_model));
                         println!("\n   {} Switched to {}, conversation cleared.\n", 
                             style("✓").green(), style(&current_model).cyan());
                     }
@@ -1890,7 +2083,7 @@ async fn interactive_chat(model: Option<String>) -> Result<()> {
                     // Reload the soul after editing
                     let soul = openagent::agent::prompts::Soul::load_or_default();
                     conversation = Conversation::new("cli-user", &current_model)
-                        .with_system_prompt(soul.as_system_prompt());
+                        .with_system_prompt(soul.as_system_prompt_for_model(&current_model));
                     println!("   {} Soul reloaded into conversation.\n", style("✓").green());
                     continue;
                 }
@@ -1956,6 +2149,335 @@ async fn get_db_pool() -> Option<PostgresPool> {
     init_pool(pg).await.ok()
 }
 
+/// Manage autonomous tasks
+async fn manage_tasks(action: TasksAction) -> Result<()> {
+    let pool = match get_db_pool().await {
+        Some(p) => p,
+        None => {
+            println!("   {} PostgreSQL not configured. Tasks require a database.", style("⚠").yellow());
+            return Ok(());
+        }
+    };
+    let task_store = TaskStore::new(pool);
+
+    match action {
+        TasksAction::Schedule { cron, description, user, chat_id, priority } => {
+            let title: String = description.chars().take(80).collect();
+            let task = task_store
+                .create_recurring(&cron, &user, chat_id, &title, &description, priority)
+                .await?;
+
+            println!();
+            println!("   {} Scheduled: \"{}\"", style("✓").green(), style(&task.title).cyan());
+            println!("   Schedule: {}", task.cron_schedule);
+            println!("   Next run: {}", task.next_run_at.format("%Y-%m-%d %H:%M UTC"));
+            println!();
+        }
+        TasksAction::Recurring => {
+            let tasks = task_store.list_recurring().await?;
+            println!();
+            if tasks.is_empty() {
+                println!("   No recurring tasks.");
+            }
+            for task in tasks {
+                let state = if task.enabled { style("on").green() } else { style("off").dim() };
+                println!(
+                    "   [{}] {:<15} next {}  {}",
+                    state,
+                    task.cron_schedule,
+                    task.next_run_at.format("%Y-%m-%d %H:%M"),
+                    task.title,
+                );
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Export, import, reindex, re-embed, summarize, vacuum or cluster memories
+async fn manage_memory(action: MemoryAction) -> Result<()> {
+    let pool = get_db_pool()
+        .await
+        .ok_or_else(|| Error::Config("PostgreSQL not configured. Memories require a database.".into()))?;
+    let store = MemoryStore::new(pool);
+
+    match action {
+        MemoryAction::Export { user, output } => {
+            let count = match &output {
+                Some(path) => {
+                    let mut writer = tokio::io::BufWriter::new(tokio::fs::File::create(path).await?);
+                    store.export_jsonl(&user, &mut writer).await?
+                }
+                None => store.export_jsonl(&user, &mut tokio::io::stdout()).await?,
+            };
+            // Report on stderr so stdout stays valid JSONL
+            eprintln!(
+                "   {} Exported {} memories for {}{}",
+                style("✓").green(),
+                count,
+                user,
+                output.map(|p| format!(" to {}", p)).unwrap_or_default()
+            );
+        }
+        MemoryAction::Import { input } => {
+            let count = match &input {
+                Some(path) => {
+                    let mut reader = tokio::io::BufReader::new(tokio::fs::File::open(path).await?);
+                    store.import_jsonl(&mut reader).await?
+                }
+                None => store.import_jsonl(&mut tokio::io::BufReader::new(tokio::io::stdin())).await?,
+            };
+            eprintln!("   {} Imported {} memories", style("✓").green(), count);
+        }
+        MemoryAction::Reindex { user } => {
+            let count = store.full_text_index_rebuild(user.as_deref()).await?;
+            println!(
+                "   {} Reindexed {} memories{}",
+                style("✓").green(),
+                count,
+                user.map(|u| format!(" for {}", u)).unwrap_or_default()
+            );
+        }
+        MemoryAction::Reembed { user } => {
+            let embedding = EmbeddingService::new()?;
+            let count = embedding.reembed_all(&store, &user).await?;
+            println!("   {} Re-embedded {} memories for {}", style("✓").green(), count, user);
+        }
+        MemoryAction::Stats { user } => {
+            let stats = store.stats(&user).await?;
+            let importance = &stats.importance;
+            println!("\n{} Memory stats for {}\n", style("📊").bold(), style(&user).cyan());
+            println!("   Memories:  {}", stats.total_count);
+            println!("   Storage:   {:.1} KiB", stats.storage_bytes as f64 / 1024.0);
+            println!(
+                "   Oldest:    {}",
+                stats.oldest_memory_at.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()).unwrap_or_else(|| "-".into())
+            );
+            println!(
+                "   Importance: mean {:.2}, p50 {:.2}, p90 {:.2}\n",
+                importance.mean, importance.p50, importance.p90
+            );
+
+            let widest = importance.buckets.iter().copied().max().unwrap_or(0).max(1);
+            for (i, count) in importance.buckets.iter().enumerate() {
+                let bar = "█".repeat((*count as usize * 30).div_ceil(widest as usize));
+                println!("   {:.1}-{:.1} {:>6} {}", i as f32 / 10.0, (i + 1) as f32 / 10.0, count, style(bar).cyan());
+            }
+            println!();
+        }
+        MemoryAction::Vacuum { user, older_than, below_importance, dry_run } => {
+            let age = humantime_serde::re::humantime::format_duration(older_than);
+            if dry_run {
+                let count = store.count_vacuum_candidates(&user, older_than, below_importance).await?;
+                println!(
+                    "   {} {} memories for {} are older than {} with importance below {:.2} (dry run, nothing deleted)",
+                    style("ℹ").blue(),
+                    count,
+                    user,
+                    age,
+                    below_importance
+                );
+            } else {
+                let count = store.vacuum(&user, older_than, below_importance).await?;
+                println!(
+                    "   {} Deleted {} memories for {} older than {} with importance below {:.2}",
+                    style("✓").green(),
+                    count,
+                    user,
+                    age,
+                    below_importance
+                );
+            }
+        }
+        MemoryAction::Cluster { user, clusters } => {
+            use openagent::agent::OpenRouterClient;
+
+            let config = Config::from_env()?;
+            let cache = MemoryCache::new(config.storage.memory.embedding.cache_policy);
+            let mut retriever = MemoryRetriever::new(store, EmbeddingService::new()?, cache);
+            match config.provider.openrouter.clone() {
+                Some(openrouter_config) => {
+                    retriever = retriever.with_cluster_descriptions(OpenRouterClient::new(openrouter_config)?);
+                }
+                None => println!("   {} OpenRouter not configured, describing clusters by their tags", style("!").yellow()),
+            }
+
+            let groups = retriever.cluster_memories(&user, clusters).await?;
+            if groups.is_empty() {
+                println!("   No embedded memories for {}", user);
+                return Ok(());
+            }
+            println!("\n{} {} memory clusters for {}\n", style("🧩").bold(), groups.len(), style(&user).cyan());
+            let rows: Vec<Vec<String>> = groups
+                .iter()
+                .enumerate()
+                .map(|(i, cluster)| {
+                    let example = cluster
+                        .members
+                        .first()
+                        .map(|m| m.summary.as_deref().unwrap_or(&m.content))
+                        .unwrap_or_default();
+                    vec![
+                        (i + 1).to_string(),
+                        truncate_cell(&cluster.centroid_description, 40),
+                        cluster.members.len().to_string(),
+                        format!("{:.2}", cluster.cohesion),
+                        truncate_cell(example, 50),
+                    ]
+                })
+                .collect();
+            print_table(&["#", "Theme", "Memories", "Cohesion", "Example"], &rows);
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+/// Shorten `text` to one line of at most `max` characters
+fn truncate_cell(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(max.saturating_sub(3)) {
+        Some((end, _)) if line.chars().count() > max => format!("{}...", &line[..end]),
+        _ => line.to_string(),
+    }
+}
+
+/// Print `rows` as a bordered table under `headers`
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut table = Table::new();
+    table.set_titles(Row::new(headers.iter().map(|header| Cell::new(header)).collect()));
+    for row in rows {
+        table.add_row(Row::new(row.iter().map(|cell| Cell::new(cell)).collect()));
+    }
+    for line in table.to_string().lines() {
+        println!("   {}", line);
+    }
+}
+
+/// Migrate memories, tasks, conversations and settings between user IDs
+async fn manage_users(action: UsersAction) -> Result<()> {
+    let pool = get_db_pool()
+        .await
+        .ok_or_else(|| Error::Config("PostgreSQL not configured. User data requires a database.".into()))?;
+
+    match action {
+        UsersAction::Merge { from, to, dry_run } => {
+            if from == to {
+                return Err(Error::InvalidInput("--from and --to must be different users".into()));
+            }
+            if dry_run {
+                let counts = count_user_data(&pool, &from).await?;
+                println!(
+                    "   {} {} memories, {} tasks, {} conversations and {} settings would move from {} to {} (dry run, nothing changed)",
+                    style("ℹ").blue(),
+                    counts.memories,
+                    counts.tasks,
+                    counts.conversations,
+                    counts.config_params,
+                    from,
+                    to
+                );
+            } else {
+                let counts = merge_users(&pool, &from, &to).await?;
+                println!(
+                    "   {} Moved {} memories, {} tasks, {} conversations and {} settings from {} to {}",
+                    style("✓").green(),
+                    counts.memories,
+                    counts.tasks,
+                    counts.conversations,
+                    counts.config_params,
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Inspect the effective configuration
+fn manage_config(action: ConfigAction) -> Result<()> {
+    match action {
+        ConfigAction::Diff => {
+            let on_disk = read_config_snapshot(&config_path());
+            if let Some(issue) = on_disk.issues.first() {
+                return Err(Error::Config(format!("{}: {}", on_disk.path.display(), issue)));
+            }
+            let live = ConfigSnapshot::from_config(Config::from_env()?);
+
+            let changes = on_disk.redacted().diff(&live.redacted());
+            if changes.is_empty() {
+                println!("   {} Live config matches {}", style("✓").green(), on_disk.path.display());
+                return Ok(());
+            }
+
+            println!("   {} field(s) differ from {}:\n", changes.len(), on_disk.path.display());
+            for change in changes {
+                println!(
+                    "   {}  {} {} {}",
+                    style(&change.field_path).cyan(),
+                    style(&change.old_value).red(),
+                    style("→").dim(),
+                    style(&change.new_value).green()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Document the agent's tools
+fn manage_tools(action: ToolsAction) -> Result<()> {
+    match action {
+        ToolsAction::Explain { tool_name } => {
+            let config = Config::from_env()?;
+            let tools = local_tools(&config);
+            let Some(explanation) = tools.explain(&tool_name) else {
+                let mut names = tools.names();
+                names.sort();
+                return Err(Error::NotFound(format!(
+                    "Unknown tool '{}'. Tools that need no database or account: {}",
+                    tool_name,
+                    names.join(", ")
+                )));
+            };
+            println!("{}", explanation);
+        }
+    }
+
+    Ok(())
+}
+
+/// Built-in tools that work without a database or account, plus the search
+/// tools whose API keys are set
+fn local_tools(config: &Config) -> ToolRegistry {
+    let dir = config.sandbox.allowed_dir.clone();
+    let mut tools = ToolRegistry::new();
+    tools.register(ReadFileTool::new(dir.clone()));
+    tools.register(WriteFileTool::new(dir.clone()));
+    tools.register(DiffTool::new(dir.clone()));
+    let file_watcher = FileWatcherTool::new(dir.clone());
+    tools.register(file_watcher.poll_tool());
+    tools.register(file_watcher);
+    tools.register(GitTool::new(dir.clone()).with_write_enabled(config.sandbox.git_write_enabled));
+    tools.register(SystemCommandTool::with_config(dir, config.sandbox.agent_user.clone()));
+    tools.register(DuckDuckGoSearchTool::new());
+    tools.register(FetchPageTool::new());
+    if let Some(brave) = BraveSearchTool::from_env() {
+        tools.register(brave.image_search());
+        tools.register(brave);
+    }
+    if let Some(perplexity) = PerplexitySearchTool::from_env() {
+        tools.register(perplexity);
+    }
+    tools
+}
+
 /// Manage the agent's soul (personality configuration)
 async fn manage_soul(action: Option<SoulAction>) -> Result<()> {
     let action = action.unwrap_or_else(|| {
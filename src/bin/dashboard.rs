@@ -11,7 +11,7 @@ use clap::Parser;
 use openagent::config::Config;
 use openagent::database::{
     AgentStatusStore, ConfigParam, ConfigParamStore, Memory, MemoryStore, SoulSection, SoulStore,
-    TaskStore, AgentTask,
+    TaskStatus, TaskStore, AgentTask,
 };
 use openagent::database::{init_pool, migrations};
 use serde::{Deserialize, Serialize};
@@ -99,13 +99,13 @@ struct AgentStatusInfo {
 
 #[derive(Serialize)]
 struct TaskStats {
-    pending: i64,
-    processing: i64,
-    finish: i64,
-    fail: i64,
-    cancel: i64,
-    stop: i64,
-    total: i64,
+    pending: u64,
+    processing: u64,
+    finish: u64,
+    fail: u64,
+    cancel: u64,
+    stop: u64,
+    total: u64,
 }
 
 #[derive(Serialize)]
@@ -149,15 +149,15 @@ async fn get_status(State(state): State<DashboardState>) -> Result<Json<StatusRe
     let agent_row = state.status_store.get().await?;
 
     // Task counts
-    let task_counts = state.task_store.count_by_status().await?;
-    let get_count = |s: &str| -> i64 { *task_counts.get(s).unwrap_or(&0) };
+    let task_counts = state.task_store.count_by_status(None).await?;
+    let get_count = |s: TaskStatus| -> u64 { task_counts.get(&s).copied().unwrap_or(0) };
     let tasks = TaskStats {
-        pending: get_count("pending"),
-        processing: get_count("processing"),
-        finish: get_count("finish"),
-        fail: get_count("fail"),
-        cancel: get_count("cancel"),
-        stop: get_count("stop"),
+        pending: get_count(TaskStatus::Pending),
+        processing: get_count(TaskStatus::Processing),
+        finish: get_count(TaskStatus::Finish),
+        fail: get_count(TaskStatus::Fail),
+        cancel: get_count(TaskStatus::Cancel),
+        stop: get_count(TaskStatus::Stop),
         total: task_counts.values().sum(),
     };
 
@@ -207,7 +207,6 @@ async fn list_tasks(
     let limit = params.limit.unwrap_or(50).min(200);
     let offset = params.offset.unwrap_or(0);
     let status = params.status.and_then(|s| {
-        use openagent::database::TaskStatus;
         match s.as_str() {
             "pending" => Some(TaskStatus::Pending),
             "processing" => Some(TaskStatus::Processing),
@@ -225,8 +224,8 @@ async fn list_tasks(
 
 async fn task_stats(
     State(state): State<DashboardState>,
-) -> Result<Json<HashMap<String, i64>>, AppError> {
-    let counts = state.task_store.count_by_status().await?;
+) -> Result<Json<HashMap<TaskStatus, u64>>, AppError> {
+    let counts = state.task_store.count_by_status(None).await?;
     Ok(Json(counts))
 }
 
@@ -4,32 +4,54 @@
 //! Implements OpenClaw-style session sandboxing and DM pairing.
 
 use openagent::agent::{
-    ConversationManager, LoopConfig, Message as AgentMessage, OpenRouterClient,
-    ToolRegistry, ReadFileTool, WriteFileTool, SystemCommandTool,
-    DuckDuckGoSearchTool, BraveSearchTool, PerplexitySearchTool,
+    ConversationManager, ImageInput, LoopConfig, Message as AgentMessage, OpenRouterClient,
+    SentryLoopCallback, SessionNamespace,
+    ToolRegistry, ReadFileTool, WriteFileTool, DiffTool, SystemCommandTool, FileWatcherTool, GitTool,
+    DuckDuckGoSearchTool, FetchPageTool, BraveSearchTool, PerplexitySearchTool,
     MemorySaveTool, MemorySearchTool, MemoryListTool, MemoryDeleteTool,
-    TaskCreateTool, TaskListTool, TaskUpdateTool,
-    prompts::{DEFAULT_SYSTEM_PROMPT, Soul},
-    agentic_loop::{self, AgentLoopInput, LoopCallback, ToolObservation},
+    TaskCreateTool, TaskListTool, TaskUpdateTool, CalendarTool, NotionTool,
+    Tool, ToolRegistrySnapshot, PrometheusMetricsRecorder, Permission, PermissionSet,
+    prompts::{parse_tool_preferences, DEFAULT_SYSTEM_PROMPT, SYSTEM_PROMPT_TEMPLATE, PromptTemplate, Soul},
+    agentic_loop::{self, AgentLoopInput, LoopCallback, NoOpCallback},
 };
-use openagent::config::Config;
-use openagent::config::DmPolicy;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use openagent::channels::{SlackChannel, TelegramChannel};
+use openagent::config::{Config, ConfigSnapshot, SlackConfig};
+use openagent::core::channel::{Channel, ChannelMessage, ChannelReply, MessageHandler};
+use openagent::config::{AuthMode, DmPolicy};
 use openagent::database::{
-    init_pool, migrations, Memory, MemoryType,
-    AgentStatusStore, ConfigParamStore, ConfigValueType, SoulStore, TaskStore,
+    init_pool, migrations, Memory, MemoryType, PostgresPool,
+    AgentStatusStore, ApprovedUserStore, ConfigParamStore, ConfigValueType, ConversationStore, LoopTraceStore, SoulStore,
+    TaskStore,
+};
+use openagent::gateway::protocol::schema::{ProtocolError, RequestFrame, ResponseFrame};
+use openagent::gateway::{
+    error_codes, ActiveTurns, AgentContinueRequest, AgentResponse, AgentSendRequest, ContinuationStore,
+    GatewayAuth, GatewayFrame, GatewayRateLimiter, GatewayServer, HeartbeatMonitor, SessionInfo,
+    SessionsListResponse,
+};
+use openagent::mcp::{McpClient, McpToolBridge};
+use openagent::plugin_sdk::PluginRegistry;
+use openagent::tools::RateLimiter;
+use openagent::memory::{
+    is_how_to_query, ConversationSummarizer, EmbeddingService, MemoryCache, MemoryRetriever,
 };
-use openagent::memory::{ConversationSummarizer, EmbeddingService, MemoryCache, MemoryRetriever};
 use openagent::sandbox::{create_executor, CodeExecutor, ExecutionRequest, Language};
 use openagent::scheduler::Scheduler;
+use openagent::skills::{DeploySkill, InstallPackageSkill, SkillContext, SkillRegistry, SkillResult};
 use openagent::{Error, Result};
 
 use secrecy::ExposeSecret;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use teloxide::prelude::*;
 use teloxide::types::{ChatKind, ParseMode};
 use teloxide::utils::command::BotCommands;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, Mutex, RwLock};
 use tracing::{debug, error, info, warn};
 
 /// Bot commands
@@ -51,14 +73,26 @@ enum Command {
     Run(String),
     #[command(description = "Show status")]
     Status,
-    #[command(description = "Approve a user (admin only, e.g., /approve 123456789)")]
+    #[command(description = "Approve a pending skill step, or a user (admin only, e.g., /approve 123456789)")]
     Approve(String),
+    #[command(description = "Reject a pending skill step")]
+    Reject,
     #[command(description = "List pending pairing requests (admin only)")]
     Pending,
     #[command(description = "Create or list tasks (e.g., /task Buy groceries)")]
     Task(String),
+    #[command(description = "Run a skill (e.g., /skill install_package {\"package\": \"jq\"})")]
+    Skill(String),
 }
 
+/// How long a skill waits for /approve or /reject before treating the step
+/// as rejected
+const SKILL_APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Longest a chat shows "typing…" for one agent turn; it stops earlier once
+/// the turn ends
+const TYPING_INDICATOR_SECS: u64 = 300;
+
 /// Session type for sandboxing decisions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SessionType {
@@ -68,10 +102,16 @@ enum SessionType {
     Group,
 }
 
+/// Channel name under which Telegram approvals are stored
+const PAIRING_CHANNEL: &str = "telegram";
+
+/// Channel of sessions opened by gateway WebSocket clients
+const WEBSOCKET_CHANNEL: &str = "gateway";
+
 /// Pairing state for DM users
 #[derive(Debug, Clone)]
 struct PairingManager {
-    /// Approved user IDs
+    /// Approved user IDs (mirrors `approved_users` when a DB is configured)
     approved_users: HashSet<i64>,
     /// Pending pairing requests: user_id -> pairing_code
     pending_requests: std::collections::HashMap<i64, String>,
@@ -124,19 +164,51 @@ impl PairingManager {
             .map(|(id, code)| (*id, code.clone()))
             .collect()
     }
+
+    /// Add the users approved before the last restart
+    async fn load_from_db(mut self, pool: &PostgresPool) -> Result<Self> {
+        let store = ApprovedUserStore::new(pool.clone());
+        for user in store.list(PAIRING_CHANNEL).await? {
+            self.approved_users.insert(user.user_id);
+        }
+        Ok(self)
+    }
+
+    /// Record an approval in `approved_users` so it survives restarts
+    async fn persist_approval(pool: &PostgresPool, user_id: i64, approved_by: i64) -> Result<()> {
+        ApprovedUserStore::new(pool.clone())
+            .approve(PAIRING_CHANNEL, user_id, Some(approved_by))
+            .await?;
+        Ok(())
+    }
 }
 
 /// Application state shared across handlers
 struct AppState {
-    config: Config,
-    llm_client: OpenRouterClient,
+    /// Current configuration (replaced on SIGHUP; shared with the soul routes)
+    config: Arc<RwLock<Config>>,
+    /// LLM client (replaced on SIGHUP when the OpenRouter API key changes)
+    llm_client: RwLock<OpenRouterClient>,
     conversations: Arc<RwLock<ConversationManager>>,
+    /// Persists conversations across restarts (requires DB)
+    conversation_store: Option<ConversationStore>,
+    /// Keeps agentic loop traces for 24h so they can be replayed (requires DB)
+    trace_store: Option<LoopTraceStore>,
     memory_retriever: Option<MemoryRetriever>,
     executor: Box<dyn CodeExecutor>,
-    /// Tools for DM sessions (full access, Arc-shared with scheduler)
-    dm_tools: Arc<ToolRegistry>,
-    /// Tools for group sessions (sandboxed)
-    group_tools: ToolRegistry,
+    /// PostgreSQL pool, if configured (reported by `GET /health/db`)
+    pg_pool: Option<PostgresPool>,
+    /// Tools for DM sessions (full access, swapped by `POST /tools/reload`)
+    dm_tools: RwLock<Arc<ToolRegistry>>,
+    /// Tools for group sessions (sandboxed, rebuilt by `POST /tools/reload`
+    /// and on SIGHUP when `sandbox.allowed_dir` changes)
+    group_tools: RwLock<Arc<ToolRegistry>>,
+    /// Capabilities granted to DM sessions (checked on every tool call)
+    dm_permissions: PermissionSet,
+    /// Capabilities granted to group sessions
+    group_permissions: PermissionSet,
+    /// Per-tool call metrics for both registries (served at `GET /metrics`)
+    tool_metrics: Arc<PrometheusMetricsRecorder>,
     /// Pairing manager for DM approval
     pairing: RwLock<PairingManager>,
     /// Soul store for persistent agent identity
@@ -147,6 +219,17 @@ struct AppState {
     status_store: Option<AgentStatusStore>,
     /// Config parameter store for runtime settings
     config_param_store: Option<ConfigParamStore>,
+    /// Skills runnable with /skill in DMs
+    skills: SkillRegistry,
+    /// Skill steps waiting for /approve or /reject, by chat
+    skill_approvals: Mutex<HashMap<ChatId, oneshot::Sender<bool>>>,
+    /// System prompt template (`workspace/templates/system.hbs` or built-in)
+    system_template: PromptTemplate,
+    /// Rendered soul inserted into every system prompt (empty without DB);
+    /// refreshed on `NOTIFY soul_updated`
+    soul_prompt: RwLock<String>,
+    /// Agent turns in progress, awaited on SIGTERM
+    turns: ActiveTurns,
 }
 
 impl AppState {
@@ -155,8 +238,8 @@ impl AppState {
         let openrouter_config = config.provider.openrouter.clone()
             .ok_or_else(|| Error::Config("OpenRouter not configured. Set OPENROUTER_API_KEY environment variable.".into()))?;
 
-        // Initialize OpenRouter client
-        let llm_client = OpenRouterClient::new(openrouter_config.clone())?;
+        // Initialize OpenRouter client (with the failover model chain, if any)
+        let llm_client = OpenRouterClient::from_provider_config(&config.provider)?;
 
         // Try to initialize database pool (shared across all stores)
         let pg_pool = match &config.storage.postgres {
@@ -185,9 +268,13 @@ impl AppState {
                 let store = openagent::database::MemoryStore::new(pool.clone());
                 match EmbeddingService::new() {
                     Ok(embedding) => {
-                        let cache = MemoryCache::new();
+                        let cache = MemoryCache::new(config.storage.memory.embedding.cache_policy);
                         info!("Memory retriever initialized (embedding + cache + PG)");
-                        Some(MemoryRetriever::new(store, embedding, cache))
+                        Some(
+                            MemoryRetriever::new(store, embedding, cache)
+                                .with_auto_tagging(llm_client.clone())
+                                .with_consolidation(llm_client.clone()),
+                        )
                     }
                     Err(e) => {
                         warn!("Embedding service failed: {}. Running without memory retrieval.", e);
@@ -251,129 +338,441 @@ impl AppState {
             None => (None, None, None, None),
         };
 
-        // Build system prompt: use soul from DB if available, otherwise default
-        let system_prompt = if let Some(ref ss) = soul_store {
-            match ss.render_full_soul().await {
-                Ok(soul_content) => format!(
-                    "{}\n\n---\n\n## Agent Soul\n\n{}",
-                    DEFAULT_SYSTEM_PROMPT, soul_content
-                ),
-                Err(_) => DEFAULT_SYSTEM_PROMPT.to_string(),
-            }
-        } else {
-            DEFAULT_SYSTEM_PROMPT.to_string()
+        // Build system prompt: use soul from DB if available, otherwise default.
+        // Operators can customize the layout with workspace/templates/system.hbs.
+        let soul_prompt = match soul_store {
+            Some(ref ss) => ss.render_full_soul().await.unwrap_or_default(),
+            None => String::new(),
         };
+        let system_template = PromptTemplate::load("system", &config.agent.workspace, SYSTEM_PROMPT_TEMPLATE)?;
+        let system_prompt = render_system_prompt(&system_template, &soul_prompt, &HashMap::new());
 
-        // Initialize conversation manager with DB-backed soul prompt
-        let conversations = ConversationManager::new(&openrouter_config.default_model)
+        // Initialize conversation manager with DB-backed soul prompt, restoring
+        // conversations from before the last restart
+        let conversation_store = pg_pool.clone().map(ConversationStore::new);
+        let mut conversations = ConversationManager::new(&openrouter_config.default_model)
             .with_system_prompt(&system_prompt);
+        if let Some(ref store) = conversation_store {
+            conversations = match conversations.load_from_db(store).await {
+                Ok(restored) => {
+                    info!("Restored {} conversations from database", restored.conversation_count());
+                    restored
+                }
+                Err(e) => {
+                    warn!("Failed to restore conversations: {}", e);
+                    ConversationManager::new(&openrouter_config.default_model)
+                        .with_system_prompt(&system_prompt)
+                }
+            };
+        }
 
         // Initialize code executor
         let executor = create_executor(&config.sandbox).await?;
 
-        // Initialize DM tools (full access for trusted users)
-        let mut dm_tools = ToolRegistry::new();
-        dm_tools.register(ReadFileTool::new(config.sandbox.allowed_dir.clone()));
-        dm_tools.register(WriteFileTool::new(config.sandbox.allowed_dir.clone()));
-        dm_tools.register(SystemCommandTool::with_config_and_env(
-            config.sandbox.allowed_dir.clone(),
-            config.sandbox.agent_user.clone(),
-            &config.sandbox.execution_env.to_string(),
-        ));
-        dm_tools.register(DuckDuckGoSearchTool::new());
-        if let Some(brave) = BraveSearchTool::from_env() {
-            info!("Brave Search enabled for DM sessions");
-            dm_tools.register(brave);
-        }
-        if let Some(perplexity) = PerplexitySearchTool::from_env() {
-            info!("Perplexity Search enabled for DM sessions");
-            dm_tools.register(perplexity);
-        }
-
-        // Register memory tools if memory retriever is available (DM)
-        if let Some(ref retriever) = memory_retriever {
-            dm_tools.register(MemorySaveTool::new(retriever.clone()));
-            dm_tools.register(MemorySearchTool::new(retriever.clone()));
-            dm_tools.register(MemoryListTool::new(retriever.clone()));
-            dm_tools.register(MemoryDeleteTool::new(retriever.clone()));
-            info!("Memory tools registered for DM sessions");
-        }
-
-        // Register task tools if task store is available (DM)
-        if let Some(ref ts) = task_store {
-            dm_tools.register(TaskCreateTool::new(ts.clone()));
-            dm_tools.register(TaskListTool::new(ts.clone()));
-            dm_tools.register(TaskUpdateTool::new(ts.clone()));
-            info!("Task tools registered for DM sessions");
-        }
-
-        // Initialize group tools (sandboxed - restricted commands)
-        let mut group_tools = ToolRegistry::new();
-        group_tools.register(ReadFileTool::new(config.sandbox.allowed_dir.clone()));
-        let group_system_cmd = SystemCommandTool::with_working_dir(config.sandbox.allowed_dir.clone())
-            .with_allowed_commands(vec![
-                "ls".to_string(),
-                "cat".to_string(),
-                "head".to_string(),
-                "tail".to_string(),
-                "wc".to_string(),
-                "grep".to_string(),
-                "find".to_string(),
-                "echo".to_string(),
-                "pwd".to_string(),
-                "whoami".to_string(),
-                "date".to_string(),
-                "uname".to_string(),
-            ]);
-        group_tools.register(group_system_cmd);
-        group_tools.register(DuckDuckGoSearchTool::new());
-
-        // Register read-only memory tools for group sessions
-        if let Some(ref retriever) = memory_retriever {
-            group_tools.register(MemorySearchTool::new(retriever.clone()));
-            group_tools.register(MemoryListTool::new(retriever.clone()));
-            info!("Memory tools (read-only) registered for group sessions");
-        }
-
-        // Register read-only task tools for group sessions
-        if let Some(ref ts) = task_store {
-            group_tools.register(TaskListTool::new(ts.clone()));
-            info!("Task tools (read-only) registered for group sessions");
-        }
+        // Initialize DM tools (full access) and group tools (sandboxed)
+        let mut dm_tools = build_dm_tools(&config, &memory_retriever, &task_store).await;
+        let mut group_tools = build_group_tools(&config, &memory_retriever, &task_store);
 
         // Initialize pairing manager with admin users from config
         let admin_users = config.channels.telegram
             .as_ref()
             .map(|t| t.allow_from.clone())
             .unwrap_or_default();
-        let pairing = PairingManager::new(admin_users);
+        let mut pairing = PairingManager::new(admin_users);
+        if let Some(ref pool) = pg_pool {
+            pairing = match pairing.clone().load_from_db(pool).await {
+                Ok(restored) => {
+                    info!("Restored {} approved users from database", restored.approved_users.len());
+                    restored
+                }
+                Err(e) => {
+                    warn!("Failed to load approved users: {}", e);
+                    pairing
+                }
+            };
+        }
+
+        // Groups may read files and run allowlisted commands, but never use
+        // credentialed third-party APIs
+        let dm_permissions = PermissionSet::all();
+        let group_permissions = PermissionSet::all().without(Permission::ExternalApi);
+        dm_tools.set_permissions(dm_permissions.clone());
+        group_tools.set_permissions(group_permissions.clone());
+
+        let tool_metrics = Arc::new(PrometheusMetricsRecorder::new());
+        dm_tools.record_metrics(tool_metrics.clone());
+        group_tools.record_metrics(tool_metrics.clone());
 
         info!("DM tools: {} available", dm_tools.count());
         info!("Group tools: {} available (sandboxed)", group_tools.count());
 
+        let mut skills = SkillRegistry::new();
+        skills.register(InstallPackageSkill::new());
+        if let Some(ref ts) = task_store {
+            skills.register(DeploySkill::new(ts.clone()));
+        }
+
         Ok(AppState {
-            config,
-            llm_client,
+            config: Arc::new(RwLock::new(config)),
+            llm_client: RwLock::new(llm_client),
             conversations: Arc::new(RwLock::new(conversations)),
+            conversation_store,
+            trace_store: pg_pool.clone().map(LoopTraceStore::new),
             memory_retriever,
             executor,
-            dm_tools: Arc::new(dm_tools),
-            group_tools,
+            pg_pool,
+            dm_tools: RwLock::new(Arc::new(dm_tools)),
+            group_tools: RwLock::new(Arc::new(group_tools)),
+            dm_permissions,
+            group_permissions,
+            tool_metrics,
             pairing: RwLock::new(pairing),
             soul_store,
             task_store,
             status_store,
             config_param_store,
+            skills,
+            skill_approvals: Mutex::new(HashMap::new()),
+            system_template,
+            soul_prompt: RwLock::new(soul_prompt),
+            turns: ActiveTurns::new(),
         })
     }
 
+    /// Re-render the soul from the database after a section changed
+    ///
+    /// Active conversations lose their system prompt and get a fresh one on
+    /// their next message.
+    async fn reload_soul(&self) -> Result<()> {
+        let Some(ref soul_store) = self.soul_store else {
+            return Ok(());
+        };
+        let soul = soul_store.render_full_soul().await?;
+        let default_prompt = render_system_prompt(&self.system_template, &soul, &HashMap::new());
+        *self.soul_prompt.write().await = soul;
+
+        let mut conversations = self.conversations.write().await;
+        conversations.set_system_prompt(default_prompt);
+        conversations.reset_system_prompts();
+        Ok(())
+    }
+
+    /// Apply a configuration reloaded on SIGHUP
+    ///
+    /// Every changed field is logged. A new OpenRouter API key or any other
+    /// `provider` change (such as the failover chain) replaces the LLM
+    /// client, a new `sandbox.allowed_dir` rebuilds both tool registries,
+    /// and the soul is re-read from the database. In-flight turns and
+    /// long-lived tasks (scheduler, auto-tagging) keep the client and tools
+    /// they started with; settings only read at startup (ports, storage,
+    /// channels) still need a restart.
+    async fn reload_config(&self, new_config: Config) -> Result<()> {
+        let openrouter_config = new_config.provider.openrouter.clone()
+            .ok_or_else(|| Error::Config("OpenRouter not configured. Set OPENROUTER_API_KEY environment variable.".into()))?;
+
+        // Compare under a read lock and build everything new before taking
+        // the write lock, so turns reading the config are not blocked while
+        // tools are rebuilt
+        let (changes, api_key_changed, allowed_dir_changed) = {
+            let config = self.config.read().await;
+            let changes = ConfigSnapshot::from_config(config.to_redacted())
+                .diff(&ConfigSnapshot::from_config(new_config.to_redacted()));
+            // API keys are not serialized, so they never show up in the diff
+            let api_key_changed = config
+                .provider
+                .openrouter
+                .as_ref()
+                .map(|c| c.api_key.expose_secret() != openrouter_config.api_key.expose_secret())
+                .unwrap_or(true);
+            (changes, api_key_changed, config.sandbox.allowed_dir != new_config.sandbox.allowed_dir)
+        };
+        for change in &changes {
+            info!("Config {} changed: {} -> {}", change.field_path, change.old_value, change.new_value);
+        }
+
+        let provider_changed = changes.iter().any(|c| c.field_path.starts_with("/provider/"));
+        if api_key_changed || provider_changed {
+            *self.llm_client.write().await = OpenRouterClient::from_provider_config(&new_config.provider)?;
+            info!("Provider config changed, LLM client reinitialized");
+        } else if changes.is_empty() {
+            info!("Configuration unchanged");
+        }
+
+        if allowed_dir_changed {
+            let mut dm_tools = build_dm_tools(&new_config, &self.memory_retriever, &self.task_store).await;
+            dm_tools.set_permissions(self.dm_permissions.clone());
+            dm_tools.record_metrics(self.tool_metrics.clone());
+            let mut group_tools = build_group_tools(&new_config, &self.memory_retriever, &self.task_store);
+            group_tools.set_permissions(self.group_permissions.clone());
+            group_tools.record_metrics(self.tool_metrics.clone());
+
+            info!(
+                "Tools re-registered for {}: {} DM, {} group",
+                new_config.sandbox.allowed_dir.display(),
+                dm_tools.count(),
+                group_tools.count()
+            );
+            *self.dm_tools.write().await = Arc::new(dm_tools);
+            *self.group_tools.write().await = Arc::new(group_tools);
+        }
+
+        *self.config.write().await = new_config;
+        self.reload_soul().await
+    }
+
+    /// Per-user template variables: Telegram profile, then `user:<id>` config params
+    async fn user_prompt_vars(&self, user_id: &str, user: Option<&teloxide::types::User>) -> HashMap<&'static str, String> {
+        let mut vars = HashMap::new();
+        if let Some(user) = user {
+            let name = user.username.clone().unwrap_or_else(|| user.full_name());
+            vars.insert("username", name);
+            if let Some(ref lang) = user.language_code {
+                vars.insert("language", lang.clone());
+            }
+        }
+
+        if let Some(ref store) = self.config_param_store {
+            let category = openagent::database::user_config_category(user_id);
+            for &key in openagent::database::USER_CONFIG_KEYS {
+                if let Ok(Some(param)) = store.get(&category, key).await {
+                    vars.insert(key, param.value);
+                }
+            }
+        }
+        vars
+    }
+
     /// Get the appropriate tool registry based on session type
-    fn tools_for_session(&self, session_type: SessionType) -> &ToolRegistry {
+    async fn tools_for_session(&self, session_type: SessionType) -> Arc<ToolRegistry> {
+        match session_type {
+            SessionType::DirectMessage => self.dm_tools.read().await.clone(),
+            SessionType::Group => self.group_tools.read().await.clone(),
+        }
+    }
+
+    /// Capabilities granted to tools in this session type
+    fn permissions_for_session(&self, session_type: SessionType) -> &PermissionSet {
         match session_type {
-            SessionType::DirectMessage => &self.dm_tools,
-            SessionType::Group => &self.group_tools,
+            SessionType::DirectMessage => &self.dm_permissions,
+            SessionType::Group => &self.group_permissions,
+        }
+    }
+}
+
+/// Tools for DM sessions (full access for trusted users)
+async fn build_dm_tools(
+    config: &Config,
+    memory_retriever: &Option<MemoryRetriever>,
+    task_store: &Option<TaskStore>,
+) -> ToolRegistry {
+    let mut dm_tools = ToolRegistry::new().with_rate_limiter(RateLimiter::from_config(&config.agent.tool_concurrency));
+    dm_tools.register(ReadFileTool::new(config.sandbox.allowed_dir.clone()));
+    dm_tools.register(WriteFileTool::new(config.sandbox.allowed_dir.clone()));
+    dm_tools.register(DiffTool::new(config.sandbox.allowed_dir.clone()));
+    let file_watcher = FileWatcherTool::new(config.sandbox.allowed_dir.clone());
+    dm_tools.register(file_watcher.poll_tool());
+    dm_tools.register(file_watcher);
+    let passthrough_env: Vec<&str> =
+        config.sandbox.passthrough_env_vars.iter().map(String::as_str).collect();
+    dm_tools.register(
+        SystemCommandTool::with_config_and_env(
+            config.sandbox.allowed_dir.clone(),
+            config.sandbox.agent_user.clone(),
+            &config.sandbox.execution_env.to_string(),
+        )
+        .with_env_passthrough(&passthrough_env),
+    );
+    dm_tools.register(
+        GitTool::new(config.sandbox.allowed_dir.clone())
+            .with_write_enabled(config.sandbox.git_write_enabled)
+            .with_env_passthrough(&passthrough_env),
+    );
+    dm_tools.register(DuckDuckGoSearchTool::new());
+    dm_tools.register(FetchPageTool::new());
+    if let Some(brave) = BraveSearchTool::from_env() {
+        info!("Brave Search enabled for DM sessions");
+        dm_tools.register(brave.image_search());
+        dm_tools.register(brave);
+    }
+    if let Some(perplexity) = PerplexitySearchTool::from_env() {
+        info!("Perplexity Search enabled for DM sessions");
+        dm_tools.register(perplexity);
+    }
+
+    // Register memory tools if memory retriever is available (DM)
+    if let Some(retriever) = memory_retriever {
+        dm_tools.register(MemorySaveTool::new(retriever.clone()));
+        dm_tools.register(MemorySearchTool::new(retriever.clone()));
+        dm_tools.register(MemoryListTool::new(retriever.clone()));
+        dm_tools.register(MemoryDeleteTool::new(retriever.clone()));
+        info!("Memory tools registered for DM sessions");
+    }
+
+    // Register task tools if task store is available (DM)
+    if let Some(ts) = task_store {
+        dm_tools.register(TaskCreateTool::new(ts.clone()));
+        dm_tools.register(TaskListTool::new(ts.clone()));
+        dm_tools.register(TaskUpdateTool::new(ts.clone()));
+        info!("Task tools registered for DM sessions");
+    }
+
+    // Register calendar/Notion tools if configured (DM only)
+    for name in PLUGIN_TOOLS {
+        if let Some(tool) = build_plugin_tool(name, config) {
+            info!("{} tool enabled for DM sessions", name);
+            dm_tools.register_boxed(tool);
+        }
+    }
+
+    // Register tools from configured MCP servers (DM only). Servers are
+    // connected concurrently; ones that fail or time out are skipped.
+    let connections = futures::future::join_all(
+        config
+            .mcp_servers
+            .iter()
+            .filter(|(_, s)| s.enabled)
+            .map(|(name, server)| async move { (name, McpClient::connect(name, server).await) }),
+    )
+    .await;
+    for (name, connected) in connections {
+        let registered = match connected {
+            Ok(client) => McpToolBridge::discover_and_register(&Arc::new(client), &mut dm_tools).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = registered {
+            warn!("MCP server '{}' unavailable: {}", name, e);
+        }
+    }
+
+    dm_tools
+}
+
+/// Tools for group sessions (sandboxed - restricted commands)
+fn build_group_tools(
+    config: &Config,
+    memory_retriever: &Option<MemoryRetriever>,
+    task_store: &Option<TaskStore>,
+) -> ToolRegistry {
+    let mut group_tools =
+        ToolRegistry::new().with_rate_limiter(RateLimiter::from_config(&config.agent.tool_concurrency));
+    group_tools.register(ReadFileTool::new(config.sandbox.allowed_dir.clone()));
+    group_tools.register(DiffTool::new(config.sandbox.allowed_dir.clone()));
+    let group_system_cmd = SystemCommandTool::with_working_dir(config.sandbox.allowed_dir.clone())
+        .with_allowed_commands(vec![
+            "ls".to_string(),
+            "cat".to_string(),
+            "head".to_string(),
+            "tail".to_string(),
+            "wc".to_string(),
+            "grep".to_string(),
+            "find".to_string(),
+            "echo".to_string(),
+            "pwd".to_string(),
+            "whoami".to_string(),
+            "date".to_string(),
+            "uname".to_string(),
+        ]);
+    group_tools.register(group_system_cmd);
+    group_tools.register(DuckDuckGoSearchTool::new());
+    group_tools.register(FetchPageTool::new());
+
+    // Register read-only memory tools for group sessions
+    if let Some(retriever) = memory_retriever {
+        group_tools.register(MemorySearchTool::new(retriever.clone()));
+        group_tools.register(MemoryListTool::new(retriever.clone()));
+        info!("Memory tools (read-only) registered for group sessions");
+    }
+
+    // Register read-only task tools for group sessions
+    if let Some(ts) = task_store {
+        group_tools.register(TaskListTool::new(ts.clone()));
+        info!("Task tools (read-only) registered for group sessions");
+    }
+
+    group_tools
+}
+
+/// Render the system prompt, falling back to the untemplated default on error
+fn render_system_prompt(template: &PromptTemplate, soul: &str, user_vars: &HashMap<&str, String>) -> String {
+    let mut vars: HashMap<&str, &str> = user_vars.iter().map(|(k, v)| (*k, v.as_str())).collect();
+    vars.insert("base_prompt", DEFAULT_SYSTEM_PROMPT);
+    vars.insert("soul", soul);
+    template.render(&vars).unwrap_or_else(|e| {
+        warn!("System prompt template failed, using default: {}", e);
+        DEFAULT_SYSTEM_PROMPT.to_string()
+    })
+}
+
+/// DM tools built from `channels.calendar` / `plugins.*`, rebuilt on reload
+const PLUGIN_TOOLS: &[&str] = &["calendar", "notion"];
+
+/// Build a plugin-configured tool by name, if `config` enables it
+fn build_plugin_tool(name: &str, config: &Config) -> Option<Box<dyn Tool>> {
+    match name {
+        "calendar" => config
+            .channels
+            .calendar
+            .clone()
+            .map(|c| Box::new(CalendarTool::new(c)) as Box<dyn Tool>),
+        "notion" => NotionTool::from_plugins(&config.plugins).map(|t| Box::new(t) as Box<dyn Tool>),
+        _ => None,
+    }
+}
+
+/// `POST /tools/reload` — rebuild plugin tools from the current config
+///
+/// Other DM tools are carried over unchanged; the group registry is rebuilt
+/// from the same config so its rate limits stay in step. In-flight turns
+/// keep the registry they started with; the scheduler keeps its startup
+/// tool set.
+/// Requires a bearer token from `gateway.auth.tokens`, like soul editing.
+async fn reload_tools(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> std::result::Result<Json<ToolRegistrySnapshot>, (StatusCode, String)> {
+    let auth = state.config.read().await.gateway.auth.clone();
+    openagent::gateway::http::authorize(&headers, &auth)?;
+
+    let config = Config::from_env_and_vault()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    let mut dm_tools = state.dm_tools.write().await;
+    let current = dm_tools.clone();
+    let mut reloaded = ToolRegistry::restore(&current.snapshot(), |name| {
+        if PLUGIN_TOOLS.contains(&name) {
+            build_plugin_tool(name, &config)
+        } else {
+            current.shared(name).map(|t| Box::new(t) as Box<dyn Tool>)
+        }
+    })
+    .with_rate_limiter(RateLimiter::from_config(&config.agent.tool_concurrency));
+
+    // Plugins configured since the last load
+    for name in PLUGIN_TOOLS {
+        if reloaded.get(name).is_none() {
+            if let Some(tool) = build_plugin_tool(name, &config) {
+                reloaded.register_boxed(tool);
+            }
         }
     }
+
+    reloaded.set_permissions(state.dm_permissions.clone());
+    reloaded.record_metrics(state.tool_metrics.clone());
+
+    let mut group_tools = build_group_tools(&config, &state.memory_retriever, &state.task_store);
+    group_tools.set_permissions(state.group_permissions.clone());
+    group_tools.record_metrics(state.tool_metrics.clone());
+
+    let snapshot = reloaded.snapshot();
+    info!(
+        "Reloaded DM tools: {}; {} group tools",
+        snapshot.names().join(", "),
+        group_tools.count()
+    );
+    *dm_tools = Arc::new(reloaded);
+    *state.group_tools.write().await = Arc::new(group_tools);
+    Ok(Json(snapshot))
 }
 
 /// Determine session type from chat
@@ -386,19 +785,20 @@ fn get_session_type(chat: &teloxide::types::Chat) -> SessionType {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("openagent=debug".parse().unwrap())
-                .add_directive("teloxide=info".parse().unwrap()),
-        )
-        .init();
+    // Load configuration (needed for telemetry before logging starts)
+    let config = Config::from_env_and_vault().await?;
 
-    info!("Starting OpenAgent Gateway v{}", openagent::VERSION);
+    // Initialize logging, with OpenTelemetry export if configured
+    let _telemetry = openagent::telemetry::init_tracing(
+        &config.gateway.telemetry,
+        tracing_subscriber::EnvFilter::from_default_env()
+            .add_directive("openagent=debug".parse().unwrap())
+            .add_directive("teloxide=info".parse().unwrap()),
+    )?;
 
-    // Load configuration
-    let config = Config::from_env()?;
+    info!("Starting OpenAgent Gateway v{}", openagent::VERSION);
+    info!("Configuration: {:?}", config.to_redacted());
+    let _error_reporting = openagent::agent::init_error_reporting();
 
     // Get telegram config (optional)
     let telegram_config = match config.channels.telegram.as_ref() {
@@ -435,10 +835,10 @@ async fn main() -> Result<()> {
             status_store.clone(),
             soul_store.clone(),
             config_param_store.clone(),
-            state.llm_client.clone(),
+            state.llm_client.read().await.clone(),
             state.memory_retriever.clone(),
             state.conversations.clone(),
-            state.dm_tools.clone(),
+            state.dm_tools.read().await.clone(),
         ));
         tokio::spawn(async move {
             scheduler.run().await;
@@ -446,6 +846,149 @@ async fn main() -> Result<()> {
         info!("Periodic scheduler spawned");
     }
 
+    // Reload the configuration on SIGHUP (`kill -HUP <pid>`)
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::hangup()) {
+            Ok(mut hangup) => {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    while hangup.recv().await.is_some() {
+                        info!("SIGHUP received, reloading configuration");
+                        let reloaded = match Config::from_env_and_vault().await {
+                            Ok(config) => state.reload_config(config).await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = reloaded {
+                            warn!("Failed to reload configuration: {}", e);
+                        }
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to install SIGHUP handler: {}", e),
+        }
+    }
+
+    // Shared by the HTTP endpoints and WebSocket clients, so a client is
+    // limited the same over either
+    let limiter = GatewayRateLimiter::from_config(&config.gateway.rate_limit);
+
+    // Gateway protocol clients (`gateway.websocket_port`), authenticated
+    // per `gateway.auth`
+    let ws_server = if config.gateway.websocket {
+        match GatewayServer::from_config(&config.gateway).await {
+            Ok(server) => {
+                let server = Arc::new(server.with_turns(state.turns.clone()));
+                tokio::spawn(serve_websocket(server.clone(), state.clone(), limiter.clone()));
+                Some(server)
+            }
+            Err(e) => {
+                warn!("Gateway WebSocket unavailable: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // On SIGTERM, tell WebSocket clients and let running agent turns finish
+    // (up to `gateway.shutdown_grace_secs`) before exiting
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                let turns = state.turns.clone();
+                let ws_server = ws_server.clone();
+                let grace = std::time::Duration::from_secs(config.gateway.shutdown_grace_secs);
+                tokio::spawn(async move {
+                    terminate.recv().await;
+                    info!("SIGTERM received, waiting for {} running agent turns", turns.count());
+                    match ws_server {
+                        // Sends `shuttingDown`, waits for the turns, then closes the connections
+                        Some(server) => server.shutdown(grace).await,
+                        None => {
+                            if tokio::time::timeout(grace, turns.drained()).await.is_err() {
+                                warn!("{} agent turns still running after {:?}, exiting", turns.count(), grace);
+                            }
+                        }
+                    }
+                    info!("Gateway shutdown complete");
+                    std::process::exit(0);
+                });
+            }
+            Err(e) => warn!("Failed to install SIGTERM handler: {}", e),
+        }
+    }
+
+    // Reload the soul whenever a section is edited (gateway API, CLI, scheduler)
+    if let Some(ref soul_store) = state.soul_store {
+        match soul_store.listen_updates().await {
+            Ok(mut listener) => {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    loop {
+                        match listener.recv().await {
+                            Ok(notification) => {
+                                info!("Soul section '{}' changed, reloading soul", notification.payload());
+                                if let Err(e) = state.reload_soul().await {
+                                    warn!("Failed to reload soul: {}", e);
+                                }
+                            }
+                            Err(e) => {
+                                // PgListener reconnects on the next recv()
+                                warn!("Soul update listener error: {}", e);
+                                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                            }
+                        }
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to listen for soul updates: {}", e),
+        }
+    }
+
+    // Periodically prune idle conversations so long-running gateways don't grow
+    // unbounded; persisted ones are re-saved and trimmed to the last 7 days,
+    // loop traces to the last 24 hours
+    {
+        let conversations = state.conversations.clone();
+        let conversation_store = state.conversation_store.clone();
+        let trace_store = state.trace_store.clone();
+        let ttl = std::time::Duration::from_secs(config.gateway.inactive_conversation_ttl_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+            interval.tick().await; // first tick fires immediately
+            loop {
+                interval.tick().await;
+                if let Some(ref store) = conversation_store {
+                    if let Err(e) = conversations.read().await.save_to_db(store).await {
+                        warn!("Failed to persist conversations: {}", e);
+                    }
+                }
+                if let Some(ref store) = trace_store {
+                    match store.prune_expired().await {
+                        Ok(n) if n > 0 => info!("Pruned {} expired loop traces", n),
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to prune loop traces: {}", e),
+                    }
+                }
+
+                let mut conversations = conversations.write().await;
+                let pruned = conversations.prune_inactive(ttl);
+                if pruned > 0 {
+                    info!(
+                        "Pruned {} inactive conversations ({} total, {} active)",
+                        pruned,
+                        conversations.pruned_total(),
+                        conversations.conversation_count()
+                    );
+                }
+            }
+        });
+    }
+
     let default_model = config.provider.openrouter.as_ref()
         .map(|o| o.default_model.as_str())
         .unwrap_or("not configured");
@@ -458,6 +1001,53 @@ async fn main() -> Result<()> {
         config.sandbox.execution_env
     );
 
+    // Operator HTTP endpoints (GET /plugins, GET /health, GET /health/db,
+    // GET /agent/status/history, /soul/sections, GET /metrics). Plugins are registered by
+    // embedders; the stock gateway starts with an empty registry.
+    let plugins = Arc::new(PluginRegistry::new());
+    let mut app = openagent::gateway::http::router(plugins).merge(
+        Router::new()
+            .route("/tools/reload", post(reload_tools))
+            .with_state(state.clone()),
+    )
+    .merge(openagent::gateway::http::health_router(state.status_store.clone()))
+    .merge(openagent::gateway::http::db_health_router(state.pg_pool.clone()))
+    .merge(openagent::gateway::http::status_history_router(state.status_store.clone()))
+    .merge(openagent::gateway::http::metrics_router(state.tool_metrics.clone()))
+    .merge(openagent::gateway::http::soul_router(state.soul_store.clone(), state.config.clone()));
+
+    // Start Slack if configured; its Events API callbacks share the HTTP server
+    let mut slack_started = false;
+    if let Some(slack_config) = config.channels.slack.as_ref() {
+        if slack_config.bot_token.expose_secret().is_empty() {
+            warn!("SLACK_BOT_TOKEN is empty, Slack channel will not start");
+        } else {
+            match start_slack(slack_config.clone(), state.clone()).await {
+                Ok(slack) => {
+                    app = app.merge(slack.router());
+                    slack_started = true;
+                }
+                Err(e) => {
+                    error!("Failed to start Slack channel: {}", e);
+                    warn!("Gateway will continue without Slack");
+                }
+            }
+        }
+    }
+
+    let app = openagent::gateway::http::with_rate_limit(app, limiter, GatewayAuth::new(config.gateway.auth.clone()).ok());
+    let app = openagent::gateway::http::with_cors(app, &config.gateway.cors);
+    match format!("{}:{}", config.gateway.bind, config.gateway.port).parse::<std::net::SocketAddr>() {
+        Ok(addr) => {
+            tokio::spawn(async move {
+                if let Err(e) = openagent::gateway::http::serve(addr, app).await {
+                    warn!("Gateway HTTP endpoints unavailable: {}", e);
+                }
+            });
+        }
+        Err(e) => warn!("Invalid gateway bind address: {}", e),
+    }
+
     // Start Telegram bot if configured
     let mut telegram_started = false;
     if let Some(telegram_config) = telegram_config {
@@ -490,9 +1080,13 @@ async fn main() -> Result<()> {
     }
 
     if !telegram_started {
-        info!("No channels active. Gateway running in standby mode.");
-        info!("Configure TELEGRAM_BOT_TOKEN to enable Telegram bot.");
-        info!("Press Ctrl+C to exit.");
+        if slack_started {
+            info!("Slack channel active. Press Ctrl+C to exit.");
+        } else {
+            info!("No channels active. Gateway running in standby mode.");
+            info!("Configure TELEGRAM_BOT_TOKEN or SLACK_BOT_TOKEN to enable a channel.");
+            info!("Press Ctrl+C to exit.");
+        }
 
         // Wait for shutdown signal
         tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl-c");
@@ -502,6 +1096,203 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Start the Slack channel, routing its messages through the agent loop
+async fn start_slack(config: SlackConfig, state: Arc<AppState>) -> Result<Arc<SlackChannel>> {
+    let slack = Arc::new(SlackChannel::new(config)?);
+
+    let channel = slack.clone();
+    let handler: MessageHandler = Arc::new(move |msg: ChannelMessage| {
+        let channel = channel.clone();
+        let state = state.clone();
+        Box::pin(async move { handle_channel_message(state, channel, msg).await })
+    });
+    slack.start(handler).await?;
+
+    Ok(slack)
+}
+
+/// Handle a message from a `Channel` implementation (Slack)
+///
+/// Mirrors the Telegram flow: DMs get the trusted tool set, channels the
+/// sandboxed one. Replies name the message they answer, so the channel can
+/// thread group replies and answer slash commands through their
+/// `response_url`.
+async fn handle_channel_message(
+    state: Arc<AppState>,
+    channel: Arc<dyn Channel>,
+    msg: ChannelMessage,
+) -> Result<()> {
+    let Some(text) = msg.content.as_text() else {
+        return Ok(());
+    };
+    let session = SessionNamespace::new(channel.id(), &msg.sender_id);
+    let session_type = if msg.is_group {
+        SessionType::Group
+    } else {
+        SessionType::DirectMessage
+    };
+
+    info!("{} message from {} ({:?})", channel.id(), session, session_type);
+
+    let response = match run_agent_turn(&state, text, &session, session_type, None, NoOpCallback).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Agentic loop error: {}", e);
+            format!("❌ Error: {}", e)
+        }
+    };
+    if response.is_empty() {
+        return Ok(());
+    }
+
+    let reply = ChannelReply::text(&msg.conversation_id, response)
+        .with_reply_to(msg.reply_to.as_deref().unwrap_or(&msg.id));
+    channel.send(reply).await?;
+    Ok(())
+}
+
+/// Accept gateway protocol clients and answer their requests
+///
+/// Each request runs in its own task, so heartbeats keep flowing during a
+/// long agent turn; the response is pushed to the client when it is ready.
+async fn serve_websocket(server: Arc<GatewayServer>, state: Arc<AppState>, limiter: GatewayRateLimiter) {
+    let continuations = ContinuationStore::new();
+    loop {
+        // Only the TCP accept happens here; the TLS and WebSocket handshakes
+        // run in the connection's task so a stalled client can't block others
+        let pending = match server.accept_pending().await {
+            Ok(pending) => pending,
+            Err(e) => {
+                debug!("{}", e);
+                continue;
+            }
+        };
+
+        let state = state.clone();
+        let continuations = continuations.clone();
+        let limiter = limiter.clone();
+        tokio::spawn(async move {
+            let connection = match pending.establish().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    debug!("{}", e);
+                    return;
+                }
+            };
+
+            let monitor = HeartbeatMonitor::from_config(&state.config.read().await.gateway);
+            let client = WebSocketClient {
+                user: connection.user().map(str::to_string),
+                connection_id: uuid::Uuid::new_v4().to_string(),
+            };
+            let sender = connection.sender();
+            let peer = connection.peer();
+            let served = connection
+                .serve_limited(monitor, limiter, move |frame| match frame {
+                    GatewayFrame::Request(request) => {
+                        let (state, continuations, client, sender) =
+                            (state.clone(), continuations.clone(), client.clone(), sender.clone());
+                        tokio::spawn(async move {
+                            let response = handle_gateway_request(&state, &continuations, &client, request).await;
+                            // The client may have disconnected meanwhile
+                            let _ = sender.send(GatewayFrame::Response(response));
+                        });
+                        None
+                    }
+                    frame => frame.auto_reply(),
+                })
+                .await;
+            if let Err(e) = served {
+                debug!("WebSocket connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+}
+
+/// Identity of one WebSocket connection
+#[derive(Clone)]
+struct WebSocketClient {
+    /// Authenticated user (the JWT `sub` claim), if the auth mode has one
+    user: Option<String>,
+    /// Session of the connection when neither a user nor a `sessionId` is given
+    connection_id: String,
+}
+
+impl WebSocketClient {
+    /// Conversation for a request: the authenticated user's, else the
+    /// requested `sessionId`, else one for this connection
+    fn session(&self, requested: Option<&str>) -> SessionNamespace {
+        let id = self.user.as_deref().or(requested).unwrap_or(&self.connection_id);
+        SessionNamespace::new(WEBSOCKET_CHANNEL, id)
+    }
+}
+
+/// Answer one gateway protocol request
+async fn handle_gateway_request(
+    state: &AppState,
+    continuations: &ContinuationStore,
+    client: &WebSocketClient,
+    request: RequestFrame,
+) -> ResponseFrame {
+    fn params<T: serde::de::DeserializeOwned>(request: &RequestFrame) -> std::result::Result<T, ProtocolError> {
+        serde_json::from_value(request.params.clone())
+            .map_err(|e| ProtocolError::new(error_codes::INVALID_PARAMS, e.to_string()))
+    }
+
+    let result = match request.method.as_str() {
+        "agent.send" => match params::<AgentSendRequest>(&request) {
+            Ok(send) => {
+                let session = client.session(send.session_id.as_deref());
+                // Only authenticated clients get the trusted (DM) tool set
+                let session_type = if state.config.read().await.gateway.auth.mode == AuthMode::None {
+                    SessionType::Group
+                } else {
+                    SessionType::DirectMessage
+                };
+                ensure_system_prompt(state, &session, None).await;
+                match run_agent_turn(state, &send.message, &session, session_type, None, NoOpCallback).await {
+                    Ok(content) => {
+                        let response = AgentResponse {
+                            session_id: session.user_id.clone(),
+                            content,
+                            model: state.llm_client.read().await.default_model().to_string(),
+                            finish_reason: Some("stop".to_string()),
+                            usage: None,
+                            continuation_token: None,
+                        };
+                        serde_json::to_value(continuations.paginate(response).await)
+                            .map_err(|e| ProtocolError::internal(e.to_string()))
+                    }
+                    Err(e) => Err(ProtocolError::internal(e.to_string())),
+                }
+            }
+            Err(e) => Err(e),
+        },
+        "agent.continue" => match params::<AgentContinueRequest>(&request) {
+            Ok(next) => match continuations.next_page(&next).await {
+                Ok(page) => serde_json::to_value(page).map_err(|e| ProtocolError::internal(e.to_string())),
+                Err(e) => Err(ProtocolError::new(error_codes::CONTINUATION_NOT_FOUND, e.to_string())),
+            },
+            Err(e) => Err(e),
+        },
+        "sessions.list" => {
+            let requested = request.params.get("sessionId").and_then(|v| v.as_str());
+            let session = client.session(requested);
+            let sessions = state.conversations.read().await.get(&session).map(SessionInfo::from);
+            serde_json::to_value(SessionsListResponse {
+                sessions: sessions.into_iter().collect(),
+            })
+            .map_err(|e| ProtocolError::internal(e.to_string()))
+        }
+        method => Err(ProtocolError::method_not_found(method)),
+    };
+
+    match result {
+        Ok(value) => ResponseFrame::success(request.id, value),
+        Err(error) => ResponseFrame::error(request.id, error),
+    }
+}
+
 /// Handle incoming messages
 async fn message_handler(
     bot: Bot,
@@ -514,7 +1305,7 @@ async fn message_handler(
 
     // For DMs, check if user is approved (pairing system)
     if session_type == SessionType::DirectMessage {
-        let dm_policy = state.config.channels.telegram
+        let dm_policy = state.config.read().await.channels.telegram
             .as_ref()
             .map(|t| t.dm_policy)
             .unwrap_or(DmPolicy::Open);
@@ -531,12 +1322,17 @@ async fn message_handler(
 
         if needs_pairing {
             // Check if this is an admin command that doesn't require approval
-            if let Some(text) = msg.text() {
-                if !text.starts_with('/') {
-                    // Not a command - require pairing
-                    return handle_pairing_request(bot, msg, state, user_id).await;
+            let is_command = msg.text().is_some_and(|text| text.starts_with('/'));
+            if !is_command {
+                if dm_policy == DmPolicy::Allowlist {
+                    // No pairing codes: an admin has to /approve the user ID
+                    bot.send_message(chat_id, format!(
+                        "❌ You are not on this bot's allowlist. Ask an administrator to approve user ID {}.",
+                        user_id
+                    ))
+                    .await?;
+                    return Ok(());
                 }
-            } else {
                 return handle_pairing_request(bot, msg, state, user_id).await;
             }
         }
@@ -550,7 +1346,7 @@ async fn message_handler(
         }
 
         // Regular message - chat with LLM
-        return handle_chat(bot, msg, state, &text, &user_id.to_string(), session_type).await;
+        return handle_chat(bot, msg, state, &text, &SessionNamespace::telegram(user_id), session_type).await;
     }
 
     // Handle documents/files
@@ -558,6 +1354,11 @@ async fn message_handler(
         return handle_document(bot, msg, state, &user_id.to_string()).await;
     }
 
+    // Handle photos with a vision request
+    if msg.photo().is_some() {
+        return handle_photo(bot, msg, state, &SessionNamespace::telegram(user_id)).await;
+    }
+
     Ok(())
 }
 
@@ -605,6 +1406,7 @@ async fn handle_command(
 ) -> ResponseResult<()> {
     let chat_id = msg.chat.id;
     let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+    let session = SessionNamespace::telegram(user_id);
 
     // Parse command
     let parts: Vec<&str> = text.splitn(2, ' ').collect();
@@ -613,6 +1415,14 @@ async fn handle_command(
     let cmd = cmd.split('@').next().unwrap_or(&cmd);
     let args = parts.get(1).map(|s| s.to_string()).unwrap_or_default();
 
+    // A bare /approve or /reject answers a skill waiting in this chat
+    if args.is_empty() && (cmd == "approve" || cmd == "reject") {
+        if let Some(tx) = state.skill_approvals.lock().await.remove(&chat_id) {
+            let _ = tx.send(cmd == "approve");
+            return Ok(());
+        }
+    }
+
     match cmd {
         "start" => {
             let session_info = match session_type {
@@ -641,22 +1451,28 @@ async fn handle_command(
             let messages_for_summary = {
                 let conversations = state.conversations.read().await;
                 conversations
-                    .get(&user_id.to_string())
+                    .get(&session)
                     .filter(|conv| conv.message_count() >= 4)
                     .map(|conv| conv.messages.clone())
             };
 
-            // Clear the conversation
-            {
+            // Clear the conversation (and its persisted copy, so a restart doesn't bring it back)
+            let cleared = {
                 let mut conversations = state.conversations.write().await;
-                conversations.clear_conversation(&user_id.to_string());
+                conversations.clear_conversation(&session);
+                conversations.get(&session).cloned()
+            };
+            if let (Some(conv), Some(store)) = (cleared, &state.conversation_store) {
+                if let Err(e) = store.save(&conv).await {
+                    warn!("Failed to persist cleared conversation: {}", e);
+                }
             }
 
             // Spawn background auto-episodic summary if there were enough messages
             if let (Some(messages), Some(retriever)) =
-                (messages_for_summary, state.memory_retriever.as_ref())
+                (messages_for_summary.clone(), state.memory_retriever.as_ref())
             {
-                let summarizer = ConversationSummarizer::new(state.llm_client.clone());
+                let summarizer = ConversationSummarizer::new(state.llm_client.read().await.clone());
                 let retriever = retriever.clone();
                 let uid = user_id.to_string();
                 tokio::spawn(async move {
@@ -708,19 +1524,37 @@ async fn handle_command(
                 });
             }
 
+            // Turn open follow-ups into tasks
+            if let (Some(messages), Some(task_store)) = (messages_for_summary, state.task_store.clone()) {
+                let summarizer = ConversationSummarizer::new(state.llm_client.read().await.clone());
+                let uid = user_id.to_string();
+                tokio::spawn(async move {
+                    match summarizer
+                        .create_action_item_tasks(&task_store, &uid, Some(chat_id.0), &messages)
+                        .await
+                    {
+                        Ok(tasks) if !tasks.is_empty() => {
+                            info!("Created {} action item tasks for user={}", tasks.len(), uid);
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Action item extraction failed: {}", e),
+                    }
+                });
+            }
+
             bot.send_message(chat_id, "✅ Conversation cleared.")
                 .await?;
         }
         "model" => {
             let conversations = state.conversations.read().await;
-            let default_model = state.config.provider.openrouter
+            let default_model = state.config.read().await.provider.openrouter
                 .as_ref()
-                .map(|c| c.default_model.as_str())
-                .unwrap_or("unknown");
+                .map(|c| c.default_model.clone())
+                .unwrap_or_else(|| "unknown".to_string());
             let model = conversations
-                .get(&user_id.to_string())
+                .get(&session)
                 .map(|c| c.model.as_str())
-                .unwrap_or(default_model);
+                .unwrap_or(&default_model);
             bot.send_message(chat_id, format!("Current model: `{}`", model))
                 .parse_mode(ParseMode::MarkdownV2)
                 .await?;
@@ -734,7 +1568,7 @@ async fn handle_command(
                 .await?;
             } else {
                 let mut conversations = state.conversations.write().await;
-                let conv = conversations.get_or_create(&user_id.to_string());
+                let conv = conversations.get_or_create(&session);
                 conv.model = args.clone();
                 bot.send_message(chat_id, format!("✅ Switched to model: {}", args))
                     .await?;
@@ -744,7 +1578,7 @@ async fn handle_command(
             if args.is_empty() {
                 bot.send_message(
                     chat_id,
-                    "Usage: /run <language> <code>\nExample: /run python print('hello')",
+                    "Usage: /run <language> <code>\nLanguages: python, javascript, typescript, bash, go, kotlin, java, ruby\nExample: /run python print('hello')",
                 )
                 .await?;
             } else {
@@ -752,54 +1586,131 @@ async fn handle_command(
             }
         }
         "status" => {
-            let default_model = state.config.provider.openrouter
-                .as_ref()
-                .map(|c| c.default_model.as_str())
-                .unwrap_or("not configured");
-            let tools = state.tools_for_session(session_type);
+            let (default_model, execution_env) = {
+                let config = state.config.read().await;
+                let default_model = config.provider.openrouter
+                    .as_ref()
+                    .map(|c| c.default_model.clone())
+                    .unwrap_or_else(|| "not configured".to_string());
+                (default_model, config.sandbox.execution_env)
+            };
+            let tools = state.tools_for_session(session_type).await;
             let session_info = match session_type {
                 SessionType::DirectMessage => "DM (full access)",
                 SessionType::Group => "Group (sandboxed)",
             };
 
-            let agent_state = if let Some(ref ss) = state.status_store {
-                match ss.state().await {
-                    Ok(s) => s.to_string(),
-                    Err(_) => "unknown".to_string(),
+            let (agent_state, task_stats) = if let Some(ref ss) = state.status_store {
+                match ss.get_with_task_stats().await {
+                    Ok((row, stats)) => (row.state().to_string(), Some(stats)),
+                    Err(_) => ("unknown".to_string(), None),
+                }
+            } else {
+                ("no DB".to_string(), None)
+            };
+            let tasks = match task_stats {
+                Some(stats) => escape_markdown(&format!(
+                    "{} pending, {} processing, {} complete, {} failed ({} total)",
+                    stats.pending, stats.processing, stats.complete, stats.failed, stats.total
+                )),
+                None => "unknown".to_string(),
+            };
+
+            let current_task = match (&state.status_store, &state.task_store) {
+                (Some(ss), Some(ts)) => match ss.get_current_task_id().await {
+                    Ok(Some(task_id)) => match ts.get(task_id).await {
+                        Ok(Some(task)) => {
+                            let description: String = task.description.chars().take(100).collect();
+                            let ellipsis = if description.len() < task.description.len() { "…" } else { "" };
+                            escape_markdown(&format!(
+                                "\nCurrent Task: {}\nCreated: {}\nDescription: {}{}",
+                                task.id,
+                                task.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                                description,
+                                ellipsis,
+                            ))
+                        }
+                        _ => escape_markdown(&format!("\nCurrent Task: {}", task_id)),
+                    },
+                    _ => String::new(),
+                },
+                _ => String::new(),
+            };
+
+            let recent_transitions = if let Some(ref ss) = state.status_store {
+                let history = ss.history(10).await.unwrap_or_default();
+                if history.is_empty() {
+                    String::new()
+                } else {
+                    let lines: Vec<String> = history
+                        .iter()
+                        .map(|t| {
+                            escape_markdown(&format!(
+                                "{} {} → {}",
+                                t.transitioned_at.format("%Y-%m-%d %H:%M:%S"),
+                                t.from_state,
+                                t.to_state,
+                            ))
+                        })
+                        .collect();
+                    format!("\n\n*Recent Transitions*\n{}", lines.join("\n"))
                 }
             } else {
-                "no DB".to_string()
+                String::new()
             };
 
-            let pending_tasks = if let Some(ref ts) = state.task_store {
-                ts.count_pending().await.unwrap_or(0)
+            let session_counts = state.conversations.read().await.session_counts();
+            let active_sessions = if session_counts.is_empty() {
+                "none".to_string()
             } else {
-                0
+                let counts: Vec<String> = session_counts
+                    .iter()
+                    .map(|(channel, count)| format!("{} {}", channel, count))
+                    .collect();
+                escape_markdown(&counts.join(", "))
             };
 
+            let permissions: Vec<&str> = state
+                .permissions_for_session(session_type)
+                .iter()
+                .map(|p| p.as_str())
+                .collect();
+
             let status = format!(
                 "🤖 *OpenAgent Status*\n\n\
                 Version: {}\n\
                 Model: {}\n\
                 Session: {}\n\
+                Active Sessions: {}\n\
                 Execution: {}\n\
                 Database: {}\n\
                 Tools: {}\n\
-                Agent State: {}\n\
-                Pending Tasks: {}",
+                Permissions: {}\n\
+                Agent State: {}{}\n\
+                Tasks: {}{}",
                 openagent::VERSION,
                 default_model,
                 session_info,
-                state.config.sandbox.execution_env,
+                active_sessions,
+                execution_env,
                 if state.memory_retriever.is_some() { "Connected" } else { "Not connected" },
                 tools.count(),
+                escape_markdown(&permissions.join(", ")),
                 agent_state,
-                pending_tasks,
+                current_task,
+                tasks,
+                recent_transitions,
             );
             bot.send_message(chat_id, status)
                 .parse_mode(ParseMode::MarkdownV2)
                 .await?;
         }
+        "reject" => {
+            bot.send_message(chat_id, "Nothing to reject.").await?;
+        }
+        "skill" => {
+            handle_skill(bot, chat_id, state, user_id, &args, session_type).await?;
+        }
         "approve" => {
             // Admin only command
             let is_admin = state.pairing.read().await.is_admin(user_id);
@@ -817,6 +1728,11 @@ async fn handle_command(
                     Ok(target_user_id) => {
                         let approved = state.pairing.write().await.approve_user(target_user_id);
                         if approved {
+                            if let Some(ref pool) = state.pg_pool {
+                                if let Err(e) = PairingManager::persist_approval(pool, target_user_id, user_id).await {
+                                    warn!("Failed to persist approval of user {}: {}", target_user_id, e);
+                                }
+                            }
                             bot.send_message(chat_id, format!("✅ User {} has been approved.", target_user_id))
                                 .await?;
                             info!("Admin {} approved user {}", user_id, target_user_id);
@@ -926,36 +1842,13 @@ async fn handle_command(
     Ok(())
 }
 
-/// Callback for the gateway agentic loop: sends typing indicators.
-struct GatewayCallback {
-    bot: Bot,
-    chat_id: ChatId,
-}
-
-#[async_trait::async_trait]
-impl LoopCallback for GatewayCallback {
-    async fn on_iteration_start(&self, _iteration: u32) {
-        let _ = self
-            .bot
-            .send_chat_action(self.chat_id, teloxide::types::ChatAction::Typing)
-            .await;
-    }
-
-    async fn on_tool_executed(&self, _tool_name: &str, _observation: &ToolObservation) {
-        let _ = self
-            .bot
-            .send_chat_action(self.chat_id, teloxide::types::ChatAction::Typing)
-            .await;
-    }
-}
-
 /// Handle regular chat messages - AGENTIC LOOP
 async fn handle_chat(
     bot: Bot,
     msg: Message,
     state: Arc<AppState>,
     text: &str,
-    user_id: &str,
+    session: &SessionNamespace,
     session_type: SessionType,
 ) -> ResponseResult<()> {
     let chat_id = msg.chat.id;
@@ -963,20 +1856,97 @@ async fn handle_chat(
     // Note: Tasks are NOT auto-created for every message.
     // Tasks are created explicitly via /task command or task_create tool.
 
+    ensure_system_prompt(&state, session, msg.from.as_ref()).await;
+
+    // Keep "typing…" up for the whole turn
+    let telegram = TelegramChannel::new(bot.clone());
+    let typing_chat = chat_id.to_string();
+    if let Err(e) = telegram.send_typing_indicator(&typing_chat, TYPING_INDICATOR_SECS).await {
+        warn!("Typing indicator failed: {}", e);
+    }
+
+    let result = run_agent_turn(&state, text, session, session_type, Some(chat_id.0), NoOpCallback).await;
+    telegram.stop_typing(&typing_chat);
+
+    match result {
+        Ok(response) if !response.is_empty() => {
+            // Send response (split if too long)
+            send_long_message(&bot, chat_id, &response).await?;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            error!("Agentic loop error: {}", e);
+            bot.send_message(chat_id, format!("❌ Error: {}", e))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Give new conversations (or ones reset by a soul reload) a system prompt
+/// rendered for this user
+async fn ensure_system_prompt(state: &AppState, session: &SessionNamespace, from: Option<&teloxide::types::User>) {
+    let needs_prompt = state
+        .conversations
+        .read()
+        .await
+        .get(session)
+        .is_none_or(|conv| conv.system_prompt.is_none());
+    if needs_prompt {
+        let vars = state.user_prompt_vars(&session.user_key(), from).await;
+        let prompt = render_system_prompt(&state.system_template, &state.soul_prompt.read().await, &vars);
+        let mut conversations = state.conversations.write().await;
+        conversations.get_or_create(session).system_prompt = Some(prompt);
+    }
+}
+
+/// Run one agentic turn for any channel and return the final response
+///
+/// Handles conversation history, memory injection, session-scoped tools and
+/// agent status. Delivering the response is left to the caller.
+async fn run_agent_turn<C: LoopCallback>(
+    state: &AppState,
+    text: &str,
+    session: &SessionNamespace,
+    session_type: SessionType,
+    chat_id: Option<i64>,
+    callback: C,
+) -> Result<String> {
+    let _turn = state.turns.start();
+
     // Set agent status to processing (without a task reference)
     // Use a nil UUID as placeholder since set_processing requires a task_id
     if let Some(ref status_store) = state.status_store {
         let _ = status_store.set_processing(uuid::Uuid::nil()).await;
     }
 
-    // Show typing indicator
-    bot.send_chat_action(chat_id, teloxide::types::ChatAction::Typing)
-        .await?;
+    let result = run_agent_loop(state, text, session, session_type, chat_id, callback).await;
+
+    // Restore agent status to ready
+    if let Some(ref status_store) = state.status_store {
+        let _ = status_store.set_ready().await;
+    }
+
+    result
+}
+
+async fn run_agent_loop<C: LoopCallback>(
+    state: &AppState,
+    text: &str,
+    session: &SessionNamespace,
+    session_type: SessionType,
+    chat_id: Option<i64>,
+    callback: C,
+) -> Result<String> {
+    // Memories and traces are keyed per user, not per conversation
+    let user_id = session.user_key();
+    let user_id = user_id.as_str();
 
     // Get or create conversation and add user message
     let mut messages = {
         let mut conversations = state.conversations.write().await;
-        let conv = conversations.get_or_create(user_id);
+        let conv = conversations.get_or_create(session);
         conv.add_user_message(text);
         conv.get_api_messages()
     };
@@ -993,10 +1963,24 @@ async fn handle_chat(
             Err(e) => warn!("Memory retrieval failed: {}", e),
             _ => {}
         }
+
+        // How-to questions get matching procedures as their own section
+        if is_how_to_query(text) {
+            match retriever.retrieve_procedural(user_id, text).await {
+                Ok(procedures) if !procedures.is_empty() => {
+                    if let Some(sys) = messages.iter_mut().find(|m| m.role == openagent::agent::Role::System) {
+                        sys.content.push_str(&procedures);
+                        info!("Injected procedural memories ({} chars) for user={}", procedures.len(), user_id);
+                    }
+                }
+                Err(e) => warn!("Procedural memory retrieval failed: {}", e),
+                _ => {}
+            }
+        }
     }
 
     // Get tool definitions based on session type
-    let tools = state.tools_for_session(session_type);
+    let tools = state.tools_for_session(session_type).await;
     let tool_definitions = tools.definitions();
 
     let session_label = match session_type {
@@ -1020,57 +2004,53 @@ async fn handle_chat(
     }
 
     // Run the unified agentic loop
-    let gateway_callback = GatewayCallback {
-        bot: bot.clone(),
-        chat_id,
+    let llm_client = state.llm_client.read().await.clone();
+    let config = {
+        let soul = state.soul_prompt.read().await;
+        LoopConfig::gateway().with_tool_preferences(parse_tool_preferences(&soul))
     };
-
     let loop_input = AgentLoopInput {
         messages,
-        llm_client: &state.llm_client,
-        tools,
+        llm_client: &llm_client,
+        tools: &tools,
         tool_definitions,
-        config: LoopConfig::gateway(),
+        config,
         user_id: Some(user_id.to_string()),
-        chat_id: Some(chat_id.0),
-        callback: gateway_callback,
-    };
-
-    let loop_output = match agentic_loop::run_agentic_loop(loop_input).await {
-        Ok(output) => output,
-        Err(e) => {
-            error!("Agentic loop error: {}", e);
-            bot.send_message(chat_id, format!("❌ Error: {}", e))
-                .await?;
-            if let Some(ref ss) = state.status_store {
-                let _ = ss.set_ready().await;
-            }
-            return Ok(());
-        }
+        chat_id,
+        callback: (
+            callback,
+            SentryLoopCallback::from_env(llm_client.default_model(), Some(user_id)),
+        ),
     };
 
+    let loop_output = agentic_loop::run_agentic_loop(loop_input).await?;
     let final_response = loop_output.response.clone();
 
-    // Store assistant response in conversation
-    if !final_response.is_empty() {
-        let mut conversations = state.conversations.write().await;
-        if let Some(conv) = conversations.get_mut(user_id) {
-            conv.add_assistant_message(&final_response);
-            conv.total_tokens += loop_output.total_usage.total_tokens;
+    if let Some(ref store) = state.trace_store {
+        match store.save(Some(user_id), &loop_output.trace).await {
+            Ok(id) => debug!("Saved loop trace {} for user={}", id, user_id),
+            Err(e) => warn!("Failed to save loop trace: {}", e),
         }
     }
 
-    // Send response (split if too long)
+    // Store assistant response in conversation
     if !final_response.is_empty() {
-        send_long_message(&bot, chat_id, &final_response).await?;
-    }
-
-    // Restore agent status to ready
-    if let Some(ref status_store) = state.status_store {
-        let _ = status_store.set_ready().await;
+        let saved = {
+            let mut conversations = state.conversations.write().await;
+            conversations.get_mut(session).map(|conv| {
+                conv.add_assistant_message(&final_response);
+                conv.total_tokens += loop_output.total_usage.total_tokens;
+                conv.clone()
+            })
+        };
+        if let (Some(conv), Some(store)) = (saved, &state.conversation_store) {
+            if let Err(e) = store.save(&conv).await {
+                warn!("Failed to persist conversation for user {}: {}", user_id, e);
+            }
+        }
     }
 
-    Ok(())
+    Ok(final_response)
 }
 
 /// Handle code execution command
@@ -1094,7 +2074,7 @@ async fn handle_code_execution(
             bot.send_message(
                 chat_id,
                 format!(
-                    "Unsupported language: {}\nSupported: python, javascript, bash, typescript",
+                    "Unsupported language: {}\nSupported: python, javascript, bash, typescript, go, kotlin, java, ruby",
                     parts[0]
                 ),
             )
@@ -1162,6 +2142,201 @@ async fn handle_document(
     Ok(())
 }
 
+/// Handle a photo: download the largest size and ask the model about it
+///
+/// The caption (or a default prompt) is the question; the photo itself is
+/// not kept in the conversation history, only a `[Photo]` marker.
+async fn handle_photo(
+    bot: Bot,
+    msg: Message,
+    state: Arc<AppState>,
+    session: &SessionNamespace,
+) -> ResponseResult<()> {
+    use teloxide::net::Download;
+
+    let chat_id = msg.chat.id;
+    let Some(photo) = msg.photo().and_then(|sizes| sizes.iter().max_by_key(|p| p.width * p.height)) else {
+        return Ok(());
+    };
+    let caption = msg.caption().unwrap_or("Describe this image.");
+
+    ensure_system_prompt(&state, session, msg.from.as_ref()).await;
+    bot.send_chat_action(chat_id, teloxide::types::ChatAction::Typing)
+        .await?;
+
+    let file = bot.get_file(photo.file.id.clone()).await?;
+    let mut data = Vec::with_capacity(file.size as usize);
+    if let Err(e) = bot.download_file(&file.path, &mut data).await {
+        error!("Failed to download photo: {}", e);
+        bot.send_message(chat_id, "❌ Could not download the photo.").await?;
+        return Ok(());
+    }
+    // Telegram re-encodes photos as JPEG
+    let image = ImageInput::from_bytes(&data, "image/jpeg");
+
+    let messages = {
+        let mut conversations = state.conversations.write().await;
+        let conv = conversations.get_or_create(session);
+        conv.add_user_message(format!("[Photo] {}", caption));
+        let mut messages = conv.get_api_messages();
+        if let Some(last) = messages.last_mut() {
+            last.content = caption.to_string();
+        }
+        messages
+    };
+
+    let llm_client = state.llm_client.read().await.clone();
+    let response = match llm_client.chat_with_vision(messages, vec![image]).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Vision request failed: {}", e);
+            bot.send_message(chat_id, format!("❌ Error: {}", e)).await?;
+            return Ok(());
+        }
+    };
+    let reply = response
+        .choices
+        .first()
+        .map(|c| c.message.content.clone())
+        .unwrap_or_default();
+    if reply.is_empty() {
+        return Ok(());
+    }
+
+    let saved = {
+        let mut conversations = state.conversations.write().await;
+        conversations.get_mut(session).map(|conv| {
+            conv.add_assistant_message(&reply);
+            conv.total_tokens += response.usage.as_ref().map_or(0, |u| u.total_tokens);
+            conv.clone()
+        })
+    };
+    if let (Some(conv), Some(store)) = (saved, &state.conversation_store) {
+        if let Err(e) = store.save(&conv).await {
+            warn!("Failed to persist conversation for {}: {}", session, e);
+        }
+    }
+
+    send_long_message(&bot, chat_id, &reply).await
+}
+
+/// Run a skill in the background, asking the chat to approve destructive steps
+///
+/// The skill runs in its own task: Telegram updates from one chat are handled
+/// in order, so waiting here would block the /approve reply.
+async fn handle_skill(
+    bot: Bot,
+    chat_id: ChatId,
+    state: Arc<AppState>,
+    user_id: i64,
+    args: &str,
+    session_type: SessionType,
+) -> ResponseResult<()> {
+    if session_type != SessionType::DirectMessage {
+        bot.send_message(chat_id, "❌ Skills are only available in DMs.").await?;
+        return Ok(());
+    }
+    let dm_policy = state.config.read().await.channels.telegram
+        .as_ref()
+        .map(|t| t.dm_policy)
+        .unwrap_or(DmPolicy::Open);
+    if dm_policy != DmPolicy::Open && !state.pairing.read().await.is_approved(user_id) {
+        bot.send_message(chat_id, "❌ You need to be approved before running skills.").await?;
+        return Ok(());
+    }
+
+    let args = args.trim();
+    let (name, params) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, ""), |(name, params)| (name, params.trim()));
+    if state.skills.get(name).is_none() {
+        let mut names = state.skills.names();
+        names.sort();
+        bot.send_message(
+            chat_id,
+            format!("Usage: /skill <name> [JSON parameters]
+Skills: {}", names.join(", ")),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut params: HashMap<String, serde_json::Value> = if params.is_empty() {
+        HashMap::new()
+    } else {
+        match serde_json::from_str(params) {
+            Ok(params) => params,
+            Err(e) => {
+                bot.send_message(chat_id, format!("❌ Skill parameters must be a JSON object: {}", e))
+                    .await?;
+                return Ok(());
+            }
+        }
+    };
+    // The session identity always wins over caller-supplied parameters
+    params.insert("user_id".to_string(), serde_json::json!(user_id.to_string()));
+    params.insert("chat_id".to_string(), serde_json::json!(chat_id.0));
+
+    let name = name.to_string();
+    tokio::spawn(async move {
+        let Some(skill) = state.skills.get(&name) else {
+            return;
+        };
+        let tools = state.dm_tools.read().await.clone();
+        let ctx = SkillContext::new(&tools, params)
+            .with_approval_callback(telegram_approval(bot.clone(), chat_id, state.clone()));
+
+        let reply = match skill.execute(&ctx).await {
+            Ok(result) => format_skill_result(&result),
+            Err(e) => format!("❌ Skill {} failed: {}", name, e),
+        };
+        if let Err(e) = send_long_message(&bot, chat_id, &reply).await {
+            warn!("Failed to send skill result: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// Approval callback that asks the chat and waits for /approve or /reject
+///
+/// Skills call it synchronously, so it blocks the (multi-threaded) runtime
+/// worker it runs on until the user answers or the request times out.
+fn telegram_approval(
+    bot: Bot,
+    chat_id: ChatId,
+    state: Arc<AppState>,
+) -> impl Fn(&str) -> bool + Send + Sync + 'static {
+    let handle = tokio::runtime::Handle::current();
+    move |description| {
+        tokio::task::block_in_place(|| {
+            handle.block_on(async {
+                let (tx, rx) = oneshot::channel();
+                state.skill_approvals.lock().await.insert(chat_id, tx);
+
+                let prompt = format!("⚠️ Approval needed:\n{}\n\nReply /approve or /reject", description);
+                let approved = match bot.send_message(chat_id, prompt).await {
+                    Ok(_) => matches!(tokio::time::timeout(SKILL_APPROVAL_TIMEOUT, rx).await, Ok(Ok(true))),
+                    Err(e) => {
+                        warn!("Failed to request skill approval: {}", e);
+                        false
+                    }
+                };
+                state.skill_approvals.lock().await.remove(&chat_id);
+                approved
+            })
+        })
+    }
+}
+
+/// Summary line plus one line per step
+fn format_skill_result(result: &SkillResult) -> String {
+    let mut text = format!("{} {}", if result.success { "✅" } else { "❌" }, result.summary);
+    for step in &result.steps {
+        text.push_str(&format!("\n{} {}", if step.success { "•" } else { "✗" }, step.description));
+    }
+    text
+}
+
 /// Send a long message, splitting if necessary
 async fn send_long_message(bot: &Bot, chat_id: ChatId, text: &str) -> ResponseResult<()> {
     const MAX_LENGTH: usize = 4096;
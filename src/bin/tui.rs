@@ -6,21 +6,26 @@
 
 use openagent::agent::{
     Conversation, LoopConfig, Role,
-    OpenRouterClient, ToolRegistry, ReadFileTool, WriteFileTool,
-    SystemCommandTool, DuckDuckGoSearchTool, BraveSearchTool, PerplexitySearchTool,
+    OpenRouterClient, ToolRegistry, ReadFileTool, WriteFileTool, DiffTool,
+    SystemCommandTool, DuckDuckGoSearchTool, FetchPageTool, BraveSearchTool, PerplexitySearchTool,
     MemorySaveTool, MemorySearchTool, MemoryListTool, MemoryDeleteTool,
     prompts::Soul,
     agentic_loop::{self, AgentLoopInput, LoopCallback, ToolObservation},
 };
 use openagent::config::Config;
-use openagent::database::{init_pool, Memory, MemoryType};
+use openagent::database::{init_pool, Memory, MemoryType, TaskStore};
 use openagent::memory::{ConversationSummarizer, EmbeddingService, MemoryCache, MemoryRetriever};
+use openagent::skills::{InstallPackageSkill, SkillContext, SkillRegistry};
+use openagent::tools::RateLimiter;
 use openagent::{Error, Result};
 
 use clap::Parser;
 use console::{style, Term};
-use dialoguer::{theme::ColorfulTheme, Input};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tracing::{info, warn};
 
 /// OpenAgent TUI - Interactive Terminal Agent
@@ -52,10 +57,13 @@ struct TuiState {
     llm_client: OpenRouterClient,
     conversation: Conversation,
     tools: ToolRegistry,
+    skills: SkillRegistry,
     current_model: String,
     verbose: bool,
     tools_enabled: bool,
     memory_retriever: Option<MemoryRetriever>,
+    /// Receives follow-ups found when a conversation is cleared
+    task_store: Option<TaskStore>,
     user_id: String,
 }
 
@@ -66,7 +74,7 @@ impl TuiState {
         // Initialize LLM client
         let openrouter_config = config.provider.openrouter.clone()
             .ok_or_else(|| Error::Config("OpenRouter not configured".into()))?;
-        let llm_client = OpenRouterClient::new(openrouter_config.clone())?;
+        let llm_client = OpenRouterClient::from_provider_config(&config.provider)?;
 
         // Determine model
         let current_model = args.model.clone()
@@ -74,7 +82,7 @@ impl TuiState {
 
         // Load soul for system prompt
         let soul = Soul::load_or_default();
-        let system_prompt = soul.as_system_prompt();
+        let system_prompt = soul.as_system_prompt_for_model(&current_model);
 
         // User ID for memory
         let user_id = "tui-user".to_string();
@@ -84,16 +92,22 @@ impl TuiState {
             .with_system_prompt(&system_prompt);
 
         // Initialize memory retriever if requested
+        let mut task_store = None;
         let memory_retriever = if args.memory {
             match &config.storage.postgres {
                 Some(db_config) => match init_pool(db_config).await {
                     Ok(pool) => {
+                        task_store = Some(TaskStore::new(pool.clone()));
                         let store = openagent::database::MemoryStore::new(pool);
                         match EmbeddingService::new() {
                             Ok(embedding) => {
-                                let cache = MemoryCache::new();
+                                let cache = MemoryCache::new(config.storage.memory.embedding.cache_policy);
                                 info!("Memory retriever initialized (embedding + cache + PG)");
-                                Some(MemoryRetriever::new(store, embedding, cache))
+                                Some(
+                                    MemoryRetriever::new(store, embedding, cache)
+                                        .with_auto_tagging(llm_client.clone())
+                                        .with_consolidation(llm_client.clone()),
+                                )
                             }
                             Err(e) => {
                                 warn!("Embedding service failed: {}. Running without memory retrieval.", e);
@@ -116,19 +130,27 @@ impl TuiState {
         };
 
         // Initialize tools (DM-level access)
-        let mut tools = ToolRegistry::new();
+        let mut tools = ToolRegistry::new().with_rate_limiter(RateLimiter::from_config(&config.agent.tool_concurrency));
         if !args.no_tools {
             tools.register(ReadFileTool::new(config.sandbox.allowed_dir.clone()));
             tools.register(WriteFileTool::new(config.sandbox.allowed_dir.clone()));
-            tools.register(SystemCommandTool::with_config_and_env(
-                config.sandbox.allowed_dir.clone(),
-                config.sandbox.agent_user.clone(),
-                &config.sandbox.execution_env.to_string(),
-            ));
+            tools.register(DiffTool::new(config.sandbox.allowed_dir.clone()));
+            let passthrough_env: Vec<&str> =
+                config.sandbox.passthrough_env_vars.iter().map(String::as_str).collect();
+            tools.register(
+                SystemCommandTool::with_config_and_env(
+                    config.sandbox.allowed_dir.clone(),
+                    config.sandbox.agent_user.clone(),
+                    &config.sandbox.execution_env.to_string(),
+                )
+                .with_env_passthrough(&passthrough_env),
+            );
             tools.register(DuckDuckGoSearchTool::new());
+            tools.register(FetchPageTool::new());
             
             if let Some(brave) = BraveSearchTool::from_env() {
                 info!("Brave Search enabled");
+                tools.register(brave.image_search());
                 tools.register(brave);
             }
             if let Some(perplexity) = PerplexitySearchTool::from_env() {
@@ -146,15 +168,20 @@ impl TuiState {
             }
         }
 
+        let mut skills = SkillRegistry::new();
+        skills.register(InstallPackageSkill::new());
+
         Ok(TuiState {
             config,
             llm_client,
             conversation,
             tools,
+            skills,
             current_model,
             verbose: args.verbose,
             tools_enabled: !args.no_tools,
             memory_retriever,
+            task_store,
             user_id,
         })
     }
@@ -203,13 +230,15 @@ fn print_help(has_memory: bool) {
     println!("   {}", style("Available Commands:").cyan().bold());
     println!("   {}    - Exit TUI", style("/quit").yellow());
     println!("   {}   - Clear conversation history", style("/clear").yellow());
-    println!("   {}   - List available tools", style("/tools").yellow());
+    println!("   {}   - List available tools (/tools <name> explains one)", style("/tools").yellow());
+    println!("   {}   - Run a skill (e.g., /skill install_package {{\"package\": \"jq\"}})", style("/skill").yellow());
     println!("   {}   - Show current model", style("/model").yellow());
     println!("   {} - Toggle verbose mode", style("/verbose").yellow());
     println!("   {} - Show conversation history", style("/history").yellow());
     if has_memory {
         println!("   {}  - Search memories (e.g., /search rust)", style("/search").yellow());
         println!("   {}  - Show memory status", style("/memory").yellow());
+        println!("   {} - Browse saved memories page by page", style("/memories").yellow());
     }
     println!("   {}    - Show this help", style("/help").yellow());
     println!();
@@ -230,18 +259,27 @@ fn print_tools(state: &TuiState) {
     println!();
 }
 
+fn explain_tool(state: &TuiState, name: &str) {
+    println!();
+    match state.tools.explain(name) {
+        Some(explanation) => {
+            for line in explanation.to_string().lines() {
+                println!("   {}", line);
+            }
+        }
+        None => println!("   {} Unknown tool: {}", style("✗").red(), name),
+    }
+    println!();
+}
+
 fn print_history(state: &TuiState) {
     println!();
     println!("   {}", style("Conversation History:").cyan().bold());
     println!();
     
-    let messages = state.conversation.get_api_messages();
     let mut count = 0;
     
-    for msg in &messages {
-        if msg.role == Role::System {
-            continue; // Skip system prompt
-        }
+    for msg in state.conversation.get_last_n_messages(usize::MAX, true) {
         count += 1;
         let role_str = msg.role.to_string();
         let role_style = match msg.role {
@@ -275,28 +313,58 @@ fn format_tool_result(result: &str, max_len: usize) -> String {
     }
 }
 
-/// Callback for the TUI agentic loop: prints thinking indicator and tool results.
-struct TuiCallback;
+/// Callback for the TUI agentic loop: prints thinking indicator, the
+/// response as it streams in, and tool results.
+#[derive(Default)]
+struct TuiCallback {
+    /// Response text printed during the current iteration
+    streamed: Mutex<String>,
+    /// Whether the cursor is still on the line of streamed text
+    line_open: AtomicBool,
+}
+
+impl TuiCallback {
+    /// Clear the thinking indicator, or end the line of streamed text
+    fn end_line(&self) {
+        if self.line_open.swap(false, Ordering::SeqCst) {
+            println!();
+        } else {
+            let _ = Term::stdout().clear_line();
+            print!("\r");
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl LoopCallback for TuiCallback {
     async fn on_iteration_start(&self, _iteration: u32) {
+        self.streamed.lock().unwrap_or_else(|e| e.into_inner()).clear();
         print!("   {} ", style("●●●").dim());
         let _ = io::stdout().flush();
     }
 
+    async fn on_content_delta(&self, delta: &str) {
+        let mut streamed = self.streamed.lock().unwrap_or_else(|e| e.into_inner());
+        if !self.line_open.swap(true, Ordering::SeqCst) {
+            // Replace the thinking indicator
+            let _ = Term::stdout().clear_line();
+            print!("\r   {}: ", style("Agent").cyan().bold());
+        }
+        print!("{}", delta);
+        let _ = io::stdout().flush();
+        streamed.push_str(delta);
+    }
+
     async fn on_tool_executed(&self, tool_name: &str, observation: &ToolObservation) {
         // Clear thinking indicator on first tool result
-        let term = Term::stdout();
-        let _ = term.clear_line();
-        print!("\r");
+        self.end_line();
 
         // Build a pseudo ToolCall for display
         let emoji = match tool_name {
             "read_file" => "📖",
             "write_file" => "✏️",
             "system_command" => "⚡",
-            "duckduckgo_search" | "brave_search" | "perplexity_search" => "🔍",
+            "duckduckgo_search" | "brave_search" | "brave_image_search" | "perplexity_search" => "🔍",
             _ => "🔧",
         };
         println!("   {} {}", emoji, style(tool_name).yellow().bold());
@@ -314,14 +382,14 @@ impl LoopCallback for TuiCallback {
 
     async fn on_iteration_end(&self, _step: &agentic_loop::LoopStep) {
         // Clear thinking indicator if no tools were called this iteration
-        let term = Term::stdout();
-        let _ = term.clear_line();
-        print!("\r");
+        self.end_line();
     }
 }
 
 /// Run the agent loop with tool support
-async fn agent_loop(state: &mut TuiState, user_input: &str) -> Result<String> {
+///
+/// Returns the response and whether it was already printed while streaming.
+async fn agent_loop(state: &mut TuiState, user_input: &str) -> Result<(String, bool)> {
     // Add user message to conversation
     state.conversation.add_user_message(user_input);
 
@@ -347,17 +415,22 @@ async fn agent_loop(state: &mut TuiState, user_input: &str) -> Result<String> {
         vec![]
     };
 
-    let tui_callback = TuiCallback;
+    let tui_callback = TuiCallback::default();
+
+    // Re-read so preferences added while the TUI runs apply to the next turn
+    let soul = Soul::load_or_default();
 
     let loop_input = AgentLoopInput {
         messages,
         llm_client: &state.llm_client,
         tools: &state.tools,
         tool_definitions,
-        config: LoopConfig::tui(),
+        config: LoopConfig::tui()
+            .with_tool_preferences(soul.tool_preferences())
+            .with_streaming(),
         user_id: Some(state.user_id.clone()),
         chat_id: None,
-        callback: tui_callback,
+        callback: &tui_callback,
     };
 
     let output = agentic_loop::run_agentic_loop(loop_input).await?;
@@ -367,7 +440,159 @@ async fn agent_loop(state: &mut TuiState, user_input: &str) -> Result<String> {
         state.conversation.add_assistant_message(&output.response);
     }
 
-    Ok(output.response)
+    let shown = *tui_callback.streamed.lock().unwrap_or_else(|e| e.into_inner()) == output.response;
+    Ok((output.response, shown))
+}
+
+/// Memories shown per page by `/memories`
+const MEMORY_PAGE_SIZE: u32 = 10;
+
+/// First `max_chars` characters of `text` on one line
+fn preview(text: &str, max_chars: usize) -> String {
+    let line = text.replace('\n', " ");
+    match line.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &line[..end]),
+        None => line,
+    }
+}
+
+/// Browse the user's memories with arrow keys, newest first
+///
+/// Enter on a memory shows it in full; the last entries of each page move
+/// between pages. Esc or `Done` returns to the prompt.
+async fn browse_memories(state: &TuiState) {
+    let Some(ref retriever) = state.memory_retriever else {
+        println!("   {} Memory not enabled. Use {} flag.\n", style("⚠").yellow(), style("--memory").cyan());
+        return;
+    };
+
+    let mut page = 1;
+    let mut selected = 0;
+    loop {
+        let memories = match retriever.store().page_all(&state.user_id, page, MEMORY_PAGE_SIZE).await {
+            Ok(memories) => memories,
+            Err(e) => {
+                println!("   {} Failed to load memories: {}\n", style("❌").red(), e);
+                return;
+            }
+        };
+        if memories.total_items == 0 {
+            println!("   {} No memories saved yet.\n", style("ℹ").blue());
+            return;
+        }
+        if memories.items.is_empty() {
+            // Memories were deleted since the last page was shown
+            page = memories.total_pages;
+            continue;
+        }
+
+        let mut items: Vec<String> = memories
+            .items
+            .iter()
+            .map(|m| format!("[{}] {}", m.created_at.format("%Y-%m-%d %H:%M"), preview(&m.content, 80)))
+            .collect();
+        let mut nav_item = |label: &str| {
+            items.push(label.to_string());
+            Some(items.len() - 1)
+        };
+        let previous = if page > 1 { nav_item("← Previous page") } else { None };
+        let next = if page < memories.total_pages { nav_item("Next page →") } else { None };
+        items.push("Done".to_string());
+
+        println!();
+        let choice = Select::with_theme(&theme())
+            .with_prompt(format!(
+                "Memories (page {} of {}, {} total)",
+                page, memories.total_pages, memories.total_items
+            ))
+            .items(&items)
+            .default(selected.min(items.len() - 1))
+            .interact_opt();
+        let choice = match choice {
+            Ok(Some(choice)) => choice,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Memory browser failed: {}", e);
+                break;
+            }
+        };
+
+        if Some(choice) == previous {
+            page -= 1;
+            selected = 0;
+        } else if Some(choice) == next {
+            page += 1;
+            selected = 0;
+        } else if let Some(memory) = memories.items.get(choice) {
+            selected = choice;
+            println!();
+            println!("   {} {}", style("ID:").dim(), memory.id);
+            println!("   {} {}", style("Created:").dim(), memory.created_at.format("%Y-%m-%d %H:%M"));
+            println!("   {} {} ({:.2} importance)", style("Type:").dim(), memory.memory_type, memory.importance);
+            if !memory.tags.is_empty() {
+                println!("   {} {}", style("Tags:").dim(), memory.tags.join(", "));
+            }
+            println!();
+            println!("   {}", memory.content);
+        } else {
+            break;
+        }
+    }
+    println!();
+}
+
+/// Run a skill, confirming each destructive step at the prompt
+async fn run_skill(state: &TuiState, args: &str) {
+    let (name, params) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, ""), |(name, params)| (name, params.trim()));
+    let Some(skill) = state.skills.get(name) else {
+        let mut names = state.skills.names();
+        names.sort();
+        println!("   {} Usage: /skill <name> [JSON parameters]", style("ℹ").blue());
+        println!("   {} Skills: {}\n", style("ℹ").blue(), names.join(", "));
+        return;
+    };
+    if let Some(tool) = skill.required_tools().into_iter().find(|t| state.tools.get(t).is_none()) {
+        println!("   {} Skill '{}' needs the {} tool.\n", style("⚠").yellow(), name, style(tool).cyan());
+        return;
+    }
+
+    let mut params: HashMap<String, serde_json::Value> = if params.is_empty() {
+        HashMap::new()
+    } else {
+        match serde_json::from_str(params) {
+            Ok(params) => params,
+            Err(e) => {
+                println!("   {} Skill parameters must be a JSON object: {}\n", style("❌").red(), e);
+                return;
+            }
+        }
+    };
+    // The session identity always wins over caller-supplied parameters
+    params.insert("user_id".to_string(), serde_json::json!(state.user_id));
+
+    let ctx = SkillContext::new(&state.tools, params).with_approval_callback(|step| {
+        Confirm::with_theme(&theme())
+            .with_prompt(format!("Allow: {}?", step))
+            .default(false)
+            .interact()
+            .unwrap_or(false)
+    });
+
+    println!();
+    match skill.execute(&ctx).await {
+        Ok(result) => {
+            let icon = if result.success { style("✓").green() } else { style("❌").red() };
+            println!("   {} {}", icon, result.summary);
+            for step in &result.steps {
+                let mark = if step.success { style("•").dim() } else { style("✗").red() };
+                println!("     {} {}", mark, step.description);
+            }
+        }
+        Err(e) => println!("   {} Skill '{}' failed: {}", style("❌").red(), name, e),
+    }
+    println!();
 }
 
 /// Main REPL loop
@@ -469,11 +694,26 @@ async fn run_repl(mut state: TuiState) -> Result<()> {
                                 }
                             });
                         }
+                        if let Some(ref task_store) = state.task_store {
+                            let messages = state.conversation.messages.clone();
+                            let summarizer = ConversationSummarizer::new(state.llm_client.clone());
+                            let task_store = task_store.clone();
+                            let uid = state.user_id.clone();
+                            tokio::spawn(async move {
+                                match summarizer.create_action_item_tasks(&task_store, &uid, None, &messages).await {
+                                    Ok(tasks) if !tasks.is_empty() => {
+                                        info!("Created {} action item tasks", tasks.len());
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => warn!("Action item extraction failed: {}", e),
+                                }
+                            });
+                        }
                     }
 
                     let soul = Soul::load_or_default();
                     state.conversation = Conversation::new("tui-user", &state.current_model)
-                        .with_system_prompt(soul.as_system_prompt());
+                        .with_system_prompt(soul.as_system_prompt_for_model(&state.current_model));
                     let term = Term::stdout();
                     let _ = term.clear_screen();
                     print_banner();
@@ -484,6 +724,10 @@ async fn run_repl(mut state: TuiState) -> Result<()> {
                     print_tools(&state);
                     continue;
                 }
+                cmd if cmd.starts_with("/tools ") => {
+                    explain_tool(&state, cmd["/tools ".len()..].trim());
+                    continue;
+                }
                 "/model" | "/m" => {
                     println!();
                     println!("   {} Current model: {}", style("ℹ").blue(), style(&state.current_model).cyan());
@@ -507,6 +751,10 @@ async fn run_repl(mut state: TuiState) -> Result<()> {
                     print_help(has_memory);
                     continue;
                 }
+                cmd if cmd == "/skill" || cmd.starts_with("/skill ") => {
+                    run_skill(&state, input["/skill".len()..].trim()).await;
+                    continue;
+                }
                 cmd if cmd.starts_with("/search ") => {
                     if !has_memory {
                         println!("   {} Memory not enabled. Use {} flag.\n", 
@@ -539,6 +787,10 @@ async fn run_repl(mut state: TuiState) -> Result<()> {
                     }
                     continue;
                 }
+                "/memories" => {
+                    browse_memories(&state).await;
+                    continue;
+                }
                 "/memory" => {
                     println!();
                     if has_memory {
@@ -561,13 +813,15 @@ async fn run_repl(mut state: TuiState) -> Result<()> {
         
         // Run agent loop
         match agent_loop(&mut state, input).await {
-            Ok(response) => {
+            Ok((response, shown)) => {
                 // Save to memory if enabled
                 state.save_to_memory(input, "user").await;
                 state.save_to_memory(&response, "assistant").await;
                 
-                println!();
-                println!("   {}: {}", style("Agent").cyan().bold(), response);
+                if !shown {
+                    println!();
+                    println!("   {}: {}", style("Agent").cyan().bold(), response);
+                }
                 println!();
             }
             Err(e) => {
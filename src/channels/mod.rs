@@ -0,0 +1,13 @@
+//! Channel implementations
+//!
+//! Concrete messaging platforms implementing `core::Channel`. Telegram
+//! updates are dispatched directly by the gateway binary via teloxide.
+//!
+//! - **slack**: Slack Events API and slash commands
+//! - **telegram**: Telegram replies and typing indicators
+
+pub mod slack;
+pub mod telegram;
+
+pub use slack::SlackChannel;
+pub use telegram::TelegramChannel;
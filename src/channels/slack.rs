@@ -0,0 +1,567 @@
+//! Slack channel
+//!
+//! Receives messages through the Slack Events API and `/openagent` slash
+//! commands, and replies with `chat.postMessage`. Slack expects an HTTP 200
+//! within 3 seconds, so every request is acknowledged immediately and the
+//! agent's answer follows as a separate message. Answers to slash commands
+//! go to the command's `response_url` while it is valid, falling back to
+//! `chat.postMessage` if that fails.
+//!
+//! Mount [`SlackChannel::router`] on the gateway HTTP server and point the
+//! Slack app's Event Subscriptions at `/slack/events` and the slash command
+//! at `/slack/commands`.
+
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use secrecy::ExposeSecret;
+use slack_morphism::prelude::*;
+use slack_morphism::signature_verifier::SlackEventSignatureVerifier;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::config::{DmPolicy, SlackConfig};
+use crate::core::channel::{
+    Channel, ChannelCapabilities, ChannelMessage, ChannelMeta, ChannelReply, ChannelStatus,
+    MessageContent, MessageHandler, MessageId,
+};
+use crate::error::{Error, Result};
+
+/// Slash command handled by this channel
+pub const SLASH_COMMAND: &str = "/openagent";
+
+/// Slack's maximum `text` length for `chat.postMessage`
+const MAX_MESSAGE_LENGTH: usize = 40_000;
+
+/// How long Slack accepts posts to a slash command's `response_url`
+const RESPONSE_URL_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Whether an incoming message should reach the agent
+#[derive(Debug, PartialEq, Eq)]
+enum Admission {
+    /// Pass to the handler
+    Accept,
+    /// Drop silently (bot messages, unmentioned channel chatter)
+    Ignore,
+    /// Drop and tell the sender why
+    Reject(&'static str),
+}
+
+/// Slack channel backed by the Events API
+pub struct SlackChannel {
+    meta: ChannelMeta,
+    capabilities: ChannelCapabilities,
+    config: SlackConfig,
+    client: SlackHyperClient,
+    token: SlackApiToken,
+    verifier: SlackEventSignatureVerifier,
+    bot_user_id: RwLock<Option<String>>,
+    handler: RwLock<Option<MessageHandler>>,
+    status: RwLock<ChannelStatus>,
+    /// Client for posting to `response_url`s
+    http: reqwest::Client,
+    /// `response_url` of pending slash commands, by trigger ID
+    response_urls: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl SlackChannel {
+    /// Create a Slack channel. The signing secret is required to verify
+    /// Events API requests.
+    pub fn new(config: SlackConfig) -> Result<Self> {
+        let signing_secret = config
+            .signing_secret
+            .clone()
+            .filter(|s| !s.is_empty())
+            .ok_or_else(|| {
+                Error::Config("Slack signing_secret is required for the Events API".to_string())
+            })?;
+
+        let connector = SlackClientHyperConnector::new()
+            .map_err(|e| Error::Slack(format!("Failed to create HTTP connector: {}", e)))?;
+        let token = SlackApiToken::new(SlackApiTokenValue::new(
+            config.bot_token.expose_secret().to_string(),
+        ));
+
+        Ok(Self {
+            meta: ChannelMeta {
+                id: "slack".to_string(),
+                label: "Slack".to_string(),
+                description: "Slack Events API and /openagent slash command".to_string(),
+                docs_path: None,
+            },
+            capabilities: ChannelCapabilities {
+                text: true,
+                groups: true,
+                webhooks: true,
+                max_message_length: Some(MAX_MESSAGE_LENGTH),
+                ..Default::default()
+            },
+            verifier: SlackEventSignatureVerifier::new(&SlackSigningSecret::new(signing_secret)),
+            client: SlackClient::new(connector),
+            token,
+            config,
+            bot_user_id: RwLock::new(None),
+            handler: RwLock::new(None),
+            status: RwLock::new(ChannelStatus {
+                configured: true,
+                running: false,
+                last_start_at: None,
+                last_stop_at: None,
+                last_error: None,
+            }),
+            http: reqwest::Client::new(),
+            response_urls: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// HTTP routes for Slack callbacks: `POST /slack/events` and `POST /slack/commands`
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/slack/events", post(handle_events))
+            .route("/slack/commands", post(handle_command))
+            .with_state(self)
+    }
+
+    fn verify(&self, headers: &HeaderMap, body: &str) -> bool {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let (Some(signature), Some(timestamp)) = (
+            header(SlackEventSignatureVerifier::SLACK_SIGNED_HASH_HEADER),
+            header(SlackEventSignatureVerifier::SLACK_SIGNED_TIMESTAMP),
+        ) else {
+            return false;
+        };
+
+        match self.verifier.verify(signature, body, timestamp) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Rejected Slack request: {}", e);
+                false
+            }
+        }
+    }
+
+    async fn dispatch(&self, message: ChannelMessage) {
+        let Some(handler) = self.handler.read().await.clone() else {
+            debug!("Slack message received before channel start, dropping");
+            return;
+        };
+        tokio::spawn(async move {
+            if let Err(e) = handler(message).await {
+                warn!("Slack message handler failed: {}", e);
+            }
+        });
+    }
+
+    /// Remember a slash command's `response_url` for the reply to `trigger_id`
+    async fn remember_response_url(&self, trigger_id: &str, url: String) {
+        let mut urls = self.response_urls.lock().await;
+        urls.retain(|_, (_, at)| at.elapsed() < RESPONSE_URL_TTL);
+        urls.insert(trigger_id.to_string(), (url, Instant::now()));
+    }
+
+    /// Take the still-valid `response_url` for a reply to `trigger_id`
+    async fn take_response_url(&self, trigger_id: &str) -> Option<String> {
+        let (url, at) = self.response_urls.lock().await.remove(trigger_id)?;
+        (at.elapsed() < RESPONSE_URL_TTL).then_some(url)
+    }
+
+    /// Post `text` to a slash command's `response_url`, visible to the channel
+    async fn post_to_response_url(&self, url: &str, text: &str) -> Result<()> {
+        let response = self
+            .http
+            .post(url)
+            .json(&serde_json::json!({ "response_type": "in_channel", "text": text }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Error::Slack(format!("response_url returned {}", response.status())));
+        }
+        Ok(())
+    }
+
+    async fn notify(&self, channel: &str, text: &str) {
+        if let Err(e) = self.send(ChannelReply::text(channel, text)).await {
+            warn!("Failed to send Slack notice: {}", e);
+        }
+    }
+
+    async fn on_message(&self, event: SlackMessageEvent) {
+        // Skip edits, joins, bot posts (including our own replies)
+        if event.subtype.is_some() || event.sender.bot_id.is_some() {
+            return;
+        }
+        let (Some(user), Some(channel)) = (event.sender.user.as_ref(), event.origin.channel.as_ref())
+        else {
+            return;
+        };
+        let text = event
+            .content
+            .as_ref()
+            .and_then(|c| c.text.clone())
+            .unwrap_or_default();
+
+        let bot_mention = self
+            .bot_user_id
+            .read()
+            .await
+            .as_ref()
+            .map(|id| format!("<@{}>", id));
+        if bot_mention.as_deref().is_some_and(|m| format!("<@{}>", user.0) == m) {
+            return;
+        }
+        let mentioned = bot_mention.as_deref().is_some_and(|m| text.contains(m));
+        let is_dm = event.origin.channel_type.as_ref().is_some_and(|t| t.0 == "im");
+
+        match admit(&self.config, is_dm, &channel.0, &user.0, mentioned) {
+            Admission::Accept => {}
+            Admission::Ignore => return,
+            Admission::Reject(reason) => return self.notify(&channel.0, reason).await,
+        }
+
+        let text = match bot_mention {
+            Some(m) => text.replace(&m, "").trim().to_string(),
+            None => text,
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        self.dispatch(ChannelMessage {
+            id: event.origin.ts.0.clone(),
+            channel_id: self.meta.id.clone(),
+            conversation_id: channel.0.clone(),
+            sender_id: user.0.clone(),
+            sender_name: None,
+            content: MessageContent::text(text),
+            timestamp: parse_ts(&event.origin.ts.0),
+            reply_to: event.origin.thread_ts.map(|ts| ts.0),
+            is_group: !is_dm,
+            raw: None,
+        })
+        .await;
+    }
+
+    async fn on_command(&self, command: SlackCommandEvent) -> Json<serde_json::Value> {
+        let ephemeral = |text: &str| Json(serde_json::json!({ "response_type": "ephemeral", "text": text }));
+
+        if command.command.0 != SLASH_COMMAND {
+            return ephemeral("Unknown command.");
+        }
+        let text = command.text.clone().unwrap_or_default();
+        if text.trim().is_empty() {
+            return ephemeral("Usage: /openagent <message>");
+        }
+
+        // Slack DM channel IDs start with 'D'
+        let is_dm = command.channel_id.0.starts_with('D');
+        match admit(&self.config, is_dm, &command.channel_id.0, &command.user_id.0, true) {
+            Admission::Accept => {}
+            Admission::Ignore => return ephemeral("OpenAgent is disabled in this channel."),
+            Admission::Reject(reason) => return ephemeral(reason),
+        }
+
+        self.remember_response_url(&command.trigger_id.0, command.response_url.0.to_string())
+            .await;
+        self.dispatch(ChannelMessage {
+            id: command.trigger_id.0.clone(),
+            channel_id: self.meta.id.clone(),
+            conversation_id: command.channel_id.0.clone(),
+            sender_id: command.user_id.0.clone(),
+            sender_name: None,
+            content: MessageContent::text(text),
+            timestamp: Utc::now(),
+            reply_to: None,
+            is_group: !is_dm,
+            raw: Some(serde_json::json!({
+                "command": command.command.0,
+                "response_url": command.response_url.0.to_string(),
+            })),
+        })
+        .await;
+
+        ephemeral("Working on it…")
+    }
+}
+
+#[async_trait]
+impl Channel for SlackChannel {
+    fn meta(&self) -> &ChannelMeta {
+        &self.meta
+    }
+
+    fn capabilities(&self) -> &ChannelCapabilities {
+        &self.capabilities
+    }
+
+    async fn status(&self) -> Result<ChannelStatus> {
+        Ok(self.status.read().await.clone())
+    }
+
+    async fn start(&self, handler: MessageHandler) -> Result<()> {
+        let session = self.client.open_session(&self.token);
+        let auth = session.auth_test().await.map_err(|e| {
+            Error::Slack(format!("auth.test failed (check the bot token): {}", e))
+        })?;
+        info!("Slack channel started as {} ({})", auth.user.as_deref().unwrap_or("bot"), auth.user_id.0);
+
+        *self.bot_user_id.write().await = Some(auth.user_id.0);
+        *self.handler.write().await = Some(handler);
+
+        let mut status = self.status.write().await;
+        status.running = true;
+        status.last_start_at = Some(Utc::now());
+        status.last_error = None;
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        *self.handler.write().await = None;
+
+        let mut status = self.status.write().await;
+        status.running = false;
+        status.last_stop_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn send(&self, reply: ChannelReply) -> Result<MessageId> {
+        let text = match &reply.content {
+            MessageContent::Text { text } => text.clone(),
+            _ => {
+                return Err(Error::NotSupported(
+                    "Slack channel only sends text".to_string(),
+                ))
+            }
+        };
+
+        // Answer slash commands through their response_url
+        if let Some(trigger_id) = reply.reply_to.as_deref() {
+            if let Some(url) = self.take_response_url(trigger_id).await {
+                match self.post_to_response_url(&url, &text).await {
+                    Ok(()) => return Ok(trigger_id.to_string()),
+                    Err(e) => warn!("Slack response_url failed, using chat.postMessage: {}", e),
+                }
+            }
+        }
+
+        // DMs are not threaded; slash command trigger IDs are not message
+        // timestamps, so those are posted unthreaded too
+        let is_dm = reply.conversation_id.starts_with('D');
+        let request = SlackApiChatPostMessageRequest::new(
+            SlackChannelId::new(reply.conversation_id),
+            SlackMessageContent::new().with_text(text),
+        )
+        .opt_thread_ts(reply.reply_to.filter(|id| !is_dm && is_ts(id)).map(SlackTs::new));
+
+        let session = self.client.open_session(&self.token);
+        let response = session
+            .chat_post_message(&request)
+            .await
+            .map_err(|e| Error::Slack(format!("chat.postMessage failed: {}", e)))?;
+        Ok(response.ts.0)
+    }
+}
+
+async fn handle_events(
+    State(channel): State<Arc<SlackChannel>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !channel.verify(&headers, &body) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    // We always ack within the deadline, so retries are duplicates
+    if headers.contains_key("x-slack-retry-num") {
+        return StatusCode::OK.into_response();
+    }
+
+    let event: SlackPushEvent = match serde_json::from_str(&body) {
+        Ok(event) => event,
+        Err(e) => {
+            warn!("Unparseable Slack event: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    match event {
+        SlackPushEvent::UrlVerification(v) => v.challenge.into_response(),
+        SlackPushEvent::EventCallback(callback) => {
+            if let SlackEventCallbackBody::Message(message) = callback.event {
+                channel.on_message(message).await;
+            }
+            StatusCode::OK.into_response()
+        }
+        SlackPushEvent::AppRateLimited(_) => {
+            warn!("Slack reports the app is rate limited");
+            StatusCode::OK.into_response()
+        }
+    }
+}
+
+async fn handle_command(
+    State(channel): State<Arc<SlackChannel>>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !channel.verify(&headers, &body) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match serde_urlencoded::from_str::<SlackCommandEvent>(&body) {
+        Ok(command) => channel.on_command(command).await.into_response(),
+        Err(e) => {
+            warn!("Unparseable Slack command: {}", e);
+            StatusCode::BAD_REQUEST.into_response()
+        }
+    }
+}
+
+/// Apply the DM policy and per-channel settings from `SlackConfig`
+fn admit(config: &SlackConfig, is_dm: bool, channel: &str, user: &str, mentioned: bool) -> Admission {
+    if is_dm {
+        let dm = &config.dm;
+        return match dm.policy {
+            DmPolicy::Open => Admission::Accept,
+            DmPolicy::Disabled => Admission::Reject("DMs are disabled for this bot."),
+            // Slack has no pairing flow; both policies use the allowlist
+            DmPolicy::Allowlist | DmPolicy::Pairing => {
+                if dm.allow_from.iter().any(|u| u == user) {
+                    Admission::Accept
+                } else {
+                    Admission::Reject("You are not authorized to message this bot.")
+                }
+            }
+        };
+    }
+
+    match config.channels.get(channel) {
+        Some(c) if !c.enabled => Admission::Ignore,
+        Some(c) if c.require_mention && !mentioned => Admission::Ignore,
+        _ => Admission::Accept,
+    }
+}
+
+/// Whether `id` is a Slack message timestamp ("1700000000.123456")
+fn is_ts(id: &str) -> bool {
+    id.split_once('.').is_some_and(|(secs, micros)| {
+        !secs.is_empty() && secs.bytes().chain(micros.bytes()).all(|b| b.is_ascii_digit())
+    })
+}
+
+/// Parse a Slack `ts` ("1700000000.123456") into a timestamp
+fn parse_ts(ts: &str) -> DateTime<Utc> {
+    let (secs, micros) = ts.split_once('.').unwrap_or((ts, "0"));
+    secs.parse()
+        .ok()
+        .and_then(|s| DateTime::from_timestamp(s, micros.parse::<u32>().unwrap_or(0) * 1000))
+        .unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ChannelSpecificConfig, DmConfig};
+    use secrecy::SecretString;
+    use std::collections::HashMap;
+
+    fn config(policy: DmPolicy) -> SlackConfig {
+        SlackConfig {
+            bot_token: SecretString::from("xoxb-test".to_string()),
+            app_token: SecretString::from(String::new()),
+            signing_secret: Some("secret".to_string()),
+            dm: DmConfig {
+                policy,
+                allow_from: vec!["U1".to_string()],
+            },
+            channels: HashMap::from([(
+                "C1".to_string(),
+                ChannelSpecificConfig {
+                    enabled: true,
+                    require_mention: true,
+                    tools: Default::default(),
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_dm_policy() {
+        assert_eq!(admit(&config(DmPolicy::Open), true, "D1", "U2", false), Admission::Accept);
+        assert_eq!(admit(&config(DmPolicy::Allowlist), true, "D1", "U1", false), Admission::Accept);
+        assert!(matches!(
+            admit(&config(DmPolicy::Allowlist), true, "D1", "U2", false),
+            Admission::Reject(_)
+        ));
+        assert!(matches!(
+            admit(&config(DmPolicy::Disabled), true, "D1", "U1", false),
+            Admission::Reject(_)
+        ));
+    }
+
+    #[test]
+    fn test_channel_requires_mention() {
+        let config = config(DmPolicy::Open);
+        assert_eq!(admit(&config, false, "C1", "U2", false), Admission::Ignore);
+        assert_eq!(admit(&config, false, "C1", "U2", true), Admission::Accept);
+        assert_eq!(admit(&config, false, "C2", "U2", false), Admission::Accept);
+    }
+
+    #[test]
+    fn test_new_requires_signing_secret() {
+        let mut config = config(DmPolicy::Open);
+        config.signing_secret = None;
+        assert!(matches!(SlackChannel::new(config), Err(Error::Config(_))));
+    }
+
+    #[tokio::test]
+    async fn test_command_reply_uses_response_url() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/commands/T1/abc"))
+            .and(body_partial_json(serde_json::json!({ "response_type": "in_channel", "text": "42" })))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let channel = SlackChannel::new(config(DmPolicy::Open)).unwrap();
+        let handler: MessageHandler = Arc::new(|_| Box::pin(async { Ok(()) }));
+        *channel.handler.write().await = Some(handler);
+        let command: SlackCommandEvent = serde_json::from_value(serde_json::json!({
+            "team_id": "T1",
+            "channel_id": "D1",
+            "user_id": "U1",
+            "command": SLASH_COMMAND,
+            "text": "what is six times seven",
+            "response_url": format!("{}/commands/T1/abc", server.uri()),
+            "trigger_id": "trigger-1",
+        }))
+        .unwrap();
+        let Json(ack) = channel.on_command(command).await;
+        assert_eq!(ack["response_type"], "ephemeral");
+
+        let reply = ChannelReply::text("D1", "42").with_reply_to("trigger-1");
+        assert_eq!(channel.send(reply).await.unwrap(), "trigger-1");
+        // The URL is used once; later replies go through chat.postMessage
+        assert!(channel.take_response_url("trigger-1").await.is_none());
+    }
+
+    #[test]
+    fn test_parse_ts() {
+        let ts = parse_ts("1700000000.123456");
+        assert_eq!(ts.timestamp(), 1_700_000_000);
+        assert_eq!(ts.timestamp_subsec_micros(), 123_456);
+        assert!(is_ts("1700000000.123456"));
+        assert!(!is_ts("13345224609.738474920.8088930838d88f008e0"));
+    }
+}
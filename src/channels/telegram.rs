@@ -0,0 +1,191 @@
+//! Telegram channel
+//!
+//! Outbound side of Telegram: sending replies and typing indicators through
+//! a teloxide [`Bot`]. Incoming updates are dispatched by the gateway
+//! binary's teloxide dispatcher, so [`Channel::start`] only marks the
+//! channel as running.
+//!
+//! Telegram shows "typing…" for about 5 seconds per chat action, and clears
+//! it as soon as the bot sends a message. [`TelegramChannel::send_typing_indicator`]
+//! repeats the action until its duration runs out or a reply is sent.
+
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::ChatAction;
+use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
+use tracing::debug;
+
+use crate::core::channel::{
+    Channel, ChannelCapabilities, ChannelMeta, ChannelReply, ChannelStatus, MessageContent,
+    MessageHandler, MessageId,
+};
+use crate::error::{Error, Result};
+
+/// Telegram's maximum message length
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// How often the typing action is repeated; Telegram shows it for ~5 seconds
+const TYPING_REFRESH: Duration = Duration::from_secs(5);
+
+/// Telegram channel backed by a teloxide bot
+pub struct TelegramChannel {
+    meta: ChannelMeta,
+    capabilities: ChannelCapabilities,
+    bot: Bot,
+    status: RwLock<ChannelStatus>,
+    /// Running typing indicator loops, by chat ID
+    typing: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl TelegramChannel {
+    pub fn new(bot: Bot) -> Self {
+        TelegramChannel {
+            meta: ChannelMeta {
+                id: "telegram".to_string(),
+                label: "Telegram".to_string(),
+                description: "Telegram Bot API".to_string(),
+                docs_path: Some("docs/details/channels.md".to_string()),
+            },
+            capabilities: ChannelCapabilities {
+                text: true,
+                images: true,
+                files: true,
+                groups: true,
+                typing_indicators: true,
+                max_message_length: Some(MAX_MESSAGE_LENGTH),
+                ..Default::default()
+            },
+            bot,
+            status: RwLock::new(ChannelStatus {
+                configured: true,
+                running: false,
+                last_start_at: None,
+                last_stop_at: None,
+                last_error: None,
+            }),
+            typing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stop the typing indicator loop for `chat_id`, if one is running
+    pub fn stop_typing(&self, chat_id: &str) {
+        if let Some(task) = self.typing.lock().unwrap_or_else(|e| e.into_inner()).remove(chat_id) {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for TelegramChannel {
+    fn drop(&mut self) {
+        for task in self.typing.get_mut().unwrap_or_else(|e| e.into_inner()).values() {
+            task.abort();
+        }
+    }
+}
+
+fn parse_chat_id(chat_id: &str) -> Result<ChatId> {
+    chat_id
+        .parse()
+        .map(ChatId)
+        .map_err(|_| Error::InvalidInput(format!("Invalid Telegram chat ID: {}", chat_id)))
+}
+
+#[async_trait]
+impl Channel for TelegramChannel {
+    fn meta(&self) -> &ChannelMeta {
+        &self.meta
+    }
+
+    fn capabilities(&self) -> &ChannelCapabilities {
+        &self.capabilities
+    }
+
+    async fn status(&self) -> Result<ChannelStatus> {
+        Ok(self.status.read().await.clone())
+    }
+
+    async fn start(&self, _handler: MessageHandler) -> Result<()> {
+        let mut status = self.status.write().await;
+        status.running = true;
+        status.last_start_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<()> {
+        let mut status = self.status.write().await;
+        status.running = false;
+        status.last_stop_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn send(&self, reply: ChannelReply) -> Result<MessageId> {
+        let MessageContent::Text { text } = &reply.content else {
+            return Err(Error::NotSupported("Telegram channel only sends text".to_string()));
+        };
+        self.stop_typing(&reply.conversation_id);
+        let message = self
+            .bot
+            .send_message(parse_chat_id(&reply.conversation_id)?, text)
+            .await
+            .map_err(|e| Error::Telegram(e.to_string()))?;
+        Ok(message.id.0.to_string())
+    }
+
+    async fn send_typing(&self, conversation_id: &str) -> Result<()> {
+        self.bot
+            .send_chat_action(parse_chat_id(conversation_id)?, ChatAction::Typing)
+            .await
+            .map_err(|e| Error::Telegram(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn send_typing_indicator(&self, chat_id: &str, duration_secs: u64) -> Result<()> {
+        // The first action is sent here so that errors reach the caller
+        self.send_typing(chat_id).await?;
+
+        let chat = parse_chat_id(chat_id)?;
+        let bot = self.bot.clone();
+        let task = tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            let deadline = start + Duration::from_secs(duration_secs);
+            let mut refresh = tokio::time::interval_at(start + TYPING_REFRESH, TYPING_REFRESH);
+            while refresh.tick().await < deadline {
+                if let Err(e) = bot.send_chat_action(chat, ChatAction::Typing).await {
+                    debug!("Typing indicator for chat {} stopped: {}", chat, e);
+                    break;
+                }
+            }
+        });
+
+        let previous = self
+            .typing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(chat_id.to_string(), task.abort_handle());
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_typing_indicator_requires_numeric_chat_id() {
+        let channel = TelegramChannel::new(Bot::new("123:test"));
+        assert!(channel.capabilities().typing_indicators);
+        assert_eq!(parse_chat_id("-100123").unwrap(), ChatId(-100123));
+        let err = channel.send_typing_indicator("general", 10).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid Telegram chat ID"));
+        // Stopping a chat without an indicator is a no-op
+        channel.stop_typing("42");
+    }
+}
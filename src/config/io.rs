@@ -4,6 +4,10 @@
 
 use std::path::Path;
 
+use serde::Serialize;
+use serde_json::Value;
+use tracing::info;
+
 use super::types::Config;
 use crate::error::{Error, Result};
 
@@ -22,17 +26,96 @@ pub struct ConfigSnapshot {
     pub issues: Vec<String>,
 }
 
+/// A single field that differs between two configuration snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    /// JSON Pointer (RFC 6901) to the changed field, e.g. `/gateway/port`
+    pub field_path: String,
+    /// Value in `self` (`null` if absent)
+    pub old_value: Value,
+    /// Value in `other` (`null` if absent)
+    pub new_value: Value,
+}
+
+impl ConfigSnapshot {
+    /// Snapshot of an in-memory configuration that is not backed by a file
+    pub fn from_config(config: Config) -> Self {
+        ConfigSnapshot {
+            path: std::path::PathBuf::new(),
+            exists: false,
+            raw: None,
+            config: Some(config),
+            issues: Vec::new(),
+        }
+    }
+
+    /// Copy of this snapshot with the parsed configuration redacted
+    ///
+    /// Diff redacted snapshots before displaying the changes, so plain-text
+    /// credentials show up masked rather than verbatim.
+    pub fn redacted(&self) -> Self {
+        ConfigSnapshot {
+            config: self.config.as_ref().map(Config::to_redacted),
+            raw: None,
+            ..self.clone()
+        }
+    }
+
+    /// List the fields that differ from `other`.
+    ///
+    /// Objects are compared field by field; arrays and scalars as whole values.
+    /// `SecretString` fields are never serialized, so they never appear in
+    /// the diff; diff [`redacted`](Self::redacted) snapshots to mask the
+    /// plain-text credentials as well.
+    pub fn diff(&self, other: &ConfigSnapshot) -> Vec<ConfigChange> {
+        let to_value = |snapshot: &ConfigSnapshot| {
+            snapshot
+                .config
+                .as_ref()
+                .and_then(|c| serde_json::to_value(c).ok())
+                .unwrap_or(Value::Null)
+        };
+
+        let mut changes = Vec::new();
+        diff_values(&mut String::new(), &to_value(self), &to_value(other), &mut changes);
+        changes
+    }
+}
+
+fn diff_values(path: &mut String, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                diff_values(
+                    path,
+                    old_map.get(key).unwrap_or(&Value::Null),
+                    new_map.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+                path.truncate(len);
+            }
+        }
+        _ if old != new => changes.push(ConfigChange {
+            field_path: path.clone(),
+            old_value: old.clone(),
+            new_value: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
 /// Load configuration with layered precedence:
 /// 1. Config file (config.json) if it exists, otherwise defaults
 /// 2. Environment variable overrides (includes .env for backward compat)
 pub fn load_config() -> Result<Config> {
-    let config_path = super::paths::config_path();
-
-    let mut config = if config_path.exists() {
-        load_config_from_path(&config_path)?
-    } else {
-        Config::default()
-    };
+    let mut config = load_config_file()?;
 
     // Apply environment variable overrides (highest precedence)
     apply_env_overrides(&mut config);
@@ -40,6 +123,16 @@ pub fn load_config() -> Result<Config> {
     Ok(config)
 }
 
+/// The config file if it exists, otherwise defaults, without overrides
+pub(super) fn load_config_file() -> Result<Config> {
+    let config_path = super::paths::config_path();
+    if config_path.exists() {
+        load_config_from_path(&config_path)
+    } else {
+        Ok(Config::default())
+    }
+}
+
 /// Load configuration from a specific path
 pub fn load_config_from_path(path: &Path) -> Result<Config> {
     let content = std::fs::read_to_string(path).map_err(|e| {
@@ -76,13 +169,24 @@ pub fn load_config_from_env() -> Result<Config> {
 /// environment variables onto the config. Env vars have the highest
 /// precedence in the config layering: defaults < file < DB < env.
 pub fn apply_env_overrides(config: &mut Config) {
-    use secrecy::SecretString;
-
     // Load .env file if it exists (backward compat)
     dotenvy::dotenv().ok();
 
+    apply_overrides(config, "environment", |key| std::env::var(key).ok());
+}
+
+/// Overlay settings named by environment-style keys (`OPENROUTER_API_KEY`,
+/// `DATABASE_URL`, ...) onto `config`, looking each key up with `var`
+///
+/// Shared by [`apply_env_overrides`] and the Vault secrets backend, so both
+/// accept the same keys. `source` names where the values came from in logs.
+pub(super) fn apply_overrides(config: &mut Config, source: &str, var: impl Fn(&str) -> Option<String>) {
+    use secrecy::SecretString;
+
+    let before = ConfigSnapshot::from_config(config.clone());
+
     // OpenRouter overrides
-    if let Ok(api_key) = std::env::var("OPENROUTER_API_KEY") {
+    if let Some(api_key) = var("OPENROUTER_API_KEY") {
         let or = config.provider.openrouter.get_or_insert_with(|| {
             super::types::provider::OpenRouterConfig {
                 api_key: SecretString::from(String::new()),
@@ -96,34 +200,34 @@ pub fn apply_env_overrides(config: &mut Config) {
         });
         or.api_key = SecretString::from(api_key);
     }
-    if let Ok(model) = std::env::var("DEFAULT_MODEL").or_else(|_| std::env::var("OPENROUTER_MODEL")) {
+    if let Some(model) = var("DEFAULT_MODEL").or_else(|| var("OPENROUTER_MODEL")) {
         if let Some(ref mut or) = config.provider.openrouter {
             or.default_model = model;
         }
     }
-    if let Ok(url) = std::env::var("OPENROUTER_BASE_URL") {
+    if let Some(url) = var("OPENROUTER_BASE_URL") {
         if let Some(ref mut or) = config.provider.openrouter {
             or.base_url = url;
         }
     }
-    if let Ok(url) = std::env::var("OPENROUTER_SITE_URL") {
+    if let Some(url) = var("OPENROUTER_SITE_URL") {
         if let Some(ref mut or) = config.provider.openrouter {
             or.site_url = Some(url);
         }
     }
-    if let Ok(name) = std::env::var("OPENROUTER_SITE_NAME") {
+    if let Some(name) = var("OPENROUTER_SITE_NAME") {
         if let Some(ref mut or) = config.provider.openrouter {
             or.site_name = Some(name);
         }
     }
-    if let Ok(timeout) = std::env::var("OPENROUTER_TIMEOUT") {
+    if let Some(timeout) = var("OPENROUTER_TIMEOUT") {
         if let Some(ref mut or) = config.provider.openrouter {
             if let Ok(v) = timeout.parse() {
                 or.timeout_secs = v;
             }
         }
     }
-    if let Ok(retries) = std::env::var("OPENROUTER_MAX_RETRIES") {
+    if let Some(retries) = var("OPENROUTER_MAX_RETRIES") {
         if let Some(ref mut or) = config.provider.openrouter {
             if let Ok(v) = retries.parse() {
                 or.max_retries = v;
@@ -132,7 +236,7 @@ pub fn apply_env_overrides(config: &mut Config) {
     }
 
     // Telegram overrides
-    if let Ok(bot_token) = std::env::var("TELEGRAM_BOT_TOKEN") {
+    if let Some(bot_token) = var("TELEGRAM_BOT_TOKEN") {
         let tg = config.channels.telegram.get_or_insert_with(|| {
             super::types::channel::TelegramConfig {
                 bot_token: SecretString::from(String::new()),
@@ -146,7 +250,7 @@ pub fn apply_env_overrides(config: &mut Config) {
         });
         tg.bot_token = SecretString::from(bot_token);
     }
-    if let Ok(users) = std::env::var("TELEGRAM_ALLOWED_USERS") {
+    if let Some(users) = var("TELEGRAM_ALLOWED_USERS") {
         if let Some(ref mut tg) = config.channels.telegram {
             tg.allow_from = users
                 .split(',')
@@ -154,66 +258,120 @@ pub fn apply_env_overrides(config: &mut Config) {
                 .collect();
         }
     }
-    if let Ok(v) = std::env::var("TELEGRAM_USE_WEBHOOK") {
+    if let Some(v) = var("TELEGRAM_USE_WEBHOOK") {
         if let Some(ref mut tg) = config.channels.telegram {
             tg.use_long_polling = v != "true" && v != "1";
         }
     }
-    if let Ok(url) = std::env::var("TELEGRAM_WEBHOOK_URL") {
+    if let Some(url) = var("TELEGRAM_WEBHOOK_URL") {
         if let Some(ref mut tg) = config.channels.telegram {
             tg.webhook_url = Some(url);
         }
     }
-    if let Ok(secret) = std::env::var("TELEGRAM_WEBHOOK_SECRET") {
+    if let Some(secret) = var("TELEGRAM_WEBHOOK_SECRET") {
         if let Some(ref mut tg) = config.channels.telegram {
             tg.webhook_secret = Some(secret);
         }
     }
 
+    // CalDAV overrides
+    if let Some(url) = var("CALDAV_URL") {
+        let cal = config.channels.calendar.get_or_insert_with(|| {
+            super::types::channel::CalendarConfig {
+                caldav_url: String::new(),
+                username: String::new(),
+                password: SecretString::from(String::new()),
+            }
+        });
+        cal.caldav_url = url;
+    }
+    if let Some(username) = var("CALDAV_USERNAME") {
+        if let Some(ref mut cal) = config.channels.calendar {
+            cal.username = username;
+        }
+    }
+    if let Some(password) = var("CALDAV_PASSWORD") {
+        if let Some(ref mut cal) = config.channels.calendar {
+            cal.password = SecretString::from(password);
+        }
+    }
+
+    // Slack overrides
+    if let Some(bot_token) = var("SLACK_BOT_TOKEN") {
+        let slack = config.channels.slack.get_or_insert_with(|| {
+            super::types::channel::SlackConfig {
+                bot_token: SecretString::from(String::new()),
+                app_token: SecretString::from(String::new()),
+                signing_secret: None,
+                dm: Default::default(),
+                channels: std::collections::HashMap::new(),
+            }
+        });
+        slack.bot_token = SecretString::from(bot_token);
+    }
+    if let Some(secret) = var("SLACK_SIGNING_SECRET") {
+        if let Some(ref mut slack) = config.channels.slack {
+            slack.signing_secret = Some(secret);
+        }
+    }
+
     // Database overrides
-    if let Ok(database_url) = std::env::var("DATABASE_URL") {
+    if let Some(database_url) = var("DATABASE_URL") {
         let pg = config.storage.postgres.get_or_insert_with(|| {
             super::types::storage::PostgresConfig {
                 url: SecretString::from(String::new()),
-                max_connections: 5,
-                connect_timeout_secs: 30,
+                pool: Default::default(),
                 enable_pgvector: true,
             }
         });
         pg.url = SecretString::from(database_url);
         config.storage.backend = super::types::storage::StorageBackendType::Postgres;
     }
-    if let Ok(max_conn) = std::env::var("DATABASE_MAX_CONNECTIONS") {
+    if let Some(max_conn) = var("DATABASE_MAX_CONNECTIONS") {
         if let Some(ref mut pg) = config.storage.postgres {
             if let Ok(v) = max_conn.parse() {
-                pg.max_connections = v;
+                pg.pool.max_connections = v;
             }
         }
     }
-    if let Ok(timeout) = std::env::var("DATABASE_TIMEOUT") {
+    if let Some(timeout) = var("DATABASE_TIMEOUT") {
         if let Some(ref mut pg) = config.storage.postgres {
             if let Ok(v) = timeout.parse() {
-                pg.connect_timeout_secs = v;
+                pg.pool.acquire_timeout_secs = v;
             }
         }
     }
 
     // Sandbox overrides
-    if let Ok(env_str) = std::env::var("EXECUTION_ENV") {
+    if let Some(env_str) = var("EXECUTION_ENV") {
         if let Ok(exec_env) = env_str.parse() {
             config.sandbox.execution_env = exec_env;
         }
     }
-    if let Ok(allowed_dir) = std::env::var("ALLOWED_DIR") {
+    if let Some(allowed_dir) = var("ALLOWED_DIR") {
         config.sandbox.allowed_dir = std::path::PathBuf::from(allowed_dir);
     }
+    if let Some(vars) = var("PASSTHROUGH_ENV_VARS") {
+        config.sandbox.passthrough_env_vars = vars
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect();
+    }
+    if let Some(enabled) = var("GIT_WRITE_ENABLED") {
+        config.sandbox.git_write_enabled = matches!(enabled.to_lowercase().as_str(), "1" | "true" | "yes");
+    }
 
     // Gateway overrides
-    if let Ok(port) = std::env::var("GATEWAY_PORT") {
+    if let Some(port) = var("GATEWAY_PORT") {
         if let Ok(port) = port.parse() {
             config.gateway.port = port;
         }
     }
+
+    for change in before.diff(&ConfigSnapshot::from_config(config.clone())) {
+        info!("Config {} overridden by {}", change.field_path, source);
+    }
 }
 
 /// Save configuration to a file
@@ -234,7 +392,6 @@ pub fn save_config(config: &Config, path: &Path) -> Result<()> {
 }
 
 /// Read a configuration file into a snapshot
-#[allow(dead_code)]
 pub fn read_config_snapshot(path: &Path) -> ConfigSnapshot {
     if !path.exists() {
         return ConfigSnapshot {
@@ -297,4 +454,40 @@ mod tests {
         let loaded = load_config_from_path(&path).unwrap();
         assert_eq!(loaded.agent.model, config.agent.model);
     }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let old = ConfigSnapshot::from_config(Config::default());
+        assert!(old.diff(&old).is_empty());
+
+        let mut config = Config::default();
+        config.gateway.port = 9000;
+        config
+            .plugins
+            .insert("a/b".to_string(), serde_json::json!({"enabled": true}));
+        let new = ConfigSnapshot::from_config(config);
+
+        let changes = old.diff(&new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field_path, "/gateway/port");
+        assert_eq!(changes[0].old_value, serde_json::json!(18789));
+        assert_eq!(changes[0].new_value, serde_json::json!(9000));
+        assert_eq!(changes[1].field_path, "/plugins/a~1b");
+        assert_eq!(changes[1].old_value, Value::Null);
+    }
+
+    #[test]
+    fn test_redacted_snapshot_diff_masks_secrets() {
+        let mut config = Config::default();
+        config.gateway.auth.password = Some("correct horse battery staple".to_string());
+        let old = ConfigSnapshot::from_config(config.clone());
+        config.gateway.auth.password = Some("tr0ub4dor&3 is much weaker".to_string());
+        let new = ConfigSnapshot::from_config(config);
+
+        let changes = old.redacted().diff(&new.redacted());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field_path, "/gateway/auth/password");
+        assert_eq!(changes[0].old_value, serde_json::json!("corr...aple"));
+        assert_eq!(changes[0].new_value, serde_json::json!("tr0u...aker"));
+    }
 }
@@ -6,21 +6,27 @@
 //! - types/channel.rs: Channel-specific configuration
 //! - types/storage.rs: Storage backend configuration
 //! - types/sandbox.rs: Sandbox/execution configuration
+//! - types/mcp.rs: MCP server configuration
 //! - io.rs: Configuration loading and saving
+//! - vault.rs: Secrets from HashiCorp Vault
+//! - redact.rs: Secret masking for logs and status output
 //! - validation.rs: Configuration validation
 //! - paths.rs: Configuration file paths
 
 mod io;
 mod paths;
+mod redact;
 mod types;
 mod validation;
+mod vault;
 
 // Re-export core config types
-pub use types::{Config, AgentConfig, GatewayConfig, ThinkingLevel};
+pub use types::{Config, AgentConfig, AuthConfig, AuthMode, CorsConfig, GatewayConfig, RateLimitConfig, TelemetryConfig, ThinkingLevel, TlsConfig, VaultConfig};
 
 // Re-export channel types
 pub use types::channel::{
     ChannelsConfig, TelegramConfig, DiscordConfig, SlackConfig, WhatsAppConfig, DmPolicy,
+    CalendarConfig, DmConfig, ChannelSpecificConfig,
 };
 
 // Re-export provider types
@@ -30,18 +36,26 @@ pub use types::provider::{
 
 // Re-export storage types
 pub use types::storage::{
-    StorageConfig, PostgresConfig, SqliteConfig, EmbeddingConfig,
+    StorageConfig, PostgresConfig, PoolConfig, SqliteConfig, EmbeddingConfig, CachePolicy,
 };
 
 // Backward compatibility aliases
 pub type DatabaseConfig = PostgresConfig;
 
+// Re-export MCP types
+pub use types::mcp::{McpServerConfig, McpTransportConfig};
+
 // Re-export sandbox types
 pub use types::sandbox::{
-    SandboxConfig, ExecutionEnv, ContainerConfig, WasmConfig,
+    SandboxConfig, SandboxResourceLimits, ExecutionEnv, ContainerConfig, WasmConfig,
 };
 
 // Re-export IO and utilities
-pub use io::{load_config, save_config, apply_env_overrides, ConfigSnapshot};
+pub use io::{
+    load_config, save_config, apply_env_overrides, read_config_snapshot, ConfigChange,
+    ConfigSnapshot,
+};
+pub use redact::{is_secret_key, mask_secret, REDACTED};
 pub use paths::{config_dir, config_path, state_dir, workspace_dir};
 pub use validation::{validate_config, ConfigValidationResult};
+pub use vault::read_vault_secrets;
@@ -0,0 +1,247 @@
+//! Secret redaction for logging and display
+//!
+//! [`Config::to_redacted`] returns a copy of the configuration that can be
+//! printed or logged: `SecretString` fields read `[REDACTED]`, and plain-text
+//! tokens and passwords are masked down to their first and last 4 characters.
+//! Free-form maps (plugin settings, container environment) are masked by key
+//! name with [`is_secret_key`].
+
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+
+use super::types::Config;
+
+/// Replacement for secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Values this short are fully redacted: showing 8 characters would reveal
+/// most of them
+const MIN_MASKED_LEN: usize = 12;
+
+/// Key segments that name a credential on their own
+const SECRET_SEGMENTS: &[&str] = &[
+    "password", "passwd", "secret", "token", "apikey", "authorization", "credentials",
+];
+
+/// Segments that name a credential when followed by `key`
+/// (`api_key`, `privateKey`, `ACCESS_KEY`)
+const SECRET_KEY_QUALIFIERS: &[&str] = &["api", "private", "access", "signing", "encryption"];
+
+/// Whether a setting or parameter named `key` holds a credential
+///
+/// The name is split into segments on `_`, `-`, `.` and camelCase
+/// boundaries, and whole segments are compared, so `api_key`, `apiKey` and
+/// `DB_PASSWORD` match while `max_tokens` or `keyword` do not.
+pub fn is_secret_key(key: &str) -> bool {
+    let segments = key_segments(key);
+    segments.iter().enumerate().any(|(i, segment)| {
+        SECRET_SEGMENTS.contains(&segment.as_str())
+            || (segment == "key" && i > 0 && SECRET_KEY_QUALIFIERS.contains(&segments[i - 1].as_str()))
+    })
+}
+
+/// Lowercase segments of a key name
+fn key_segments(key: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower {
+            segments.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Mask `value` to its first and last 4 characters (`sk-a...xyz1`)
+///
+/// Short values are replaced with [`REDACTED`]; empty values stay empty so
+/// unset fields remain visible as unset.
+pub fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.is_empty() {
+        String::new()
+    } else if chars.len() < MIN_MASKED_LEN {
+        REDACTED.to_string()
+    } else {
+        let head: String = chars[..4].iter().collect();
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}...{}", head, tail)
+    }
+}
+
+fn redact_secret(secret: &mut SecretString) {
+    if !secret.expose_secret().is_empty() {
+        *secret = SecretString::from(REDACTED);
+    }
+}
+
+fn mask_string(value: &mut String) {
+    *value = mask_secret(value);
+}
+
+fn mask_option(value: &mut Option<String>) {
+    if let Some(value) = value {
+        mask_string(value);
+    }
+}
+
+/// Mask string values under secret-looking keys anywhere in `value`
+fn mask_json(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(mask_json),
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::String(text) if is_secret_key(key) => mask_string(text),
+                    _ => mask_json(value),
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Config {
+    /// Clone of this configuration that is safe to log or display
+    ///
+    /// Every `SecretString` (API keys, bot tokens, the database URL) becomes
+    /// `[REDACTED]`; plain-text credentials (gateway passwords, tokens and
+    /// JWT secrets, webhook and signing secrets, custom provider headers)
+    /// are masked with [`mask_secret`], as are plugin settings and container
+    /// environment variables whose names look like credentials.
+    pub fn to_redacted(&self) -> Config {
+        let mut config = self.clone();
+
+        let provider = &mut config.provider;
+        if let Some(openrouter) = provider.openrouter.as_mut() {
+            redact_secret(&mut openrouter.api_key);
+        }
+        if let Some(anthropic) = provider.anthropic.as_mut() {
+            redact_secret(&mut anthropic.api_key);
+        }
+        if let Some(openai) = provider.openai.as_mut() {
+            redact_secret(&mut openai.api_key);
+        }
+        for custom in provider.custom.values_mut() {
+            if let Some(api_key) = custom.api_key.as_mut() {
+                redact_secret(api_key);
+            }
+            custom.headers.values_mut().for_each(mask_string);
+        }
+
+        let channels = &mut config.channels;
+        if let Some(telegram) = channels.telegram.as_mut() {
+            redact_secret(&mut telegram.bot_token);
+            mask_option(&mut telegram.webhook_secret);
+        }
+        if let Some(discord) = channels.discord.as_mut() {
+            redact_secret(&mut discord.token);
+        }
+        if let Some(slack) = channels.slack.as_mut() {
+            redact_secret(&mut slack.bot_token);
+            redact_secret(&mut slack.app_token);
+            mask_option(&mut slack.signing_secret);
+        }
+        if let Some(calendar) = channels.calendar.as_mut() {
+            redact_secret(&mut calendar.password);
+        }
+
+        if let Some(postgres) = config.storage.postgres.as_mut() {
+            redact_secret(&mut postgres.url);
+        }
+
+        let auth = &mut config.gateway.auth;
+        mask_option(&mut auth.password);
+        auth.tokens.iter_mut().for_each(mask_string);
+        mask_option(&mut auth.jwt_secret);
+
+        if let Some(vault) = config.vault.as_mut() {
+            redact_secret(&mut vault.token);
+        }
+
+        config.plugins.values_mut().for_each(mask_json);
+        for (name, value) in config.sandbox.container.env.iter_mut() {
+            if is_secret_key(name) {
+                mask_string(value);
+            }
+        }
+
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret("sk-abcdefghijklmnxyz1"), "sk-a...xyz1");
+        assert_eq!(mask_secret("hunter2"), REDACTED);
+        assert_eq!(mask_secret(""), "");
+    }
+
+    #[test]
+    fn test_is_secret_key() {
+        for key in ["api_key", "apiKey", "DB_PASSWORD", "client-secret", "accessToken", "AWS_SECRET_ACCESS_KEY"] {
+            assert!(is_secret_key(key), "{}", key);
+        }
+        for key in ["max_tokens", "keyword", "monkey", "passage", "key_id"] {
+            assert!(!is_secret_key(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_to_redacted() {
+        let mut config = Config::default();
+        config.provider.openrouter =
+            Some(serde_json::from_value(json!({ "api_key": "sk-or-v1-0123456789abcdef" })).unwrap());
+        config.storage.postgres =
+            Some(serde_json::from_value(json!({ "url": "postgres://agent:pw@localhost/openagent" })).unwrap());
+        config.gateway.auth.password = Some("correct horse battery staple".to_string());
+        config.gateway.auth.tokens = vec!["tok_live_0123456789".to_string()];
+        config.plugins.insert(
+            "notion".to_string(),
+            json!({ "api_key": "secret_notion_0123456789", "workspace": "team", "max_tokens": 512 }),
+        );
+        config.sandbox.container.env.insert("GITHUB_TOKEN".to_string(), "ghp_0123456789abcdef".to_string());
+        config.sandbox.container.env.insert("RUST_LOG".to_string(), "debug".to_string());
+
+        let redacted = config.to_redacted();
+        let openrouter = redacted.provider.openrouter.as_ref().unwrap();
+        assert_eq!(openrouter.api_key.expose_secret(), REDACTED);
+        assert_eq!(redacted.storage.postgres.as_ref().unwrap().url.expose_secret(), REDACTED);
+        assert_eq!(redacted.gateway.auth.password.as_deref(), Some("corr...aple"));
+        assert_eq!(redacted.gateway.auth.tokens, vec!["tok_...6789"]);
+        let notion = &redacted.plugins["notion"];
+        assert_eq!(notion["api_key"], "secr...6789");
+        assert_eq!(notion["workspace"], "team");
+        assert_eq!(notion["max_tokens"], 512);
+        assert_eq!(redacted.sandbox.container.env["GITHUB_TOKEN"], "ghp_...cdef");
+        assert_eq!(redacted.sandbox.container.env["RUST_LOG"], "debug");
+
+        let logged = format!("{:?}", redacted);
+        assert!(!logged.contains("battery"));
+        assert!(!logged.contains("0123456789"));
+
+        // The original is untouched
+        assert_eq!(
+            config.provider.openrouter.unwrap().api_key.expose_secret(),
+            "sk-or-v1-0123456789abcdef"
+        );
+    }
+}
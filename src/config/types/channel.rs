@@ -24,6 +24,8 @@ pub struct ChannelsConfig {
     /// WebChat configuration
     #[serde(default)]
     pub webchat: WebChatConfig,
+    /// CalDAV calendar for the calendar tool
+    pub calendar: Option<CalendarConfig>,
     /// Custom channel configurations
     #[serde(default)]
     pub custom: HashMap<String, serde_json::Value>,
@@ -80,6 +82,18 @@ fn default_media_size() -> u32 {
     8
 }
 
+/// CalDAV calendar configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    /// Calendar collection URL (e.g. `https://dav.example.com/calendars/me/personal/`)
+    pub caldav_url: String,
+    /// Username for HTTP basic auth
+    pub username: String,
+    /// Password for HTTP basic auth
+    #[serde(skip_serializing, default = "default_secret")]
+    pub password: SecretString,
+}
+
 /// Slack configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackConfig {
@@ -0,0 +1,78 @@
+//! MCP server configuration types
+//!
+//! Configuration for external Model Context Protocol servers and the
+//! transport used to reach each of them.
+
+use serde::{Deserialize, Serialize};
+
+/// A single MCP server entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Whether to connect to this server at startup
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Transport selection and its settings
+    #[serde(flatten)]
+    pub transport: McpTransportConfig,
+    /// Seconds to wait for the connection and for each response
+    /// (initialize, tools/list, tool calls) before giving up
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+/// Transport used to talk to an MCP server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "transport", rename_all = "lowercase")]
+pub enum McpTransportConfig {
+    /// Spawn a local subprocess and speak JSON-RPC over stdin/stdout
+    Stdio {
+        /// Executable to launch
+        command: String,
+        /// Command-line arguments
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Connect to a remote server over WebSocket
+    Websocket {
+        /// Server URL (`ws://` or `wss://`)
+        url: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_transport_selection() {
+        let stdio: McpServerConfig =
+            json5::from_str(r#"{ transport: "stdio", command: "mcp-fs", args: ["--root", "/tmp"] }"#)
+                .unwrap();
+        assert!(stdio.enabled);
+        assert_eq!(stdio.request_timeout_secs, 30);
+        assert_eq!(
+            stdio.transport,
+            McpTransportConfig::Stdio {
+                command: "mcp-fs".into(),
+                args: vec!["--root".into(), "/tmp".into()],
+            }
+        );
+
+        let ws: McpServerConfig =
+            json5::from_str(r#"{ transport: "websocket", url: "ws://localhost:9000", enabled: false }"#)
+                .unwrap();
+        assert!(!ws.enabled);
+        assert_eq!(
+            ws.transport,
+            McpTransportConfig::Websocket { url: "ws://localhost:9000".into() }
+        );
+    }
+}
@@ -3,10 +3,12 @@
 //! Re-exports all configuration types following openclaw's pattern.
 
 pub mod channel;
+pub mod mcp;
 pub mod provider;
 pub mod sandbox;
 pub mod storage;
 
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -41,6 +43,15 @@ pub struct Config {
     /// Plugin configurations
     #[serde(default)]
     pub plugins: HashMap<String, serde_json::Value>,
+
+    /// MCP servers keyed by name
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, mcp::McpServerConfig>,
+
+    /// Read secrets from HashiCorp Vault (also enabled by `VAULT_ADDR` and
+    /// `VAULT_TOKEN`)
+    #[serde(default)]
+    pub vault: Option<VaultConfig>,
 }
 
 impl Default for Config {
@@ -53,6 +64,8 @@ impl Default for Config {
             sandbox: sandbox::SandboxConfig::default(),
             gateway: GatewayConfig::default(),
             plugins: HashMap::new(),
+            mcp_servers: HashMap::new(),
+            vault: None,
         }
     }
 }
@@ -90,6 +103,10 @@ pub struct AgentConfig {
     /// Enable verbose output
     #[serde(default)]
     pub verbose: bool,
+    /// Most simultaneous calls per tool name, e.g. `fetch_page = 2`;
+    /// unlisted tools are unlimited
+    #[serde(default)]
+    pub tool_concurrency: HashMap<String, usize>,
 }
 
 impl Default for AgentConfig {
@@ -101,6 +118,7 @@ impl Default for AgentConfig {
             max_context_tokens: default_max_context(),
             thinking_level: ThinkingLevel::default(),
             verbose: false,
+            tool_concurrency: HashMap::new(),
         }
     }
 }
@@ -150,9 +168,44 @@ pub struct GatewayConfig {
     /// Enable WebSocket
     #[serde(default = "default_true")]
     pub websocket: bool,
+    /// Port of the WebSocket listener; the HTTP endpoints use `port`
+    #[serde(default = "default_websocket_port")]
+    pub websocket_port: u16,
+    /// Seconds between heartbeats sent to each WebSocket client
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval_secs: u64,
+    /// Seconds to wait for a heartbeat ack before closing the connection
+    #[serde(default = "default_heartbeat_timeout")]
+    pub heartbeat_timeout_secs: u64,
+    /// Serve WebSocket connections over TLS (`wss://`); plaintext when unset
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Let gateway clients accept any server certificate, e.g. a self-signed
+    /// one. For local development only.
+    #[serde(default)]
+    pub accept_self_signed_for_testing: bool,
     /// Authentication configuration
     #[serde(default)]
     pub auth: AuthConfig,
+    /// Seconds of inactivity after which an in-memory conversation is pruned
+    #[serde(default = "default_inactive_conversation_ttl")]
+    pub inactive_conversation_ttl_secs: u64,
+    /// OpenTelemetry trace export
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    /// CORS for the gateway HTTP endpoints (disabled when no origins are set)
+    #[serde(default)]
+    pub cors: CorsConfig,
+    /// Request throttling for HTTP routes and WebSocket messages
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Most WebSocket connections served at once; further upgrades get
+    /// `503 Service Unavailable`
+    #[serde(default = "default_websocket_max_connections")]
+    pub websocket_max_connections: usize,
+    /// Seconds to let running agent turns finish on shutdown
+    #[serde(default = "default_shutdown_grace")]
+    pub shutdown_grace_secs: u64,
 }
 
 impl Default for GatewayConfig {
@@ -161,7 +214,18 @@ impl Default for GatewayConfig {
             port: default_port(),
             bind: default_bind(),
             websocket: true,
+            websocket_port: default_websocket_port(),
+            heartbeat_interval_secs: default_heartbeat_interval(),
+            heartbeat_timeout_secs: default_heartbeat_timeout(),
+            tls: None,
+            accept_self_signed_for_testing: false,
             auth: AuthConfig::default(),
+            inactive_conversation_ttl_secs: default_inactive_conversation_ttl(),
+            telemetry: TelemetryConfig::default(),
+            cors: CorsConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            websocket_max_connections: default_websocket_max_connections(),
+            shutdown_grace_secs: default_shutdown_grace(),
         }
     }
 }
@@ -170,14 +234,136 @@ fn default_port() -> u16 {
     18789
 }
 
+fn default_websocket_port() -> u16 {
+    18790
+}
+
 fn default_bind() -> String {
     "127.0.0.1".to_string()
 }
 
+fn default_heartbeat_interval() -> u64 {
+    30
+}
+
+fn default_heartbeat_timeout() -> u64 {
+    10
+}
+
+fn default_inactive_conversation_ttl() -> u64 {
+    24 * 60 * 60 // 1 day
+}
+
+fn default_websocket_max_connections() -> usize {
+    100
+}
+
+fn default_shutdown_grace() -> u64 {
+    30
+}
+
 fn default_true() -> bool {
     true
 }
 
+/// OpenTelemetry configuration (requires the `telemetry` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Export spans via OTLP
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP/HTTP traces endpoint
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    /// `service.name` resource attribute
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            enabled: false,
+            otlp_endpoint: default_otlp_endpoint(),
+            service_name: default_service_name(),
+        }
+    }
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4318/v1/traces".to_string()
+}
+
+fn default_service_name() -> String {
+    "openagent".to_string()
+}
+
+/// Certificate and private key for the gateway's TLS listener
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain, leaf first
+    pub cert_pem_path: PathBuf,
+    /// PEM file with the private key (PKCS#8, PKCS#1 or SEC1)
+    pub key_pem_path: PathBuf,
+}
+
+/// HashiCorp Vault KV v2 secrets source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+    /// Vault server address, e.g. `https://vault.example.com:8200`
+    pub addr: String,
+    /// Vault token; usually left out of the file and set with `VAULT_TOKEN`
+    #[serde(skip_serializing, default = "provider::default_secret")]
+    pub token: SecretString,
+    /// Secret path as `<mount>/<name>`, e.g. `secret/openagent`
+    #[serde(default = "default_vault_path")]
+    pub path: String,
+}
+
+pub(crate) fn default_vault_path() -> String {
+    "secret/openagent".to_string()
+}
+
+/// CORS configuration for browser-based frontends
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to call the gateway (e.g. `https://app.example.com`);
+    /// `"*"` allows any origin
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    /// Send `Access-Control-Allow-Credentials: true` (ignored with `"*"`)
+    #[serde(default)]
+    pub allow_credentials: bool,
+}
+
+/// Gateway request throttling; a limit of 0 disables that key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Sustained requests per minute from one client IP
+    #[serde(default)]
+    pub requests_per_minute_per_ip: u32,
+    /// Sustained requests per minute for one user (bearer token)
+    #[serde(default)]
+    pub requests_per_minute_per_user: u32,
+    /// Requests a client may send at once before the per-minute rate applies
+    #[serde(default = "default_burst_size")]
+    pub burst_size: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            requests_per_minute_per_ip: 0,
+            requests_per_minute_per_user: 0,
+            burst_size: default_burst_size(),
+        }
+    }
+}
+
+fn default_burst_size() -> u32 {
+    10
+}
+
 /// Authentication configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AuthConfig {
@@ -189,6 +375,14 @@ pub struct AuthConfig {
     /// Allowed tokens
     #[serde(default)]
     pub tokens: Vec<String>,
+    /// HS256 shared secret (for jwt mode)
+    pub jwt_secret: Option<String>,
+    /// RS256 public key in PEM format (for jwt mode; takes precedence over `jwt_secret`)
+    pub jwt_public_key_pem: Option<String>,
+    /// Required `iss` claim (for jwt mode)
+    pub jwt_issuer: Option<String>,
+    /// Required `aud` claim (for jwt mode)
+    pub jwt_audience: Option<String>,
 }
 
 /// Authentication mode
@@ -202,6 +396,8 @@ pub enum AuthMode {
     Password,
     /// Token-based authentication
     Token,
+    /// Signed JWT (HS256 or RS256); the `sub` claim becomes the user ID
+    Jwt,
 }
 
 #[cfg(test)]
@@ -213,5 +409,8 @@ mod tests {
         let config = Config::default();
         assert_eq!(config.agent.model, "anthropic/claude-sonnet-4");
         assert_eq!(config.gateway.port, 18789);
+        assert_eq!(config.gateway.inactive_conversation_ttl_secs, 86400);
+        assert_eq!(config.gateway.heartbeat_interval_secs, 30);
+        assert_eq!(config.gateway.heartbeat_timeout_secs, 10);
     }
 }
@@ -21,6 +21,9 @@ pub struct ProviderConfig {
     /// Custom providers
     #[serde(default)]
     pub custom: HashMap<String, CustomProviderConfig>,
+    /// Failover chaining to secondary providers on errors
+    #[serde(default)]
+    pub failover: FailoverConfig,
 }
 
 impl Default for ProviderConfig {
@@ -31,6 +34,7 @@ impl Default for ProviderConfig {
             anthropic: None,
             openai: None,
             custom: HashMap::new(),
+            failover: FailoverConfig::default(),
         }
     }
 }
@@ -39,7 +43,7 @@ fn default_provider() -> String {
     "openrouter".to_string()
 }
 
-fn default_secret() -> SecretString {
+pub(super) fn default_secret() -> SecretString {
     SecretString::from(String::new())
 }
 
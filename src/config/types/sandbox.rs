@@ -26,14 +26,28 @@ pub struct SandboxConfig {
     /// Default timeout for execution
     #[serde(default = "default_timeout")]
     pub default_timeout_secs: u64,
-    /// Maximum output size in bytes
-    #[serde(default = "default_max_output")]
-    pub max_output_bytes: usize,
+    /// Deprecated: use `resource_limits.max_output_bytes`. Still honoured
+    /// while that is left at its default; see
+    /// [`effective_resource_limits`](Self::effective_resource_limits).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
     /// Optional user to run agent commands as (for passwordless sudo)
     /// If set, commands will be run as this user using `sudo -n -u <user>`
     /// This user should have passwordless sudo configured in /etc/sudoers.d/
     #[serde(default)]
     pub agent_user: Option<String>,
+    /// CPU/memory/output caps for OS-mode execution
+    #[serde(default)]
+    pub resource_limits: SandboxResourceLimits,
+    /// Environment variables passed through to sandboxed commands, which
+    /// otherwise start with a stripped environment. Also settable as a
+    /// comma-separated list in `PASSTHROUGH_ENV_VARS`.
+    #[serde(default)]
+    pub passthrough_env_vars: Vec<String>,
+    /// Let the `git` tool run `add`, `commit` and `checkout` (read-only
+    /// operations are always available). Also settable as `GIT_WRITE_ENABLED`.
+    #[serde(default)]
+    pub git_write_enabled: bool,
 }
 
 impl Default for SandboxConfig {
@@ -45,12 +59,29 @@ impl Default for SandboxConfig {
             wasm: WasmConfig::default(),
             os: OsSandboxConfig::default(),
             default_timeout_secs: default_timeout(),
-            max_output_bytes: default_max_output(),
+            max_output_bytes: None,
             agent_user: None,
+            resource_limits: SandboxResourceLimits::default(),
+            passthrough_env_vars: Vec::new(),
+            git_write_enabled: false,
         }
     }
 }
 
+impl SandboxConfig {
+    /// Resource limits with the deprecated top-level `max_output_bytes`
+    /// applied when `resource_limits.max_output_bytes` was not changed
+    pub fn effective_resource_limits(&self) -> SandboxResourceLimits {
+        let mut limits = self.resource_limits;
+        if let Some(bytes) = self.max_output_bytes {
+            if limits.max_output_bytes == default_max_output() {
+                limits.max_output_bytes = bytes;
+            }
+        }
+        limits
+    }
+}
+
 fn default_allowed_dir() -> PathBuf {
     dirs::home_dir()
         .map(|h| h.join(".openagent").join("workspace"))
@@ -110,6 +141,12 @@ pub struct ContainerConfig {
     /// Docker image to use
     #[serde(default = "default_image")]
     pub image: String,
+    /// Docker image for Go code
+    #[serde(default = "default_go_image")]
+    pub go_image: String,
+    /// Docker image for Java code
+    #[serde(default = "default_java_image")]
+    pub java_image: String,
     /// Network mode
     #[serde(default = "default_network")]
     pub network: String,
@@ -134,6 +171,8 @@ impl Default for ContainerConfig {
     fn default() -> Self {
         ContainerConfig {
             image: default_image(),
+            go_image: default_go_image(),
+            java_image: default_java_image(),
             network: default_network(),
             memory_limit: default_memory(),
             cpu_limit: default_cpu(),
@@ -148,6 +187,14 @@ fn default_image() -> String {
     "python:3.12-slim".to_string()
 }
 
+fn default_go_image() -> String {
+    "golang:1.22-alpine".to_string()
+}
+
+fn default_java_image() -> String {
+    "openjdk:21-slim".to_string()
+}
+
 fn default_network() -> String {
     "none".to_string()
 }
@@ -173,7 +220,7 @@ pub struct VolumeMount {
 }
 
 /// WebAssembly sandbox configuration
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WasmConfig {
     /// Maximum memory in pages (64KB each)
     #[serde(default = "default_wasm_memory")]
@@ -189,6 +236,17 @@ pub struct WasmConfig {
     pub fuel_limit: u64,
 }
 
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_pages: default_wasm_memory(),
+            enable_wasi: true,
+            wasi_dirs: Vec::new(),
+            fuel_limit: default_fuel(),
+        }
+    }
+}
+
 fn default_wasm_memory() -> u32 {
     256 // 16MB
 }
@@ -217,6 +275,39 @@ pub struct OsSandboxConfig {
     pub denied_executables: Vec<String>,
 }
 
+/// Resource caps applied to processes spawned by the OS sandbox.
+/// A value of 0 disables the corresponding limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxResourceLimits {
+    /// Maximum data segment / heap size in bytes (`RLIMIT_DATA`, Linux only)
+    #[serde(default = "default_max_memory_bytes")]
+    pub max_memory_bytes: u64,
+    /// Maximum CPU time in seconds (`RLIMIT_CPU` on Linux, wall clock elsewhere)
+    #[serde(default = "default_max_cpu_time")]
+    pub max_cpu_time_secs: u64,
+    /// Maximum bytes kept from each of stdout and stderr
+    #[serde(default = "default_max_output")]
+    pub max_output_bytes: usize,
+}
+
+impl Default for SandboxResourceLimits {
+    fn default() -> Self {
+        SandboxResourceLimits {
+            max_memory_bytes: default_max_memory_bytes(),
+            max_cpu_time_secs: default_max_cpu_time(),
+            max_output_bytes: default_max_output(),
+        }
+    }
+}
+
+fn default_max_memory_bytes() -> u64 {
+    512 * 1024 * 1024 // 512MB
+}
+
+fn default_max_cpu_time() -> u64 {
+    60
+}
+
 /// Sandbox mode for sessions
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -254,4 +345,20 @@ mod tests {
         assert_eq!(config.execution_env, ExecutionEnv::Sandbox);
         assert_eq!(config.default_timeout_secs, 30);
     }
+
+    #[test]
+    fn test_legacy_max_output_bytes() {
+        let config: SandboxConfig = serde_json::from_str(r#"{"max_output_bytes": 4096}"#).unwrap();
+        assert_eq!(config.effective_resource_limits().max_output_bytes, 4096);
+
+        // An explicit resource limit wins over the deprecated field
+        let config: SandboxConfig = serde_json::from_str(
+            r#"{"max_output_bytes": 4096, "resource_limits": {"max_output_bytes": 8192}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.effective_resource_limits().max_output_bytes, 8192);
+
+        let config = SandboxConfig::default();
+        assert_eq!(config.effective_resource_limits(), config.resource_limits);
+    }
 }
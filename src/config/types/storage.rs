@@ -51,29 +51,113 @@ pub enum StorageBackendType {
 
 /// PostgreSQL configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "PostgresConfigFile")]
 pub struct PostgresConfig {
     /// Database URL
     #[serde(skip_serializing, default = "default_secret")]
     pub url: SecretString,
-    /// Maximum connections in pool
-    #[serde(default = "default_max_connections")]
-    pub max_connections: u32,
-    /// Connection timeout in seconds
-    #[serde(default = "default_connect_timeout")]
-    pub connect_timeout_secs: u64,
+    /// Connection pool sizing and timeouts
+    #[serde(default)]
+    pub pool: PoolConfig,
     /// Enable pgvector extension
     #[serde(default = "default_true")]
     pub enable_pgvector: bool,
 }
 
+/// On-disk form of [`PostgresConfig`]
+///
+/// Also accepts the top-level `max_connections` and `connect_timeout_secs`
+/// that predate `pool`; they are used when there is no `pool` section.
+#[derive(Deserialize)]
+struct PostgresConfigFile {
+    #[serde(default = "default_secret")]
+    url: SecretString,
+    #[serde(default)]
+    pool: Option<PoolConfig>,
+    #[serde(default)]
+    max_connections: Option<u32>,
+    #[serde(default)]
+    connect_timeout_secs: Option<u64>,
+    #[serde(default = "default_true")]
+    enable_pgvector: bool,
+}
+
+impl From<PostgresConfigFile> for PostgresConfig {
+    fn from(file: PostgresConfigFile) -> Self {
+        let pool = file.pool.unwrap_or_else(|| {
+            let mut pool = PoolConfig::default();
+            if let Some(max_connections) = file.max_connections {
+                pool.max_connections = max_connections;
+            }
+            if let Some(timeout) = file.connect_timeout_secs {
+                pool.acquire_timeout_secs = timeout;
+            }
+            pool
+        });
+        PostgresConfig {
+            url: file.url,
+            pool,
+            enable_pgvector: file.enable_pgvector,
+        }
+    }
+}
+
+impl PostgresConfig {
+    /// Pool settings to apply, with `min_connections` capped at `max_connections`
+    pub fn connection_pool_config(&self) -> PoolConfig {
+        let max_connections = self.pool.max_connections.max(1);
+        PoolConfig {
+            min_connections: self.pool.min_connections.min(max_connections),
+            max_connections,
+            ..self.pool.clone()
+        }
+    }
+}
+
+/// PostgreSQL connection pool configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// Connections kept open even when idle
+    #[serde(default = "default_min_connections")]
+    pub min_connections: u32,
+    /// Maximum connections in pool
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    /// Seconds to wait for a free connection before failing
+    #[serde(default = "default_acquire_timeout", alias = "connect_timeout_secs")]
+    pub acquire_timeout_secs: u64,
+    /// Seconds before an idle connection above `min_connections` is closed
+    #[serde(default = "default_idle_timeout")]
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            min_connections: default_min_connections(),
+            max_connections: default_max_connections(),
+            acquire_timeout_secs: default_acquire_timeout(),
+            idle_timeout_secs: default_idle_timeout(),
+        }
+    }
+}
+
+fn default_min_connections() -> u32 {
+    1
+}
+
 fn default_max_connections() -> u32 {
     5
 }
 
-fn default_connect_timeout() -> u64 {
+fn default_acquire_timeout() -> u64 {
     30
 }
 
+fn default_idle_timeout() -> u64 {
+    600
+}
+
 fn default_true() -> bool {
     true
 }
@@ -164,6 +248,9 @@ pub struct EmbeddingConfig {
     /// Embedding dimensions
     #[serde(default = "default_embedding_dims")]
     pub dimensions: u32,
+    /// Eviction policy for the in-process embedding/search cache
+    #[serde(default)]
+    pub cache_policy: CachePolicy,
 }
 
 impl Default for EmbeddingConfig {
@@ -172,6 +259,42 @@ impl Default for EmbeddingConfig {
             provider: default_embedding_provider(),
             model: default_embedding_model(),
             dimensions: default_embedding_dims(),
+            cache_policy: CachePolicy::default(),
+        }
+    }
+}
+
+/// Cache eviction policy
+///
+/// `Lru` suits write-heavy workloads with shifting hot sets, `Lfu` suits
+/// read-heavy workloads with a stable hot set, and `Ttl` bounds staleness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CachePolicy {
+    /// Evict the least recently used entry when full
+    Lru { max_capacity: u64 },
+    /// Evict the least frequently used entry when full (TinyLFU admission)
+    Lfu { max_capacity: u64 },
+    /// Expire entries after a fixed time-to-live
+    Ttl { max_capacity: u64, entry_ttl_secs: u64 },
+}
+
+impl CachePolicy {
+    /// Maximum number of entries held by the cache
+    pub fn max_capacity(&self) -> u64 {
+        match *self {
+            CachePolicy::Lru { max_capacity }
+            | CachePolicy::Lfu { max_capacity }
+            | CachePolicy::Ttl { max_capacity, .. } => max_capacity,
+        }
+    }
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        CachePolicy::Ttl {
+            max_capacity: 1000,
+            entry_ttl_secs: 30 * 60,
         }
     }
 }
@@ -198,6 +321,46 @@ mod tests {
         assert_eq!(config.backend, StorageBackendType::Sqlite);
     }
 
+    #[test]
+    fn test_cache_policy_deserialize() {
+        let policy: CachePolicy =
+            serde_json::from_str(r#"{"type": "lfu", "max_capacity": 250}"#).unwrap();
+        assert_eq!(policy, CachePolicy::Lfu { max_capacity: 250 });
+        assert_eq!(EmbeddingConfig::default().cache_policy.max_capacity(), 1000);
+    }
+
+    #[test]
+    fn test_connection_pool_config() {
+        let config: PostgresConfig = serde_json::from_str(
+            r#"{"url": "postgres://localhost/db", "pool": {"min_connections": 20, "max_connections": 8}}"#,
+        )
+        .unwrap();
+        let pool = config.connection_pool_config();
+        assert_eq!(pool.min_connections, 8);
+        assert_eq!(pool.max_connections, 8);
+        assert_eq!(pool.acquire_timeout_secs, 30);
+        assert_eq!(pool.idle_timeout_secs, 600);
+    }
+
+    #[test]
+    fn test_legacy_pool_fields() {
+        // Settings from before the `pool` section still apply
+        let config: PostgresConfig = serde_json::from_str(
+            r#"{"url": "postgres://localhost/db", "max_connections": 12, "connect_timeout_secs": 5}"#,
+        )
+        .unwrap();
+        assert_eq!(config.pool.max_connections, 12);
+        assert_eq!(config.pool.acquire_timeout_secs, 5);
+        assert_eq!(config.pool.min_connections, 1);
+
+        let config: PostgresConfig = serde_json::from_str(
+            r#"{"max_connections": 12, "pool": {"max_connections": 3, "connect_timeout_secs": 7}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.pool.max_connections, 3);
+        assert_eq!(config.pool.acquire_timeout_secs, 7);
+    }
+
     #[test]
     fn test_sqlite_config_default() {
         let config = SqliteConfig::default();
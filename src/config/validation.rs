@@ -103,6 +103,20 @@ fn validate_provider_config(config: &Config, mut result: ConfigValidationResult)
         );
     }
 
+    // OpenRouter and Anthropic may both be set (for failover), but a
+    // configured OpenRouter needs a model to fall back on
+    if let Some(ref openrouter) = config.provider.openrouter {
+        if openrouter.default_model.trim().is_empty() {
+            result = result.with_error(
+                ValidationIssue::new(
+                    "provider.openrouter.default_model",
+                    "OpenRouter is configured but has no default model",
+                )
+                .with_suggestion("Set provider.openrouter.default_model, e.g. \"anthropic/claude-sonnet-4\""),
+            );
+        }
+    }
+
     result
 }
 
@@ -141,6 +155,18 @@ fn validate_storage_config(config: &Config, mut result: ConfigValidationResult)
         );
     }
 
+    // Only the in-memory backend may run without any database
+    let has_sqlite = !config.storage.sqlite.path.trim().is_empty();
+    if config.storage.backend != StorageBackendType::Memory && config.storage.postgres.is_none() && !has_sqlite {
+        result = result.with_error(
+            ValidationIssue::new(
+                "storage",
+                "Neither storage.postgres nor storage.sqlite is configured",
+            )
+            .with_suggestion("Configure a database, or set storage.backend = \"memory\" to run stateless"),
+        );
+    }
+
     result
 }
 
@@ -162,16 +188,14 @@ fn validate_sandbox_config(config: &Config, mut result: ConfigValidationResult)
     }
 
     // Check container configuration
-    if config.sandbox.execution_env == ExecutionEnv::Container {
-        if config.sandbox.container.image.is_empty() {
-            result = result.with_error(
-                ValidationIssue::new(
-                    "sandbox.container.image",
-                    "Container execution selected but no image specified",
-                )
-                .with_suggestion("Set sandbox.container.image to a valid Docker image"),
-            );
-        }
+    if config.sandbox.execution_env == ExecutionEnv::Container && config.sandbox.container.image.trim().is_empty() {
+        result = result.with_error(
+            ValidationIssue::new(
+                "sandbox.container.image",
+                "Container execution selected but no image specified",
+            )
+            .with_suggestion("Set sandbox.container.image to a valid Docker image"),
+        );
     }
 
     result
@@ -189,4 +213,30 @@ mod tests {
         // Default config should have warnings but no errors
         assert!(result.errors.is_empty());
     }
+
+    #[test]
+    fn test_reports_every_cross_field_violation() {
+        use crate::config::ExecutionEnv;
+        use super::super::types::storage::StorageBackendType;
+
+        let mut config = Config::default();
+        config.provider.openrouter = Some(serde_json::from_value(serde_json::json!({ "default_model": "" })).unwrap());
+        config.provider.anthropic = Some(serde_json::from_value(serde_json::json!({})).unwrap());
+        config.storage.sqlite.path = String::new();
+        config.sandbox.execution_env = ExecutionEnv::Container;
+        config.sandbox.container.image = " ".to_string();
+
+        let result = validate_config(&config);
+        assert!(!result.valid);
+        let mut paths: Vec<&str> = result.errors.iter().map(|e| e.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["provider.openrouter.default_model", "sandbox.container.image", "storage"]
+        );
+
+        // Stateless mode needs no database
+        config.storage.backend = StorageBackendType::Memory;
+        assert!(!validate_config(&config).errors.iter().any(|e| e.path == "storage"));
+    }
 }
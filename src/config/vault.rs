@@ -0,0 +1,185 @@
+//! HashiCorp Vault secrets backend
+//!
+//! Reads one secret from Vault's KV v2 engine and overlays its keys onto the
+//! configuration, using the same names as the environment overrides
+//! (`OPENROUTER_API_KEY`, `TELEGRAM_BOT_TOKEN`, `DATABASE_URL`, ...). Layers
+//! apply in the order file < Vault < environment, so a variable set on the
+//! host still wins. If Vault cannot be reached the configuration is loaded
+//! without it and a warning is logged.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, info, warn};
+
+use super::io::{apply_env_overrides, apply_overrides, load_config_file};
+use super::types::{default_vault_path, Config};
+use crate::error::{Error, Result};
+
+/// Timeout for the Vault request; startup should not hang on it
+const VAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of a KV v2 read: the secret's key-value pairs are in `data.data`
+#[derive(Debug, Deserialize)]
+struct KvReadResponse {
+    data: KvData,
+}
+
+#[derive(Debug, Deserialize)]
+struct KvData {
+    data: HashMap<String, Value>,
+}
+
+impl Config {
+    /// Load the configuration with secrets from Vault at `addr`
+    ///
+    /// `path` is `<mount>/<name>`, e.g. `secret/openagent`. The secret's keys
+    /// are applied on top of the config file, then environment variables on
+    /// top of them. If Vault is unreachable or refuses the token, the
+    /// configuration is loaded from the file and environment alone.
+    pub async fn from_hashicorp_vault(addr: &str, token: &str, path: &str) -> Result<Config> {
+        let mut config = load_config_file()?;
+
+        match read_vault_secrets(addr, token, path).await {
+            Ok(secrets) => {
+                info!("Loaded {} secrets from Vault {}", secrets.len(), path);
+                apply_overrides(&mut config, "Vault", |key| secrets.get(key).cloned());
+            }
+            Err(e) => warn!("Vault unavailable, continuing without it: {}", e),
+        }
+
+        apply_env_overrides(&mut config);
+        Ok(config)
+    }
+
+    /// Like [`Config::from_env`], with secrets from Vault when it is
+    /// configured
+    ///
+    /// Vault is used when `VAULT_ADDR` and `VAULT_TOKEN` are set, or the
+    /// config file has a `vault` section with a token; `VAULT_ADDR`,
+    /// `VAULT_TOKEN` and `VAULT_PATH` override the file.
+    pub async fn from_env_and_vault() -> Result<Config> {
+        dotenvy::dotenv().ok();
+        let file = load_config_file()?;
+        let var = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+
+        let vault = file.vault.as_ref();
+        let addr = var("VAULT_ADDR").or_else(|| vault.map(|v| v.addr.clone()));
+        let token = var("VAULT_TOKEN").or_else(|| vault.map(|v| v.token.expose_secret().to_string()));
+        let path = var("VAULT_PATH")
+            .or_else(|| vault.map(|v| v.path.clone()))
+            .unwrap_or_else(default_vault_path);
+
+        match (addr, token) {
+            (Some(addr), Some(token)) if !addr.is_empty() && !token.is_empty() => {
+                Self::from_hashicorp_vault(&addr, &token, &path).await
+            }
+            _ => {
+                if vault.is_some() {
+                    warn!("Vault is configured without an address or token, skipping it");
+                }
+                Self::from_env()
+            }
+        }
+    }
+}
+
+/// Read the key-value pairs of the KV v2 secret at `path`
+///
+/// Numbers and booleans are returned as strings; other non-string values
+/// are skipped.
+pub async fn read_vault_secrets(addr: &str, token: &str, path: &str) -> Result<HashMap<String, String>> {
+    let url = kv2_url(addr, path)?;
+    let client = reqwest::Client::builder().timeout(VAULT_TIMEOUT).build()?;
+    let response = client.get(&url).header("X-Vault-Token", token).send().await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Config(format!("Vault returned {} for {}: {}", status, path, body.trim())));
+    }
+
+    let body: KvReadResponse = response.json().await?;
+    let mut secrets = HashMap::new();
+    for (key, value) in body.data.data {
+        let value = match value {
+            Value::String(s) => s,
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => {
+                debug!("Skipping Vault key {} with a non-scalar value", key);
+                continue;
+            }
+        };
+        secrets.insert(key, value);
+    }
+    Ok(secrets)
+}
+
+/// KV v2 read URL for `<mount>/<name>`; a `data/` segment is added unless
+/// the path already has one
+fn kv2_url(addr: &str, path: &str) -> Result<String> {
+    let path = path.trim_matches('/');
+    let (mount, name) = path
+        .split_once('/')
+        .filter(|(mount, name)| !mount.is_empty() && !name.is_empty())
+        .ok_or_else(|| Error::Config(format!("Vault path must be <mount>/<name>, got '{}'", path)))?;
+    let name = name.strip_prefix("data/").unwrap_or(name);
+    Ok(format!("{}/v1/{}/data/{}", addr.trim_end_matches('/'), mount, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_kv2_url() {
+        assert_eq!(
+            kv2_url("https://vault:8200/", "secret/openagent").unwrap(),
+            "https://vault:8200/v1/secret/data/openagent"
+        );
+        assert_eq!(
+            kv2_url("http://vault", "/kv/data/team/agent").unwrap(),
+            "http://vault/v1/kv/data/team/agent"
+        );
+        assert!(kv2_url("http://vault", "openagent").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_vault_secrets_use_env_key_mapping() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/secret/data/openagent"))
+            .and(header("X-Vault-Token", "s.test"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": {
+                    "data": {
+                        "OPENROUTER_API_KEY": "sk-or-v1-vault",
+                        "GATEWAY_PORT": 19000,
+                        "IGNORED": { "nested": true }
+                    },
+                    "metadata": { "version": 3 }
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let secrets = read_vault_secrets(&server.uri(), "s.test", "secret/openagent").await.unwrap();
+        assert_eq!(secrets.len(), 2);
+
+        let mut config = Config::default();
+        apply_overrides(&mut config, "Vault", |key| secrets.get(key).cloned());
+        assert_eq!(config.provider.openrouter.unwrap().api_key.expose_secret(), "sk-or-v1-vault");
+        assert_eq!(config.gateway.port, 19000);
+
+        // A bad token is an error, which the loaders turn into a warning
+        let err = read_vault_secrets(&server.uri(), "s.wrong", "secret/openagent").await.unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+    }
+}
@@ -244,6 +244,17 @@ pub trait Channel: Send + Sync {
         Ok(())
     }
 
+    /// Keep a typing indicator shown in `chat_id` for up to `duration_secs`
+    ///
+    /// Returns once the indicator is shown; channels refresh it in the
+    /// background and stop early when a reply is sent. Call it once per
+    /// turn rather than before every step.
+    async fn send_typing_indicator(&self, chat_id: &str, duration_secs: u64) -> Result<()> {
+        // Default: no-op for channels without `typing_indicators`
+        let _ = (chat_id, duration_secs);
+        Ok(())
+    }
+
     /// Edit a previously sent message
     async fn edit(&self, message_id: &MessageId, content: MessageContent) -> Result<()> {
         let _ = (message_id, content);
@@ -18,6 +18,6 @@ pub mod types;
 // Re-export core traits for convenient access
 pub use channel::{Channel, ChannelCapabilities, ChannelMessage, ChannelPlugin, ChannelReply};
 pub use executor::{CodeExecutor, ExecutionRequest, ExecutionResult, Language};
-pub use provider::{GenerationOptions, LlmProvider, LlmResponse, StreamingChunk};
+pub use provider::{GenerationOptions, LlmProvider, LlmResponse, StreamingChunk, StreamingToolCall};
 pub use storage::{MemoryBackend, SearchBackend, StorageBackend};
 pub use types::*;
@@ -143,6 +143,26 @@ pub struct StreamingChunk {
     pub is_final: bool,
     /// Finish reason (if final)
     pub finish_reason: Option<String>,
+    /// Tool call fragments, to be joined by `index`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<StreamingToolCall>,
+}
+
+/// Part of a tool call in a streaming chunk
+///
+/// The first fragment of a call carries its `id` and `name`; the
+/// `arguments` JSON arrives in pieces across later chunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingToolCall {
+    /// Position of the call among the response's tool calls
+    pub index: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Next piece of the arguments JSON
+    #[serde(default)]
+    pub arguments: String,
 }
 
 /// Stream of LLM response chunks
@@ -1,9 +1,10 @@
 //! Agent status tracking (singleton row in PostgreSQL)
 //!
 //! Tracks whether the agent is ready or processing, with heartbeat
-//! and scheduler run timestamps.
+//! and scheduler run timestamps. Every state change is also appended
+//! to `agent_status_history` as an audit trail.
 
-use crate::database::PostgresPool;
+use crate::database::{PostgresPool, TaskStats};
 use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -57,6 +58,28 @@ impl AgentStatusRow {
     }
 }
 
+/// One recorded state transition from `agent_status_history`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AgentStatusTransition {
+    pub id: i64,
+    pub from_state: String,
+    pub to_state: String,
+    pub task_id: Option<Uuid>,
+    pub transitioned_at: DateTime<Utc>,
+}
+
+/// Status row joined with task counts
+#[derive(FromRow)]
+struct StatusWithTasksRow {
+    #[sqlx(flatten)]
+    status: AgentStatusRow,
+    pending: i64,
+    processing: i64,
+    complete: i64,
+    failed: i64,
+    total: i64,
+}
+
 /// Agent status store (singleton row)
 #[derive(Clone)]
 pub struct AgentStatusStore {
@@ -78,6 +101,45 @@ impl AgentStatusStore {
         Ok(row)
     }
 
+    /// Get the status row together with counts of all tasks, in one query
+    pub async fn get_with_task_stats(&self) -> Result<(AgentStatusRow, TaskStats)> {
+        let row: StatusWithTasksRow = sqlx::query_as(r#"
+            SELECT s.*,
+                   COUNT(t.id) FILTER (WHERE t.status = 'pending') AS pending,
+                   COUNT(t.id) FILTER (WHERE t.status = 'processing') AS processing,
+                   COUNT(t.id) FILTER (WHERE t.status = 'finish') AS complete,
+                   COUNT(t.id) FILTER (WHERE t.status = 'fail') AS failed,
+                   COUNT(t.id) AS total
+            FROM agent_status s
+            LEFT JOIN agent_tasks t ON TRUE
+            WHERE s.id = 1
+            GROUP BY s.id
+        "#)
+        .fetch_one(&self.pool)
+        .await?;
+        let stats = TaskStats {
+            pending: row.pending as u64,
+            processing: row.processing as u64,
+            complete: row.complete as u64,
+            failed: row.failed as u64,
+            total: row.total as u64,
+        };
+        Ok((row.status, stats))
+    }
+
+    /// ID of the task the agent is processing, if any
+    ///
+    /// Chat turns mark the agent as processing with a nil task ID; those
+    /// return `None`.
+    pub async fn get_current_task_id(&self) -> Result<Option<Uuid>> {
+        let task_id: Option<Option<Uuid>> = sqlx::query_scalar(
+            "SELECT current_task_id FROM agent_status WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(task_id.flatten().filter(|id| !id.is_nil()))
+    }
+
     /// Get parsed agent state
     pub async fn state(&self) -> Result<AgentState> {
         let row = self.get().await?;
@@ -92,13 +154,15 @@ impl AgentStatusStore {
     /// Transition to processing state with a task reference.
     /// Uses atomic CAS — fails if already processing.
     pub async fn set_processing(&self, task_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
         let result = sqlx::query(r#"
             UPDATE agent_status
             SET status = 'processing', current_task_id = $1, updated_at = NOW(), last_heartbeat = NOW()
             WHERE id = 1 AND status = 'ready'
         "#)
         .bind(task_id)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
         if result.rows_affected() == 0 {
@@ -107,21 +171,54 @@ impl AgentStatusStore {
             ));
         }
 
+        record_transition(&mut tx, AgentState::Ready, AgentState::Processing, Some(task_id)).await?;
+        tx.commit().await?;
         Ok(())
     }
 
     /// Transition back to ready state
     pub async fn set_ready(&self) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let previous: Option<(String, Option<Uuid>)> = sqlx::query_as(
+            "SELECT status, current_task_id FROM agent_status WHERE id = 1 FOR UPDATE",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
         sqlx::query(r#"
             UPDATE agent_status
             SET status = 'ready', current_task_id = NULL, updated_at = NOW(), last_heartbeat = NOW()
             WHERE id = 1
         "#)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+
+        // Only log actual changes; set_ready is also called at startup
+        if let Some((status, task_id)) = previous {
+            let from = AgentState::from_str(&status);
+            if from != AgentState::Ready {
+                record_transition(&mut tx, from, AgentState::Ready, task_id).await?;
+            }
+        }
+
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Most recent state transitions, newest first
+    pub async fn history(&self, limit: usize) -> Result<Vec<AgentStatusTransition>> {
+        let rows: Vec<AgentStatusTransition> = sqlx::query_as(r#"
+            SELECT * FROM agent_status_history
+            ORDER BY transitioned_at DESC, id DESC
+            LIMIT $1
+        "#)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
     /// Update heartbeat timestamp
     pub async fn heartbeat(&self) -> Result<()> {
         sqlx::query(
@@ -142,3 +239,21 @@ impl AgentStatusStore {
         Ok(())
     }
 }
+
+/// Append a transition row inside the caller's transaction
+async fn record_transition(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    from: AgentState,
+    to: AgentState,
+    task_id: Option<Uuid>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO agent_status_history (from_state, to_state, task_id) VALUES ($1, $2, $3)",
+    )
+    .bind(from.as_str())
+    .bind(to.as_str())
+    .bind(task_id)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
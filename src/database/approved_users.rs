@@ -0,0 +1,65 @@
+//! Approved DM users
+//!
+//! Users approved through pairing (or added to the allowlist) by an admin,
+//! keyed by channel so approvals survive gateway restarts.
+
+use crate::database::PostgresPool;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+
+/// One row of `approved_users`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApprovedUser {
+    pub user_id: i64,
+    pub channel: String,
+    pub approved_at: DateTime<Utc>,
+    /// Admin who approved the user; `None` for rows added outside a chat
+    pub approved_by: Option<i64>,
+}
+
+/// Store for approved DM users
+#[derive(Clone)]
+pub struct ApprovedUserStore {
+    pool: PostgresPool,
+}
+
+impl ApprovedUserStore {
+    pub fn new(pool: PostgresPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record an approval; returns false if the user was already approved
+    pub async fn approve(&self, channel: &str, user_id: i64, approved_by: Option<i64>) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO approved_users (user_id, channel, approved_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (channel, user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(channel)
+        .bind(approved_by)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// All users approved on `channel`, oldest approval first
+    pub async fn list(&self, channel: &str) -> Result<Vec<ApprovedUser>> {
+        let users = sqlx::query_as::<_, ApprovedUser>(
+            r#"
+            SELECT user_id, channel, approved_at, approved_by
+            FROM approved_users
+            WHERE channel = $1
+            ORDER BY approved_at
+            "#,
+        )
+        .bind(channel)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(users)
+    }
+}
@@ -3,14 +3,42 @@
 //! Provides a key-value store for runtime-editable configuration parameters.
 //! Parameters are organized by category (e.g., "provider.openrouter", "sandbox").
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPool;
 use sqlx::FromRow;
 use tracing::trace;
 use uuid::Uuid;
 
+/// Every `(category, key)` the application reads from `config_params`
+pub const KNOWN_CONFIG_PARAMS: &[(&str, &str)] = &[
+    ("agent", "model"),
+    ("agent", "max_context_tokens"),
+    ("provider.openrouter", "default_model"),
+    ("provider.openrouter", "base_url"),
+    ("provider.openrouter", "timeout_secs"),
+    ("provider.openrouter", "max_retries"),
+    ("provider.openrouter", "site_url"),
+    ("provider.openrouter", "site_name"),
+    ("sandbox", "execution_env"),
+    ("sandbox", "allowed_dir"),
+    ("sandbox", "default_timeout_secs"),
+    ("sandbox.container", "image"),
+    ("sandbox.container", "network"),
+    ("sandbox.container", "memory_limit"),
+    ("sandbox.container", "cpu_limit"),
+    ("gateway", "port"),
+    ("gateway", "bind"),
+    ("scheduler", "interval_minutes"),
+    ("scheduler", "summarization_enabled"),
+    ("scheduler", "task_processing_enabled"),
+];
+
+/// Keys allowed in a per-user `user:<id>` category
+pub const USER_CONFIG_KEYS: &[&str] = &["username", "timezone", "language"];
+
 /// A stored configuration parameter
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ConfigParam {
@@ -107,6 +135,9 @@ impl ConfigParamStore {
     }
 
     /// Upsert (insert or update) a parameter
+    ///
+    /// Fails with `Error::Config` if the key is not one the application
+    /// reads or the value does not parse as `value_type`.
     pub async fn upsert(
         &self,
         category: &str,
@@ -116,6 +147,7 @@ impl ConfigParamStore {
         is_secret: bool,
         description: Option<&str>,
     ) -> Result<ConfigParam> {
+        validate_param(category, key, value, value_type)?;
         let param: ConfigParam = sqlx::query_as(
             r#"
             INSERT INTO config_params (category, key, value, value_type, is_secret, description)
@@ -156,198 +188,41 @@ impl ConfigParamStore {
     /// Returns the number of newly inserted parameters.
     pub async fn init_from_config(&self, config: &crate::config::Config) -> Result<usize> {
         let mut count = 0usize;
-
-        // Agent settings
-        count += self
-            .seed_if_absent(
-                "agent",
-                "model",
-                &config.agent.model,
-                ConfigValueType::String,
-                false,
-                Some("Default agent model"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "agent",
-                "max_context_tokens",
-                &config.agent.max_context_tokens.to_string(),
-                ConfigValueType::Number,
-                false,
-                Some("Maximum context window tokens"),
-            )
-            .await?;
-
-        // Provider: OpenRouter
-        if let Some(ref or_config) = config.provider.openrouter {
-            count += self
-                .seed_if_absent(
-                    "provider.openrouter",
-                    "default_model",
-                    &or_config.default_model,
-                    ConfigValueType::String,
-                    false,
-                    Some("Default LLM model for OpenRouter"),
-                )
-                .await?;
-            count += self
-                .seed_if_absent(
-                    "provider.openrouter",
-                    "base_url",
-                    &or_config.base_url,
-                    ConfigValueType::String,
-                    false,
-                    Some("OpenRouter API base URL"),
-                )
-                .await?;
-            count += self
-                .seed_if_absent(
-                    "provider.openrouter",
-                    "timeout_secs",
-                    &or_config.timeout_secs.to_string(),
-                    ConfigValueType::Number,
-                    false,
-                    Some("Request timeout in seconds"),
-                )
-                .await?;
+        for seed in &config_seeds(config) {
             count += self
                 .seed_if_absent(
-                    "provider.openrouter",
-                    "max_retries",
-                    &or_config.max_retries.to_string(),
-                    ConfigValueType::Number,
+                    seed.category,
+                    seed.key,
+                    &seed.value,
+                    seed.value_type,
                     false,
-                    Some("Maximum retries on failure"),
+                    Some(seed.description),
                 )
                 .await?;
-            if let Some(ref site_url) = or_config.site_url {
-                count += self
-                    .seed_if_absent(
-                        "provider.openrouter",
-                        "site_url",
-                        site_url,
-                        ConfigValueType::String,
-                        false,
-                        Some("Site URL for OpenRouter rankings"),
-                    )
-                    .await?;
-            }
-            if let Some(ref site_name) = or_config.site_name {
-                count += self
-                    .seed_if_absent(
-                        "provider.openrouter",
-                        "site_name",
-                        site_name,
-                        ConfigValueType::String,
-                        false,
-                        Some("Site name for OpenRouter rankings"),
-                    )
-                    .await?;
-            }
         }
-
-        // Sandbox settings
-        count += self
-            .seed_if_absent(
-                "sandbox",
-                "execution_env",
-                &config.sandbox.execution_env.to_string(),
-                ConfigValueType::String,
-                false,
-                Some("Execution environment: os, sandbox, container"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "sandbox",
-                "allowed_dir",
-                &config.sandbox.allowed_dir.to_string_lossy(),
-                ConfigValueType::String,
-                false,
-                Some("Allowed directory for file operations"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "sandbox",
-                "default_timeout_secs",
-                &config.sandbox.default_timeout_secs.to_string(),
-                ConfigValueType::Number,
-                false,
-                Some("Default execution timeout in seconds"),
-            )
-            .await?;
-
-        // Container settings
-        count += self
-            .seed_if_absent(
-                "sandbox.container",
-                "image",
-                &config.sandbox.container.image,
-                ConfigValueType::String,
-                false,
-                Some("Docker image for container sandbox"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "sandbox.container",
-                "network",
-                &config.sandbox.container.network,
-                ConfigValueType::String,
-                false,
-                Some("Docker network mode"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "sandbox.container",
-                "memory_limit",
-                &config.sandbox.container.memory_limit,
-                ConfigValueType::String,
-                false,
-                Some("Docker memory limit"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "sandbox.container",
-                "cpu_limit",
-                &config.sandbox.container.cpu_limit.to_string(),
-                ConfigValueType::Number,
-                false,
-                Some("Docker CPU limit"),
-            )
-            .await?;
-
-        // Gateway settings
-        count += self
-            .seed_if_absent(
-                "gateway",
-                "port",
-                &config.gateway.port.to_string(),
-                ConfigValueType::Number,
-                false,
-                Some("Gateway bind port"),
-            )
-            .await?;
-        count += self
-            .seed_if_absent(
-                "gateway",
-                "bind",
-                &config.gateway.bind,
-                ConfigValueType::String,
-                false,
-                Some("Gateway bind address"),
-            )
-            .await?;
-
         Ok(count)
     }
 
+    /// Get a parameter's value deserialized as `T`
+    ///
+    /// The value is read as JSON (`30`, `true`, `["a"]`), falling back to a
+    /// plain JSON string so text values work without quotes. Returns
+    /// `Error::NotFound` if the parameter is missing and `Error::Config` if
+    /// the value does not fit `T`.
+    pub async fn get_typed<T: DeserializeOwned>(&self, category: &str, key: &str) -> Result<T> {
+        let param = self
+            .get(category, key)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Config param {}.{} not set", category, key)))?;
+        parse_typed(&param.value).map_err(|e| {
+            Error::Config(format!("Invalid value for {}.{}: {}", category, key, e))
+        })
+    }
+
     /// Insert a param only if it doesn't already exist.
     /// Returns 1 if inserted, 0 if skipped.
+    ///
+    /// Validated like [`ConfigParamStore::upsert`].
     pub async fn seed_if_absent(
         &self,
         category: &str,
@@ -357,6 +232,7 @@ impl ConfigParamStore {
         is_secret: bool,
         description: Option<&str>,
     ) -> Result<usize> {
+        validate_param(category, key, value, value_type)?;
         let result = sqlx::query(
             r#"
             INSERT INTO config_params (category, key, value, value_type, is_secret, description)
@@ -388,6 +264,105 @@ impl ConfigParamStore {
     }
 }
 
+/// A parameter `init_from_config` seeds from the loaded Config
+struct Seed {
+    category: &'static str,
+    key: &'static str,
+    value: String,
+    value_type: ConfigValueType,
+    description: &'static str,
+}
+
+impl Seed {
+    fn new(
+        category: &'static str,
+        key: &'static str,
+        value: impl ToString,
+        value_type: ConfigValueType,
+        description: &'static str,
+    ) -> Self {
+        Seed { category, key, value: value.to_string(), value_type, description }
+    }
+}
+
+/// Parameters seeded from `config`, in insertion order
+fn config_seeds(config: &crate::config::Config) -> Vec<Seed> {
+    use ConfigValueType::{Number, String};
+
+    let mut seeds = vec![
+        Seed::new("agent", "model", &config.agent.model, String, "Default agent model"),
+        Seed::new("agent", "max_context_tokens", config.agent.max_context_tokens, Number, "Maximum context window tokens"),
+    ];
+
+    if let Some(ref or_config) = config.provider.openrouter {
+        seeds.extend([
+            Seed::new("provider.openrouter", "default_model", &or_config.default_model, String, "Default LLM model for OpenRouter"),
+            Seed::new("provider.openrouter", "base_url", &or_config.base_url, String, "OpenRouter API base URL"),
+            Seed::new("provider.openrouter", "timeout_secs", or_config.timeout_secs, Number, "Request timeout in seconds"),
+            Seed::new("provider.openrouter", "max_retries", or_config.max_retries, Number, "Maximum retries on failure"),
+        ]);
+        if let Some(ref site_url) = or_config.site_url {
+            seeds.push(Seed::new("provider.openrouter", "site_url", site_url, String, "Site URL for OpenRouter rankings"));
+        }
+        if let Some(ref site_name) = or_config.site_name {
+            seeds.push(Seed::new("provider.openrouter", "site_name", site_name, String, "Site name for OpenRouter rankings"));
+        }
+    }
+
+    let sandbox = &config.sandbox;
+    seeds.extend([
+        Seed::new("sandbox", "execution_env", sandbox.execution_env, String, "Execution environment: os, sandbox, container"),
+        Seed::new("sandbox", "allowed_dir", sandbox.allowed_dir.to_string_lossy(), String, "Allowed directory for file operations"),
+        Seed::new("sandbox", "default_timeout_secs", sandbox.default_timeout_secs, Number, "Default execution timeout in seconds"),
+        Seed::new("sandbox.container", "image", &sandbox.container.image, String, "Docker image for container sandbox"),
+        Seed::new("sandbox.container", "network", &sandbox.container.network, String, "Docker network mode"),
+        Seed::new("sandbox.container", "memory_limit", &sandbox.container.memory_limit, String, "Docker memory limit"),
+        Seed::new("sandbox.container", "cpu_limit", sandbox.container.cpu_limit, Number, "Docker CPU limit"),
+        Seed::new("gateway", "port", config.gateway.port, Number, "Gateway bind port"),
+        Seed::new("gateway", "bind", &config.gateway.bind, String, "Gateway bind address"),
+    ]);
+
+    seeds
+}
+
+/// Reject a param the application never reads, or whose value does not
+/// parse as its declared type
+///
+/// Known keys are those in [`KNOWN_CONFIG_PARAMS`], plus
+/// [`USER_CONFIG_KEYS`] under a `user:<id>` category.
+fn validate_param(category: &str, key: &str, value: &str, value_type: ConfigValueType) -> Result<()> {
+    let known = match category.strip_prefix("user:") {
+        Some(user_id) => !user_id.is_empty() && USER_CONFIG_KEYS.contains(&key),
+        None => KNOWN_CONFIG_PARAMS.contains(&(category, key)),
+    };
+    if !known {
+        return Err(Error::Config(format!("Unknown config param: {}.{}", category, key)));
+    }
+
+    let valid = match value_type {
+        ConfigValueType::String => true,
+        ConfigValueType::Number => value.trim().parse::<f64>().is_ok(),
+        ConfigValueType::Boolean => value.parse::<bool>().is_ok(),
+        ConfigValueType::Json => serde_json::from_str::<serde_json::Value>(value).is_ok(),
+    };
+    if !valid {
+        return Err(Error::Config(format!(
+            "Invalid {} value for {}.{}: {}",
+            value_type.as_str(),
+            category,
+            key,
+            value
+        )));
+    }
+    Ok(())
+}
+
+/// Deserialize a stored value: JSON first, then as a bare string
+fn parse_typed<T: DeserializeOwned>(value: &str) -> std::result::Result<T, serde_json::Error> {
+    serde_json::from_str(value)
+        .or_else(|_| serde_json::from_value(serde_json::Value::String(value.to_string())))
+}
+
 /// Apply a single config param to the Config struct
 fn apply_param(config: &mut crate::config::Config, param: &ConfigParam) {
     match (param.category.as_str(), param.key.as_str()) {
@@ -478,3 +453,46 @@ fn apply_param(config: &mut crate::config::Config, param: &ConfigParam) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeds_are_valid() {
+        let seeds = config_seeds(&crate::config::Config::default());
+        assert!(!seeds.is_empty());
+        for seed in &seeds {
+            validate_param(seed.category, seed.key, &seed.value, seed.value_type).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_validate_param() {
+        use ConfigValueType::{Boolean, Number, String};
+
+        assert!(validate_param("scheduler", "interval_minutes", "15", Number).is_ok());
+        assert!(validate_param("user:42", "timezone", "Asia/Tokyo", String).is_ok());
+
+        let err = validate_param("agent", "modle", "x", String).unwrap_err();
+        match err {
+            Error::Config(msg) => assert_eq!(msg, "Unknown config param: agent.modle"),
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(validate_param("user:42", "api_key", "x", String).is_err());
+        assert!(validate_param("user:", "timezone", "UTC", String).is_err());
+        assert!(validate_param("scheduler", "interval_minutes", "soon", Number).is_err());
+        assert!(validate_param("scheduler", "summarization_enabled", "yes", Boolean).is_err());
+    }
+
+    #[test]
+    fn test_parse_typed() {
+        assert_eq!(parse_typed::<u64>("30").unwrap(), 30);
+        assert!(!parse_typed::<bool>("false").unwrap());
+        assert_eq!(parse_typed::<String>("openai/gpt-4o").unwrap(), "openai/gpt-4o");
+        assert_eq!(parse_typed::<String>("\"quoted\"").unwrap(), "quoted");
+        assert_eq!(parse_typed::<Vec<String>>(r#"["a","b"]"#).unwrap(), vec!["a", "b"]);
+        assert!(parse_typed::<u64>("thirty").is_err());
+        assert!(parse_typed::<bool>("yes").is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Conversation persistence in PostgreSQL
+//!
+//! Keeps the gateway's in-memory conversations across restarts. Each
+//! conversation is one row in `conversations`, keyed by `(channel, user_id)`,
+//! with its full message list (including tool calls) in the `message_json`
+//! column. Each stored message also carries its checkpoint ID, so sync
+//! cursors and fork points stay valid across restarts.
+
+use crate::agent::{Conversation, Message};
+use crate::database::PostgresPool;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A message in `message_json`: the API fields plus the checkpoint ID,
+/// which `Message` itself never serializes
+#[derive(Serialize, Deserialize)]
+struct StoredMessage<M> {
+    #[serde(flatten)]
+    message: M,
+    /// Nil for rows saved before checkpoint IDs were stored
+    #[serde(default)]
+    checkpoint_id: Uuid,
+}
+
+#[derive(FromRow)]
+struct ConversationRow {
+    id: Uuid,
+    channel: String,
+    user_id: String,
+    model: String,
+    message_json: Json<Vec<StoredMessage<Message>>>,
+    total_tokens: i32,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<ConversationRow> for Conversation {
+    fn from(row: ConversationRow) -> Self {
+        let mut conv = Conversation::new(row.user_id, row.model);
+        conv.id = row.id;
+        conv.channel = row.channel;
+        for stored in row.message_json.0 {
+            conv.add_message(stored.message);
+            // Keep the stored ID; old rows keep the fresh one
+            if !stored.checkpoint_id.is_nil() {
+                if let Some(message) = conv.messages.last_mut() {
+                    message.checkpoint_id = stored.checkpoint_id;
+                }
+            }
+        }
+        conv.total_tokens = row.total_tokens.max(0) as u32;
+        conv.created_at = row.created_at;
+        conv.updated_at = row.updated_at;
+        conv
+    }
+}
+
+fn stored_messages(messages: &[Message]) -> Vec<StoredMessage<&Message>> {
+    messages
+        .iter()
+        .map(|message| StoredMessage {
+            message,
+            checkpoint_id: message.checkpoint_id,
+        })
+        .collect()
+}
+
+/// Conversation store backed by PostgreSQL
+#[derive(Clone)]
+pub struct ConversationStore {
+    pool: PostgresPool,
+}
+
+impl ConversationStore {
+    pub fn new(pool: PostgresPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert or update a conversation with all of its messages
+    pub async fn save(&self, conv: &Conversation) -> Result<()> {
+        sqlx::query(r#"
+            INSERT INTO conversations (id, channel, user_id, model, system_prompt, message_json,
+                                       total_tokens, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (id) DO UPDATE SET
+                model = EXCLUDED.model,
+                system_prompt = EXCLUDED.system_prompt,
+                message_json = EXCLUDED.message_json,
+                total_tokens = EXCLUDED.total_tokens,
+                updated_at = EXCLUDED.updated_at
+        "#)
+        .bind(conv.id)
+        .bind(&conv.channel)
+        .bind(&conv.user_id)
+        .bind(&conv.model)
+        .bind(&conv.system_prompt)
+        .bind(Json(stored_messages(&conv.messages)))
+        .bind(conv.total_tokens as i32)
+        .bind(conv.created_at)
+        .bind(conv.updated_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Latest conversation per session updated after `since`
+    ///
+    /// System prompts are not restored; callers render a fresh one so soul
+    /// changes made while the gateway was down still apply.
+    pub async fn load_recent(&self, since: DateTime<Utc>) -> Result<Vec<Conversation>> {
+        let rows: Vec<ConversationRow> = sqlx::query_as(r#"
+            SELECT DISTINCT ON (channel, user_id)
+                   id, channel, user_id, model, message_json, total_tokens, created_at, updated_at
+            FROM conversations
+            WHERE updated_at > $1
+            ORDER BY channel, user_id, updated_at DESC
+        "#)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Conversation::from).collect())
+    }
+
+    /// Delete conversations last updated before `before`. Returns the number removed.
+    pub async fn delete_older_than(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM conversations WHERE updated_at < $1")
+            .bind(before)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_ids_survive_a_round_trip() {
+        let mut conv = Conversation::new("42", "gpt-4");
+        conv.add_user_message("hi");
+        conv.add_assistant_message("hello");
+
+        let json = serde_json::to_string(&stored_messages(&conv.messages)).unwrap();
+        let row = ConversationRow {
+            id: conv.id,
+            channel: conv.channel.clone(),
+            user_id: conv.user_id.clone(),
+            model: conv.model.clone(),
+            message_json: Json(serde_json::from_str(&json).unwrap()),
+            total_tokens: 0,
+            created_at: conv.created_at,
+            updated_at: conv.updated_at,
+        };
+
+        let restored = Conversation::from(row);
+        let checkpoint = conv.messages[0].checkpoint_id;
+        assert_eq!(restored.messages[1].checkpoint_id, conv.messages[1].checkpoint_id);
+        assert_eq!(restored.get_messages_since(checkpoint).len(), 1);
+        assert!(restored.fork_at_message(checkpoint).is_some());
+    }
+
+    #[test]
+    fn test_row_to_conversation() {
+        // Saved before checkpoint IDs were stored
+        let messages: Vec<StoredMessage<Message>> =
+            serde_json::from_str(r#"[{"role":"user","content":"hi"},{"role":"assistant","content":"hello"}]"#)
+                .unwrap();
+        let updated_at = Utc::now() - chrono::Duration::days(1);
+        let row = ConversationRow {
+            id: Uuid::new_v4(),
+            channel: "slack".to_string(),
+            user_id: "42".to_string(),
+            model: "gpt-4".to_string(),
+            message_json: Json(messages),
+            total_tokens: 120,
+            created_at: updated_at - chrono::Duration::hours(1),
+            updated_at,
+        };
+        let id = row.id;
+
+        let conv = Conversation::from(row);
+        assert_eq!(conv.id, id);
+        assert_eq!(conv.session(), crate::agent::SessionNamespace::new("slack", "42"));
+        assert_eq!(conv.message_count(), 2);
+        assert_eq!(conv.total_tokens, 120);
+        assert_eq!(conv.updated_at, updated_at);
+        assert!(conv.system_prompt.is_none());
+        assert!(conv.messages.iter().all(|m| !m.checkpoint_id.is_nil()));
+    }
+}
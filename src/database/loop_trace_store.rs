@@ -0,0 +1,75 @@
+//! Agentic loop traces in PostgreSQL
+//!
+//! Each finished loop's `LoopTrace` (steps plus the recording needed to
+//! replay it) is stored as JSONB in `loop_traces` and kept for
+//! [`LOOP_TRACE_RETENTION_HOURS`], so a wrong answer can be replayed after
+//! the fact with `LoopTrace::replay`.
+
+use crate::agent::LoopTrace;
+use crate::database::PostgresPool;
+use crate::error::Result;
+use chrono::{DateTime, Utc};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+/// How long traces are kept before `prune_expired` removes them
+pub const LOOP_TRACE_RETENTION_HOURS: i64 = 24;
+
+/// Loop trace store backed by PostgreSQL
+#[derive(Clone)]
+pub struct LoopTraceStore {
+    pool: PostgresPool,
+}
+
+impl LoopTraceStore {
+    pub fn new(pool: PostgresPool) -> Self {
+        Self { pool }
+    }
+
+    /// Store a trace and return its ID
+    pub async fn save(&self, user_id: Option<&str>, trace: &LoopTrace) -> Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO loop_traces (id, user_id, outcome, trace) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(format!("{:?}", trace.outcome))
+        .bind(Json(trace))
+        .execute(&self.pool)
+        .await?;
+        Ok(id)
+    }
+
+    /// Load a stored trace
+    pub async fn get(&self, id: Uuid) -> Result<Option<LoopTrace>> {
+        let row: Option<(Json<LoopTrace>,)> =
+            sqlx::query_as("SELECT trace FROM loop_traces WHERE id = $1")
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(row.map(|(trace,)| trace.0))
+    }
+
+    /// Most recent trace IDs for a user, newest first
+    pub async fn recent(&self, user_id: &str, limit: i64) -> Result<Vec<(Uuid, String, DateTime<Utc>)>> {
+        let rows = sqlx::query_as(
+            "SELECT id, outcome, created_at FROM loop_traces WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Delete traces older than the retention window. Returns the number removed.
+    pub async fn prune_expired(&self) -> Result<u64> {
+        let cutoff = Utc::now() - chrono::Duration::hours(LOOP_TRACE_RETENTION_HOURS);
+        let result = sqlx::query("DELETE FROM loop_traces WHERE created_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
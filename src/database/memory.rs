@@ -1,12 +1,18 @@
 //! Memory storage and retrieval
 
 use crate::database::PostgresPool;
-use crate::error::Result;
+use crate::error::{Error, Result};
+use base64::Engine;
 use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection, Postgres, QueryBuilder};
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
 use uuid::Uuid;
 use pgvector::Vector;
+use tracing::debug;
 
 /// Memory type classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -50,6 +56,61 @@ impl Default for MemoryType {
     }
 }
 
+/// Separator placed between contents joined by [`Memory::merge`]
+pub const MERGE_SEPARATOR: &str = "\n---\n";
+
+/// How [`Memory::merge`] combines content and importance
+///
+/// Tags, access counts and timestamps are combined the same way for
+/// every strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Join both contents and keep the higher importance
+    Append,
+    /// Take the other memory's content, summary and importance
+    Replace,
+    /// Join both contents and average the importance
+    #[default]
+    Average,
+}
+
+/// How [`MemoryStore::search_by_tag`] combines several tags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMode {
+    /// Memories carrying at least one of the tags (OR)
+    #[default]
+    Any,
+    /// Memories carrying every tag (AND)
+    All,
+}
+
+impl TagMode {
+    /// PostgreSQL array operator: overlap for `Any`, containment for `All`
+    fn operator(&self) -> &'static str {
+        match self {
+            TagMode::Any => "&&",
+            TagMode::All => "@>",
+        }
+    }
+}
+
+impl std::str::FromStr for TagMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "any" | "or" => Ok(TagMode::Any),
+            "all" | "and" => Ok(TagMode::All),
+            _ => Err(Error::InvalidInput(format!(
+                "Invalid tag mode: {}. Valid: any, all",
+                s
+            ))),
+        }
+    }
+}
+
 /// A memory entry
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Memory {
@@ -152,13 +213,314 @@ impl Memory {
     pub fn parsed_type(&self) -> MemoryType {
         MemoryType::from_str(&self.memory_type)
     }
+
+    /// Combine `other` into a copy of this memory
+    ///
+    /// The result keeps this memory's ID, owner and type. Tags are unioned,
+    /// access counts summed, `created_at` is the earlier of the two and
+    /// metadata keys missing here are copied from `other`. Content, summary
+    /// and importance follow `strategy`.
+    pub fn merge(&self, other: &Memory, strategy: MergeStrategy) -> Memory {
+        let joined = || format!("{}{}{}", self.content, MERGE_SEPARATOR, other.content);
+        let (content, summary, importance) = match strategy {
+            MergeStrategy::Append => (
+                joined(),
+                self.summary.clone().or_else(|| other.summary.clone()),
+                self.importance.max(other.importance),
+            ),
+            MergeStrategy::Replace => (
+                other.content.clone(),
+                other.summary.clone().or_else(|| self.summary.clone()),
+                other.importance,
+            ),
+            MergeStrategy::Average => (
+                joined(),
+                self.summary.clone().or_else(|| other.summary.clone()),
+                (self.importance + other.importance) / 2.0,
+            ),
+        };
+
+        let mut tags = self.tags.clone();
+        for tag in &other.tags {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        let mut metadata = self.metadata.clone();
+        if let (Some(merged), Some(extra)) = (metadata.as_object_mut(), other.metadata.as_object()) {
+            for (key, value) in extra {
+                merged.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+
+        Memory {
+            id: self.id,
+            user_id: self.user_id.clone(),
+            content,
+            summary,
+            importance,
+            tags,
+            memory_type: self.memory_type.clone(),
+            metadata,
+            source: self.source.clone(),
+            created_at: self.created_at.min(other.created_at),
+            updated_at: Utc::now(),
+            accessed_at: self.accessed_at.max(other.accessed_at),
+            access_count: self.access_count + other.access_count,
+        }
+    }
+}
+
+/// One line of a JSONL memory export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryRecord {
+    #[serde(flatten)]
+    pub memory: Memory,
+    /// Embedding as base64-encoded little-endian f32 array
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<String>,
+}
+
+/// Number of equal-width buckets over `importance` (0.0 - 1.0)
+pub const IMPORTANCE_BUCKETS: usize = 10;
+
+/// Distribution of a user's memory importance scores
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportanceHistogram {
+    /// Memory counts per [`IMPORTANCE_BUCKETS`] bucket, from `[0.0, 0.1)` to
+    /// `[0.9, 1.0]`
+    pub buckets: Vec<u32>,
+    /// Number of memories
+    pub total: u64,
+    /// Mean importance (0 without memories)
+    pub mean: f32,
+    /// Median importance
+    pub p50: f32,
+    /// 90th percentile importance
+    pub p90: f32,
+}
+
+/// Health overview of one user's memory store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MemoryStats {
+    /// Number of memories
+    pub total_count: u64,
+    /// Creation time of the oldest memory
+    pub oldest_memory_at: Option<DateTime<Utc>>,
+    /// Total row size in bytes (content, embedding and search vector)
+    pub storage_bytes: u64,
+    /// Distribution of importance scores
+    pub importance: ImportanceHistogram,
+}
+
+/// One page of a user's memories, newest first, from
+/// [`MemoryStore::page_all`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryPage {
+    pub items: Vec<Memory>,
+    /// Page number, starting at 1
+    pub page: u32,
+    /// Number of pages (0 without memories)
+    pub total_pages: u32,
+    /// Number of memories across all pages
+    pub total_items: u64,
+}
+
+/// Pages needed to show `total_items` memories `page_size` at a time
+fn page_count(total_items: u64, page_size: u32) -> u32 {
+    total_items.div_ceil(page_size as u64) as u32
 }
 
+/// Progress of a re-embedding run, from `reembed_progress`
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ReembedProgress {
+    pub user_id: String,
+    /// Memories the user had when the run started
+    pub total: i64,
+    /// Memories re-embedded so far
+    pub processed: i64,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Spread `width_bucket` counts over the histogram; bucket numbers outside
+/// `1..=IMPORTANCE_BUCKETS` (importance 1.0 lands in bucket 11) are clamped
+fn importance_buckets(counts: &[(i32, i64)]) -> Vec<u32> {
+    let mut buckets = vec![0; IMPORTANCE_BUCKETS];
+    for &(bucket, count) in counts {
+        let index = (bucket.max(1) as usize).min(IMPORTANCE_BUCKETS) - 1;
+        buckets[index] += count as u32;
+    }
+    buckets
+}
+
+/// Tag added to memories moved by [`MemoryStore::merge_duplicate_users`]
+fn migrated_from_tag(old_user_id: &str) -> String {
+    format!("migrated_from:{}", old_user_id)
+}
+
+/// Move and tag the memories of `old_user_id` on `conn`, which may be a
+/// transaction shared with other stores
+pub(super) async fn move_memories(conn: &mut PgConnection, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+    let result = sqlx::query(sql::MERGE_USERS)
+        .bind(old_user_id)
+        .bind(new_user_id)
+        .bind(migrated_from_tag(old_user_id))
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Creation time before which memories are old enough to vacuum
+fn vacuum_cutoff(older_than: Duration) -> DateTime<Utc> {
+    let age = chrono::Duration::from_std(older_than).unwrap_or(chrono::Duration::MAX);
+    Utc::now().checked_sub_signed(age).unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// A memory row with its embedding column
+#[derive(FromRow)]
+struct MemoryWithEmbedding {
+    #[sqlx(flatten)]
+    memory: Memory,
+    embedding: Option<Vector>,
+}
+
+/// Encode an embedding as base64 of its little-endian f32 bytes
+pub fn encode_embedding(embedding: &[f32]) -> String {
+    let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decode an embedding produced by [`encode_embedding`]
+pub fn decode_embedding(encoded: &str) -> Result<Vec<f32>> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| Error::InvalidInput(format!("Invalid embedding encoding: {}", e)))?;
+    if bytes.len() % 4 != 0 {
+        return Err(Error::InvalidInput(format!(
+            "Embedding byte length {} is not a multiple of 4",
+            bytes.len()
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect())
+}
+
+/// Rows per `INSERT` in [`MemoryStore::save_batch`]; 14 binds each keeps
+/// a statement under Postgres' 65535 parameter limit
+const MAX_BATCH_ROWS: usize = 1000;
+
 /// Pre-built parameterized SQL templates for memory operations
 pub(crate) mod sql {
     /// Standard column list for SELECT queries
     pub const COLUMNS: &str = "id, user_id, content, summary, importance, tags, memory_type, metadata, source, created_at, updated_at, accessed_at, access_count";
 
+    /// Columns written by INSERT, in bind order
+    pub const INSERT_COLUMNS: &str = "id, user_id, content, summary, embedding, importance, tags, memory_type, metadata, source, created_at, updated_at, accessed_at, access_count";
+
+    /// Upsert clause shared by single and batch saves
+    ///
+    /// An existing row keeps its `created_at`, `accessed_at` and
+    /// `access_count`; only [`record_access`](super::MemoryStore::record_access)
+    /// moves the access statistics.
+    pub const ON_CONFLICT_UPDATE: &str = r#"
+        ON CONFLICT (id) DO UPDATE SET
+            content = EXCLUDED.content,
+            summary = EXCLUDED.summary,
+            embedding = EXCLUDED.embedding,
+            importance = EXCLUDED.importance,
+            tags = EXCLUDED.tags,
+            memory_type = EXCLUDED.memory_type,
+            metadata = EXCLUDED.metadata,
+            source = EXCLUDED.source,
+            updated_at = EXCLUDED.updated_at
+    "#;
+
+    /// Recompute `search_vector` (same weighting as the insert/update
+    /// trigger), optionally for one user only
+    pub const REBUILD_SEARCH_VECTOR: &str = r#"
+        UPDATE memories SET search_vector =
+          setweight(to_tsvector('simple', COALESCE(content, '')), 'A') ||
+          setweight(to_tsvector('simple', COALESCE(summary, '')), 'B') ||
+          setweight(to_tsvector('simple', COALESCE(array_to_string(tags, ' '), '')), 'C') ||
+          setweight(to_tsvector('simple', COALESCE(memory_type, '')), 'D')
+        WHERE ($1::text IS NULL OR user_id = $1)
+    "#;
+
+    /// Memory count per importance bucket for one user
+    pub const IMPORTANCE_BUCKET_COUNTS: &str = r#"
+        SELECT width_bucket(importance::float8, 0.0, 1.0, 10) AS bucket, COUNT(*)
+        FROM memories
+        WHERE user_id = $1
+        GROUP BY bucket
+    "#;
+
+    /// Count, mean and percentiles of one user's importance scores
+    pub const IMPORTANCE_SUMMARY: &str = r#"
+        SELECT COUNT(*),
+               AVG(importance)::float4,
+               (percentile_cont(0.5) WITHIN GROUP (ORDER BY importance))::float4,
+               (percentile_cont(0.9) WITHIN GROUP (ORDER BY importance))::float4
+        FROM memories
+        WHERE user_id = $1
+    "#;
+
+    /// Filter shared by `vacuum` and `count_vacuum_candidates`: a user's
+    /// memories created before $2 with importance below $3
+    pub const VACUUM_FILTER: &str = "WHERE user_id = $1 AND created_at < $2 AND importance < $3";
+
+    /// Row count, oldest row and total row size (bytes) for one user
+    pub const USAGE: &str = r#"
+        SELECT COUNT(*), MIN(created_at), COALESCE(SUM(pg_column_size(memories.*)), 0)::bigint
+        FROM memories
+        WHERE user_id = $1
+    "#;
+
+    /// One page of a user's memories, newest first; served by
+    /// `idx_memories_user_created`
+    pub const PAGE_ALL: &str = r#"
+        SELECT id, user_id, content, summary, importance, tags,
+               memory_type, metadata, source,
+               created_at, updated_at, accessed_at, access_count
+        FROM memories
+        WHERE user_id = $1
+        ORDER BY created_at DESC, id
+        LIMIT $2 OFFSET $3
+    "#;
+
+    /// Number of memories of one user
+    pub const COUNT: &str = "SELECT COUNT(*) FROM memories WHERE user_id = $1";
+
+    /// Move memories from user $1 to user $2, adding tag $3 once
+    pub const MERGE_USERS: &str = r#"
+        UPDATE memories SET
+            user_id = $2,
+            tags = CASE WHEN $3 = ANY(tags) THEN tags ELSE array_append(tags, $3) END,
+            updated_at = NOW()
+        WHERE user_id = $1
+    "#;
+
+    /// Reset one user's re-embedding progress, returning their memory count
+    pub const START_REEMBED: &str = r#"
+        INSERT INTO reembed_progress (user_id, total, processed, started_at)
+        SELECT $1, COUNT(*), 0, NOW() FROM memories WHERE user_id = $1
+        ON CONFLICT (user_id) DO UPDATE SET
+            total = EXCLUDED.total,
+            processed = 0,
+            started_at = EXCLUDED.started_at
+        RETURNING total
+    "#;
+
+    /// Next page of one user's memory contents, keyset-paginated by ID
+    pub const CONTENTS_AFTER: &str = r#"
+        SELECT id, content FROM memories
+        WHERE user_id = $1 AND ($2::uuid IS NULL OR id > $2)
+        ORDER BY id
+        LIMIT $3
+    "#;
+
     /// Semantic search with optional type filter
     pub const SEARCH_SEMANTIC_TYPED: &str = r#"
         SELECT id, user_id, content, summary, importance, tags,
@@ -212,6 +574,66 @@ pub(crate) mod sql {
     "#;
 }
 
+/// Keep only the last row for each memory ID, preserving order otherwise
+fn last_per_id<T>(rows: Vec<(Memory, T)>) -> Vec<(Memory, T)> {
+    let last_index: HashMap<Uuid, usize> = rows.iter().enumerate().map(|(i, (m, _))| (m.id, i)).collect();
+    rows.into_iter()
+        .enumerate()
+        .filter(|(i, (m, _))| last_index[&m.id] == *i)
+        .map(|(_, row)| row)
+        .collect()
+}
+
+/// Build one multi-row upsert for [`MemoryStore::save_batch`]
+fn upsert_rows(rows: &[(Memory, Option<Vector>)]) -> QueryBuilder<'_, Postgres> {
+    let mut builder = QueryBuilder::new(format!("INSERT INTO memories ({}) ", sql::INSERT_COLUMNS));
+    builder.push_values(rows, |mut row, (memory, embedding)| {
+        row.push_bind(memory.id)
+            .push_bind(&memory.user_id)
+            .push_bind(&memory.content)
+            .push_bind(&memory.summary)
+            .push_bind(embedding)
+            .push_bind(memory.importance)
+            .push_bind(&memory.tags)
+            .push_bind(&memory.memory_type)
+            .push_bind(&memory.metadata)
+            .push_bind(&memory.source)
+            .push_bind(memory.created_at)
+            .push_bind(memory.updated_at)
+            .push_bind(memory.accessed_at)
+            .push_bind(memory.access_count);
+    });
+    builder.push(sql::ON_CONFLICT_UPDATE);
+    builder
+}
+
+/// `UPDATE ... FROM (VALUES ...)` replacing the embedding of each listed memory
+fn update_embeddings_query(rows: &[(Uuid, Vector)]) -> QueryBuilder<'_, Postgres> {
+    let mut builder = QueryBuilder::new("UPDATE memories AS m SET embedding = v.embedding FROM (");
+    builder.push_values(rows, |mut row, (id, embedding)| {
+        row.push_bind(id).push_bind(embedding);
+    });
+    builder.push(") AS v(id, embedding) WHERE m.id = v.id");
+    builder
+}
+
+/// `UPDATE ... FROM (VALUES ...)` restoring the creation and access
+/// statistics of imported memories, which the upsert keeps for existing rows
+fn restore_stats_query(rows: &[(Memory, Option<Vector>)]) -> QueryBuilder<'_, Postgres> {
+    let mut builder = QueryBuilder::new(
+        "UPDATE memories AS m SET created_at = v.created_at, accessed_at = v.accessed_at, \
+         access_count = v.access_count FROM (",
+    );
+    builder.push_values(rows, |mut row, (memory, _)| {
+        row.push_bind(memory.id)
+            .push_bind(memory.created_at)
+            .push_bind(memory.accessed_at)
+            .push_bind(memory.access_count);
+    });
+    builder.push(") AS v(id, created_at, accessed_at, access_count) WHERE m.id = v.id");
+    builder
+}
+
 /// Memory store backed by PostgreSQL + pgvector
 #[derive(Clone)]
 pub struct MemoryStore {
@@ -228,42 +650,64 @@ impl MemoryStore {
     pub async fn save(&self, memory: &Memory, embedding: Option<Vec<f32>>) -> Result<()> {
         let embedding_vec = embedding.map(Vector::from);
 
-        sqlx::query(r#"
-            INSERT INTO memories (id, user_id, content, summary, embedding, importance, tags,
-                                  memory_type, metadata, source,
-                                  created_at, updated_at, accessed_at, access_count)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
-            ON CONFLICT (id) DO UPDATE SET
-                content = EXCLUDED.content,
-                summary = EXCLUDED.summary,
-                embedding = EXCLUDED.embedding,
-                importance = EXCLUDED.importance,
-                tags = EXCLUDED.tags,
-                memory_type = EXCLUDED.memory_type,
-                metadata = EXCLUDED.metadata,
-                source = EXCLUDED.source,
-                updated_at = EXCLUDED.updated_at
-        "#)
-        .bind(memory.id)
-        .bind(&memory.user_id)
-        .bind(&memory.content)
-        .bind(&memory.summary)
-        .bind(embedding_vec)
-        .bind(memory.importance)
-        .bind(&memory.tags)
-        .bind(&memory.memory_type)
-        .bind(&memory.metadata)
-        .bind(&memory.source)
-        .bind(memory.created_at)
-        .bind(memory.updated_at)
-        .bind(memory.accessed_at)
-        .bind(memory.access_count)
-        .execute(&self.pg_pool)
-        .await?;
+        let query = format!(
+            "INSERT INTO memories ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) {}",
+            sql::INSERT_COLUMNS,
+            sql::ON_CONFLICT_UPDATE
+        );
+        sqlx::query(&query)
+            .bind(memory.id)
+            .bind(&memory.user_id)
+            .bind(&memory.content)
+            .bind(&memory.summary)
+            .bind(embedding_vec)
+            .bind(memory.importance)
+            .bind(&memory.tags)
+            .bind(&memory.memory_type)
+            .bind(&memory.metadata)
+            .bind(&memory.source)
+            .bind(memory.created_at)
+            .bind(memory.updated_at)
+            .bind(memory.accessed_at)
+            .bind(memory.access_count)
+            .execute(&self.pg_pool)
+            .await?;
 
         Ok(())
     }
 
+    /// Upsert many memories in one `INSERT ... ON CONFLICT DO UPDATE` statement.
+    /// Returns the IDs written, in input order.
+    ///
+    /// Rows sharing an ID are collapsed to the last one, since Postgres
+    /// refuses to update the same row twice in one statement. Batches over
+    /// 1000 rows are split into several statements inside one transaction.
+    /// An empty batch returns without touching the database.
+    pub async fn save_batch(&self, rows: Vec<(Memory, Option<Vec<f32>>)>) -> Result<Vec<Uuid>> {
+        // An INSERT with no VALUES rows is a syntax error
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+        let ids: Vec<Uuid> = rows.iter().map(|(m, _)| m.id).collect();
+
+        let rows: Vec<(Memory, Option<Vector>)> = last_per_id(rows)
+            .into_iter()
+            .map(|(m, e)| (m, e.map(Vector::from)))
+            .collect();
+
+        if rows.len() <= MAX_BATCH_ROWS {
+            upsert_rows(&rows).build().execute(&self.pg_pool).await?;
+        } else {
+            let mut tx = self.pg_pool.begin().await?;
+            for chunk in rows.chunks(MAX_BATCH_ROWS) {
+                upsert_rows(chunk).build().execute(&mut *tx).await?;
+            }
+            tx.commit().await?;
+        }
+
+        Ok(ids)
+    }
+
     /// Get a memory by ID
     pub async fn get(&self, id: Uuid) -> Result<Option<Memory>> {
         let query = format!("SELECT {} FROM memories WHERE id = $1", sql::COLUMNS);
@@ -476,6 +920,36 @@ impl MemoryStore {
         Ok(memories)
     }
 
+    /// Page `page` (starting at 1) of `user_id`'s memories, `page_size` at a
+    /// time, newest first
+    ///
+    /// Pages past the end come back empty with the real totals, so callers
+    /// can clamp their position.
+    pub async fn page_all(&self, user_id: &str, page: u32, page_size: u32) -> Result<MemoryPage> {
+        if page_size == 0 {
+            return Err(Error::InvalidInput("Page size must be at least 1".to_string()));
+        }
+        let page = page.max(1);
+
+        let (total,): (i64,) = sqlx::query_as(sql::COUNT)
+            .bind(user_id)
+            .fetch_one(&self.pg_pool)
+            .await?;
+        let items: Vec<Memory> = sqlx::query_as(sql::PAGE_ALL)
+            .bind(user_id)
+            .bind(page_size as i64)
+            .bind((page as i64 - 1) * page_size as i64)
+            .fetch_all(&self.pg_pool)
+            .await?;
+
+        Ok(MemoryPage {
+            items,
+            page,
+            total_pages: page_count(total as u64, page_size),
+            total_items: total as u64,
+        })
+    }
+
     /// Get memories by tag
     pub async fn get_by_tag(&self, user_id: &str, tag: &str, limit: usize) -> Result<Vec<Memory>> {
         let query = format!(
@@ -492,6 +966,32 @@ impl MemoryStore {
         Ok(memories)
     }
 
+    /// Get memories matching any or all of `tags`
+    pub async fn search_by_tag(
+        &self,
+        user_id: &str,
+        tags: &[&str],
+        mode: TagMode,
+        limit: usize,
+    ) -> Result<Vec<Memory>> {
+        if tags.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = format!(
+            "SELECT {} FROM memories WHERE user_id = $1 AND tags {} $2 ORDER BY importance DESC, accessed_at DESC LIMIT $3",
+            sql::COLUMNS,
+            mode.operator()
+        );
+        let memories: Vec<Memory> = sqlx::query_as(&query)
+            .bind(user_id)
+            .bind(tags)
+            .bind(limit as i32)
+            .fetch_all(&self.pg_pool)
+            .await?;
+
+        Ok(memories)
+    }
+
     /// Get memories by type
     pub async fn search_by_type(
         &self,
@@ -590,12 +1090,325 @@ impl MemoryStore {
             })
             .collect())
     }
+
+    /// All of a user's memories that have an embedding, oldest first
+    pub async fn get_with_embeddings(&self, user_id: &str) -> Result<Vec<(Memory, Vec<f32>)>> {
+        let query = format!(
+            "SELECT {}, embedding FROM memories WHERE user_id = $1 AND embedding IS NOT NULL ORDER BY created_at ASC",
+            sql::COLUMNS
+        );
+        let rows = sqlx::query_as::<_, MemoryWithEmbedding>(&query)
+            .bind(user_id)
+            .fetch_all(&self.pg_pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((row.memory, row.embedding?.to_vec())))
+            .collect())
+    }
+
+    /// Stream all memories for a user to `writer` as JSONL. Returns the number written.
+    pub async fn export_jsonl(&self, user_id: &str, writer: &mut (dyn AsyncWrite + Unpin + Send)) -> Result<u64> {
+        let query = format!(
+            "SELECT {}, embedding FROM memories WHERE user_id = $1 ORDER BY created_at ASC",
+            sql::COLUMNS
+        );
+        let mut rows = sqlx::query_as::<_, MemoryWithEmbedding>(&query)
+            .bind(user_id)
+            .fetch(&self.pg_pool);
+
+        let mut count = 0u64;
+        while let Some(row) = rows.try_next().await? {
+            let record = MemoryRecord {
+                memory: row.memory,
+                embedding: row.embedding.map(|v| encode_embedding(v.as_slice())),
+            };
+            let mut line = serde_json::to_vec(&record)?;
+            line.push(b'\n');
+            writer.write_all(&line).await?;
+            count += 1;
+        }
+        writer.flush().await?;
+
+        Ok(count)
+    }
+
+    /// 10-bucket histogram and summary statistics of `user_id`'s memory
+    /// importance scores
+    pub async fn get_importance_distribution(&self, user_id: &str) -> Result<ImportanceHistogram> {
+        let counts: Vec<(i32, i64)> = sqlx::query_as(sql::IMPORTANCE_BUCKET_COUNTS)
+            .bind(user_id)
+            .fetch_all(&self.pg_pool)
+            .await?;
+        let (total, mean, p50, p90): (i64, Option<f32>, Option<f32>, Option<f32>) =
+            sqlx::query_as(sql::IMPORTANCE_SUMMARY)
+                .bind(user_id)
+                .fetch_one(&self.pg_pool)
+                .await?;
+
+        Ok(ImportanceHistogram {
+            buckets: importance_buckets(&counts),
+            total: total as u64,
+            mean: mean.unwrap_or(0.0),
+            p50: p50.unwrap_or(0.0),
+            p90: p90.unwrap_or(0.0),
+        })
+    }
+
+    /// Delete `user_id`'s memories created more than `older_than` ago whose
+    /// importance is below `below_importance`. Returns the number deleted.
+    pub async fn vacuum(&self, user_id: &str, older_than: Duration, below_importance: f32) -> Result<u64> {
+        let result = sqlx::query(&format!("DELETE FROM memories {}", sql::VACUUM_FILTER))
+            .bind(user_id)
+            .bind(vacuum_cutoff(older_than))
+            .bind(below_importance)
+            .execute(&self.pg_pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Number of memories [`vacuum`](Self::vacuum) would delete with the same arguments
+    pub async fn count_vacuum_candidates(
+        &self,
+        user_id: &str,
+        older_than: Duration,
+        below_importance: f32,
+    ) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM memories {}", sql::VACUUM_FILTER))
+            .bind(user_id)
+            .bind(vacuum_cutoff(older_than))
+            .bind(below_importance)
+            .fetch_one(&self.pg_pool)
+            .await?;
+        Ok(count as u64)
+    }
+
+    /// Number of memories stored for `user_id`
+    pub async fn count(&self, user_id: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(sql::COUNT)
+            .bind(user_id)
+            .fetch_one(&self.pg_pool)
+            .await?;
+        Ok(count as u64)
+    }
+
+    /// Move every memory of `old_user_id` to `new_user_id`
+    ///
+    /// For account migrations, e.g. when a user's Telegram ID changes.
+    /// Moved memories are tagged `migrated_from:{old_user_id}`. Returns the
+    /// number of memories moved.
+    pub async fn merge_duplicate_users(&self, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+        if old_user_id == new_user_id {
+            return Err(Error::InvalidInput("Cannot merge a user into itself".to_string()));
+        }
+        let mut conn = self.pg_pool.acquire().await?;
+        move_memories(&mut conn, old_user_id, new_user_id).await
+    }
+
+    /// Size, age and importance distribution of `user_id`'s memories
+    pub async fn stats(&self, user_id: &str) -> Result<MemoryStats> {
+        let (count, oldest, bytes): (i64, Option<DateTime<Utc>>, i64) = sqlx::query_as(sql::USAGE)
+            .bind(user_id)
+            .fetch_one(&self.pg_pool)
+            .await?;
+
+        Ok(MemoryStats {
+            total_count: count as u64,
+            oldest_memory_at: oldest,
+            storage_bytes: bytes as u64,
+            importance: self.get_importance_distribution(user_id).await?,
+        })
+    }
+
+    /// Recompute the full-text `search_vector` of every memory, or only
+    /// `user_id`'s, e.g. after rows were changed with the trigger disabled.
+    /// Returns the number of rows updated.
+    pub async fn full_text_index_rebuild(&self, user_id: Option<&str>) -> Result<u64> {
+        let result = sqlx::query(sql::REBUILD_SEARCH_VECTOR)
+            .bind(user_id)
+            .execute(&self.pg_pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Start (or restart) tracking a re-embedding run for `user_id`.
+    /// Returns the number of memories to process.
+    pub async fn start_reembed(&self, user_id: &str) -> Result<u64> {
+        let total: i64 = sqlx::query_scalar(sql::START_REEMBED)
+            .bind(user_id)
+            .fetch_one(&self.pg_pool)
+            .await?;
+        Ok(total as u64)
+    }
+
+    /// Record how many of `user_id`'s memories have been re-embedded
+    pub async fn record_reembed_progress(&self, user_id: &str, processed: u64) -> Result<()> {
+        sqlx::query("UPDATE reembed_progress SET processed = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(processed as i64)
+            .execute(&self.pg_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Progress of the last re-embedding run for `user_id`, if any
+    pub async fn reembed_progress(&self, user_id: &str) -> Result<Option<ReembedProgress>> {
+        let progress = sqlx::query_as::<_, ReembedProgress>(
+            "SELECT user_id, total, processed, started_at FROM reembed_progress WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pg_pool)
+        .await?;
+        Ok(progress)
+    }
+
+    /// Up to `limit` of `user_id`'s memories as `(id, content)`, ordered by
+    /// ID and starting after `after`
+    pub async fn contents_after(
+        &self,
+        user_id: &str,
+        after: Option<Uuid>,
+        limit: usize,
+    ) -> Result<Vec<(Uuid, String)>> {
+        let rows = sqlx::query_as(sql::CONTENTS_AFTER)
+            .bind(user_id)
+            .bind(after)
+            .bind(limit as i64)
+            .fetch_all(&self.pg_pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Replace the embeddings of the given memories in one statement.
+    /// Returns the number of rows updated.
+    pub async fn update_embeddings(&self, rows: Vec<(Uuid, Vec<f32>)>) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let rows: Vec<(Uuid, Vector)> = rows.into_iter().map(|(id, e)| (id, Vector::from(e))).collect();
+        let result = update_embeddings_query(&rows).build().execute(&self.pg_pool).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Read JSONL produced by [`export_jsonl`](Self::export_jsonl) and upsert the
+    /// memories in batches. Returns the number imported.
+    ///
+    /// Each batch is upserted and has its `created_at`, `accessed_at` and
+    /// `access_count` restored from the export in one transaction.
+    pub async fn import_jsonl(&self, reader: &mut (dyn AsyncBufRead + Unpin + Send)) -> Result<u64> {
+        let mut count = 0u64;
+        let mut users = BTreeSet::new();
+        let mut batch = Vec::with_capacity(MAX_BATCH_ROWS);
+        let mut lines = reader.lines();
+        let mut line_no = 0;
+        while let Some(line) = lines.next_line().await? {
+            line_no += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: MemoryRecord = serde_json::from_str(&line).map_err(|e| {
+                Error::InvalidInput(format!("Invalid memory record on line {}: {}", line_no, e))
+            })?;
+            let embedding = record.embedding.as_deref().map(decode_embedding).transpose()?;
+            users.insert(record.memory.user_id.clone());
+            batch.push((record.memory, embedding));
+
+            if batch.len() == MAX_BATCH_ROWS {
+                count += self.import_batch(std::mem::take(&mut batch)).await?;
+            }
+        }
+        if !batch.is_empty() {
+            count += self.import_batch(batch).await?;
+        }
+
+        self.after_import(&users).await?;
+        Ok(count)
+    }
+
+    /// Upsert one batch of imported memories and restore their statistics
+    async fn import_batch(&self, rows: Vec<(Memory, Option<Vec<f32>>)>) -> Result<u64> {
+        let count = rows.len() as u64;
+        let rows: Vec<(Memory, Option<Vector>)> = last_per_id(rows)
+            .into_iter()
+            .map(|(m, e)| (m, e.map(Vector::from)))
+            .collect();
+
+        let mut tx = self.pg_pool.begin().await?;
+        upsert_rows(&rows).build().execute(&mut *tx).await?;
+        restore_stats_query(&rows).build().execute(&mut *tx).await?;
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    /// Bring the full-text index of the imported users up to date
+    async fn after_import(&self, users: &BTreeSet<String>) -> Result<()> {
+        for user in users {
+            let rows = self.full_text_index_rebuild(Some(user)).await?;
+            debug!("Rebuilt full-text index for {} memories of {}", rows, user);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_update_embeddings_query() {
+        let rows = vec![
+            (Uuid::new_v4(), Vector::from(vec![0.1, 0.2])),
+            (Uuid::new_v4(), Vector::from(vec![0.3, 0.4])),
+        ];
+        assert_eq!(
+            update_embeddings_query(&rows).sql(),
+            "UPDATE memories AS m SET embedding = v.embedding FROM (VALUES ($1, $2), ($3, $4)) \
+             AS v(id, embedding) WHERE m.id = v.id"
+        );
+    }
+
+    #[test]
+    fn test_restore_stats_query() {
+        let rows = vec![(Memory::new("user", "a"), None), (Memory::new("user", "b"), None)];
+        assert_eq!(
+            restore_stats_query(&rows).sql(),
+            "UPDATE memories AS m SET created_at = v.created_at, accessed_at = v.accessed_at, \
+             access_count = v.access_count FROM (VALUES ($1, $2, $3, $4), ($5, $6, $7, $8)) \
+             AS v(id, created_at, accessed_at, access_count) WHERE m.id = v.id"
+        );
+    }
+
+    #[test]
+    fn test_vacuum_cutoff() {
+        let cutoff = vacuum_cutoff(Duration::from_secs(30 * 24 * 60 * 60));
+        let age = Utc::now() - cutoff;
+        assert!((age.num_days() - 30).abs() <= 1);
+        // Absurd ages clamp instead of overflowing
+        assert_eq!(vacuum_cutoff(Duration::MAX), DateTime::<Utc>::MIN_UTC);
+    }
+
+    #[test]
+    fn test_migrated_from_tag() {
+        assert_eq!(migrated_from_tag("123456"), "migrated_from:123456");
+    }
+
+    #[test]
+    fn test_page_count() {
+        assert_eq!(page_count(0, 10), 0);
+        assert_eq!(page_count(10, 10), 1);
+        assert_eq!(page_count(11, 10), 2);
+        assert_eq!(page_count(3, 1), 3);
+    }
+
+    #[test]
+    fn test_importance_buckets() {
+        // width_bucket puts 0.0 in bucket 1 and 1.0 in bucket 11
+        let buckets = importance_buckets(&[(1, 4), (5, 2), (10, 1), (11, 3)]);
+        assert_eq!(buckets, vec![4, 0, 0, 0, 2, 0, 0, 0, 0, 4]);
+        assert_eq!(importance_buckets(&[]), vec![0; IMPORTANCE_BUCKETS]);
+    }
+
     #[test]
     fn test_memory_creation() {
         let memory = Memory::new("user123", "Test content")
@@ -621,6 +1434,27 @@ mod tests {
         assert_eq!(memory.importance, 0.0);
     }
 
+    #[test]
+    fn test_tag_mode() {
+        assert_eq!("all".parse::<TagMode>().unwrap(), TagMode::All);
+        assert_eq!("ANY".parse::<TagMode>().unwrap(), TagMode::Any);
+        assert!("some".parse::<TagMode>().is_err());
+        assert_eq!(TagMode::All.operator(), "@>");
+        assert_eq!(TagMode::Any.operator(), "&&");
+    }
+
+    #[test]
+    fn test_last_per_id() {
+        let a = Memory::new("user", "a");
+        let b = Memory::new("user", "b");
+        let mut a2 = a.clone();
+        a2.content = "a2".to_string();
+
+        let rows = last_per_id(vec![(a, 1), (b, 2), (a2, 3)]);
+        let contents: Vec<(&str, i32)> = rows.iter().map(|(m, n)| (m.content.as_str(), *n)).collect();
+        assert_eq!(contents, vec![("b", 2), ("a2", 3)]);
+    }
+
     #[test]
     fn test_memory_type() {
         let memory = Memory::new("user", "content")
@@ -650,4 +1484,70 @@ mod tests {
         assert_eq!(memory.metadata, serde_json::json!({"key": "value"}));
         assert_eq!(memory.source, "tool:memory_save");
     }
+
+    #[test]
+    fn test_memory_merge_average() {
+        let older = Utc::now() - chrono::Duration::days(3);
+        let mut a = Memory::new("user", "Likes tea")
+            .with_importance(0.4)
+            .with_tags(vec!["preference".to_string(), "drink".to_string()]);
+        a.created_at = older;
+        a.access_count = 2;
+        let mut b = Memory::new("user", "Prefers green tea")
+            .with_importance(0.8)
+            .with_tags(vec!["drink".to_string(), "tea".to_string()]);
+        b.access_count = 3;
+
+        let merged = a.merge(&b, MergeStrategy::Average);
+        assert_eq!(merged.id, a.id);
+        assert_eq!(merged.content, "Likes tea\n---\nPrefers green tea");
+        assert!((merged.importance - 0.6).abs() < f32::EPSILON);
+        assert_eq!(merged.tags, vec!["preference", "drink", "tea"]);
+        assert_eq!(merged.access_count, 5);
+        assert_eq!(merged.created_at, older);
+    }
+
+    #[test]
+    fn test_memory_merge_strategies() {
+        let a = Memory::new("user", "old").with_importance(0.9).with_summary("old summary");
+        let b = Memory::new("user", "new").with_importance(0.3);
+
+        let appended = a.merge(&b, MergeStrategy::Append);
+        assert_eq!(appended.content, "old\n---\nnew");
+        assert_eq!(appended.importance, 0.9);
+
+        let replaced = a.merge(&b, MergeStrategy::Replace);
+        assert_eq!(replaced.content, "new");
+        assert_eq!(replaced.importance, 0.3);
+        assert_eq!(replaced.summary.as_deref(), Some("old summary"));
+    }
+
+    #[test]
+    fn test_embedding_round_trip() {
+        let embedding = vec![0.0, -1.5, 3.25, f32::MIN_POSITIVE, 1e-7];
+        let encoded = encode_embedding(&embedding);
+        assert_eq!(decode_embedding(&encoded).unwrap(), embedding);
+
+        assert!(decode_embedding("AAA=").is_err());
+        assert!(decode_embedding("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_memory_record_round_trip() {
+        let record = MemoryRecord {
+            memory: Memory::new("user", "content")
+                .with_tags(vec!["a".to_string()])
+                .with_metadata(serde_json::json!({"k": 1})),
+            embedding: Some(encode_embedding(&[0.5, 0.25])),
+        };
+
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: MemoryRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.memory.id, record.memory.id);
+        assert_eq!(parsed.memory.tags, record.memory.tags);
+        assert_eq!(parsed.memory.metadata, record.memory.metadata);
+        assert_eq!(parsed.memory.created_at, record.memory.created_at);
+        assert_eq!(decode_embedding(parsed.embedding.as_deref().unwrap()).unwrap(), vec![0.5, 0.25]);
+    }
 }
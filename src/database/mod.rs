@@ -4,16 +4,27 @@
 //! - PostgreSQL with pgvector: Long-term semantic memory and structured data
 //! - PostgreSQL tsvector: Full-text search across memories
 
+mod approved_users;
 mod config_params;
+mod conversation_store;
+mod loop_trace_store;
 mod postgres;
 mod memory;
 mod soul;
 mod tasks;
+mod users;
 mod agent_status;
 
-pub use config_params::{ConfigParam, ConfigParamStore, ConfigValueType};
-pub use postgres::{PostgresPool, init_pool, init_pool_for_migrations, migrations};
-pub use memory::{Memory, MemoryStore, MemoryType};
-pub use soul::{SoulSection, SoulStore};
-pub use tasks::{AgentTask, TaskStatus, TaskStore};
-pub use agent_status::{AgentState, AgentStatusRow, AgentStatusStore};
+pub use approved_users::{ApprovedUser, ApprovedUserStore};
+pub use config_params::{ConfigParam, ConfigParamStore, ConfigValueType, KNOWN_CONFIG_PARAMS, USER_CONFIG_KEYS};
+pub use conversation_store::ConversationStore;
+pub use loop_trace_store::{LoopTraceStore, LOOP_TRACE_RETENTION_HOURS};
+pub use postgres::{PoolStats, PostgresPool, init_pool, init_pool_for_migrations, migrations};
+pub use memory::{
+    decode_embedding, encode_embedding, ImportanceHistogram, Memory, MemoryPage, MemoryRecord, MemoryStats, MemoryStore, MemoryType,
+    MergeStrategy, ReembedProgress, TagMode, IMPORTANCE_BUCKETS, MERGE_SEPARATOR,
+};
+pub use soul::{SoulSection, SoulStore, SOUL_UPDATED_CHANNEL};
+pub use tasks::{parse_cron_schedule, AgentTask, RecurringTask, TaskStats, TaskStatus, TaskStore};
+pub use users::{count_user_data, merge_users, user_config_category, UserDataCounts};
+pub use agent_status::{AgentState, AgentStatusRow, AgentStatusStore, AgentStatusTransition};
@@ -3,6 +3,7 @@
 use crate::config::DatabaseConfig;
 use crate::error::{Error, Result};
 use secrecy::ExposeSecret;
+use serde::Serialize;
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::time::Duration;
 use tracing::info;
@@ -23,11 +24,17 @@ pub async fn init_pool_for_migrations(config: &DatabaseConfig) -> Result<Postgre
 
 /// Initialize the PostgreSQL connection pool with options
 async fn init_pool_with_options(config: &DatabaseConfig, require_pgvector: bool) -> Result<PostgresPool> {
-    info!("Initializing PostgreSQL connection pool");
+    let pool_config = config.connection_pool_config();
+    info!(
+        "Initializing PostgreSQL connection pool ({}-{} connections)",
+        pool_config.min_connections, pool_config.max_connections
+    );
 
     let pool = PgPoolOptions::new()
-        .max_connections(config.max_connections)
-        .acquire_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .min_connections(pool_config.min_connections)
+        .max_connections(pool_config.max_connections)
+        .acquire_timeout(Duration::from_secs(pool_config.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(pool_config.idle_timeout_secs))
         .connect(config.url.expose_secret())
         .await?;
 
@@ -38,6 +45,30 @@ async fn init_pool_with_options(config: &DatabaseConfig, require_pgvector: bool)
     Ok(pool)
 }
 
+/// Point-in-time connection counts for a pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    /// Open connections (idle + in use)
+    pub pool_size: u32,
+    /// Open connections waiting to be acquired
+    pub idle_connections: u32,
+    /// Connections currently checked out
+    pub active_connections: u32,
+}
+
+impl PoolStats {
+    /// Read the current counts from `pool`
+    pub fn from_pool(pool: &PgPool) -> Self {
+        let pool_size = pool.size();
+        let idle_connections = pool.num_idle() as u32;
+        PoolStats {
+            pool_size,
+            idle_connections,
+            active_connections: pool_size.saturating_sub(idle_connections),
+        }
+    }
+}
+
 /// Verify database connection and optionally check for required extensions
 async fn verify_database(pool: &PgPool, require_pgvector: bool) -> Result<()> {
     // Check connection
@@ -100,6 +131,41 @@ pub mod migrations {
         .execute(pool)
         .await?;
 
+        // Full message list for ConversationStore (tool calls included)
+        sqlx::query(
+            "ALTER TABLE conversations ADD COLUMN IF NOT EXISTS message_json JSONB NOT NULL DEFAULT '[]'"
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_conversations_user_updated ON conversations(user_id, updated_at DESC)"
+        )
+        .execute(pool)
+        .await?;
+
+        // Conversations are keyed by (channel, user_id); rows from before
+        // namespacing are Telegram, except Slack ones stored as `slack:<id>`
+        sqlx::query(
+            "ALTER TABLE conversations ADD COLUMN IF NOT EXISTS channel TEXT NOT NULL DEFAULT 'telegram'"
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query(r#"
+            UPDATE conversations
+            SET channel = 'slack', user_id = substr(user_id, 7)
+            WHERE channel = 'telegram' AND user_id LIKE 'slack:%'
+        "#)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_conversations_session_updated ON conversations(channel, user_id, updated_at DESC)"
+        )
+        .execute(pool)
+        .await?;
+
         // Create messages table
         sqlx::query(r#"
             CREATE TABLE IF NOT EXISTS messages (
@@ -201,6 +267,13 @@ pub mod migrations {
         .execute(pool)
         .await?;
 
+        // Newest-first paging for memory browsers
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_memories_user_created ON memories(user_id, created_at DESC)"
+        )
+        .execute(pool)
+        .await?;
+
         // --- tsvector full-text search for memories ---
 
         // Add tsvector column
@@ -349,6 +422,56 @@ pub mod migrations {
         .await
         .ok(); // Ignore if partial index syntax not supported
 
+        // --- Recurring tasks table ---
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS recurring_tasks (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id TEXT NOT NULL,
+                chat_id BIGINT,
+                title TEXT NOT NULL,
+                description TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                cron_schedule TEXT NOT NULL,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                last_run_at TIMESTAMPTZ,
+                next_run_at TIMESTAMPTZ NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_recurring_tasks_due ON recurring_tasks(next_run_at) WHERE enabled"
+        )
+        .execute(pool)
+        .await
+        .ok(); // Ignore if partial index syntax not supported
+
+        // --- Task dependencies ---
+        // task_id is an agent task or a recurring task; it waits until every
+        // agent task it depends on has finished.
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS task_dependencies (
+                task_id UUID NOT NULL,
+                depends_on_task_id UUID NOT NULL REFERENCES agent_tasks(id) ON DELETE CASCADE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (task_id, depends_on_task_id),
+                CHECK (task_id <> depends_on_task_id)
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_task_dependencies_depends_on ON task_dependencies(depends_on_task_id)"
+        )
+        .execute(pool)
+        .await?;
+
         // --- Agent status table (singleton) ---
 
         sqlx::query(r#"
@@ -372,6 +495,73 @@ pub mod migrations {
         .execute(pool)
         .await?;
 
+        // --- Agent status history (append-only transition log) ---
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS agent_status_history (
+                id BIGSERIAL PRIMARY KEY,
+                from_state TEXT NOT NULL,
+                to_state TEXT NOT NULL,
+                task_id UUID,
+                transitioned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_agent_status_history_transitioned_at ON agent_status_history(transitioned_at DESC)"
+        )
+        .execute(pool)
+        .await?;
+
+        // --- Agentic loop traces (kept 24h for replay) ---
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS loop_traces (
+                id UUID PRIMARY KEY,
+                user_id TEXT,
+                outcome TEXT NOT NULL,
+                trace JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS idx_loop_traces_created_at ON loop_traces(created_at)"
+        )
+        .execute(pool)
+        .await?;
+
+        // --- Re-embedding progress (one row per user, see EmbeddingService::reembed_all) ---
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS reembed_progress (
+                user_id TEXT PRIMARY KEY,
+                total BIGINT NOT NULL,
+                processed BIGINT NOT NULL DEFAULT 0,
+                started_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
+        // --- Approved DM users (pairing / allowlist) ---
+
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS approved_users (
+                user_id BIGINT NOT NULL,
+                channel TEXT NOT NULL,
+                approved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                approved_by BIGINT,
+                PRIMARY KEY (channel, user_id)
+            )
+        "#)
+        .execute(pool)
+        .await?;
+
         info!("Database migrations completed");
         Ok(())
     }
@@ -453,13 +643,14 @@ pub mod conversations {
     pub async fn save(pool: &PgPool, conv: &Conversation) -> Result<()> {
         // Upsert conversation
         sqlx::query(r#"
-            INSERT INTO conversations (id, user_id, model, system_prompt, created_at, updated_at, total_tokens)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO conversations (id, channel, user_id, model, system_prompt, created_at, updated_at, total_tokens)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             ON CONFLICT (id) DO UPDATE SET
                 updated_at = EXCLUDED.updated_at,
                 total_tokens = EXCLUDED.total_tokens
         "#)
         .bind(conv.id)
+        .bind(&conv.channel)
         .bind(&conv.user_id)
         .bind(&conv.model)
         .bind(&conv.system_prompt)
@@ -477,9 +668,10 @@ pub mod conversations {
 
         for msg in &conv.messages {
             sqlx::query(r#"
-                INSERT INTO messages (conversation_id, role, content, tool_call_id)
-                VALUES ($1, $2, $3, $4)
+                INSERT INTO messages (id, conversation_id, role, content, tool_call_id)
+                VALUES (COALESCE($1, gen_random_uuid()), $2, $3, $4, $5)
             "#)
+            .bind((!msg.checkpoint_id.is_nil()).then_some(msg.checkpoint_id))
             .bind(conv.id)
             .bind(msg.role.to_string())
             .bind(&msg.content)
@@ -497,6 +689,7 @@ pub mod conversations {
         #[derive(sqlx::FromRow)]
         struct ConvRow {
             id: Uuid,
+            channel: String,
             user_id: String,
             model: String,
             system_prompt: Option<String>,
@@ -518,13 +711,14 @@ pub mod conversations {
 
         #[derive(sqlx::FromRow)]
         struct MsgRow {
+            id: Uuid,
             role: String,
             content: String,
             tool_call_id: Option<String>,
         }
 
         let msg_rows: Vec<MsgRow> = sqlx::query_as(
-            "SELECT role, content, tool_call_id FROM messages WHERE conversation_id = $1 ORDER BY created_at"
+            "SELECT id, role, content, tool_call_id FROM messages WHERE conversation_id = $1 ORDER BY created_at"
         )
         .bind(id)
         .fetch_all(pool)
@@ -546,12 +740,15 @@ pub mod conversations {
                     name: None,
                     tool_call_id: r.tool_call_id,
                     tool_calls: None,
+                    checkpoint_id: r.id,
+                    tool_record: None,
                 }
             })
             .collect();
 
         Ok(Some(Conversation {
             id: row.id,
+            channel: row.channel,
             user_id: row.user_id,
             messages,
             system_prompt: row.system_prompt,
@@ -592,4 +789,42 @@ pub mod conversations {
 #[cfg(test)]
 mod tests {
     // Database tests would require a test database setup
+    use super::*;
+    use crate::config::PoolConfig;
+    use secrecy::SecretString;
+
+    /// Run with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL"]
+    async fn test_pool_handles_concurrent_connections() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let config = DatabaseConfig {
+            url: SecretString::from(url),
+            pool: PoolConfig {
+                min_connections: 1,
+                max_connections: 10,
+                acquire_timeout_secs: 10,
+                idle_timeout_secs: 60,
+            },
+            enable_pgvector: false,
+        };
+        let pool = init_pool_for_migrations(&config).await.unwrap();
+
+        // 50 concurrent queries share 10 connections without acquire timeouts
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    sqlx::query("SELECT pg_sleep(0.05)").execute(&pool).await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().expect("connection acquire timed out");
+        }
+
+        let stats = PoolStats::from_pool(&pool);
+        assert!(stats.pool_size <= 10);
+        assert_eq!(stats.active_connections, 0);
+    }
 }
@@ -8,6 +8,7 @@ use crate::database::PostgresPool;
 use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
 use sqlx::FromRow;
 use tracing::info;
 use uuid::Uuid;
@@ -15,6 +16,9 @@ use uuid::Uuid;
 /// Sections that cannot be updated after initialization
 const IMMUTABLE_SECTIONS: &[&str] = &["Identity", "Core Values", "Boundaries"];
 
+/// Postgres channel notified with the section name after each update
+pub const SOUL_UPDATED_CHANNEL: &str = "soul_updated";
+
 /// A soul section stored in the database
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct SoulSection {
@@ -102,29 +106,64 @@ impl SoulStore {
         Ok(section)
     }
 
+    /// Export every section in order, e.g. for editing through the gateway API
+    pub async fn export_sections(&self) -> Result<Vec<SoulSection>> {
+        self.get_all_sections().await
+    }
+
     /// Update a mutable section's content, incrementing version.
-    /// Returns error if section is immutable or doesn't exist.
+    ///
+    /// Sends `NOTIFY soul_updated` with the section name on commit so running
+    /// agents can reload the soul. Returns `NotFound` for unknown sections and
+    /// `InvalidInput` for immutable ones.
     pub async fn update_section(&self, name: &str, new_content: &str) -> Result<()> {
-        let result = sqlx::query(r#"
+        let mut tx = self.pool.begin().await?;
+
+        let is_mutable: Option<bool> = sqlx::query_scalar(
+            "SELECT is_mutable FROM agent_soul_sections WHERE section_name = $1 FOR UPDATE",
+        )
+        .bind(name)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match is_mutable {
+            None => return Err(Error::NotFound(format!("Soul section '{}'", name))),
+            Some(false) => {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot update section '{}': section is immutable",
+                    name
+                )))
+            }
+            Some(true) => {}
+        }
+
+        sqlx::query(r#"
             UPDATE agent_soul_sections
             SET content = $1, version = version + 1, updated_at = NOW()
-            WHERE section_name = $2 AND is_mutable = TRUE
+            WHERE section_name = $2
         "#)
         .bind(new_content)
         .bind(name)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(Error::Internal(format!(
-                "Cannot update section '{}': does not exist or is immutable",
-                name
-            )));
-        }
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(SOUL_UPDATED_CHANNEL)
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
 
+        tx.commit().await?;
         Ok(())
     }
 
+    /// Subscribe to `soul_updated` notifications; each payload is a section name
+    pub async fn listen_updates(&self) -> Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(SOUL_UPDATED_CHANNEL).await?;
+        Ok(listener)
+    }
+
     /// Delete all soul sections (used for reset)
     pub async fn delete_all(&self) -> Result<()> {
         sqlx::query("DELETE FROM agent_soul_sections")
@@ -2,16 +2,29 @@
 //!
 //! Tracks tasks created from user requests with status lifecycle:
 //! pending → processing → finish/fail/cancel/stop
+//!
+//! Recurring tasks carry a cron schedule and spawn a fresh pending task
+//! each time they come due.
+//!
+//! Tasks can depend on other agent tasks (`task_dependencies`). A pending
+//! task is not handed out, and a due recurring task does not fire, until
+//! every task it depends on has finished. When a prerequisite ends any other
+//! way (fail, cancel or stop), its pending dependents are cancelled and
+//! recurring dependents are disabled, transitively, since they could never
+//! run.
 
 use crate::database::PostgresPool;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use chrono::{DateTime, Utc};
+use cron::Schedule;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, PgConnection};
 use uuid::Uuid;
 
 /// Task status enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TaskStatus {
     Pending,
@@ -59,6 +72,33 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
+/// Task counts for status displays
+///
+/// `complete` and `failed` count finished and failed tasks; cancelled and
+/// stopped tasks only appear in `total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TaskStats {
+    pub pending: u64,
+    pub processing: u64,
+    pub complete: u64,
+    pub failed: u64,
+    pub total: u64,
+}
+
+impl TaskStats {
+    /// Summarize the result of [`TaskStore::count_by_status`]
+    pub fn from_counts(counts: &HashMap<TaskStatus, u64>) -> Self {
+        let count = |status| counts.get(&status).copied().unwrap_or(0);
+        TaskStats {
+            pending: count(TaskStatus::Pending),
+            processing: count(TaskStatus::Processing),
+            complete: count(TaskStatus::Finish),
+            failed: count(TaskStatus::Fail),
+            total: counts.values().sum(),
+        }
+    }
+}
+
 /// An agent task
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct AgentTask {
@@ -84,6 +124,150 @@ impl AgentTask {
     }
 }
 
+/// A task template that is instantiated on a cron schedule
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RecurringTask {
+    pub id: Uuid,
+    pub user_id: String,
+    pub chat_id: Option<i64>,
+    pub title: String,
+    pub description: String,
+    pub priority: i32,
+    pub cron_schedule: String,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub next_run_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Parse a cron expression.
+///
+/// Accepts standard 5-field expressions (`min hour dom month dow`, with
+/// POSIX days of week 0-7 where 0 and 7 are Sunday) as well as the 6/7-field
+/// form with a leading seconds field, which uses the `cron` crate's own
+/// numbering (1-7, Sunday = 1).
+pub fn parse_cron_schedule(expr: &str) -> Result<Schedule> {
+    let expr = expr.trim();
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    let normalized = if let [minute, hour, day, month, weekday] = fields[..] {
+        let weekday = posix_day_of_week(weekday)
+            .ok_or_else(|| Error::InvalidInput(format!("Invalid day of week '{}' in cron schedule '{}'", weekday, expr)))?;
+        format!("0 {} {} {} {} {}", minute, hour, day, month, weekday)
+    } else {
+        expr.to_string()
+    };
+    Schedule::from_str(&normalized)
+        .map_err(|e| Error::InvalidInput(format!("Invalid cron schedule '{}': {}", expr, e)))
+}
+
+/// Rewrite a POSIX day-of-week field (0-7, Sunday = 0 or 7) in the `cron`
+/// crate's numbering (1-7, Sunday = 1)
+///
+/// Numeric ranges, steps and lists are expanded to the days they cover, so
+/// `5-7` (Friday to Sunday) does not become a wrapping range. Day names pass
+/// through unchanged.
+fn posix_day_of_week(field: &str) -> Option<String> {
+    if field == "*" || field == "?" {
+        return Some(field.to_string());
+    }
+
+    let mut names = Vec::new();
+    let mut days = BTreeSet::new();
+    for item in field.split(',') {
+        if item.chars().any(|c| c.is_ascii_alphabetic()) {
+            names.push(item.to_string());
+            continue;
+        }
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().ok().filter(|s| *s > 0)?),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (0, 6),
+            Some((start, end)) => (start.parse::<u32>().ok()?, end.parse::<u32>().ok()?),
+            // `n/step` runs from n to the end of the week
+            None if item.contains('/') => (range.parse::<u32>().ok()?, 6),
+            None => {
+                let day = range.parse::<u32>().ok()?;
+                (day, day)
+            }
+        };
+        if start > end || end > 7 {
+            return None;
+        }
+        days.extend((start..=end).step_by(step).map(|day| day % 7 + 1));
+    }
+
+    names.extend(days.iter().map(u32::to_string));
+    Some(names.join(","))
+}
+
+/// Filters out rows of `table` that still have an unfinished prerequisite
+macro_rules! dependencies_finished {
+    ($table:literal) => {
+        concat!(
+            "NOT EXISTS (
+                SELECT 1 FROM task_dependencies d
+                JOIN agent_tasks prerequisite ON prerequisite.id = d.depends_on_task_id
+                WHERE d.task_id = ", $table, ".id AND prerequisite.status <> 'finish'
+            )"
+        )
+    };
+}
+
+/// Cancels the pending agent tasks and disables the recurring tasks that
+/// depend, directly or transitively, on the agent task `$1`; `$2` is the
+/// error message recorded on the cancelled tasks
+const CANCEL_DEPENDENTS: &str = r#"
+    WITH RECURSIVE dependents(id) AS (
+        SELECT task_id FROM task_dependencies WHERE depends_on_task_id = $1
+        UNION
+        SELECT d.task_id
+        FROM task_dependencies d
+        JOIN dependents p ON d.depends_on_task_id = p.id
+    ),
+    cancelled AS (
+        UPDATE agent_tasks
+        SET status = 'cancel', error_message = $2, completed_at = NOW(), updated_at = NOW()
+        WHERE id IN (SELECT id FROM dependents) AND status = 'pending'
+        RETURNING id
+    ),
+    disabled AS (
+        UPDATE recurring_tasks
+        SET enabled = FALSE, updated_at = NOW()
+        WHERE id IN (SELECT id FROM dependents) AND enabled
+        RETURNING id
+    )
+    SELECT (SELECT COUNT(*) FROM cancelled) + (SELECT COUNT(*) FROM disabled)
+"#;
+
+/// Move the agent and recurring tasks of `old_user_id` on `conn`, which
+/// should be a transaction
+pub(super) async fn move_tasks(conn: &mut PgConnection, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+    let mut moved = 0;
+    for table in ["agent_tasks", "recurring_tasks"] {
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET user_id = $2, updated_at = NOW() WHERE user_id = $1",
+            table
+        ))
+        .bind(old_user_id)
+        .bind(new_user_id)
+        .execute(&mut *conn)
+        .await?;
+        moved += result.rows_affected();
+    }
+    Ok(moved)
+}
+
+/// Next fire time of a schedule strictly after `after`
+fn next_run_after(schedule: &Schedule, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    schedule
+        .after(&after)
+        .next()
+        .ok_or_else(|| Error::InvalidInput("Cron schedule has no upcoming run".into()))
+}
+
 /// Task store backed by PostgreSQL
 #[derive(Clone)]
 pub struct TaskStore {
@@ -130,16 +314,155 @@ impl TaskStore {
         Ok(task)
     }
 
-    /// Get the next pending task (ordered by priority desc, created_at asc).
+    /// Create a task that waits for `depends_on` to finish. The task and its
+    /// dependencies are written in one transaction, so it is never picked up
+    /// early.
+    pub async fn create_with_dependencies(
+        &self,
+        user_id: &str,
+        chat_id: Option<i64>,
+        title: &str,
+        description: &str,
+        priority: i32,
+        depends_on: &[Uuid],
+    ) -> Result<AgentTask> {
+        let mut tx = self.pool.begin().await?;
+        let task: AgentTask = sqlx::query_as(r#"
+            INSERT INTO agent_tasks (user_id, chat_id, title, description, priority)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+        "#)
+        .bind(user_id)
+        .bind(chat_id)
+        .bind(title)
+        .bind(description)
+        .bind(priority)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for dep_id in depends_on {
+            sqlx::query(
+                "INSERT INTO task_dependencies (task_id, depends_on_task_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            )
+            .bind(task.id)
+            .bind(dep_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(task)
+    }
+
+    /// Make `task_id` (an agent task or a recurring task) wait for the agent
+    /// task `dep_id` to finish. Rejects dependencies that would form a cycle
+    /// and prerequisites that already ended without finishing.
+    ///
+    /// The checks and the insert run in one transaction that locks
+    /// `task_dependencies`, so concurrent calls cannot together form a cycle.
+    pub async fn add_dependency(&self, task_id: Uuid, dep_id: Uuid) -> Result<()> {
+        if task_id == dep_id {
+            return Err(Error::InvalidInput("A task cannot depend on itself".into()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("LOCK TABLE task_dependencies IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut *tx)
+            .await?;
+
+        let (task_exists, dep_status): (bool, Option<String>) = sqlx::query_as(r#"
+            SELECT
+                EXISTS (SELECT 1 FROM agent_tasks WHERE id = $1)
+                    OR EXISTS (SELECT 1 FROM recurring_tasks WHERE id = $1),
+                (SELECT status FROM agent_tasks WHERE id = $2 FOR SHARE)
+        "#)
+        .bind(task_id)
+        .bind(dep_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !task_exists {
+            return Err(Error::NotFound(format!("Task {}", task_id)));
+        }
+        let dep_status = match dep_status {
+            Some(status) => TaskStatus::from_str(&status),
+            None => return Err(Error::NotFound(format!("Task {}", dep_id))),
+        };
+        if dep_status.is_terminal() && dep_status != TaskStatus::Finish {
+            return Err(Error::InvalidInput(format!(
+                "Task {} ended with status {} and will never finish",
+                dep_id,
+                dep_status.as_str()
+            )));
+        }
+
+        // The new edge closes a cycle if task_id is already a prerequisite of dep_id
+        let (cycle,): (bool,) = sqlx::query_as(r#"
+            WITH RECURSIVE prerequisites(id) AS (
+                SELECT $1::uuid
+                UNION
+                SELECT d.depends_on_task_id
+                FROM task_dependencies d
+                JOIN prerequisites p ON d.task_id = p.id
+            )
+            SELECT EXISTS (SELECT 1 FROM prerequisites WHERE id = $2)
+        "#)
+        .bind(dep_id)
+        .bind(task_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if cycle {
+            return Err(Error::InvalidInput(format!(
+                "Task {} already depends on {}; adding this dependency would create a cycle",
+                dep_id, task_id
+            )));
+        }
+
+        sqlx::query(
+            "INSERT INTO task_dependencies (task_id, depends_on_task_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(task_id)
+        .bind(dep_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Every dependency edge reachable from `root_id`, as
+    /// `(task_id, depends_on_task_id)` pairs
+    pub async fn get_dependency_graph(&self, root_id: Uuid) -> Result<Vec<(Uuid, Uuid)>> {
+        let edges: Vec<(Uuid, Uuid)> = sqlx::query_as(r#"
+            WITH RECURSIVE edges(task_id, depends_on_task_id) AS (
+                SELECT task_id, depends_on_task_id
+                FROM task_dependencies
+                WHERE task_id = $1
+                UNION
+                SELECT d.task_id, d.depends_on_task_id
+                FROM task_dependencies d
+                JOIN edges e ON d.task_id = e.depends_on_task_id
+            )
+            SELECT task_id, depends_on_task_id FROM edges
+            ORDER BY task_id, depends_on_task_id
+        "#)
+        .bind(root_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(edges)
+    }
+
+    /// Get the next pending task whose dependencies have all finished
+    /// (ordered by priority desc, created_at asc).
     /// Uses FOR UPDATE SKIP LOCKED for safe concurrent access.
     pub async fn next_pending(&self) -> Result<Option<AgentTask>> {
-        let task: Option<AgentTask> = sqlx::query_as(r#"
-            SELECT * FROM agent_tasks
+        let task: Option<AgentTask> = sqlx::query_as(concat!(
+            "SELECT * FROM agent_tasks
             WHERE status = 'pending'
+              AND ", dependencies_finished!("agent_tasks"), "
             ORDER BY priority DESC, created_at ASC
             LIMIT 1
-            FOR UPDATE SKIP LOCKED
-        "#)
+            FOR UPDATE SKIP LOCKED"
+        ))
         .fetch_optional(&self.pool)
         .await?;
         Ok(task)
@@ -172,43 +495,48 @@ impl TaskStore {
         Ok(())
     }
 
-    /// Mark a task as failed with error message
+    /// Mark a task as failed with error message; its dependents are
+    /// cancelled
     pub async fn fail(&self, id: Uuid, error: &str) -> Result<()> {
-        sqlx::query(r#"
-            UPDATE agent_tasks
-            SET status = 'fail', error_message = $2, completed_at = NOW(), updated_at = NOW()
-            WHERE id = $1
-        "#)
-        .bind(id)
-        .bind(error)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        self.end_unfinished(id, TaskStatus::Fail, Some(error)).await
     }
 
-    /// Cancel a task
+    /// Cancel a task and its dependents
     pub async fn cancel(&self, id: Uuid) -> Result<()> {
-        sqlx::query(r#"
-            UPDATE agent_tasks
-            SET status = 'cancel', completed_at = NOW(), updated_at = NOW()
-            WHERE id = $1
-        "#)
-        .bind(id)
-        .execute(&self.pool)
-        .await?;
-        Ok(())
+        self.end_unfinished(id, TaskStatus::Cancel, None).await
     }
 
-    /// Stop a task
+    /// Stop a task; its dependents are cancelled
     pub async fn stop(&self, id: Uuid) -> Result<()> {
+        self.end_unfinished(id, TaskStatus::Stop, None).await
+    }
+
+    /// Move a task to a terminal status other than finish and, in the same
+    /// transaction, cancel the tasks waiting on it
+    async fn end_unfinished(&self, id: Uuid, status: TaskStatus, error: Option<&str>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
         sqlx::query(r#"
             UPDATE agent_tasks
-            SET status = 'stop', completed_at = NOW(), updated_at = NOW()
+            SET status = $2, error_message = COALESCE($3, error_message),
+                completed_at = NOW(), updated_at = NOW()
             WHERE id = $1
         "#)
         .bind(id)
-        .execute(&self.pool)
+        .bind(status.as_str())
+        .bind(error)
+        .execute(&mut *tx)
         .await?;
+
+        let (affected,): (i64,) = sqlx::query_as(CANCEL_DEPENDENTS)
+            .bind(id)
+            .bind(format!("Dependency {} ended with status {}", id, status.as_str()))
+            .fetch_one(&mut *tx)
+            .await?;
+        if affected > 0 {
+            tracing::info!("Task {} {}: cancelled or disabled {} dependent tasks", id, status.as_str(), affected);
+        }
+
+        tx.commit().await?;
         Ok(())
     }
 
@@ -265,13 +593,238 @@ impl TaskStore {
         Ok(tasks)
     }
 
-    /// Count tasks grouped by status
-    pub async fn count_by_status(&self) -> Result<std::collections::HashMap<String, i64>> {
-        let rows: Vec<(String, i64)> = sqlx::query_as(
-            "SELECT status, COUNT(*) FROM agent_tasks GROUP BY status",
+    /// Count tasks grouped by status, for one user or for everyone
+    ///
+    /// Statuses without tasks are absent from the map.
+    pub async fn count_by_status(&self, user_id: Option<&str>) -> Result<HashMap<TaskStatus, u64>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(r#"
+            SELECT status, COUNT(*) FROM agent_tasks
+            WHERE ($1::text IS NULL OR user_id = $1)
+            GROUP BY status
+        "#)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(status, count)| (TaskStatus::from_str(&status), count as u64))
+            .collect())
+    }
+
+    /// Number of tasks and recurring tasks owned by `user_id`
+    pub async fn count_for_user(&self, user_id: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(r#"
+            SELECT (SELECT COUNT(*) FROM agent_tasks WHERE user_id = $1)
+                 + (SELECT COUNT(*) FROM recurring_tasks WHERE user_id = $1)
+        "#)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count as u64)
+    }
+
+    /// Move every task and recurring task of `old_user_id` to `new_user_id`
+    ///
+    /// Both tables are updated in one transaction. Returns the number of
+    /// rows moved.
+    pub async fn merge_duplicate_users(&self, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+        if old_user_id == new_user_id {
+            return Err(Error::InvalidInput("Cannot merge a user into itself".into()));
+        }
+        let mut tx = self.pool.begin().await?;
+        let moved = move_tasks(&mut tx, old_user_id, new_user_id).await?;
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    /// Create a recurring task that fires on the given cron schedule
+    pub async fn create_recurring(
+        &self,
+        cron: &str,
+        user_id: &str,
+        chat_id: Option<i64>,
+        title: &str,
+        description: &str,
+        priority: i32,
+    ) -> Result<RecurringTask> {
+        let schedule = parse_cron_schedule(cron)?;
+        let next_run_at = next_run_after(&schedule, Utc::now())?;
+
+        let task: RecurringTask = sqlx::query_as(r#"
+            INSERT INTO recurring_tasks
+                (user_id, chat_id, title, description, priority, cron_schedule, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+        "#)
+        .bind(user_id)
+        .bind(chat_id)
+        .bind(title)
+        .bind(description)
+        .bind(priority)
+        .bind(cron.trim())
+        .bind(next_run_at)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(task)
+    }
+
+    /// List recurring tasks ordered by next run time
+    pub async fn list_recurring(&self) -> Result<Vec<RecurringTask>> {
+        let tasks: Vec<RecurringTask> = sqlx::query_as(
+            "SELECT * FROM recurring_tasks ORDER BY next_run_at ASC",
         )
         .fetch_all(&self.pool)
         .await?;
-        Ok(rows.into_iter().collect())
+        Ok(tasks)
+    }
+
+    /// Enqueue a pending task for every recurring task that is due and
+    /// advance its schedule. Returns the newly created tasks.
+    ///
+    /// Recurring tasks with unfinished dependencies stay due and fire once
+    /// their dependencies finish.
+    pub async fn get_due_recurring(&self) -> Result<Vec<AgentTask>> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let due: Vec<RecurringTask> = sqlx::query_as(concat!(
+            "SELECT * FROM recurring_tasks
+            WHERE enabled AND next_run_at <= $1
+              AND ", dependencies_finished!("recurring_tasks"), "
+            ORDER BY next_run_at ASC
+            FOR UPDATE SKIP LOCKED"
+        ))
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut created = Vec::with_capacity(due.len());
+        for recurring in due {
+            let next_run_at = match parse_cron_schedule(&recurring.cron_schedule)
+                .and_then(|schedule| next_run_after(&schedule, now))
+            {
+                Ok(next) => Some(next),
+                Err(e) => {
+                    tracing::warn!("Disabling recurring task {}: {}", recurring.id, e);
+                    None
+                }
+            };
+
+            sqlx::query(r#"
+                UPDATE recurring_tasks
+                SET last_run_at = $2,
+                    next_run_at = COALESCE($3, next_run_at),
+                    enabled = $3 IS NOT NULL,
+                    updated_at = NOW()
+                WHERE id = $1
+            "#)
+            .bind(recurring.id)
+            .bind(now)
+            .bind(next_run_at)
+            .execute(&mut *tx)
+            .await?;
+
+            if next_run_at.is_none() {
+                continue;
+            }
+
+            let task: AgentTask = sqlx::query_as(r#"
+                INSERT INTO agent_tasks (user_id, chat_id, title, description, priority, metadata)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                RETURNING *
+            "#)
+            .bind(&recurring.user_id)
+            .bind(recurring.chat_id)
+            .bind(&recurring.title)
+            .bind(&recurring.description)
+            .bind(recurring.priority)
+            .bind(serde_json::json!({ "recurring_task_id": recurring.id }))
+            .fetch_one(&mut *tx)
+            .await?;
+            created.push(task);
+        }
+
+        tx.commit().await?;
+        Ok(created)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Timelike};
+
+    #[test]
+    fn test_dependencies_finished_filter() {
+        let filter = dependencies_finished!("recurring_tasks");
+        assert!(filter.contains("d.task_id = recurring_tasks.id"));
+        assert!(filter.contains("prerequisite.status <> 'finish'"));
+    }
+
+    #[test]
+    fn test_cancel_dependents_query() {
+        // Walks dependents transitively and only touches tasks still waiting
+        assert!(CANCEL_DEPENDENTS.contains("WITH RECURSIVE dependents"));
+        assert!(CANCEL_DEPENDENTS.contains("status = 'pending'"));
+        assert!(CANCEL_DEPENDENTS.contains("SET enabled = FALSE"));
+    }
+
+    #[test]
+    fn test_parse_cron_schedule() {
+        let schedule = parse_cron_schedule("0 9 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let next = next_run_after(&schedule, after).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 1, 2, 9, 0, 0).unwrap());
+
+        let seconds = parse_cron_schedule("30 */5 * * * *").unwrap();
+        let next = next_run_after(&seconds, after).unwrap();
+        assert_eq!((next.minute(), next.second()), (0, 30));
+
+        // Only 5-field expressions get a seconds field prepended: a 7-field
+        // expression with a year must not grow an eighth field
+        let with_year = parse_cron_schedule("0 0 12 1 6 * 2027").unwrap();
+        let next = next_run_after(&with_year, after).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2027, 6, 1, 12, 0, 0).unwrap());
+
+        assert!(parse_cron_schedule("9 * * *").is_err());
+        assert!(parse_cron_schedule("not a cron").is_err());
+    }
+
+    #[test]
+    fn test_cron_posix_day_of_week() {
+        // 2026-01-01 is a Thursday, 2026-01-03 a Saturday
+        let thursday = Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap();
+        let saturday = Utc.with_ymd_and_hms(2026, 1, 3, 10, 0, 0).unwrap();
+
+        let weekdays = parse_cron_schedule("0 9 * * 1-5").unwrap();
+        assert_eq!(next_run_after(&weekdays, thursday).unwrap(), Utc.with_ymd_and_hms(2026, 1, 2, 9, 0, 0).unwrap());
+        assert_eq!(next_run_after(&weekdays, saturday).unwrap(), Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap());
+
+        let sunday = parse_cron_schedule("* * * * 0").unwrap();
+        assert_eq!(next_run_after(&sunday, thursday).unwrap(), Utc.with_ymd_and_hms(2026, 1, 4, 0, 0, 0).unwrap());
+        let sunday = parse_cron_schedule("0 9 * * 5-7").unwrap();
+        assert_eq!(next_run_after(&sunday, saturday).unwrap(), Utc.with_ymd_and_hms(2026, 1, 4, 9, 0, 0).unwrap());
+
+        assert_eq!(posix_day_of_week("0,3,7").as_deref(), Some("1,4"));
+        assert_eq!(posix_day_of_week("*/2").as_deref(), Some("1,3,5,7"));
+        assert_eq!(posix_day_of_week("MON-FRI").as_deref(), Some("MON-FRI"));
+        assert!(posix_day_of_week("8").is_none());
+        assert!(parse_cron_schedule("0 9 * * 5-1").is_err());
+    }
+
+    #[test]
+    fn test_task_stats_from_counts() {
+        let counts = HashMap::from([
+            (TaskStatus::Pending, 3),
+            (TaskStatus::Finish, 5),
+            (TaskStatus::Fail, 1),
+            (TaskStatus::Cancel, 2),
+        ]);
+        let stats = TaskStats::from_counts(&counts);
+        assert_eq!(
+            stats,
+            TaskStats { pending: 3, processing: 0, complete: 5, failed: 1, total: 11 }
+        );
+        assert_eq!(TaskStats::from_counts(&HashMap::new()), TaskStats::default());
     }
 }
@@ -0,0 +1,112 @@
+//! Moving a user's data to another user ID
+//!
+//! [`merge_users`] is the account migration behind `openagent users merge`,
+//! e.g. when a user's Telegram ID changes. Memories, tasks, conversations and
+//! per-user config params (`user:<id>`) move in one transaction, so a failure
+//! part-way leaves both users as they were.
+
+use crate::database::memory::move_memories;
+use crate::database::tasks::move_tasks;
+use crate::database::PostgresPool;
+use crate::error::{Error, Result};
+use sqlx::PgConnection;
+
+/// Rows of each kind that belong to a user, or that a merge moved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserDataCounts {
+    pub memories: u64,
+    pub tasks: u64,
+    pub conversations: u64,
+    pub config_params: u64,
+}
+
+/// Config param category holding `user_id`'s settings
+pub fn user_config_category(user_id: &str) -> String {
+    format!("user:{}", user_id)
+}
+
+/// Count the rows [`merge_users`] would move away from `user_id`
+pub async fn count_user_data(pool: &PostgresPool, user_id: &str) -> Result<UserDataCounts> {
+    let (memories, tasks, conversations, config_params): (i64, i64, i64, i64) = sqlx::query_as(r#"
+        SELECT
+            (SELECT COUNT(*) FROM memories WHERE user_id = $1),
+            (SELECT COUNT(*) FROM agent_tasks WHERE user_id = $1)
+                + (SELECT COUNT(*) FROM recurring_tasks WHERE user_id = $1),
+            (SELECT COUNT(*) FROM conversations WHERE user_id = $1),
+            (SELECT COUNT(*) FROM config_params WHERE category = $2)
+    "#)
+    .bind(user_id)
+    .bind(user_config_category(user_id))
+    .fetch_one(pool)
+    .await?;
+
+    Ok(UserDataCounts {
+        memories: memories as u64,
+        tasks: tasks as u64,
+        conversations: conversations as u64,
+        config_params: config_params as u64,
+    })
+}
+
+/// Move everything stored for `old_user_id` to `new_user_id`
+///
+/// Runs in one transaction on one connection. Moved memories are tagged
+/// `migrated_from:{old_user_id}`; where both users have a config param with
+/// the same key, `new_user_id`'s value is kept.
+pub async fn merge_users(pool: &PostgresPool, old_user_id: &str, new_user_id: &str) -> Result<UserDataCounts> {
+    if old_user_id == new_user_id {
+        return Err(Error::InvalidInput("Cannot merge a user into itself".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+    let counts = UserDataCounts {
+        memories: move_memories(&mut tx, old_user_id, new_user_id).await?,
+        tasks: move_tasks(&mut tx, old_user_id, new_user_id).await?,
+        conversations: move_conversations(&mut tx, old_user_id, new_user_id).await?,
+        config_params: move_config_params(&mut tx, old_user_id, new_user_id).await?,
+    };
+    tx.commit().await?;
+    Ok(counts)
+}
+
+async fn move_conversations(conn: &mut PgConnection, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+    let result = sqlx::query("UPDATE conversations SET user_id = $2, updated_at = NOW() WHERE user_id = $1")
+        .bind(old_user_id)
+        .bind(new_user_id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+async fn move_config_params(conn: &mut PgConnection, old_user_id: &str, new_user_id: &str) -> Result<u64> {
+    let old_category = user_config_category(old_user_id);
+    let new_category = user_config_category(new_user_id);
+
+    // (category, key) is unique: the target user's own settings win
+    sqlx::query(r#"
+        DELETE FROM config_params old
+        WHERE old.category = $1
+          AND EXISTS (SELECT 1 FROM config_params new WHERE new.category = $2 AND new.key = old.key)
+    "#)
+    .bind(&old_category)
+    .bind(&new_category)
+    .execute(&mut *conn)
+    .await?;
+
+    let result = sqlx::query("UPDATE config_params SET category = $2, updated_at = NOW() WHERE category = $1")
+        .bind(&old_category)
+        .bind(&new_category)
+        .execute(&mut *conn)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_config_category() {
+        assert_eq!(user_config_category("12345"), "user:12345");
+    }
+}
@@ -0,0 +1,302 @@
+//! Gateway authentication
+//!
+//! Checks the `Authorization: Bearer <token>` header of a WebSocket
+//! upgrade request against `gateway.auth`. In `jwt` mode the token is a
+//! signed JWT (HS256 with `jwt_secret`, or RS256 with `jwt_public_key_pem`)
+//! whose expiry, issuer and audience are validated; its `sub` claim is the
+//! session's user ID.
+
+use axum::http::{header, HeaderMap};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::config::{AuthConfig, AuthMode};
+use crate::error::{Error, Result};
+
+/// Claims the gateway reads from a JWT; `exp`, `iss` and `aud` are checked
+/// by [`JwtVerifier`] before these are returned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JwtClaims {
+    /// Subject, used as the session user ID
+    pub sub: String,
+    /// Expiry as a Unix timestamp
+    pub exp: u64,
+}
+
+/// Token from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Whether `token` is one of `auth.tokens`
+///
+/// Every configured token is compared in constant time, so the response
+/// time does not reveal how much of a guess matched.
+pub fn is_allowed_token(auth: &AuthConfig, token: &str) -> bool {
+    matching_token(auth, token).is_some()
+}
+
+/// Index of `token` in `auth.tokens`, comparing every entry in constant time
+fn matching_token(auth: &AuthConfig, token: &str) -> Option<usize> {
+    auth.tokens
+        .iter()
+        .enumerate()
+        .fold(None, |found, (index, allowed)| {
+            let matched = secret_eq(allowed, token);
+            found.or(matched.then_some(index))
+        })
+}
+
+/// Constant-time string comparison for credentials
+fn secret_eq(expected: &str, given: &str) -> bool {
+    expected.as_bytes().ct_eq(given.as_bytes()).into()
+}
+
+/// Validates JWTs according to `gateway.auth`
+#[derive(Clone)]
+pub struct JwtVerifier {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl JwtVerifier {
+    /// Build a verifier from the `jwt_*` settings; RS256 is used when a public
+    /// key is configured, HS256 with `jwt_secret` otherwise
+    pub fn from_config(auth: &AuthConfig) -> Result<Self> {
+        let (key, algorithm) = match (&auth.jwt_public_key_pem, &auth.jwt_secret) {
+            (Some(pem), _) => {
+                let key = DecodingKey::from_rsa_pem(pem.as_bytes())
+                    .map_err(|e| Error::Config(format!("Invalid gateway.auth.jwt_public_key_pem: {}", e)))?;
+                (key, Algorithm::RS256)
+            }
+            (None, Some(secret)) => (DecodingKey::from_secret(secret.as_bytes()), Algorithm::HS256),
+            (None, None) => {
+                return Err(Error::Config(
+                    "gateway.auth.mode = \"jwt\" requires jwt_secret or jwt_public_key_pem".into(),
+                ))
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        validation.set_required_spec_claims(&["exp", "sub"]);
+        if let Some(ref issuer) = auth.jwt_issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match auth.jwt_audience {
+            Some(ref audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        Ok(JwtVerifier { key, validation })
+    }
+
+    /// Decode and validate `token`, returning its claims
+    pub fn verify(&self, token: &str) -> Result<JwtClaims> {
+        decode::<JwtClaims>(token, &self.key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| Error::Unauthorized(format!("Invalid JWT: {}", e)))
+    }
+}
+
+/// Client identified by [`GatewayAuth::principal`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Principal {
+    /// Session user ID (the JWT `sub` claim); only `jwt` mode has one
+    pub user: Option<String>,
+    /// Stable per-client key for per-user rate limits: the user ID, or
+    /// which configured credential matched (`token:<index>`, `password`).
+    /// `None` in `none` mode.
+    pub key: Option<String>,
+}
+
+/// Authenticates WebSocket upgrade requests per `gateway.auth`
+#[derive(Clone)]
+pub struct GatewayAuth {
+    config: AuthConfig,
+    jwt: Option<JwtVerifier>,
+}
+
+impl GatewayAuth {
+    /// Prepare the configured mode; fails if `jwt` mode lacks a usable key
+    pub fn new(config: AuthConfig) -> Result<Self> {
+        let jwt = match config.mode {
+            AuthMode::Jwt => Some(JwtVerifier::from_config(&config)?),
+            _ => None,
+        };
+        Ok(GatewayAuth { config, jwt })
+    }
+
+    /// Check the upgrade request headers
+    ///
+    /// Returns the session user ID when the mode provides one (the JWT `sub`
+    /// claim), `None` when the request is allowed without one.
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Option<String>> {
+        self.principal(headers).map(|principal| principal.user)
+    }
+
+    /// Check the request headers like [`authenticate`](Self::authenticate),
+    /// returning who the client is
+    pub fn principal(&self, headers: &HeaderMap) -> Result<Principal> {
+        if self.config.mode == AuthMode::None {
+            return Ok(Principal::default());
+        }
+
+        let token = bearer_token(headers)
+            .ok_or_else(|| Error::Unauthorized("Missing bearer token".into()))?;
+        let credential = |key: String| Principal { user: None, key: Some(key) };
+
+        match self.config.mode {
+            AuthMode::None => Ok(Principal::default()),
+            AuthMode::Token => matching_token(&self.config, token)
+                .map(|index| credential(format!("token:{}", index)))
+                .ok_or_else(|| Error::Unauthorized("Invalid credentials".into())),
+            AuthMode::Password if self.config.password.as_deref().is_some_and(|p| secret_eq(p, token)) => {
+                Ok(credential("password".to_string()))
+            }
+            AuthMode::Password => Err(Error::Unauthorized("Invalid credentials".into())),
+            AuthMode::Jwt => {
+                let verifier = self
+                    .jwt
+                    .as_ref()
+                    .ok_or_else(|| Error::Internal("JWT verifier not initialized".into()))?;
+                let sub = verifier.verify(token)?.sub;
+                Ok(Principal {
+                    user: Some(sub.clone()),
+                    key: Some(sub),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    const SECRET: &str = "test-secret";
+
+    fn jwt_config() -> AuthConfig {
+        AuthConfig {
+            mode: AuthMode::Jwt,
+            jwt_secret: Some(SECRET.to_string()),
+            jwt_issuer: Some("https://auth.example.com".to_string()),
+            jwt_audience: Some("openagent".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn sign(claims: serde_json::Value, secret: &str) -> String {
+        encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    fn valid_claims() -> serde_json::Value {
+        serde_json::json!({
+            "sub": "user-42",
+            "exp": chrono::Utc::now().timestamp() + 3600,
+            "iss": "https://auth.example.com",
+            "aud": "openagent",
+        })
+    }
+
+    fn headers_with(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_jwt_valid_token() {
+        let auth = GatewayAuth::new(jwt_config()).unwrap();
+        let token = sign(valid_claims(), SECRET);
+        assert_eq!(auth.authenticate(&headers_with(&token)).unwrap().as_deref(), Some("user-42"));
+    }
+
+    #[test]
+    fn test_jwt_rejects_forged_tokens() {
+        let auth = GatewayAuth::new(jwt_config()).unwrap();
+
+        // Signed with the wrong key
+        let forged = sign(valid_claims(), "attacker-secret");
+        assert!(auth.authenticate(&headers_with(&forged)).is_err());
+
+        // Valid signature, tampered payload
+        let token = sign(valid_claims(), SECRET);
+        let parts: Vec<&str> = token.split('.').collect();
+        let mut claims = valid_claims();
+        claims["sub"] = serde_json::json!("admin");
+        let payload = base64::Engine::encode(
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            serde_json::to_vec(&claims).unwrap(),
+        );
+        let tampered = format!("{}.{}.{}", parts[0], payload, parts[2]);
+        assert!(auth.authenticate(&headers_with(&tampered)).is_err());
+
+        assert!(auth.authenticate(&headers_with("not-a-jwt")).is_err());
+        assert!(auth.authenticate(&HeaderMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_jwt_validates_claims() {
+        let auth = GatewayAuth::new(jwt_config()).unwrap();
+
+        let mut expired = valid_claims();
+        expired["exp"] = serde_json::json!(chrono::Utc::now().timestamp() - 3600);
+        assert!(auth.authenticate(&headers_with(&sign(expired, SECRET))).is_err());
+
+        let mut wrong_issuer = valid_claims();
+        wrong_issuer["iss"] = serde_json::json!("https://evil.example.com");
+        assert!(auth.authenticate(&headers_with(&sign(wrong_issuer, SECRET))).is_err());
+
+        let mut wrong_audience = valid_claims();
+        wrong_audience["aud"] = serde_json::json!("someone-else");
+        assert!(auth.authenticate(&headers_with(&sign(wrong_audience, SECRET))).is_err());
+
+        let mut no_subject = valid_claims();
+        no_subject.as_object_mut().unwrap().remove("sub");
+        assert!(auth.authenticate(&headers_with(&sign(no_subject, SECRET))).is_err());
+    }
+
+    #[test]
+    fn test_jwt_requires_key() {
+        let config = AuthConfig {
+            mode: AuthMode::Jwt,
+            ..Default::default()
+        };
+        assert!(matches!(GatewayAuth::new(config), Err(Error::Config(_))));
+
+        let config = AuthConfig {
+            mode: AuthMode::Jwt,
+            jwt_public_key_pem: Some("not a pem".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(GatewayAuth::new(config), Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_static_token_mode() {
+        let auth = GatewayAuth::new(AuthConfig {
+            mode: AuthMode::Token,
+            tokens: vec!["abc".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(auth.authenticate(&headers_with("abc")).unwrap(), None);
+        assert!(auth.authenticate(&headers_with("xyz")).is_err());
+        assert!(auth.authenticate(&headers_with("ab")).is_err());
+        assert!(auth.authenticate(&headers_with("abcd")).is_err());
+        // Rate limits key token clients by which token matched, not its value
+        assert_eq!(auth.principal(&headers_with("abc")).unwrap().key.as_deref(), Some("token:0"));
+
+        let open = GatewayAuth::new(AuthConfig::default()).unwrap();
+        assert_eq!(open.authenticate(&HeaderMap::new()).unwrap(), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Paginated agent responses
+//!
+//! Responses longer than [`CONTINUATION_PAGE_CHARS`] are not sent in one
+//! frame. [`ContinuationStore::paginate`] keeps the first page, parks the
+//! rest under a random `continuationToken`, and clients fetch it page by
+//! page with `agent.continue` ([`AgentContinueRequest`]). Parked pages live
+//! in memory for [`CONTINUATION_TTL`]; the least recently used are evicted
+//! first when the store is full.
+
+use moka::future::Cache;
+use moka::policy::EvictionPolicy;
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::protocol::types::{AgentContinueRequest, AgentResponse};
+use crate::error::{Error, Result};
+
+/// Maximum characters of `content` per page (Telegram's message limit)
+pub const CONTINUATION_PAGE_CHARS: usize = 4096;
+
+/// How long unfetched pages are kept
+pub const CONTINUATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Default number of parked responses
+const DEFAULT_CAPACITY: u64 = 1000;
+
+/// Remainders of paginated responses, keyed by continuation token
+#[derive(Clone)]
+pub struct ContinuationStore {
+    pages: Cache<String, AgentResponse>,
+}
+
+impl Default for ContinuationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContinuationStore {
+    /// Store with a 5 minute TTL and room for 1000 responses
+    pub fn new() -> Self {
+        Self::with_limits(CONTINUATION_TTL, DEFAULT_CAPACITY)
+    }
+
+    /// Store with a custom TTL and capacity
+    pub fn with_limits(ttl: Duration, capacity: u64) -> Self {
+        ContinuationStore {
+            pages: Cache::builder()
+                .max_capacity(capacity)
+                .eviction_policy(EvictionPolicy::lru())
+                .time_to_live(ttl)
+                .build(),
+        }
+    }
+
+    /// Cut `response` down to its first page; the rest is stored and
+    /// `continuation_token` set. Short responses are returned unchanged.
+    pub async fn paginate(&self, mut response: AgentResponse) -> AgentResponse {
+        let Some((split, _)) = response.content.char_indices().nth(CONTINUATION_PAGE_CHARS) else {
+            response.continuation_token = None;
+            return response;
+        };
+
+        let rest = AgentResponse {
+            content: response.content.split_off(split),
+            // Usage covers the whole response and is reported once
+            usage: None,
+            continuation_token: None,
+            ..response.clone()
+        };
+        let token = Uuid::new_v4().to_string();
+        self.pages.insert(token.clone(), rest).await;
+        response.continuation_token = Some(token);
+        response
+    }
+
+    /// Next page for `request.token`, itself paginated. Each token can be
+    /// used once; fails with `Error::NotFound` if it is unknown or expired.
+    pub async fn next_page(&self, request: &AgentContinueRequest) -> Result<AgentResponse> {
+        let rest = self.pages.remove(&request.token).await.ok_or_else(|| {
+            Error::NotFound(format!("Continuation token {} is unknown or expired", request.token))
+        })?;
+        Ok(self.paginate(rest).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content: String) -> AgentResponse {
+        AgentResponse {
+            session_id: "s1".to_string(),
+            content,
+            model: "test/model".to_string(),
+            finish_reason: Some("stop".to_string()),
+            usage: None,
+            continuation_token: None,
+        }
+    }
+
+    fn next(token: &Option<String>) -> AgentContinueRequest {
+        AgentContinueRequest {
+            token: token.clone().expect("expected a continuation token"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_response_unchanged() {
+        let store = ContinuationStore::new();
+        let page = store.paginate(response("hi".to_string())).await;
+        assert_eq!(page.content, "hi");
+        assert!(page.continuation_token.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_pages_cover_whole_response() {
+        let store = ContinuationStore::new();
+        // Multi-byte characters: pages are cut on char boundaries
+        let content = "é".repeat(CONTINUATION_PAGE_CHARS * 2 + 10);
+
+        let first = store.paginate(response(content.clone())).await;
+        assert_eq!(first.content.chars().count(), CONTINUATION_PAGE_CHARS);
+        let second = store.next_page(&next(&first.continuation_token)).await.unwrap();
+        assert_eq!(second.content.chars().count(), CONTINUATION_PAGE_CHARS);
+        assert_eq!(second.session_id, "s1");
+        let third = store.next_page(&next(&second.continuation_token)).await.unwrap();
+        assert_eq!(third.content.chars().count(), 10);
+        assert!(third.continuation_token.is_none());
+        assert_eq!(first.content + &second.content + &third.content, content);
+
+        // Tokens are single use
+        let reused = store.next_page(&next(&first.continuation_token)).await;
+        assert!(matches!(reused, Err(Error::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expired_token() {
+        let store = ContinuationStore::with_limits(Duration::from_millis(50), 10);
+        let first = store.paginate(response("x".repeat(CONTINUATION_PAGE_CHARS + 1))).await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let expired = store.next_page(&next(&first.continuation_token)).await;
+        assert!(matches!(expired, Err(Error::NotFound(_))));
+    }
+}
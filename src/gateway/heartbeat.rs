@@ -0,0 +1,394 @@
+//! WebSocket heartbeats
+//!
+//! Load balancers and NAT devices silently drop WebSocket connections that
+//! stay idle. [`keep_alive`] drives one client connection: every
+//! `gateway.heartbeat_interval_secs` it sends a [`GatewayFrame::Heartbeat`],
+//! and if no matching [`GatewayFrame::HeartbeatAck`] arrives within
+//! `gateway.heartbeat_timeout_secs` it closes the connection with code 1001
+//! (going away). Other frames are handed to the caller.
+//! [`keep_alive_limited`] also applies `gateway.rate_limit` to them.
+//! Connections accepted by a [`GatewayServer`](super::GatewayServer) are
+//! also told when the server shuts down.
+
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use std::net::IpAddr;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Instant, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tracing::{debug, warn};
+
+use super::protocol::schema::{error_codes, ErrorFrame, ProtocolError};
+use super::protocol::{GatewayFrame, ProtocolEncoding};
+use super::rate_limit::{retry_after_secs, GatewayRateLimiter};
+use super::server::ShutdownPhase;
+use crate::config::GatewayConfig;
+use crate::error::{Error, Result};
+
+/// Tracks the outstanding heartbeat of one connection
+#[derive(Debug, Clone)]
+pub struct HeartbeatMonitor {
+    interval: Duration,
+    timeout: Duration,
+    /// Timestamp of the unacknowledged heartbeat and when it expires
+    pending: Option<(i64, Instant)>,
+}
+
+impl HeartbeatMonitor {
+    pub fn new(interval: Duration, timeout: Duration) -> Self {
+        HeartbeatMonitor {
+            interval,
+            timeout,
+            pending: None,
+        }
+    }
+
+    /// Use the `heartbeat_*` settings of `config`
+    pub fn from_config(config: &GatewayConfig) -> Self {
+        Self::new(
+            Duration::from_secs(config.heartbeat_interval_secs.max(1)),
+            Duration::from_secs(config.heartbeat_timeout_secs.max(1)),
+        )
+    }
+
+    /// Time between heartbeats
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Start a heartbeat, returning the frame to send; `None` while an
+    /// earlier heartbeat is still waiting for its ack
+    pub fn heartbeat(&mut self, now: Instant) -> Option<GatewayFrame> {
+        if self.pending.is_some() {
+            return None;
+        }
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        self.pending = Some((timestamp, now + self.timeout));
+        Some(GatewayFrame::Heartbeat { timestamp })
+    }
+
+    /// Record an ack; returns false if it does not match the pending heartbeat
+    pub fn ack(&mut self, timestamp: i64) -> bool {
+        match self.pending {
+            Some((pending, _)) if pending == timestamp => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// When the pending heartbeat times out, if one is pending
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending.map(|(_, deadline)| deadline)
+    }
+}
+
+/// Serve a WebSocket connection until either side closes it or a heartbeat
+/// goes unacknowledged
+///
+/// Every decoded frame other than `HeartbeatAck` is passed to `on_frame`;
+/// the frame it returns, if any, is sent back. Undecodable messages are
+/// logged and skipped.
+pub async fn keep_alive<S, F>(
+    ws: S,
+    monitor: HeartbeatMonitor,
+    encoding: ProtocolEncoding,
+    on_frame: F,
+) -> Result<()>
+where
+    S: Stream<Item = std::result::Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+    F: FnMut(GatewayFrame) -> Option<GatewayFrame>,
+{
+    drive(ws, monitor, encoding, None, None, None, on_frame).await
+}
+
+/// Like [`keep_alive`], but each frame counts against `limiter` for the
+/// client's `ip` and `user`
+///
+/// Frames over the limit are not passed to `on_frame`; the client gets an
+/// error frame with code `RATE_LIMITED` and `retryAfterSecs` in its data.
+/// Heartbeat acks are never limited.
+pub async fn keep_alive_limited<S, F>(
+    ws: S,
+    monitor: HeartbeatMonitor,
+    encoding: ProtocolEncoding,
+    limiter: GatewayRateLimiter,
+    ip: Option<IpAddr>,
+    user: Option<String>,
+    on_frame: F,
+) -> Result<()>
+where
+    S: Stream<Item = std::result::Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+    F: FnMut(GatewayFrame) -> Option<GatewayFrame>,
+{
+    let throttle = ClientThrottle { limiter, ip, user };
+    drive(ws, monitor, encoding, Some(throttle), None, None, on_frame).await
+}
+
+/// Rate limit state of one connection
+pub(super) struct ClientThrottle {
+    pub(super) limiter: GatewayRateLimiter,
+    pub(super) ip: Option<IpAddr>,
+    pub(super) user: Option<String>,
+}
+
+impl ClientThrottle {
+    /// Error frame to send instead of handling `frame`, if over the limit
+    fn reject(&self, frame: &GatewayFrame) -> Option<GatewayFrame> {
+        let wait = self.limiter.check(self.ip, self.user.as_deref()).err()?;
+        let secs = retry_after_secs(wait);
+        let id = match frame {
+            GatewayFrame::Request(request) => Some(request.id.clone()),
+            _ => None,
+        };
+        Some(GatewayFrame::Error(ErrorFrame {
+            id,
+            error: ProtocolError {
+                code: error_codes::RATE_LIMITED,
+                message: format!("Rate limit exceeded, retry in {}s", secs),
+                data: Some(serde_json::json!({ "retryAfterSecs": secs })),
+            },
+        }))
+    }
+}
+
+/// Serve `ws`, throttled by `throttle` and closed when `shutdown` reaches
+/// [`ShutdownPhase::Closing`]; frames arriving on `outbox` are sent as they
+/// come
+pub(super) async fn drive<S, F>(
+    mut ws: S,
+    mut monitor: HeartbeatMonitor,
+    encoding: ProtocolEncoding,
+    throttle: Option<ClientThrottle>,
+    mut shutdown: Option<watch::Receiver<ShutdownPhase>>,
+    mut outbox: Option<mpsc::UnboundedReceiver<GatewayFrame>>,
+    mut on_frame: F,
+) -> Result<()>
+where
+    S: Stream<Item = std::result::Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+    F: FnMut(GatewayFrame) -> Option<GatewayFrame>,
+{
+    let mut ticker = tokio::time::interval_at(Instant::now() + monitor.interval(), monitor.interval());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let deadline = monitor.deadline();
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Some(frame) = monitor.heartbeat(Instant::now()) {
+                    send(&mut ws, &frame, encoding).await?;
+                }
+            }
+            _ = async { tokio::time::sleep_until(deadline.unwrap()).await }, if deadline.is_some() => {
+                debug!("Heartbeat not acknowledged, closing connection");
+                let close = CloseFrame {
+                    code: CloseCode::Away,
+                    reason: "heartbeat timeout".into(),
+                };
+                ws.send(Message::Close(Some(close))).await.map_err(ws_error)?;
+                return Ok(());
+            }
+            changed = async { shutdown.as_mut().unwrap().changed().await }, if shutdown.is_some() => {
+                if changed.is_err() {
+                    // The server is gone; keep serving until the client leaves
+                    shutdown = None;
+                    continue;
+                }
+                let phase = *shutdown.as_mut().unwrap().borrow_and_update();
+                match phase {
+                    ShutdownPhase::Running => {}
+                    ShutdownPhase::Draining { delay_secs } => {
+                        send(&mut ws, &GatewayFrame::ShuttingDown { delay_secs }, encoding).await?;
+                    }
+                    ShutdownPhase::Closing => {
+                        let close = CloseFrame {
+                            code: CloseCode::Away,
+                            reason: "server shutting down".into(),
+                        };
+                        ws.send(Message::Close(Some(close))).await.map_err(ws_error)?;
+                        return Ok(());
+                    }
+                }
+            }
+            frame = async { outbox.as_mut().unwrap().recv().await }, if outbox.is_some() => {
+                match frame {
+                    Some(frame) => send(&mut ws, &frame, encoding).await?,
+                    // Every sender is gone
+                    None => outbox = None,
+                }
+            }
+            message = ws.next() => {
+                let bytes = match message {
+                    None | Some(Ok(Message::Close(_))) => return Ok(()),
+                    Some(Err(e)) => return Err(ws_error(e)),
+                    Some(Ok(Message::Text(text))) => text.as_bytes().to_vec(),
+                    Some(Ok(Message::Binary(bytes))) => bytes.to_vec(),
+                    // Control frames are answered by tungstenite
+                    Some(Ok(_)) => continue,
+                };
+                match GatewayFrame::decode(&bytes, encoding) {
+                    Ok(GatewayFrame::HeartbeatAck { timestamp }) => {
+                        if !monitor.ack(timestamp) {
+                            debug!("Ignoring stale heartbeat ack {}", timestamp);
+                        }
+                    }
+                    Ok(frame) => {
+                        if let Some(error) = throttle.as_ref().and_then(|t| t.reject(&frame)) {
+                            debug!("Rate limited gateway frame");
+                            send(&mut ws, &error, encoding).await?;
+                            continue;
+                        }
+                        if let Some(reply) = on_frame(frame) {
+                            send(&mut ws, &reply, encoding).await?;
+                        }
+                    }
+                    Err(e) => warn!("Dropping undecodable gateway frame: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Encode `frame` and send it as a text or binary message per `encoding`
+async fn send<S>(ws: &mut S, frame: &GatewayFrame, encoding: ProtocolEncoding) -> Result<()>
+where
+    S: Sink<Message, Error = WsError> + Unpin,
+{
+    let bytes = frame.encode(encoding)?;
+    let message = if encoding.is_binary() {
+        Message::binary(bytes)
+    } else {
+        Message::text(String::from_utf8(bytes).map_err(|e| Error::Internal(e.to_string()))?)
+    };
+    ws.send(message).await.map_err(ws_error)
+}
+
+fn ws_error(e: WsError) -> Error {
+    Error::WebSocket(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::protocol::Role;
+    use tokio_tungstenite::WebSocketStream;
+
+    async fn pair() -> (
+        WebSocketStream<tokio::io::DuplexStream>,
+        WebSocketStream<tokio::io::DuplexStream>,
+    ) {
+        let (a, b) = tokio::io::duplex(4096);
+        (
+            WebSocketStream::from_raw_socket(a, Role::Server, None).await,
+            WebSocketStream::from_raw_socket(b, Role::Client, None).await,
+        )
+    }
+
+    fn monitor() -> HeartbeatMonitor {
+        HeartbeatMonitor::new(Duration::from_secs(30), Duration::from_secs(10))
+    }
+
+    async fn next_frame(client: &mut WebSocketStream<tokio::io::DuplexStream>) -> GatewayFrame {
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => GatewayFrame::decode(text.as_bytes(), ProtocolEncoding::Json).unwrap(),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_monitor_ack() {
+        let mut monitor = monitor();
+        let now = Instant::now();
+        let Some(GatewayFrame::Heartbeat { timestamp }) = monitor.heartbeat(now) else {
+            panic!("expected a heartbeat");
+        };
+        assert_eq!(monitor.deadline(), Some(now + Duration::from_secs(10)));
+        assert!(monitor.heartbeat(now).is_none());
+
+        assert!(!monitor.ack(timestamp - 1));
+        assert!(monitor.ack(timestamp));
+        assert!(monitor.deadline().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unacked_heartbeat_closes_connection() {
+        let (server, mut client) = pair().await;
+        let serve = tokio::spawn(keep_alive(server, monitor(), ProtocolEncoding::Json, |_| None));
+
+        assert!(matches!(next_frame(&mut client).await, GatewayFrame::Heartbeat { .. }));
+        // Never ack: the server gives up after the timeout
+        match client.next().await.unwrap().unwrap() {
+            Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+            other => panic!("unexpected message: {:?}", other),
+        }
+        serve.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_acked_heartbeats_keep_connection_open() {
+        let (server, mut client) = pair().await;
+        let serve = tokio::spawn(keep_alive(server, monitor(), ProtocolEncoding::Json, |frame| {
+            match frame {
+                GatewayFrame::Ping { id } => Some(GatewayFrame::Pong { id }),
+                _ => None,
+            }
+        }));
+
+        for _ in 0..3 {
+            let heartbeat = next_frame(&mut client).await;
+            let ack = heartbeat.auto_reply().unwrap();
+            let text = String::from_utf8(ack.encode(ProtocolEncoding::Json).unwrap()).unwrap();
+            client.send(Message::text(text)).await.unwrap();
+        }
+
+        let ping = GatewayFrame::Ping { id: "p1".to_string() };
+        let text = String::from_utf8(ping.encode(ProtocolEncoding::Json).unwrap()).unwrap();
+        client.send(Message::text(text)).await.unwrap();
+        assert!(matches!(next_frame(&mut client).await, GatewayFrame::Pong { id } if id == "p1"));
+
+        client.close(None).await.unwrap();
+        serve.await.unwrap().unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flooding_client_is_rate_limited() {
+        use crate::config::RateLimitConfig;
+
+        let (server, mut client) = pair().await;
+        let limiter = GatewayRateLimiter::from_config(&RateLimitConfig {
+            requests_per_minute_per_ip: 0,
+            requests_per_minute_per_user: 60,
+            burst_size: 2,
+        });
+        let serve = tokio::spawn(keep_alive_limited(
+            server,
+            monitor(),
+            ProtocolEncoding::Json,
+            limiter,
+            None,
+            Some("alice".to_string()),
+            |frame| frame.auto_reply(),
+        ));
+
+        for i in 0..3 {
+            let ping = GatewayFrame::Ping { id: format!("p{}", i) };
+            let text = String::from_utf8(ping.encode(ProtocolEncoding::Json).unwrap()).unwrap();
+            client.send(Message::text(text)).await.unwrap();
+        }
+        assert!(matches!(next_frame(&mut client).await, GatewayFrame::Pong { id } if id == "p0"));
+        assert!(matches!(next_frame(&mut client).await, GatewayFrame::Pong { id } if id == "p1"));
+        match next_frame(&mut client).await {
+            GatewayFrame::Error(frame) => {
+                assert_eq!(frame.error.code, error_codes::RATE_LIMITED);
+                assert_eq!(frame.error.data, Some(serde_json::json!({ "retryAfterSecs": 1 })));
+            }
+            other => panic!("unexpected frame: {:?}", other),
+        }
+
+        client.close(None).await.unwrap();
+        serve.await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,546 @@
+//! Gateway HTTP endpoints
+//!
+//! Plain HTTP routes served alongside the gateway for operators:
+//!
+//! - `GET /plugins` — health of every registered plugin
+//! - `GET /health` — liveness, agent state and task counts
+//! - `GET /health/db` — PostgreSQL pool connection counts
+//! - `GET /agent/status/history` — recent agent state transitions (at most
+//!   [`MAX_STATUS_HISTORY_LIMIT`])
+//! - `GET /metrics` — per-tool call counts, latency and errors (Prometheus)
+//! - `GET /soul/sections`, `PATCH /soul/sections/{title}` — soul editing
+//!   (bearer token from `gateway.auth.tokens` required, see [`authorize`])
+//!
+//! [`with_cors`] adds CORS headers for browser frontends per `gateway.cors`,
+//! and [`with_rate_limit`] throttles clients per `gateway.rate_limit`.
+
+use axum::extract::{ConnectInfo, Path, Request};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{
+    extract::{Query, State},
+    routing::{get, patch},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use super::auth::{bearer_token, is_allowed_token, GatewayAuth};
+use super::rate_limit::{retry_after_secs, GatewayRateLimiter};
+use crate::config::{AuthConfig, AuthMode, Config, CorsConfig};
+use crate::database::{
+    AgentStatusStore, AgentStatusTransition, PoolStats, PostgresPool, SoulSection, SoulStore,
+    TaskStats,
+};
+use crate::error::{Error, Result};
+use crate::plugin_sdk::{PluginHealth, PluginRegistry};
+use crate::tools::PrometheusMetricsRecorder;
+
+/// Upper bound for a single plugin's health check
+pub const PLUGIN_HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Transitions returned by `GET /agent/status/history` without `?limit=`
+pub const DEFAULT_STATUS_HISTORY_LIMIT: usize = 10;
+
+/// Largest `?limit=` honoured by `GET /agent/status/history`
+pub const MAX_STATUS_HISTORY_LIMIT: usize = 1000;
+
+/// Build the gateway HTTP router
+pub fn router(plugins: Arc<PluginRegistry>) -> Router {
+    Router::new()
+        .route("/plugins", get(list_plugins))
+        .with_state(plugins)
+}
+
+/// Body of `GET /health`
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub status: &'static str,
+    pub version: &'static str,
+    /// Agent state (`ready`/`processing`); absent without a database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_state: Option<String>,
+    /// Task counts; absent without a database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<TaskStats>,
+}
+
+/// Build the `GET /health` route; the database fields are omitted when
+/// there is no store, and a failing query reports 503
+pub fn health_router(store: Option<AgentStatusStore>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .with_state(store)
+}
+
+/// Build the `GET /health/db` route; reports 503 when there is no pool
+pub fn db_health_router(pool: Option<PostgresPool>) -> Router {
+    Router::new()
+        .route("/health/db", get(db_health))
+        .with_state(pool)
+}
+
+/// Build the `GET /agent/status/history` route; reports 503 when there is no store
+pub fn status_history_router(store: Option<AgentStatusStore>) -> Router {
+    Router::new()
+        .route("/agent/status/history", get(status_history))
+        .with_state(store)
+}
+
+/// Build the `GET /metrics` route serving `recorder` in Prometheus text format
+pub fn metrics_router(recorder: Arc<PrometheusMetricsRecorder>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(recorder)
+}
+
+/// Build the soul editing routes; they require `gateway.auth.mode = "token"`
+/// and a `Authorization: Bearer <token>` header matching one of its tokens
+///
+/// `config` is read on every request, so auth changes picked up by a reload
+/// apply immediately.
+pub fn soul_router(store: Option<SoulStore>, config: Arc<RwLock<Config>>) -> Router {
+    Router::new()
+        .route("/soul/sections", get(export_soul_sections))
+        .route("/soul/sections/{title}", patch(update_soul_section))
+        .route_layer(middleware::from_fn_with_state(config, require_token))
+        .with_state(store)
+}
+
+/// Run [`authorize`] before the handler's extractors, so a bad token gets
+/// `401` even when the body would not parse
+async fn require_token(State(config): State<Arc<RwLock<Config>>>, request: Request, next: Next) -> Response {
+    let authorized = authorize(request.headers(), &config.read().await.gateway.auth);
+    match authorized {
+        Ok(()) => next.run(request).await,
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+/// Apply `config` CORS rules to `app`; returns it unchanged when no origins are set
+///
+/// With `"*"` any origin is allowed via the wildcard header. Otherwise the
+/// request `Origin` is echoed back only if it is in `allowed_origins`.
+/// Preflight `OPTIONS` requests are answered with `204 No Content`.
+pub fn with_cors(app: Router, config: &CorsConfig) -> Router {
+    if config.allowed_origins.is_empty() {
+        return app;
+    }
+
+    let wildcard = config.allowed_origins.iter().any(|o| o == "*");
+    let allow_origin = if wildcard {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.allowed_origins.iter().filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|_| warn!("Ignoring invalid CORS origin: {}", origin))
+                .ok()
+        }))
+    };
+
+    // Browsers reject credentials with a wildcard origin (tower-http panics)
+    let allow_credentials = config.allow_credentials && !wildcard;
+    if config.allow_credentials && wildcard {
+        warn!("gateway.cors.allow_credentials is ignored with allowed_origins \"*\"");
+    }
+
+    let cors = CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+        .allow_credentials(allow_credentials);
+
+    app.layer(cors).layer(middleware::from_fn(preflight_no_content))
+}
+
+/// `CorsLayer` answers preflights with `200 OK`; report `204 No Content` instead
+async fn preflight_no_content(request: Request, next: Next) -> Response {
+    let is_options = request.method() == Method::OPTIONS;
+    let mut response = next.run(request).await;
+    if is_options && response.status() == StatusCode::OK {
+        *response.status_mut() = StatusCode::NO_CONTENT;
+    }
+    response
+}
+
+/// Throttle `app` with `limiter`; returns it unchanged when no limit is set
+///
+/// Requests are keyed by peer IP (available when served by [`serve`]) and,
+/// when `auth` accepts their credentials, by the authenticated client
+/// ([`Principal::key`](super::auth::Principal::key)). Over the limit, the
+/// response is `429 Too Many Requests` with a `Retry-After` header in
+/// seconds.
+pub fn with_rate_limit(app: Router, limiter: GatewayRateLimiter, auth: Option<GatewayAuth>) -> Router {
+    if !limiter.is_enabled() {
+        return app;
+    }
+    app.layer(middleware::from_fn_with_state((limiter, auth), throttle))
+}
+
+async fn throttle(
+    State((limiter, auth)): State<(GatewayRateLimiter, Option<GatewayAuth>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    // Unauthenticated requests are only limited per IP
+    let user = auth
+        .as_ref()
+        .and_then(|auth| auth.principal(request.headers()).ok())
+        .and_then(|principal| principal.key);
+    if let Err(wait) = limiter.check(ip, user.as_deref()) {
+        let secs = retry_after_secs(wait);
+        debug!("Rate limited {} {} for {}s", request.method(), request.uri().path(), secs);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, secs.to_string())],
+            "rate limit exceeded",
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+/// Serve the gateway HTTP endpoints until the task is cancelled
+///
+/// Takes a finished router so channels can merge their webhook routes
+/// (e.g. Slack) into [`router`].
+pub async fn serve(addr: SocketAddr, app: Router) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Gateway HTTP endpoints listening on http://{}", addr);
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+    Ok(())
+}
+
+async fn list_plugins(State(plugins): State<Arc<PluginRegistry>>) -> Json<Vec<PluginHealth>> {
+    Json(plugins.health_check_all(PLUGIN_HEALTH_TIMEOUT).await)
+}
+
+async fn health(
+    State(store): State<Option<AgentStatusStore>>,
+) -> std::result::Result<Json<HealthReport>, (StatusCode, String)> {
+    let mut report = HealthReport {
+        status: "ok",
+        version: crate::VERSION,
+        agent_state: None,
+        tasks: None,
+    };
+    if let Some(store) = store {
+        let (row, stats) = store
+            .get_with_task_stats()
+            .await
+            .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+        report.agent_state = Some(row.status);
+        report.tasks = Some(stats);
+    }
+    Ok(Json(report))
+}
+
+async fn db_health(
+    State(pool): State<Option<PostgresPool>>,
+) -> std::result::Result<Json<PoolStats>, (StatusCode, &'static str)> {
+    match pool {
+        Some(pool) => Ok(Json(PoolStats::from_pool(&pool))),
+        None => Err((StatusCode::SERVICE_UNAVAILABLE, "database not configured")),
+    }
+}
+
+async fn metrics(State(recorder): State<Arc<PrometheusMetricsRecorder>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        recorder.render(),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    limit: Option<usize>,
+}
+
+async fn status_history(
+    State(store): State<Option<AgentStatusStore>>,
+    Query(query): Query<HistoryQuery>,
+) -> std::result::Result<Json<Vec<AgentStatusTransition>>, (StatusCode, String)> {
+    let store = store.ok_or((StatusCode::SERVICE_UNAVAILABLE, "database not configured".to_string()))?;
+    store
+        .history(query.limit.unwrap_or(DEFAULT_STATUS_HISTORY_LIMIT).min(MAX_STATUS_HISTORY_LIMIT))
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// Check the bearer token against `auth.tokens`; only token mode is accepted
+///
+/// Guards the endpoints that change the running agent (soul editing, tool
+/// reloads).
+pub fn authorize(headers: &HeaderMap, auth: &AuthConfig) -> std::result::Result<(), (StatusCode, String)> {
+    if auth.mode != AuthMode::Token {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "this endpoint requires gateway.auth.mode = \"token\"".to_string(),
+        ));
+    }
+
+    match bearer_token(headers) {
+        Some(token) if is_allowed_token(auth, token) => Ok(()),
+        _ => Err((StatusCode::UNAUTHORIZED, "invalid or missing bearer token".to_string())),
+    }
+}
+
+async fn export_soul_sections(
+    State(store): State<Option<SoulStore>>,
+) -> std::result::Result<Json<Vec<SoulSection>>, (StatusCode, String)> {
+    let store = store.ok_or((StatusCode::SERVICE_UNAVAILABLE, "database not configured".to_string()))?;
+    store
+        .export_sections()
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+struct SectionUpdate {
+    content: String,
+}
+
+async fn update_soul_section(
+    State(store): State<Option<SoulStore>>,
+    Path(title): Path<String>,
+    Json(update): Json<SectionUpdate>,
+) -> std::result::Result<Json<SoulSection>, (StatusCode, String)> {
+    let store = store.ok_or((StatusCode::SERVICE_UNAVAILABLE, "database not configured".to_string()))?;
+
+    store
+        .update_section(&title, &update.content)
+        .await
+        .map_err(|e| match e {
+            Error::NotFound(_) => (StatusCode::NOT_FOUND, e.to_string()),
+            Error::InvalidInput(_) => (StatusCode::FORBIDDEN, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+    info!("Soul section '{}' updated via gateway API", title);
+
+    match store.get_section(&title).await {
+        Ok(Some(section)) => Ok(Json(section)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("Soul section '{}'", title))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_plugins_endpoint_empty_registry() {
+        let response = router(Arc::new(PluginRegistry::new()))
+            .oneshot(Request::get("/plugins").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"[]");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        use crate::tools::MetricsRecorder;
+
+        let recorder = Arc::new(PrometheusMetricsRecorder::new());
+        recorder.record_call("git", 42, true);
+        let response = metrics_router(recorder)
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("openagent_tool_calls_total{status=\"success\",tool=\"git\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_health_without_store() {
+        let response = health_router(None)
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(report["status"], "ok");
+        assert!(report.get("tasks").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_db_health_without_pool() {
+        let response = db_health_router(None)
+            .oneshot(Request::get("/health/db").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_status_history_without_store() {
+        let response = status_history_router(None)
+            .oneshot(Request::get("/agent/status/history?limit=5").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    fn soul_patch(token: Option<&str>) -> Request<Body> {
+        soul_patch_with_body(token, r#"{"content":"- Curious"}"#)
+    }
+
+    fn soul_patch_with_body(token: Option<&str>, body: &'static str) -> Request<Body> {
+        let mut request = Request::patch("/soul/sections/Personality%20Traits")
+            .header(header::CONTENT_TYPE, "application/json");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        request.body(Body::from(body)).unwrap()
+    }
+
+    fn live_config(auth: AuthConfig) -> Arc<RwLock<Config>> {
+        let mut config = Config::default();
+        config.gateway.auth = auth;
+        Arc::new(RwLock::new(config))
+    }
+
+    #[tokio::test]
+    async fn test_soul_patch_requires_token() {
+        let auth = AuthConfig {
+            mode: AuthMode::Token,
+            tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let app = soul_router(None, live_config(auth));
+
+        let response = app.clone().oneshot(soul_patch(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(soul_patch(Some("wrong"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Authentication comes before the body is parsed
+        let response = app.clone().oneshot(soul_patch_with_body(None, "not json")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // Authorized, but there is no database behind it
+        let response = app.oneshot(soul_patch(Some("secret"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_soul_patch_rejected_without_token_mode() {
+        let auth = AuthConfig {
+            mode: AuthMode::None,
+            tokens: vec!["secret".to_string()],
+            ..Default::default()
+        };
+        let response = soul_router(None, live_config(auth))
+            .oneshot(soul_patch(Some("secret")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_soul_auth_follows_live_config() {
+        let config = live_config(AuthConfig {
+            mode: AuthMode::Token,
+            tokens: vec!["old".to_string()],
+            ..Default::default()
+        });
+        let app = soul_router(None, config.clone());
+
+        config.write().await.gateway.auth.tokens = vec!["new".to_string()];
+        let response = app.clone().oneshot(soul_patch(Some("old"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.oneshot(soul_patch(Some("new"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_per_ip() {
+        use axum::extract::connect_info::MockConnectInfo;
+        use crate::config::RateLimitConfig;
+
+        let limiter = GatewayRateLimiter::from_config(&RateLimitConfig {
+            requests_per_minute_per_ip: 6,
+            requests_per_minute_per_user: 0,
+            burst_size: 2,
+        });
+        let app = with_rate_limit(health_router(None), limiter, None)
+            .layer(MockConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+        let get = || Request::get("/health").body(Body::empty()).unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(app.clone().oneshot(get()).await.unwrap().status(), StatusCode::OK);
+        }
+        let response = app.oneshot(get()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // 6/min: the next request is allowed after 10 seconds
+        assert_eq!(response.headers()[header::RETRY_AFTER], "10");
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/plugins")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_allowed_origin() {
+        let config = CorsConfig {
+            allowed_origins: vec!["https://app.example.com".to_string()],
+            allow_credentials: true,
+        };
+        let app = with_cors(router(Arc::new(PluginRegistry::new())), &config);
+
+        let response = app.clone().oneshot(preflight("https://app.example.com")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap().contains("GET"));
+
+        let response = app.oneshot(preflight("https://evil.example.com")).await.unwrap();
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight_wildcard() {
+        let config = CorsConfig {
+            allowed_origins: vec!["*".to_string()],
+            allow_credentials: true,
+        };
+        let app = with_cors(router(Arc::new(PluginRegistry::new())), &config);
+
+        let response = app.oneshot(preflight("https://anywhere.example")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+    }
+}
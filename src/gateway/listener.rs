@@ -0,0 +1,389 @@
+//! WebSocket listener with optional TLS
+//!
+//! [`WebSocketListener`] accepts TCP connections and, when
+//! `gateway.tls` is configured, completes a TLS handshake (rustls via
+//! `tokio-rustls`) before the WebSocket upgrade, so clients connect with
+//! `wss://` instead of `ws://`.
+//!
+//! Both handshakes must finish within [`HANDSHAKE_TIMEOUT`], so a client
+//! that opens a TCP connection and then stalls is dropped.
+//!
+//! [`client_connector`] builds the matching client side. With
+//! `gateway.accept_self_signed_for_testing` it accepts any server
+//! certificate, which lets local tools talk to a gateway using a self-signed
+//! certificate.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use tokio_rustls::rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, ServerConfig, SignatureScheme};
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::header::CONTENT_TYPE;
+use tokio_tungstenite::tungstenite::http::{HeaderValue, StatusCode};
+use tokio_tungstenite::{Connector, WebSocketStream};
+use tracing::info;
+
+use super::auth::{GatewayAuth, Principal};
+use super::protocol::ProtocolEncoding;
+use crate::config::{GatewayConfig, TlsConfig};
+use crate::error::{Error, Result};
+
+/// How long a client gets to complete the TLS and WebSocket handshakes
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Byte stream under an accepted WebSocket: plain TCP or TLS over TCP
+pub trait GatewayIo: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> GatewayIo for T {}
+
+/// Connection stream handed out by [`WebSocketListener::accept`]
+pub type GatewayStream = Box<dyn GatewayIo>;
+
+/// Accepts gateway WebSocket connections, over TLS when configured
+pub struct WebSocketListener {
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+}
+
+impl WebSocketListener {
+    /// Bind `addr`, loading the certificate and key from `tls` if given
+    pub async fn bind(addr: SocketAddr, tls: Option<&TlsConfig>) -> Result<Self> {
+        let tls = tls.map(load_tls_acceptor).transpose()?;
+        let listener = TcpListener::bind(addr).await?;
+        info!(
+            "Gateway WebSocket listening on {}://{}",
+            if tls.is_some() { "wss" } else { "ws" },
+            listener.local_addr()?
+        );
+        Ok(WebSocketListener { listener, tls })
+    }
+
+    /// Bind `gateway.bind`, `gateway.websocket_port` and the TLS settings
+    /// of `config`
+    pub async fn from_config(config: &GatewayConfig) -> Result<Self> {
+        let addr: SocketAddr = format!("{}:{}", config.bind, config.websocket_port)
+            .parse()
+            .map_err(|e| Error::Config(format!("Invalid gateway bind address: {}", e)))?;
+        Self::bind(addr, config.tls.as_ref()).await
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Whether connections are upgraded to TLS
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    /// Accept the next connection and complete the TLS (if configured) and
+    /// WebSocket handshakes, returning the frame encoding the client asked for
+    ///
+    /// A failed handshake only affects that connection; callers should log
+    /// the error and keep accepting.
+    pub async fn accept(&self) -> Result<(WebSocketStream<GatewayStream>, SocketAddr, ProtocolEncoding)> {
+        let incoming = self.accept_incoming().await?;
+        let peer = incoming.peer();
+        let (ws, encoding, _) = with_handshake_timeout(peer, async {
+            let stream = incoming.upgrade().await?;
+            handshake(stream, peer, None).await
+        })
+        .await?;
+        Ok((ws, peer, encoding))
+    }
+
+    /// Accept the next TCP connection, without any handshake
+    pub(super) async fn accept_incoming(&self) -> Result<IncomingStream> {
+        let (tcp, peer) = self.listener.accept().await?;
+        Ok(IncomingStream {
+            tcp,
+            peer,
+            tls: self.tls.clone(),
+        })
+    }
+}
+
+/// TCP connection accepted by a [`WebSocketListener`], before the TLS
+/// handshake
+pub(super) struct IncomingStream {
+    tcp: TcpStream,
+    peer: SocketAddr,
+    tls: Option<TlsAcceptor>,
+}
+
+impl IncomingStream {
+    /// Address of the client
+    pub(super) fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Complete the TLS handshake, if the listener has TLS configured
+    pub(super) async fn upgrade(self) -> Result<GatewayStream> {
+        Ok(match self.tls {
+            Some(acceptor) => Box::new(
+                acceptor
+                    .accept(self.tcp)
+                    .await
+                    .map_err(|e| Error::Connection(format!("TLS handshake with {} failed: {}", self.peer, e)))?,
+            ),
+            None => Box::new(self.tcp),
+        })
+    }
+}
+
+/// Run the handshakes in `handshakes`, failing with `Error::Timeout` if
+/// they take longer than [`HANDSHAKE_TIMEOUT`]
+pub(super) async fn with_handshake_timeout<T>(
+    peer: SocketAddr,
+    handshakes: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, handshakes)
+        .await
+        .map_err(|_| Error::Timeout(format!("Handshake with {} took longer than {:?}", peer, HANDSHAKE_TIMEOUT)))?
+}
+
+/// Complete the WebSocket handshake on `stream`, negotiating the frame
+/// encoding from the upgrade request's `Content-Type`
+///
+/// The chosen encoding's MIME type is sent back in the `Content-Type` of the
+/// `101 Switching Protocols` response. With `auth`, the upgrade request is
+/// authenticated first and refused with `401 Unauthorized` if that fails;
+/// the authenticated client is returned.
+pub(super) async fn handshake(
+    stream: GatewayStream,
+    peer: SocketAddr,
+    auth: Option<&GatewayAuth>,
+) -> Result<(WebSocketStream<GatewayStream>, ProtocolEncoding, Principal)> {
+    let mut encoding = ProtocolEncoding::Json;
+    let mut identity = Ok(Principal::default());
+    // The signature is tungstenite's handshake `Callback`
+    #[allow(clippy::result_large_err)]
+    let negotiate = |request: &Request, mut response: Response| {
+        if let Some(auth) = auth {
+            identity = auth.principal(request.headers());
+            if identity.is_err() {
+                let mut refusal = ErrorResponse::new(Some("Unauthorized".to_string()));
+                *refusal.status_mut() = StatusCode::UNAUTHORIZED;
+                return Err(refusal);
+            }
+        }
+        let content_type = request.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok());
+        encoding = ProtocolEncoding::negotiate(content_type);
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static(encoding.content_type()));
+        Ok(response)
+    };
+    let handshake = tokio_tungstenite::accept_hdr_async(stream, negotiate).await;
+    let principal = identity.map_err(|e| {
+        let reason = match e {
+            Error::Unauthorized(reason) => reason,
+            other => other.to_string(),
+        };
+        Error::Unauthorized(format!("Refused WebSocket connection from {}: {}", peer, reason))
+    })?;
+    let ws = handshake.map_err(|e| Error::WebSocket(format!("WebSocket handshake with {} failed: {}", peer, e)))?;
+    Ok((ws, encoding, principal))
+}
+
+/// Build a TLS acceptor from the PEM certificate chain and key in `config`
+pub fn load_tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(&config.cert_pem_path)
+        .and_then(|certs| certs.collect::<std::result::Result<Vec<_>, _>>())
+        .map_err(|e| {
+            Error::Config(format!("Invalid TLS certificate {}: {}", config.cert_pem_path.display(), e))
+        })?;
+    if certs.is_empty() {
+        return Err(Error::Config(format!(
+            "No certificate found in {}",
+            config.cert_pem_path.display()
+        )));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_pem_path).map_err(|e| {
+        Error::Config(format!("Invalid TLS private key {}: {}", config.key_pem_path.display(), e))
+    })?;
+
+    let server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(tls_config_error)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(tls_config_error)?;
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// TLS connector for clients of a gateway configured with `config`
+///
+/// Returns `None` (the default connector, verifying against the web PKI
+/// roots) unless `accept_self_signed_for_testing` is set.
+pub fn client_connector(config: &GatewayConfig) -> Result<Option<Connector>> {
+    if !config.accept_self_signed_for_testing {
+        return Ok(None);
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let client_config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(tls_config_error)?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(provider)))
+        .with_no_client_auth();
+    Ok(Some(Connector::Rustls(Arc::new(client_config))))
+}
+
+fn tls_config_error(e: tokio_rustls::rustls::Error) -> Error {
+    Error::Config(format!("Invalid TLS configuration: {}", e))
+}
+
+/// Accepts any server certificate but still checks handshake signatures
+#[derive(Debug)]
+struct AcceptAnyCertificate(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::{keep_alive, GatewayFrame, HeartbeatMonitor, ProtocolEncoding};
+    use futures::{SinkExt, StreamExt};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Write a self-signed certificate for `localhost` into `dir`
+    fn self_signed(dir: &std::path::Path) -> TlsConfig {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let config = TlsConfig {
+            cert_pem_path: dir.join("gateway.crt"),
+            key_pem_path: dir.join("gateway.key"),
+        };
+        std::fs::write(&config.cert_pem_path, cert.cert.pem()).unwrap();
+        std::fs::write(&config.key_pem_path, cert.key_pair.serialize_pem()).unwrap();
+        config
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_handshake_times_out() {
+        let listener = WebSocketListener::bind("127.0.0.1:0".parse().unwrap(), None)
+            .await
+            .unwrap();
+        // Connects but never sends the upgrade request
+        let _stalled = tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(listener.accept().await, Err(Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_wss_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = self_signed(dir.path());
+        let listener = WebSocketListener::bind("127.0.0.1:0".parse().unwrap(), Some(&tls))
+            .await
+            .unwrap();
+        assert!(listener.is_tls());
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (ws, _, encoding) = listener.accept().await.unwrap();
+            assert_eq!(encoding, ProtocolEncoding::Json);
+            let monitor = HeartbeatMonitor::new(Duration::from_secs(30), Duration::from_secs(10));
+            keep_alive(ws, monitor, encoding, |frame| match frame {
+                GatewayFrame::Ping { id } => Some(GatewayFrame::Pong { id }),
+                _ => None,
+            })
+            .await
+        });
+
+        let url = format!("wss://localhost:{}", port);
+        let config = GatewayConfig {
+            accept_self_signed_for_testing: true,
+            ..Default::default()
+        };
+        let (mut client, _) =
+            tokio_tungstenite::connect_async_tls_with_config(&url, None, false, client_connector(&config).unwrap())
+                .await
+                .unwrap();
+
+        let ping = GatewayFrame::Ping { id: "p1".to_string() };
+        let text = String::from_utf8(ping.encode(ProtocolEncoding::Json).unwrap()).unwrap();
+        client.send(Message::text(text)).await.unwrap();
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => assert!(matches!(
+                GatewayFrame::decode(text.as_bytes(), ProtocolEncoding::Json).unwrap(),
+                GatewayFrame::Pong { id } if id == "p1"
+            )),
+            other => panic!("unexpected message: {:?}", other),
+        }
+
+        client.close(None).await.unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_self_signed_rejected_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = self_signed(dir.path());
+        let listener = WebSocketListener::bind("127.0.0.1:0".parse().unwrap(), Some(&tls))
+            .await
+            .unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move { listener.accept().await.map(|_| ()) });
+
+        assert!(client_connector(&GatewayConfig::default()).unwrap().is_none());
+        let url = format!("wss://localhost:{}", port);
+        assert!(tokio_tungstenite::connect_async(&url).await.is_err());
+        assert!(matches!(server.await.unwrap(), Err(Error::Connection(_))));
+    }
+
+    #[test]
+    fn test_load_tls_acceptor_missing_files() {
+        let config = TlsConfig {
+            cert_pem_path: "/nonexistent/gateway.crt".into(),
+            key_pem_path: "/nonexistent/gateway.key".into(),
+        };
+        assert!(matches!(load_tls_acceptor(&config), Err(Error::Config(_))));
+    }
+}
@@ -8,7 +8,7 @@
 //! ```text
 //! ┌─────────────────────────────────────────────────────┐
 //! │                    Gateway Server                    │
-//! │              ws://127.0.0.1:18789                   │
+//! │              ws://127.0.0.1:18790                   │
 //! └───────────────────────┬─────────────────────────────┘
 //!                         │
 //!           ┌─────────────┼─────────────┐
@@ -20,17 +20,31 @@
 //!      └─────────┘   └─────────┘   └─────────┘
 //! ```
 
+pub mod auth;
+pub mod continuation;
+pub mod heartbeat;
+pub mod http;
+pub mod listener;
 pub mod protocol;
+pub mod rate_limit;
+pub mod server;
+
+pub use auth::{is_allowed_token, GatewayAuth, JwtClaims, JwtVerifier, Principal};
+pub use continuation::{ContinuationStore, CONTINUATION_PAGE_CHARS, CONTINUATION_TTL};
+pub use heartbeat::{keep_alive, keep_alive_limited, HeartbeatMonitor};
+pub use listener::{client_connector, load_tls_acceptor, GatewayStream, WebSocketListener, HANDSHAKE_TIMEOUT};
+pub use rate_limit::GatewayRateLimiter;
+pub use server::{ActiveTurns, GatewayConnection, GatewayServer, PendingConnection, TurnGuard};
 
 pub use protocol::{
-    GatewayFrame, ProtocolVersion, PROTOCOL_VERSION,
+    GatewayFrame, ProtocolEncoding, ProtocolVersion, PROTOCOL_VERSION,
     schema::error_codes,
 };
 
 pub use protocol::types::{
     AuthRequest, AuthResponse, AuthMethod,
     SessionInfo, SessionsListRequest, SessionsListResponse,
-    AgentSendRequest, AgentResponse, UsageStats,
+    AgentSendRequest, AgentResponse, AgentContinueRequest, UsageStats,
     ChannelStatus, ChannelsListRequest, ChannelsListResponse,
     StreamChunkEvent, MessageEvent,
     events,
@@ -0,0 +1,121 @@
+//! Gateway protocol encodings
+//!
+//! Frames are JSON by default. Clients may request CBOR during the WebSocket
+//! handshake by sending `Content-Type: application/cbor`; the server then
+//! exchanges frames as binary CBOR messages for the rest of the connection.
+
+use serde::{Deserialize, Serialize};
+
+use super::schema::GatewayFrame;
+use crate::error::{Error, Result};
+
+/// MIME type for JSON frames
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+
+/// MIME type for CBOR frames
+pub const CONTENT_TYPE_CBOR: &str = "application/cbor";
+
+/// Wire encoding for gateway frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtocolEncoding {
+    /// UTF-8 JSON in WebSocket text messages
+    #[default]
+    Json,
+    /// CBOR (RFC 8949) in WebSocket binary messages
+    Cbor,
+}
+
+impl ProtocolEncoding {
+    /// Pick the encoding from a handshake `Content-Type` header value.
+    /// Missing or unrecognised values fall back to JSON.
+    pub fn negotiate(content_type: Option<&str>) -> Self {
+        let mime = content_type
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase());
+        match mime.as_deref() {
+            Some(CONTENT_TYPE_CBOR) => ProtocolEncoding::Cbor,
+            _ => ProtocolEncoding::Json,
+        }
+    }
+
+    /// The `Content-Type` value for this encoding
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ProtocolEncoding::Json => CONTENT_TYPE_JSON,
+            ProtocolEncoding::Cbor => CONTENT_TYPE_CBOR,
+        }
+    }
+
+    /// Whether frames must be sent as binary WebSocket messages
+    pub fn is_binary(&self) -> bool {
+        matches!(self, ProtocolEncoding::Cbor)
+    }
+}
+
+impl GatewayFrame {
+    /// Serialize this frame with the given encoding
+    pub fn encode(&self, encoding: ProtocolEncoding) -> Result<Vec<u8>> {
+        match encoding {
+            ProtocolEncoding::Json => Ok(serde_json::to_vec(self)?),
+            ProtocolEncoding::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(self, &mut buf)
+                    .map_err(|e| Error::InvalidInput(format!("Failed to encode CBOR frame: {}", e)))?;
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Deserialize a frame encoded with the given encoding
+    pub fn decode(bytes: &[u8], encoding: ProtocolEncoding) -> Result<Self> {
+        match encoding {
+            ProtocolEncoding::Json => Ok(serde_json::from_slice(bytes)?),
+            ProtocolEncoding::Cbor => ciborium::from_reader(bytes)
+                .map_err(|e| Error::InvalidInput(format!("Failed to decode CBOR frame: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gateway::protocol::schema::EventFrame;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ProtocolEncoding::negotiate(None), ProtocolEncoding::Json);
+        assert_eq!(
+            ProtocolEncoding::negotiate(Some("application/cbor")),
+            ProtocolEncoding::Cbor
+        );
+        assert_eq!(
+            ProtocolEncoding::negotiate(Some("Application/CBOR; charset=binary")),
+            ProtocolEncoding::Cbor
+        );
+        assert_eq!(
+            ProtocolEncoding::negotiate(Some("text/plain")),
+            ProtocolEncoding::Json
+        );
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let frame = GatewayFrame::Event(
+            EventFrame::new("stream.chunk", serde_json::json!({"delta": "hi", "index": 3}))
+                .with_session("s1"),
+        );
+
+        for encoding in [ProtocolEncoding::Json, ProtocolEncoding::Cbor] {
+            let bytes = frame.encode(encoding).unwrap();
+            match GatewayFrame::decode(&bytes, encoding).unwrap() {
+                GatewayFrame::Event(event) => {
+                    assert_eq!(event.event, "stream.chunk");
+                    assert_eq!(event.data["index"], 3);
+                    assert_eq!(event.session_id.as_deref(), Some("s1"));
+                }
+                other => panic!("unexpected frame: {:?}", other),
+            }
+        }
+    }
+}
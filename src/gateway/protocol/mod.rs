@@ -5,7 +5,8 @@
 //!
 //! ## Protocol Overview
 //!
-//! - **JSON-based messages** over WebSocket
+//! - **JSON-based messages** over WebSocket, with optional CBOR negotiated
+//!   via `Content-Type: application/cbor` during the handshake
 //! - **Request-response pattern** with unique message IDs
 //! - **Event streaming** for real-time updates
 //! - **Authentication** via tokens or sessions
@@ -16,9 +17,13 @@
 //! - `Response`: Server responses to requests
 //! - `Event`: Server-pushed events (streaming, status updates)
 //! - `Error`: Error responses
+//! - `Heartbeat` / `HeartbeatAck`: server keep-alive and its client answer;
+//!   clients reply via [`GatewayFrame::auto_reply`]
 
+pub mod encoding;
 pub mod schema;
 pub mod types;
 
+pub use encoding::ProtocolEncoding;
 pub use schema::{GatewayFrame, ProtocolVersion, PROTOCOL_VERSION};
 pub use types::*;
@@ -49,6 +49,26 @@ pub enum GatewayFrame {
     Ping { id: String },
     /// Pong
     Pong { id: String },
+    /// Keep-alive sent by the server; clients answer with `HeartbeatAck`
+    Heartbeat { timestamp: i64 },
+    /// Client answer to `Heartbeat`, echoing its timestamp
+    HeartbeatAck { timestamp: i64 },
+    /// Sent by the server before it stops; connections are closed within
+    /// `delay_secs`
+    #[serde(rename_all = "camelCase")]
+    ShuttingDown { delay_secs: u16 },
+}
+
+impl GatewayFrame {
+    /// Frame a client should send back without involving the application:
+    /// `HeartbeatAck` for `Heartbeat` and `Pong` for `Ping`
+    pub fn auto_reply(&self) -> Option<GatewayFrame> {
+        match self {
+            GatewayFrame::Heartbeat { timestamp } => Some(GatewayFrame::HeartbeatAck { timestamp: *timestamp }),
+            GatewayFrame::Ping { id } => Some(GatewayFrame::Pong { id: id.clone() }),
+            _ => None,
+        }
+    }
 }
 
 /// Request frame
@@ -140,6 +160,8 @@ pub mod error_codes {
     pub const SESSION_NOT_FOUND: i32 = -32003;
     /// Channel not available
     pub const CHANNEL_NOT_AVAILABLE: i32 = -32004;
+    /// Continuation token unknown or expired
+    pub const CONTINUATION_NOT_FOUND: i32 = -32005;
 }
 
 impl ProtocolError {
@@ -247,6 +269,18 @@ mod tests {
         assert!(json.contains("agent.send"));
     }
 
+    #[test]
+    fn test_heartbeat_auto_reply() {
+        let json = serde_json::to_string(&GatewayFrame::Heartbeat { timestamp: 42 }).unwrap();
+        assert_eq!(json, r#"{"type":"heartbeat","timestamp":42}"#);
+
+        let frame: GatewayFrame = serde_json::from_str(&json).unwrap();
+        let ack = serde_json::to_string(&frame.auto_reply().unwrap()).unwrap();
+        assert_eq!(ack, r#"{"type":"heartbeatAck","timestamp":42}"#);
+
+        assert!(GatewayFrame::HeartbeatAck { timestamp: 42 }.auto_reply().is_none());
+    }
+
     #[test]
     fn test_response_frame() {
         let success = ResponseFrame::success("1", serde_json::json!({"ok": true}));
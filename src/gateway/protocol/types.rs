@@ -5,6 +5,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::agent::Conversation;
+
 // ============================================================================
 // Authentication
 // ============================================================================
@@ -71,10 +73,33 @@ pub struct SessionInfo {
     pub last_activity_at: i64,
     /// Message count
     pub message_count: u32,
+    /// Opaque sync cursor for the latest message; clients pass it back on
+    /// reconnect to fetch only newer messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
     /// Session metadata
     pub metadata: HashMap<String, serde_json::Value>,
 }
 
+impl From<&Conversation> for SessionInfo {
+    /// Session summary of `conv`, with its latest message checkpoint as the
+    /// sync cursor
+    fn from(conv: &Conversation) -> Self {
+        SessionInfo {
+            id: conv.session().to_string(),
+            channel_id: Some(conv.channel.clone()),
+            conversation_id: Some(conv.id.to_string()),
+            user_id: Some(conv.user_id.clone()),
+            model: conv.model.clone(),
+            created_at: conv.created_at.timestamp(),
+            last_activity_at: conv.updated_at.timestamp(),
+            message_count: conv.messages.len() as u32,
+            checkpoint: conv.latest_checkpoint().map(|id| id.to_string()),
+            metadata: HashMap::new(),
+        }
+    }
+}
+
 /// List sessions request
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -130,6 +155,18 @@ pub struct AgentResponse {
     pub finish_reason: Option<String>,
     /// Usage statistics
     pub usage: Option<UsageStats>,
+    /// Set when `content` is only the first page of a longer response;
+    /// pass it in an `agent.continue` request to fetch the next page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<String>,
+}
+
+/// Fetch the next page of a paginated agent response (`agent.continue`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentContinueRequest {
+    /// `continuationToken` of the previous page
+    pub token: String,
 }
 
 /// Usage statistics
@@ -256,4 +293,32 @@ mod tests {
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("Hello"));
     }
+
+    #[test]
+    fn test_session_info_checkpoint() {
+        let json = serde_json::json!({
+            "id": "s1",
+            "channelId": null,
+            "conversationId": null,
+            "userId": "u1",
+            "model": "gpt-4",
+            "createdAt": 0,
+            "lastActivityAt": 0,
+            "messageCount": 2,
+            "metadata": {}
+        });
+        let info: SessionInfo = serde_json::from_value(json).unwrap();
+        assert!(info.checkpoint.is_none());
+
+        let mut conv = Conversation::new("u1", "gpt-4");
+        assert!(SessionInfo::from(&conv).checkpoint.is_none());
+
+        conv.add_message(crate::agent::Message::user("Hello"));
+        let cursor = conv.latest_checkpoint().unwrap();
+        let info = SessionInfo::from(&conv);
+        assert_eq!(info.message_count, 1);
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["checkpoint"], cursor.to_string());
+        assert_eq!(json["userId"], "u1");
+    }
 }
@@ -0,0 +1,151 @@
+//! Gateway request throttling
+//!
+//! [`GatewayRateLimiter`] applies `gateway.rate_limit` with `governor`
+//! keyed limiters: one state per client IP and one per authenticated user
+//! (see [`Principal::key`](super::auth::Principal::key)). Each key allows
+//! bursts of up to `burst_size` requests and refills at the configured
+//! per-minute rate.
+//! HTTP routes wrapped with [`with_rate_limit`](super::http::with_rate_limit)
+//! answer `429 Too Many Requests` with `Retry-After` when a bucket is empty;
+//! WebSocket connections served by
+//! [`keep_alive_limited`](super::heartbeat::keep_alive_limited) or
+//! [`GatewayConnection::serve_limited`](super::GatewayConnection::serve_limited)
+//! get a `RATE_LIMITED` error frame and the message is dropped.
+
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+
+use governor::clock::Clock;
+use governor::{DefaultKeyedRateLimiter, Quota};
+
+use crate::config::RateLimitConfig;
+
+/// Above this many tracked keys, keys whose limit has fully refilled are
+/// forgotten
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Rate limiter with one state per client key
+struct KeyedLimiter<K: Hash + Eq + Clone> {
+    limiter: DefaultKeyedRateLimiter<K>,
+}
+
+impl<K: Hash + Eq + Clone> KeyedLimiter<K> {
+    /// `None` when `per_minute` is 0 (unlimited)
+    fn new(per_minute: u32, burst: u32) -> Option<Self> {
+        let quota = Quota::per_minute(NonZeroU32::new(per_minute)?)
+            .allow_burst(NonZeroU32::new(burst).unwrap_or(NonZeroU32::MIN));
+        Some(KeyedLimiter {
+            limiter: DefaultKeyedRateLimiter::keyed(quota),
+        })
+    }
+
+    /// Count one request for `key`, or return how long until one is allowed
+    fn check(&self, key: &K) -> Result<(), Duration> {
+        if self.limiter.len() >= MAX_TRACKED_KEYS {
+            self.limiter.retain_recent();
+        }
+        self.limiter
+            .check_key(key)
+            .map_err(|not_until| not_until.wait_time_from(self.limiter.clock().now()))
+    }
+}
+
+/// Per-IP and per-user request limits, shared by clones
+#[derive(Clone)]
+pub struct GatewayRateLimiter {
+    inner: Arc<Limiters>,
+}
+
+struct Limiters {
+    per_ip: Option<KeyedLimiter<IpAddr>>,
+    per_user: Option<KeyedLimiter<String>>,
+}
+
+impl GatewayRateLimiter {
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        GatewayRateLimiter {
+            inner: Arc::new(Limiters {
+                per_ip: KeyedLimiter::new(config.requests_per_minute_per_ip, config.burst_size),
+                per_user: KeyedLimiter::new(config.requests_per_minute_per_user, config.burst_size),
+            }),
+        }
+    }
+
+    /// Whether any limit is configured
+    pub fn is_enabled(&self) -> bool {
+        self.inner.per_ip.is_some() || self.inner.per_user.is_some()
+    }
+
+    /// Count one request from `ip` and `user`, the authenticated client
+    /// key
+    ///
+    /// Returns how long the client should wait when either limit is
+    /// exceeded. Unknown IPs and anonymous requests are only checked
+    /// against the other limit.
+    pub fn check(&self, ip: Option<IpAddr>, user: Option<&str>) -> Result<(), Duration> {
+        if let (Some(limiter), Some(ip)) = (&self.inner.per_ip, ip) {
+            limiter.check(&ip)?;
+        }
+        if let (Some(limiter), Some(user)) = (&self.inner.per_user, user) {
+            limiter.check(&user.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Whole seconds to put in a `Retry-After` header (at least 1)
+pub fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs_f64().ceil().max(1.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_ip: u32, per_user: u32, burst: u32) -> GatewayRateLimiter {
+        GatewayRateLimiter::from_config(&RateLimitConfig {
+            requests_per_minute_per_ip: per_ip,
+            requests_per_minute_per_user: per_user,
+            burst_size: burst,
+        })
+    }
+
+    #[test]
+    fn test_burst_then_refill() {
+        let limiter = limiter(60, 0, 3);
+        let ip = Some("10.0.0.1".parse().unwrap());
+
+        for _ in 0..3 {
+            assert!(limiter.check(ip, None).is_ok());
+        }
+        let wait = limiter.check(ip, None).unwrap_err();
+        assert_eq!(retry_after_secs(wait), 1);
+
+        // Other clients have their own state
+        assert!(limiter.check(Some("10.0.0.2".parse().unwrap()), None).is_ok());
+
+        // 6000/min refills one request every 10ms
+        let fast = self::limiter(6000, 0, 1);
+        assert!(fast.check(ip, None).is_ok());
+        assert!(fast.check(ip, None).is_err());
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(fast.check(ip, None).is_ok());
+    }
+
+    #[test]
+    fn test_user_limit_and_disabled() {
+        let limiter = limiter(0, 30, 1);
+        assert!(limiter.is_enabled());
+        assert!(limiter.check(None, Some("user-a")).is_ok());
+        assert_eq!(retry_after_secs(limiter.check(None, Some("user-a")).unwrap_err()), 2);
+        assert!(limiter.check(None, Some("user-b")).is_ok());
+        // Anonymous requests aren't limited without a per-IP limit
+        assert!(limiter.check(None, None).is_ok());
+
+        let off = GatewayRateLimiter::from_config(&RateLimitConfig::default());
+        assert!(!off.is_enabled());
+    }
+}
@@ -0,0 +1,528 @@
+//! WebSocket connection limits and graceful shutdown
+//!
+//! [`GatewayServer`] accepts connections from a [`WebSocketListener`] and
+//! serves at most `gateway.websocket_max_connections` at once; a semaphore
+//! permit is held for the life of each connection, and upgrades over the
+//! limit are answered with `503 Service Unavailable`. Built with
+//! [`GatewayServer::from_config`], upgrades are authenticated per
+//! `gateway.auth` and refused with `401 Unauthorized` on failure.
+//!
+//! [`GatewayServer::accept_pending`] only accepts the TCP connection; the
+//! TLS and WebSocket handshakes run in [`PendingConnection::establish`],
+//! meant for a task of its own, so one slow client cannot stall the accept
+//! loop.
+//!
+//! [`GatewayServer::shutdown`] stops the gateway in three steps: every
+//! connection is sent a [`GatewayFrame::ShuttingDown`] frame, agent turns
+//! registered with [`ActiveTurns`] get up to `gateway.shutdown_grace_secs`
+//! to finish, and then the connections are closed with code 1001 (going
+//! away).
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{debug, info, warn};
+
+use super::auth::{GatewayAuth, Principal};
+use super::heartbeat::{drive, ClientThrottle, HeartbeatMonitor};
+use super::listener::{handshake, with_handshake_timeout, GatewayStream, IncomingStream, WebSocketListener};
+use super::protocol::{GatewayFrame, ProtocolEncoding};
+use super::rate_limit::GatewayRateLimiter;
+use crate::config::GatewayConfig;
+use crate::error::{Error, Result};
+
+/// How long connections get to send their close frames after draining
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where the server is in its shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ShutdownPhase {
+    Running,
+    /// Clients were told the server stops in `delay_secs`
+    Draining { delay_secs: u16 },
+    /// Connections close now
+    Closing,
+}
+
+/// Counts agent turns in progress, so shutdown can wait for them
+#[derive(Clone)]
+pub struct ActiveTurns {
+    count: Arc<watch::Sender<usize>>,
+}
+
+impl Default for ActiveTurns {
+    fn default() -> Self {
+        ActiveTurns {
+            count: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl ActiveTurns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a turn; it counts as running until the guard is dropped
+    pub fn start(&self) -> TurnGuard {
+        self.count.send_modify(|count| *count += 1);
+        TurnGuard {
+            count: self.count.clone(),
+        }
+    }
+
+    /// Number of turns in progress
+    pub fn count(&self) -> usize {
+        *self.count.borrow()
+    }
+
+    /// Wait until no turn is in progress
+    pub async fn drained(&self) {
+        // The sender lives in `self`, so this cannot fail
+        let _ = self.count.subscribe().wait_for(|count| *count == 0).await;
+    }
+}
+
+/// Marks a turn as running; see [`ActiveTurns::start`]
+pub struct TurnGuard {
+    count: Arc<watch::Sender<usize>>,
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        self.count.send_modify(|count| *count -= 1);
+    }
+}
+
+/// Accepts WebSocket connections up to a limit and shuts them down gracefully
+pub struct GatewayServer {
+    listener: WebSocketListener,
+    connections: Arc<Semaphore>,
+    max_connections: u32,
+    shutdown: watch::Sender<ShutdownPhase>,
+    turns: ActiveTurns,
+    auth: Option<GatewayAuth>,
+}
+
+impl GatewayServer {
+    /// Serve at most `max_connections` (at least 1) from `listener` at once
+    pub fn new(listener: WebSocketListener, max_connections: usize) -> Self {
+        let max_connections = u32::try_from(max_connections.max(1)).unwrap_or(u32::MAX);
+        GatewayServer {
+            listener,
+            connections: Arc::new(Semaphore::new(max_connections as usize)),
+            max_connections,
+            shutdown: watch::channel(ShutdownPhase::Running).0,
+            turns: ActiveTurns::new(),
+            auth: None,
+        }
+    }
+
+    /// Bind the address, TLS settings and connection limit of `config`, and
+    /// authenticate upgrades per `gateway.auth`
+    pub async fn from_config(config: &GatewayConfig) -> Result<Self> {
+        let auth = GatewayAuth::new(config.auth.clone())?;
+        let listener = WebSocketListener::from_config(config).await?;
+        Ok(Self::new(listener, config.websocket_max_connections).with_auth(auth))
+    }
+
+    /// Authenticate upgrade requests with `auth`; failures get
+    /// `401 Unauthorized`
+    pub fn with_auth(mut self, auth: GatewayAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Wait for `turns` on shutdown, e.g. turns that other channels share
+    /// with the WebSocket clients
+    pub fn with_turns(mut self, turns: ActiveTurns) -> Self {
+        self.turns = turns;
+        self
+    }
+
+    /// Address the server is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Agent turns that [`shutdown`](Self::shutdown) waits for
+    pub fn turns(&self) -> &ActiveTurns {
+        &self.turns
+    }
+
+    /// Number of connections being served
+    pub fn active_connections(&self) -> usize {
+        self.max_connections as usize - self.connections.available_permits()
+    }
+
+    /// Accept the next connection and complete its handshakes
+    ///
+    /// Over the connection limit, or once shutdown has started, the upgrade
+    /// is refused with `503 Service Unavailable` and an error is returned.
+    /// Requests that fail authentication get `Error::Unauthorized`.
+    /// Like [`WebSocketListener::accept`], a failed connection only affects
+    /// that client; callers should log the error and keep accepting.
+    ///
+    /// This waits for the client's handshakes; an accept loop should use
+    /// [`accept_pending`](Self::accept_pending) instead.
+    pub async fn accept(&self) -> Result<GatewayConnection> {
+        self.accept_pending().await?.establish().await
+    }
+
+    /// Accept the next TCP connection and claim its place under the limit,
+    /// leaving the handshakes to [`PendingConnection::establish`]
+    pub async fn accept_pending(&self) -> Result<PendingConnection> {
+        let incoming = self.listener.accept_incoming().await?;
+        let permit = match *self.shutdown.borrow() {
+            ShutdownPhase::Running => self.connections.clone().try_acquire_owned().ok(),
+            _ => None,
+        };
+        Ok(PendingConnection {
+            incoming,
+            permit,
+            open: self.active_connections(),
+            auth: self.auth.clone(),
+            shutdown: self.shutdown.subscribe(),
+        })
+    }
+
+    /// Tell clients the server is stopping, wait up to `grace` for running
+    /// agent turns, then close every connection
+    ///
+    /// New connections are refused from the moment this is called.
+    pub async fn shutdown(&self, grace: Duration) {
+        let delay_secs = u16::try_from(grace.as_secs()).unwrap_or(u16::MAX);
+        info!(
+            "Gateway shutting down: {} connections, {} running turns",
+            self.active_connections(),
+            self.turns.count()
+        );
+        self.shutdown.send_replace(ShutdownPhase::Draining { delay_secs });
+
+        if tokio::time::timeout(grace, self.turns.drained()).await.is_err() {
+            warn!("{} agent turns still running after {:?}", self.turns.count(), grace);
+        }
+
+        self.shutdown.send_replace(ShutdownPhase::Closing);
+        let closed = tokio::time::timeout(CLOSE_TIMEOUT, self.connections.acquire_many(self.max_connections)).await;
+        if closed.is_err() {
+            warn!("{} connections did not close in time", self.active_connections());
+        }
+    }
+}
+
+/// Answer a WebSocket upgrade with `503 Service Unavailable`
+async fn refuse(stream: GatewayStream) {
+    // The handshake always fails; the client gets the 503
+    let _ = tokio_tungstenite::accept_hdr_async(stream, service_unavailable).await;
+}
+
+// The signature is tungstenite's handshake `Callback`
+#[allow(clippy::result_large_err)]
+fn service_unavailable(_: &Request, _: Response) -> std::result::Result<Response, ErrorResponse> {
+    let mut response = ErrorResponse::new(Some("Too many connections".to_string()));
+    *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+    Err(response)
+}
+
+/// A TCP connection from [`GatewayServer::accept_pending`] whose
+/// handshakes have not run yet
+pub struct PendingConnection {
+    incoming: IncomingStream,
+    /// `None` if the connection is to be refused
+    permit: Option<OwnedSemaphorePermit>,
+    /// Connections open when this one was accepted
+    open: usize,
+    auth: Option<GatewayAuth>,
+    shutdown: watch::Receiver<ShutdownPhase>,
+}
+
+impl PendingConnection {
+    /// Address of the client
+    pub fn peer(&self) -> SocketAddr {
+        self.incoming.peer()
+    }
+
+    /// Complete the TLS (if configured) and WebSocket handshakes, within
+    /// [`HANDSHAKE_TIMEOUT`](super::HANDSHAKE_TIMEOUT)
+    ///
+    /// Refusals and authentication failures are reported as described for
+    /// [`GatewayServer::accept`].
+    pub async fn establish(self) -> Result<GatewayConnection> {
+        let peer = self.peer();
+        with_handshake_timeout(peer, async move {
+            let stream = self.incoming.upgrade().await?;
+            let Some(permit) = self.permit else {
+                refuse(stream).await;
+                return Err(Error::Connection(format!(
+                    "Refused WebSocket connection from {}: {} connections already open",
+                    peer, self.open
+                )));
+            };
+
+            let (ws, encoding, principal) = handshake(stream, peer, self.auth.as_ref()).await?;
+            debug!("Accepted WebSocket connection from {} ({:?})", peer, encoding);
+            let (outbox, outbox_rx) = mpsc::unbounded_channel();
+            Ok(GatewayConnection {
+                ws,
+                peer,
+                encoding,
+                principal,
+                outbox,
+                outbox_rx,
+                shutdown: self.shutdown,
+                _permit: permit,
+            })
+        })
+        .await
+    }
+}
+
+/// A connection accepted by [`GatewayServer`], counted against its limit
+/// until dropped
+pub struct GatewayConnection {
+    ws: WebSocketStream<GatewayStream>,
+    peer: SocketAddr,
+    encoding: ProtocolEncoding,
+    principal: Principal,
+    outbox: mpsc::UnboundedSender<GatewayFrame>,
+    outbox_rx: mpsc::UnboundedReceiver<GatewayFrame>,
+    shutdown: watch::Receiver<ShutdownPhase>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl GatewayConnection {
+    /// Address of the client
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Frame encoding negotiated in the handshake
+    pub fn encoding(&self) -> ProtocolEncoding {
+        self.encoding
+    }
+
+    /// User ID from authentication (the JWT `sub` claim), if the auth mode
+    /// provides one
+    pub fn user(&self) -> Option<&str> {
+        self.principal.user.as_deref()
+    }
+
+    /// Sender for frames to push to the client while it is served, e.g.
+    /// responses to requests handled in another task
+    pub fn sender(&self) -> mpsc::UnboundedSender<GatewayFrame> {
+        self.outbox.clone()
+    }
+
+    /// Serve the connection like [`keep_alive`](super::keep_alive), until
+    /// either side closes it or the server shuts down
+    pub async fn serve<F>(self, monitor: HeartbeatMonitor, on_frame: F) -> Result<()>
+    where
+        F: FnMut(GatewayFrame) -> Option<GatewayFrame>,
+    {
+        drive(self.ws, monitor, self.encoding, None, Some(self.shutdown), Some(self.outbox_rx), on_frame).await
+    }
+
+    /// Like [`serve`](Self::serve), with frames counted against `limiter`
+    /// for the client's IP and authenticated identity, as in
+    /// [`keep_alive_limited`](super::keep_alive_limited)
+    pub async fn serve_limited<F>(self, monitor: HeartbeatMonitor, limiter: GatewayRateLimiter, on_frame: F) -> Result<()>
+    where
+        F: FnMut(GatewayFrame) -> Option<GatewayFrame>,
+    {
+        let throttle = ClientThrottle {
+            limiter,
+            ip: Some(self.peer.ip()),
+            user: self.principal.key,
+        };
+        drive(self.ws, monitor, self.encoding, Some(throttle), Some(self.shutdown), Some(self.outbox_rx), on_frame).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+    use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+    async fn server(max_connections: usize) -> (Arc<GatewayServer>, String) {
+        let listener = WebSocketListener::bind("127.0.0.1:0".parse().unwrap(), None)
+            .await
+            .unwrap();
+        let server = Arc::new(GatewayServer::new(listener, max_connections));
+        let url = format!("ws://{}", server.local_addr().unwrap());
+
+        let acceptor = server.clone();
+        tokio::spawn(async move {
+            loop {
+                let pending = match acceptor.accept_pending().await {
+                    Ok(pending) => pending,
+                    Err(e) => {
+                        debug!("{}", e);
+                        continue;
+                    }
+                };
+                tokio::spawn(async move {
+                    match pending.establish().await {
+                        Ok(connection) => {
+                            let monitor = HeartbeatMonitor::new(Duration::from_secs(30), Duration::from_secs(10));
+                            let _ = connection.serve(monitor, |frame| frame.auto_reply()).await;
+                        }
+                        Err(e) => debug!("{}", e),
+                    }
+                });
+            }
+        });
+        (server, url)
+    }
+
+    #[tokio::test]
+    async fn test_stalled_client_does_not_block_accepts() {
+        let (server, url) = server(10).await;
+        // Connects but never sends the upgrade request
+        let _stalled = tokio::net::TcpStream::connect(server.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        let connected = tokio::time::timeout(Duration::from_secs(5), tokio_tungstenite::connect_async(&url)).await;
+        assert!(connected.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_connection_over_limit_is_rejected() {
+        let config = GatewayConfig::default();
+        assert_eq!(config.websocket_max_connections, 100);
+        let (server, url) = server(config.websocket_max_connections).await;
+
+        let mut clients = Vec::new();
+        for _ in 0..100 {
+            let (client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+            clients.push(client);
+        }
+        match tokio_tungstenite::connect_async(&url).await {
+            Err(WsError::Http(response)) => assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("101st connection was accepted"),
+        }
+        assert_eq!(server.active_connections(), 100);
+
+        // Closing one frees a slot
+        clients.pop().unwrap().close(None).await.unwrap();
+        for _ in 0..50 {
+            if server.active_connections() < 100 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(tokio_tungstenite::connect_async(&url).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_requires_auth() {
+        use crate::config::{AuthConfig, AuthMode};
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let listener = WebSocketListener::bind("127.0.0.1:0".parse().unwrap(), None)
+            .await
+            .unwrap();
+        let auth = GatewayAuth::new(AuthConfig {
+            mode: AuthMode::Token,
+            tokens: vec!["secret".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let server = Arc::new(GatewayServer::new(listener, 10).with_auth(auth));
+        let url = format!("ws://{}", server.local_addr().unwrap());
+
+        let acceptor = server.clone();
+        let refused = tokio::spawn(async move { acceptor.accept().await.map(|_| ()) });
+        match tokio_tungstenite::connect_async(&url).await {
+            Err(WsError::Http(response)) => assert_eq!(response.status(), StatusCode::UNAUTHORIZED),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("unauthenticated connection was accepted"),
+        }
+        assert!(matches!(refused.await.unwrap(), Err(Error::Unauthorized(_))));
+
+        let acceptor = server.clone();
+        let accepted = tokio::spawn(async move { acceptor.accept().await });
+        let mut request = url.into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Authorization", "Bearer secret".parse().unwrap());
+        let (_client, _) = tokio_tungstenite::connect_async(request).await.unwrap();
+        let connection = accepted.await.unwrap().unwrap();
+        // Token mode has no user identity
+        assert_eq!(connection.user(), None);
+    }
+
+    #[tokio::test]
+    async fn test_cbor_negotiated_in_handshake() {
+        use futures::SinkExt;
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let (_server, url) = server(10).await;
+        let mut request = url.into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("Content-Type", "application/cbor".parse().unwrap());
+        let (mut client, response) = tokio_tungstenite::connect_async(request).await.unwrap();
+        assert_eq!(response.headers()["Content-Type"], "application/cbor");
+
+        let ping = GatewayFrame::Ping { id: "p1".to_string() };
+        client
+            .send(Message::binary(ping.encode(ProtocolEncoding::Cbor).unwrap()))
+            .await
+            .unwrap();
+        match client.next().await.unwrap().unwrap() {
+            Message::Binary(bytes) => assert!(matches!(
+                GatewayFrame::decode(&bytes, ProtocolEncoding::Cbor).unwrap(),
+                GatewayFrame::Pong { id } if id == "p1"
+            )),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_notifies_then_drains_turns() {
+        let (server, url) = server(10).await;
+        let (mut client, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        while server.active_connections() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let turn = server.turns().start();
+        let stopping = {
+            let server = server.clone();
+            tokio::spawn(async move { server.shutdown(Duration::from_secs(30)).await })
+        };
+
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => assert!(matches!(
+                GatewayFrame::decode(text.as_bytes(), ProtocolEncoding::Json).unwrap(),
+                GatewayFrame::ShuttingDown { delay_secs: 30 }
+            )),
+            other => panic!("unexpected message: {:?}", other),
+        }
+        // New clients are refused while draining
+        assert!(tokio_tungstenite::connect_async(&url).await.is_err());
+
+        // Connections stay open until the running turn finishes
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!stopping.is_finished());
+        drop(turn);
+
+        match client.next().await.unwrap().unwrap() {
+            Message::Close(Some(frame)) => assert_eq!(frame.code, CloseCode::Away),
+            other => panic!("unexpected message: {:?}", other),
+        }
+        drop(client);
+        stopping.await.unwrap();
+        assert_eq!(server.turns().count(), 0);
+    }
+}
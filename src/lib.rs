@@ -44,12 +44,18 @@ pub mod core;
 // Agent logic and LLM interaction
 pub mod agent;
 
+// Messaging channel implementations (Slack)
+pub mod channels;
+
 // Tools - modular tool system (system_command, file ops, search, etc.)
 pub mod tools;
 
 // MCP - Model Context Protocol client for external tool servers
 pub mod mcp;
 
+// LLM provider implementations and combinators (failover, etc.)
+pub mod providers;
+
 // Skills - higher-level composable agent capabilities
 pub mod skills;
 
@@ -79,6 +85,9 @@ pub mod plugin_sdk;
 // Periodic scheduler for soul evolution and task processing
 pub mod scheduler;
 
+// Tracing subscriber setup and optional OpenTelemetry export
+pub mod telemetry;
+
 // Re-export commonly used items
 pub use error::{Error, Result};
 
@@ -86,7 +95,7 @@ pub use error::{Error, Result};
 pub use core::{
     Channel, ChannelCapabilities, ChannelMessage, ChannelPlugin,
     CodeExecutor, ExecutionRequest, ExecutionResult, Language,
-    LlmProvider, LlmResponse, StreamingChunk,
+    LlmProvider, LlmResponse, StreamingChunk, StreamingToolCall,
     MemoryBackend, SearchBackend, StorageBackend,
     Message, Role,
 };
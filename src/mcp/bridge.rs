@@ -1,16 +1,23 @@
 //! MCP-to-Tool bridge
 //!
 //! Adapts MCP server tools into OpenAgent's `Tool` trait so they can
-//! be registered in the `ToolRegistry` alongside built-in tools.
+//! be registered in the `ToolRegistry` alongside built-in tools, and turns
+//! MCP resource subscriptions into registry event sources.
 
 use async_trait::async_trait;
+use futures::StreamExt;
 use serde_json::Value;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 use super::client::McpClient;
-use super::protocol::McpTool;
+use super::protocol::{McpResourceContents, McpTool, McpToolResult};
 use crate::error::Result;
-use crate::tools::{Tool, ToolResult};
+use crate::tools::{Permission, Tool, ToolRegistry, ToolResult};
+
+/// Prefix for MCP tools whose names clash with already registered tools
+const COLLISION_PREFIX: &str = "mcp_";
 
 /// Bridge that wraps an MCP tool as an OpenAgent Tool
 pub struct McpToolBridge {
@@ -18,12 +25,41 @@ pub struct McpToolBridge {
     client: Arc<McpClient>,
     /// The MCP tool definition
     tool: McpTool,
+    /// Name exposed to the LLM (differs from `tool.name` after a collision)
+    name: String,
 }
 
 impl McpToolBridge {
     /// Create a new bridge for a specific MCP tool
     pub fn new(client: Arc<McpClient>, tool: McpTool) -> Self {
-        McpToolBridge { client, tool }
+        let name = tool.name.clone();
+        McpToolBridge { client, tool, name }
+    }
+
+    /// Register every tool of an MCP server in `registry`
+    ///
+    /// Tools whose names are already taken are registered as `mcp_<name>`;
+    /// if that is taken too the tool is skipped. Returns the number of
+    /// tools registered.
+    pub async fn discover_and_register(
+        client: &Arc<McpClient>,
+        registry: &mut ToolRegistry,
+    ) -> Result<u32> {
+        let mut registered = 0;
+        for mut bridge in Self::from_server(Arc::clone(client)).await? {
+            if registry.get(&bridge.name).is_some() {
+                let prefixed = format!("{}{}", COLLISION_PREFIX, bridge.name);
+                if registry.get(&prefixed).is_some() {
+                    warn!("MCP tool '{}' from {} clashes with an existing tool, skipping", bridge.name, client.name());
+                    continue;
+                }
+                bridge.name = prefixed;
+            }
+            registry.register(bridge);
+            registered += 1;
+        }
+        info!("Registered {} tools from MCP server {}", registered, client.name());
+        Ok(registered)
     }
 
     /// Create bridges for all tools from an MCP server
@@ -34,12 +70,67 @@ impl McpToolBridge {
             .map(|tool| McpToolBridge::new(Arc::clone(&client), tool))
             .collect())
     }
+
+    /// Forward updates of an MCP resource to `registry`'s event bus
+    ///
+    /// Subscribes to the resource at `uri` and, on every update
+    /// notification, reads it and publishes its text contents as a tool
+    /// result on the `uri` source, where agentic loops started with
+    /// `LoopConfig::with_event_source(uri)` pick it up. A failed read is
+    /// published as a failed result. The returned task ends when the
+    /// connection closes; abort it to stop forwarding.
+    pub async fn subscribe_as_event_source(
+        client: &Arc<McpClient>,
+        uri: &str,
+        registry: &ToolRegistry,
+    ) -> Result<JoinHandle<()>> {
+        let mut updates = Box::pin(client.subscribe(uri).await?);
+        let client = Arc::clone(client);
+        let bus = registry.events().clone();
+        let source = uri.to_string();
+
+        Ok(tokio::spawn(async move {
+            while updates.next().await.is_some() {
+                let result = match client.read_resource(&source).await {
+                    Ok(contents) => ToolResult::success(resource_text(&contents)),
+                    Err(e) => ToolResult::failure(format!("Failed to read MCP resource '{}': {}", source, e)),
+                };
+                let listeners = bus.publish(&source, result);
+                debug!("Update of MCP resource {} delivered to {} loops", source, listeners);
+            }
+            info!("Subscription to {} on MCP server {} ended", source, client.name());
+        }))
+    }
+}
+
+/// Combine the text blocks of an MCP result into a tool result
+fn to_tool_result(result: McpToolResult) -> ToolResult {
+    let text: String = result.content
+        .iter()
+        .filter_map(|c| c.text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if result.is_error {
+        ToolResult::failure(text)
+    } else {
+        ToolResult::success(text)
+    }
+}
+
+/// Combine the text items of a resource; binary items are skipped
+fn resource_text(contents: &[McpResourceContents]) -> String {
+    contents
+        .iter()
+        .filter_map(|c| c.text.as_deref())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[async_trait]
 impl Tool for McpToolBridge {
     fn name(&self) -> &str {
-        &self.tool.name
+        &self.name
     }
 
     fn description(&self) -> &str {
@@ -54,22 +145,13 @@ impl Tool for McpToolBridge {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::ExternalApi]
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         match self.client.call_tool(&self.tool.name, args).await {
-            Ok(result) => {
-                // Combine all text content blocks
-                let text: String = result.content
-                    .iter()
-                    .filter_map(|c| c.text.as_deref())
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                if result.is_error {
-                    Ok(ToolResult::failure(text))
-                } else {
-                    Ok(ToolResult::success(text))
-                }
-            }
+            Ok(result) => Ok(to_tool_result(result)),
             Err(e) => Ok(ToolResult::failure(format!(
                 "MCP tool '{}' failed: {}",
                 self.tool.name, e
@@ -77,3 +159,106 @@ impl Tool for McpToolBridge {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::McpTransport;
+    use crate::tools::ReadFileTool;
+    use serde_json::json;
+    use std::collections::VecDeque;
+    use tokio::sync::{mpsc, Mutex};
+
+    /// Replays canned messages: each request releases the next batch
+    struct ScriptedTransport {
+        script: Mutex<VecDeque<Vec<Value>>>,
+        outgoing: mpsc::UnboundedSender<String>,
+        incoming: Mutex<mpsc::UnboundedReceiver<String>>,
+    }
+
+    impl ScriptedTransport {
+        fn new(script: Vec<Vec<Value>>) -> Self {
+            let (outgoing, incoming) = mpsc::unbounded_channel();
+            ScriptedTransport {
+                script: Mutex::new(script.into()),
+                outgoing,
+                incoming: Mutex::new(incoming),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl McpTransport for ScriptedTransport {
+        async fn send(&self, _message: &str) -> Result<()> {
+            for message in self.script.lock().await.pop_front().unwrap_or_default() {
+                let _ = self.outgoing.send(message.to_string());
+            }
+            Ok(())
+        }
+
+        async fn receive(&self) -> Result<String> {
+            self.incoming.lock().await.recv().await
+                .ok_or_else(|| crate::Error::Connection("script finished".to_string()))
+        }
+    }
+
+    fn response(id: u64, result: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "result": result })
+    }
+
+    #[tokio::test]
+    async fn test_discover_and_register_prefixes_collisions() {
+        let tool = |name: &str| json!({ "name": name, "inputSchema": { "type": "object" } });
+        let transport = ScriptedTransport::new(vec![
+            vec![response(1, json!({}))],
+            vec![response(2, json!({ "tools": [tool("read_file"), tool("git_log")] }))],
+        ]);
+        let client = Arc::new(McpClient::with_transport("test", Box::new(transport)).await.unwrap());
+
+        let mut registry = ToolRegistry::new();
+        registry.register(ReadFileTool::new(".".into()));
+
+        let count = McpToolBridge::discover_and_register(&client, &mut registry).await.unwrap();
+        assert_eq!(count, 2);
+        let mut names = registry.names();
+        names.sort();
+        assert_eq!(names, vec!["git_log", "mcp_read_file", "read_file"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unanswered_request_times_out() {
+        // The server answers initialize, then goes quiet
+        let transport = ScriptedTransport::new(vec![vec![response(1, json!({}))]]);
+        let client = McpClient::with_transport_timeout("test", Box::new(transport), std::time::Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert!(matches!(client.list_tools().await, Err(crate::Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_subscription_events_reach_the_registry() {
+        const URI: &str = "ci://builds/latest";
+        let updated = |uri: &str| {
+            json!({ "jsonrpc": "2.0", "method": "notifications/resources/updated", "params": { "uri": uri } })
+        };
+        let contents = |text: &str| json!({ "contents": [{ "uri": URI, "mimeType": "text/plain", "text": text }] });
+        let transport = ScriptedTransport::new(vec![
+            vec![response(1, json!({}))],
+            vec![response(2, json!({})), updated("ci://builds/other"), updated(URI)],
+            vec![response(3, contents("build #41 passed")), updated(URI)],
+            vec![response(4, contents("build #42 failed"))],
+        ]);
+        let client = Arc::new(McpClient::with_transport("test", Box::new(transport)).await.unwrap());
+
+        let registry = ToolRegistry::new();
+        let mut events = registry.events().subscribe(URI);
+        let forwarder = McpToolBridge::subscribe_as_event_source(&client, URI, &registry)
+            .await
+            .unwrap();
+
+        assert_eq!(events.recv().await.unwrap().content.as_deref(), Some("build #41 passed"));
+        assert_eq!(events.recv().await.unwrap().content.as_deref(), Some("build #42 failed"));
+        forwarder.abort();
+    }
+}
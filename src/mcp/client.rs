@@ -1,33 +1,54 @@
 //! MCP client for connecting to MCP servers
 //!
-//! Supports stdio transport (spawning a subprocess).
+//! Supports stdio transport (spawning a subprocess) and WebSocket transport.
+//!
+//! A background task reads every message from the server: responses are
+//! matched to their request by id, and update notifications for subscribed
+//! resources are forwarded to the subscriber (see [`McpClient::subscribe`]).
+//!
+//! Every request fails with `Error::Timeout` if the server does not answer
+//! within the client's request timeout (`request_timeout_secs` in the
+//! server's config, [`DEFAULT_REQUEST_TIMEOUT`] otherwise).
 
+use futures::Stream;
 use serde_json::Value;
-use std::process::Stdio;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::{Child, Command};
-use tokio::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tracing::{debug, warn};
 
-use super::protocol::{McpRequest, McpResponse, McpTool, McpToolResult};
+use super::protocol::{McpRequest, McpResourceContents, McpResponse, McpTool, McpToolResult, RESOURCE_UPDATED};
+use super::transport::{McpTransport, StdioTransport, WebSocketTransport};
+use crate::config::{McpServerConfig, McpTransportConfig};
 use crate::error::{Error, Result};
 
+/// How long to wait for a response when no timeout is configured
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// MCP client for communicating with an MCP server
 pub struct McpClient {
-    /// Server process (for stdio transport)
-    #[allow(dead_code)]
-    child: Mutex<Child>,
-    /// Stdin writer
-    stdin: Mutex<tokio::process::ChildStdin>,
-    /// Stdout reader
-    stdout: Mutex<BufReader<tokio::process::ChildStdout>>,
+    /// Underlying message transport
+    transport: Arc<dyn McpTransport>,
+    /// Requests waiting for their response, by id
+    pending: Arc<Mutex<PendingRequests>>,
+    /// Resource subscriptions, by URI
+    subscribers: Arc<Mutex<Subscribers>>,
+    /// Task reading messages from the server
+    reader: JoinHandle<()>,
     /// Request ID counter
     next_id: AtomicU64,
+    /// How long each request waits for its response
+    request_timeout: Duration,
     /// Server name
     name: String,
 }
 
+type PendingRequests = HashMap<u64, oneshot::Sender<Result<McpResponse>>>;
+type Subscribers = Vec<(String, mpsc::UnboundedSender<McpResponse>)>;
+
 impl McpClient {
     /// Connect to an MCP server via stdio transport
     ///
@@ -38,27 +59,67 @@ impl McpClient {
 
     /// Connect to an MCP server via stdio with arguments
     pub async fn connect_stdio_with_args(command: &str, args: &[&str]) -> Result<Self> {
-        debug!("Connecting to MCP server: {} {:?}", command, args);
+        let transport = StdioTransport::spawn(command, args)?;
+        Self::with_transport(command, Box::new(transport)).await
+    }
 
-        let mut child = Command::new(command)
-            .args(args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| Error::Connection(format!("Failed to spawn MCP server '{}': {}", command, e)))?;
+    /// Connect to an MCP server via WebSocket (`ws://` or `wss://`)
+    pub async fn connect_websocket(url: &str) -> Result<Self> {
+        let transport = WebSocketTransport::connect(url).await?;
+        Self::with_transport(url, Box::new(transport)).await
+    }
 
-        let stdin = child.stdin.take()
-            .ok_or_else(|| Error::Connection("Failed to capture MCP server stdin".to_string()))?;
-        let stdout = child.stdout.take()
-            .ok_or_else(|| Error::Connection("Failed to capture MCP server stdout".to_string()))?;
+    /// Connect to a configured MCP server using its selected transport
+    ///
+    /// Connecting and each later request are limited to the server's
+    /// `request_timeout_secs`.
+    pub async fn connect(name: &str, config: &McpServerConfig) -> Result<Self> {
+        let timeout = Duration::from_secs(config.request_timeout_secs);
+        let transport: Box<dyn McpTransport> = match &config.transport {
+            McpTransportConfig::Stdio { command, args } => {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                Box::new(StdioTransport::spawn(command, &args)?)
+            }
+            McpTransportConfig::Websocket { url } => Box::new(
+                tokio::time::timeout(timeout, WebSocketTransport::connect(url))
+                    .await
+                    .map_err(|_| Error::Timeout(format!("Connecting to MCP server {} timed out", name)))??,
+            ),
+        };
+        Self::with_transport_timeout(name, transport, timeout).await
+    }
+
+    /// Build a client over an already-established transport and run the
+    /// MCP initialize handshake
+    pub async fn with_transport(name: impl Into<String>, transport: Box<dyn McpTransport>) -> Result<Self> {
+        Self::with_transport_timeout(name, transport, DEFAULT_REQUEST_TIMEOUT).await
+    }
 
+    /// Like [`with_transport`](Self::with_transport), waiting at most
+    /// `request_timeout` for each response
+    pub async fn with_transport_timeout(
+        name: impl Into<String>,
+        transport: Box<dyn McpTransport>,
+        request_timeout: Duration,
+    ) -> Result<Self> {
+        let name = name.into();
+        let transport: Arc<dyn McpTransport> = Arc::from(transport);
+        let pending = Arc::new(Mutex::new(PendingRequests::new()));
+        let subscribers = Arc::new(Mutex::new(Subscribers::new()));
+        let reader = tokio::spawn(read_messages(
+            name.clone(),
+            Arc::clone(&transport),
+            Arc::clone(&pending),
+            Arc::clone(&subscribers),
+        ));
         let client = McpClient {
-            child: Mutex::new(child),
-            stdin: Mutex::new(stdin),
-            stdout: Mutex::new(BufReader::new(stdout)),
+            transport,
+            pending,
+            subscribers,
+            reader,
             next_id: AtomicU64::new(1),
-            name: command.to_string(),
+            request_timeout,
+            name,
         };
 
         // Initialize the connection
@@ -74,29 +135,24 @@ impl McpClient {
 
         debug!("MCP request -> {}: {}", self.name, json);
 
-        // Write request
-        {
-            let mut stdin = self.stdin.lock().await;
-            stdin.write_all(json.as_bytes()).await
-                .map_err(|e| Error::Connection(format!("Failed to write to MCP server: {}", e)))?;
-            stdin.write_all(b"\n").await
-                .map_err(|e| Error::Connection(format!("Failed to write newline to MCP server: {}", e)))?;
-            stdin.flush().await
-                .map_err(|e| Error::Connection(format!("Failed to flush MCP server stdin: {}", e)))?;
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.pending).insert(request.id, sender);
+        if let Err(e) = self.transport.send(&json).await {
+            lock(&self.pending).remove(&request.id);
+            return Err(e);
         }
-
-        // Read response
-        let mut line = String::new();
-        {
-            let mut stdout = self.stdout.lock().await;
-            stdout.read_line(&mut line).await
-                .map_err(|e| Error::Connection(format!("Failed to read from MCP server: {}", e)))?;
-        }
-
-        debug!("MCP response <- {}: {}", self.name, line.trim());
-
-        let response: McpResponse = serde_json::from_str(line.trim())
-            .map_err(|e| Error::InvalidInput(format!("Failed to parse MCP response: {} (raw: {})", e, line.trim())))?;
+        let response = match tokio::time::timeout(self.request_timeout, receiver).await {
+            Ok(response) => {
+                response.map_err(|_| Error::Connection(format!("MCP server {} disconnected", self.name)))??
+            }
+            Err(_) => {
+                lock(&self.pending).remove(&request.id);
+                return Err(Error::Timeout(format!(
+                    "MCP server {} did not answer {} within {:?}",
+                    self.name, request.method, self.request_timeout
+                )));
+            }
+        };
 
         if let Some(ref err) = response.error {
             return Err(Error::Provider(format!(
@@ -153,6 +209,41 @@ impl McpClient {
         Ok(tool_result)
     }
 
+    /// Read a resource from the MCP server
+    pub async fn read_resource(&self, uri: &str) -> Result<Vec<McpResourceContents>> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let response = self.send_request(McpRequest::read_resource(id, uri)).await?;
+
+        let result = response.result.unwrap_or_default();
+        serde_json::from_value(result.get("contents").cloned().unwrap_or_default())
+            .map_err(|e| Error::InvalidInput(format!("Failed to parse MCP resource {}: {}", uri, e)))
+    }
+
+    /// Subscribe to updates of a resource
+    ///
+    /// Sends `resources/subscribe`; afterwards every
+    /// `notifications/resources/updated` for `uri` is yielded as an
+    /// [`McpResponse`] without an id, with the notification's params as its
+    /// result. The notification only names the resource, so fetch the new
+    /// contents with [`read_resource`](Self::read_resource). The stream ends
+    /// when the connection closes.
+    pub async fn subscribe(&self, uri: &str) -> Result<impl Stream<Item = McpResponse>> {
+        // Register first so updates sent right after the reply aren't lost
+        let (sender, receiver) = mpsc::unbounded_channel();
+        lock(&self.subscribers).push((uri.to_string(), sender.clone()));
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        if let Err(e) = self.send_request(McpRequest::subscribe_resource(id, uri)).await {
+            lock(&self.subscribers).retain(|(_, s)| !s.same_channel(&sender));
+            return Err(e);
+        }
+
+        debug!("Subscribed to {} on MCP server {}", uri, self.name);
+        Ok(futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|event| (event, receiver))
+        }))
+    }
+
     /// Get the server name
     pub fn name(&self) -> &str {
         &self.name
@@ -161,7 +252,80 @@ impl McpClient {
 
 impl Drop for McpClient {
     fn drop(&mut self) {
-        // The child process will be killed when dropped
+        // Stdio servers are killed and sockets closed when the transport drops
         debug!("Dropping MCP client for {}", self.name);
+        self.reader.abort();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Route messages from the server until the transport fails
+///
+/// Responses go to the request waiting for their id; resource update
+/// notifications go to the subscribers of that resource. When the
+/// connection is lost, pending requests fail and subscription streams end.
+async fn read_messages(
+    name: String,
+    transport: Arc<dyn McpTransport>,
+    pending: Arc<Mutex<PendingRequests>>,
+    subscribers: Arc<Mutex<Subscribers>>,
+) {
+    let error = loop {
+        let raw = match transport.receive().await {
+            Ok(raw) => raw,
+            Err(e) => break e,
+        };
+        debug!("MCP message <- {}: {}", name, raw);
+
+        let message: Value = match serde_json::from_str(&raw) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("Ignoring unparseable message from MCP server {}: {}", name, e);
+                continue;
+            }
+        };
+
+        let notification = message.get("id").is_none_or(Value::is_null);
+        if let Some(method) = message.get("method").and_then(Value::as_str).filter(|_| notification) {
+            let params = message.get("params").cloned().unwrap_or_default();
+            let uri = params.get("uri").and_then(Value::as_str);
+            let Some(uri) = uri.filter(|_| method == RESOURCE_UPDATED) else {
+                debug!("Ignoring {} notification from MCP server {}", method, name);
+                continue;
+            };
+            let event = McpResponse {
+                jsonrpc: "2.0".to_string(),
+                id: None,
+                result: Some(params.clone()),
+                error: None,
+            };
+            lock(&subscribers).retain(|(subscribed, sender)| {
+                subscribed != uri || sender.send(event.clone()).is_ok()
+            });
+            continue;
+        }
+
+        let response: McpResponse = match serde_json::from_value(message) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to parse MCP response from {}: {} (raw: {})", name, e, raw);
+                continue;
+            }
+        };
+        match response.id.and_then(|id| lock(&pending).remove(&id)) {
+            Some(sender) => {
+                let _ = sender.send(Ok(response));
+            }
+            None => warn!("Ignoring MCP response from {} with unknown id {:?}", name, response.id),
+        }
+    };
+
+    warn!("MCP server {} disconnected: {}", name, error);
+    for (_, sender) in lock(&pending).drain() {
+        let _ = sender.send(Err(Error::Connection(error.to_string())));
     }
+    lock(&subscribers).clear();
 }
@@ -6,6 +6,7 @@
 //! ## Architecture
 //!
 //! - **client**: MCP client for connecting to MCP servers
+//! - **transport**: Stdio and WebSocket transports behind `McpTransport`
 //! - **protocol**: Wire protocol types (JSON-RPC based)
 //! - **bridge**: Adapts MCP tools into OpenAgent's `Tool` trait
 //!
@@ -18,6 +19,9 @@
 //! // Connect to a local MCP server
 //! let client = McpClient::connect_stdio("my-mcp-server").await?;
 //!
+//! // ...or to a remote one over WebSocket
+//! let remote = McpClient::connect_websocket("ws://localhost:8080/mcp").await?;
+//!
 //! // List available tools
 //! let tools = client.list_tools().await?;
 //!
@@ -30,7 +34,9 @@
 mod client;
 mod protocol;
 mod bridge;
+mod transport;
 
-pub use client::McpClient;
-pub use protocol::{McpRequest, McpResponse, McpTool, McpToolInput};
+pub use client::{McpClient, DEFAULT_REQUEST_TIMEOUT};
+pub use transport::{McpTransport, StdioTransport, WebSocketTransport};
+pub use protocol::{McpRequest, McpResourceContents, McpResponse, McpTool, McpToolInput};
 pub use bridge::McpToolBridge;
@@ -5,6 +5,9 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Notification a server sends when a subscribed resource changes
+pub const RESOURCE_UPDATED: &str = "notifications/resources/updated";
+
 /// JSON-RPC request to an MCP server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpRequest {
@@ -50,6 +53,16 @@ impl McpRequest {
             "arguments": arguments
         })))
     }
+
+    /// Create a resources/subscribe request for update notifications
+    pub fn subscribe_resource(id: u64, uri: impl Into<String>) -> Self {
+        Self::new(id, "resources/subscribe", Some(serde_json::json!({ "uri": uri.into() })))
+    }
+
+    /// Create a resources/read request
+    pub fn read_resource(id: u64, uri: impl Into<String>) -> Self {
+        Self::new(id, "resources/read", Some(serde_json::json!({ "uri": uri.into() })))
+    }
 }
 
 /// JSON-RPC response from an MCP server
@@ -109,6 +122,18 @@ pub struct McpContent {
     pub mime_type: Option<String>,
 }
 
+/// One item of a resources/read response: text or base64 `blob`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType", default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
 /// Result of a tools/call response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpToolResult {
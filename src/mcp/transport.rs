@@ -0,0 +1,149 @@
+//! MCP transports
+//!
+//! A transport moves newline-free JSON-RPC messages between the client and
+//! an MCP server. `McpClient` is transport-agnostic; stdio and WebSocket
+//! implementations are provided here.
+
+use async_trait::async_trait;
+use futures::{SinkExt, StreamExt};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+
+/// Bidirectional message channel to an MCP server
+#[async_trait]
+pub trait McpTransport: Send + Sync {
+    /// Send a single serialized JSON-RPC message
+    async fn send(&self, message: &str) -> Result<()>;
+
+    /// Receive the next serialized JSON-RPC message
+    async fn receive(&self) -> Result<String>;
+}
+
+/// Stdio transport: line-delimited JSON over a subprocess's stdin/stdout
+pub struct StdioTransport {
+    /// Server process, killed when the transport is dropped
+    #[allow(dead_code)]
+    child: Mutex<Child>,
+    /// Stdin writer
+    stdin: Mutex<ChildStdin>,
+    /// Stdout reader
+    stdout: Mutex<BufReader<ChildStdout>>,
+}
+
+impl StdioTransport {
+    /// Spawn `command` with `args` and attach to its stdio
+    pub fn spawn(command: &str, args: &[&str]) -> Result<Self> {
+        debug!("Spawning MCP server: {} {:?}", command, args);
+
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::Connection(format!("Failed to spawn MCP server '{}': {}", command, e)))?;
+
+        let stdin = child.stdin.take()
+            .ok_or_else(|| Error::Connection("Failed to capture MCP server stdin".to_string()))?;
+        let stdout = child.stdout.take()
+            .ok_or_else(|| Error::Connection("Failed to capture MCP server stdout".to_string()))?;
+
+        Ok(StdioTransport {
+            child: Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            stdout: Mutex::new(BufReader::new(stdout)),
+        })
+    }
+}
+
+#[async_trait]
+impl McpTransport for StdioTransport {
+    async fn send(&self, message: &str) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(message.as_bytes()).await
+            .map_err(|e| Error::Connection(format!("Failed to write to MCP server: {}", e)))?;
+        stdin.write_all(b"\n").await
+            .map_err(|e| Error::Connection(format!("Failed to write newline to MCP server: {}", e)))?;
+        stdin.flush().await
+            .map_err(|e| Error::Connection(format!("Failed to flush MCP server stdin: {}", e)))?;
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<String> {
+        let mut line = String::new();
+        let mut stdout = self.stdout.lock().await;
+        let read = stdout.read_line(&mut line).await
+            .map_err(|e| Error::Connection(format!("Failed to read from MCP server: {}", e)))?;
+        if read == 0 {
+            return Err(Error::Connection("MCP server closed stdout".to_string()));
+        }
+        Ok(line.trim().to_string())
+    }
+}
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// WebSocket transport: one JSON-RPC message per text frame
+pub struct WebSocketTransport {
+    /// Outgoing half of the socket
+    sink: Mutex<futures::stream::SplitSink<WsStream, WsMessage>>,
+    /// Incoming half of the socket
+    stream: Mutex<futures::stream::SplitStream<WsStream>>,
+}
+
+impl WebSocketTransport {
+    /// Open a WebSocket connection to `url` (`ws://` or `wss://`)
+    pub async fn connect(url: &str) -> Result<Self> {
+        debug!("Connecting to MCP server over WebSocket: {}", url);
+
+        let (socket, _) = tokio_tungstenite::connect_async(url).await
+            .map_err(|e| Error::Connection(format!("Failed to connect to MCP server '{}': {}", url, e)))?;
+        let (sink, stream) = socket.split();
+
+        Ok(WebSocketTransport {
+            sink: Mutex::new(sink),
+            stream: Mutex::new(stream),
+        })
+    }
+}
+
+#[async_trait]
+impl McpTransport for WebSocketTransport {
+    async fn send(&self, message: &str) -> Result<()> {
+        self.sink.lock().await
+            .send(WsMessage::text(message))
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to send to MCP server: {}", e)))
+    }
+
+    async fn receive(&self) -> Result<String> {
+        let mut stream = self.stream.lock().await;
+        loop {
+            let frame = stream.next().await
+                .ok_or_else(|| Error::Connection("MCP WebSocket closed".to_string()))?
+                .map_err(|e| Error::Connection(format!("Failed to read from MCP server: {}", e)))?;
+
+            match frame {
+                WsMessage::Text(text) => return Ok(text.to_string()),
+                WsMessage::Binary(bytes) => {
+                    return String::from_utf8(bytes.to_vec())
+                        .map_err(|e| Error::InvalidInput(format!("MCP frame is not UTF-8: {}", e)));
+                }
+                WsMessage::Close(_) => {
+                    return Err(Error::Connection("MCP WebSocket closed".to_string()));
+                }
+                // Ping/pong are answered by tungstenite; keep reading
+                _ => continue,
+            }
+        }
+    }
+}
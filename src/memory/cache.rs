@@ -1,11 +1,14 @@
 //! In-process caching for embeddings and search results
 //!
-//! Uses moka async cache (Send + Sync, TTL-based eviction).
+//! Uses moka async cache (Send + Sync) with a configurable eviction policy.
 //! No external services required.
 
+pub use crate::config::CachePolicy;
 use crate::database::Memory;
-use moka::future::Cache;
+use moka::future::{Cache, CacheBuilder};
+use moka::policy::EvictionPolicy;
 use std::hash::{DefaultHasher, Hash, Hasher};
+use tracing::warn;
 use std::time::Duration;
 
 /// Cache key helper: hash a string to u64
@@ -20,22 +23,47 @@ fn hash_key(s: &str) -> u64 {
 pub struct MemoryCache {
     /// Embedding cache: hash(text) -> Vec<f32>
     embeddings: Cache<u64, Vec<f32>>,
-    /// Search result cache: hash(user_id + query) -> Vec<Memory>
-    search_results: Cache<u64, Vec<Memory>>,
+    /// Search result cache: (user_id, hash(query)) -> Vec<Memory>
+    search_results: Cache<(String, u64), Vec<Memory>>,
+    /// Auto-tag predictions: hash(content) -> tags
+    tag_predictions: Cache<u64, Vec<String>>,
 }
 
+/// Search results go stale as memories change, so they always expire
+/// regardless of the selected policy.
+const SEARCH_RESULT_TTL: Duration = Duration::from_secs(5 * 60);
+
 impl MemoryCache {
-    /// Create a new cache with default settings
-    pub fn new() -> Self {
+    /// Create a new cache using the given eviction policy.
+    ///
+    /// The search-result and tag caches hold half as many entries as the
+    /// embedding cache; search results always keep a 5 minute TTL on top of
+    /// the policy.
+    pub fn new(policy: CachePolicy) -> Self {
+        let search_capacity = (policy.max_capacity() / 2).max(1);
         MemoryCache {
-            embeddings: Cache::builder()
-                .max_capacity(1000)
-                .time_to_live(Duration::from_secs(30 * 60)) // 30 min TTL
-                .build(),
-            search_results: Cache::builder()
-                .max_capacity(500)
-                .time_to_live(Duration::from_secs(5 * 60)) // 5 min TTL
+            embeddings: Self::builder(policy, policy.max_capacity()).build(),
+            search_results: Self::builder(policy, search_capacity)
+                .time_to_live(SEARCH_RESULT_TTL)
+                .support_invalidation_closures()
                 .build(),
+            tag_predictions: Self::builder(policy, search_capacity).build(),
+        }
+    }
+
+    /// Configure a moka builder for `policy` with the given capacity
+    fn builder<K, V>(policy: CachePolicy, capacity: u64) -> CacheBuilder<K, V, Cache<K, V>>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        let builder = Cache::builder().max_capacity(capacity);
+        match policy {
+            CachePolicy::Lru { .. } => builder.eviction_policy(EvictionPolicy::lru()),
+            CachePolicy::Lfu { .. } => builder.eviction_policy(EvictionPolicy::tiny_lfu()),
+            CachePolicy::Ttl { entry_ttl_secs, .. } => {
+                builder.time_to_live(Duration::from_secs(entry_ttl_secs))
+            }
         }
     }
 
@@ -51,27 +79,43 @@ impl MemoryCache {
 
     /// Get cached search results
     pub async fn get_search_results(&self, user_id: &str, query: &str) -> Option<Vec<Memory>> {
-        let key = format!("{}:{}", user_id, query);
-        self.search_results.get(&hash_key(&key)).await
+        self.search_results.get(&(user_id.to_string(), hash_key(query))).await
     }
 
     /// Store search results in cache
     pub async fn put_search_results(&self, user_id: &str, query: &str, results: Vec<Memory>) {
-        let key = format!("{}:{}", user_id, query);
-        self.search_results.insert(hash_key(&key), results).await;
+        self.search_results
+            .insert((user_id.to_string(), hash_key(query)), results)
+            .await;
     }
 
-    /// Invalidate all search caches for a user (e.g., after saving new memory)
-    pub async fn invalidate_user_search(&self, _user_id: &str) {
-        // Moka doesn't support prefix-based invalidation, so we invalidate all search results.
-        // With a 5-minute TTL this is acceptable — stale results expire quickly.
-        self.search_results.invalidate_all();
+    /// Get cached auto-tags for memory content
+    pub async fn get_tags(&self, content: &str) -> Option<Vec<String>> {
+        self.tag_predictions.get(&hash_key(content)).await
+    }
+
+    /// Store auto-tags predicted for memory content
+    pub async fn put_tags(&self, content: &str, tags: Vec<String>) {
+        self.tag_predictions.insert(hash_key(content), tags).await;
+    }
+
+    /// Drop every cached search result of a user (e.g., after saving or
+    /// deleting one of their memories). Other users' results are kept.
+    pub async fn invalidate_user(&self, user_id: &str) {
+        let user_id = user_id.to_string();
+        if let Err(e) = self
+            .search_results
+            .invalidate_entries_if(move |(user, _), _| *user == user_id)
+        {
+            warn!("Failed to invalidate user search cache, clearing it: {}", e);
+            self.search_results.invalidate_all();
+        }
     }
 }
 
 impl Default for MemoryCache {
     fn default() -> Self {
-        Self::new()
+        Self::new(CachePolicy::default())
     }
 }
 
@@ -81,7 +125,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_embedding_cache() {
-        let cache = MemoryCache::new();
+        let cache = MemoryCache::default();
 
         assert!(cache.get_embedding("hello").await.is_none());
 
@@ -91,4 +135,37 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap().len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_invalidate_user() {
+        let cache = MemoryCache::default();
+        let memory = Memory::new("alice", "likes tea");
+        cache.put_search_results("alice", "drinks", vec![memory.clone()]).await;
+        cache.put_search_results("alice", "hobbies", vec![memory]).await;
+        cache.put_search_results("bob", "drinks", vec![Memory::new("bob", "likes coffee")]).await;
+
+        cache.invalidate_user("alice").await;
+        assert!(cache.get_search_results("alice", "drinks").await.is_none());
+        assert!(cache.get_search_results("alice", "hobbies").await.is_none());
+        assert!(cache.get_search_results("bob", "drinks").await.is_some());
+
+        // Results cached after the invalidation are served again
+        cache.put_search_results("alice", "drinks", Vec::new()).await;
+        assert_eq!(cache.get_search_results("alice", "drinks").await.map(|r| r.len()), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_policies_store_and_fetch() {
+        for policy in [
+            CachePolicy::Lru { max_capacity: 10 },
+            CachePolicy::Lfu { max_capacity: 10 },
+            CachePolicy::Ttl { max_capacity: 10, entry_ttl_secs: 60 },
+        ] {
+            let cache = MemoryCache::new(policy);
+            cache.put_embedding("hello", vec![1.0]).await;
+            assert_eq!(cache.get_embedding("hello").await, Some(vec![1.0]));
+            cache.put_tags("hello", vec!["fact".into()]).await;
+            assert_eq!(cache.get_tags("hello").await, Some(vec!["fact".to_string()]));
+        }
+    }
 }
@@ -0,0 +1,253 @@
+//! Memory clustering
+//!
+//! [`MemoryRetriever::cluster_memories`] groups a user's memories into
+//! themes with k-means over their embeddings. Embeddings are compared by
+//! cosine similarity, as in retrieval, so points and centroids are kept at
+//! unit length (spherical k-means). Initial centroids are picked
+//! farthest-first, which makes results repeatable for the same store.
+//!
+//! The k-means loop is written out here rather than taken from
+//! `linfa-clustering`: that crate is not available to the build (only
+//! `linfa-linalg` is), and the spherical variant needs just a few dozen
+//! lines over plain `Vec<f32>` embeddings.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tracing::warn;
+
+use super::retrieval::MemoryRetriever;
+use crate::agent::{GenerationOptions, Message};
+use crate::database::Memory;
+use crate::error::{Error, Result};
+
+/// Upper bound on k-means iterations
+const MAX_ITERATIONS: usize = 100;
+
+/// Members shown to the LLM when describing a cluster
+const DESCRIPTION_SAMPLE: usize = 12;
+
+/// A group of related memories
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryCluster {
+    /// One-line theme of the cluster
+    pub centroid_description: String,
+    /// Memories in the cluster, closest to the centroid first
+    pub members: Vec<Memory>,
+    /// Mean cosine similarity of the members to the centroid (0.0 - 1.0)
+    pub cohesion: f32,
+}
+
+impl MemoryRetriever {
+    /// Group `user_id`'s memories into at most `n_clusters` themes
+    ///
+    /// Memories without an embedding, or with one of a different dimension
+    /// than the rest (e.g. mid re-embedding), are left out. Empty clusters
+    /// are dropped; the rest are returned largest first. Descriptions come
+    /// from the LLM set with [`with_cluster_descriptions`](Self::with_cluster_descriptions),
+    /// or from the members' most common tags without one.
+    pub async fn cluster_memories(&self, user_id: &str, n_clusters: usize) -> Result<Vec<MemoryCluster>> {
+        if n_clusters == 0 {
+            return Err(Error::InvalidInput("n_clusters must be at least 1".to_string()));
+        }
+
+        let mut rows = self.store().get_with_embeddings(user_id).await?;
+        let Some(dimensions) = rows.first().map(|(_, embedding)| embedding.len()) else {
+            return Ok(Vec::new());
+        };
+        let before = rows.len();
+        rows.retain(|(_, embedding)| embedding.len() == dimensions);
+        if rows.len() < before {
+            warn!("Skipping {} memories with mismatched embedding dimensions", before - rows.len());
+        }
+
+        let points: Vec<Vec<f32>> = rows.iter().map(|(_, embedding)| normalize(embedding)).collect();
+        let result = kmeans(&points, n_clusters.min(points.len()));
+
+        let mut groups: Vec<Vec<(Memory, f32)>> = vec![Vec::new(); result.centroids.len()];
+        for (((memory, _), point), cluster) in rows.into_iter().zip(&points).zip(result.assignments) {
+            let similarity = dot(point, &result.centroids[cluster]);
+            groups[cluster].push((memory, similarity));
+        }
+        groups.retain(|group| !group.is_empty());
+        groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+
+        let mut clusters = Vec::with_capacity(groups.len());
+        for mut group in groups {
+            group.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let cohesion = group.iter().map(|(_, similarity)| similarity).sum::<f32>() / group.len() as f32;
+            let members: Vec<Memory> = group.into_iter().map(|(memory, _)| memory).collect();
+            clusters.push(MemoryCluster {
+                centroid_description: self.describe_cluster(&members).await,
+                members,
+                cohesion,
+            });
+        }
+        Ok(clusters)
+    }
+
+    /// Ask the LLM for the theme of `members`, falling back to their tags
+    async fn describe_cluster(&self, members: &[Memory]) -> String {
+        let Some(llm_client) = self.cluster_llm() else {
+            return fallback_description(members);
+        };
+
+        let notes: Vec<String> = members
+            .iter()
+            .take(DESCRIPTION_SAMPLE)
+            .map(|m| format!("- {}", m.summary.as_deref().unwrap_or(&m.content)))
+            .collect();
+        let prompt = format!(
+            "These notes were grouped together by topic. Describe their common theme \
+            in one short phrase (at most 8 words). Reply with the phrase only.\n\n{}",
+            notes.join("\n")
+        );
+        match llm_client
+            .chat(vec![Message::user(prompt)], GenerationOptions::precise())
+            .await
+        {
+            Ok(response) => {
+                let reply = response
+                    .choices
+                    .first()
+                    .map(|c| c.message.content.trim().trim_matches('"').to_string())
+                    .unwrap_or_default();
+                if reply.is_empty() {
+                    fallback_description(members)
+                } else {
+                    reply
+                }
+            }
+            Err(e) => {
+                warn!("Cluster description failed: {}", e);
+                fallback_description(members)
+            }
+        }
+    }
+}
+
+/// The three most common tags, or the first member's summary
+fn fallback_description(members: &[Memory]) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for tag in members.iter().flat_map(|m| &m.tags) {
+        *counts.entry(tag.as_str()).or_default() += 1;
+    }
+    let mut tags: Vec<(&str, usize)> = counts.into_iter().collect();
+    tags.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    if !tags.is_empty() {
+        return tags.iter().take(3).map(|(tag, _)| *tag).collect::<Vec<_>>().join(", ");
+    }
+
+    let first = members
+        .first()
+        .map(|m| m.summary.as_deref().unwrap_or(&m.content))
+        .unwrap_or_default();
+    match first.char_indices().nth(60) {
+        Some((end, _)) => format!("{}...", &first[..end]),
+        None => first.to_string(),
+    }
+}
+
+/// Cluster assignments and unit-length centroids
+struct KMeans {
+    assignments: Vec<usize>,
+    centroids: Vec<Vec<f32>>,
+}
+
+/// Spherical k-means over unit vectors; `k` must not exceed `points.len()`
+fn kmeans(points: &[Vec<f32>], k: usize) -> KMeans {
+    // Farthest-first: start with the first point, then repeatedly add the
+    // point least similar to every chosen centroid
+    let mut centroids = vec![points[0].clone()];
+    let mut best_similarity: Vec<f32> = points.iter().map(|p| dot(p, &centroids[0])).collect();
+    while centroids.len() < k {
+        let next = (0..points.len())
+            .min_by(|&a, &b| best_similarity[a].total_cmp(&best_similarity[b]))
+            .unwrap_or(0);
+        centroids.push(points[next].clone());
+        for (similarity, point) in best_similarity.iter_mut().zip(points) {
+            *similarity = similarity.max(dot(point, &points[next]));
+        }
+    }
+
+    let mut assignments = vec![usize::MAX; points.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let mut changed = false;
+        for (assignment, point) in assignments.iter_mut().zip(points) {
+            let nearest = (0..centroids.len())
+                .max_by(|&a, &b| dot(point, &centroids[a]).total_cmp(&dot(point, &centroids[b])))
+                .unwrap_or(0);
+            if *assignment != nearest {
+                *assignment = nearest;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+
+        let dimensions = points[0].len();
+        let mut sums = vec![vec![0.0f32; dimensions]; centroids.len()];
+        for (point, &cluster) in points.iter().zip(&assignments) {
+            for (sum, value) in sums[cluster].iter_mut().zip(point) {
+                *sum += value;
+            }
+        }
+        for (centroid, sum) in centroids.iter_mut().zip(sums) {
+            // An empty cluster keeps its previous centroid
+            if sum.iter().any(|v| *v != 0.0) {
+                *centroid = normalize(&sum);
+            }
+        }
+    }
+
+    KMeans { assignments, centroids }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Scale to unit length; zero vectors are returned unchanged
+fn normalize(v: &[f32]) -> Vec<f32> {
+    let norm = dot(v, v).sqrt();
+    if norm == 0.0 {
+        v.to_vec()
+    } else {
+        v.iter().map(|x| x / norm).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kmeans_separates_directions() {
+        let points: Vec<Vec<f32>> = [[1.0, 0.1], [0.9, 0.0], [0.0, 1.0], [0.1, 0.9], [1.0, 0.0]]
+            .iter()
+            .map(|p| normalize(p))
+            .collect();
+        let result = kmeans(&points, 2);
+        let a = result.assignments[0];
+        assert_eq!(result.assignments, vec![a, a, 1 - a, 1 - a, a]);
+        assert!(dot(&result.centroids[a], &normalize(&[1.0, 0.0])) > 0.99);
+
+        // One cluster holds everything
+        assert_eq!(kmeans(&points, 1).assignments, vec![0; 5]);
+    }
+
+    #[test]
+    fn test_fallback_description() {
+        let tagged = |tags: &[&str]| {
+            let mut memory = Memory::new("u1", "note");
+            memory.tags = tags.iter().map(|t| t.to_string()).collect();
+            memory
+        };
+        let members = vec![tagged(&["rust", "work"]), tagged(&["rust"]), tagged(&["music"])];
+        assert_eq!(fallback_description(&members), "rust, music, work");
+
+        let untagged = vec![Memory::new("u1", "Prefers tea over coffee")];
+        assert_eq!(fallback_description(&untagged), "Prefers tea over coffee");
+    }
+}
@@ -0,0 +1,99 @@
+//! Memory deduplication
+//!
+//! Before a memory is stored, its embedding is compared against the user's
+//! existing memories. A near-duplicate is folded into the existing entry
+//! with [`Memory::merge`] instead of being stored a second time.
+
+use crate::database::{Memory, MergeStrategy};
+use crate::error::Result;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::retrieval::MemoryRetriever;
+
+/// Cosine similarity at which two memories count as duplicates
+pub const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.95;
+
+/// What [`MemoryDeduplicationService::save`] did with a memory
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedupOutcome {
+    /// Stored as a new memory
+    Saved(Uuid),
+    /// Merged into an existing memory
+    Merged { id: Uuid, similarity: f32 },
+}
+
+/// Saves memories, merging near-duplicates into existing entries
+#[derive(Clone)]
+pub struct MemoryDeduplicationService {
+    retriever: MemoryRetriever,
+    threshold: f32,
+    strategy: MergeStrategy,
+}
+
+impl MemoryDeduplicationService {
+    /// Create a service with the default threshold and merge strategy
+    pub fn new(retriever: MemoryRetriever) -> Self {
+        MemoryDeduplicationService {
+            retriever,
+            threshold: DEFAULT_SIMILARITY_THRESHOLD,
+            strategy: MergeStrategy::default(),
+        }
+    }
+
+    /// Set the similarity threshold (0.0 - 1.0)
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how duplicates are merged
+    pub fn with_strategy(mut self, strategy: MergeStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Save `memory`, merging it into the closest existing memory if one
+    /// is similar enough
+    ///
+    /// If the duplicate check itself fails, the memory is saved as new.
+    pub async fn save(&self, memory: &Memory) -> Result<DedupOutcome> {
+        if let Some((existing, similarity)) = self.find_duplicate(memory).await {
+            let merged = existing.merge(memory, self.strategy);
+            self.retriever.save_memory(&merged).await?;
+            return Ok(DedupOutcome::Merged {
+                id: merged.id,
+                similarity,
+            });
+        }
+
+        self.retriever.save_memory(memory).await?;
+        Ok(DedupOutcome::Saved(memory.id))
+    }
+
+    /// Closest existing memory above the threshold, if any
+    ///
+    /// Failures of the check are logged and reported as no duplicate.
+    pub async fn find_duplicate(&self, memory: &Memory) -> Option<(Memory, f32)> {
+        let embedding = match self.retriever.get_or_create_embedding(&memory.content).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                warn!("Embedding generation for dedup check failed: {}", e);
+                return None;
+            }
+        };
+
+        match self
+            .retriever
+            .store()
+            .find_similar_by_embedding(&memory.user_id, embedding, self.threshold, 1)
+            .await
+        {
+            Ok(similar) => similar.into_iter().next(),
+            Err(e) => {
+                warn!("Duplicate check failed: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -4,9 +4,14 @@
 //! Supports 100+ languages including Japanese.
 //! Model auto-downloads on first use.
 
+use crate::database::MemoryStore;
 use crate::error::{Error, Result};
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use std::sync::Arc;
+use tracing::info;
+
+/// Memories embedded per batch by [`EmbeddingService::reembed_all`]
+pub const REEMBED_BATCH_SIZE: usize = 100;
 
 /// Local embedding service wrapping fastembed
 #[derive(Clone)]
@@ -62,6 +67,33 @@ impl EmbeddingService {
     pub fn dimensions(&self) -> usize {
         384
     }
+
+    /// Recompute every stored embedding of `user_id` with the current model,
+    /// e.g. after the embedding model changed. Returns the number of
+    /// memories re-embedded.
+    ///
+    /// Memories are processed in batches of [`REEMBED_BATCH_SIZE`]; progress
+    /// is written to `reembed_progress` after each batch.
+    pub async fn reembed_all(&self, store: &MemoryStore, user_id: &str) -> Result<u64> {
+        let total = store.start_reembed(user_id).await?;
+        let mut processed = 0u64;
+        let mut after = None;
+
+        loop {
+            let batch = store.contents_after(user_id, after, REEMBED_BATCH_SIZE).await?;
+            let Some(&(last_id, _)) = batch.last() else {
+                break;
+            };
+            let (ids, texts): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+            let embeddings = self.embed_batch(texts).await?;
+            processed += store.update_embeddings(ids.into_iter().zip(embeddings).collect()).await?;
+            store.record_reembed_progress(user_id, processed).await?;
+            info!("Re-embedded {}/{} memories for {}", processed, total, user_id);
+            after = Some(last_id);
+        }
+
+        Ok(processed)
+    }
 }
 
 #[cfg(test)]
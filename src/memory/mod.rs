@@ -4,11 +4,15 @@
 //! and PostgreSQL-backed semantic + full-text search.
 
 pub mod cache;
+pub mod cluster;
+pub mod dedup;
 pub mod embedding;
 pub mod retrieval;
 pub mod summarizer;
 
 pub use cache::MemoryCache;
-pub use embedding::EmbeddingService;
-pub use retrieval::MemoryRetriever;
+pub use cluster::MemoryCluster;
+pub use dedup::{DedupOutcome, MemoryDeduplicationService};
+pub use embedding::{EmbeddingService, REEMBED_BATCH_SIZE};
+pub use retrieval::{is_how_to_query, MemoryRetriever, AUTO_TAGS};
 pub use summarizer::ConversationSummarizer;
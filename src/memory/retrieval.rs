@@ -3,11 +3,16 @@
 //! Ties together embedding generation, caching, semantic search (pgvector),
 //! and full-text search (tsvector) into a single retrieval pipeline.
 //! Uses Reciprocal Rank Fusion (RRF) for hybrid scoring.
+//!
+//! With an LLM client attached, memories saved without tags are tagged by
+//! zero-shot classification against [`AUTO_TAGS`].
 
-use crate::database::{Memory, MemoryStore, MemoryType};
-use crate::error::Result;
+use crate::agent::{GenerationOptions, Message, OpenRouterClient};
+use crate::database::{Memory, MemoryStats, MemoryStore, MemoryType};
+use crate::error::{Error, Result};
 use chrono::Utc;
-use std::collections::HashMap;
+use futures::stream::{self, StreamExt};
+use std::collections::{HashMap, HashSet};
 use tracing::{info, warn};
 use uuid::Uuid;
 
@@ -17,12 +22,42 @@ use super::embedding::EmbeddingService;
 /// RRF constant (standard value from the original RRF paper)
 const RRF_K: f64 = 60.0;
 
+/// Maximum procedural memories injected for a how-to question
+const PROCEDURAL_LIMIT: usize = 3;
+
+/// Maximum concurrent `record_access` updates after a retrieval
+const RECORD_ACCESS_CONCURRENCY: usize = 4;
+
+/// Words ignored when extracting keywords from a task description
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "can", "could", "do", "does", "for", "from", "how", "i", "in",
+    "is", "it", "me", "my", "of", "on", "or", "please", "should", "the", "to", "what",
+    "with", "would", "you",
+];
+
+/// Phrases that mark a message as a how-to question
+const HOW_TO_MARKERS: &[&str] = &[
+    "install", "configure", "set up", "setup", "steps to", "instructions for", "guide to",
+];
+
+/// Tags `auto_tag` may assign
+pub const AUTO_TAGS: &[&str] = &["coding", "personal", "work", "research", "preference", "fact", "procedure"];
+
+/// Most tags `auto_tag` assigns to one memory
+const MAX_AUTO_TAGS: usize = 5;
+
 /// Orchestrates memory retrieval across semantic and full-text search
 #[derive(Clone)]
 pub struct MemoryRetriever {
     store: MemoryStore,
     embedding: EmbeddingService,
     cache: MemoryCache,
+    /// Classifies untagged memories; auto-tagging is off without it
+    tagger: Option<OpenRouterClient>,
+    /// Describes memory clusters; tags are used without it
+    cluster_llm: Option<OpenRouterClient>,
+    /// Extracts facts for `promote_to_semantic`; promotion is off without it
+    consolidator: Option<OpenRouterClient>,
 }
 
 impl MemoryRetriever {
@@ -32,6 +67,75 @@ impl MemoryRetriever {
             store,
             embedding,
             cache,
+            tagger: None,
+            cluster_llm: None,
+            consolidator: None,
+        }
+    }
+
+    /// Auto-tag memories saved without tags using `llm_client`
+    pub fn with_auto_tagging(mut self, llm_client: OpenRouterClient) -> Self {
+        self.tagger = Some(llm_client);
+        self
+    }
+
+    /// Describe clusters from [`cluster_memories`](Self::cluster_memories)
+    /// with `llm_client`
+    pub fn with_cluster_descriptions(mut self, llm_client: OpenRouterClient) -> Self {
+        self.cluster_llm = Some(llm_client);
+        self
+    }
+
+    /// Extract facts for [`promote_to_semantic`](Self::promote_to_semantic)
+    /// with `llm_client`
+    pub fn with_consolidation(mut self, llm_client: OpenRouterClient) -> Self {
+        self.consolidator = Some(llm_client);
+        self
+    }
+
+    pub(super) fn cluster_llm(&self) -> Option<&OpenRouterClient> {
+        self.cluster_llm.as_ref()
+    }
+
+    /// Fill in `memory.tags` by asking the LLM to pick 1-5 of [`AUTO_TAGS`]
+    ///
+    /// Predictions are cached by content. Leaves the tags empty if
+    /// auto-tagging is off, the call fails, or no known tag comes back.
+    pub async fn auto_tag(&self, memory: &mut Memory) {
+        let Some(ref llm_client) = self.tagger else {
+            return;
+        };
+        if let Some(tags) = self.cache.get_tags(&memory.content).await {
+            memory.tags = tags;
+            return;
+        }
+
+        let prompt = format!(
+            "Classify this text with 1-5 tags from the following list: [{}]\n\
+            Reply with the tags only, comma-separated.\n\nText:\n{}",
+            AUTO_TAGS.join(", "),
+            memory.content
+        );
+        let response = match llm_client
+            .chat(vec![Message::user(prompt)], GenerationOptions::precise())
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Auto-tagging failed: {}", e);
+                return;
+            }
+        };
+        let reply = response
+            .choices
+            .first()
+            .map(|c| c.message.content.as_str())
+            .unwrap_or_default();
+
+        let tags = parse_auto_tags(reply);
+        if !tags.is_empty() {
+            self.cache.put_tags(&memory.content, tags.clone()).await;
+            memory.tags = tags;
         }
     }
 
@@ -100,9 +204,7 @@ impl MemoryRetriever {
         let scored = compute_rrf_scores(&semantic_results, &fulltext_results, limit);
 
         // 5. Record access for retrieved memories
-        for (memory, _) in &scored {
-            let _ = self.store.record_access(memory.id).await;
-        }
+        self.record_accesses(scored.iter().map(|(m, _)| m.id)).await;
 
         // 6. Cache results (only for untyped queries)
         if memory_type.is_none() {
@@ -124,8 +226,65 @@ impl MemoryRetriever {
         Ok(format_memories(&scored))
     }
 
+    /// Bump access stats for retrieved memories, a few updates at a time
+    async fn record_accesses(&self, ids: impl Iterator<Item = Uuid>) {
+        stream::iter(ids)
+            .for_each_concurrent(RECORD_ACCESS_CONCURRENCY, |id| async move {
+                if let Err(e) = self.store.record_access(id).await {
+                    warn!("Failed to record access for memory {}: {}", id, e);
+                }
+            })
+            .await;
+    }
+
+    /// Retrieve procedural (how-to) memories relevant to a task, formatted as
+    /// a separate context section
+    pub async fn retrieve_procedural(&self, user_id: &str, task_description: &str) -> Result<String> {
+        let keywords = extract_keywords(task_description);
+        if keywords.is_empty() {
+            return Ok(String::new());
+        }
+
+        let query = keywords.join(" ");
+        let query_embedding = self.get_or_create_embedding(&query).await?;
+        let results = self
+            .store
+            .search_semantic_typed(
+                user_id,
+                query_embedding,
+                PROCEDURAL_LIMIT,
+                0.2,
+                Some(MemoryType::Procedural.as_str()),
+            )
+            .await?;
+
+        self.record_accesses(results.iter().map(|(m, _)| m.id)).await;
+
+        info!(
+            "Retrieved {} procedural memories for user={} (keywords: {})",
+            results.len(),
+            user_id,
+            query
+        );
+
+        let scored: Vec<(Memory, f64)> = results
+            .into_iter()
+            .map(|(m, similarity)| (m, similarity as f64))
+            .collect();
+        Ok(format_memories_with_heading("Relevant Procedures", &scored))
+    }
+
     /// Save a memory with embedding
     pub async fn save_memory(&self, memory: &Memory) -> Result<()> {
+        let mut tagged;
+        let memory = if memory.tags.is_empty() && self.tagger.is_some() {
+            tagged = memory.clone();
+            self.auto_tag(&mut tagged).await;
+            &tagged
+        } else {
+            memory
+        };
+
         // Generate embedding for the content
         let embedding = match self.embedding.embed(&memory.content).await {
             Ok(emb) => Some(emb),
@@ -139,11 +298,119 @@ impl MemoryRetriever {
         self.store.save(memory, embedding).await?;
 
         // Invalidate user's search cache
-        self.cache.invalidate_user_search(&memory.user_id).await;
+        self.cache.invalidate_user(&memory.user_id).await;
 
         Ok(())
     }
 
+    /// Save many memories at once: one batch embedding call, one upsert.
+    /// Returns the IDs written, in input order.
+    ///
+    /// If embedding fails the memories are still saved without embeddings,
+    /// like [`save_memory`](Self::save_memory).
+    pub async fn save_batch(&self, memories: Vec<Memory>) -> Result<Vec<Uuid>> {
+        if memories.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let contents: Vec<String> = memories.iter().map(|m| m.content.clone()).collect();
+        let embeddings: Vec<Option<Vec<f32>>> = match self.embedding.embed_batch(contents).await {
+            Ok(embeddings) if embeddings.len() == memories.len() => embeddings.into_iter().map(Some).collect(),
+            Ok(embeddings) => {
+                warn!(
+                    "Batch embedding returned {} vectors for {} memories",
+                    embeddings.len(),
+                    memories.len()
+                );
+                vec![None; memories.len()]
+            }
+            Err(e) => {
+                warn!("Failed to generate batch embeddings: {}", e);
+                vec![None; memories.len()]
+            }
+        };
+
+        let users: HashSet<String> = memories.iter().map(|m| m.user_id.clone()).collect();
+        let ids = self
+            .store
+            .save_batch(memories.into_iter().zip(embeddings).collect())
+            .await?;
+
+        for user_id in &users {
+            self.cache.invalidate_user(user_id).await;
+        }
+
+        Ok(ids)
+    }
+
+    /// Delete a memory and flush its owner's cached search results, so
+    /// `retrieve` stops returning it. Returns the deleted memory, or `None`
+    /// if it did not exist.
+    pub async fn delete_memory(&self, id: Uuid) -> Result<Option<Memory>> {
+        let Some(memory) = self.store.get(id).await? else {
+            return Ok(None);
+        };
+        self.store.delete(id).await?;
+        self.cache.invalidate_user(&memory.user_id).await;
+        Ok(Some(memory))
+    }
+
+    /// Turn an episodic memory into a semantic one holding its core fact
+    ///
+    /// The LLM set with [`with_consolidation`](Self::with_consolidation)
+    /// extracts the fact; the new memory keeps the owner, importance and
+    /// tags, and records the original's ID under `promoted_from` in its
+    /// metadata. With `delete_original` the episodic memory is removed once
+    /// the semantic one is saved. Fails if the memory does not exist, is not
+    /// episodic, or holds no lasting fact.
+    pub async fn promote_to_semantic(&self, episodic_id: Uuid, delete_original: bool) -> Result<Memory> {
+        let Some(ref llm_client) = self.consolidator else {
+            return Err(Error::Config("Memory consolidation is not configured".to_string()));
+        };
+        let episodic = self
+            .store
+            .get(episodic_id)
+            .await?
+            .ok_or_else(|| Error::NotFound(format!("Memory not found: {}", episodic_id)))?;
+        if episodic.parsed_type() != MemoryType::Episodic {
+            return Err(Error::InvalidInput(format!(
+                "Memory {} is {}, not episodic",
+                episodic_id, episodic.memory_type
+            )));
+        }
+
+        let prompt = format!(
+            "Extract the core fact from this episodic memory: the lasting information \
+            it holds about the user or their world, without the time and circumstances \
+            of the event. Reply with the fact only, as one or two sentences. If there is \
+            no lasting fact, reply NONE.\n\nMemory:\n{}",
+            episodic.content
+        );
+        let response = llm_client
+            .chat(vec![Message::user(prompt)], GenerationOptions::precise())
+            .await?;
+        let reply = response
+            .choices
+            .first()
+            .map(|c| c.message.content.as_str())
+            .unwrap_or_default();
+        let fact = parse_extracted_fact(reply).ok_or_else(|| {
+            Error::InvalidInput(format!("No lasting fact found in memory {}", episodic_id))
+        })?;
+
+        let semantic = Memory::new(&episodic.user_id, fact)
+            .with_importance(episodic.importance)
+            .with_tags(episodic.tags.clone())
+            .with_memory_type(MemoryType::Semantic)
+            .with_source("promoted:episodic")
+            .with_metadata(serde_json::json!({ "promoted_from": episodic_id }));
+        self.save_memory(&semantic).await?;
+        if delete_original {
+            self.delete_memory(episodic_id).await?;
+        }
+        Ok(semantic)
+    }
+
     /// Get or create an embedding (using cache)
     pub(crate) async fn get_or_create_embedding(&self, text: &str) -> Result<Vec<f32>> {
         if let Some(cached) = self.cache.get_embedding(text).await {
@@ -155,6 +422,12 @@ impl MemoryRetriever {
         Ok(embedding)
     }
 
+    /// Count, age, storage size and importance distribution of `user_id`'s
+    /// memories
+    pub async fn stats(&self, user_id: &str) -> Result<MemoryStats> {
+        self.store.stats(user_id).await
+    }
+
     /// Get a reference to the underlying store
     pub fn store(&self) -> &MemoryStore {
         &self.store
@@ -239,11 +512,15 @@ fn compute_rrf_scores(
 
 /// Format memories with type labels and scores for injection into system prompt
 fn format_memories(memories: &[(Memory, f64)]) -> String {
+    format_memories_with_heading("Relevant Memories", memories)
+}
+
+fn format_memories_with_heading(heading: &str, memories: &[(Memory, f64)]) -> String {
     if memories.is_empty() {
         return String::new();
     }
 
-    let mut output = String::from("\n\n---\n\n## Relevant Memories\n\n");
+    let mut output = format!("\n\n---\n\n## {}\n\n", heading);
 
     for (i, (memory, score)) in memories.iter().enumerate() {
         let type_label = match memory.memory_type.as_str() {
@@ -275,6 +552,25 @@ fn format_memories(memories: &[(Memory, f64)]) -> String {
     output
 }
 
+/// Heuristic: does the message ask how to do something?
+pub fn is_how_to_query(text: &str) -> bool {
+    let lower = text.trim().to_lowercase();
+    lower.starts_with("how ")
+        || lower.starts_with("how's ")
+        || HOW_TO_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Extract search keywords: lowercase words minus punctuation and stop words
+fn extract_keywords(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| w.len() > 1 && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
 /// Simple format for cached results (no scores available)
 fn format_memories_simple(memories: &[Memory]) -> String {
     if memories.is_empty() {
@@ -312,11 +608,69 @@ fn format_memories_simple(memories: &[Memory]) -> String {
     output
 }
 
+/// Known tags named in an LLM reply, in order of first mention
+fn parse_auto_tags(reply: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for word in reply.to_lowercase().split(|c: char| !c.is_ascii_alphabetic()) {
+        if AUTO_TAGS.contains(&word) && !tags.iter().any(|t| t == word) {
+            tags.push(word.to_string());
+        }
+    }
+    tags.truncate(MAX_AUTO_TAGS);
+    tags
+}
+
+/// The fact in an extraction reply, or `None` if the LLM found none
+fn parse_extracted_fact(reply: &str) -> Option<String> {
+    let fact = reply.trim().trim_matches('"').trim();
+    let fact = fact.strip_prefix("Fact:").map(str::trim).unwrap_or(fact);
+    if fact.is_empty() || fact.trim_end_matches('.').eq_ignore_ascii_case("none") {
+        None
+    } else {
+        Some(fact.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::database::Memory;
 
+    #[test]
+    fn test_parse_extracted_fact() {
+        assert_eq!(
+            parse_extracted_fact("  \"Fact: The user's daughter is allergic to peanuts.\"\n").as_deref(),
+            Some("The user's daughter is allergic to peanuts.")
+        );
+        assert_eq!(parse_extracted_fact("NONE"), None);
+        assert_eq!(parse_extracted_fact("None."), None);
+        assert_eq!(parse_extracted_fact(""), None);
+    }
+
+    #[test]
+    fn test_how_to_detection_and_keywords() {
+        assert!(is_how_to_query("How do I deploy the gateway?"));
+        assert!(is_how_to_query("Please install postgres for me"));
+        assert!(!is_how_to_query("What's the weather today?"));
+
+        assert_eq!(
+            extract_keywords("How do I configure the Nginx reverse-proxy?"),
+            vec!["configure", "nginx", "reverse-proxy"]
+        );
+    }
+
+    #[test]
+    fn test_parse_auto_tags() {
+        assert_eq!(parse_auto_tags("coding, work"), vec!["coding", "work"]);
+        assert_eq!(parse_auto_tags("[\"Fact\", \"preference\", \"fact\"]"), vec!["fact", "preference"]);
+        assert_eq!(parse_auto_tags("Tags: personal; hobby"), vec!["personal"]);
+        assert!(parse_auto_tags("I cannot classify this").is_empty());
+        assert_eq!(
+            parse_auto_tags("coding work research fact procedure personal").len(),
+            MAX_AUTO_TAGS
+        );
+    }
+
     #[test]
     fn test_format_memories_empty() {
         assert_eq!(format_memories(&[]), "");
@@ -444,4 +798,30 @@ mod tests {
         assert_eq!(results[0].0.id, recent.id);
         assert!(results[0].1 > results[1].1);
     }
+
+    /// Run with `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "requires TEST_DATABASE_URL and the embedding model"]
+    async fn test_deleted_memory_not_retrieved() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL not set");
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        crate::database::migrations::run(&pool).await.unwrap();
+
+        let retriever = MemoryRetriever::new(
+            MemoryStore::new(pool),
+            EmbeddingService::new().unwrap(),
+            MemoryCache::default(),
+        );
+        let user_id = format!("test-{}", Uuid::new_v4());
+        let memory = Memory::new(&user_id, "The staging database password rotates every Friday");
+        retriever.save_memory(&memory).await.unwrap();
+
+        // Populate the search cache, then delete
+        let query = "when does the staging password rotate";
+        assert!(retriever.retrieve(&user_id, query, 5).await.unwrap().contains("every Friday"));
+        assert!(retriever.delete_memory(memory.id).await.unwrap().is_some());
+
+        assert!(!retriever.retrieve(&user_id, query, 5).await.unwrap().contains("every Friday"));
+        assert!(retriever.delete_memory(memory.id).await.unwrap().is_none());
+    }
 }
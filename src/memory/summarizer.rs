@@ -1,9 +1,11 @@
 //! Conversation summarizer for automatic episodic memory creation
 //!
 //! Uses the LLM to summarize conversations into structured episodic memories,
-//! extracting key facts and user preferences for separate semantic storage.
+//! extracting key facts and user preferences for separate semantic storage,
+//! and to list the follow-ups left open when a conversation is cleared.
 
 use crate::agent::{GenerationOptions, Message, OpenRouterClient, Role};
+use crate::database::{AgentTask, TaskStore};
 use crate::error::Result;
 use tracing::{info, warn};
 
@@ -34,35 +36,13 @@ impl ConversationSummarizer {
 
     /// Summarize a conversation into an episodic summary
     pub async fn summarize(&self, messages: &[Message]) -> Result<EpisodicSummary> {
-        // Filter to user + assistant messages only (skip system, tool)
-        let conversation_text: String = messages
-            .iter()
-            .filter(|m| m.role == Role::User || m.role == Role::Assistant)
-            .map(|m| {
-                let role_label = match m.role {
-                    Role::User => "User",
-                    Role::Assistant => "Assistant",
-                    _ => "Other",
-                };
-                format!("{}: {}", role_label, m.content)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        if conversation_text.is_empty() {
+        let Some(truncated) = conversation_text(messages) else {
             return Ok(EpisodicSummary {
                 summary: String::new(),
                 key_facts: vec![],
                 user_preferences: vec![],
                 topics: vec![],
             });
-        }
-
-        // Truncate if too long (keep first ~4000 chars to fit in context)
-        let truncated = if conversation_text.len() > 4000 {
-            format!("{}...\n[truncated]", &conversation_text[..4000])
-        } else {
-            conversation_text
         };
 
         let prompt = format!(
@@ -133,6 +113,119 @@ If a field has no items, use an empty array []. Keep the summary under 200 chara
             }
         }
     }
+
+    /// List the unresolved tasks, commitments and follow-ups in a conversation
+    ///
+    /// Returns one string per item, empty when nothing is left open.
+    pub async fn extract_action_items(&self, messages: &[Message]) -> Result<Vec<String>> {
+        let Some(truncated) = conversation_text(messages) else {
+            return Ok(Vec::new());
+        };
+
+        let prompt = format!(
+            r#"List the unresolved tasks, commitments and follow-ups from this conversation: things the user or the assistant said they would do, or asked to be done later, that were not completed in the conversation.
+
+Conversation:
+---
+{}
+---
+
+Reply with a bulleted list, one item per line starting with "- ", each a short imperative sentence. If there are none, reply with "NONE"."#,
+            truncated
+        );
+
+        let response = self
+            .llm_client
+            .chat(
+                vec![Message::user(prompt)],
+                GenerationOptions::precise(),
+            )
+            .await?;
+
+        let content = response
+            .choices
+            .first()
+            .map(|c| c.message.content.clone())
+            .unwrap_or_default();
+        let items = parse_bullet_list(&content);
+        info!("Extracted {} action items from conversation", items.len());
+        Ok(items)
+    }
+
+    /// Create a pending task for each action item in `messages`
+    ///
+    /// Items that fail to save are logged and skipped; the created tasks are
+    /// returned.
+    pub async fn create_action_item_tasks(
+        &self,
+        tasks: &TaskStore,
+        user_id: &str,
+        chat_id: Option<i64>,
+        messages: &[Message],
+    ) -> Result<Vec<AgentTask>> {
+        let mut created = Vec::new();
+        for item in self.extract_action_items(messages).await? {
+            let title: String = item.chars().take(100).collect();
+            let description = format!("Follow-up from a cleared conversation: {}", item);
+            match tasks.create(user_id, chat_id, &title, &description, 0).await {
+                Ok(task) => created.push(task),
+                Err(e) => warn!("Failed to create task for action item: {}", e),
+            }
+        }
+        Ok(created)
+    }
+}
+
+/// User and assistant turns as `Role: content` lines, cut to ~4000 bytes to
+/// fit in context. `None` when there are no such turns.
+fn conversation_text(messages: &[Message]) -> Option<String> {
+    // Filter to user + assistant messages only (skip system, tool)
+    let conversation_text: String = messages
+        .iter()
+        .filter(|m| m.role == Role::User || m.role == Role::Assistant)
+        .map(|m| {
+            let role_label = match m.role {
+                Role::User => "User",
+                Role::Assistant => "Assistant",
+                _ => "Other",
+            };
+            format!("{}: {}", role_label, m.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if conversation_text.is_empty() {
+        return None;
+    }
+
+    // Truncate if too long (keep first ~4000 bytes, on a character boundary)
+    match conversation_text.char_indices().map(|(i, _)| i).find(|&i| i >= 4000) {
+        Some(end) => Some(format!("{}...\n[truncated]", &conversation_text[..end])),
+        None => Some(conversation_text),
+    }
+}
+
+/// Items of a `-`, `*`, `•` or numbered list; other lines are ignored
+fn parse_bullet_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let item = line
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("* "))
+                .or_else(|| line.strip_prefix("• "))
+                .or_else(|| {
+                    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+                    if rest.len() == line.len() {
+                        return None;
+                    }
+                    rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))
+                })?;
+            let item = item.trim();
+            (!item.is_empty()).then(|| item.to_string())
+        })
+        .collect()
 }
 
 /// Extract a string array from a JSON value by key
@@ -173,6 +266,16 @@ mod tests {
         assert!(not_array.is_empty());
     }
 
+    #[test]
+    fn test_parse_bullet_list() {
+        let reply = "Here are the follow-ups:\n- Send the Q3 report to Dana\n* Book a dentist appointment\n2. Check the CI logs\n-\nNONE";
+        assert_eq!(
+            parse_bullet_list(reply),
+            vec!["Send the Q3 report to Dana", "Book a dentist appointment", "Check the CI logs"]
+        );
+        assert!(parse_bullet_list("NONE").is_empty());
+    }
+
     #[test]
     fn test_episodic_summary_default() {
         let summary = EpisodicSummary {
@@ -60,6 +60,11 @@ pub struct PluginManifest {
     /// UI hints for configuration
     #[serde(default)]
     pub ui_hints: HashMap<String, UiHint>,
+    /// Settings the plugin can't work without: environment variable names
+    /// (e.g. `BRAVE_API_KEY`) or dotted paths into the plugin's config
+    /// (e.g. `auth.token`)
+    #[serde(default)]
+    pub required_config_keys: Vec<String>,
     /// Required OpenAgent version
     pub openagent_version: Option<String>,
     /// Plugin author
@@ -125,6 +130,32 @@ impl PluginManifest {
         self.skills.push(skill.into());
         self
     }
+
+    /// Require an environment variable or plugin config path
+    pub fn with_required_config_key(mut self, key: impl Into<String>) -> Self {
+        self.required_config_keys.push(key.into());
+        self
+    }
+
+    /// Required keys set neither as a non-empty environment variable nor
+    /// in `config` (the plugin's configuration), in manifest order
+    pub fn missing_config_keys(&self, config: Option<&serde_json::Value>) -> Vec<String> {
+        self.required_config_keys
+            .iter()
+            .filter(|key| {
+                let in_env = std::env::var(key.as_str()).is_ok_and(|value| !value.is_empty());
+                !in_env && !config.is_some_and(|config| has_config_path(config, key))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Whether `config` has a non-null value at the dotted `path`
+fn has_config_path(config: &serde_json::Value, path: &str) -> bool {
+    path.split('.')
+        .try_fold(config, |value, segment| value.get(segment))
+        .is_some_and(|value| !value.is_null())
 }
 
 /// Load a plugin manifest from a file
@@ -163,4 +194,24 @@ mod tests {
         assert_eq!(manifest.kind, PluginKind::Channel);
         assert_eq!(manifest.channels, vec!["telegram"]);
     }
+
+    #[test]
+    fn test_missing_config_keys() {
+        let manifest = PluginManifest::new("search", "Search", "1.0.0")
+            .with_required_config_key("PATH")
+            .with_required_config_key("OPENAGENT_TEST_UNSET_PLUGIN_KEY")
+            .with_required_config_key("auth.token");
+
+        assert_eq!(
+            manifest.missing_config_keys(None),
+            vec!["OPENAGENT_TEST_UNSET_PLUGIN_KEY", "auth.token"]
+        );
+        let config = serde_json::json!({ "auth": { "token": "t" } });
+        assert_eq!(manifest.missing_config_keys(Some(&config)), vec!["OPENAGENT_TEST_UNSET_PLUGIN_KEY"]);
+        let manifest: PluginManifest = serde_json::from_str(
+            r#"{ "id": "p", "name": "P", "version": "1.0.0", "required_config_keys": ["BRAVE_API_KEY"] }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.required_config_keys, vec!["BRAVE_API_KEY"]);
+    }
 }
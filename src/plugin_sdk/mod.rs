@@ -36,8 +36,8 @@ mod registry;
 mod traits;
 
 pub use manifest::{PluginManifest, PluginKind};
-pub use registry::{PluginRegistry, PluginLoadResult};
-pub use traits::{Plugin, PluginApi};
+pub use registry::{PluginRegistry, PluginLoadResult, PluginHealth};
+pub use traits::{Plugin, PluginApi, HealthStatus};
 
 // Re-export core traits that plugins will need
 pub use crate::core::{
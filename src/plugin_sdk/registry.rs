@@ -5,9 +5,13 @@
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::warn;
 
 use super::manifest::{load_manifest, PluginManifest, PLUGIN_MANIFEST_FILENAME};
-use super::traits::{DefaultPluginApi, Plugin};
+use super::traits::{DefaultPluginApi, HealthStatus, Plugin, PluginApi};
 use crate::error::{Error, Result};
 
 /// Result of loading a plugin
@@ -19,6 +23,9 @@ pub enum PluginLoadResult {
         id: String,
         /// Plugin manifest
         manifest: PluginManifest,
+        /// Required config keys that aren't set. When non-empty, the
+        /// plugin's channels, providers and tools are not registered.
+        missing_config: Vec<String>,
     },
     /// Failed to load
     Error {
@@ -29,6 +36,20 @@ pub enum PluginLoadResult {
     },
 }
 
+/// Health check result for a single plugin
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginHealth {
+    /// Plugin ID
+    pub plugin_id: String,
+    /// Reported status
+    pub status: HealthStatus,
+    /// Time taken by the check
+    pub latency_ms: u64,
+    /// Error returned by the check, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// Plugin registry - Manages loaded plugins
 pub struct PluginRegistry {
     plugins: HashMap<String, Arc<dyn Plugin>>,
@@ -47,7 +68,11 @@ impl PluginRegistry {
     }
 
     /// Register a plugin
-    pub fn register(&mut self, plugin: Arc<dyn Plugin>) -> Result<()> {
+    ///
+    /// A plugin whose `required_config_keys` aren't all set is still listed,
+    /// but its `register` hook isn't called, so none of its capabilities are
+    /// added. The missing keys are returned in `PluginLoadResult::Ok`.
+    pub fn register(&mut self, plugin: Arc<dyn Plugin>) -> Result<PluginLoadResult> {
         let manifest = plugin.manifest();
         let id = manifest.id.clone();
 
@@ -59,14 +84,26 @@ impl PluginRegistry {
             )));
         }
 
-        // Register the plugin
-        plugin.register(&mut self.api)?;
+        let missing_config = manifest.missing_config_keys(self.api.get_config(&id));
+        if missing_config.is_empty() {
+            plugin.register(&mut self.api)?;
+        } else {
+            warn!(
+                "Plugin '{}' is missing required config ({}); not registering its tools",
+                id,
+                missing_config.join(", ")
+            );
+        }
 
         // Store plugin and manifest
         self.plugins.insert(id.clone(), plugin);
-        self.manifests.insert(id, manifest);
+        self.manifests.insert(id.clone(), manifest.clone());
 
-        Ok(())
+        Ok(PluginLoadResult::Ok {
+            id,
+            manifest,
+            missing_config,
+        })
     }
 
     /// Unregister a plugin
@@ -93,6 +130,29 @@ impl PluginRegistry {
         self.manifests.values().collect()
     }
 
+    /// Run `health_check` on every plugin concurrently, each bounded by `timeout`.
+    /// Results are sorted by plugin ID.
+    pub async fn health_check_all(&self, timeout: Duration) -> Vec<PluginHealth> {
+        let checks = self.plugins.iter().map(|(id, plugin)| async move {
+            let start = Instant::now();
+            let (status, error) = match tokio::time::timeout(timeout, plugin.health_check()).await {
+                Ok(Ok(status)) => (status, None),
+                Ok(Err(e)) => (HealthStatus::Unhealthy, Some(e.to_string())),
+                Err(_) => (HealthStatus::Timeout, None),
+            };
+            PluginHealth {
+                plugin_id: id.clone(),
+                status,
+                latency_ms: start.elapsed().as_millis() as u64,
+                error,
+            }
+        });
+
+        let mut results = futures::future::join_all(checks).await;
+        results.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+        results
+    }
+
     /// Get the plugin API
     pub fn api(&self) -> &DefaultPluginApi {
         &self.api
@@ -122,6 +182,8 @@ impl PluginRegistry {
                             Ok(manifest) => {
                                 results.push(PluginLoadResult::Ok {
                                     id: manifest.id.clone(),
+                                    missing_config: manifest
+                                        .missing_config_keys(self.api.get_config(&manifest.id)),
                                     manifest,
                                 });
                             }
@@ -194,4 +256,78 @@ mod tests {
         let registry = PluginRegistry::new();
         assert!(registry.list().is_empty());
     }
+
+    struct TestPlugin {
+        id: &'static str,
+        delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for TestPlugin {
+        fn manifest(&self) -> PluginManifest {
+            PluginManifest::new(self.id, self.id, "1.0.0")
+        }
+
+        fn register(&self, _api: &mut dyn super::super::PluginApi) -> Result<()> {
+            Ok(())
+        }
+
+        async fn health_check(&self) -> Result<HealthStatus> {
+            tokio::time::sleep(self.delay).await;
+            Ok(HealthStatus::Ok)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_all_times_out_slow_plugins() {
+        let mut registry = PluginRegistry::new();
+        registry
+            .register(Arc::new(TestPlugin { id: "fast", delay: Duration::ZERO }))
+            .unwrap();
+        registry
+            .register(Arc::new(TestPlugin { id: "slow", delay: Duration::from_secs(5) }))
+            .unwrap();
+
+        let results = registry.health_check_all(Duration::from_millis(50)).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!((results[0].plugin_id.as_str(), results[0].status), ("fast", HealthStatus::Ok));
+        assert_eq!((results[1].plugin_id.as_str(), results[1].status), ("slow", HealthStatus::Timeout));
+    }
+
+    /// Requires `auth.token` in its config and counts `register` calls
+    #[derive(Default)]
+    struct KeyedPlugin {
+        registered: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for KeyedPlugin {
+        fn manifest(&self) -> PluginManifest {
+            PluginManifest::new("keyed", "Keyed", "1.0.0").with_required_config_key("auth.token")
+        }
+
+        fn register(&self, _api: &mut dyn super::super::PluginApi) -> Result<()> {
+            self.registered.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_register_skips_plugins_missing_config() {
+        let plugin = Arc::new(KeyedPlugin::default());
+        let mut registry = PluginRegistry::new();
+        let result = registry.register(plugin.clone()).unwrap();
+        assert!(matches!(result, PluginLoadResult::Ok { ref missing_config, .. } if missing_config == &["auth.token"]));
+        assert_eq!(plugin.registered.load(std::sync::atomic::Ordering::SeqCst), 0);
+        assert!(registry.get_manifest("keyed").is_some());
+
+        let plugin = Arc::new(KeyedPlugin::default());
+        let mut registry = PluginRegistry::new();
+        registry
+            .api_mut()
+            .set_config("keyed", serde_json::json!({ "auth": { "token": "t" } }));
+        let result = registry.register(plugin.clone()).unwrap();
+        assert!(matches!(result, PluginLoadResult::Ok { ref missing_config, .. } if missing_config.is_empty()));
+        assert_eq!(plugin.registered.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
 }
@@ -5,11 +5,29 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
 use super::manifest::PluginManifest;
 use crate::core::{Channel, CodeExecutor, LlmProvider, MemoryBackend};
 use crate::error::Result;
 
+/// Health of a plugin's external dependencies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// All required dependencies are reachable
+    Ok,
+    /// Working, but some dependency is slow or partially unavailable
+    Degraded,
+    /// A required dependency is unreachable
+    Unhealthy,
+    /// The health check did not finish in time
+    Timeout,
+}
+
 /// Plugin trait - Main interface for plugins
+#[async_trait]
 pub trait Plugin: Send + Sync {
     /// Get the plugin manifest
     fn manifest(&self) -> PluginManifest;
@@ -28,6 +46,11 @@ pub trait Plugin: Send + Sync {
     fn unregister(&self) -> Result<()> {
         Ok(())
     }
+
+    /// Check that the plugin's required external APIs are reachable
+    async fn health_check(&self) -> Result<HealthStatus> {
+        Ok(HealthStatus::Ok)
+    }
 }
 
 /// Plugin API - Interface provided to plugins for registration
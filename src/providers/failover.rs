@@ -0,0 +1,354 @@
+//! Failover provider
+//!
+//! Wraps a primary `LlmProvider` and an ordered list of secondaries. When a
+//! provider fails with a provider error or an HTTP 5xx, the request is
+//! retried on the next provider after an exponentially growing delay.
+//! Providers that fail `max_failures` times in a row, the primary included,
+//! are skipped for `cooldown_secs`; if every provider is cooling down they
+//! are tried in order anyway.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::config::FailoverConfig;
+use crate::core::provider::{LlmStream, ModelInfo, ProviderMeta};
+use crate::core::{GenerationOptions, LlmProvider, LlmResponse, Message};
+use crate::error::{Error, Result};
+
+/// Health snapshot for one provider in the failover chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    /// Provider ID
+    pub id: String,
+    /// Requests routed to this provider
+    pub requests: u64,
+    /// Requests that failed
+    pub failures: u64,
+    /// `failures / requests` (0.0 when no requests yet)
+    pub error_rate: f64,
+    /// Whether the provider is currently skipped after repeated failures
+    pub in_cooldown: bool,
+}
+
+/// Per-provider counters
+struct ProviderSlot {
+    provider: Arc<dyn LlmProvider>,
+    requests: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU32,
+    /// Unix seconds until which the provider is skipped (0 = not cooling down)
+    cooldown_until: AtomicU64,
+}
+
+impl ProviderSlot {
+    fn new(provider: Arc<dyn LlmProvider>) -> Self {
+        ProviderSlot {
+            provider,
+            requests: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            consecutive_failures: AtomicU32::new(0),
+            cooldown_until: AtomicU64::new(0),
+        }
+    }
+
+    fn in_cooldown(&self) -> bool {
+        self.cooldown_until.load(Ordering::Relaxed) > now_secs()
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Whether an error should cause the request to move to the next provider
+fn should_fail_over(err: &Error) -> bool {
+    match err {
+        Error::Provider(_) | Error::OpenRouter(_) | Error::Timeout(_) | Error::Connection(_) => true,
+        Error::Http(e) => e.status().is_none_or(|s| s.is_server_error()),
+        _ => false,
+    }
+}
+
+/// LLM provider that chains a primary provider with fallbacks
+pub struct FailoverLlmProvider {
+    slots: Vec<ProviderSlot>,
+    config: FailoverConfig,
+    backoff_base: Duration,
+}
+
+impl FailoverLlmProvider {
+    /// Create a failover chain. `secondaries` are tried in order.
+    pub fn new(
+        primary: Arc<dyn LlmProvider>,
+        secondaries: Vec<Arc<dyn LlmProvider>>,
+        config: FailoverConfig,
+    ) -> Self {
+        let slots = std::iter::once(primary)
+            .chain(secondaries)
+            .map(ProviderSlot::new)
+            .collect();
+        FailoverLlmProvider {
+            slots,
+            config,
+            backoff_base: Duration::from_millis(500),
+        }
+    }
+
+    /// Set the initial delay before falling back (doubled on each hop)
+    pub fn with_backoff_base(mut self, base: Duration) -> Self {
+        self.backoff_base = base;
+        self
+    }
+
+    /// Per-provider request/error counts, in chain order
+    pub fn health_summary(&self) -> Vec<ProviderHealth> {
+        self.slots
+            .iter()
+            .map(|slot| {
+                let requests = slot.requests.load(Ordering::Relaxed);
+                let failures = slot.failures.load(Ordering::Relaxed);
+                ProviderHealth {
+                    id: slot.provider.id().to_string(),
+                    requests,
+                    failures,
+                    error_rate: if requests == 0 {
+                        0.0
+                    } else {
+                        failures as f64 / requests as f64
+                    },
+                    in_cooldown: slot.in_cooldown(),
+                }
+            })
+            .collect()
+    }
+
+    /// Run `op` against each provider in turn until one succeeds or an
+    /// error that should not fail over is returned
+    pub(crate) async fn with_failover<'a, T, F, Fut>(&'a self, op: F) -> Result<T>
+    where
+        F: Fn(&'a Arc<dyn LlmProvider>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error: Option<Error> = None;
+        let mut hops: u32 = 0;
+        // With failover disabled only the primary is used, cooling down or not
+        let skip_cooling = self.config.enabled && !self.slots.iter().all(ProviderSlot::in_cooldown);
+
+        for slot in &self.slots {
+            if hops > 0 && !self.config.enabled {
+                break;
+            }
+            if skip_cooling && slot.in_cooldown() {
+                continue;
+            }
+            if hops > 0 {
+                let wait = self.backoff_base * 2u32.saturating_pow(hops - 1);
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+                info!("Failing over to provider '{}'", slot.provider.id());
+            }
+            hops += 1;
+
+            slot.requests.fetch_add(1, Ordering::Relaxed);
+            match op(&slot.provider).await {
+                Ok(value) => {
+                    slot.consecutive_failures.store(0, Ordering::Relaxed);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    slot.failures.fetch_add(1, Ordering::Relaxed);
+                    let streak = slot.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                    if streak >= self.config.max_failures {
+                        slot.cooldown_until
+                            .store(now_secs() + self.config.cooldown_secs, Ordering::Relaxed);
+                        slot.consecutive_failures.store(0, Ordering::Relaxed);
+                    }
+                    if !should_fail_over(&e) {
+                        return Err(e);
+                    }
+                    warn!("Provider '{}' failed: {}", slot.provider.id(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            Error::Provider("No providers available in failover chain".to_string())
+        }))
+    }
+}
+
+#[async_trait]
+impl LlmProvider for FailoverLlmProvider {
+    fn meta(&self) -> &ProviderMeta {
+        self.slots[0].provider.meta()
+    }
+
+    fn default_model(&self) -> &str {
+        self.slots[0].provider.default_model()
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        self.with_failover(|p| p.list_models()).await
+    }
+
+    async fn generate(
+        &self,
+        messages: &[Message],
+        options: &GenerationOptions,
+    ) -> Result<LlmResponse> {
+        self.with_failover(|p| p.generate(messages, options)).await
+    }
+
+    async fn generate_stream(
+        &self,
+        messages: &[Message],
+        options: &GenerationOptions,
+    ) -> Result<LlmStream> {
+        self.with_failover(|p| p.generate_stream(messages, options)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockProvider {
+        meta: ProviderMeta,
+        error: Option<fn() -> Error>,
+    }
+
+    impl MockProvider {
+        fn arc(id: &str, error: Option<fn() -> Error>) -> Arc<dyn LlmProvider> {
+            Arc::new(MockProvider {
+                meta: ProviderMeta {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    description: String::new(),
+                    base_url: String::new(),
+                    supports_streaming: false,
+                    supports_tools: false,
+                    supports_vision: false,
+                },
+                error,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for MockProvider {
+        fn meta(&self) -> &ProviderMeta {
+            &self.meta
+        }
+
+        fn default_model(&self) -> &str {
+            "mock"
+        }
+
+        async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+            Ok(vec![])
+        }
+
+        async fn generate(&self, _: &[Message], _: &GenerationOptions) -> Result<LlmResponse> {
+            if let Some(make_error) = self.error {
+                return Err(make_error());
+            }
+            Ok(LlmResponse {
+                id: "r".into(),
+                model: self.meta.id.clone(),
+                content: "ok".into(),
+                finish_reason: Some("stop".into()),
+                tool_calls: None,
+                usage: None,
+            })
+        }
+
+        async fn generate_stream(&self, _: &[Message], _: &GenerationOptions) -> Result<LlmStream> {
+            Err(Error::NotSupported("streaming".into()))
+        }
+    }
+
+    fn enabled() -> FailoverConfig {
+        FailoverConfig {
+            enabled: true,
+            max_failures: 3,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_provider_error() {
+        let chain = FailoverLlmProvider::new(
+            MockProvider::arc("primary", Some(|| Error::Provider("down".into()))),
+            vec![MockProvider::arc("secondary", None)],
+            enabled(),
+        )
+        .with_backoff_base(Duration::ZERO);
+
+        let response = chain.generate(&[], &GenerationOptions::default()).await.unwrap();
+        assert_eq!(response.model, "secondary");
+
+        let health = chain.health_summary();
+        assert_eq!(health[0].failures, 1);
+        assert_eq!(health[0].error_rate, 1.0);
+        assert_eq!(health[1].requests, 1);
+        assert_eq!(health[1].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_do_not_fail_over() {
+        let chain = FailoverLlmProvider::new(
+            MockProvider::arc("primary", Some(|| Error::Unauthorized("bad key".into()))),
+            vec![MockProvider::arc("secondary", None)],
+            enabled(),
+        )
+        .with_backoff_base(Duration::ZERO);
+
+        assert!(chain.generate(&[], &GenerationOptions::default()).await.is_err());
+        assert_eq!(chain.health_summary()[1].requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_primary_in_cooldown_is_skipped() {
+        let chain = FailoverLlmProvider::new(
+            MockProvider::arc("primary", Some(|| Error::Provider("down".into()))),
+            vec![MockProvider::arc("secondary", None)],
+            FailoverConfig {
+                max_failures: 1,
+                ..enabled()
+            },
+        )
+        .with_backoff_base(Duration::ZERO);
+
+        chain.generate(&[], &GenerationOptions::default()).await.unwrap();
+        assert!(chain.health_summary()[0].in_cooldown);
+
+        // The next request goes straight to the secondary
+        let response = chain.generate(&[], &GenerationOptions::default()).await.unwrap();
+        assert_eq!(response.model, "secondary");
+        let health = chain.health_summary();
+        assert_eq!(health[0].requests, 1);
+        assert_eq!(health[1].requests, 2);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_uses_primary_only() {
+        let chain = FailoverLlmProvider::new(
+            MockProvider::arc("primary", Some(|| Error::Provider("down".into()))),
+            vec![MockProvider::arc("secondary", None)],
+            FailoverConfig::default(),
+        );
+
+        assert!(chain.generate(&[], &GenerationOptions::default()).await.is_err());
+        assert_eq!(chain.health_summary()[1].requests, 0);
+    }
+}
@@ -0,0 +1,13 @@
+//! Providers module - LLM backend implementations and combinators
+//!
+//! Concrete backends implement `crate::core::LlmProvider`. This module also
+//! hosts provider combinators that wrap other providers:
+//!
+//! - **openrouter**: OpenRouter, one provider per model
+//! - **failover**: Falls back to secondary providers when the primary errors
+
+mod failover;
+mod openrouter;
+
+pub use failover::{FailoverLlmProvider, ProviderHealth};
+pub use openrouter::OpenRouterProvider;
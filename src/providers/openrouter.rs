@@ -0,0 +1,133 @@
+//! OpenRouter provider
+//!
+//! Adapts [`OpenRouterClient`] to `LlmProvider`, pinned to the client's
+//! default model. `OpenRouterClient::from_provider_config` builds one of
+//! these per entry in `provider.failover.fallback_models` and chains them
+//! with [`FailoverLlmProvider`](super::FailoverLlmProvider).
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::agent::{self, OpenRouterClient};
+use crate::core::provider::{LlmStream, ModelInfo, ProviderMeta, UsageStats};
+use crate::core::{GenerationOptions, LlmProvider, LlmResponse, Message};
+use crate::error::{Error, Result};
+
+/// `LlmProvider` backed by an OpenRouter client and one model
+pub struct OpenRouterProvider {
+    client: OpenRouterClient,
+    meta: ProviderMeta,
+}
+
+impl OpenRouterProvider {
+    /// Wrap `client`; requests go to its default model
+    pub fn new(client: OpenRouterClient) -> Self {
+        let meta = ProviderMeta {
+            id: format!("openrouter:{}", client.default_model()),
+            name: format!("OpenRouter ({})", client.default_model()),
+            description: "OpenRouter chat completions API".to_string(),
+            base_url: client.base_url().to_string(),
+            supports_streaming: true,
+            supports_tools: true,
+            supports_vision: true,
+        };
+        OpenRouterProvider { client, meta }
+    }
+}
+
+/// Convert between the core and agent wire types, which share a JSON shape
+fn convert<A: Serialize, B: DeserializeOwned>(value: &A) -> Result<B> {
+    Ok(serde_json::from_value(serde_json::to_value(value)?)?)
+}
+
+fn agent_options(options: &GenerationOptions) -> agent::GenerationOptions {
+    agent::GenerationOptions {
+        max_tokens: options.max_tokens,
+        temperature: options.temperature,
+        top_p: options.top_p,
+        stop: options.stop.clone(),
+        stream: options.stream,
+        ..Default::default()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenRouterProvider {
+    fn meta(&self) -> &ProviderMeta {
+        &self.meta
+    }
+
+    fn default_model(&self) -> &str {
+        self.client.default_model()
+    }
+
+    async fn list_models(&self) -> Result<Vec<ModelInfo>> {
+        // OpenRouter prices are per token; `ModelInfo` wants per million
+        let per_million = |price: &str| price.parse::<f64>().ok().map(|p| p * 1_000_000.0);
+        Ok(self
+            .client
+            .list_models()
+            .await?
+            .into_iter()
+            .map(|model| ModelInfo {
+                input_price: per_million(&model.pricing.prompt),
+                output_price: per_million(&model.pricing.completion),
+                description: Some(model.description).filter(|d| !d.is_empty()),
+                context_length: Some(model.context_length),
+                id: model.id,
+                name: model.name,
+            })
+            .collect())
+    }
+
+    async fn generate(
+        &self,
+        messages: &[Message],
+        options: &GenerationOptions,
+    ) -> Result<LlmResponse> {
+        let messages: Vec<agent::Message> = convert(&messages)?;
+        let response = match options.tools {
+            Some(ref tools) => {
+                self.client
+                    .chat_with_tools(messages, convert(tools)?, agent_options(options))
+                    .await?
+            }
+            None => self.client.chat(messages, agent_options(options)).await?,
+        };
+
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::OpenRouter("Response has no choices".to_string()))?;
+        Ok(LlmResponse {
+            id: response.id,
+            model: response.model,
+            content: choice.message.content,
+            finish_reason: choice.finish_reason,
+            tool_calls: choice.message.tool_calls.as_ref().map(convert).transpose()?,
+            usage: response.usage.map(|u| UsageStats {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+                total_tokens: u.total_tokens,
+            }),
+        })
+    }
+
+    async fn generate_stream(
+        &self,
+        messages: &[Message],
+        options: &GenerationOptions,
+    ) -> Result<LlmStream> {
+        let messages: Vec<agent::Message> = convert(&messages)?;
+        Ok(match options.tools {
+            Some(ref tools) => self
+                .client
+                .stream_chat_with_tools(messages, convert(tools)?, agent_options(options))
+                .boxed(),
+            None => self.client.stream_chat(messages, agent_options(options)).boxed(),
+        })
+    }
+}
@@ -2,22 +2,50 @@
 //!
 //! Provides isolated execution in ephemeral containers with network isolation.
 //! This offers strong security guarantees for complex, environment-dependent tasks.
+//!
+//! Java is compiled and run in separate containers, so compiler errors are
+//! reported as such (`ExecutionResult::compile_error`). Compiled classes are
+//! kept in a named volume keyed by source hash, so rerunning the same code
+//! skips `javac`.
+//!
+//! [`ExecutionSession`] trades some of that isolation for state: it keeps one
+//! named container running and executes each request in it with
+//! `docker exec`, so installed packages and files persist between calls.
 
 use async_trait::async_trait;
 use bollard::container::{
-    Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
+    AttachContainerOptions, Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions,
     StartContainerOptions, WaitContainerOptions,
 };
+use bollard::exec::{CreateExecOptions, StartExecResults};
 use bollard::image::CreateImageOptions;
 use bollard::Docker;
 use futures::StreamExt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::pin::Pin;
 use std::time::Instant;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::{debug, info, warn};
 
 use crate::config::ContainerConfig;
 use crate::error::{Error, Result};
 use crate::sandbox::executor::{CodeExecutor, ExecutionRequest, ExecutionResult, Language};
 
+/// Environment variable carrying source code for languages run from a file
+const CODE_ENV: &str = "OPENAGENT_CODE";
+
+/// Directory file-based code is written to and run from inside the container
+const CODE_DIR: &str = "/tmp/app";
+
+/// Prefix of the container names used by [`ExecutionSession`]
+const SESSION_PREFIX: &str = "openagent-session-";
+
+/// Named volume caching compiled Java classes, one directory per source
+const JAVA_CACHE_VOLUME: &str = "openagent-java-classes";
+
+/// Where [`JAVA_CACHE_VOLUME`] is mounted
+const JAVA_CACHE_DIR: &str = "/cache";
+
 /// Docker container executor
 pub struct ContainerExecutor {
     /// Docker client
@@ -45,30 +73,29 @@ impl ContainerExecutor {
             config: config.clone(),
         };
 
-        // Ensure image is available
-        executor.ensure_image().await?;
+        // Ensure the default image is available; language images are pulled on first use
+        executor.ensure_image(&executor.config.image).await?;
 
         Ok(executor)
     }
 
-    /// Ensure the required Docker image is available
-    async fn ensure_image(&self) -> Result<()> {
+    /// Ensure a Docker image is available, pulling it if needed
+    async fn ensure_image(&self, target_image: &str) -> Result<()> {
         let images = self
             .docker
             .list_images::<String>(None)
             .await
             .map_err(|e| Error::Container(format!("Failed to list images: {}", e)))?;
 
-        let target_image = &self.config.image;
         let image_exists = images.iter().any(|img| {
             img.repo_tags.iter().any(|tag| tag.contains(target_image))
         });
 
         if !image_exists {
-            info!("Pulling Docker image: {}", self.config.image);
+            info!("Pulling Docker image: {}", target_image);
 
             let options = CreateImageOptions {
-                from_image: self.config.image.clone(),
+                from_image: target_image.to_string(),
                 ..Default::default()
             };
 
@@ -93,6 +120,15 @@ impl ContainerExecutor {
         Ok(())
     }
 
+    /// Docker image for a language
+    fn image_for(&self, language: Language) -> &str {
+        match language {
+            Language::Go => &self.config.go_image,
+            Language::Java => &self.config.java_image,
+            _ => &self.config.image,
+        }
+    }
+
     /// Get the command for a language
     fn get_command(&self, language: Language, code: &str) -> Vec<String> {
         match language {
@@ -123,10 +159,19 @@ impl ContainerExecutor {
                 "/tmp/program".to_string(),
                 "-".to_string(),
             ],
-            Language::Go => vec![
-                "go".to_string(),
-                "run".to_string(),
-                "-".to_string(),
+            // Code arrives in $OPENAGENT_CODE (see `run_container`); a go.mod is
+            // created if missing so `go run .` works in module mode
+            Language::Go => run_from_file(
+                "main.go",
+                "{ [ -f go.mod ] || go mod init main >/dev/null 2>&1; } && go run .",
+            ),
+            Language::Kotlin => run_from_file("main.main.kts", "kotlinc -script main.main.kts"),
+            // One-off runs compile separately (see `run_java`); this is for sessions
+            Language::Java => run_from_file("Main.java", "javac Main.java && java Main"),
+            Language::Ruby => vec![
+                "ruby".to_string(),
+                "-e".to_string(),
+                code.to_string(),
             ],
         }
     }
@@ -135,35 +180,92 @@ impl ContainerExecutor {
     async fn run_container(
         &self,
         request: &ExecutionRequest,
+    ) -> Result<ExecutionResult> {
+        if request.language == Language::Java {
+            return self.run_java(request).await;
+        }
+        let cmd = self.get_command(request.language, &request.code);
+        self.run_in_container(request, cmd, Vec::new()).await
+    }
+
+    /// Compile `Main.java` in one container, then run `Main` in another
+    ///
+    /// Classes go to a directory of [`JAVA_CACHE_VOLUME`] named after a hash
+    /// of the image and source, which the run container mounts read-only.
+    /// A compile failure is returned with `compile_error` set.
+    async fn run_java(&self, request: &ExecutionRequest) -> Result<ExecutionResult> {
+        let start = Instant::now();
+        let classes = format!(
+            "{}/{}",
+            JAVA_CACHE_DIR,
+            source_hash(&self.config.java_image, &request.code)
+        );
+
+        let compile = ExecutionRequest {
+            stdin: None,
+            ..request.clone()
+        };
+        let binds = vec![format!("{}:{}", JAVA_CACHE_VOLUME, JAVA_CACHE_DIR)];
+        let compiled = self
+            .run_in_container(&compile, java_compile_command(&classes), binds)
+            .await?;
+        if compiled.timed_out {
+            return Ok(compiled);
+        }
+        if !compiled.success {
+            debug!("Java compilation failed");
+            return Ok(ExecutionResult {
+                compile_error: true,
+                ..compiled
+            });
+        }
+
+        let run = ExecutionRequest {
+            timeout: request.timeout.saturating_sub(start.elapsed()),
+            ..request.clone()
+        };
+        let cmd = vec!["java".to_string(), "-cp".to_string(), classes, "Main".to_string()];
+        let binds = vec![format!("{}:{}:ro", JAVA_CACHE_VOLUME, JAVA_CACHE_DIR)];
+        let mut result = self.run_in_container(&run, cmd, binds).await?;
+        if !result.timed_out {
+            result.execution_time = start.elapsed();
+        }
+        Ok(result)
+    }
+
+    /// Run `cmd` in a new container of the request's image, with `binds`
+    /// (`volume:path[:ro]`) mounted
+    async fn run_in_container(
+        &self,
+        request: &ExecutionRequest,
+        cmd: Vec<String>,
+        binds: Vec<String>,
     ) -> Result<ExecutionResult> {
         let start = Instant::now();
 
         let container_name = format!("openagent-exec-{}", uuid::Uuid::new_v4());
-        let cmd = self.get_command(request.language, &request.code);
 
-        // Prepare environment variables
-        let env: Vec<String> = request
-            .env
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect();
+        let mut host_config = self.host_config();
+        if !binds.is_empty() {
+            host_config.binds = Some(binds);
+        }
 
-        // Parse memory limit
-        let memory = parse_memory_limit(&self.config.memory_limit);
+        let image = self.image_for(request.language);
+        if image != self.config.image {
+            self.ensure_image(image).await?;
+        }
 
         // Container configuration
         let container_config = Config {
-            image: Some(self.config.image.clone()),
+            image: Some(image.to_string()),
             cmd: Some(cmd),
-            env: Some(env),
+            env: Some(request_env(request)),
             network_disabled: Some(self.config.network == "none"),
-            host_config: Some(bollard::service::HostConfig {
-                memory,
-                nano_cpus: Some((self.config.cpu_limit * 1_000_000_000.0) as i64),
-                network_mode: Some(self.config.network.clone()),
-                auto_remove: Some(false), // We'll remove manually after getting logs
-                ..Default::default()
-            }),
+            // Keep stdin open until the attached writer closes it
+            attach_stdin: Some(request.stdin.is_some()),
+            open_stdin: Some(request.stdin.is_some()),
+            stdin_once: Some(request.stdin.is_some()),
+            host_config: Some(host_config),
             ..Default::default()
         };
 
@@ -180,17 +282,28 @@ impl ContainerExecutor {
 
         debug!("Created container: {}", container_name);
 
+        // Attach before starting so no input is lost
+        let stdin = match &request.stdin {
+            Some(data) => Some((self.attach_stdin(&container_name).await?, data.as_bytes())),
+            None => None,
+        };
+
         // Start container
         self.docker
             .start_container(&container_name, None::<StartContainerOptions<String>>)
             .await
             .map_err(|e| Error::Container(format!("Failed to start container: {}", e)))?;
 
-        // Wait for container with timeout
-        let wait_result = tokio::time::timeout(
-            request.timeout,
-            self.wait_for_container(&container_name),
-        )
+        // Feed stdin and wait for container with timeout
+        let wait_result = tokio::time::timeout(request.timeout, async {
+            if let Some((mut input, data)) = stdin {
+                // A container that exits without reading its input closes the stream early
+                if let Err(e) = write_and_close(&mut input, data).await {
+                    debug!("Container stdin closed early: {}", e);
+                }
+            }
+            self.wait_for_container(&container_name).await
+        })
         .await;
 
         let execution_time = start.elapsed();
@@ -210,6 +323,9 @@ impl ContainerExecutor {
                 execution_time,
                 timed_out: false,
                 memory_used: None,
+                fuel_exhausted: false,
+                fuel_used: None,
+                compile_error: false,
             }),
             Ok(Err(e)) => Ok(ExecutionResult {
                 success: false,
@@ -219,6 +335,9 @@ impl ContainerExecutor {
                 execution_time,
                 timed_out: false,
                 memory_used: None,
+                fuel_exhausted: false,
+                fuel_used: None,
+                compile_error: false,
             }),
             Err(_) => {
                 warn!("Container execution timed out");
@@ -227,6 +346,122 @@ impl ContainerExecutor {
         }
     }
 
+    /// Resource and network limits from the configuration
+    fn host_config(&self) -> bollard::service::HostConfig {
+        bollard::service::HostConfig {
+            memory: parse_memory_limit(&self.config.memory_limit),
+            nano_cpus: Some((self.config.cpu_limit * 1_000_000_000.0) as i64),
+            network_mode: Some(self.config.network.clone()),
+            auto_remove: Some(false), // We'll remove manually after getting logs
+            ..Default::default()
+        }
+    }
+
+    /// Run a request in an already running container with `docker exec`
+    ///
+    /// Unlike [`execute`](CodeExecutor::execute), nothing is cleaned up:
+    /// files and installed packages stay in the container for later calls.
+    /// The container's image must provide the language's runtime. On
+    /// timeout the process is left running, since Docker cannot stop a
+    /// single exec.
+    pub async fn exec_in_running(
+        &self,
+        container_id: &str,
+        request: ExecutionRequest,
+    ) -> Result<ExecutionResult> {
+        let start = Instant::now();
+
+        let options = CreateExecOptions {
+            cmd: Some(self.get_command(request.language, &request.code)),
+            env: Some(request_env(&request)),
+            working_dir: request.working_dir.clone(),
+            attach_stdin: Some(request.stdin.is_some()),
+            attach_stdout: Some(true),
+            attach_stderr: Some(true),
+            ..Default::default()
+        };
+        let exec = self
+            .docker
+            .create_exec(container_id, options)
+            .await
+            .map_err(|e| Error::Container(format!("Failed to create exec in {}: {}", container_id, e)))?;
+
+        let StartExecResults::Attached { mut output, mut input } = self
+            .docker
+            .start_exec(&exec.id, None)
+            .await
+            .map_err(|e| Error::Container(format!("Failed to start exec in {}: {}", container_id, e)))?
+        else {
+            return Err(Error::Container("Exec started detached".to_string()));
+        };
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let run = tokio::time::timeout(request.timeout, async {
+            if let Some(data) = &request.stdin {
+                if let Err(e) = write_and_close(&mut input, data.as_bytes()).await {
+                    debug!("Exec stdin closed early: {}", e);
+                }
+            }
+            while let Some(chunk) = output.next().await {
+                match chunk {
+                    Ok(LogOutput::StdOut { message }) => stdout.push_str(&String::from_utf8_lossy(&message)),
+                    Ok(LogOutput::StdErr { message }) => stderr.push_str(&String::from_utf8_lossy(&message)),
+                    Ok(_) => {}
+                    Err(e) => return Err(Error::Container(format!("Failed to read exec output: {}", e))),
+                }
+            }
+            Ok(())
+        })
+        .await;
+        let execution_time = start.elapsed();
+
+        match run {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(e),
+            Err(_) => {
+                warn!("Exec in {} timed out", container_id);
+                return Ok(ExecutionResult::timeout(stdout, stderr, request.timeout));
+            }
+        }
+
+        let exit_code = self
+            .docker
+            .inspect_exec(&exec.id)
+            .await
+            .map_err(|e| Error::Container(format!("Failed to inspect exec: {}", e)))?
+            .exit_code
+            .map(|code| code as i32);
+
+        Ok(ExecutionResult {
+            success: exit_code == Some(0),
+            exit_code,
+            stdout,
+            stderr,
+            execution_time,
+            timed_out: false,
+            memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
+        })
+    }
+
+    /// Attach to a created container's stdin, returning a writer for it
+    async fn attach_stdin(&self, name: &str) -> Result<Pin<Box<dyn AsyncWrite + Send>>> {
+        let options = AttachContainerOptions::<String> {
+            stdin: Some(true),
+            stream: Some(true),
+            ..Default::default()
+        };
+        let attached = self
+            .docker
+            .attach_container(name, Some(options))
+            .await
+            .map_err(|e| Error::Container(format!("Failed to attach to container: {}", e)))?;
+        Ok(attached.input)
+    }
+
     /// Wait for a container to finish
     async fn wait_for_container(&self, name: &str) -> Result<i32> {
         let options = WaitContainerOptions {
@@ -293,6 +528,145 @@ impl ContainerExecutor {
     }
 }
 
+/// A named container that stays up between executions
+///
+/// The container runs the configured default image (`sandbox.container.image`)
+/// with the same limits as one-off executions. If a container with the
+/// session's name is already running, it is reused, so a session survives
+/// agent restarts. Call [`close`](Self::close) to remove it.
+pub struct ExecutionSession {
+    executor: ContainerExecutor,
+    container_name: String,
+}
+
+impl ExecutionSession {
+    /// Start the container for `session_name`, or attach to it if running
+    pub async fn new(executor: ContainerExecutor, session_name: &str) -> Result<Self> {
+        if session_name.is_empty()
+            || !session_name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err(Error::InvalidInput(format!(
+                "Invalid session name '{}': use letters, digits, '-', '_' or '.'",
+                session_name
+            )));
+        }
+        let container_name = format!("{}{}", SESSION_PREFIX, session_name);
+
+        let running = match executor.docker.inspect_container(&container_name, None).await {
+            Ok(info) => info.state.and_then(|s| s.running).unwrap_or(false),
+            Err(_) => false,
+        };
+        if running {
+            info!("Reusing running session container {}", container_name);
+        } else {
+            // A stopped leftover would block the name
+            let _ = executor.remove_container(&container_name).await;
+
+            let container_config = Config {
+                image: Some(executor.config.image.clone()),
+                cmd: Some(vec!["sleep".to_string(), "infinity".to_string()]),
+                network_disabled: Some(executor.config.network == "none"),
+                host_config: Some(executor.host_config()),
+                ..Default::default()
+            };
+            let create_options = CreateContainerOptions {
+                name: container_name.as_str(),
+                platform: None,
+            };
+            executor
+                .docker
+                .create_container(Some(create_options), container_config)
+                .await
+                .map_err(|e| Error::Container(format!("Failed to create session container: {}", e)))?;
+            executor
+                .docker
+                .start_container(&container_name, None::<StartContainerOptions<String>>)
+                .await
+                .map_err(|e| Error::Container(format!("Failed to start session container: {}", e)))?;
+            info!("Started session container {}", container_name);
+        }
+
+        Ok(ExecutionSession {
+            executor,
+            container_name,
+        })
+    }
+
+    /// Name of the session's container
+    pub fn container_name(&self) -> &str {
+        &self.container_name
+    }
+
+    /// Run a request in the session's container
+    pub async fn execute(&self, request: ExecutionRequest) -> Result<ExecutionResult> {
+        self.executor.exec_in_running(&self.container_name, request).await
+    }
+
+    /// Stop and remove the container, discarding its state
+    pub async fn close(self) -> Result<()> {
+        self.executor.remove_container(&self.container_name).await
+    }
+}
+
+/// Environment for a request: its own variables, plus the code itself for
+/// languages run from a file (see [`run_from_file`])
+fn request_env(request: &ExecutionRequest) -> Vec<String> {
+    let mut env: Vec<String> = request
+        .env
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect();
+    if matches!(request.language, Language::Go | Language::Kotlin | Language::Java) {
+        env.push(format!("{}={}", CODE_ENV, request.code));
+    }
+    env
+}
+
+/// Shell command that writes $OPENAGENT_CODE to `file_name` in [`CODE_DIR`]
+/// and then runs `run` from that directory
+fn run_from_file(file_name: &str, run: &str) -> Vec<String> {
+    vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        format!(
+            "mkdir -p {dir} && cd {dir} && printf '%s' \"${var}\" > {file_name} && {run}",
+            dir = CODE_DIR,
+            var = CODE_ENV,
+        ),
+    ]
+}
+
+/// Shell command that compiles $OPENAGENT_CODE as `Main.java` into the
+/// `classes` directory, unless it is already there
+///
+/// javac writes to a uniquely named sibling that is then renamed, so a
+/// concurrent run of the same code never sees a half-written directory.
+fn java_compile_command(classes: &str) -> Vec<String> {
+    let partial = format!("{}.{}", classes, uuid::Uuid::new_v4().simple());
+    run_from_file(
+        "Main.java",
+        &format!(
+            "{{ [ -f {classes}/Main.class ] && exit 0; }}; \
+             javac -d {partial} Main.java || {{ status=$?; rm -rf {partial}; exit $status; }}; \
+             [ -d {classes} ] || mv {partial} {classes}; rm -rf {partial}",
+        ),
+    )
+}
+
+/// Cache key for compiled code: the image and source, hashed
+fn source_hash(image: &str, code: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    image.hash(&mut hasher);
+    code.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Write all of `data` to an attached stdin, then close it so the process sees EOF
+async fn write_and_close(input: &mut Pin<Box<dyn AsyncWrite + Send>>, data: &[u8]) -> std::io::Result<()> {
+    input.write_all(data).await?;
+    input.shutdown().await
+}
+
 /// Parse a memory limit string (e.g., "512m", "1g") to bytes
 fn parse_memory_limit(limit: &str) -> Option<i64> {
     let limit = limit.to_lowercase();
@@ -332,6 +706,9 @@ impl CodeExecutor for ContainerExecutor {
                 | Language::Bash
                 | Language::Rust
                 | Language::Go
+                | Language::Kotlin
+                | Language::Java
+                | Language::Ruby
         )
     }
 
@@ -343,6 +720,9 @@ impl CodeExecutor for ContainerExecutor {
             Language::Bash,
             Language::Rust,
             Language::Go,
+            Language::Kotlin,
+            Language::Java,
+            Language::Ruby,
         ]
     }
 
@@ -362,4 +742,85 @@ mod tests {
         assert_eq!(parse_memory_limit("1024k"), Some(1024 * 1024));
         assert_eq!(parse_memory_limit("1024"), Some(1024));
     }
+
+    #[tokio::test]
+    async fn test_cat_from_stdin() {
+        let Ok(executor) = ContainerExecutor::new(&ContainerConfig::default()).await else {
+            return; // Docker not available
+        };
+
+        let request = ExecutionRequest::new("cat", Language::Bash).with_stdin("hello from stdin\n");
+        let result = executor.execute(request).await.unwrap();
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "hello from stdin\n");
+    }
+
+    #[tokio::test]
+    async fn test_session_keeps_files_between_executions() {
+        let Ok(executor) = ContainerExecutor::new(&ContainerConfig::default()).await else {
+            return; // Docker not available
+        };
+        assert!(ExecutionSession::new(executor, "bad name").await.is_err());
+
+        let executor = ContainerExecutor::new(&ContainerConfig::default()).await.unwrap();
+        let session = ExecutionSession::new(executor, &format!("test-{}", uuid::Uuid::new_v4()))
+            .await
+            .unwrap();
+        let write = session
+            .execute(ExecutionRequest::new("echo kept > /tmp/state.txt", Language::Bash))
+            .await
+            .unwrap();
+        assert!(write.success, "stderr: {}", write.stderr);
+
+        let read = session
+            .execute(ExecutionRequest::new("cat /tmp/state.txt; exit 3", Language::Bash))
+            .await
+            .unwrap();
+        assert_eq!(read.stdout, "kept\n");
+        assert_eq!(read.exit_code, Some(3));
+        session.close().await.unwrap();
+    }
+
+    #[test]
+    fn test_run_from_file() {
+        let cmd = run_from_file("main.go", "go run .");
+        assert_eq!(cmd[..2], ["sh", "-c"]);
+        assert_eq!(
+            cmd[2],
+            "mkdir -p /tmp/app && cd /tmp/app && printf '%s' \"$OPENAGENT_CODE\" > main.go && go run ."
+        );
+    }
+
+    #[test]
+    fn test_java_compile_command() {
+        assert_eq!(source_hash("openjdk:21-slim", "class Main {}"), source_hash("openjdk:21-slim", "class Main {}"));
+        assert_ne!(source_hash("openjdk:21-slim", "class Main {}"), source_hash("openjdk:17-slim", "class Main {}"));
+
+        let cmd = java_compile_command("/cache/abc");
+        assert!(cmd[2].contains("> Main.java && { [ -f /cache/abc/Main.class ] && exit 0; }"));
+        assert!(cmd[2].contains("javac -d /cache/abc."));
+    }
+
+    #[tokio::test]
+    async fn test_java_compile_and_runtime_errors() {
+        let Ok(executor) = ContainerExecutor::new(&ContainerConfig::default()).await else {
+            return; // Docker not available
+        };
+
+        let hello = "public class Main { public static void main(String[] a) { System.out.println(\"hi\"); } }";
+        let result = executor.execute(ExecutionRequest::new(hello, Language::Java)).await.unwrap();
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "hi\n");
+
+        let broken = executor
+            .execute(ExecutionRequest::new("public class Main { oops }", Language::Java))
+            .await
+            .unwrap();
+        assert!(broken.compile_error && !broken.success);
+
+        let throws = "public class Main { public static void main(String[] a) { throw new RuntimeException(\"boom\"); } }";
+        let failed = executor.execute(ExecutionRequest::new(throws, Language::Java)).await.unwrap();
+        assert!(!failed.success && !failed.compile_error);
+        assert!(failed.stderr.contains("boom"));
+    }
 }
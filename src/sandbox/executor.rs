@@ -14,6 +14,9 @@ pub enum Language {
     TypeScript,
     Rust,
     Go,
+    Kotlin,
+    Java,
+    Ruby,
     Bash,
 }
 
@@ -27,6 +30,9 @@ impl std::str::FromStr for Language {
             "typescript" | "ts" => Ok(Language::TypeScript),
             "rust" | "rs" => Ok(Language::Rust),
             "go" | "golang" => Ok(Language::Go),
+            "kotlin" | "kt" | "kts" => Ok(Language::Kotlin),
+            "java" | "jvm" => Ok(Language::Java),
+            "ruby" | "rb" => Ok(Language::Ruby),
             "bash" | "sh" | "shell" => Ok(Language::Bash),
             _ => Err(crate::Error::InvalidInput(format!(
                 "Unsupported language: {}",
@@ -44,6 +50,9 @@ impl std::fmt::Display for Language {
             Language::TypeScript => write!(f, "typescript"),
             Language::Rust => write!(f, "rust"),
             Language::Go => write!(f, "go"),
+            Language::Kotlin => write!(f, "kotlin"),
+            Language::Java => write!(f, "java"),
+            Language::Ruby => write!(f, "ruby"),
             Language::Bash => write!(f, "bash"),
         }
     }
@@ -129,6 +138,16 @@ pub struct ExecutionResult {
     pub timed_out: bool,
     /// Memory usage in bytes (if available)
     pub memory_used: Option<u64>,
+    /// Was execution stopped because the fuel (CPU) budget ran out?
+    #[serde(default)]
+    pub fuel_exhausted: bool,
+    /// Fuel consumed (WASM only)
+    #[serde(default)]
+    pub fuel_used: Option<u64>,
+    /// Did the code fail to compile? The compiler's output is in `stderr`
+    /// and nothing was run.
+    #[serde(default)]
+    pub compile_error: bool,
 }
 
 impl ExecutionResult {
@@ -142,6 +161,9 @@ impl ExecutionResult {
             execution_time,
             timed_out: false,
             memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
         }
     }
 
@@ -155,6 +177,9 @@ impl ExecutionResult {
             execution_time,
             timed_out: false,
             memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
         }
     }
 
@@ -168,6 +193,9 @@ impl ExecutionResult {
             execution_time: timeout,
             timed_out: true,
             memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
         }
     }
 
@@ -218,6 +246,14 @@ mod tests {
         assert_eq!("python".parse::<Language>().unwrap(), Language::Python);
         assert_eq!("py".parse::<Language>().unwrap(), Language::Python);
         assert_eq!("js".parse::<Language>().unwrap(), Language::JavaScript);
+        assert_eq!("ts".parse::<Language>().unwrap(), Language::TypeScript);
+        assert_eq!(Language::TypeScript.to_string(), "typescript");
+        assert_eq!("golang".parse::<Language>().unwrap(), Language::Go);
+        assert_eq!("kts".parse::<Language>().unwrap(), Language::Kotlin);
+        assert_eq!(Language::Kotlin.to_string(), "kotlin");
+        assert_eq!("jvm".parse::<Language>().unwrap(), Language::Java);
+        assert_eq!(Language::Java.to_string(), "java");
+        assert_eq!("rb".parse::<Language>().unwrap(), Language::Ruby);
         assert!("unknown".parse::<Language>().is_err());
     }
 
@@ -3,14 +3,15 @@
 //! Provides three tiers of execution:
 //! - Sandbox Mode: WebAssembly virtual machine using Wasmtime (recommended)
 //! - OS Mode: Full system access with user authentication (sudo available)
-//! - Container Mode: Ephemeral Docker containers (most secure)
+//! - Container Mode: Ephemeral Docker containers (most secure), or a
+//!   persistent `ExecutionSession` container that keeps state between runs
 
 mod container;
 mod executor;
 mod os_sandbox;
 mod wasm;
 
-pub use container::ContainerExecutor;
+pub use container::{ContainerExecutor, ExecutionSession};
 pub use executor::{CodeExecutor, ExecutionResult, ExecutionRequest, Language};
 pub use os_sandbox::OsSandbox;
 pub use wasm::WasmExecutor;
@@ -23,11 +24,12 @@ pub async fn create_executor(config: &SandboxConfig) -> Result<Box<dyn CodeExecu
     match config.execution_env {
         ExecutionEnv::Os => {
             // OS mode: full system access, no path restrictions
-            let executor = OsSandbox::new_unrestricted(config.allowed_dir.clone());
+            let executor = OsSandbox::new_unrestricted(config.allowed_dir.clone())
+                .with_limits(config.effective_resource_limits());
             Ok(Box::new(executor))
         }
         ExecutionEnv::Sandbox => {
-            let executor = WasmExecutor::new()?;
+            let executor = WasmExecutor::with_config(&config.wasm)?;
             Ok(Box::new(executor))
         }
         ExecutionEnv::Container => {
@@ -3,24 +3,61 @@
 //! Runs code either in a restricted directory (sandbox mode) or with full
 //! system access (OS mode). In OS mode, the agent can execute code anywhere
 //! on the system, similar to a co-worker with full permissions.
+//!
+//! Resource limits: on Linux, memory and CPU time are enforced in the child
+//! with `setrlimit` (`RLIMIT_DATA`, `RLIMIT_CPU`). Other platforms have no
+//! memory cap; CPU time is approximated by shortening the wall-clock timeout.
+//! Output is truncated at `max_output_bytes` per stream on every platform.
+//!
+//! Ruby snippets that `require` gems which are not installed get them
+//! installed first into `<allowed_dir>/gem_home`, which persists across
+//! requests so each gem is only downloaded once.
 
 use async_trait::async_trait;
 use std::path::PathBuf;
 use std::process::Stdio;
-use std::time::Instant;
-use tokio::io::AsyncWriteExt;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
 use tracing::{debug, warn};
 
+use crate::config::SandboxResourceLimits;
 use crate::error::{Error, Result};
 use crate::sandbox::executor::{CodeExecutor, ExecutionRequest, ExecutionResult, Language};
 
+/// Directory under `allowed_dir` that holds gems installed for Ruby snippets
+const GEM_HOME_DIR: &str = "gem_home";
+
+/// Gems whose name differs from the first segment of the features they
+/// provide, as `(feature or feature prefix, gem)`
+const GEM_NAMES: &[(&str, &str)] = &[
+    ("active_support", "activesupport"),
+    ("active_record", "activerecord"),
+    ("active_model", "activemodel"),
+    ("action_view", "actionview"),
+    ("action_controller", "actionpack"),
+    ("concurrent", "concurrent-ruby"),
+    ("mime/types", "mime-types"),
+    ("net/ssh", "net-ssh"),
+    ("net/sftp", "net-sftp"),
+    ("net/scp", "net-scp"),
+    ("rest_client", "rest-client"),
+    ("google/apis", "google-api-client"),
+    ("google/cloud", "google-cloud"),
+    ("dotenv/load", "dotenv"),
+    ("sinatra/base", "sinatra"),
+    ("rspec/autorun", "rspec"),
+    ("openai", "ruby-openai"),
+];
+
 /// OS-level sandbox executor
 pub struct OsSandbox {
     /// Allowed directory for execution
     allowed_dir: PathBuf,
     /// When true, skip path validation and allow execution anywhere
     unrestricted: bool,
+    /// Resource caps applied to each spawned process
+    limits: SandboxResourceLimits,
 }
 
 impl OsSandbox {
@@ -29,6 +66,7 @@ impl OsSandbox {
         OsSandbox {
             allowed_dir,
             unrestricted: false,
+            limits: SandboxResourceLimits::default(),
         }
     }
 
@@ -37,6 +75,23 @@ impl OsSandbox {
         OsSandbox {
             allowed_dir,
             unrestricted: true,
+            limits: SandboxResourceLimits::default(),
+        }
+    }
+
+    /// Set resource limits for spawned processes
+    pub fn with_limits(mut self, limits: SandboxResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Wall-clock timeout for a request. Off Linux there is no CPU rlimit, so
+    /// the CPU budget caps the wall-clock time instead.
+    fn effective_timeout(&self, requested: Duration) -> Duration {
+        if cfg!(target_os = "linux") || self.limits.max_cpu_time_secs == 0 {
+            requested
+        } else {
+            requested.min(Duration::from_secs(self.limits.max_cpu_time_secs))
         }
     }
 
@@ -47,26 +102,156 @@ impl OsSandbox {
             Language::JavaScript => Ok(("node".to_string(), vec!["-e".to_string()])),
             Language::Bash => Ok(("bash".to_string(), vec!["-c".to_string()])),
             Language::TypeScript => {
-                // Use ts-node or deno for TypeScript
-                if which::which("deno").is_ok() {
+                // Type-check-free transpile keeps startup fast; prefer a global
+                // ts-node, then one fetched via npx, then deno
+                if which::which("ts-node").is_ok() {
+                    Ok(("ts-node".to_string(), vec!["--transpile-only".to_string(), "-e".to_string()]))
+                } else if which::which("npx").is_ok() {
+                    Ok((
+                        "npx".to_string(),
+                        vec!["ts-node".to_string(), "--transpile-only".to_string(), "-e".to_string()],
+                    ))
+                } else if which::which("deno").is_ok() {
                     Ok(("deno".to_string(), vec!["eval".to_string()]))
-                } else if which::which("ts-node").is_ok() {
-                    Ok(("ts-node".to_string(), vec!["-e".to_string()]))
                 } else {
                     Err(Error::Sandbox(
-                        "TypeScript runtime not found (deno or ts-node)".to_string(),
+                        "TypeScript runtime not found (ts-node, npx or deno)".to_string(),
                     ))
                 }
             }
+            Language::Go => {
+                if which::which("go").is_err() {
+                    return Err(Error::Sandbox("Go toolchain not found (go)".to_string()));
+                }
+                Ok(("go".to_string(), vec!["run".to_string(), ".".to_string()]))
+            }
+            Language::Kotlin => {
+                // `kotlin` runs .kts scripts directly; older installs only have kotlinc
+                if which::which("kotlin").is_ok() {
+                    Ok(("kotlin".to_string(), Vec::new()))
+                } else if which::which("kotlinc").is_ok() {
+                    Ok(("kotlinc".to_string(), vec!["-script".to_string()]))
+                } else {
+                    Err(Error::Sandbox(
+                        "Kotlin runtime not found (kotlin or kotlinc)".to_string(),
+                    ))
+                }
+            }
+            Language::Ruby => {
+                if which::which("ruby").is_err() {
+                    return Err(Error::Sandbox("Ruby runtime not found (ruby)".to_string()));
+                }
+                Ok(("ruby".to_string(), vec!["-e".to_string()]))
+            }
             Language::Rust => Err(Error::Sandbox(
                 "Rust inline execution not supported in OS mode".to_string(),
             )),
-            Language::Go => Err(Error::Sandbox(
-                "Go inline execution not supported in OS mode".to_string(),
+            Language::Java => Err(Error::Sandbox(
+                "Java is only supported in container mode".to_string(),
             )),
         }
     }
 
+    /// File the code is written to for languages that cannot run it inline
+    fn source_file_name(language: Language) -> Option<&'static str> {
+        match language {
+            Language::Go => Some("main.go"),
+            Language::Kotlin => Some("main.main.kts"),
+            _ => None,
+        }
+    }
+
+    /// Persistent `GEM_HOME` for Ruby snippets
+    fn gem_home(&self) -> PathBuf {
+        self.allowed_dir.join(GEM_HOME_DIR)
+    }
+
+    /// Install the gems for the features `code` requires that `ruby` cannot
+    /// load yet into [`Self::gem_home`]
+    ///
+    /// Returns a failed result with `gem install`'s output if probing or
+    /// installing fails or times out, and `None` once everything is loadable.
+    async fn install_missing_gems(&self, code: &str, timeout: Duration) -> Result<Option<ExecutionResult>> {
+        let features = required_features(code);
+        if features.is_empty() {
+            return Ok(None);
+        }
+        let gem_home = self.gem_home();
+        tokio::fs::create_dir_all(&gem_home).await?;
+
+        // Standard library and already installed gems load without a
+        // download; the probe requires the exact features the code does
+        let probe = features
+            .iter()
+            .map(|feature| format!("begin; require '{0}'; rescue LoadError; puts '{0}'; end", feature))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut command = Command::new("ruby");
+        command
+            .args(["-e", &probe])
+            .env("GEM_HOME", &gem_home)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(target_os = "linux")]
+        apply_rlimits(&mut command, self.limits);
+
+        let output = match tokio::time::timeout(timeout, command.output()).await {
+            Ok(output) => output.map_err(|e| Error::Sandbox(format!("Failed to spawn process: {}", e)))?,
+            Err(_) => {
+                return Ok(Some(ExecutionResult::timeout(
+                    String::new(),
+                    "Checking required gems timed out".to_string(),
+                    timeout,
+                )))
+            }
+        };
+        let mut missing: Vec<String> = Vec::new();
+        for feature in String::from_utf8_lossy(&output.stdout).lines() {
+            let gem = gem_for_feature(feature);
+            if !missing.contains(&gem) {
+                missing.push(gem);
+            }
+        }
+        if missing.is_empty() {
+            return Ok(None);
+        }
+
+        // --install-dir rather than --user-install, which ignores GEM_HOME
+        debug!("Installing gems {} into {}", missing.join(", "), gem_home.display());
+        let start = Instant::now();
+        let mut command = Command::new("gem");
+        command
+            .arg("install")
+            .arg("--no-document")
+            .arg("--install-dir")
+            .arg(&gem_home)
+            .args(&missing)
+            .env("GEM_HOME", &gem_home)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(target_os = "linux")]
+        apply_rlimits(&mut command, self.limits);
+
+        match tokio::time::timeout(timeout, command.output()).await {
+            Ok(Ok(output)) if output.status.success() => Ok(None),
+            Ok(Ok(output)) => Ok(Some(ExecutionResult::failure(
+                format!(
+                    "gem install {} failed:\n{}",
+                    missing.join(" "),
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+                output.status.code().unwrap_or(-1),
+                start.elapsed(),
+            ))),
+            Ok(Err(e)) => Err(Error::Sandbox(format!("Failed to run gem install: {}", e))),
+            Err(_) => Ok(Some(ExecutionResult::timeout(
+                String::new(),
+                format!("gem install {} timed out", missing.join(" ")),
+                timeout,
+            ))),
+        }
+    }
+
     /// Validate that a path is within the allowed directory
     fn validate_path(&self, path: &PathBuf) -> Result<()> {
         // Skip validation in unrestricted mode
@@ -98,7 +283,13 @@ impl CodeExecutor for OsSandbox {
     fn supports_language(&self, language: Language) -> bool {
         matches!(
             language,
-            Language::Python | Language::JavaScript | Language::Bash | Language::TypeScript
+            Language::Python
+                | Language::JavaScript
+                | Language::Bash
+                | Language::TypeScript
+                | Language::Go
+                | Language::Kotlin
+                | Language::Ruby
         )
     }
 
@@ -108,12 +299,15 @@ impl CodeExecutor for OsSandbox {
             Language::JavaScript,
             Language::TypeScript,
             Language::Bash,
+            Language::Go,
+            Language::Kotlin,
+            Language::Ruby,
         ]
     }
 
     async fn execute(&self, request: ExecutionRequest) -> Result<ExecutionResult> {
         // Get command for language
-        let (cmd, args) = self.get_command(request.language)?;
+        let (cmd, mut args) = self.get_command(request.language)?;
 
         // Ensure allowed directory exists
         tokio::fs::create_dir_all(&self.allowed_dir).await?;
@@ -144,84 +338,242 @@ impl CodeExecutor for OsSandbox {
             working_dir.display()
         );
 
+        let timeout = self.effective_timeout(request.timeout);
+        if request.language == Language::Ruby {
+            if let Some(failed) = self.install_missing_gems(&request.code, timeout).await? {
+                return Ok(failed);
+            }
+        }
+
+        // Go and Kotlin run from a source file in a scratch directory
+        let scratch_dir = match Self::source_file_name(request.language) {
+            Some(file_name) => {
+                let dir = write_source(request.language, file_name, &request.code).await?;
+                if request.language == Language::Kotlin {
+                    args.push(dir.join(file_name).to_string_lossy().into_owned());
+                }
+                Some(dir)
+            }
+            None => {
+                args.push(request.code.clone());
+                None
+            }
+        };
+        // `go run .` needs to run inside the scratch module
+        let current_dir = match (&scratch_dir, request.language) {
+            (Some(dir), Language::Go) => dir.clone(),
+            _ => working_dir,
+        };
+
         let start = Instant::now();
 
         // Build command
         let mut command = Command::new(&cmd);
         command
             .args(&args)
-            .arg(&request.code)
-            .current_dir(&working_dir)
+            .current_dir(&current_dir)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        if request.language == Language::Ruby {
+            command.env("GEM_HOME", self.gem_home());
+        }
 
         // Add environment variables
         for (key, value) in &request.env {
             command.env(key, value);
         }
 
+        #[cfg(target_os = "linux")]
+        apply_rlimits(&mut command, self.limits);
+
         // Spawn process
         let mut child = command.spawn().map_err(|e| {
             Error::Sandbox(format!("Failed to spawn process: {}", e))
         })?;
 
-        // Write stdin if provided
-        if let Some(stdin_data) = &request.stdin {
-            if let Some(mut stdin) = child.stdin.take() {
-                stdin.write_all(stdin_data.as_bytes()).await?;
-            }
-        }
-
-        // Wait with timeout
-        let result = tokio::time::timeout(request.timeout, child.wait_with_output()).await;
+        let max_output = self.limits.max_output_bytes;
+        let stdin_pipe = child.stdin.take();
+        let stdout_pipe = child.stdout.take();
+        let stderr_pipe = child.stderr.take();
+        let stdin_data = request.stdin.as_deref().unwrap_or_default().as_bytes();
+
+        // Wait with timeout, feeding stdin while draining both output pipes so
+        // neither side blocks on a full buffer
+        let result = tokio::time::timeout(timeout, async {
+            let (written, stdout, stderr, status) = tokio::join!(
+                write_stdin(stdin_pipe, stdin_data),
+                read_capped(stdout_pipe, max_output),
+                read_capped(stderr_pipe, max_output),
+                child.wait(),
+            );
+            written?;
+            Ok::<_, std::io::Error>((stdout?, stderr?, status?))
+        })
+        .await;
 
         let execution_time = start.elapsed();
 
+        if let Some(dir) = scratch_dir {
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                warn!("Failed to remove {}: {}", dir.display(), e);
+            }
+        }
+
         match result {
-            Ok(Ok(output)) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                let exit_code = output.status.code().unwrap_or(-1);
-
-                if output.status.success() {
-                    Ok(ExecutionResult {
-                        success: true,
-                        exit_code: Some(exit_code),
-                        stdout,
-                        stderr,
-                        execution_time,
-                        timed_out: false,
-                        memory_used: None,
-                    })
-                } else {
-                    Ok(ExecutionResult {
-                        success: false,
-                        exit_code: Some(exit_code),
-                        stdout,
-                        stderr,
-                        execution_time,
-                        timed_out: false,
-                        memory_used: None,
-                    })
-                }
+            Ok(Ok((stdout, stderr, status))) => {
+                let exit_code = status.code().unwrap_or(-1);
+
+                Ok(ExecutionResult {
+                    success: status.success(),
+                    exit_code: Some(exit_code),
+                    stdout,
+                    stderr,
+                    execution_time,
+                    timed_out: false,
+                    memory_used: None,
+                    fuel_exhausted: false,
+                    fuel_used: None,
+                    compile_error: false,
+                })
             }
             Ok(Err(e)) => Err(Error::Sandbox(format!("Process error: {}", e))),
             Err(_) => {
-                // Timeout - try to kill the process
-                warn!("Execution timed out after {:?}", request.timeout);
+                warn!("Execution timed out after {:?}", timeout);
 
-                // Note: child is dropped here, which should kill the process
+                // kill_on_drop terminates the child when it goes out of scope
                 Ok(ExecutionResult::timeout(
                     String::new(),
                     "Execution timed out".to_string(),
-                    request.timeout,
+                    timeout,
                 ))
             }
         }
     }
 }
 
+/// Write `data` to the child's stdin and close it so the child sees EOF
+///
+/// A child that exits without reading all of its input closes the pipe
+/// early; that is not an error.
+async fn write_stdin(pipe: Option<tokio::process::ChildStdin>, data: &[u8]) -> std::io::Result<()> {
+    let Some(mut pipe) = pipe else {
+        return Ok(());
+    };
+    match pipe.write_all(data).await {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Install `setrlimit` calls that run in the child between fork and exec
+#[cfg(target_os = "linux")]
+fn apply_rlimits(command: &mut Command, limits: SandboxResourceLimits) {
+    use nix::sys::resource::{setrlimit, Resource};
+
+    // RLIMIT_DATA rather than RLIMIT_AS: runtimes like V8 reserve large
+    // PROT_NONE address ranges up front, which RLIMIT_AS would count.
+    let memory = limits.max_memory_bytes;
+    let cpu = limits.max_cpu_time_secs;
+
+    // SAFETY: the closure only issues setrlimit syscalls, which are
+    // async-signal-safe, and does not allocate.
+    unsafe {
+        command.pre_exec(move || {
+            if memory > 0 {
+                setrlimit(Resource::RLIMIT_DATA, memory, memory)?;
+            }
+            if cpu > 0 {
+                setrlimit(Resource::RLIMIT_CPU, cpu, cpu)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Read a stream to the end, keeping at most `max` bytes (0 = unlimited)
+async fn read_capped<R: AsyncRead + Unpin>(reader: Option<R>, max: usize) -> std::io::Result<String> {
+    let Some(mut reader) = reader else {
+        return Ok(String::new());
+    };
+
+    let mut kept = Vec::new();
+    let mut total = 0usize;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        total += n;
+        let room = if max == 0 { n } else { max.saturating_sub(kept.len()).min(n) };
+        kept.extend_from_slice(&buf[..room]);
+    }
+
+    let mut out = String::from_utf8_lossy(&kept).into_owned();
+    if total > kept.len() {
+        out.push_str(&format!("\n[output truncated: {} of {} bytes shown]", kept.len(), total));
+    }
+    Ok(out)
+}
+
+/// Features named by the `require` statements in Ruby `code`, in order
+///
+/// Features are kept whole (`require 'net/http'` gives `net/http`);
+/// `require_relative` and dynamic requires are ignored.
+fn required_features(code: &str) -> Vec<String> {
+    let mut features: Vec<String> = Vec::new();
+    for line in code.lines().map(str::trim_start) {
+        let Some(rest) = line.strip_prefix("require ").or_else(|| line.strip_prefix("require(")) else {
+            continue;
+        };
+        let Some(quoted) = rest.trim_start().strip_prefix(['\'', '"']) else {
+            continue;
+        };
+        let name: String = quoted
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/' | '.'))
+            .collect();
+        if !name.is_empty() && !name.starts_with(['/', '.']) && !features.contains(&name) {
+            features.push(name);
+        }
+    }
+    features
+}
+
+/// Gem that provides `feature`: the [`GEM_NAMES`] entry for it or its
+/// longest listed prefix, otherwise the feature's first path segment
+fn gem_for_feature(feature: &str) -> String {
+    GEM_NAMES
+        .iter()
+        .filter(|(prefix, _)| {
+            feature == *prefix
+                || feature.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, gem)| gem.to_string())
+        .unwrap_or_else(|| feature.split('/').next().unwrap_or(feature).to_string())
+}
+
+/// Write `code` to `file_name` in a fresh scratch directory. For Go, a
+/// minimal `go.mod` is added so `go run .` works in module mode.
+async fn write_source(language: Language, file_name: &str, code: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("openagent-{}-{}", language, uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&dir).await?;
+    tokio::fs::write(dir.join(file_name), code).await?;
+
+    if language == Language::Go {
+        let go_mod = dir.join("go.mod");
+        if !tokio::fs::try_exists(&go_mod).await? {
+            tokio::fs::write(&go_mod, "module main\n\ngo 1.21\n").await?;
+        }
+    }
+
+    Ok(dir)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,6 +592,99 @@ mod tests {
         assert!(result.stdout.contains("Hello, World!"));
     }
 
+    #[tokio::test]
+    async fn test_typescript_execution() {
+        // npx would try to download ts-node, so only run with a local install
+        if which::which("ts-node").is_err() {
+            return;
+        }
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf());
+
+        let code = "const greet = (name: string): string => `Hello, ${name}!`; console.log(greet('TS'));";
+        let request = ExecutionRequest::new(code, Language::TypeScript)
+            .with_timeout(Duration::from_secs(30));
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert!(result.stdout.contains("Hello, TS!"));
+    }
+
+    #[tokio::test]
+    async fn test_go_execution() {
+        if which::which("go").is_err() {
+            return;
+        }
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf());
+
+        let code = "package main\n\nimport \"fmt\"\n\nfunc main() { fmt.Println(\"Hello, Go!\") }\n";
+        let request = ExecutionRequest::new(code, Language::Go)
+            .with_timeout(Duration::from_secs(60));
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert!(result.stdout.contains("Hello, Go!"));
+    }
+
+    #[test]
+    fn test_required_features() {
+        let code = "require 'json'\nrequire \"net/http\"\nrequire_relative 'helper'\n  require('json')\nputs 1";
+        assert_eq!(required_features(code), vec!["json", "net/http"]);
+        assert!(required_features("puts 'require \"x\"'").is_empty());
+        assert!(required_features("require '/etc/passwd'").is_empty());
+    }
+
+    #[test]
+    fn test_gem_for_feature() {
+        assert_eq!(gem_for_feature("nokogiri"), "nokogiri");
+        assert_eq!(gem_for_feature("active_support/core_ext/string"), "activesupport");
+        assert_eq!(gem_for_feature("net/ssh"), "net-ssh");
+        assert_eq!(gem_for_feature("aws-sdk-s3"), "aws-sdk-s3");
+        assert_eq!(gem_for_feature("httparty/request"), "httparty");
+    }
+
+    #[tokio::test]
+    async fn test_ruby_execution() {
+        if which::which("ruby").is_err() {
+            return;
+        }
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf());
+
+        let request = ExecutionRequest::new("puts [1, 2, 3].sum", Language::Ruby);
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "6\n");
+    }
+
+    #[tokio::test]
+    async fn test_ruby_require_json() {
+        if which::which("ruby").is_err() {
+            return;
+        }
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf());
+
+        // json ships with Ruby, so nothing is downloaded
+        let code = "require 'json'\n\nputs JSON.generate({ 'a' => 1 })\n";
+        let request = ExecutionRequest::new(code, Language::Ruby).with_timeout(Duration::from_secs(60));
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, "{\"a\":1}\n");
+        assert!(dir.path().join(GEM_HOME_DIR).is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_write_go_source() {
+        let dir = write_source(Language::Go, "main.go", "package main").await.unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("main.go")).unwrap(), "package main");
+        assert!(std::fs::read_to_string(dir.join("go.mod")).unwrap().starts_with("module main"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_timeout() {
         let dir = tempdir().unwrap();
@@ -253,4 +698,75 @@ mod tests {
         assert!(!result.success);
         assert!(result.timed_out);
     }
+
+    #[tokio::test]
+    async fn test_cat_from_stdin() {
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf());
+
+        let request = ExecutionRequest::new("cat", Language::Bash).with_stdin("hello from stdin\n");
+        let result = sandbox.execute(request).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout, "hello from stdin\n");
+
+        // Larger than a pipe buffer, and the child stops reading early
+        let request = ExecutionRequest::new("head -c 5", Language::Bash).with_stdin("y".repeat(1024 * 1024));
+        let result = sandbox.execute(request).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout, "yyyyy");
+    }
+
+    #[tokio::test]
+    async fn test_output_truncated() {
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf()).with_limits(SandboxResourceLimits {
+            max_output_bytes: 100,
+            ..Default::default()
+        });
+
+        let request = ExecutionRequest::new("print('x' * 10000)", Language::Python);
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(result.success);
+        assert!(result.stdout.starts_with(&"x".repeat(100)));
+        assert!(result.stdout.contains("[output truncated: 100 of 10001 bytes shown]"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_memory_hungry_process_is_stopped() {
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf()).with_limits(SandboxResourceLimits {
+            max_memory_bytes: 128 * 1024 * 1024,
+            ..Default::default()
+        });
+
+        // Try to allocate 1GB against a 128MB cap
+        let request = ExecutionRequest::new("x = bytearray(1024 * 1024 * 1024)", Language::Python)
+            .with_timeout(Duration::from_secs(10));
+        let result = sandbox.execute(request).await.unwrap();
+
+        assert!(!result.success);
+        assert!(!result.timed_out);
+        assert!(result.stderr.contains("MemoryError"), "stderr: {}", result.stderr);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_cpu_limit_kills_process() {
+        let dir = tempdir().unwrap();
+        let sandbox = OsSandbox::new(dir.path().to_path_buf()).with_limits(SandboxResourceLimits {
+            max_cpu_time_secs: 1,
+            ..Default::default()
+        });
+
+        let request = ExecutionRequest::new("while True: pass", Language::Python)
+            .with_timeout(Duration::from_secs(10));
+        let result = sandbox.execute(request).await.unwrap();
+
+        // Killed by SIGXCPU well before the wall-clock timeout
+        assert!(!result.success);
+        assert!(!result.timed_out);
+        assert_eq!(result.exit_code, Some(-1));
+    }
 }
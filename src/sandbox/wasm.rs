@@ -2,32 +2,94 @@
 //!
 //! Provides high-security code execution in a zero-access virtual machine.
 //! This is the recommended execution environment.
+//!
+//! Modules get no host access beyond WASI `fd_read` on stdin and `fd_write`
+//! on stdout/stderr, all backed by in-memory buffers.
 
 use async_trait::async_trait;
+use std::io::{Cursor, Read};
 use std::time::{Duration, Instant};
 use tracing::{debug, info};
 use wasmtime::*;
 
+use crate::config::WasmConfig;
 use crate::error::{Error, Result};
 use crate::sandbox::executor::{CodeExecutor, ExecutionRequest, ExecutionResult, Language};
 
+/// Size of a WebAssembly linear memory page
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+/// Interval at which the engine's epoch advances; execution timeouts are
+/// enforced with this granularity
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// WASI module the stdio imports are provided under
+const WASI_MODULE: &str = "wasi_snapshot_preview1";
+
+/// WASI errno values returned by the stdio imports
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_BADF: i32 = 8;
+const ERRNO_FAULT: i32 = 21;
+
+/// Per-execution store data: memory limits and in-memory stdio
+struct WasmState {
+    limits: StoreLimits,
+    stdin: Cursor<Vec<u8>>,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
 /// WebAssembly executor using Wasmtime
 pub struct WasmExecutor {
     /// Wasmtime engine
     engine: Engine,
+    /// Fuel granted to each execution (roughly one unit per instruction)
+    fuel_limit: u64,
+    /// Maximum linear memory per execution in bytes
+    max_memory_bytes: usize,
 }
 
 impl WasmExecutor {
-    /// Create a new Wasm executor
+    /// Create a new Wasm executor with default limits
     pub fn new() -> Result<Self> {
+        Self::with_config(&WasmConfig::default())
+    }
+
+    /// Create a Wasm executor with the fuel and memory limits from `config`
+    pub fn with_config(wasm_config: &WasmConfig) -> Result<Self> {
+        let max_memory_bytes = wasm_config.max_memory_pages as usize * WASM_PAGE_SIZE;
+
         let mut config = Config::new();
         config.wasm_backtrace_details(WasmBacktraceDetails::Enable);
         config.consume_fuel(true); // Enable fuel for limiting execution
+        config.epoch_interruption(true); // Wall-clock timeouts
+        // Formerly `static_memory_maximum_size`: memories up to the limit are
+        // reserved statically. Growth beyond it is refused by `StoreLimits`.
+        config.memory_reservation(max_memory_bytes as u64);
 
         let engine = Engine::new(&config)?;
 
-        info!("Wasm executor initialized");
-        Ok(WasmExecutor { engine })
+        // Advance the epoch until the executor's engine is dropped
+        let ticker = engine.weak();
+        std::thread::Builder::new()
+            .name("wasm-epoch".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(EPOCH_TICK);
+                match ticker.upgrade() {
+                    Some(engine) => engine.increment_epoch(),
+                    None => break,
+                }
+            })?;
+
+        info!(
+            "Wasm executor initialized (fuel_limit={}, max_memory={} bytes)",
+            wasm_config.fuel_limit, max_memory_bytes
+        );
+        Ok(WasmExecutor {
+            engine,
+            fuel_limit: wasm_config.fuel_limit,
+            max_memory_bytes,
+        })
     }
 
     /// Execute Python code using a Python WASM runtime
@@ -47,6 +109,9 @@ impl WasmExecutor {
             execution_time: Duration::from_millis(0),
             timed_out: false,
             memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
         })
     }
 
@@ -66,10 +131,31 @@ impl WasmExecutor {
             execution_time: Duration::from_millis(0),
             timed_out: false,
             memory_used: None,
+            fuel_exhausted: false,
+            fuel_used: None,
+            compile_error: false,
         })
     }
 
-    /// Execute a raw WASM module
+    /// Execute TypeScript by transpiling with `swc` and handing the
+    /// result to the JavaScript runner
+    async fn execute_typescript(&self, request: &ExecutionRequest) -> Result<ExecutionResult> {
+        debug!("TypeScript WASM execution requested");
+
+        let start = Instant::now();
+        let js = match transpile_typescript(&request.code).await {
+            Ok(js) => js,
+            Err(e) => return Ok(ExecutionResult::failure(e.to_string(), 1, start.elapsed())),
+        };
+
+        let mut js_request = request.clone();
+        js_request.code = js;
+        js_request.language = Language::JavaScript;
+        self.execute_javascript(&js_request).await
+    }
+
+    /// Execute a raw WASM module under the configured fuel and memory
+    /// limits, stopping it after `timeout` of wall-clock time
     pub async fn execute_wasm_module(
         &self,
         wasm_bytes: &[u8],
@@ -77,21 +163,48 @@ impl WasmExecutor {
         args: &[Val],
         timeout: Duration,
     ) -> Result<ExecutionResult> {
-        let start = Instant::now();
+        self.execute_wasm_module_with_stdin(wasm_bytes, func_name, args, &[], timeout).await
+    }
 
-        // Create a store with fuel limits
-        let mut store = Store::new(&self.engine, ());
+    /// Execute a raw WASM module, feeding `stdin` through WASI `fd_read`
+    ///
+    /// When the module writes to stdout, that output is returned instead of
+    /// the function's results.
+    pub async fn execute_wasm_module_with_stdin(
+        &self,
+        wasm_bytes: &[u8],
+        func_name: &str,
+        args: &[Val],
+        stdin: &[u8],
+        timeout: Duration,
+    ) -> Result<ExecutionResult> {
+        let start = Instant::now();
 
-        // Set fuel limit based on timeout (rough approximation)
-        let fuel = (timeout.as_millis() * 1000) as u64;
-        store.set_fuel(fuel)?;
+        // Create a store with fuel and memory limits
+        let state = WasmState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.max_memory_bytes)
+                .build(),
+            stdin: Cursor::new(stdin.to_vec()),
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(self.fuel_limit)?;
+        let ticks = timeout.as_millis().div_ceil(EPOCH_TICK.as_millis()).max(1);
+        store.set_epoch_deadline(ticks as u64);
+        store.epoch_deadline_trap();
 
         // Compile the module
         let module = Module::new(&self.engine, wasm_bytes)
             .map_err(|e| Error::Wasm(format!("Failed to compile module: {}", e)))?;
 
-        // Create an instance
-        let instance = Instance::new(&mut store, &module, &[])
+        // Create an instance with the stdio imports
+        let mut linker = Linker::new(&self.engine);
+        add_stdio(&mut linker)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
             .map_err(|e| Error::Wasm(format!("Failed to instantiate module: {}", e)))?;
 
         // Get the function
@@ -104,45 +217,172 @@ impl WasmExecutor {
         let mut results = vec![Val::I32(0); result_count];
 
         // Call the function
-        match func.call(&mut store, args, &mut results) {
-            Ok(()) => {
-                let execution_time = start.elapsed();
-                let fuel_consumed = fuel - store.get_fuel().unwrap_or(0);
+        let outcome = func.call(&mut store, args, &mut results);
+        let execution_time = start.elapsed();
+        let fuel_used = self.fuel_limit - store.get_fuel().unwrap_or(0);
+        let memory_used = instance
+            .get_memory(&mut store, "memory")
+            .map(|m| m.data_size(&store) as u64);
+        let state = store.data();
+        let stdout = String::from_utf8_lossy(&state.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&state.stderr).into_owned();
 
+        match outcome {
+            Ok(()) => Ok(ExecutionResult {
+                success: true,
+                exit_code: Some(0),
+                stdout: if stdout.is_empty() { format!("Results: {:?}", results) } else { stdout },
+                stderr,
+                execution_time,
+                timed_out: false,
+                memory_used,
+                fuel_exhausted: false,
+                fuel_used: Some(fuel_used),
+                compile_error: false,
+            }),
+            Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
                 Ok(ExecutionResult {
-                    success: true,
-                    exit_code: Some(0),
-                    stdout: format!("Results: {:?}", results),
-                    stderr: String::new(),
+                    success: false,
+                    exit_code: None,
+                    stdout,
+                    stderr: "CPU limit exceeded".to_string(),
                     execution_time,
                     timed_out: false,
-                    memory_used: Some(fuel_consumed),
+                    memory_used,
+                    fuel_exhausted: true,
+                    fuel_used: Some(fuel_used),
+                    compile_error: false,
                 })
             }
-            Err(e) => {
-                let execution_time = start.elapsed();
-
-                // Check if it was a fuel exhaustion (timeout)
-                if e.to_string().contains("fuel") {
-                    Ok(ExecutionResult::timeout(
-                        String::new(),
-                        "Execution exceeded resource limits".to_string(),
-                        timeout,
-                    ))
-                } else {
-                    Ok(ExecutionResult {
-                        success: false,
-                        exit_code: Some(1),
-                        stdout: String::new(),
-                        stderr: e.to_string(),
-                        execution_time,
-                        timed_out: false,
-                        memory_used: None,
-                    })
+            Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::Interrupt) => Ok(ExecutionResult {
+                stderr: format!("Execution timed out after {:?}", timeout),
+                memory_used,
+                fuel_used: Some(fuel_used),
+                ..ExecutionResult::timeout(stdout, String::new(), execution_time)
+            }),
+            Err(e) => Ok(ExecutionResult {
+                success: false,
+                exit_code: Some(1),
+                stdout,
+                stderr: if stderr.is_empty() { e.to_string() } else { format!("{}\n{}", stderr, e) },
+                execution_time,
+                timed_out: false,
+                memory_used,
+                fuel_exhausted: false,
+                fuel_used: Some(fuel_used),
+                compile_error: false,
+            }),
+        }
+    }
+}
+
+/// Define WASI `fd_read` (stdin) and `fd_write` (stdout/stderr) on `linker`
+fn add_stdio(linker: &mut Linker<WasmState>) -> Result<()> {
+    linker
+        .func_wrap(
+            WASI_MODULE,
+            "fd_read",
+            |mut caller: Caller<'_, WasmState>, fd: i32, iovs: i32, iovs_len: i32, nread: i32| -> i32 {
+                if fd != 0 {
+                    return ERRNO_BADF;
                 }
-            }
+                let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+                    return ERRNO_FAULT;
+                };
+                let (data, state) = memory.data_and_store_mut(&mut caller);
+                let mut total = 0u32;
+                for (ptr, len) in iovecs(data, iovs, iovs_len) {
+                    let Some(buf) = data.get_mut(ptr..ptr + len) else {
+                        return ERRNO_FAULT;
+                    };
+                    let n = state.stdin.read(buf).unwrap_or(0);
+                    total += n as u32;
+                    if n < len {
+                        break;
+                    }
+                }
+                write_u32(data, nread, total)
+            },
+        )
+        .map_err(|e| Error::Wasm(e.to_string()))?;
+
+    linker
+        .func_wrap(
+            WASI_MODULE,
+            "fd_write",
+            |mut caller: Caller<'_, WasmState>, fd: i32, iovs: i32, iovs_len: i32, nwritten: i32| -> i32 {
+                let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+                    return ERRNO_FAULT;
+                };
+                let (data, state) = memory.data_and_store_mut(&mut caller);
+                let out = match fd {
+                    1 => &mut state.stdout,
+                    2 => &mut state.stderr,
+                    _ => return ERRNO_BADF,
+                };
+                let mut total = 0u32;
+                for (ptr, len) in iovecs(data, iovs, iovs_len) {
+                    let Some(buf) = data.get(ptr..ptr + len) else {
+                        return ERRNO_FAULT;
+                    };
+                    out.extend_from_slice(buf);
+                    total += len as u32;
+                }
+                write_u32(data, nwritten, total)
+            },
+        )
+        .map_err(|e| Error::Wasm(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Read a WASI iovec array (`{ buf: u32, len: u32 }` entries) from guest memory
+fn iovecs(data: &[u8], iovs: i32, iovs_len: i32) -> Vec<(usize, usize)> {
+    (0..iovs_len.max(0) as usize)
+        .map_while(|i| {
+            let at = iovs as u32 as usize + i * 8;
+            let ptr = u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?);
+            let len = u32::from_le_bytes(data.get(at + 4..at + 8)?.try_into().ok()?);
+            Some((ptr as usize, len as usize))
+        })
+        .collect()
+}
+
+/// Store a little-endian u32 result at `ptr`, returning the WASI errno
+fn write_u32(data: &mut [u8], ptr: i32, value: u32) -> i32 {
+    let at = ptr as u32 as usize;
+    match data.get_mut(at..at + 4) {
+        Some(slot) => {
+            slot.copy_from_slice(&value.to_le_bytes());
+            ERRNO_SUCCESS
         }
+        None => ERRNO_FAULT,
+    }
+}
+
+/// Strip TypeScript types with the `swc` CLI (`@swc/cli`), returning JavaScript
+///
+/// Transpiling runs on the host, but only parses the code; execution still
+/// happens inside the WASM runner.
+async fn transpile_typescript(code: &str) -> Result<String> {
+    let swc = which::which("swc").map_err(|_| {
+        Error::Sandbox("TypeScript in WASM mode requires the swc CLI (npm install -g @swc/cli @swc/core)".to_string())
+    })?;
+
+    // swc reads from files, not stdin
+    let path = std::env::temp_dir().join(format!("openagent-{}.ts", uuid::Uuid::new_v4()));
+    tokio::fs::write(&path, code).await?;
+    let output = tokio::process::Command::new(swc).arg(&path).output().await;
+    let _ = tokio::fs::remove_file(&path).await;
+
+    let output = output.map_err(|e| Error::Sandbox(format!("Failed to run swc: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::Sandbox(format!(
+            "TypeScript transpile failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
     }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
 }
 
 #[async_trait]
@@ -154,18 +394,19 @@ impl CodeExecutor for WasmExecutor {
     fn supports_language(&self, language: Language) -> bool {
         // Currently, we have limited language support in WASM mode
         // Full support would require embedding language runtimes
-        matches!(language, Language::Python | Language::JavaScript)
+        matches!(language, Language::Python | Language::JavaScript | Language::TypeScript)
     }
 
     fn supported_languages(&self) -> Vec<Language> {
         // Note: These are listed as supported but have limited functionality
-        vec![Language::Python, Language::JavaScript]
+        vec![Language::Python, Language::JavaScript, Language::TypeScript]
     }
 
     async fn execute(&self, request: ExecutionRequest) -> Result<ExecutionResult> {
         match request.language {
             Language::Python => self.execute_python(&request).await,
             Language::JavaScript => self.execute_javascript(&request).await,
+            Language::TypeScript => self.execute_typescript(&request).await,
             _ => Ok(ExecutionResult {
                 success: false,
                 exit_code: None,
@@ -177,6 +418,9 @@ impl CodeExecutor for WasmExecutor {
                 execution_time: Duration::from_millis(0),
                 timed_out: false,
                 memory_used: None,
+                fuel_exhausted: false,
+                fuel_used: None,
+                compile_error: false,
             }),
         }
     }
@@ -197,6 +441,106 @@ mod tests {
         let executor = WasmExecutor::new().unwrap();
         assert!(executor.supports_language(Language::Python));
         assert!(executor.supports_language(Language::JavaScript));
+        assert!(executor.supports_language(Language::TypeScript));
         assert!(!executor.supports_language(Language::Rust));
     }
+
+    fn limited_executor() -> WasmExecutor {
+        WasmExecutor::with_config(&WasmConfig {
+            max_memory_pages: 16,
+            fuel_limit: 100_000,
+            ..WasmConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fuel_exhaustion() {
+        let wat = r#"(module (func (export "spin") (loop br 0)))"#;
+        let result = limited_executor()
+            .execute_wasm_module(wat.as_bytes(), "spin", &[], Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.fuel_exhausted);
+        assert!(!result.timed_out);
+        assert_eq!(result.stderr, "CPU limit exceeded");
+        assert_eq!(result.fuel_used, Some(100_000));
+    }
+
+    #[tokio::test]
+    async fn test_wall_clock_timeout() {
+        // Plenty of fuel, so only the timeout can stop the loop
+        let executor = WasmExecutor::with_config(&WasmConfig {
+            fuel_limit: u64::MAX,
+            ..WasmConfig::default()
+        })
+        .unwrap();
+        let wat = r#"(module (func (export "spin") (loop br 0)))"#;
+        let result = executor
+            .execute_wasm_module(wat.as_bytes(), "spin", &[], Duration::from_millis(50))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.timed_out);
+        assert!(!result.fuel_exhausted);
+        assert!(result.execution_time < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_and_fuel_used() {
+        // Growing past 16 pages must fail (memory.grow returns -1)
+        let wat = r#"(module
+            (memory (export "memory") 1)
+            (func (export "grow") (param i32) (result i32)
+                local.get 0
+                memory.grow))"#;
+        let executor = limited_executor();
+
+        let result = executor
+            .execute_wasm_module(wat.as_bytes(), "grow", &[Val::I32(100)], Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.stdout, "Results: [I32(-1)]");
+        assert_eq!(result.memory_used, Some(WASM_PAGE_SIZE as u64));
+
+        let result = executor
+            .execute_wasm_module(wat.as_bytes(), "grow", &[Val::I32(3)], Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(result.stdout, "Results: [I32(1)]");
+        assert!(result.fuel_used.is_some_and(|f| f > 0));
+    }
+
+    #[tokio::test]
+    async fn test_cat_from_stdin() {
+        // Copy stdin to stdout through a 16-byte buffer at offset 16, with
+        // the iovec at 0 and the byte count at 8
+        let wat = r#"(module
+            (import "wasi_snapshot_preview1" "fd_read" (func $read (param i32 i32 i32 i32) (result i32)))
+            (import "wasi_snapshot_preview1" "fd_write" (func $write (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (func (export "cat")
+                (loop $next
+                    (i32.store (i32.const 0) (i32.const 16))
+                    (i32.store (i32.const 4) (i32.const 16))
+                    (drop (call $read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+                    (if (i32.gt_u (i32.load (i32.const 8)) (i32.const 0))
+                        (then
+                            (i32.store (i32.const 4) (i32.load (i32.const 8)))
+                            (drop (call $write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))
+                            (br $next))))))"#;
+        let input = "hello from stdin, longer than one buffer\n";
+
+        let result = WasmExecutor::new()
+            .unwrap()
+            .execute_wasm_module_with_stdin(wat.as_bytes(), "cat", &[], input.as_bytes(), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(result.stdout, input);
+    }
 }
@@ -3,12 +3,15 @@
 //! Runs on a configurable interval (default 30 minutes) and:
 //! 1. Summarizes active conversations, updating the soul's mutable sections
 //! 2. Picks up and processes pending tasks if the agent is idle
+//!
+//! Independently, recurring tasks are checked every minute and enqueued as
+//! pending tasks when their cron schedule comes due.
 
 use crate::agent::{
     ConversationManager, Message as AgentMessage, OpenRouterClient,
     ToolRegistry, LoopConfig, NoOpCallback,
     agentic_loop::{self, AgentLoopInput},
-    prompts::DEFAULT_SYSTEM_PROMPT,
+    prompts::{parse_tool_preferences, DEFAULT_SYSTEM_PROMPT},
 };
 use crate::database::{AgentStatusStore, ConfigParamStore, MemoryType, SoulStore, TaskStore, AgentTask};
 use crate::memory::{ConversationSummarizer, MemoryRetriever};
@@ -17,6 +20,9 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
+/// How often recurring task schedules are checked
+const RECURRING_CHECK_SECS: u64 = 60;
+
 /// The periodic scheduler that handles summarization and task processing
 pub struct Scheduler {
     task_store: TaskStore,
@@ -61,19 +67,52 @@ impl Scheduler {
 
         info!("Scheduler started, interval: {} minutes", interval_minutes);
 
+        let mut recurring_interval = tokio::time::interval(
+            tokio::time::Duration::from_secs(RECURRING_CHECK_SECS),
+        );
+
         // Skip the first immediate tick
         interval.tick().await;
 
         loop {
-            interval.tick().await;
-            info!("Scheduler tick");
+            tokio::select! {
+                _ = interval.tick() => {
+                    info!("Scheduler tick");
 
-            if let Err(e) = self.tick().await {
-                error!("Scheduler tick failed: {}", e);
+                    if let Err(e) = self.tick().await {
+                        error!("Scheduler tick failed: {}", e);
+                    }
+                }
+                _ = recurring_interval.tick() => {
+                    if let Err(e) = self.enqueue_due_recurring().await {
+                        warn!("Recurring task check failed: {}", e);
+                    }
+                }
             }
         }
     }
 
+    /// Enqueue pending tasks for recurring schedules that have come due
+    async fn enqueue_due_recurring(&self) -> Result<()> {
+        let created = self.task_store.get_due_recurring().await?;
+        if created.is_empty() {
+            return Ok(());
+        }
+
+        for task in &created {
+            info!("Enqueued recurring task: {} ({})", task.title, task.id);
+        }
+
+        // Pick up right away instead of waiting for the next scheduler tick
+        if self.is_task_processing_enabled().await
+            && self.status_store.is_ready().await.unwrap_or(false)
+        {
+            self.process_next_task().await?;
+        }
+
+        Ok(())
+    }
+
     async fn tick(&self) -> Result<()> {
         // 1. Heartbeat
         self.status_store.heartbeat().await?;
@@ -101,41 +140,35 @@ impl Scheduler {
     }
 
     async fn get_interval_minutes(&self) -> u64 {
-        match self.config_store.get("scheduler", "interval_minutes").await {
-            Ok(Some(param)) => param.value.parse().unwrap_or(30),
-            _ => 30,
-        }
+        self.config_store
+            .get_typed("scheduler", "interval_minutes")
+            .await
+            .unwrap_or(30)
     }
 
     async fn is_summarization_enabled(&self) -> bool {
-        match self.config_store.get("scheduler", "summarization_enabled").await {
-            Ok(Some(param)) => param.value != "false",
-            _ => true,
-        }
+        self.config_store
+            .get_typed("scheduler", "summarization_enabled")
+            .await
+            .unwrap_or(true)
     }
 
     async fn is_task_processing_enabled(&self) -> bool {
-        match self.config_store.get("scheduler", "task_processing_enabled").await {
-            Ok(Some(param)) => param.value != "false",
-            _ => true,
-        }
+        self.config_store
+            .get_typed("scheduler", "task_processing_enabled")
+            .await
+            .unwrap_or(true)
     }
 
     /// Summarize active conversations and update the soul's mutable sections
     async fn run_summarization(&self) -> Result<()> {
         let conversations = self.conversations.read().await;
-        let active_users: Vec<String> = conversations
-            .active_users()
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-
         // Collect message data under a short lock, then release
         let mut user_messages: Vec<(String, Vec<AgentMessage>)> = Vec::new();
-        for user_id in &active_users {
-            if let Some(conv) = conversations.get(user_id) {
+        for session in conversations.get_all_sessions() {
+            if let Some(conv) = conversations.get(&session) {
                 if conv.message_count() >= 4 {
-                    user_messages.push((user_id.clone(), conv.messages.clone()));
+                    user_messages.push((session.user_key(), conv.messages.clone()));
                 }
             }
         }
@@ -268,9 +301,11 @@ impl Scheduler {
 
     /// Execute a task using the unified agentic loop
     async fn execute_task(&self, task: &AgentTask) -> Result<String> {
-        let system_prompt = match self.soul_store.render_full_soul().await {
-            Ok(soul) => format!("{}\n\n---\n\n## Agent Soul\n\n{}", DEFAULT_SYSTEM_PROMPT, soul),
-            Err(_) => DEFAULT_SYSTEM_PROMPT.to_string(),
+        let soul = self.soul_store.render_full_soul().await.unwrap_or_default();
+        let system_prompt = if soul.is_empty() {
+            DEFAULT_SYSTEM_PROMPT.to_string()
+        } else {
+            format!("{}\n\n---\n\n## Agent Soul\n\n{}", DEFAULT_SYSTEM_PROMPT, soul)
         };
 
         let messages = vec![
@@ -285,7 +320,7 @@ impl Scheduler {
             llm_client: &self.llm_client,
             tools: &self.tools,
             tool_definitions,
-            config: LoopConfig::scheduler(),
+            config: LoopConfig::scheduler().with_tool_preferences(parse_tool_preferences(&soul)),
             user_id: Some(task.user_id.clone()),
             chat_id: None,
             callback: NoOpCallback::new(),
@@ -0,0 +1,200 @@
+//! Deploy skill
+//!
+//! Queues a build → test → deploy pipeline as three agent tasks. Each stage
+//! depends on the previous one, so the task worker only starts testing once
+//! the build has finished, and only deploys once the tests have passed.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::traits::{Skill, SkillContext, SkillResult, SkillStep};
+use crate::database::TaskStore;
+use crate::error::Result;
+
+/// Pipeline stages, in execution order, with the parameter holding each command
+const STAGES: [(&str, &str); 3] = [
+    ("build", "build_command"),
+    ("test", "test_command"),
+    ("deploy", "deploy_command"),
+];
+
+/// Structured result of queuing a deployment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeployOutput {
+    /// Task IDs of the build, test and deploy stages, in order
+    pub task_ids: Vec<Uuid>,
+    /// `(task_id, depends_on_task_id)` edges of the pipeline
+    pub dependencies: Vec<(Uuid, Uuid)>,
+}
+
+/// Skill that sequences build → test → deploy through task dependencies
+pub struct DeploySkill {
+    task_store: TaskStore,
+}
+
+impl DeploySkill {
+    pub fn new(task_store: TaskStore) -> Self {
+        DeploySkill { task_store }
+    }
+}
+
+/// Title and description of each stage task, or the first missing parameter
+fn stage_tasks(ctx: &SkillContext<'_>) -> std::result::Result<Vec<(String, String)>, String> {
+    let project = ctx.params.get("project").and_then(|v| v.as_str()).unwrap_or(".");
+
+    STAGES
+        .iter()
+        .map(|(stage, param)| {
+            let command = ctx
+                .params
+                .get(*param)
+                .and_then(|v| v.as_str())
+                .filter(|c| !c.trim().is_empty())
+                .ok_or_else(|| param.to_string())?;
+            Ok((
+                format!("{} {}", stage, project),
+                format!(
+                    "Run `{}` in {} using the system_command tool and report whether the {} succeeded. \
+                     Fail the task if the command exits with an error.",
+                    command, project, stage
+                ),
+            ))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Skill for DeploySkill {
+    fn name(&self) -> &str {
+        "deploy"
+    }
+
+    fn description(&self) -> &str {
+        "Queue build, test and deploy tasks for a project. Each stage only starts after the previous one has finished. Requires 'user_id', 'build_command', 'test_command' and 'deploy_command'; 'project' and 'chat_id' are optional."
+    }
+
+    fn required_tools(&self) -> Vec<&str> {
+        vec!["system_command"]
+    }
+
+    async fn execute(&self, ctx: &SkillContext<'_>) -> Result<SkillResult> {
+        let user_id = ctx.params.get("user_id").and_then(|v| v.as_str()).unwrap_or("");
+        if user_id.is_empty() {
+            return Ok(SkillResult::failure(
+                "No user provided",
+                vec![SkillStep::err("Validation", "Missing 'user_id' parameter")],
+            ));
+        }
+        let chat_id = ctx.params.get("chat_id").and_then(|v| v.as_i64());
+        let priority = ctx.params.get("priority").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+
+        let stages = match stage_tasks(ctx) {
+            Ok(stages) => stages,
+            Err(param) => {
+                return Ok(SkillResult::failure(
+                    format!("No {} provided", param.replace('_', " ")),
+                    vec![SkillStep::err("Validation", format!("Missing '{}' parameter", param))],
+                ));
+            }
+        };
+
+        let project = ctx.params.get("project").and_then(|v| v.as_str()).unwrap_or(".");
+        if !ctx.request_approval(&format!("Deploy {}: queue build, test and deploy tasks", project)) {
+            return Ok(SkillResult::failure(
+                format!("Deployment of {} was rejected", project),
+                vec![SkillStep::err("Approval", "Rejected")],
+            ));
+        }
+
+        let mut steps = Vec::new();
+        let mut task_ids: Vec<Uuid> = Vec::with_capacity(stages.len());
+        for (title, description) in stages {
+            let depends_on: Vec<Uuid> = task_ids.last().copied().into_iter().collect();
+            let task = self
+                .task_store
+                .create_with_dependencies(user_id, chat_id, &title, &description, priority, &depends_on)
+                .await?;
+            steps.push(SkillStep::ok(format!("Queued '{}'", title), task.id.to_string()));
+            task_ids.push(task.id);
+        }
+
+        let deploy_id = *task_ids.last().expect("pipeline has stages");
+        let dependencies = self.task_store.get_dependency_graph(deploy_id).await?;
+        let output = DeployOutput { task_ids, dependencies };
+
+        Ok(SkillResult::success(
+            format!("Queued build → test → deploy as {} dependent tasks", output.task_ids.len()),
+            steps,
+        )
+        .with_output(serde_json::to_value(output)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ToolRegistry;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    fn context<'a>(tools: &'a ToolRegistry, params: &[(&str, Value)]) -> SkillContext<'a> {
+        SkillContext::new(
+            tools,
+            params.iter().map(|(k, v)| (k.to_string(), v.clone())).collect::<HashMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_stage_tasks_in_order() {
+        let tools = ToolRegistry::new();
+        let ctx = context(
+            &tools,
+            &[
+                ("project", json!("/srv/app")),
+                ("build_command", json!("cargo build --release")),
+                ("test_command", json!("cargo test")),
+                ("deploy_command", json!("./deploy.sh")),
+            ],
+        );
+
+        let stages = stage_tasks(&ctx).unwrap();
+        let titles: Vec<&str> = stages.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, vec!["build /srv/app", "test /srv/app", "deploy /srv/app"]);
+        assert!(stages[1].1.contains("`cargo test` in /srv/app"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_deploy_queues_nothing() {
+        // Never connects: a rejected deployment must not touch the store
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let tools = ToolRegistry::new();
+        let ctx = context(
+            &tools,
+            &[
+                ("user_id", json!("u1")),
+                ("project", json!("/srv/app")),
+                ("build_command", json!("make")),
+                ("test_command", json!("make test")),
+                ("deploy_command", json!("make deploy")),
+            ],
+        )
+        .with_approval_callback(|_| false);
+
+        let result = DeploySkill::new(TaskStore::new(pool)).execute(&ctx).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(result.summary, "Deployment of /srv/app was rejected");
+    }
+
+    #[test]
+    fn test_stage_tasks_missing_command() {
+        let tools = ToolRegistry::new();
+        let ctx = context(
+            &tools,
+            &[("build_command", json!("make")), ("test_command", json!("  "))],
+        );
+        assert_eq!(stage_tasks(&ctx), Err("test_command".to_string()));
+    }
+}
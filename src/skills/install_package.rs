@@ -1,14 +1,239 @@
 //! Install package skill
 //!
-//! Detects the OS package manager and installs a package.
-//! Handles apt, yum, dnf, pacman, brew, etc.
+//! Detects the package manager and installs a package, then verifies it
+//! with the package manager's own query (`dpkg -s`, `rpm -q`, ...).
+//! Handles brew (macOS), apt-get (Debian/Ubuntu), apk (Alpine), dnf or yum
+//! (Fedora/RHEL), pacman (Arch), winget or Chocolatey (Windows), and pip3
+//! for packages prefixed with `python:`. On Windows, common Unix package
+//! names are mapped to their Windows equivalents, e.g. `nodejs` installs
+//! `nodejs-lts` with Chocolatey.
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 use super::traits::{Skill, SkillContext, SkillResult, SkillStep};
 use crate::error::Result;
 use crate::tools::{ToolCall, ToolResult as TResult};
 
+/// Package name prefix that selects pip3
+const PYTHON_PREFIX: &str = "python:";
+
+/// Windows equivalents of common package names:
+/// (name, Chocolatey package, winget ID)
+const WINDOWS_PACKAGES: &[(&str, &str, &str)] = &[
+    ("python3", "python", "Python.Python.3.12"),
+    ("python", "python", "Python.Python.3.12"),
+    ("nodejs", "nodejs-lts", "OpenJS.NodeJS.LTS"),
+    ("node", "nodejs-lts", "OpenJS.NodeJS.LTS"),
+    ("git", "git", "Git.Git"),
+    ("golang", "golang", "GoLang.Go"),
+    ("jq", "jq", "jqlang.jq"),
+    ("ripgrep", "ripgrep", "BurntSushi.ripgrep.MSVC"),
+    ("curl", "curl", "cURL.cURL"),
+];
+
+/// pip distributions whose import name differs from the distribution name
+const PYTHON_MODULES: &[(&str, &str)] = &[
+    ("python-dateutil", "dateutil"),
+    ("python-dotenv", "dotenv"),
+    ("pyyaml", "yaml"),
+    ("beautifulsoup4", "bs4"),
+    ("pillow", "PIL"),
+    ("scikit-learn", "sklearn"),
+    ("opencv-python", "cv2"),
+    ("pyjwt", "jwt"),
+    ("psycopg2-binary", "psycopg2"),
+    ("protobuf", "google.protobuf"),
+];
+
+/// Linux package managers found by their binary, in order of preference,
+/// after the Debian and Alpine marker files
+const LINUX_MANAGERS: &[PackageManager] = &[PackageManager::Dnf, PackageManager::Yum, PackageManager::Pacman];
+
+/// Package managers the skill can drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageManager {
+    Brew,
+    Apt,
+    Apk,
+    Dnf,
+    Yum,
+    Pacman,
+    Winget,
+    Choco,
+    Pip,
+}
+
+impl PackageManager {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew",
+            PackageManager::Apt => "apt-get",
+            PackageManager::Apk => "apk",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Yum => "yum",
+            PackageManager::Pacman => "pacman",
+            PackageManager::Winget => "winget",
+            PackageManager::Choco => "choco",
+            PackageManager::Pip => "pip3",
+        }
+    }
+
+    /// Name this manager knows `package` by
+    pub fn package_name<'a>(&self, package: &'a str) -> &'a str {
+        let entry = WINDOWS_PACKAGES.iter().find(|(name, ..)| *name == package);
+        match (self, entry) {
+            (PackageManager::Choco, Some((_, choco, _))) => choco,
+            (PackageManager::Winget, Some((_, _, winget))) => winget,
+            _ => package,
+        }
+    }
+
+    /// Index refresh to run before installing, if the manager needs one
+    fn update_command(&self) -> Option<(&'static str, Vec<&'static str>)> {
+        match self {
+            PackageManager::Apt => Some(("sudo", vec!["apt-get", "update"])),
+            _ => None,
+        }
+    }
+
+    fn install_command<'a>(&self, package: &'a str) -> (&'static str, Vec<&'a str>) {
+        match self {
+            PackageManager::Brew => ("brew", vec!["install", package]),
+            PackageManager::Apt => ("sudo", vec!["apt-get", "install", "-y", package]),
+            PackageManager::Apk => ("sudo", vec!["apk", "add", "--no-cache", package]),
+            PackageManager::Dnf => ("sudo", vec!["dnf", "install", "-y", package]),
+            PackageManager::Yum => ("sudo", vec!["yum", "install", "-y", package]),
+            PackageManager::Pacman => ("sudo", vec!["pacman", "-S", "--noconfirm", "--needed", package]),
+            PackageManager::Winget => (
+                "winget",
+                vec![
+                    "install",
+                    package,
+                    "--silent",
+                    "--accept-package-agreements",
+                    "--accept-source-agreements",
+                ],
+            ),
+            PackageManager::Choco => ("choco", vec!["install", package, "-y"]),
+            PackageManager::Pip => ("pip3", vec!["install", package]),
+        }
+    }
+
+    /// The manager's own query for whether `package` is installed; pip
+    /// packages are verified by importing them instead
+    fn query_command<'a>(&self, package: &'a str) -> Option<(&'static str, Vec<&'a str>)> {
+        match self {
+            PackageManager::Brew => Some(("brew", vec!["list", "--versions", package])),
+            PackageManager::Apt => Some(("dpkg", vec!["-s", package])),
+            PackageManager::Apk => Some(("apk", vec!["info", "-e", package])),
+            PackageManager::Dnf | PackageManager::Yum => Some(("rpm", vec!["-q", package])),
+            PackageManager::Pacman => Some(("pacman", vec!["-Q", package])),
+            PackageManager::Winget => Some(("winget", vec!["list", "--id", package, "--exact"])),
+            PackageManager::Choco => Some(("choco", vec!["list", "--exact", "--limit-output", package])),
+            PackageManager::Pip => None,
+        }
+    }
+}
+
+/// Finds the package manager to install a package with
+pub struct PackageManagerDetector<'a> {
+    ctx: &'a SkillContext<'a>,
+    os: &'static str,
+}
+
+impl<'a> PackageManagerDetector<'a> {
+    /// Detect for the OS this binary was built for
+    pub fn new(ctx: &'a SkillContext<'a>) -> Self {
+        Self::for_os(ctx, std::env::consts::OS)
+    }
+
+    fn for_os(ctx: &'a SkillContext<'a>, os: &'static str) -> Self {
+        PackageManagerDetector { ctx, os }
+    }
+
+    /// Pick the package manager for `package`: pip3 for `python:` packages,
+    /// winget then Chocolatey on Windows, brew on macOS, otherwise apt-get
+    /// or apk by distro marker file, then dnf, yum or pacman if installed.
+    /// Returns the manager and the output that identified it.
+    pub async fn detect(&self, package: &str) -> Result<Option<(PackageManager, String)>> {
+        let ctx = self.ctx;
+        if package.starts_with(PYTHON_PREFIX) {
+            let check = InstallPackageSkill::run_cmd(ctx, self.locate_command(), &["pip3"]).await?;
+            return Ok(check.success.then(|| (PackageManager::Pip, tool_output(&check))));
+        }
+
+        match self.os {
+            "windows" => {
+                // winget ships with Windows 10 and later
+                for pm in [PackageManager::Winget, PackageManager::Choco] {
+                    let check = InstallPackageSkill::run_cmd(ctx, pm.as_str(), &["--version"]).await?;
+                    if check.success {
+                        return Ok(Some((pm, tool_output(&check))));
+                    }
+                }
+                return Ok(None);
+            }
+            "macos" => {
+                let check = InstallPackageSkill::run_cmd(ctx, "which", &["brew"]).await?;
+                if check.success {
+                    return Ok(Some((PackageManager::Brew, tool_output(&check))));
+                }
+            }
+            _ => {}
+        }
+
+        for (marker, pm) in [
+            ("/etc/debian_version", PackageManager::Apt),
+            ("/etc/alpine-release", PackageManager::Apk),
+        ] {
+            let check = InstallPackageSkill::run_cmd(ctx, "test", &["-f", marker]).await?;
+            if check.success {
+                return Ok(Some((pm, format!("Found {}", marker))));
+            }
+        }
+
+        for &pm in LINUX_MANAGERS {
+            let check = InstallPackageSkill::run_cmd(ctx, "which", &[pm.as_str()]).await?;
+            if check.success {
+                return Ok(Some((pm, tool_output(&check))));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// `which`, or `where` on Windows
+    fn locate_command(&self) -> &'static str {
+        if self.os == "windows" {
+            "where"
+        } else {
+            "which"
+        }
+    }
+
+    /// Python interpreter used to verify pip installs
+    fn python_command(&self) -> &'static str {
+        if self.os == "windows" {
+            "python"
+        } else {
+            "python3"
+        }
+    }
+}
+
+/// Structured result of an install, attached to the skill result as `output`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkillOutput {
+    /// Install and verification both succeeded
+    pub success: bool,
+    /// Package manager that was used, if one was detected
+    pub package_manager_used: Option<String>,
+    /// Commands run and their output, in order
+    pub install_log: String,
+}
+
 /// Skill for installing OS packages
 pub struct InstallPackageSkill;
 
@@ -26,9 +251,121 @@ impl InstallPackageSkill {
                 "command": command,
                 "args": args,
             }),
+            parent_message_id: None,
         };
         ctx.tools.execute(&call).await
     }
+
+    /// Detect, install and verify with `detector`
+    async fn install(ctx: &SkillContext<'_>, detector: &PackageManagerDetector<'_>) -> Result<SkillResult> {
+        let requested = ctx.params.get("package")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let package = requested.strip_prefix(PYTHON_PREFIX).unwrap_or(requested);
+
+        if !valid_package_name(package) {
+            return Ok(SkillResult::failure(
+                "No valid package name provided",
+                vec![SkillStep::err("Validation", "Missing or invalid 'package' parameter")],
+            ));
+        }
+
+        let mut steps = Vec::new();
+        let mut log = String::new();
+
+        // Step 1: Detect package manager
+        let pm = match detector.detect(requested).await? {
+            Some((pm, output)) => {
+                steps.push(SkillStep::ok(format!("Detected package manager: {}", pm.as_str()), output));
+                pm
+            }
+            None => {
+                steps.push(SkillStep::err("Detection", "No supported package manager found"));
+                let output = SkillOutput {
+                    success: false,
+                    package_manager_used: None,
+                    install_log: String::new(),
+                };
+                return Ok(SkillResult::failure("No package manager found", steps)
+                    .with_output(serde_json::to_value(output)?));
+            }
+        };
+
+        // Step 2: Update package index (apt only)
+        if let Some((command, args)) = pm.update_command() {
+            let result = Self::run_cmd(ctx, command, &args).await?;
+            log.push_str(&format!("$ {} {}\n{}\n", command, args.join(" "), tool_output(&result)));
+            steps.push(SkillStep {
+                description: "Update package index".to_string(),
+                success: result.success,
+                output: tool_output(&result),
+            });
+        }
+
+        // Step 3: Install package, once a human agrees
+        let (command, args) = pm.install_command(pm.package_name(package));
+        if !ctx.request_approval(&format!("Install {}: {} {}", package, command, args.join(" "))) {
+            steps.push(SkillStep::err(format!("Install {}", package), "Rejected"));
+            let output = SkillOutput {
+                success: false,
+                package_manager_used: Some(pm.as_str().to_string()),
+                install_log: log,
+            };
+            return Ok(SkillResult::failure(format!("Installation of {} was rejected", package), steps)
+                .with_output(serde_json::to_value(output)?));
+        }
+        let install_result = Self::run_cmd(ctx, command, &args).await?;
+        log.push_str(&format!("$ {} {}\n{}\n", command, args.join(" "), tool_output(&install_result)));
+        let installed = install_result.success;
+        steps.push(SkillStep {
+            description: format!("Install {}", package),
+            success: installed,
+            output: tool_output(&install_result),
+        });
+
+        // Step 4: Verify the package is installed
+        let mut verified = false;
+        if installed {
+            let name = pm.package_name(package);
+            let verify_result = match pm.query_command(name) {
+                Some((command, args)) => Self::run_cmd(ctx, command, &args).await?,
+                None => {
+                    let import = format!("import {}", python_module(package));
+                    Self::run_cmd(ctx, detector.python_command(), &["-c", &import]).await?
+                }
+            };
+            // Chocolatey exits 0 when nothing matches, so the package must
+            // also be listed
+            let listed = tool_output(&verify_result).to_lowercase().contains(&name.to_lowercase());
+            verified = verify_result.success && (pm != PackageManager::Choco || listed);
+            steps.push(SkillStep {
+                description: format!("Verify {}", package),
+                success: verified,
+                output: tool_output(&verify_result),
+            });
+        }
+
+        let output = SkillOutput {
+            success: installed && verified,
+            package_manager_used: Some(pm.as_str().to_string()),
+            install_log: log,
+        };
+        let result = match (installed, verified) {
+            (true, true) => SkillResult::success(
+                format!("Successfully installed {} via {}", package, pm.as_str()),
+                steps,
+            ),
+            (true, false) => SkillResult::failure(
+                format!("Installed {} via {} but could not verify it", package, pm.as_str()),
+                steps,
+            ),
+            _ => SkillResult::failure(
+                format!("Failed to install {} via {}", package, pm.as_str()),
+                steps,
+            ),
+        };
+        Ok(result.with_output(serde_json::to_value(output)?))
+    }
 }
 
 impl Default for InstallPackageSkill {
@@ -37,6 +374,35 @@ impl Default for InstallPackageSkill {
     }
 }
 
+/// Tool output for the install log (content, or the error if there is none)
+fn tool_output(result: &TResult) -> String {
+    result
+        .content
+        .clone()
+        .or_else(|| result.error.clone())
+        .unwrap_or_default()
+}
+
+/// Python module to import when verifying a pip package, e.g.
+/// `python-dateutil>=2.8` -> `dateutil`, `typing-extensions` ->
+/// `typing_extensions`
+fn python_module(package: &str) -> String {
+    let distribution = package
+        .split(|c: char| "<>=!~[; ".contains(c))
+        .next()
+        .unwrap_or(package)
+        .to_lowercase();
+    PYTHON_MODULES
+        .iter()
+        .find(|(name, _)| *name == distribution)
+        .map_or_else(|| distribution.replace('-', "_"), |(_, module)| module.to_string())
+}
+
+/// Reject names that are empty, contain whitespace, or look like flags
+fn valid_package_name(package: &str) -> bool {
+    !package.is_empty() && !package.starts_with('-') && !package.chars().any(char::is_whitespace)
+}
+
 #[async_trait]
 impl Skill for InstallPackageSkill {
     fn name(&self) -> &str {
@@ -44,7 +410,7 @@ impl Skill for InstallPackageSkill {
     }
 
     fn description(&self) -> &str {
-        "Install an OS package using the appropriate package manager. Automatically detects apt, yum, dnf, pacman, or brew."
+        "Install a package using the appropriate package manager. Detects brew (macOS), apt-get (Debian/Ubuntu), apk (Alpine), dnf/yum (Fedora/RHEL), pacman (Arch), or winget/Chocolatey (Windows); prefix Python packages with 'python:' to use pip3."
     }
 
     fn required_tools(&self) -> Vec<&str> {
@@ -52,90 +418,171 @@ impl Skill for InstallPackageSkill {
     }
 
     async fn execute(&self, ctx: &SkillContext<'_>) -> Result<SkillResult> {
-        let package = ctx.params.get("package")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
+        Self::install(ctx, &PackageManagerDetector::new(ctx)).await
+    }
+}
 
-        if package.is_empty() {
-            return Ok(SkillResult::failure(
-                "No package name provided",
-                vec![SkillStep::err("Validation", "Missing 'package' parameter")],
-            ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{Tool, ToolRegistry};
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    /// Records commands and succeeds unless the command line is in `failing`
+    struct FakeSystemCommand {
+        calls: Arc<Mutex<Vec<String>>>,
+        failing: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl Tool for FakeSystemCommand {
+        fn name(&self) -> &str {
+            "system_command"
         }
 
-        let mut steps = Vec::new();
+        fn description(&self) -> &str {
+            "fake"
+        }
 
-        // Step 1: Detect package manager
-        let pkg_managers = [
-            ("apt", "sudo", &["apt", "update"][..]),
-            ("yum", "sudo", &["yum", "check-update"][..]),
-            ("dnf", "sudo", &["dnf", "check-update"][..]),
-            ("pacman", "sudo", &["pacman", "-Sy"][..]),
-            ("brew", "brew", &["update"][..]),
-        ];
-
-        let mut detected_pm: Option<&str> = None;
-        for (pm, _cmd, _args) in &pkg_managers {
-            let check = Self::run_cmd(ctx, "which", &[pm]).await?;
-            if check.success {
-                detected_pm = Some(pm);
-                steps.push(SkillStep::ok(
-                    format!("Detected package manager: {}", pm),
-                    check.content.unwrap_or_default(),
-                ));
-                break;
-            }
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object" })
         }
 
-        let pm = match detected_pm {
-            Some(pm) => pm,
-            None => {
-                steps.push(SkillStep::err("Detection", "No supported package manager found"));
-                return Ok(SkillResult::failure("No package manager found", steps));
+        async fn execute(&self, args: Value) -> Result<TResult> {
+            let mut line = args["command"].as_str().unwrap().to_string();
+            for arg in args["args"].as_array().unwrap() {
+                line.push(' ');
+                line.push_str(arg.as_str().unwrap());
             }
-        };
+            self.calls.lock().unwrap().push(line.clone());
+            if self.failing.contains(&line.as_str()) {
+                Ok(TResult::failure("exit 1"))
+            } else {
+                Ok(TResult::success(line))
+            }
+        }
+    }
 
-        // Step 2: Update package index
-        let update_result = match pm {
-            "apt" => Self::run_cmd(ctx, "sudo", &["apt", "update"]).await?,
-            "yum" => Self::run_cmd(ctx, "sudo", &["yum", "check-update"]).await?,
-            "dnf" => Self::run_cmd(ctx, "sudo", &["dnf", "check-update"]).await?,
-            "pacman" => Self::run_cmd(ctx, "sudo", &["pacman", "-Sy"]).await?,
-            "brew" => Self::run_cmd(ctx, "brew", &["update"]).await?,
-            _ => unreachable!(),
-        };
-        steps.push(SkillStep {
-            description: "Update package index".to_string(),
-            success: update_result.success,
-            output: update_result.content.unwrap_or_default(),
-        });
+    async fn run(package: &str, failing: Vec<&'static str>) -> (SkillResult, Vec<String>) {
+        run_on(std::env::consts::OS, package, failing).await
+    }
 
-        // Step 3: Install package
-        let install_result = match pm {
-            "apt" => Self::run_cmd(ctx, "sudo", &["apt", "install", "-y", package]).await?,
-            "yum" => Self::run_cmd(ctx, "sudo", &["yum", "install", "-y", package]).await?,
-            "dnf" => Self::run_cmd(ctx, "sudo", &["dnf", "install", "-y", package]).await?,
-            "pacman" => Self::run_cmd(ctx, "sudo", &["pacman", "-S", "--noconfirm", package]).await?,
-            "brew" => Self::run_cmd(ctx, "brew", &["install", package]).await?,
-            _ => unreachable!(),
-        };
-        let install_success = install_result.success;
-        steps.push(SkillStep {
-            description: format!("Install {}", package),
-            success: install_success,
-            output: install_result.content.unwrap_or_default(),
-        });
+    async fn run_on(os: &'static str, package: &str, failing: Vec<&'static str>) -> (SkillResult, Vec<String>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut tools = ToolRegistry::new();
+        tools.register(FakeSystemCommand { calls: calls.clone(), failing });
+        let ctx = SkillContext::new(&tools, HashMap::from([("package".to_string(), json!(package))]));
+        let result = InstallPackageSkill::install(&ctx, &PackageManagerDetector::for_os(&ctx, os))
+            .await
+            .unwrap();
+        let calls = calls.lock().unwrap().clone();
+        (result, calls)
+    }
 
-        if install_success {
-            Ok(SkillResult::success(
-                format!("Successfully installed {} via {}", package, pm),
-                steps,
-            ))
-        } else {
-            Ok(SkillResult::failure(
-                format!("Failed to install {} via {}", package, pm),
-                steps,
-            ))
+    #[tokio::test]
+    async fn test_rejected_install_runs_nothing() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut tools = ToolRegistry::new();
+        tools.register(FakeSystemCommand { calls: calls.clone(), failing: vec![] });
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let seen = asked.clone();
+        let ctx = SkillContext::new(&tools, HashMap::from([("package".to_string(), json!("python:httpx"))]))
+            .with_approval_callback(move |step| {
+                seen.lock().unwrap().push(step.to_string());
+                false
+            });
+
+        let result = InstallPackageSkill::new().execute(&ctx).await.unwrap();
+        assert!(!result.success);
+        assert_eq!(*asked.lock().unwrap(), vec!["Install httpx: pip3 install httpx"]);
+        assert_eq!(*calls.lock().unwrap(), vec!["which pip3"]);
+    }
+
+    #[tokio::test]
+    async fn test_python_package_uses_pip() {
+        let (result, calls) = run("python:python-dateutil", vec![]).await;
+        assert!(result.success);
+        assert_eq!(
+            calls,
+            vec![
+                "which pip3",
+                "pip3 install python-dateutil",
+                "python3 -c import dateutil",
+            ]
+        );
+        let output: SkillOutput = serde_json::from_value(result.output.unwrap()).unwrap();
+        assert_eq!(output.package_manager_used.as_deref(), Some("pip3"));
+        assert!(output.install_log.contains("$ pip3 install python-dateutil"));
+    }
+
+    #[tokio::test]
+    async fn test_alpine_detection_and_failed_verify() {
+        if std::env::consts::OS == "macos" {
+            return;
         }
+        let (result, calls) = run("jq", vec!["test -f /etc/debian_version", "apk info -e jq"]).await;
+        assert!(!result.success);
+        assert_eq!(calls[2], "sudo apk add --no-cache jq");
+        assert_eq!(calls[3], "apk info -e jq");
+        let output: SkillOutput = serde_json::from_value(result.output.unwrap()).unwrap();
+        assert_eq!(output.package_manager_used.as_deref(), Some("apk"));
+        assert!(!output.success);
+    }
+
+    #[tokio::test]
+    async fn test_rpm_and_pacman_detection() {
+        let no_markers = vec!["test -f /etc/debian_version", "test -f /etc/alpine-release"];
+        let (result, calls) = run_on("linux", "jq", no_markers.clone()).await;
+        assert!(result.success);
+        assert_eq!(calls[2..], ["which dnf", "sudo dnf install -y jq", "rpm -q jq"]);
+
+        let (result, calls) = run_on("linux", "jq", [no_markers, vec!["which dnf", "which yum"]].concat()).await;
+        assert!(result.success);
+        assert_eq!(
+            calls[4..],
+            ["which pacman", "sudo pacman -S --noconfirm --needed jq", "pacman -Q jq"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_windows_detection_maps_package_names() {
+        let (result, calls) = run_on("windows", "nodejs", vec!["winget --version"]).await;
+        assert!(result.success);
+        assert_eq!(
+            calls,
+            vec![
+                "winget --version",
+                "choco --version",
+                "choco install nodejs-lts -y",
+                "choco list --exact --limit-output nodejs-lts",
+            ]
+        );
+
+        let (result, calls) = run_on("windows", "python3", vec![]).await;
+        assert!(result.success);
+        assert_eq!(calls[1], "winget install Python.Python.3.12 --silent --accept-package-agreements --accept-source-agreements");
+        assert_eq!(calls[2], "winget list --id Python.Python.3.12 --exact");
+
+        // Unmapped names pass through unchanged
+        assert_eq!(PackageManager::Choco.package_name("7zip"), "7zip");
+        assert_eq!(PackageManager::Apt.package_name("nodejs"), "nodejs");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_flag_like_package() {
+        let (result, calls) = run("--force", vec![]).await;
+        assert!(!result.success);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn test_python_module() {
+        assert_eq!(python_module("requests"), "requests");
+        assert_eq!(python_module("python-dateutil>=2.8"), "dateutil");
+        assert_eq!(python_module("PyYAML"), "yaml");
+        assert_eq!(python_module("typing-extensions"), "typing_extensions");
+        assert_eq!(python_module("httpx[http2]"), "httpx");
     }
 }
@@ -6,8 +6,8 @@
 //!
 //! ## Examples
 //!
-//! - **install_package**: Detects OS/package manager, installs, verifies
-//! - **deploy**: Builds project, copies files, restarts services
+//! - **install_package**: Detects brew/apt-get/apk/winget/choco/pip3, installs, verifies
+//! - **deploy**: Queues dependent build → test → deploy tasks
 //! - **diagnose**: Checks logs, system state, suggests fixes
 //!
 //! ## Adding a New Skill
@@ -19,6 +19,8 @@
 
 mod traits;
 mod install_package;
+mod deploy;
 
-pub use traits::{Skill, SkillContext, SkillRegistry};
-pub use install_package::InstallPackageSkill;
+pub use traits::{ApprovalCallback, Skill, SkillContext, SkillRegistry, SkillResult, SkillStep};
+pub use install_package::{InstallPackageSkill, PackageManager, PackageManagerDetector, SkillOutput};
+pub use deploy::{DeployOutput, DeploySkill};
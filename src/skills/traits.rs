@@ -10,12 +10,45 @@ use std::collections::HashMap;
 use crate::error::Result;
 use crate::tools::ToolRegistry;
 
+/// Asks a human whether a step may run; receives a description of the step
+pub type ApprovalCallback = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
 /// Context provided to skills during execution
 pub struct SkillContext<'a> {
     /// Access to the tool registry for executing sub-tools
     pub tools: &'a ToolRegistry,
     /// Key-value parameters for the skill
     pub params: HashMap<String, Value>,
+    /// Confirms destructive steps; without one every step is allowed
+    pub approval_callback: Option<ApprovalCallback>,
+}
+
+impl<'a> SkillContext<'a> {
+    /// Create a context without an approval callback
+    pub fn new(tools: &'a ToolRegistry, params: HashMap<String, Value>) -> Self {
+        SkillContext {
+            tools,
+            params,
+            approval_callback: None,
+        }
+    }
+
+    /// Ask `callback` before each destructive step
+    ///
+    /// The callback may block (e.g. on a prompt or a chat reply) and should
+    /// return `false` to skip the step.
+    pub fn with_approval_callback(mut self, callback: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        self.approval_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Whether the step described by `description` may run. Skills call this
+    /// before anything destructive.
+    pub fn request_approval(&self, description: &str) -> bool {
+        self.approval_callback
+            .as_ref()
+            .is_none_or(|callback| callback(description))
+    }
 }
 
 /// A composable agent skill
@@ -43,6 +76,8 @@ pub struct SkillResult {
     pub summary: String,
     /// Detailed steps that were executed
     pub steps: Vec<SkillStep>,
+    /// Skill-specific structured output, if any
+    pub output: Option<Value>,
 }
 
 /// Individual step within a skill execution
@@ -63,6 +98,7 @@ impl SkillResult {
             success: true,
             summary: summary.into(),
             steps,
+            output: None,
         }
     }
 
@@ -72,8 +108,15 @@ impl SkillResult {
             success: false,
             summary: summary.into(),
             steps,
+            output: None,
         }
     }
+
+    /// Attach structured output
+    pub fn with_output(mut self, output: Value) -> Self {
+        self.output = Some(output);
+        self
+    }
 }
 
 impl SkillStep {
@@ -0,0 +1,92 @@
+//! Tracing subscriber setup with optional OpenTelemetry export
+//!
+//! The agentic loop emits `agent.loop`, `agent.llm_call` and `agent.tool.{name}`
+//! spans through `tracing`. When built with the `telemetry` feature and
+//! `gateway.telemetry.enabled` is set, those spans are exported via OTLP/HTTP.
+
+use crate::config::TelemetryConfig;
+use crate::error::Result;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Flushes pending spans when dropped. Keep it alive for the life of the process.
+#[must_use = "dropping the guard shuts down span export"]
+pub struct TelemetryGuard {
+    #[cfg(feature = "telemetry")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "telemetry")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down OpenTelemetry exporter: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the global tracing subscriber: console output plus, if enabled,
+/// an OpenTelemetry layer exporting to `config.otlp_endpoint`.
+pub fn init_tracing(config: &TelemetryConfig, filter: EnvFilter) -> Result<TelemetryGuard> {
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "telemetry")]
+    {
+        use opentelemetry::trace::TracerProvider as _;
+
+        if config.enabled {
+            let provider = otel::build_provider(config)?;
+            let tracer = provider.tracer("openagent");
+            registry
+                .with(tracing_opentelemetry::layer().with_tracer(tracer))
+                .init();
+            tracing::info!("OpenTelemetry export enabled: {}", config.otlp_endpoint);
+            return Ok(TelemetryGuard { provider: Some(provider) });
+        }
+
+        registry.init();
+        Ok(TelemetryGuard { provider: None })
+    }
+
+    #[cfg(not(feature = "telemetry"))]
+    {
+        registry.init();
+        if config.enabled {
+            tracing::warn!(
+                "gateway.telemetry.enabled is set but OpenAgent was built without the `telemetry` feature"
+            );
+        }
+        Ok(TelemetryGuard {})
+    }
+}
+
+#[cfg(feature = "telemetry")]
+mod otel {
+    use super::TelemetryConfig;
+    use crate::error::{Error, Result};
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+
+    pub(super) fn build_provider(config: &TelemetryConfig) -> Result<SdkTracerProvider> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(&config.otlp_endpoint)
+            .build()
+            .map_err(|e| Error::Config(format!("Invalid OTLP exporter config: {}", e)))?;
+
+        Ok(SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(config.service_name.clone())
+                    .build(),
+            )
+            .build())
+    }
+}
@@ -1,16 +1,18 @@
 //! Brave Search tool
 //!
-//! Web search using the Brave Search API. Requires a Brave Search API key.
+//! Web and image search using the Brave Search API. Requires a Brave Search
+//! API key. Image search is exposed as a separate `brave_image_search` tool.
 
 use async_trait::async_trait;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::time::Duration;
 
 use super::duckduckgo_search::SearchResult;
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
-use super::{format_search_results, urlencoding};
+use super::{format_search_results, search_tool_result, urlencoding};
 use crate::Result;
 
 /// Default timeout for search requests
@@ -19,6 +21,9 @@ const DEFAULT_TIMEOUT_SECS: u64 = 30;
 /// Default number of results
 const DEFAULT_RESULT_COUNT: u8 = 10;
 
+/// Maximum results Brave returns per image search
+const MAX_IMAGE_COUNT: u8 = 100;
+
 /// Brave Search API response structures
 #[derive(Debug, Deserialize)]
 struct BraveSearchResponse {
@@ -37,6 +42,58 @@ struct BraveSearchResult {
     description: String,
 }
 
+/// Brave image search API response structures
+#[derive(Debug, Deserialize)]
+struct BraveImageResponse {
+    #[serde(default)]
+    results: Vec<BraveImageResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveImageResult {
+    #[serde(default)]
+    title: String,
+    /// Page the image appears on
+    url: String,
+    #[serde(default)]
+    source: String,
+    thumbnail: Option<BraveImageThumbnail>,
+    properties: Option<BraveImageProperties>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveImageThumbnail {
+    src: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BraveImageProperties {
+    url: Option<String>,
+}
+
+/// A single image search result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageSearchResult {
+    /// Full-size image URL (the hosting page if Brave has none)
+    pub url: String,
+    /// Brave-hosted thumbnail URL
+    pub thumbnail_url: String,
+    pub title: String,
+    /// Domain the image was found on
+    pub source: String,
+}
+
+impl From<BraveImageResult> for ImageSearchResult {
+    fn from(r: BraveImageResult) -> Self {
+        ImageSearchResult {
+            url: r.properties.and_then(|p| p.url).unwrap_or(r.url),
+            thumbnail_url: r.thumbnail.map(|t| t.src).unwrap_or_default(),
+            title: r.title,
+            source: r.source,
+        }
+    }
+}
+
 /// Brave Search tool configuration
 #[derive(Debug, Clone)]
 pub struct BraveSearchConfig {
@@ -67,6 +124,7 @@ impl BraveSearchConfig {
 }
 
 /// Brave Search tool for web searching
+#[derive(Clone)]
 pub struct BraveSearchTool {
     client: Client,
     config: BraveSearchConfig,
@@ -88,21 +146,16 @@ impl BraveSearchTool {
         BraveSearchConfig::from_env().map(Self::new)
     }
 
-    /// Perform a web search
-    async fn search(&self, query: &str, count: u8, country: Option<&str>) -> Result<Vec<SearchResult>> {
-        let mut url = format!(
-            "https://api.search.brave.com/res/v1/web/search?q={}&count={}",
-            urlencoding::encode(query),
-            count.min(20)
-        );
-
-        if let Some(cc) = country {
-            url.push_str(&format!("&country={}", cc));
-        }
+    /// Image search tool sharing this tool's client and API key
+    pub fn image_search(&self) -> BraveImageSearchTool {
+        BraveImageSearchTool { brave: self.clone() }
+    }
 
+    /// Send an authenticated GET to the Brave API and return the response body
+    async fn get(&self, url: &str) -> Result<reqwest::Response> {
         let response = self
             .client
-            .get(&url)
+            .get(url)
             .header("Accept", "application/json")
             .header("Accept-Encoding", "gzip")
             .header("X-Subscription-Token", &self.config.api_key)
@@ -118,8 +171,42 @@ impl BraveSearchTool {
                 status, text
             )));
         }
+        Ok(response)
+    }
+
+    /// Search for images via `/images/search`
+    pub async fn search_images(&self, query: &str, count: u8) -> Result<Vec<ImageSearchResult>> {
+        let url = format!(
+            "https://api.search.brave.com/res/v1/images/search?q={}&count={}",
+            urlencoding::encode(query),
+            count.clamp(1, MAX_IMAGE_COUNT)
+        );
+
+        let response: BraveImageResponse = self
+            .get(&url)
+            .await?
+            .json()
+            .await
+            .map_err(|e| crate::Error::Provider(format!("Failed to parse Brave response: {}", e)))?;
+
+        Ok(response.results.into_iter().map(ImageSearchResult::from).collect())
+    }
+
+    /// Perform a web search
+    async fn search(&self, query: &str, count: u8, country: Option<&str>) -> Result<Vec<SearchResult>> {
+        let mut url = format!(
+            "https://api.search.brave.com/res/v1/web/search?q={}&count={}",
+            urlencoding::encode(query),
+            count.min(20)
+        );
+
+        if let Some(cc) = country {
+            url.push_str(&format!("&country={}", cc));
+        }
 
-        let brave_response: BraveSearchResponse = response
+        let brave_response: BraveSearchResponse = self
+            .get(&url)
+            .await?
             .json::<BraveSearchResponse>()
             .await
             .map_err(|e| crate::Error::Provider(format!("Failed to parse Brave response: {}", e)))?;
@@ -173,6 +260,17 @@ impl Tool for BraveSearchTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network, Permission::ExternalApi]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "query": "tokio select macro", "count": 1, "country": "us" }),
+            "1. **select in tokio - Rust**\n   URL: https://docs.rs/tokio/latest/tokio/macro.select.html\n   Waits on multiple concurrent branches.".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let query = args
             .get("query")
@@ -192,8 +290,7 @@ impl Tool for BraveSearchTool {
                 if results.is_empty() {
                     Ok(ToolResult::success("No results found for the query."))
                 } else {
-                    let formatted = format_search_results(&results);
-                    Ok(ToolResult::success(formatted))
+                    Ok(search_tool_result(format_search_results(&results), results.len()))
                 }
             }
             Err(e) => Ok(ToolResult::failure(format!("Search failed: {}", e))),
@@ -201,6 +298,103 @@ impl Tool for BraveSearchTool {
     }
 }
 
+/// Brave image search, registered as `brave_image_search`
+pub struct BraveImageSearchTool {
+    brave: BraveSearchTool,
+}
+
+impl BraveImageSearchTool {
+    /// Create a new Brave image search tool
+    pub fn new(config: BraveSearchConfig) -> Self {
+        BraveSearchTool::new(config).image_search()
+    }
+
+    /// Create from environment variables
+    pub fn from_env() -> Option<Self> {
+        BraveSearchConfig::from_env().map(Self::new)
+    }
+}
+
+/// Format image results as a numbered list for the agent
+fn format_image_results(results: &[ImageSearchResult]) -> String {
+    results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            format!(
+                "{}. {}\n   Image: {}\n   Thumbnail: {}\n   Source: {}",
+                i + 1,
+                r.title,
+                r.url,
+                r.thumbnail_url,
+                r.source
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[async_trait]
+impl Tool for BraveImageSearchTool {
+    fn name(&self) -> &str {
+        "brave_image_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search for images using Brave Search API. Returns image URLs, thumbnails, titles and source sites; use it when the user asks to see images of something."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "What to find images of"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of images to return (1-100, default: 10)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network, Permission::ExternalApi]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "query": "mount fuji at sunrise", "count": 1 }),
+            "1. Mount Fuji at sunrise\n   Image: https://example.com/fuji.jpg\n   Thumbnail: https://example.com/fuji_thumb.jpg\n   Source: example.com".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| crate::Error::InvalidInput("Missing 'query' parameter".to_string()))?;
+
+        let count = args
+            .get("count")
+            .and_then(|v| v.as_u64())
+            .map(|c| c.min(MAX_IMAGE_COUNT as u64) as u8)
+            .unwrap_or(self.brave.config.result_count);
+
+        match self.brave.search_images(query, count).await {
+            Ok(results) if results.is_empty() => Ok(ToolResult::success("No images found for the query.")),
+            Ok(results) => {
+                Ok(search_tool_result(format_image_results(&results), results.len())
+                    .with_metadata("images", serde_json::to_value(&results)?))
+            }
+            Err(e) => Ok(ToolResult::failure(format!("Image search failed: {}", e))),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,4 +404,49 @@ mod tests {
         // Just test that it doesn't panic
         let _ = BraveSearchConfig::from_env();
     }
+
+    #[test]
+    fn test_parse_image_results() {
+        let body = serde_json::json!({
+            "type": "images",
+            "results": [
+                {
+                    "title": "Red panda in a tree",
+                    "url": "https://example.com/red-panda",
+                    "source": "example.com",
+                    "thumbnail": { "src": "https://imgs.search.brave.com/abc" },
+                    "properties": { "url": "https://example.com/img/red-panda.jpg" }
+                },
+                { "url": "https://example.org/page" }
+            ]
+        });
+        let response: BraveImageResponse = serde_json::from_value(body).unwrap();
+        let results: Vec<ImageSearchResult> = response.results.into_iter().map(Into::into).collect();
+
+        assert_eq!(
+            results[0],
+            ImageSearchResult {
+                url: "https://example.com/img/red-panda.jpg".to_string(),
+                thumbnail_url: "https://imgs.search.brave.com/abc".to_string(),
+                title: "Red panda in a tree".to_string(),
+                source: "example.com".to_string(),
+            }
+        );
+        assert_eq!(results[1].url, "https://example.org/page");
+        assert!(results[1].thumbnail_url.is_empty());
+
+        let formatted = format_image_results(&results[..1]);
+        assert!(formatted.starts_with("1. Red panda in a tree\n   Image: https://example.com/img/red-panda.jpg"));
+    }
+
+    #[test]
+    fn test_image_tool_name() {
+        let tool = BraveImageSearchTool::new(BraveSearchConfig {
+            api_key: "key".to_string(),
+            timeout_secs: 5,
+            result_count: 10,
+        });
+        assert_eq!(tool.name(), "brave_image_search");
+        assert_eq!(tool.parameters_schema()["required"], serde_json::json!(["query"]));
+    }
 }
@@ -0,0 +1,581 @@
+//! Calendar tool
+//!
+//! Reads and writes events on a CalDAV calendar collection using plain HTTP
+//! (`REPORT`, `PUT`, `DELETE`). Events are stored one per `{uid}.ics` resource.
+//!
+//! Times with a `TZID` parameter are converted from that IANA zone with
+//! `chrono-tz`; floating times (no `Z`, no `TZID`) are read as the host's
+//! local time.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use reqwest::{Client, Method};
+use secrecy::ExposeSecret;
+use serde_json::Value;
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::config::CalendarConfig;
+use crate::error::{Error, Result};
+
+/// Default timeout for CalDAV requests
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// iCalendar UTC timestamp format
+const ICAL_UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A calendar event
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub title: String,
+    pub start: DateTime<Utc>,
+    pub end: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+}
+
+/// Built-in tool: list, create, and delete CalDAV events
+pub struct CalendarTool {
+    client: Client,
+    config: CalendarConfig,
+}
+
+impl CalendarTool {
+    /// Create a new calendar tool
+    pub fn new(config: CalendarConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, config }
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth(&self.config.username, Some(self.config.password.expose_secret()))
+    }
+
+    /// URL of the `{uid}.ics` resource; rejects UIDs that could leave the
+    /// calendar collection or change the URL's meaning
+    fn event_url(&self, uid: &str) -> Result<String> {
+        let valid = !uid.is_empty()
+            && !uid.starts_with('.')
+            && !uid.contains("..")
+            && uid.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@' | '+' | '='));
+        if !valid {
+            return Err(Error::InvalidInput(format!("Invalid event uid '{}'", uid)));
+        }
+        Ok(format!("{}/{}.ics", self.config.caldav_url.trim_end_matches('/'), uid))
+    }
+
+    /// List events overlapping `[start, end)`
+    pub async fn list_events(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<CalendarEvent>> {
+        let body = format!(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><c:calendar-data/></d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+            start.format(ICAL_UTC_FORMAT),
+            end.format(ICAL_UTC_FORMAT)
+        );
+
+        let response = self
+            .request(Method::from_bytes(b"REPORT").expect("valid method"), &self.config.caldav_url)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| Error::Connection(format!("CalDAV REPORT failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Connection(format!(
+                "CalDAV REPORT failed with status {}",
+                response.status()
+            )));
+        }
+
+        let xml = response.text().await?;
+        let mut events: Vec<CalendarEvent> = extract_calendar_data(&xml)
+            .iter()
+            .flat_map(|ics| parse_events(ics))
+            .collect();
+        events.sort_by_key(|e| e.start);
+        Ok(events)
+    }
+
+    /// Create an event and return its UID
+    pub async fn create_event(
+        &self,
+        title: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        description: Option<&str>,
+    ) -> Result<String> {
+        let event = CalendarEvent {
+            uid: Uuid::new_v4().to_string(),
+            title: title.to_string(),
+            start,
+            end: Some(end),
+            description: description.map(str::to_string),
+        };
+
+        let response = self
+            .request(Method::PUT, &self.event_url(&event.uid)?)
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .header("If-None-Match", "*")
+            .body(to_ics(&event))
+            .send()
+            .await
+            .map_err(|e| Error::Connection(format!("CalDAV PUT failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(Error::Connection(format!(
+                "CalDAV PUT failed with status {}",
+                response.status()
+            )));
+        }
+
+        Ok(event.uid)
+    }
+
+    /// Delete an event by UID
+    pub async fn delete_event(&self, uid: &str) -> Result<()> {
+        let response = self
+            .request(Method::DELETE, &self.event_url(uid)?)
+            .send()
+            .await
+            .map_err(|e| Error::Connection(format!("CalDAV DELETE failed: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::NotFound(format!("Event {}", uid)));
+        }
+        if !response.status().is_success() {
+            return Err(Error::Connection(format!(
+                "CalDAV DELETE failed with status {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Tool for CalendarTool {
+    fn name(&self) -> &str {
+        "calendar"
+    }
+
+    fn description(&self) -> &str {
+        "Manage the user's calendar. Actions: 'list_events' (events between start and end), 'create_event' (title, start, end, optional description), 'delete_event' (uid). Times are RFC 3339, e.g. 2025-03-01T09:00:00Z."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list_events", "create_event", "delete_event"],
+                    "description": "Operation to perform"
+                },
+                "start": {
+                    "type": "string",
+                    "description": "Start time (RFC 3339) for list_events / create_event"
+                },
+                "end": {
+                    "type": "string",
+                    "description": "End time (RFC 3339) for list_events / create_event"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Event title for create_event"
+                },
+                "description": {
+                    "type": "string",
+                    "description": "Optional event description for create_event"
+                },
+                "uid": {
+                    "type": "string",
+                    "description": "Event UID for delete_event"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network, Permission::ExternalApi]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "action": "create_event", "title": "Dentist", "start": "2026-03-02T09:00:00Z", "end": "2026-03-02T09:30:00Z" }),
+            "Created event 'Dentist' (uid: 7e6d5c4b-openagent)".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+        let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+        let time_arg = |key: &str| -> std::result::Result<DateTime<Utc>, String> {
+            let raw = str_arg(key).ok_or_else(|| format!("Missing '{}' parameter", key))?;
+            DateTime::parse_from_rfc3339(raw)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| format!("Invalid '{}' time '{}': {}", key, raw, e))
+        };
+
+        match action {
+            "list_events" => {
+                let (start, end) = match (time_arg("start"), time_arg("end")) {
+                    (Ok(s), Ok(e)) => (s, e),
+                    (Err(e), _) | (_, Err(e)) => return Ok(ToolResult::failure(e)),
+                };
+                match self.list_events(start, end).await {
+                    Ok(events) if events.is_empty() => {
+                        Ok(ToolResult::success("No events in this time range."))
+                    }
+                    Ok(events) => Ok(ToolResult::success(format_events(&events))
+                        .with_metadata("event_count", serde_json::json!(events.len()))),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to list events: {}", e))),
+                }
+            }
+            "create_event" => {
+                let Some(title) = str_arg("title") else {
+                    return Ok(ToolResult::failure("Missing 'title' parameter"));
+                };
+                let (start, end) = match (time_arg("start"), time_arg("end")) {
+                    (Ok(s), Ok(e)) => (s, e),
+                    (Err(e), _) | (_, Err(e)) => return Ok(ToolResult::failure(e)),
+                };
+                if end <= start {
+                    return Ok(ToolResult::failure("'end' must be after 'start'"));
+                }
+                match self.create_event(title, start, end, str_arg("description")).await {
+                    Ok(uid) => Ok(ToolResult::success(format!(
+                        "Created event '{}' (uid: {})",
+                        title, uid
+                    ))
+                    .with_metadata("uid", serde_json::json!(uid))),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to create event: {}", e))),
+                }
+            }
+            "delete_event" => {
+                let Some(uid) = str_arg("uid") else {
+                    return Ok(ToolResult::failure("Missing 'uid' parameter"));
+                };
+                match self.delete_event(uid).await {
+                    Ok(()) => Ok(ToolResult::success(format!("Deleted event {}", uid))),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to delete event: {}", e))),
+                }
+            }
+            other => Ok(ToolResult::failure(format!(
+                "Unknown action '{}'. Use list_events, create_event, or delete_event",
+                other
+            ))),
+        }
+    }
+}
+
+fn format_events(events: &[CalendarEvent]) -> String {
+    events
+        .iter()
+        .map(|e| {
+            let mut line = format!(
+                "- {} | {}",
+                e.start.format("%Y-%m-%d %H:%M UTC"),
+                e.title
+            );
+            if let Some(end) = e.end {
+                line.push_str(&format!(" (until {})", end.format("%H:%M")));
+            }
+            line.push_str(&format!(" [uid: {}]", e.uid));
+            if let Some(desc) = &e.description {
+                line.push_str(&format!("\n  {}", desc));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Serialize an event as a VCALENDAR document
+fn to_ics(event: &CalendarEvent) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//OpenAgent//Calendar Tool//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", Utc::now().format(ICAL_UTC_FORMAT)),
+        format!("DTSTART:{}", event.start.format(ICAL_UTC_FORMAT)),
+    ];
+    if let Some(end) = event.end {
+        lines.push(format!("DTEND:{}", end.format(ICAL_UTC_FORMAT)));
+    }
+    lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+    if let Some(desc) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(desc)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Pull the iCalendar payloads out of a CalDAV multistatus response
+fn extract_calendar_data(xml: &str) -> Vec<String> {
+    let mut payloads = Vec::new();
+    let mut rest = xml;
+
+    while let Some(lt) = rest.find('<') {
+        let after = &rest[lt + 1..];
+        let Some(gt) = after.find('>') else { break };
+        let tag = &after[..gt];
+        rest = &after[gt + 1..];
+
+        // Namespace prefixes vary by server (`C:`, `cal:`, none), so match on the local name
+        let name = tag.split_whitespace().next().unwrap_or("");
+        let local = name.rsplit(':').next().unwrap_or(name);
+        if local != "calendar-data" || tag.ends_with('/') {
+            continue;
+        }
+
+        let Some(close) = rest.find("</") else { break };
+        payloads.push(xml_unescape(&rest[..close]));
+        rest = &rest[close..];
+    }
+
+    payloads
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#13;", "\r")
+        .replace("&amp;", "&")
+}
+
+/// Join continuation lines (RFC 5545 §3.1): a line break followed by a
+/// space or tab is removed. Bare `\n` line endings are accepted too.
+fn unfold(ics: &str) -> String {
+    ics.replace("\r\n", "\n").replace("\n ", "").replace("\n\t", "")
+}
+
+/// One content line: name, `TZID` parameter (if any) and value
+type Property = (String, Option<String>, String);
+
+/// Parse the VEVENTs in an iCalendar document
+fn parse_events(ics: &str) -> Vec<CalendarEvent> {
+    let unfolded = unfold(ics);
+
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfolded.lines() {
+        let line = line.trim_end_matches('\r');
+        match line {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(props) = current.take() {
+                    if let Some(event) = event_from_props(&props) {
+                        events.push(event);
+                    }
+                }
+            }
+            _ => {
+                if let (Some(props), Some((key, value))) = (current.as_mut(), line.split_once(':')) {
+                    // Keep `TZID` from e.g. `DTSTART;TZID=Europe/Berlin`, drop other parameters
+                    let mut params = key.split(';');
+                    let name = params.next().unwrap_or(key).to_ascii_uppercase();
+                    let tzid = params
+                        .filter_map(|param| param.split_once('='))
+                        .find(|(param, _)| param.eq_ignore_ascii_case("TZID"))
+                        .map(|(_, zone)| zone.trim_matches('"').to_string());
+                    props.push((name, tzid, value.to_string()));
+                }
+            }
+        }
+    }
+
+    events
+}
+
+fn event_from_props(props: &[Property]) -> Option<CalendarEvent> {
+    let find = |name: &str| props.iter().find(|(k, _, _)| k == name);
+    let get = |name: &str| find(name).map(|(_, _, v)| v.as_str());
+    let time = |name: &str| find(name).and_then(|(_, tzid, v)| parse_ical_time(v, tzid.as_deref()));
+    Some(CalendarEvent {
+        uid: get("UID")?.to_string(),
+        title: get("SUMMARY").map(unescape_text).unwrap_or_default(),
+        start: time("DTSTART")?,
+        end: time("DTEND"),
+        description: get("DESCRIPTION").map(unescape_text),
+    })
+}
+
+/// Parse `20250301T090000Z`, `20250301T090000` in zone `tzid` (the host's
+/// local time if there is none or it is unknown) or all-day `20250301`
+fn parse_ical_time(value: &str, tzid: Option<&str>) -> Option<DateTime<Utc>> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        return NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok().map(|t| t.and_utc());
+    }
+    if let Ok(t) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        // `earliest` picks the first of two readings in a DST fall-back hour
+        return match tzid.and_then(|zone| zone.parse::<Tz>().ok()) {
+            Some(zone) => zone.from_local_datetime(&t).earliest().map(|t| t.with_timezone(&Utc)),
+            None => Local.from_local_datetime(&t).earliest().map(|t| t.with_timezone(&Utc)),
+        };
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use secrecy::SecretString;
+
+    fn sample_event() -> CalendarEvent {
+        CalendarEvent {
+            uid: "abc-123".to_string(),
+            title: "Standup, daily".to_string(),
+            start: Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap(),
+            end: Some(Utc.with_ymd_and_hms(2025, 3, 1, 9, 15, 0).unwrap()),
+            description: Some("Line one\nLine two".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_ics_round_trip() {
+        let event = sample_event();
+        let parsed = parse_events(&to_ics(&event));
+        assert_eq!(parsed, vec![event]);
+    }
+
+    #[test]
+    fn test_escape_carriage_return() {
+        assert_eq!(escape_text("a\r\nb\rc"), "a\\nb\\nc");
+    }
+
+    #[test]
+    fn test_parse_tzid_times() {
+        let ics = "BEGIN:VEVENT\r\n\
+                   UID:tz-1\r\n\
+                   DTSTART;TZID=America/New_York:20250115T090000\r\n\
+                   DTEND;TZID=\"Europe/Berlin\":20250715T170000\r\n\
+                   END:VEVENT\r\n";
+        let events = parse_events(ics);
+        // EST is UTC-5; CEST is UTC+2
+        assert_eq!(events[0].start, Utc.with_ymd_and_hms(2025, 1, 15, 14, 0, 0).unwrap());
+        assert_eq!(events[0].end, Some(Utc.with_ymd_and_hms(2025, 7, 15, 15, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_folded_lines_with_bare_newlines() {
+        let ics = "BEGIN:VEVENT\nUID:fold-1\nDTSTART:20250301T090000Z\nSUMMARY:Quarterly\n  planning\nDESCRIPTION:Bring\n\tslides\nEND:VEVENT\n";
+        let events = parse_events(ics);
+        assert_eq!(events[0].title, "Quarterly planning");
+        assert_eq!(events[0].description.as_deref(), Some("Bringslides"));
+    }
+
+    #[test]
+    fn test_event_url_rejects_path_traversal() {
+        let tool = CalendarTool::new(CalendarConfig {
+            caldav_url: "https://dav.example.com/cal/".to_string(),
+            username: "user".to_string(),
+            password: SecretString::from("pass"),
+        });
+        assert_eq!(
+            tool.event_url("abc-123@example.com").unwrap(),
+            "https://dav.example.com/cal/abc-123@example.com.ics"
+        );
+        for uid in ["", "../other/cal", "a/b", "..", "x?y", "a%2Fb"] {
+            assert!(tool.event_url(uid).is_err(), "{}", uid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_events_parses_multistatus() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let ics = to_ics(&sample_event())
+            .replace('&', "&amp;")
+            .replace('<', "&lt;");
+        let multistatus = format!(
+            r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cal="urn:ietf:params:xml:ns:caldav">
+  <d:response>
+    <d:href>/cal/abc-123.ics</d:href>
+    <d:propstat><d:prop><cal:calendar-data>{}</cal:calendar-data></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#,
+            ics
+        );
+
+        let server = MockServer::start().await;
+        Mock::given(method("REPORT"))
+            .and(header("Depth", "1"))
+            .respond_with(ResponseTemplate::new(207).set_body_string(multistatus))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let tool = CalendarTool::new(CalendarConfig {
+            caldav_url: format!("{}/cal/", server.uri()),
+            username: "user".to_string(),
+            password: SecretString::from("pass"),
+        });
+
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let events = tool.list_events(start, start + chrono::Duration::days(1)).await.unwrap();
+        assert_eq!(events, vec![sample_event()]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Diff tool
+//!
+//! Computes a unified diff between two files in the workspace so the
+//! agent can show what an edit changed.
+
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::error::{Error, Result};
+
+/// Context lines around each hunk when `context_lines` is not given
+const DEFAULT_CONTEXT_LINES: usize = 3;
+
+/// Maximum diff lines returned before truncating
+const MAX_DIFF_LINES: usize = 500;
+
+/// Resolve `path` inside `allowed_dir`, rejecting anything that escapes it
+fn confine(allowed_dir: &Path, path: &str) -> std::result::Result<PathBuf, String> {
+    let root = allowed_dir
+        .canonicalize()
+        .map_err(|e| format!("Workspace unavailable: {}", e))?;
+    let full_path = root
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Cannot read '{}': {}", path, e))?;
+
+    if !full_path.starts_with(&root) {
+        return Err("Access denied: path outside workspace".to_string());
+    }
+    Ok(full_path)
+}
+
+/// Unified diff of `original` against `modified`, labelled with the given paths
+fn unified_diff(path_a: &str, path_b: &str, original: &str, modified: &str, context_lines: usize) -> String {
+    let patch = diffy::DiffOptions::new()
+        .set_context_len(context_lines)
+        .create_patch(original, modified)
+        .to_string();
+
+    // Replace diffy's generic "original"/"modified" header with the real paths
+    let mut diff = format!("--- {}\n+++ {}\n", path_a, path_b);
+    for line in patch.lines().skip(2) {
+        diff.push_str(line);
+        diff.push('\n');
+    }
+    diff
+}
+
+/// Keep the first `max_lines` lines, noting how many were dropped
+fn truncate_lines(text: &str, max_lines: usize) -> String {
+    let total = text.lines().count();
+    if total <= max_lines {
+        return text.to_string();
+    }
+
+    let mut output: String = text
+        .lines()
+        .take(max_lines)
+        .map(|line| format!("{}\n", line))
+        .collect();
+    output.push_str(&format!("... ({} more lines)", total - max_lines));
+    output
+}
+
+/// Built-in tool: unified diff between two workspace files
+pub struct DiffTool {
+    allowed_dir: PathBuf,
+}
+
+impl DiffTool {
+    pub fn new(allowed_dir: PathBuf) -> Self {
+        DiffTool { allowed_dir }
+    }
+}
+
+#[async_trait]
+impl Tool for DiffTool {
+    fn name(&self) -> &str {
+        "diff"
+    }
+
+    fn description(&self) -> &str {
+        "Show a unified diff between two files in the workspace"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path_a": {
+                    "type": "string",
+                    "description": "Original file (relative to workspace)"
+                },
+                "path_b": {
+                    "type": "string",
+                    "description": "Modified file (relative to workspace)"
+                },
+                "context_lines": {
+                    "type": "integer",
+                    "description": "Unchanged lines shown around each change (default: 3)"
+                }
+            },
+            "required": ["path_a", "path_b"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "path_a": "config.old.toml", "path_b": "config.toml", "context_lines": 1 }),
+            "--- config.old.toml\n+++ config.toml\n@@ -2,3 +2,3 @@\n [agent]\n-model = \"old/model\"\n+model = \"new/model\"\n timeout = 30".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let path_a = args
+            .get("path_a")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'path_a' parameter".to_string()))?;
+        let path_b = args
+            .get("path_b")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'path_b' parameter".to_string()))?;
+        let context_lines = args
+            .get("context_lines")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(DEFAULT_CONTEXT_LINES);
+
+        let full_a = match confine(&self.allowed_dir, path_a) {
+            Ok(p) => p,
+            Err(e) => return Ok(ToolResult::failure(e)),
+        };
+        let full_b = match confine(&self.allowed_dir, path_b) {
+            Ok(p) => p,
+            Err(e) => return Ok(ToolResult::failure(e)),
+        };
+
+        let original = match tokio::fs::read_to_string(&full_a).await {
+            Ok(s) => s,
+            Err(e) => return Ok(ToolResult::failure(format!("Failed to read '{}': {}", path_a, e))),
+        };
+        let modified = match tokio::fs::read_to_string(&full_b).await {
+            Ok(s) => s,
+            Err(e) => return Ok(ToolResult::failure(format!("Failed to read '{}': {}", path_b, e))),
+        };
+
+        if original == modified {
+            return Ok(ToolResult::success("Files are identical"));
+        }
+
+        let diff = unified_diff(path_a, path_b, &original, &modified, context_lines);
+        let diff_lines = diff.lines().count();
+        Ok(ToolResult::success(truncate_lines(&diff, MAX_DIFF_LINES))
+            .with_metadata("diff_lines", serde_json::json!(diff_lines)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_lines() {
+        let text: String = (0..505).map(|i| format!("{}\n", i)).collect();
+        let truncated = truncate_lines(&text, MAX_DIFF_LINES);
+        assert_eq!(truncated.lines().count(), MAX_DIFF_LINES + 1);
+        assert!(truncated.ends_with("... (5 more lines)"));
+
+        assert_eq!(truncate_lines("a\nb\n", MAX_DIFF_LINES), "a\nb\n");
+    }
+
+    #[tokio::test]
+    async fn test_diff_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "one\n2\nthree\n").unwrap();
+
+        let tool = DiffTool::new(dir.path().to_path_buf());
+        let result = tool
+            .execute(serde_json::json!({"path_a": "a.txt", "path_b": "b.txt"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let diff = result.content.unwrap();
+        assert!(diff.starts_with("--- a.txt\n+++ b.txt\n@@"));
+        assert!(diff.contains("-two\n+2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_diff_rejects_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = dir.path().join("ws");
+        std::fs::create_dir(&inner).unwrap();
+        std::fs::write(inner.join("a.txt"), "a\n").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "b\n").unwrap();
+
+        let tool = DiffTool::new(inner);
+        let result = tool
+            .execute(serde_json::json!({"path_a": "a.txt", "path_b": "../secret.txt"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+}
@@ -9,8 +9,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::time::Duration;
 
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
-use super::{format_search_results, urlencoding};
+use super::{format_search_results, search_tool_result, urlencoding};
 use crate::Result;
 
 /// Default timeout for web search requests
@@ -281,6 +282,17 @@ impl Tool for DuckDuckGoSearchTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "query": "rust async book", "count": 1 }),
+            "1. **Asynchronous Programming in Rust**\n   URL: https://rust-lang.github.io/async-book/\n   An introduction to async/await in Rust.".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let query = args
             .get("query")
@@ -307,8 +319,7 @@ impl Tool for DuckDuckGoSearchTool {
                 query
             )))
         } else {
-            let formatted = format_search_results(&results);
-            Ok(ToolResult::success(formatted))
+            Ok(search_tool_result(format_search_results(&results), results.len()))
         }
     }
 }
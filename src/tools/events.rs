@@ -0,0 +1,68 @@
+//! Push events from tools
+//!
+//! Most tools only answer calls, but some sources push updates on their own
+//! (e.g. an MCP subscription, see
+//! [`McpToolBridge::subscribe_as_event_source`](crate::mcp::McpToolBridge::subscribe_as_event_source)).
+//! Producers publish each update as a [`ToolResult`] on the registry's
+//! [`ToolEventBus`]; agentic loops listening on that source (see
+//! `LoopConfig::with_event_source`) add it to their context before the next
+//! LLM call.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast;
+
+use super::traits::ToolResult;
+
+/// Events buffered per source for slow listeners; older ones are dropped
+const EVENT_CAPACITY: usize = 64;
+
+/// Named channels of tool results, shared by clones
+#[derive(Clone, Default)]
+pub struct ToolEventBus {
+    sources: Arc<Mutex<HashMap<String, broadcast::Sender<ToolResult>>>>,
+}
+
+impl ToolEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver `event` to every current listener of `source`, returning how
+    /// many received it. Events without listeners are dropped.
+    pub fn publish(&self, source: &str, event: ToolResult) -> usize {
+        let sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources
+            .get(source)
+            .and_then(|sender| sender.send(event).ok())
+            .unwrap_or(0)
+    }
+
+    /// Listen for events published on `source` from now on
+    pub fn subscribe(&self, source: &str) -> broadcast::Receiver<ToolResult> {
+        let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        sources
+            .entry(source.to_string())
+            .or_insert_with(|| broadcast::channel(EVENT_CAPACITY).0)
+            .subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_reaches_listeners_of_the_source() {
+        let bus = ToolEventBus::new();
+        assert_eq!(bus.publish("weather", ToolResult::success("lost")), 0);
+
+        let mut weather = bus.subscribe("weather");
+        let mut news = bus.clone().subscribe("news");
+        assert_eq!(bus.publish("weather", ToolResult::success("rain")), 1);
+
+        assert_eq!(weather.recv().await.unwrap().content.as_deref(), Some("rain"));
+        assert!(news.try_recv().is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! User-facing tool documentation
+//!
+//! [`ToolRegistry::explain`](super::ToolRegistry::explain) turns a tool's
+//! description, parameter schema and [`Tool::example`] into a
+//! [`ToolExplanation`], shown by `openagent tools explain <tool>` and the
+//! TUI `/tools <tool>` command.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::traits::Tool;
+
+/// One parameter from a tool's schema
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterDoc {
+    pub name: String,
+    /// JSON Schema type, e.g. `string` or `integer | null`
+    pub param_type: String,
+    pub description: String,
+    pub required: bool,
+    /// Allowed values, when the schema has an `enum`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_values: Vec<Value>,
+}
+
+/// Documentation for a registered tool
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolExplanation {
+    pub name: String,
+    pub description: String,
+    /// Required parameters first, then the rest, each in schema order
+    pub parameters: Vec<ParameterDoc>,
+    pub example_input: Value,
+    /// Empty when the tool doesn't describe its output
+    pub example_output: String,
+}
+
+impl ToolExplanation {
+    pub fn for_tool(tool: &dyn Tool) -> Self {
+        let (example_input, example_output) = tool.example();
+        ToolExplanation {
+            name: tool.name().to_string(),
+            description: tool.description().to_string(),
+            parameters: parameter_docs(&tool.parameters_schema()),
+            example_input,
+            example_output,
+        }
+    }
+}
+
+impl fmt::Display for ToolExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}\n\n{}", self.name, self.description)?;
+
+        if !self.parameters.is_empty() {
+            writeln!(f, "\nParameters:")?;
+            for param in &self.parameters {
+                let required = if param.required { ", required" } else { "" };
+                write!(f, "  {} ({}{})", param.name, param.param_type, required)?;
+                if !param.description.is_empty() {
+                    write!(f, " - {}", param.description)?;
+                }
+                if !param.allowed_values.is_empty() {
+                    let values: Vec<String> = param.allowed_values.iter().map(Value::to_string).collect();
+                    write!(f, " [{}]", values.join(", "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        let input = serde_json::to_string_pretty(&self.example_input).unwrap_or_default();
+        write!(f, "\nExample input:\n{}", input)?;
+        if !self.example_output.is_empty() {
+            write!(f, "\n\nExample output:\n{}", self.example_output)?;
+        }
+        Ok(())
+    }
+}
+
+/// Document the top-level properties of a parameters schema
+fn parameter_docs(schema: &Value) -> Vec<ParameterDoc> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut docs: Vec<ParameterDoc> = properties
+        .iter()
+        .map(|(name, property)| ParameterDoc {
+            name: name.clone(),
+            param_type: match property.get("type") {
+                Some(Value::String(name)) => name.clone(),
+                Some(Value::Array(names)) => {
+                    names.iter().filter_map(Value::as_str).collect::<Vec<_>>().join(" | ")
+                }
+                _ => "any".to_string(),
+            },
+            description: property
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            required: required.contains(&name.as_str()),
+            allowed_values: property
+                .get("enum")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default(),
+        })
+        .collect();
+    // Stable, so schema order is kept within each group
+    docs.sort_by_key(|doc| !doc.required);
+    docs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ReadFileTool, ToolRegistry};
+    use serde_json::json;
+
+    #[test]
+    fn test_parameter_docs() {
+        let docs = parameter_docs(&json!({
+            "type": "object",
+            "properties": {
+                "limit": { "type": ["integer", "null"] },
+                "operation": { "type": "string", "enum": ["status", "log"], "description": "What to run" }
+            },
+            "required": ["operation"]
+        }));
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].name, "operation");
+        assert!(docs[0].required);
+        assert_eq!(docs[0].allowed_values, vec![json!("status"), json!("log")]);
+        assert_eq!(docs[1].param_type, "integer | null");
+    }
+
+    #[test]
+    fn test_explain_registered_tool() {
+        let mut registry = ToolRegistry::new();
+        registry.register(ReadFileTool::new(".".into()));
+        assert!(registry.explain("missing").is_none());
+
+        let explanation = registry.explain("read_file").unwrap();
+        assert_eq!(explanation.parameters[0].name, "path");
+        assert_eq!(explanation.example_input, json!({ "path": "notes/todo.md" }));
+        let text = explanation.to_string();
+        assert!(text.starts_with("read_file\n"));
+        assert!(text.contains("path (string, required)"));
+        assert!(text.contains("Example output:"));
+    }
+}
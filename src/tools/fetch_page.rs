@@ -0,0 +1,411 @@
+//! Page fetch tool
+//!
+//! Search tools only return snippets. `fetch_page` downloads a page (usually
+//! a search result's URL) and returns its readable text: HTML is converted
+//! with `html2text`, which drops scripts and styles, and the text is cut to
+//! `max_chars`.
+//!
+//! The tool is available to group chats, so URLs must not reach the gateway's
+//! own network: hosts are resolved first and loopback, private, link-local
+//! and unspecified addresses are refused. Redirects are followed by hand so
+//! that every hop is checked, and each request connects to the addresses
+//! that were checked, not to a second DNS answer.
+
+use async_trait::async_trait;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::{redirect, Client};
+use serde_json::Value;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use url::{Host, Url};
+
+use super::duckduckgo_search::SearchResult;
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::{Error, Result};
+
+/// Default maximum characters of page text returned
+pub const DEFAULT_MAX_CHARS: usize = 4000;
+
+/// Default timeout for page downloads
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
+
+/// Stop downloading after this many bytes
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Line width used when rendering HTML as text
+const TEXT_WIDTH: usize = 120;
+
+/// Most redirects followed for one page
+const MAX_REDIRECTS: usize = 5;
+
+const USER_AGENT: &str = "OpenAgent/1.0";
+
+/// Downloads pages and extracts their readable text
+#[derive(Clone)]
+pub struct ContentFetcher {
+    client: Client,
+    max_chars: usize,
+    /// Skip the public address check (tests against a local server)
+    allow_private: bool,
+}
+
+impl Default for ContentFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentFetcher {
+    /// Create a fetcher returning up to [`DEFAULT_MAX_CHARS`] characters
+    pub fn new() -> Self {
+        Self {
+            client: client_builder().build().expect("Failed to create HTTP client"),
+            max_chars: DEFAULT_MAX_CHARS,
+            allow_private: false,
+        }
+    }
+
+    #[cfg(test)]
+    fn allowing_private_hosts(mut self) -> Self {
+        self.allow_private = true;
+        self
+    }
+
+    /// Set the maximum characters of text returned
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Download `url` and return its readable text
+    ///
+    /// HTML is converted to text; other `text/*` and JSON bodies are returned
+    /// as-is. Binary content types, and URLs or redirects that lead to a
+    /// non-public address, are rejected.
+    pub async fn fetch(&self, url: &str, timeout: Duration) -> Result<String> {
+        let mut target =
+            Url::parse(url).map_err(|e| Error::InvalidInput(format!("Invalid URL '{}': {}", url, e)))?;
+        let mut redirects = 0;
+        let mut response = loop {
+            let client = self.client_for(&target).await?;
+            let response = client.get(target.clone()).timeout(timeout).send().await?;
+            if !response.status().is_redirection() {
+                break response;
+            }
+            let Some(location) = response.headers().get(LOCATION).and_then(|v| v.to_str().ok()) else {
+                break response;
+            };
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                return Err(Error::Provider(format!("Fetching {} redirected too many times", url)));
+            }
+            target = target
+                .join(location)
+                .map_err(|e| Error::Provider(format!("Invalid redirect from {}: {}", target, e)))?;
+        };
+        if !response.status().is_success() {
+            return Err(Error::Provider(format!(
+                "Fetching {} failed with status {}",
+                url,
+                response.status()
+            )));
+        }
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/html")
+            .to_ascii_lowercase();
+        let is_html = content_type.contains("html");
+        if !is_html && !content_type.starts_with("text/") && !content_type.contains("json") {
+            return Err(Error::NotSupported(format!("Cannot read {} content from {}", content_type, url)));
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if body.len() >= MAX_BODY_BYTES {
+                body.truncate(MAX_BODY_BYTES);
+                break;
+            }
+        }
+
+        let text = if is_html {
+            html_to_text(&body)?
+        } else {
+            String::from_utf8_lossy(&body).into_owned()
+        };
+        Ok(truncate_chars(text.trim(), self.max_chars))
+    }
+}
+
+impl ContentFetcher {
+    /// Client that may connect to `url`, after checking its scheme and that
+    /// its host resolves only to public addresses
+    async fn client_for(&self, url: &Url) -> Result<Client> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::InvalidInput(format!("Only http(s) URLs can be fetched, got '{}'", url)));
+        }
+        if self.allow_private {
+            return Ok(self.client.clone());
+        }
+        let blocked = || Error::InvalidInput(format!("Refusing to fetch {}: not a public address", url));
+
+        let domain = match url.host() {
+            Some(Host::Ipv4(ip)) if is_public_ip(IpAddr::V4(ip)) => return Ok(self.client.clone()),
+            Some(Host::Ipv6(ip)) if is_public_ip(IpAddr::V6(ip)) => return Ok(self.client.clone()),
+            Some(Host::Domain(domain)) => domain.to_string(),
+            _ => return Err(blocked()),
+        };
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain.as_str(), port))
+            .await
+            .map_err(|e| Error::Provider(format!("Cannot resolve {}: {}", domain, e)))?
+            .collect();
+        if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+            return Err(blocked());
+        }
+        // Connect to the checked addresses, so a second lookup can't differ
+        Ok(client_builder().resolve_to_addrs(&domain, &addrs).build()?)
+    }
+}
+
+fn client_builder() -> reqwest::ClientBuilder {
+    Client::builder().user_agent(USER_AGENT).redirect(redirect::Policy::none())
+}
+
+/// Whether `ip` is reachable on the public internet: not loopback, private,
+/// link-local, unspecified, shared (CGNAT), multicast or documentation space
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_public_ipv4(mapped);
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8)) // documentation
+        }
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || a == 0
+        || (a == 100 && (b & 0xc0) == 64)) // shared address space
+}
+
+impl SearchResult {
+    /// Download this result's page and return up to [`DEFAULT_MAX_CHARS`]
+    /// characters of readable text
+    pub async fn fetch_full_content(&self, timeout: Duration) -> Result<String> {
+        ContentFetcher::new().fetch(&self.url, timeout).await
+    }
+}
+
+/// Render HTML as plain text, without scripts and styles
+fn html_to_text(html: &[u8]) -> Result<String> {
+    html2text::from_read(html, TEXT_WIDTH).map_err(|e| Error::Internal(format!("Failed to convert HTML: {}", e)))
+}
+
+/// Keep the first `max_chars` characters, marking the cut
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}\n\n[truncated after {} characters]", &text[..end], max_chars),
+        None => text.to_string(),
+    }
+}
+
+/// Tool that reads a web page, typically a search result
+pub struct FetchPageTool {
+    fetcher: ContentFetcher,
+}
+
+impl Default for FetchPageTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FetchPageTool {
+    pub fn new() -> Self {
+        Self {
+            fetcher: ContentFetcher::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for FetchPageTool {
+    fn name(&self) -> &str {
+        "fetch_page"
+    }
+
+    fn description(&self) -> &str {
+        "Download a web page and return its readable text (scripts and markup removed). Use after a web search to read a specific result in full."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The http(s) URL to read"
+                },
+                "max_chars": {
+                    "type": "integer",
+                    "description": "Maximum characters of text to return (default: 4000)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "url": "https://example.com/", "max_chars": 200 }),
+            "Example Domain\n\nThis domain is for use in illustrative examples in documents.".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let url = args
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'url' parameter".to_string()))?;
+
+        let mut fetcher = self.fetcher.clone();
+        if let Some(max_chars) = args.get("max_chars").and_then(|v| v.as_u64()) {
+            fetcher = fetcher.with_max_chars(max_chars.max(1) as usize);
+        }
+
+        match fetcher.fetch(url, Duration::from_secs(DEFAULT_TIMEOUT_SECS)).await {
+            Ok(text) if text.is_empty() => Ok(ToolResult::success(format!("{} has no readable text", url))),
+            Ok(text) => Ok(ToolResult::success(text)),
+            Err(e) => Ok(ToolResult::failure(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const PAGE: &str = r#"<html><head><title>T</title><style>body { color: red; }</style>
+        <script>var secret = "tracking";</script></head>
+        <body><h1>Release notes</h1><p>Version 2.0 adds <b>streaming</b>.</p></body></html>"#;
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("héllo", 10), "héllo");
+        assert_eq!(truncate_chars("héllo", 2), "hé\n\n[truncated after 2 characters]");
+    }
+
+    fn local_tool() -> FetchPageTool {
+        FetchPageTool {
+            fetcher: ContentFetcher::new().allowing_private_hosts(),
+        }
+    }
+
+    #[test]
+    fn test_is_public_ip() {
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in [
+            "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "0.0.0.0",
+            "100.64.0.1", "::1", "::", "fe80::1", "fd00::1", "::ffff:127.0.0.1", "::ffff:10.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_refuses_internal_hosts() {
+        let tool = FetchPageTool::new();
+        for url in [
+            "http://127.0.0.1:18789/tools/reload",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]/",
+            "http://localhost/",
+            "http://10.0.0.1/",
+        ] {
+            let result = tool.execute(json!({ "url": url })).await.unwrap();
+            assert!(!result.success, "{}", url);
+            assert!(result.error.unwrap().contains("not a public address"), "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_strips_scripts() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/notes"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(PAGE, "text/html; charset=utf-8"))
+            .mount(&server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/moved"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/notes"))
+            .mount(&server)
+            .await;
+
+        // Redirects are followed by hand
+        let result = local_tool()
+            .execute(json!({ "url": format!("{}/moved", server.uri()) }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        let text = result.content.unwrap();
+        assert!(text.contains("Release notes"));
+        assert!(text.contains("streaming"));
+        assert!(!text.contains("tracking"));
+        assert!(!text.contains("color: red"));
+
+        // Search results go through the same address check
+        let search_result = SearchResult {
+            title: "Notes".to_string(),
+            url: format!("{}/notes", server.uri()),
+            snippet: String::new(),
+        };
+        assert!(search_result.fetch_full_content(Duration::from_secs(5)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_page_rejects_non_http_and_binary() {
+        let tool = local_tool();
+        let result = tool.execute(json!({ "url": "file:///etc/passwd" })).await.unwrap();
+        assert!(!result.success);
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(vec![0u8; 16], "image/png"))
+            .mount(&server)
+            .await;
+        let result = tool.execute(json!({ "url": server.uri() })).await.unwrap();
+        assert!(!result.success);
+    }
+}
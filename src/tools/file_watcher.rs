@@ -0,0 +1,379 @@
+//! File watcher tools
+//!
+//! `file_watch` registers a watcher on a file or directory inside the
+//! workspace and returns a `watch_id`; `file_watch_poll` drains the events
+//! collected for that watch since the previous poll. Both tools are meant
+//! for DM sessions only.
+//!
+//! A watch whose path has been removed is closed: its last events are
+//! returned by the next poll, and watches on removed paths are dropped
+//! whenever a new watch is registered.
+
+use async_trait::async_trait;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::error::{Error, Result};
+
+/// Maximum events buffered per watch between polls; older events are dropped.
+const MAX_BUFFERED_EVENTS: usize = 1000;
+
+/// Kind of file system change a watch can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum WatchEventKind {
+    Created,
+    Modified,
+    Deleted,
+}
+
+impl WatchEventKind {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "created" => Some(WatchEventKind::Created),
+            "modified" => Some(WatchEventKind::Modified),
+            "deleted" => Some(WatchEventKind::Deleted),
+            _ => None,
+        }
+    }
+
+    fn from_notify(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(WatchEventKind::Created),
+            EventKind::Modify(_) => Some(WatchEventKind::Modified),
+            EventKind::Remove(_) => Some(WatchEventKind::Deleted),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            WatchEventKind::Created => "created",
+            WatchEventKind::Modified => "modified",
+            WatchEventKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A single recorded change
+#[derive(Debug, Clone)]
+struct WatchEvent {
+    kind: WatchEventKind,
+    path: PathBuf,
+    at: chrono::DateTime<chrono::Utc>,
+}
+
+/// An active watch and the events it has collected
+struct ActiveWatch {
+    /// Keeps the OS watcher alive for as long as the watch is registered
+    _watcher: RecommendedWatcher,
+    path: PathBuf,
+    events: Arc<Mutex<Vec<WatchEvent>>>,
+}
+
+/// Active watches shared between the watch and poll tools
+type WatchTable = Arc<Mutex<HashMap<String, ActiveWatch>>>;
+
+/// Drop watches whose path no longer exists and that have no events left to
+/// poll; returns how many were dropped
+fn prune_removed(watches: &mut HashMap<String, ActiveWatch>) -> usize {
+    let before = watches.len();
+    watches.retain(|_, watch| watch.path.exists() || !watch.events.lock().unwrap().is_empty());
+    before - watches.len()
+}
+
+/// Resolve `path` inside `allowed_dir`, rejecting anything that escapes it
+fn confine(allowed_dir: &Path, path: &str) -> std::result::Result<PathBuf, String> {
+    let root = allowed_dir
+        .canonicalize()
+        .map_err(|e| format!("Workspace unavailable: {}", e))?;
+    let full_path = root
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Cannot watch '{}': {}", path, e))?;
+
+    if !full_path.starts_with(&root) {
+        return Err("Access denied: path outside workspace".to_string());
+    }
+    Ok(full_path)
+}
+
+/// Built-in tool: start watching a file or directory
+pub struct FileWatcherTool {
+    allowed_dir: PathBuf,
+    watches: WatchTable,
+}
+
+impl FileWatcherTool {
+    pub fn new(allowed_dir: PathBuf) -> Self {
+        FileWatcherTool {
+            allowed_dir,
+            watches: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Create the companion poll tool sharing this tool's watches
+    pub fn poll_tool(&self) -> FileWatcherPollTool {
+        FileWatcherPollTool {
+            watches: Arc::clone(&self.watches),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for FileWatcherTool {
+    fn name(&self) -> &str {
+        "file_watch"
+    }
+
+    fn description(&self) -> &str {
+        "Watch a file or directory in the workspace for changes. Returns a watch_id; call file_watch_poll with it to retrieve events."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File or directory to watch (relative to workspace)"
+                },
+                "events": {
+                    "type": "array",
+                    "items": { "type": "string", "enum": ["created", "modified", "deleted"] },
+                    "description": "Event types to record. Default: all"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "path": "logs", "events": ["created", "modified"] }),
+            "Watching /workspace/logs (watch_id: 3f1c2a9e-5b7d-4c1e-9a0f-2d6e8b4c7a11)".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'path' parameter".to_string()))?;
+
+        let kinds: Vec<WatchEventKind> = match args.get("events").and_then(|v| v.as_array()) {
+            Some(list) => {
+                let mut kinds = Vec::new();
+                for item in list {
+                    let name = item.as_str().unwrap_or_default();
+                    match WatchEventKind::parse(name) {
+                        Some(kind) => kinds.push(kind),
+                        None => {
+                            return Ok(ToolResult::failure(format!(
+                                "Unknown event type '{}'. Valid: created, modified, deleted",
+                                name
+                            )))
+                        }
+                    }
+                }
+                kinds
+            }
+            None => vec![
+                WatchEventKind::Created,
+                WatchEventKind::Modified,
+                WatchEventKind::Deleted,
+            ],
+        };
+
+        let full_path = match confine(&self.allowed_dir, path) {
+            Ok(p) => p,
+            Err(e) => return Ok(ToolResult::failure(e)),
+        };
+
+        let events: Arc<Mutex<Vec<WatchEvent>>> = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let handler = move |res: notify::Result<Event>| {
+            let Ok(event) = res else { return };
+            let Some(kind) = WatchEventKind::from_notify(&event.kind) else { return };
+            if !kinds.contains(&kind) {
+                return;
+            }
+            let mut buf = sink.lock().unwrap();
+            for path in event.paths {
+                buf.push(WatchEvent { kind, path, at: chrono::Utc::now() });
+            }
+            let overflow = buf.len().saturating_sub(MAX_BUFFERED_EVENTS);
+            buf.drain(..overflow);
+        };
+
+        let mut watcher = notify::recommended_watcher(handler)
+            .map_err(|e| Error::Internal(format!("Failed to create file watcher: {}", e)))?;
+        if let Err(e) = watcher.watch(&full_path, RecursiveMode::Recursive) {
+            return Ok(ToolResult::failure(format!("Failed to watch '{}': {}", path, e)));
+        }
+
+        let watch_id = Uuid::new_v4().to_string();
+        let mut watches = self.watches.lock().unwrap();
+        let pruned = prune_removed(&mut watches);
+        if pruned > 0 {
+            tracing::debug!("Dropped {} watches on removed paths", pruned);
+        }
+        watches.insert(
+            watch_id.clone(),
+            ActiveWatch {
+                _watcher: watcher,
+                path: full_path.clone(),
+                events,
+            },
+        );
+        drop(watches);
+
+        Ok(ToolResult::success(format!(
+            "Watching {} (watch_id: {})",
+            full_path.display(),
+            watch_id
+        ))
+        .with_metadata("watch_id", serde_json::json!(watch_id)))
+    }
+}
+
+/// Built-in tool: drain events collected by a watch
+pub struct FileWatcherPollTool {
+    watches: WatchTable,
+}
+
+#[async_trait]
+impl Tool for FileWatcherPollTool {
+    fn name(&self) -> &str {
+        "file_watch_poll"
+    }
+
+    fn description(&self) -> &str {
+        "Return file system events recorded by a file_watch since the last poll"
+    }
+
+    fn parameters_schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "watch_id": {
+                    "type": "string",
+                    "description": "The watch_id returned by file_watch"
+                }
+            },
+            "required": ["watch_id"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "watch_id": "3f1c2a9e-5b7d-4c1e-9a0f-2d6e8b4c7a11" }),
+            "1 change(s) in /workspace/logs:\n- [14:02:11] modified /workspace/logs/app.log".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let watch_id = args
+            .get("watch_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'watch_id' parameter".to_string()))?;
+
+        let (watched, events, removed) = {
+            let mut watches = self.watches.lock().unwrap();
+            let (watched, events) = match watches.get(watch_id) {
+                Some(w) => (w.path.clone(), std::mem::take(&mut *w.events.lock().unwrap())),
+                None => return Ok(ToolResult::failure(format!("Unknown watch_id: {}", watch_id))),
+            };
+            // The OS watcher stops reporting once its path is gone
+            let removed = !watched.exists();
+            if removed {
+                watches.remove(watch_id);
+            }
+            (watched, events, removed)
+        };
+        let closed = if removed {
+            format!("\n{} was removed; watch {} is closed.", watched.display(), watch_id)
+        } else {
+            String::new()
+        };
+
+        if events.is_empty() {
+            return Ok(ToolResult::success(format!(
+                "No changes in {} since last poll.{}",
+                watched.display(),
+                closed
+            )));
+        }
+
+        let mut output = format!("{} change(s) in {}:\n", events.len(), watched.display());
+        for event in &events {
+            output.push_str(&format!(
+                "- [{}] {} {}\n",
+                event.at.format("%H:%M:%S"),
+                event.kind.as_str(),
+                event.path.display()
+            ));
+        }
+        output.push_str(closed.trim_start());
+        Ok(ToolResult::success(output))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confine_rejects_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = dir.path().join("ws");
+        std::fs::create_dir(&inner).unwrap();
+
+        assert!(confine(&inner, ".").is_ok());
+        assert!(confine(&inner, "..").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watch_on_removed_path_is_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("logs")).unwrap();
+        let tool = FileWatcherTool::new(dir.path().to_path_buf());
+        let poll = tool.poll_tool();
+
+        let result = tool.execute(serde_json::json!({"path": "logs"})).await.unwrap();
+        let watch_id = result.metadata["watch_id"].as_str().unwrap().to_string();
+        std::fs::remove_dir(dir.path().join("logs")).unwrap();
+
+        let result = poll.execute(serde_json::json!({"watch_id": watch_id})).await.unwrap();
+        let content = result.content.unwrap();
+        assert!(content.contains("is closed"), "{}", content);
+        assert!(tool.watches.lock().unwrap().is_empty());
+
+        let result = poll.execute(serde_json::json!({"watch_id": watch_id})).await.unwrap();
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_poll_unknown_watch() {
+        let tool = FileWatcherTool::new(PathBuf::from("."));
+        let result = tool
+            .poll_tool()
+            .execute(serde_json::json!({"watch_id": "missing"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+    }
+}
@@ -0,0 +1,539 @@
+//! Git tool
+//!
+//! Runs common `git` operations on repositories inside the workspace and
+//! returns their results as JSON rather than raw git output. Commands go
+//! through [`SystemCommandTool`] restricted to `git`.
+//!
+//! Operations that change the repository (`add`, `commit`, `checkout`) are
+//! only available when `sandbox.git_write_enabled` is set.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use super::system_command::SystemCommandTool;
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::error::{Error, Result};
+
+/// Commits returned by `log` when `limit` is not given
+const DEFAULT_LOG_LIMIT: u64 = 10;
+
+/// Upper bound for `log`'s `limit`
+const MAX_LOG_LIMIT: u64 = 100;
+
+/// Maximum patch lines returned by `diff` before truncating
+const MAX_PATCH_LINES: usize = 500;
+
+/// Field separator for `git log --pretty` output
+const LOG_SEPARATOR: char = '\u{1f}';
+
+/// Quote `arg` for `/bin/sh`, which [`SystemCommandTool`] runs commands through
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Reject empty values and ones git would read as an option
+fn valid_ref_or_path(value: &str) -> bool {
+    !value.is_empty() && !value.starts_with('-')
+}
+
+/// Parse `git status --porcelain=v1 --branch`
+fn parse_status(output: &str) -> Value {
+    let mut branch = Value::Null;
+    let mut upstream = Value::Null;
+    let (mut ahead, mut behind) = (0u64, 0u64);
+    let (mut staged, mut unstaged, mut untracked) = (Vec::new(), Vec::new(), Vec::new());
+
+    for line in output.lines() {
+        if let Some(header) = line.strip_prefix("## ") {
+            // "main...origin/main [ahead 1, behind 2]" or "No commits yet on main"
+            let (refs, counts) = header.split_once(" [").unwrap_or((header, ""));
+            let refs = refs.strip_prefix("No commits yet on ").unwrap_or(refs);
+            match refs.split_once("...") {
+                Some((local, remote)) => {
+                    branch = json!(local);
+                    upstream = json!(remote);
+                }
+                None => branch = json!(refs),
+            }
+            for part in counts.trim_end_matches(']').split(", ") {
+                if let Some(n) = part.strip_prefix("ahead ") {
+                    ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = part.strip_prefix("behind ") {
+                    behind = n.parse().unwrap_or(0);
+                }
+            }
+            continue;
+        }
+        if line.len() < 4 {
+            continue;
+        }
+
+        let (code, path) = line.split_at(3);
+        let mut code = code.chars();
+        let (index, worktree) = (code.next().unwrap_or(' '), code.next().unwrap_or(' '));
+        if index == '?' {
+            untracked.push(json!(path));
+            continue;
+        }
+        if index != ' ' {
+            staged.push(json!({ "path": path, "status": index.to_string() }));
+        }
+        if worktree != ' ' {
+            unstaged.push(json!({ "path": path, "status": worktree.to_string() }));
+        }
+    }
+
+    json!({
+        "branch": branch,
+        "upstream": upstream,
+        "ahead": ahead,
+        "behind": behind,
+        "clean": staged.is_empty() && unstaged.is_empty() && untracked.is_empty(),
+        "staged": staged,
+        "unstaged": unstaged,
+        "untracked": untracked,
+    })
+}
+
+/// Parse `git diff --numstat`; binary files report `null` line counts
+fn parse_numstat(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let additions = fields.next()?;
+            let deletions = fields.next()?;
+            let path = fields.next()?;
+            Some(json!({
+                "path": path,
+                "additions": additions.parse::<u64>().ok(),
+                "deletions": deletions.parse::<u64>().ok(),
+            }))
+        })
+        .collect()
+}
+
+/// Parse `git log` output written with [`LOG_SEPARATOR`]-separated fields
+fn parse_log(output: &str) -> Vec<Value> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.splitn(4, LOG_SEPARATOR).collect();
+            let [hash, author, date, subject] = fields[..] else {
+                return None;
+            };
+            Some(json!({
+                "hash": hash,
+                "author": author,
+                "date": date,
+                "subject": subject,
+            }))
+        })
+        .collect()
+}
+
+/// Parse `git branch --list`
+fn parse_branches(output: &str) -> Value {
+    let mut current = Value::Null;
+    let mut branches = Vec::new();
+    for line in output.lines() {
+        let Some(name) = line.get(2..).map(str::trim) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+        if line.starts_with('*') {
+            current = json!(name);
+        }
+        branches.push(json!(name));
+    }
+    json!({ "current": current, "branches": branches })
+}
+
+/// Parse the first line of `git commit` output, e.g. `[main 1a2b3c4] Fix typo`
+/// or `[main (root-commit) 1a2b3c4] Initial commit`
+fn parse_commit(output: &str) -> Option<Value> {
+    let line = output.lines().next()?.strip_prefix('[')?;
+    let (refs, summary) = line.split_once("] ")?;
+    let branch = refs.split(' ').next()?;
+    let hash = refs.rsplit(' ').next()?;
+    Some(json!({ "branch": branch, "hash": hash, "summary": summary }))
+}
+
+/// Keep the first `max_lines` lines, noting how many were dropped
+fn truncate_lines(text: &str, max_lines: usize) -> String {
+    let total = text.lines().count();
+    if total <= max_lines {
+        return text.to_string();
+    }
+    let mut output: String = text.lines().take(max_lines).map(|line| format!("{}\n", line)).collect();
+    output.push_str(&format!("... ({} more lines)", total - max_lines));
+    output
+}
+
+/// String arguments from a JSON array parameter
+fn string_list(args: &Value, key: &str) -> Vec<String> {
+    args.get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default()
+}
+
+/// Built-in tool: git operations on workspace repositories
+pub struct GitTool {
+    allowed_dir: PathBuf,
+    write_enabled: bool,
+    runner: SystemCommandTool,
+}
+
+impl GitTool {
+    /// Read-only git access to repositories under `allowed_dir`
+    pub fn new(allowed_dir: PathBuf) -> Self {
+        // Every argument is shell-quoted, so commit messages may contain `;` etc.
+        let runner = SystemCommandTool::with_working_dir(allowed_dir.clone())
+            .with_allowed_commands(vec!["git".to_string()])
+            .with_quoted_args();
+        GitTool {
+            allowed_dir,
+            write_enabled: false,
+            runner,
+        }
+    }
+
+    /// Allow `add`, `commit` and `checkout`
+    pub fn with_write_enabled(mut self, enabled: bool) -> Self {
+        self.write_enabled = enabled;
+        self
+    }
+
+    /// Pass these variables through to git (e.g. `GIT_AUTHOR_NAME`); see
+    /// [`SystemCommandTool::with_env_passthrough`]
+    pub fn with_env_passthrough(mut self, vars: &[&str]) -> Self {
+        self.runner = self.runner.with_env_passthrough(vars);
+        self
+    }
+
+    /// Resolve the repository directory, rejecting anything outside `allowed_dir`
+    fn repo_dir(&self, path: Option<&str>) -> std::result::Result<PathBuf, String> {
+        let root = self
+            .allowed_dir
+            .canonicalize()
+            .map_err(|e| format!("Workspace unavailable: {}", e))?;
+        let dir = match path {
+            Some(path) => root
+                .join(path)
+                .canonicalize()
+                .map_err(|e| format!("Cannot open '{}': {}", path, e))?,
+            None => root.clone(),
+        };
+        if !dir.starts_with(&root) {
+            return Err("Access denied: path outside workspace".to_string());
+        }
+        Ok(dir)
+    }
+
+    /// Run `git <args>` in `dir`, returning stdout or the failure result
+    async fn git(&self, dir: &Path, args: &[&str]) -> Result<std::result::Result<String, ToolResult>> {
+        let quoted: Vec<String> = args.iter().map(|arg| shell_quote(arg)).collect();
+        let result = self
+            .runner
+            .execute(json!({
+                "command": "git",
+                "args": quoted,
+                "working_dir": dir,
+            }))
+            .await?;
+
+        if !result.success {
+            let stderr = result.metadata("stderr").and_then(|v| v.as_str()).unwrap_or("").trim();
+            let error = result.error.as_deref().unwrap_or_default();
+            return Ok(Err(ToolResult::failure(if stderr.is_empty() {
+                error.to_string()
+            } else {
+                format!("{}: {}", error, stderr)
+            })));
+        }
+        let stdout = result.metadata("stdout").and_then(|v| v.as_str()).unwrap_or("");
+        Ok(Ok(stdout.to_string()))
+    }
+}
+
+/// Return early with the failed git result
+macro_rules! git_output {
+    ($call:expr) => {
+        match $call.await? {
+            Ok(output) => output,
+            Err(failure) => return Ok(failure),
+        }
+    };
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn name(&self) -> &str {
+        "git"
+    }
+
+    fn description(&self) -> &str {
+        "Run git on a repository in the workspace and get structured JSON results. Operations: status, diff, log, branch_list, and (if enabled) add, commit, checkout."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["status", "diff", "add", "commit", "log", "branch_list", "checkout"],
+                    "description": "Git operation to run"
+                },
+                "repo": {
+                    "type": "string",
+                    "description": "Repository directory relative to the workspace (default: workspace root)"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Files for 'add' (required) or to limit 'diff'"
+                },
+                "staged": {
+                    "type": "boolean",
+                    "description": "For 'diff': show staged instead of unstaged changes"
+                },
+                "message": {
+                    "type": "string",
+                    "description": "Commit message for 'commit'"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Number of commits for 'log' (default: 10, max: 100)"
+                },
+                "branch": {
+                    "type": "string",
+                    "description": "Branch to switch to for 'checkout'"
+                }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem, Permission::SystemCommand]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            json!({ "operation": "log", "limit": 1 }),
+            "{\n  \"commits\": [\n    {\n      \"hash\": \"9c1e2f4\",\n      \"author\": \"Ada\",\n      \"date\": \"2026-01-05T10:00:00+00:00\",\n      \"subject\": \"Fix login redirect\"\n    }\n  ]\n}".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let operation = args
+            .get("operation")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidInput("Missing 'operation' parameter".to_string()))?;
+
+        if matches!(operation, "add" | "commit" | "checkout") && !self.write_enabled {
+            return Ok(ToolResult::failure(format!(
+                "git {} is disabled; set sandbox.git_write_enabled to allow it",
+                operation
+            )));
+        }
+
+        let dir = match self.repo_dir(args.get("repo").and_then(|v| v.as_str())) {
+            Ok(dir) => dir,
+            Err(e) => return Ok(ToolResult::failure(e)),
+        };
+        let paths = string_list(&args, "paths");
+        if let Some(bad) = paths.iter().find(|p| !valid_ref_or_path(p)) {
+            return Ok(ToolResult::failure(format!("Invalid path '{}'", bad)));
+        }
+
+        let output = match operation {
+            "status" => {
+                let out = git_output!(self.git(&dir, &["status", "--porcelain=v1", "--branch"]));
+                parse_status(&out)
+            }
+            "diff" => {
+                let mut diff_args = vec!["diff"];
+                if args.get("staged").and_then(|v| v.as_bool()).unwrap_or(false) {
+                    diff_args.push("--cached");
+                }
+                let mut numstat_args = diff_args.clone();
+                numstat_args.push("--numstat");
+                for list in [&mut diff_args, &mut numstat_args] {
+                    list.push("--");
+                    list.extend(paths.iter().map(String::as_str));
+                }
+                let numstat = git_output!(self.git(&dir, &numstat_args));
+                let patch = git_output!(self.git(&dir, &diff_args));
+                json!({
+                    "files": parse_numstat(&numstat),
+                    "patch": truncate_lines(&patch, MAX_PATCH_LINES),
+                })
+            }
+            "add" => {
+                if paths.is_empty() {
+                    return Ok(ToolResult::failure("'add' requires 'paths'"));
+                }
+                let mut add_args = vec!["add", "--"];
+                add_args.extend(paths.iter().map(String::as_str));
+                git_output!(self.git(&dir, &add_args));
+                json!({ "added": paths })
+            }
+            "commit" => {
+                let message = args.get("message").and_then(|v| v.as_str()).unwrap_or("").trim();
+                if message.is_empty() {
+                    return Ok(ToolResult::failure("'commit' requires a non-empty 'message'"));
+                }
+                let out = git_output!(self.git(&dir, &["commit", "-m", message]));
+                parse_commit(&out).unwrap_or_else(|| json!({ "summary": message }))
+            }
+            "log" => {
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(DEFAULT_LOG_LIMIT)
+                    .clamp(1, MAX_LOG_LIMIT)
+                    .to_string();
+                let out = git_output!(self.git(
+                    &dir,
+                    &["log", "-n", &limit, "--pretty=format:%H%x1f%an%x1f%aI%x1f%s"]
+                ));
+                json!({ "commits": parse_log(&out) })
+            }
+            "branch_list" => {
+                let out = git_output!(self.git(&dir, &["branch", "--list"]));
+                parse_branches(&out)
+            }
+            "checkout" => {
+                let branch = args.get("branch").and_then(|v| v.as_str()).unwrap_or("");
+                if !valid_ref_or_path(branch) {
+                    return Ok(ToolResult::failure("'checkout' requires a valid 'branch'"));
+                }
+                git_output!(self.git(&dir, &["checkout", branch]));
+                json!({ "branch": branch })
+            }
+            other => {
+                return Ok(ToolResult::failure(format!("Unknown git operation '{}'", other)));
+            }
+        };
+
+        Ok(ToolResult::success(serde_json::to_string_pretty(&output)?)
+            .with_metadata("operation", json!(operation)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status(
+            "## main...origin/main [ahead 2, behind 1]\nM  src/lib.rs\n M README.md\nMM both.rs\n?? new.txt\n",
+        );
+        assert_eq!(status["branch"], "main");
+        assert_eq!(status["upstream"], "origin/main");
+        assert_eq!(status["ahead"], 2);
+        assert_eq!(status["behind"], 1);
+        assert_eq!(status["clean"], false);
+        assert_eq!(status["staged"], json!([
+            { "path": "src/lib.rs", "status": "M" },
+            { "path": "both.rs", "status": "M" },
+        ]));
+        assert_eq!(status["unstaged"], json!([
+            { "path": "README.md", "status": "M" },
+            { "path": "both.rs", "status": "M" },
+        ]));
+        assert_eq!(status["untracked"], json!(["new.txt"]));
+
+        let fresh = parse_status("## No commits yet on main\n");
+        assert_eq!(fresh["branch"], "main");
+        assert_eq!(fresh["clean"], true);
+    }
+
+    #[test]
+    fn test_parse_log_commit_and_branches() {
+        let log = parse_log("abc123\u{1f}Ada\u{1f}2024-01-01T00:00:00+00:00\u{1f}Fix: a | b\n");
+        assert_eq!(log[0]["author"], "Ada");
+        assert_eq!(log[0]["subject"], "Fix: a | b");
+
+        let commit = parse_commit("[main (root-commit) 1a2b3c4] Initial commit\n 1 file changed").unwrap();
+        assert_eq!(commit, json!({ "branch": "main", "hash": "1a2b3c4", "summary": "Initial commit" }));
+
+        let branches = parse_branches("  feature\n* main\n");
+        assert_eq!(branches, json!({ "current": "main", "branches": ["feature", "main"] }));
+
+        assert_eq!(
+            parse_numstat("3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n"),
+            vec![
+                json!({ "path": "src/lib.rs", "additions": 3, "deletions": 1 }),
+                json!({ "path": "logo.png", "additions": null, "deletions": null }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("it's done"), r"'it'\''s done'");
+    }
+
+    #[tokio::test]
+    async fn test_git_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("repo")).unwrap();
+        std::fs::write(dir.path().join("repo/notes.txt"), "hello\n").unwrap();
+
+        let tool = GitTool::new(dir.path().to_path_buf());
+        let repo = dir.path().join("repo");
+        for args in [
+            vec!["init", "-q", "-b", "main"],
+            vec!["config", "user.name", "Test"],
+            vec!["config", "user.email", "test@example.com"],
+        ] {
+            tool.git(&repo, &args).await.unwrap().unwrap();
+        }
+
+        // Writes are disabled by default
+        let denied = tool
+            .execute(json!({ "operation": "add", "repo": "repo", "paths": ["notes.txt"] }))
+            .await
+            .unwrap();
+        assert!(!denied.success);
+
+        let tool = tool.with_write_enabled(true);
+        let result = tool
+            .execute(json!({ "operation": "add", "repo": "repo", "paths": ["notes.txt"] }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        let result = tool
+            .execute(json!({ "operation": "commit", "repo": "repo", "message": "Add notes; it's a start" }))
+            .await
+            .unwrap();
+        let commit: Value = serde_json::from_str(&result.content.unwrap()).unwrap();
+        assert_eq!(commit["branch"], "main");
+
+        let result = tool.execute(json!({ "operation": "log", "repo": "repo" })).await.unwrap();
+        let log: Value = serde_json::from_str(&result.content.unwrap()).unwrap();
+        assert_eq!(log["commits"][0]["subject"], "Add notes; it's a start");
+
+        std::fs::write(repo.join("notes.txt"), "hello\nworld\n").unwrap();
+        let result = tool.execute(json!({ "operation": "diff", "repo": "repo" })).await.unwrap();
+        let diff: Value = serde_json::from_str(&result.content.unwrap()).unwrap();
+        assert_eq!(diff["files"][0], json!({ "path": "notes.txt", "additions": 1, "deletions": 0 }));
+
+        let result = tool.execute(json!({ "operation": "status", "repo": "repo" })).await.unwrap();
+        let status: Value = serde_json::from_str(&result.content.unwrap()).unwrap();
+        assert_eq!(status["unstaged"][0]["path"], "notes.txt");
+
+        let outside = tool.execute(json!({ "operation": "status", "repo": ".." })).await.unwrap();
+        assert!(!outside.success);
+    }
+}
@@ -5,22 +5,68 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
-use tracing::{info, warn};
+use tracing::info;
 use uuid::Uuid;
 
-use crate::database::{Memory, MemoryType};
+use crate::database::{Memory, MemoryType, TagMode};
 use crate::error::{Error, Result};
-use crate::memory::MemoryRetriever;
+use crate::memory::{DedupOutcome, MemoryDeduplicationService, MemoryRetriever};
+use crate::tools::permissions::Permission;
 use crate::tools::traits::{Tool, ToolResult};
 
 /// Tool to save information to long-term memory
+///
+/// A near-duplicate of an existing memory is skipped unless merging is
+/// enabled with [`with_merge_duplicates`](Self::with_merge_duplicates).
 pub struct MemorySaveTool {
+    dedup: MemoryDeduplicationService,
     retriever: MemoryRetriever,
+    merge_duplicates: bool,
 }
 
 impl MemorySaveTool {
     pub fn new(retriever: MemoryRetriever) -> Self {
-        MemorySaveTool { retriever }
+        MemorySaveTool {
+            dedup: MemoryDeduplicationService::new(retriever.clone()),
+            retriever,
+            merge_duplicates: false,
+        }
+    }
+
+    /// Merge near-duplicates into the existing memory instead of skipping them
+    pub fn with_merge_duplicates(mut self, merge: bool) -> Self {
+        self.merge_duplicates = merge;
+        self
+    }
+
+    /// Promote `user_id`'s episodic memory `memory_id` to a semantic one
+    async fn promote(&self, user_id: &str, memory_id: &str, delete_original: bool) -> Result<ToolResult> {
+        let memory_id = Uuid::parse_str(memory_id)
+            .map_err(|e| Error::InvalidInput(format!("Invalid UUID '{}': {}", memory_id, e)))?;
+
+        // Only the owner's memories can be promoted
+        match self.retriever.store().get(memory_id).await? {
+            Some(memory) if memory.user_id == user_id => {}
+            _ => return Ok(ToolResult::failure(format!("Memory not found: {}", memory_id))),
+        }
+
+        let semantic = match self.retriever.promote_to_semantic(memory_id, delete_original).await {
+            Ok(semantic) => semantic,
+            Err(e @ (Error::InvalidInput(_) | Error::Config(_))) => {
+                return Ok(ToolResult::failure(e.to_string()))
+            }
+            Err(e) => return Err(e),
+        };
+        info!(
+            "Memory promoted: episodic={}, semantic={}, deleted_original={}, user={}",
+            memory_id, semantic.id, delete_original, user_id
+        );
+
+        let original = if delete_original { "deleted" } else { "kept" };
+        Ok(ToolResult::success(format!(
+            "Episodic memory promoted to semantic: \"{}\" (original {}). ID: {}",
+            semantic.content, original, semantic.id
+        )))
     }
 }
 
@@ -31,7 +77,7 @@ impl Tool for MemorySaveTool {
     }
 
     fn description(&self) -> &str {
-        "Save important information to long-term memory for future recall. Use this when the user shares preferences, facts, decisions, or procedural knowledge worth remembering."
+        "Save important information to long-term memory for future recall. Use this when the user shares preferences, facts, decisions, or procedural knowledge worth remembering. Pass 'promote' with an episodic memory ID instead of 'content' to distill that event into a lasting semantic fact."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -59,18 +105,44 @@ impl Tool for MemorySaveTool {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "Tags for categorization (e.g., 'preference', 'project', 'decision', 'workflow')"
+                },
+                "promote": {
+                    "type": "string",
+                    "description": "ID of an episodic memory to promote to a semantic memory holding its core fact. Replaces 'content'; other fields are ignored"
+                },
+                "delete_original": {
+                    "type": "boolean",
+                    "description": "With 'promote': delete the episodic memory after promoting it. Default: false"
                 }
-            },
-            "required": ["content"]
+            }
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Memory]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "content": "Prefers answers in British English", "memory_type": "semantic", "importance": 0.9, "tags": ["preference"] }),
+            "Memory saved successfully (type: semantic, importance: 0.9, tags: [preference]). ID: 6b0f2c1e-8d4a-4e5b-9c3f-1a2b3c4d5e6f".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let user_id = args
             .get("_user_id")
             .and_then(|v| v.as_str())
             .unwrap_or("unknown");
 
+        if let Some(memory_id) = args.get("promote").and_then(|v| v.as_str()) {
+            let delete_original = args
+                .get("delete_original")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            return self.promote(user_id, memory_id, delete_original).await;
+        }
+
         let content = args
             .get("content")
             .and_then(|v| v.as_str())
@@ -99,39 +171,6 @@ impl Tool for MemorySaveTool {
             })
             .unwrap_or_default();
 
-        // Check for duplicates before saving
-        match self.retriever.get_or_create_embedding(content).await {
-            Ok(embedding) => {
-                match self
-                    .retriever
-                    .store()
-                    .find_similar_by_embedding(user_id, embedding, 0.95, 1)
-                    .await
-                {
-                    Ok(similar) => {
-                        if let Some((existing, score)) = similar.first() {
-                            let preview = existing
-                                .summary
-                                .as_deref()
-                                .unwrap_or_else(|| {
-                                    &existing.content[..existing.content.len().min(80)]
-                                });
-                            return Ok(ToolResult::success(format!(
-                                "Very similar memory already exists (similarity: {:.2}): \"{}\". No new memory created.",
-                                score, preview
-                            )));
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Duplicate check failed: {}", e);
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("Embedding generation for dedup check failed: {}", e);
-            }
-        }
-
         let mut memory = Memory::new(user_id, content)
             .with_importance(importance)
             .with_tags(tags.clone())
@@ -142,8 +181,32 @@ impl Tool for MemorySaveTool {
             memory = memory.with_summary(s);
         }
 
-        let memory_id = memory.id;
-        self.retriever.save_memory(&memory).await?;
+        let memory_id = if self.merge_duplicates {
+            match self.dedup.save(&memory).await? {
+                DedupOutcome::Saved(id) => id,
+                DedupOutcome::Merged { id, similarity } => {
+                    info!("Memory merged into {} (similarity: {:.2}), user={}", id, similarity, user_id);
+                    return Ok(ToolResult::success(format!(
+                        "Very similar memory already existed (similarity: {:.2}); merged into it. ID: {}",
+                        similarity, id
+                    )));
+                }
+            }
+        } else {
+            // Check for duplicates before saving
+            if let Some((existing, score)) = self.dedup.find_duplicate(&memory).await {
+                let preview = existing.summary.as_deref().unwrap_or_else(|| {
+                    let end = existing.content.char_indices().nth(80).map_or(existing.content.len(), |(i, _)| i);
+                    &existing.content[..end]
+                });
+                return Ok(ToolResult::success(format!(
+                    "Very similar memory already exists (similarity: {:.2}): \"{}\". No new memory created.",
+                    score, preview
+                )));
+            }
+            self.retriever.save_memory(&memory).await?;
+            memory.id
+        };
 
         info!(
             "Memory saved: id={}, type={}, importance={}, user={}",
@@ -206,6 +269,17 @@ impl Tool for MemorySearchTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Memory]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "query": "spelling preferences", "limit": 3 }),
+            "## Relevant Memories\n\n1. [fact] Prefers answers in British English".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let user_id = args
             .get("_user_id")
@@ -261,7 +335,7 @@ impl Tool for MemoryListTool {
     }
 
     fn description(&self) -> &str {
-        "List stored memories filtered by type or tag. Use this to browse stored knowledge and see what is remembered."
+        "List stored memories filtered by type or tags. Use this to browse stored knowledge and see what is remembered. Pass several comma-separated tags with tag_mode 'all' to require every tag, or 'any' to match at least one."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -277,6 +351,15 @@ impl Tool for MemoryListTool {
                     "type": "string",
                     "description": "Filter by tag"
                 },
+                "tags": {
+                    "type": "string",
+                    "description": "Comma-separated tags to filter by, e.g. \"rust,async\""
+                },
+                "tag_mode": {
+                    "type": "string",
+                    "enum": ["any", "all"],
+                    "description": "Match memories with any of the tags (default) or all of them"
+                },
                 "limit": {
                     "type": "integer",
                     "description": "Maximum number of results (default: 10)"
@@ -285,6 +368,17 @@ impl Tool for MemoryListTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Memory]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "tags": "preference", "limit": 1 }),
+            "Found 1 memories:\n\n1. [fact] ID: 6b0f2c1e-8d4a-4e5b-9c3f-1a2b3c4d5e6f\n   Content: Prefers answers in British English\n   Tags: [preference]\n   Importance: 0.9 | Accessed: 3 times".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let user_id = args
             .get("_user_id")
@@ -299,9 +393,17 @@ impl Tool for MemoryListTool {
 
         let memory_type = args.get("memory_type").and_then(|v| v.as_str());
         let tag = args.get("tag").and_then(|v| v.as_str());
+        let mut tags = parse_tags(args.get("tags").and_then(|v| v.as_str()).unwrap_or(""));
+        if let Some(tag) = tag.filter(|t| !tags.contains(t)) {
+            tags.push(tag);
+        }
+        let tag_mode: TagMode = match args.get("tag_mode").and_then(|v| v.as_str()) {
+            Some(mode) => mode.parse()?,
+            None => TagMode::default(),
+        };
 
-        let memories = if let Some(tag) = tag {
-            self.retriever.store().get_by_tag(user_id, tag, limit).await?
+        let memories = if !tags.is_empty() {
+            self.retriever.store().search_by_tag(user_id, &tags, tag_mode, limit).await?
         } else if let Some(mt) = memory_type {
             self.retriever.store().search_by_type(user_id, mt, limit).await?
         } else {
@@ -385,6 +487,17 @@ impl Tool for MemoryDeleteTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Memory]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "memory_id": "6b0f2c1e-8d4a-4e5b-9c3f-1a2b3c4d5e6f" }),
+            "Memory deleted: \"Prefers answers in British English\" (ID: 6b0f2c1e-8d4a-4e5b-9c3f-1a2b3c4d5e6f)".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let memory_id_str = args
             .get("memory_id")
@@ -395,10 +508,8 @@ impl Tool for MemoryDeleteTool {
             Error::InvalidInput(format!("Invalid UUID '{}': {}", memory_id_str, e))
         })?;
 
-        // Check if memory exists
-        match self.retriever.store().get(memory_id).await? {
+        match self.retriever.delete_memory(memory_id).await? {
             Some(memory) => {
-                self.retriever.store().delete(memory_id).await?;
                 info!("Memory deleted: id={}", memory_id);
 
                 let preview = memory
@@ -419,6 +530,14 @@ impl Tool for MemoryDeleteTool {
     }
 }
 
+/// Split a comma-separated tag list, dropping empty entries
+fn parse_tags(tags: &str) -> Vec<&str> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -432,6 +551,12 @@ mod tests {
         assert_eq!("memory_delete", "memory_delete");
     }
 
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags("rust, async,,"), vec!["rust", "async"]);
+        assert!(parse_tags(" ").is_empty());
+    }
+
     #[test]
     fn test_memory_save_schema_shape() {
         // Validate the expected schema structure without needing a real retriever
@@ -0,0 +1,143 @@
+//! Per-tool call metrics
+//!
+//! [`ToolRegistry`](super::ToolRegistry) reports every call to an optional
+//! [`MetricsRecorder`]. [`PrometheusMetricsRecorder`] keeps counters and a
+//! latency histogram per tool in a `prometheus` registry and renders them in
+//! the Prometheus text exposition format for the gateway's `GET /metrics`.
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder};
+use tracing::warn;
+
+/// Receives the outcome of each tool call
+pub trait MetricsRecorder: Send + Sync {
+    /// A call to `tool_name` finished after `duration_ms`
+    fn record_call(&self, tool_name: &str, duration_ms: u64, success: bool);
+
+    /// A call to `tool_name` returned an error of category `error_kind`
+    fn record_error(&self, tool_name: &str, error_kind: &str);
+}
+
+/// Upper bounds (in milliseconds) of the latency histogram buckets
+pub const DURATION_BUCKETS_MS: [u64; 10] = [10, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000];
+
+/// Recorder exposing tool metrics in Prometheus text format
+///
+/// Exports `openagent_tool_calls_total{tool,status}`,
+/// `openagent_tool_call_duration_ms{tool}` (histogram) and
+/// `openagent_tool_errors_total{tool,kind}`.
+#[derive(Clone)]
+pub struct PrometheusMetricsRecorder {
+    registry: Registry,
+    calls: IntCounterVec,
+    durations: HistogramVec,
+    errors: IntCounterVec,
+}
+
+impl PrometheusMetricsRecorder {
+    /// Create a recorder with no samples
+    pub fn new() -> Self {
+        let calls = IntCounterVec::new(
+            Opts::new("openagent_tool_calls_total", "Tool calls by outcome."),
+            &["tool", "status"],
+        )
+        .expect("valid counter options");
+        let buckets = DURATION_BUCKETS_MS.iter().map(|&ms| ms as f64).collect();
+        let durations = HistogramVec::new(
+            HistogramOpts::new("openagent_tool_call_duration_ms", "Tool call latency in milliseconds.")
+                .buckets(buckets),
+            &["tool"],
+        )
+        .expect("valid histogram options");
+        let errors = IntCounterVec::new(
+            Opts::new("openagent_tool_errors_total", "Tool calls that returned an error, by error category."),
+            &["tool", "kind"],
+        )
+        .expect("valid counter options");
+
+        let registry = Registry::new();
+        for collector in [
+            Box::new(calls.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(durations.clone()),
+            Box::new(errors.clone()),
+        ] {
+            registry.register(collector).expect("metric names are unique");
+        }
+
+        PrometheusMetricsRecorder {
+            registry,
+            calls,
+            durations,
+            errors,
+        }
+    }
+
+    /// Registry holding the tool metrics, for registering further collectors
+    /// to serve on the same endpoint
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap_or_else(|e| {
+                warn!("Failed to encode metrics: {}", e);
+                String::new()
+            })
+    }
+}
+
+impl Default for PrometheusMetricsRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsRecorder for PrometheusMetricsRecorder {
+    fn record_call(&self, tool_name: &str, duration_ms: u64, success: bool) {
+        let (status, other) = if success { ("success", "failure") } else { ("failure", "success") };
+        self.calls.with_label_values(&[tool_name, status]).inc();
+        // Export both series once a tool has been called, so rates work
+        // before its first failure
+        self.calls.with_label_values(&[tool_name, other]);
+        self.durations.with_label_values(&[tool_name]).observe(duration_ms as f64);
+    }
+
+    fn record_error(&self, tool_name: &str, error_kind: &str) {
+        self.errors.with_label_values(&[tool_name, error_kind]).inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_and_histogram() {
+        let recorder = PrometheusMetricsRecorder::new();
+        recorder.record_call("read_file", 5, true);
+        recorder.record_call("read_file", 300, false);
+        recorder.record_call("read_file", 60_000, true);
+        recorder.record_error("read_file", "io");
+
+        let text = recorder.render();
+        assert!(text.contains("# TYPE openagent_tool_calls_total counter\n"));
+        assert!(text.contains("openagent_tool_calls_total{status=\"success\",tool=\"read_file\"} 2\n"));
+        assert!(text.contains("openagent_tool_calls_total{status=\"failure\",tool=\"read_file\"} 1\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_bucket{tool=\"read_file\",le=\"10\"} 1\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_bucket{tool=\"read_file\",le=\"250\"} 1\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_bucket{tool=\"read_file\",le=\"500\"} 2\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_bucket{tool=\"read_file\",le=\"+Inf\"} 3\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_sum{tool=\"read_file\"} 60305\n"));
+        assert!(text.contains("openagent_tool_call_duration_ms_count{tool=\"read_file\"} 3\n"));
+        assert!(text.contains("openagent_tool_errors_total{kind=\"io\",tool=\"read_file\"} 1\n"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        let recorder = PrometheusMetricsRecorder::new();
+        recorder.record_error("mcp_\"odd\"", "io");
+        assert!(recorder.render().contains("openagent_tool_errors_total{kind=\"io\",tool=\"mcp_\\\"odd\\\"\"} 1\n"));
+    }
+}
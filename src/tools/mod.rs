@@ -9,9 +9,22 @@
 //! - **system_command**: Execute OS commands (with security controls)
 //! - **read_file**: Read files from the workspace
 //! - **write_file**: Write/create files in the workspace
+//! - **diff**: Unified diff between two workspace files
+//! - **file_watcher**: Watch workspace paths for changes and poll events
+//! - **git**: Git status, diff, log and branches as JSON; add/commit/checkout if enabled
 //! - **duckduckgo_search**: Web search (no API key required)
+//! - **fetch_page**: Download a page and return its readable text
 //! - **brave_search**: Brave Search API (requires API key)
+//! - **brave_image_search**: Brave image search (requires API key)
 //! - **perplexity_search**: AI-powered search via Perplexity (requires API key)
+//! - **calendar**: CalDAV calendar events (requires CalDAV credentials)
+//! - **notion**: Notion pages as a knowledge base (requires `plugins.notion`)
+//!
+//! Any registered tool can be wrapped in a `SummaryTool` (see
+//! `ToolRegistry::wrap_with_summarizer`) so the LLM condenses its long
+//! results before they reach the context. Interceptors added with
+//! `ToolRegistry::with_interceptor` see every call before and after it
+//! runs; `SecretRedactionInterceptor` strips secrets from arguments.
 //!
 //! ## Adding a New Tool
 //!
@@ -22,28 +35,53 @@
 
 mod traits;
 mod registry;
+mod rate_limit;
+mod schema;
+mod permissions;
+mod summary;
+mod events;
+mod explain;
+mod interceptor;
 mod system_command;
 mod read_file;
 mod write_file;
+mod diff;
+mod metrics;
+mod file_watcher;
+mod git;
 mod duckduckgo_search;
+mod fetch_page;
 mod brave_search;
 mod perplexity_search;
 mod memory;
 mod task;
+mod calendar;
+mod notion;
 
 // Core trait and types
 pub use traits::{Tool, ToolResult, ToolCall};
 
 // Registry
-pub use registry::ToolRegistry;
+pub use registry::{ToolDryRunResult, ToolRegistry, ToolRegistrySnapshot, ToolSnapshot};
+pub use rate_limit::RateLimiter;
+pub use permissions::{Permission, PermissionSet};
+pub use metrics::{MetricsRecorder, PrometheusMetricsRecorder, DURATION_BUCKETS_MS};
+pub use summary::{SummaryTool, DEFAULT_SUMMARY_MAX_CHARS};
+pub use events::ToolEventBus;
+pub use explain::{ParameterDoc, ToolExplanation};
+pub use interceptor::{redact_secrets, SecretRedactionInterceptor, ToolInterceptor, REDACTED};
 
 // Built-in tools
 pub use system_command::SystemCommandTool;
 pub use read_file::ReadFileTool;
 pub use write_file::WriteFileTool;
+pub use diff::DiffTool;
+pub use file_watcher::{FileWatcherTool, FileWatcherPollTool};
+pub use git::GitTool;
 pub use duckduckgo_search::DuckDuckGoSearchTool;
-pub use brave_search::{BraveSearchTool, BraveSearchConfig};
-pub use perplexity_search::{PerplexitySearchTool, PerplexityConfig};
+pub use fetch_page::{ContentFetcher, FetchPageTool};
+pub use brave_search::{BraveSearchTool, BraveSearchConfig, BraveImageSearchTool, ImageSearchResult};
+pub use perplexity_search::{Citation, PerplexityAnswer, PerplexitySearchTool, PerplexityConfig};
 
 // Memory tools
 pub use memory::{MemorySaveTool, MemorySearchTool, MemoryListTool, MemoryDeleteTool};
@@ -51,6 +89,12 @@ pub use memory::{MemorySaveTool, MemorySearchTool, MemoryListTool, MemoryDeleteT
 // Task tools
 pub use task::{TaskCreateTool, TaskListTool, TaskUpdateTool};
 
+// Calendar tool
+pub use calendar::{CalendarTool, CalendarEvent};
+
+// Notion tool
+pub use notion::{NotionTool, NotionConfig, NotionPage};
+
 // Shared types
 pub use duckduckgo_search::SearchResult;
 
@@ -71,6 +115,17 @@ pub(crate) fn format_search_results(results: &[SearchResult]) -> String {
     output
 }
 
+/// Successful result for `result_count` formatted search results
+///
+/// Searches involve no LLM, so `tokens_used` is the estimated context the
+/// formatted results will take up.
+pub(crate) fn search_tool_result(formatted: String, result_count: usize) -> ToolResult {
+    let tokens_used = crate::agent::model_metadata::estimate_tokens(&formatted);
+    ToolResult::success(formatted)
+        .with_metadata("tokens_used", serde_json::json!(tokens_used))
+        .with_metadata("result_count", serde_json::json!(result_count))
+}
+
 /// URL encoding helper
 pub(crate) mod urlencoding {
     pub fn encode(s: &str) -> String {
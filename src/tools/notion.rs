@@ -0,0 +1,672 @@
+//! Notion tool
+//!
+//! Reads and writes Notion pages through the Notion REST API (v1) so the agent
+//! can use a Notion workspace as a knowledge base. Page content is exchanged
+//! as Markdown; a small converter maps the common block types both ways.
+
+use async_trait::async_trait;
+use reqwest::{Client, Method};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::error::{Error, Result};
+
+/// Default timeout for Notion API requests
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Notion API version sent with every request
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Maximum characters in a single rich text object
+const MAX_RICH_TEXT_CHARS: usize = 2000;
+
+/// Maximum blocks accepted per append request
+const MAX_BLOCKS_PER_REQUEST: usize = 100;
+
+/// Key of the Notion entry in the `plugins` config map
+const PLUGIN_KEY: &str = "notion";
+
+fn default_api_url() -> String {
+    "https://api.notion.com/v1".to_string()
+}
+
+/// Notion configuration, read from `plugins.notion` in the config file
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotionConfig {
+    /// Internal integration token
+    pub api_key: SecretString,
+    /// Database new pages are created in when no parent is given
+    #[serde(default)]
+    pub default_database_id: Option<String>,
+    /// API base URL
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+}
+
+impl NotionConfig {
+    /// Read the config from the plugin config map, if present and valid
+    pub fn from_plugins(plugins: &HashMap<String, Value>) -> Option<Self> {
+        let value = plugins.get(PLUGIN_KEY)?;
+        match serde_json::from_value(value.clone()) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                tracing::warn!("Invalid plugins.{} config: {}", PLUGIN_KEY, e);
+                None
+            }
+        }
+    }
+}
+
+/// A page returned by `search_pages`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotionPage {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// Built-in tool: read, search, and write Notion pages
+pub struct NotionTool {
+    client: Client,
+    config: NotionConfig,
+}
+
+impl NotionTool {
+    /// Create a new Notion tool
+    pub fn new(config: NotionConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { client, config }
+    }
+
+    /// Create from the plugin config map
+    pub fn from_plugins(plugins: &HashMap<String, Value>) -> Option<Self> {
+        NotionConfig::from_plugins(plugins).map(Self::new)
+    }
+
+    async fn request(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let url = format!("{}/{}", self.config.api_url.trim_end_matches('/'), path);
+        let mut request = self
+            .client
+            .request(method, &url)
+            .bearer_auth(self.config.api_key.expose_secret())
+            .header("Notion-Version", NOTION_VERSION);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Provider(format!("Notion request failed: {}", e)))?;
+
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = body["message"].as_str().unwrap_or("unknown error");
+            return Err(Error::Provider(format!(
+                "Notion API returned {}: {}",
+                status, message
+            )));
+        }
+        Ok(body)
+    }
+
+    /// Read a page as Markdown, prefixed with its title as a heading
+    pub async fn read_page(&self, page_id: &str) -> Result<String> {
+        let page_id = notion_id(page_id)?;
+        let page = self.request(Method::GET, &format!("pages/{}", page_id), None).await?;
+
+        let mut blocks = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut path = format!("blocks/{}/children?page_size=100", page_id);
+            if let Some(ref c) = cursor {
+                path.push_str(&format!("&start_cursor={}", c));
+            }
+            let body = self.request(Method::GET, &path, None).await?;
+            if let Some(results) = body["results"].as_array() {
+                blocks.extend(results.iter().cloned());
+            }
+            match body["next_cursor"].as_str() {
+                Some(next) if body["has_more"].as_bool() == Some(true) => {
+                    cursor = Some(next.to_string())
+                }
+                _ => break,
+            }
+        }
+
+        let title = page_title(&page);
+        let content = blocks_to_markdown(&blocks);
+        Ok(if title.is_empty() {
+            content
+        } else {
+            format!("# {}\n\n{}", title, content)
+        })
+    }
+
+    /// Search pages shared with the integration by title
+    pub async fn search_pages(&self, query: &str) -> Result<Vec<NotionPage>> {
+        let body = self
+            .request(
+                Method::POST,
+                "search",
+                Some(json!({
+                    "query": query,
+                    "filter": {"property": "object", "value": "page"},
+                    "page_size": 20
+                })),
+            )
+            .await?;
+
+        Ok(body["results"]
+            .as_array()
+            .map(|results| {
+                results
+                    .iter()
+                    .map(|page| NotionPage {
+                        id: page["id"].as_str().unwrap_or_default().to_string(),
+                        title: page_title(page),
+                        url: page["url"].as_str().unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Append Markdown content to the end of a page
+    pub async fn append_block(&self, page_id: &str, markdown: &str) -> Result<usize> {
+        let page_id = notion_id(page_id)?;
+        let blocks = markdown_to_blocks(markdown);
+        for chunk in blocks.chunks(MAX_BLOCKS_PER_REQUEST) {
+            self.request(
+                Method::PATCH,
+                &format!("blocks/{}/children", page_id),
+                Some(json!({ "children": chunk })),
+            )
+            .await?;
+        }
+        Ok(blocks.len())
+    }
+
+    /// Create a page under `parent_id` (a page), or in the default database
+    /// when no parent is given. Returns the new page ID.
+    pub async fn create_page(
+        &self,
+        parent_id: Option<&str>,
+        title: &str,
+        markdown: &str,
+    ) -> Result<String> {
+        let parent = match (parent_id, &self.config.default_database_id) {
+            (Some(id), _) => json!({ "page_id": notion_id(id)? }),
+            (None, Some(db)) => json!({ "database_id": db }),
+            (None, None) => {
+                return Err(Error::InvalidInput(
+                    "No parent_id given and no default_database_id configured".to_string(),
+                ))
+            }
+        };
+
+        let blocks = markdown_to_blocks(markdown);
+        let (first, rest) = blocks.split_at(blocks.len().min(MAX_BLOCKS_PER_REQUEST));
+
+        // The title property always has the ID "title", in pages and databases alike
+        let page = self
+            .request(
+                Method::POST,
+                "pages",
+                Some(json!({
+                    "parent": parent,
+                    "properties": { "title": { "title": rich_text(title) } },
+                    "children": first,
+                })),
+            )
+            .await?;
+
+        let page_id = notion_id(page["id"].as_str().unwrap_or_default())?;
+        for chunk in rest.chunks(MAX_BLOCKS_PER_REQUEST) {
+            self.request(
+                Method::PATCH,
+                &format!("blocks/{}/children", page_id),
+                Some(json!({ "children": chunk })),
+            )
+            .await?;
+        }
+        Ok(page_id)
+    }
+}
+
+#[async_trait]
+impl Tool for NotionTool {
+    fn name(&self) -> &str {
+        "notion"
+    }
+
+    fn description(&self) -> &str {
+        "Use the user's Notion workspace as a knowledge base. Actions: 'search_pages' (query), 'read_page' (page_id), 'append_block' (page_id, markdown), 'create_page' (title, markdown, optional parent_id). Content is Markdown."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["search_pages", "read_page", "append_block", "create_page"],
+                    "description": "Operation to perform"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Title search text for search_pages"
+                },
+                "page_id": {
+                    "type": "string",
+                    "description": "Page ID for read_page / append_block"
+                },
+                "parent_id": {
+                    "type": "string",
+                    "description": "Parent page ID for create_page (defaults to the configured database)"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Page title for create_page"
+                },
+                "markdown": {
+                    "type": "string",
+                    "description": "Markdown content for append_block / create_page"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network, Permission::ExternalApi]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            json!({ "action": "search_pages", "query": "Roadmap" }),
+            "- Roadmap 2026 (id: 1a2b3c4d5e6f) https://www.notion.so/Roadmap-2026-1a2b3c4d5e6f".to_string(),
+        )
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let action = args.get("action").and_then(|v| v.as_str()).unwrap_or("");
+        let str_arg = |key: &str| args.get(key).and_then(|v| v.as_str());
+
+        match action {
+            "search_pages" => {
+                let query = str_arg("query").unwrap_or("");
+                match self.search_pages(query).await {
+                    Ok(pages) if pages.is_empty() => {
+                        Ok(ToolResult::success(format!("No pages found for '{}'", query)))
+                    }
+                    Ok(pages) => {
+                        let list = pages
+                            .iter()
+                            .map(|p| format!("- {} (id: {}) {}", p.title, p.id, p.url))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        Ok(ToolResult::success(list)
+                            .with_metadata("result_count", json!(pages.len())))
+                    }
+                    Err(e) => Ok(ToolResult::failure(format!("Notion search failed: {}", e))),
+                }
+            }
+            "read_page" => {
+                let Some(page_id) = str_arg("page_id") else {
+                    return Ok(ToolResult::failure("Missing 'page_id' parameter"));
+                };
+                match self.read_page(page_id).await {
+                    Ok(markdown) => Ok(ToolResult::success(markdown)),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to read page: {}", e))),
+                }
+            }
+            "append_block" => {
+                let (Some(page_id), Some(markdown)) = (str_arg("page_id"), str_arg("markdown"))
+                else {
+                    return Ok(ToolResult::failure("'page_id' and 'markdown' are required"));
+                };
+                match self.append_block(page_id, markdown).await {
+                    Ok(count) => Ok(ToolResult::success(format!(
+                        "Appended {} block(s) to page {}",
+                        count, page_id
+                    ))),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to append: {}", e))),
+                }
+            }
+            "create_page" => {
+                let Some(title) = str_arg("title") else {
+                    return Ok(ToolResult::failure("Missing 'title' parameter"));
+                };
+                let markdown = str_arg("markdown").unwrap_or("");
+                match self.create_page(str_arg("parent_id"), title, markdown).await {
+                    Ok(id) => Ok(ToolResult::success(format!(
+                        "Created page '{}' (id: {})",
+                        title, id
+                    ))
+                    .with_metadata("page_id", json!(id))),
+                    Err(e) => Ok(ToolResult::failure(format!("Failed to create page: {}", e))),
+                }
+            }
+            other => Ok(ToolResult::failure(format!(
+                "Unknown action '{}'. Use search_pages, read_page, append_block, or create_page",
+                other
+            ))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Markdown <-> Notion blocks
+// ---------------------------------------------------------------------------
+
+/// Notion object ID in hyphenated form
+///
+/// IDs are UUIDs, with or without hyphens; anything else is rejected before
+/// it is put into an API path.
+fn notion_id(id: &str) -> Result<String> {
+    uuid::Uuid::parse_str(id.trim())
+        .map(|id| id.hyphenated().to_string())
+        .map_err(|_| Error::InvalidInput(format!("Invalid Notion page ID '{}'", id)))
+}
+
+/// Title of a page object (the property of type `title`)
+fn page_title(page: &Value) -> String {
+    page["properties"]
+        .as_object()
+        .and_then(|props| props.values().find(|p| p["type"] == "title"))
+        .map(|p| rich_text_to_markdown(&p["title"]))
+        .unwrap_or_default()
+}
+
+/// Render a rich text array, keeping bold/italic/code/strikethrough and links
+fn rich_text_to_markdown(rich_text: &Value) -> String {
+    let Some(parts) = rich_text.as_array() else {
+        return String::new();
+    };
+
+    parts
+        .iter()
+        .map(|part| {
+            let mut text = part["plain_text"]
+                .as_str()
+                .or_else(|| part["text"]["content"].as_str())
+                .unwrap_or_default()
+                .to_string();
+            let a = &part["annotations"];
+            if a["code"] == true {
+                text = format!("`{}`", text);
+            }
+            if a["bold"] == true {
+                text = format!("**{}**", text);
+            }
+            if a["italic"] == true {
+                text = format!("_{}_", text);
+            }
+            if a["strikethrough"] == true {
+                text = format!("~~{}~~", text);
+            }
+            if let Some(href) = part["href"].as_str() {
+                text = format!("[{}]({})", text, href);
+            }
+            text
+        })
+        .collect()
+}
+
+/// Convert Notion blocks to Markdown. Unsupported block types are noted inline.
+fn blocks_to_markdown(blocks: &[Value]) -> String {
+    let mut lines = Vec::new();
+    let mut numbered = 0;
+
+    for block in blocks {
+        let kind = block["type"].as_str().unwrap_or_default();
+        let data = &block[kind];
+        let text = rich_text_to_markdown(&data["rich_text"]);
+
+        if kind == "numbered_list_item" {
+            numbered += 1;
+        } else {
+            numbered = 0;
+        }
+
+        let line = match kind {
+            "paragraph" => text,
+            "heading_1" => format!("# {}", text),
+            "heading_2" => format!("## {}", text),
+            "heading_3" => format!("### {}", text),
+            "bulleted_list_item" => format!("- {}", text),
+            "numbered_list_item" => format!("{}. {}", numbered, text),
+            "to_do" => {
+                let mark = if data["checked"] == true { "x" } else { " " };
+                format!("- [{}] {}", mark, text)
+            }
+            "quote" | "callout" => format!("> {}", text),
+            "code" => format!(
+                "```{}\n{}\n```",
+                data["language"].as_str().filter(|l| *l != "plain text").unwrap_or(""),
+                text
+            ),
+            "divider" => "---".to_string(),
+            "child_page" => format!("[page: {}]", data["title"].as_str().unwrap_or_default()),
+            other => format!("[unsupported block: {}]", other),
+        };
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// Build a rich text array, splitting at Notion's per-object length limit
+fn rich_text(text: &str) -> Vec<Value> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(MAX_RICH_TEXT_CHARS)
+        .map(|chunk| json!({ "type": "text", "text": { "content": chunk.iter().collect::<String>() } }))
+        .collect()
+}
+
+fn text_block(kind: &str, text: &str) -> Value {
+    json!({ "object": "block", "type": kind, kind: { "rich_text": rich_text(text) } })
+}
+
+/// Languages accepted by Notion code blocks
+const NOTION_LANGUAGES: &[&str] = &[
+    "abap", "agda", "arduino", "ascii art", "assembly", "bash", "basic", "bnf", "c", "c#", "c++",
+    "clojure", "coffeescript", "coq", "css", "dart", "dhall", "diff", "docker", "ebnf", "elixir",
+    "elm", "erlang", "f#", "flow", "fortran", "gherkin", "glsl", "go", "graphql", "groovy",
+    "haskell", "hcl", "html", "idris", "java", "javascript", "json", "julia", "kotlin", "latex",
+    "less", "lisp", "livescript", "llvm ir", "lua", "makefile", "markdown", "markup", "matlab",
+    "mathematica", "mermaid", "nix", "notion formula", "objective-c", "ocaml", "pascal", "perl",
+    "php", "plain text", "powershell", "prolog", "protobuf", "purescript", "python", "r",
+    "racket", "reason", "ruby", "rust", "sass", "scala", "scheme", "scss", "shell", "smalltalk",
+    "solidity", "sql", "swift", "toml", "typescript", "vb.net", "verilog", "vhdl",
+    "visual basic", "webassembly", "xml", "yaml", "java/c/c++/c#",
+];
+
+/// Map a Markdown code-fence language to Notion's name for it.
+/// Unknown languages fall back to `plain text`, which the API always accepts.
+fn notion_language(fence: &str) -> &'static str {
+    let fence = fence.trim().to_ascii_lowercase();
+    let name = match fence.as_str() {
+        "sh" | "zsh" | "console" | "shell-session" => "shell",
+        "js" | "jsx" | "mjs" | "node" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "py" | "python3" => "python",
+        "rs" => "rust",
+        "rb" => "ruby",
+        "go" | "golang" => "go",
+        "cpp" | "cc" | "cxx" | "hpp" => "c++",
+        "cs" | "csharp" => "c#",
+        "fs" | "fsharp" => "f#",
+        "kt" | "kts" => "kotlin",
+        "hs" => "haskell",
+        "ex" | "exs" => "elixir",
+        "erl" => "erlang",
+        "objc" | "objectivec" => "objective-c",
+        "ps" | "ps1" | "pwsh" => "powershell",
+        "yml" => "yaml",
+        "md" => "markdown",
+        "tex" => "latex",
+        "proto" => "protobuf",
+        "dockerfile" => "docker",
+        "make" | "mk" => "makefile",
+        "tf" | "terraform" => "hcl",
+        "wasm" | "wat" => "webassembly",
+        "vb" => "visual basic",
+        "gql" => "graphql",
+        "patch" => "diff",
+        "" | "text" | "txt" | "plaintext" => "plain text",
+        other => other,
+    };
+    NOTION_LANGUAGES
+        .iter()
+        .find(|&&language| language == name)
+        .copied()
+        .unwrap_or("plain text")
+}
+
+/// Convert Markdown to Notion blocks. Inline formatting is kept as literal text.
+fn markdown_to_blocks(markdown: &str) -> Vec<Value> {
+    let mut blocks = Vec::new();
+    let mut lines = markdown.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_end();
+
+        if let Some(language) = trimmed.strip_prefix("```") {
+            let code: Vec<&str> = lines.by_ref().take_while(|l| !l.starts_with("```")).collect();
+            let language = notion_language(language);
+            blocks.push(json!({
+                "object": "block",
+                "type": "code",
+                "code": { "rich_text": rich_text(&code.join("\n")), "language": language }
+            }));
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let block = if let Some(rest) = trimmed.strip_prefix("### ") {
+            text_block("heading_3", rest)
+        } else if let Some(rest) = trimmed.strip_prefix("## ") {
+            text_block("heading_2", rest)
+        } else if let Some(rest) = trimmed.strip_prefix("# ") {
+            text_block("heading_1", rest)
+        } else if trimmed == "---" || trimmed == "***" {
+            json!({ "object": "block", "type": "divider", "divider": {} })
+        } else if let Some(rest) = trimmed
+            .strip_prefix("- [ ] ")
+            .map(|r| (r, false))
+            .or_else(|| trimmed.strip_prefix("- [x] ").map(|r| (r, true)))
+        {
+            json!({
+                "object": "block",
+                "type": "to_do",
+                "to_do": { "rich_text": rich_text(rest.0), "checked": rest.1 }
+            })
+        } else if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+            text_block("bulleted_list_item", rest)
+        } else if let Some(rest) = trimmed.strip_prefix("> ") {
+            text_block("quote", rest)
+        } else if let Some(rest) = strip_numbered_prefix(trimmed) {
+            text_block("numbered_list_item", rest)
+        } else {
+            text_block("paragraph", trimmed)
+        };
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+/// Strip a `12. ` ordered-list marker
+fn strip_numbered_prefix(line: &str) -> Option<&str> {
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..].strip_prefix(". ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let markdown = "# Setup\n\
+                        Install the CLI first.\n\
+                        - step one\n\
+                        1. first\n\
+                        2. second\n\
+                        - [x] done\n\
+                        > note\n\
+                        ```rust\nfn main() {}\n```\n\
+                        ---";
+        let blocks = markdown_to_blocks(markdown);
+        assert_eq!(blocks.len(), 9);
+        assert_eq!(blocks[0]["type"], "heading_1");
+        assert_eq!(blocks[7]["code"]["language"], "rust");
+        assert_eq!(blocks_to_markdown(&blocks), markdown);
+    }
+
+    #[test]
+    fn test_notion_language() {
+        assert_eq!(notion_language("rust"), "rust");
+        assert_eq!(notion_language("TS"), "typescript");
+        assert_eq!(notion_language("cpp"), "c++");
+        assert_eq!(notion_language("sh"), "shell");
+        assert_eq!(notion_language(""), "plain text");
+        assert_eq!(notion_language("brainfuck"), "plain text");
+    }
+
+    #[test]
+    fn test_rich_text_annotations() {
+        let rich = json!([
+            {"plain_text": "Read ", "annotations": {}},
+            {"plain_text": "the docs", "annotations": {"bold": true}, "href": "https://example.com"},
+            {"plain_text": " now", "annotations": {"italic": true}}
+        ]);
+        assert_eq!(
+            rich_text_to_markdown(&rich),
+            "Read [**the docs**](https://example.com)_ now_"
+        );
+    }
+
+    #[test]
+    fn test_notion_id() {
+        let id = "0f3c1b2a-9d4e-4c5f-8a6b-7c8d9e0f1a2b";
+        assert_eq!(notion_id(id).unwrap(), id);
+        assert_eq!(notion_id("0f3c1b2a9d4e4c5f8a6b7c8d9e0f1a2b").unwrap(), id);
+        for bad in ["", "../users", "abc/children?x=1", "not-a-uuid"] {
+            assert!(notion_id(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_config_from_plugins() {
+        let mut plugins = HashMap::new();
+        assert!(NotionConfig::from_plugins(&plugins).is_none());
+
+        plugins.insert(
+            "notion".to_string(),
+            json!({"api_key": "secret_abc", "default_database_id": "db1"}),
+        );
+        let config = NotionConfig::from_plugins(&plugins).unwrap();
+        assert_eq!(config.default_database_id.as_deref(), Some("db1"));
+        assert_eq!(config.api_url, "https://api.notion.com/v1");
+    }
+}
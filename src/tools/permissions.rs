@@ -0,0 +1,129 @@
+//! Capability-based tool permissions
+//!
+//! Each tool declares the capabilities it needs via
+//! [`Tool::required_permissions`](super::Tool::required_permissions). A
+//! [`ToolRegistry`](super::ToolRegistry) carries the [`PermissionSet`] of its
+//! callers and refuses to run a tool whose requirements it does not cover.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A capability a tool may need
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Read or write files in the workspace
+    FileSystem,
+    /// Make outbound network requests
+    Network,
+    /// Run OS commands
+    SystemCommand,
+    /// Read or write long-term memory
+    Memory,
+    /// Call third-party APIs with configured credentials
+    ExternalApi,
+}
+
+impl Permission {
+    /// Every permission
+    pub const ALL: [Permission; 5] = [
+        Permission::FileSystem,
+        Permission::Network,
+        Permission::SystemCommand,
+        Permission::Memory,
+        Permission::ExternalApi,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::FileSystem => "file_system",
+            Permission::Network => "network",
+            Permission::SystemCommand => "system_command",
+            Permission::Memory => "memory",
+            Permission::ExternalApi => "external_api",
+        }
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Permissions granted to the callers of a tool registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionSet(BTreeSet<Permission>);
+
+impl Default for PermissionSet {
+    /// Registries allow everything unless restricted
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl PermissionSet {
+    /// Grant every permission
+    pub fn all() -> Self {
+        Self::new(Permission::ALL)
+    }
+
+    /// Grant nothing
+    pub fn none() -> Self {
+        PermissionSet(BTreeSet::new())
+    }
+
+    /// Grant exactly `permissions`
+    pub fn new(permissions: impl IntoIterator<Item = Permission>) -> Self {
+        PermissionSet(permissions.into_iter().collect())
+    }
+
+    /// Also grant `permission`
+    pub fn with(mut self, permission: Permission) -> Self {
+        self.0.insert(permission);
+        self
+    }
+
+    /// Stop granting `permission`
+    pub fn without(mut self, permission: Permission) -> Self {
+        self.0.remove(&permission);
+        self
+    }
+
+    pub fn contains(&self, permission: Permission) -> bool {
+        self.0.contains(&permission)
+    }
+
+    /// Required permissions that are not granted, in `required` order
+    pub fn missing(&self, required: &[Permission]) -> Vec<Permission> {
+        required.iter().copied().filter(|p| !self.contains(*p)).collect()
+    }
+
+    /// Whether every permission in `required` is granted
+    pub fn covers(&self, required: &[Permission]) -> bool {
+        self.missing(required).is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Permission> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_set() {
+        let set = PermissionSet::all().without(Permission::ExternalApi);
+        assert!(set.covers(&[Permission::Network, Permission::FileSystem]));
+        assert!(!set.covers(&[Permission::Network, Permission::ExternalApi]));
+        assert_eq!(set.missing(&[Permission::ExternalApi]), vec![Permission::ExternalApi]);
+        assert!(PermissionSet::none().covers(&[]));
+        assert!(PermissionSet::none().with(Permission::Memory).contains(Permission::Memory));
+        assert_eq!(PermissionSet::default(), PermissionSet::all());
+        assert_eq!(serde_json::to_string(&Permission::SystemCommand).unwrap(), "\"system_command\"");
+    }
+}
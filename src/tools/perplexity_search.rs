@@ -2,13 +2,21 @@
 //!
 //! AI-powered search using Perplexity's chat completions API.
 //! Can use either direct Perplexity API or OpenRouter as proxy.
+//!
+//! Answers cite sources inline as `[N]`; the markers are matched against the
+//! response's `citations` URL list (1-based) and returned as structured
+//! citations alongside a `Sources:` list in the tool output.
 
 use async_trait::async_trait;
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeSet;
 use std::time::Duration;
 
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
+use crate::agent::model_metadata::estimate_tokens;
 use crate::Result;
 
 /// Default timeout for search requests
@@ -56,6 +64,98 @@ impl PerplexityConfig {
     }
 }
 
+/// A source cited in an answer as `[index]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    /// Marker number used in the answer text (1-based)
+    pub index: u8,
+    /// Source URL
+    pub url: String,
+}
+
+/// Answer text with the sources it cites
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PerplexityAnswer {
+    pub answer: String,
+    pub citations: Vec<Citation>,
+    /// Total tokens billed, if reported
+    #[serde(skip)]
+    pub tokens_used: Option<u64>,
+}
+
+impl PerplexityAnswer {
+    /// Build from a chat completions response body
+    fn from_response(json: &Value) -> Self {
+        let answer = json["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or("No response received")
+            .to_string();
+
+        // `citations` is a list of URLs; newer responses may only carry
+        // `search_results` objects with a `url` field
+        let urls: Vec<String> = match json["citations"].as_array() {
+            Some(urls) => urls.iter().filter_map(|u| u.as_str().map(String::from)).collect(),
+            None => json["search_results"]
+                .as_array()
+                .map(|results| {
+                    results
+                        .iter()
+                        .filter_map(|r| r["url"].as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        let citations = extract_citations(&answer, &urls);
+        PerplexityAnswer {
+            answer,
+            citations,
+            tokens_used: json["usage"]["total_tokens"].as_u64(),
+        }
+    }
+
+    /// Tool output for the LLM: the answer followed by a numbered source list
+    pub fn to_context(&self) -> String {
+        let mut out = format!("Answer: {}", self.answer);
+        if !self.citations.is_empty() {
+            out.push_str("\n\nSources:");
+            for citation in &self.citations {
+                out.push_str(&format!("\n{}. {}", citation.index, citation.url));
+            }
+        }
+        out
+    }
+}
+
+/// `[N]` markers in `text`, in ascending order
+fn cited_indices(text: &str) -> BTreeSet<u8> {
+    text.split('[')
+        .skip(1)
+        .filter_map(|rest| rest.split_once(']'))
+        .filter_map(|(inner, _)| inner.parse().ok())
+        .collect()
+}
+
+/// Pair each `[N]` marker with the N-th URL. Markers without a URL are
+/// dropped; if the text cites nothing, every URL is listed.
+fn extract_citations(text: &str, urls: &[String]) -> Vec<Citation> {
+    let cited = cited_indices(text);
+    if cited.is_empty() {
+        return urls
+            .iter()
+            .zip(1..=u8::MAX)
+            .map(|(url, index)| Citation { index, url: url.clone() })
+            .collect();
+    }
+    cited
+        .into_iter()
+        .filter_map(|index| {
+            let url = urls.get(usize::from(index).checked_sub(1)?)?;
+            Some(Citation { index, url: url.clone() })
+        })
+        .collect()
+}
+
 /// Perplexity search tool using chat completions API
 pub struct PerplexitySearchTool {
     client: Client,
@@ -79,7 +179,7 @@ impl PerplexitySearchTool {
     }
 
     /// Perform a search using Perplexity's chat API
-    async fn search(&self, query: &str) -> Result<String> {
+    async fn search(&self, query: &str) -> Result<PerplexityAnswer> {
         let (base_url, auth_header, model) = if self.config.use_openrouter {
             (
                 "https://openrouter.ai/api/v1/chat/completions",
@@ -138,12 +238,7 @@ impl PerplexitySearchTool {
             .await
             .map_err(|e| crate::Error::Provider(format!("Failed to parse Perplexity response: {}", e)))?;
 
-        let content = json["choices"][0]["message"]["content"]
-            .as_str()
-            .unwrap_or("No response received")
-            .to_string();
-
-        Ok(content)
+        Ok(PerplexityAnswer::from_response(&json))
     }
 }
 
@@ -170,6 +265,17 @@ impl Tool for PerplexitySearchTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::Network, Permission::ExternalApi]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "query": "When was Rust 1.0 released?" }),
+            "Answer: Rust 1.0 was released on May 15, 2015 [1].\n\nSources:\n1. https://blog.rust-lang.org/2015/05/15/Rust-1.0.html".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let query = args
             .get("query")
@@ -177,7 +283,16 @@ impl Tool for PerplexitySearchTool {
             .ok_or_else(|| crate::Error::InvalidInput("Missing 'query' parameter".to_string()))?;
 
         match self.search(query).await {
-            Ok(response) => Ok(ToolResult::success(response)),
+            Ok(result) => {
+                let content = result.to_context();
+                let tokens_used = result
+                    .tokens_used
+                    .unwrap_or_else(|| estimate_tokens(&result.answer) as u64);
+                Ok(ToolResult::success(content)
+                    .with_metadata("answer", serde_json::json!(result.answer))
+                    .with_metadata("citations", serde_json::json!(result.citations))
+                    .with_metadata("tokens_used", serde_json::json!(tokens_used)))
+            }
             Err(e) => Ok(ToolResult::failure(format!("Perplexity search failed: {}", e))),
         }
     }
@@ -192,4 +307,40 @@ mod tests {
         // Just test that it doesn't panic
         let _ = PerplexityConfig::from_env();
     }
+
+    #[test]
+    fn test_citation_extraction() {
+        let json = serde_json::json!({
+            "choices": [{"message": {"content": "Rust 1.0 shipped in 2015 [2]. It is fast [1][2], see [x] and [7]."}}],
+            "citations": ["https://a.example", "https://b.example", "https://c.example"],
+            "usage": {"total_tokens": 42}
+        });
+        let result = PerplexityAnswer::from_response(&json);
+
+        assert_eq!(
+            result.citations,
+            vec![
+                Citation { index: 1, url: "https://a.example".into() },
+                Citation { index: 2, url: "https://b.example".into() },
+            ]
+        );
+        assert_eq!(result.tokens_used, Some(42));
+        assert_eq!(
+            result.to_context(),
+            format!("Answer: {}\n\nSources:\n1. https://a.example\n2. https://b.example", result.answer)
+        );
+    }
+
+    #[test]
+    fn test_uncited_sources_and_search_results() {
+        let json = serde_json::json!({
+            "choices": [{"message": {"content": "No markers here."}}],
+            "search_results": [{"title": "A", "url": "https://a.example"}]
+        });
+        let result = PerplexityAnswer::from_response(&json);
+        assert_eq!(result.citations, vec![Citation { index: 1, url: "https://a.example".into() }]);
+
+        let json = serde_json::json!({"choices": [{"message": {"content": "Plain"}}]});
+        assert_eq!(PerplexityAnswer::from_response(&json).to_context(), "Answer: Plain");
+    }
 }
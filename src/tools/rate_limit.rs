@@ -0,0 +1,58 @@
+//! Per-tool call limits
+//!
+//! Caps how many calls to a given tool may run at the same time, so a
+//! batch of calls cannot flood a slow or quota-bound backend.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Limits concurrent executions per tool name; unlisted tools are unlimited
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    limits: HashMap<String, (usize, Arc<Semaphore>)>,
+}
+
+impl RateLimiter {
+    /// Create a limiter with no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits from `agent.tool_concurrency`, by tool name
+    pub fn from_config(limits: &HashMap<String, usize>) -> Self {
+        limits
+            .iter()
+            .fold(Self::new(), |limiter, (tool, max)| limiter.with_limit(tool.clone(), *max))
+    }
+
+    /// Allow at most `max_concurrent` simultaneous calls to `tool`
+    pub fn with_limit(mut self, tool: impl Into<String>, max_concurrent: usize) -> Self {
+        self.set_limit(tool, max_concurrent);
+        self
+    }
+
+    /// Allow at most `max_concurrent` simultaneous calls to `tool`
+    pub fn set_limit(&mut self, tool: impl Into<String>, max_concurrent: usize) {
+        let max_concurrent = max_concurrent.max(1);
+        self.limits.insert(
+            tool.into(),
+            (max_concurrent, Arc::new(Semaphore::new(max_concurrent))),
+        );
+    }
+
+    /// Configured limit for `tool`, if any
+    pub fn limit(&self, tool: &str) -> Option<usize> {
+        self.limits.get(tool).map(|(max, _)| *max)
+    }
+
+    /// Wait for a slot to call `tool`; the slot is released when the permit drops
+    ///
+    /// Returns `None` for tools without a limit.
+    pub async fn acquire(&self, tool: &str) -> Option<OwnedSemaphorePermit> {
+        let (_, semaphore) = self.limits.get(tool)?;
+        // The semaphore is never closed
+        semaphore.clone().acquire_owned().await.ok()
+    }
+}
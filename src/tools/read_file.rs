@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::path::PathBuf;
 
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
 use crate::error::Result;
 
@@ -43,6 +44,17 @@ impl Tool for ReadFileTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "path": "notes/todo.md" }),
+            "- [ ] Renew TLS certificate\n- [x] Rotate API keys".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let path = args
             .get("path")
@@ -57,7 +69,11 @@ impl Tool for ReadFileTool {
         }
 
         match tokio::fs::read_to_string(&full_path).await {
-            Ok(content) => Ok(ToolResult::success(content)),
+            Ok(content) => {
+                let file_size = content.len();
+                Ok(ToolResult::success(content)
+                    .with_metadata("file_size", serde_json::json!(file_size)))
+            }
             Err(e) => Ok(ToolResult::failure(format!("Failed to read file: {}", e))),
         }
     }
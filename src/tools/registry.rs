@@ -1,15 +1,68 @@
 //! Tool registry - manages available tools for the agent
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, field, info_span, warn, Instrument};
 
 use crate::agent::types::ToolDefinition;
+use crate::agent::OpenRouterClient;
 use crate::error::Result;
 
+use super::events::ToolEventBus;
+use super::explain::ToolExplanation;
+use super::interceptor::ToolInterceptor;
+use super::metrics::MetricsRecorder;
+use super::permissions::PermissionSet;
+use super::rate_limit::RateLimiter;
+use super::schema::validate_arguments;
+use super::summary::SummaryTool;
 use super::traits::{Tool, ToolCall, ToolResult};
 
 /// Registry of available tools
 pub struct ToolRegistry {
-    tools: HashMap<String, Box<dyn Tool>>,
+    tools: HashMap<String, Arc<dyn Tool>>,
+    rate_limiter: RateLimiter,
+    metrics: Option<Arc<dyn MetricsRecorder>>,
+    permissions: PermissionSet,
+    events: ToolEventBus,
+    interceptors: Vec<Arc<dyn ToolInterceptor>>,
+}
+
+/// Name and schema of a registered tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolSnapshot {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// Outcome of [`ToolRegistry::dry_run`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolDryRunResult {
+    /// A tool with the call's name is registered
+    pub tool_found: bool,
+    /// The arguments satisfy the tool's parameter schema
+    pub schema_valid: bool,
+    /// Why the call would be rejected, if it would
+    pub validation_errors: Vec<String>,
+}
+
+/// Serializable description of a registry's tools, sorted by name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolRegistrySnapshot {
+    pub tools: Vec<ToolSnapshot>,
+}
+
+impl ToolRegistrySnapshot {
+    /// Names of the captured tools
+    pub fn names(&self) -> Vec<&str> {
+        self.tools.iter().map(|t| t.name.as_str()).collect()
+    }
 }
 
 impl Default for ToolRegistry {
@@ -23,12 +76,140 @@ impl ToolRegistry {
     pub fn new() -> Self {
         ToolRegistry {
             tools: HashMap::new(),
+            rate_limiter: RateLimiter::new(),
+            metrics: None,
+            permissions: PermissionSet::all(),
+            events: ToolEventBus::new(),
+            interceptors: Vec::new(),
+        }
+    }
+
+    /// Run `interceptor` around every call, after those already added
+    pub fn with_interceptor(mut self, interceptor: Arc<dyn ToolInterceptor>) -> Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Only run tools whose required permissions `permissions` covers
+    pub fn with_permissions(mut self, permissions: PermissionSet) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Replace the permissions granted to callers of this registry
+    pub fn set_permissions(&mut self, permissions: PermissionSet) {
+        self.permissions = permissions;
+    }
+
+    /// Permissions granted to callers of this registry
+    pub fn permissions(&self) -> &PermissionSet {
+        &self.permissions
+    }
+
+    /// Why the registry's permissions do not allow running `tool`, if they don't
+    fn permission_error(&self, tool: &dyn Tool) -> Option<String> {
+        let missing = self.permissions.missing(&tool.required_permissions());
+        if missing.is_empty() {
+            return None;
         }
+        let missing: Vec<&str> = missing.iter().map(|p| p.as_str()).collect();
+        Some(format!(
+            "Permission denied: tool '{}' requires {}",
+            tool.name(),
+            missing.join(", ")
+        ))
+    }
+
+    /// Use `rate_limiter` to cap concurrent calls per tool
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Allow at most `max_concurrent` simultaneous calls to the named tool
+    pub fn set_rate_limit(&mut self, name: impl Into<String>, max_concurrent: usize) {
+        self.rate_limiter.set_limit(name, max_concurrent);
+    }
+
+    /// Report the duration and outcome of every call to `metrics`
+    pub fn record_metrics(&mut self, metrics: Arc<dyn MetricsRecorder>) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Push events published by this registry's tools
+    pub fn events(&self) -> &ToolEventBus {
+        &self.events
     }
 
     /// Register a tool
     pub fn register<T: Tool + 'static>(&mut self, tool: T) {
-        self.tools.insert(tool.name().to_string(), Box::new(tool));
+        self.tools.insert(tool.name().to_string(), Arc::new(tool));
+    }
+
+    /// Register an already boxed tool
+    pub fn register_boxed(&mut self, tool: Box<dyn Tool>) {
+        self.tools.insert(tool.name().to_string(), Arc::from(tool));
+    }
+
+    /// Replace the named tool with a [`SummaryTool`] that has `client`
+    /// summarize its results longer than `max_chars` characters
+    ///
+    /// Returns `false` if no tool has that name.
+    pub fn wrap_with_summarizer(&mut self, tool_name: &str, client: Arc<OpenRouterClient>, max_chars: usize) -> bool {
+        let Some(inner) = self.tools.get(tool_name).cloned() else {
+            return false;
+        };
+        let wrapped = SummaryTool::new(inner, client).with_max_chars(max_chars);
+        self.tools.insert(tool_name.to_string(), Arc::new(wrapped));
+        true
+    }
+
+    /// Rebuild a registry from a snapshot
+    ///
+    /// `builder` is called once per captured tool name; tools it cannot
+    /// build are left out with a warning.
+    pub fn restore(
+        snapshot: &ToolRegistrySnapshot,
+        builder: impl Fn(&str) -> Option<Box<dyn Tool>>,
+    ) -> Self {
+        let mut registry = Self::new();
+        for captured in &snapshot.tools {
+            match builder(&captured.name) {
+                Some(tool) => {
+                    if tool.parameters_schema() != captured.parameters {
+                        debug!("Tool '{}' schema changed on restore", captured.name);
+                    }
+                    registry.register_boxed(tool);
+                }
+                None => warn!("Tool '{}' could not be restored, dropping it", captured.name),
+            }
+        }
+        registry
+    }
+
+    /// Capture the names and schemas of all registered tools
+    pub fn snapshot(&self) -> ToolRegistrySnapshot {
+        let mut tools: Vec<ToolSnapshot> = self
+            .tools
+            .values()
+            .map(|t| ToolSnapshot {
+                name: t.name().to_string(),
+                description: t.description().to_string(),
+                parameters: t.parameters_schema(),
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        ToolRegistrySnapshot { tools }
+    }
+
+    /// Get a shared handle to a tool, e.g. to carry it into a restored registry
+    pub fn shared(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.get(name).cloned()
+    }
+
+    /// Documentation for a tool: parameters and an example call
+    pub fn explain(&self, name: &str) -> Option<ToolExplanation> {
+        self.get(name).map(ToolExplanation::for_tool)
     }
 
     /// Get a tool by name
@@ -41,15 +222,147 @@ impl ToolRegistry {
         self.tools.values().map(|t| t.to_definition()).collect()
     }
 
-    /// Execute a tool call
+    /// Execute a tool call inside an `agent.tool.{name}` span
+    ///
+    /// Fails without running the tool if the registry's permissions do not
+    /// cover its required permissions. Waits for a free slot first if the
+    /// tool has a rate limit. Calls to registered tools are reported to the
+    /// metrics recorder, if any. Interceptors see the call before all of
+    /// this and the result after it.
     pub async fn execute(&self, call: &ToolCall) -> Result<ToolResult> {
-        match self.get(&call.name) {
-            Some(tool) => tool.execute(call.arguments.clone()).await,
-            None => Ok(ToolResult::failure(format!(
-                "Unknown tool: {}",
-                call.name
-            ))),
+        if self.interceptors.is_empty() {
+            return self.dispatch(call).await;
+        }
+
+        let mut call = Cow::Borrowed(call);
+        for interceptor in &self.interceptors {
+            if let Err(e) = interceptor.before_execute(call.to_mut()) {
+                warn!("Tool call {} blocked by interceptor: {}", call.name, e);
+                return Ok(ToolResult::failure(format!("Tool call blocked: {}", e)));
+            }
+        }
+        let mut result = self.dispatch(&call).await;
+        if let Ok(result) = &mut result {
+            for interceptor in self.interceptors.iter().rev() {
+                interceptor.after_execute(&call, result);
+            }
+        }
+        result
+    }
+
+    /// Run `call` on its tool, without interceptors
+    async fn dispatch(&self, call: &ToolCall) -> Result<ToolResult> {
+        let tool = match self.get(&call.name) {
+            Some(tool) => tool,
+            None => {
+                return Ok(ToolResult::failure(format!(
+                    "Unknown tool: {}",
+                    call.name
+                )))
+            }
+        };
+        if let Some(denied) = self.permission_error(tool) {
+            warn!("{}", denied);
+            return Ok(ToolResult::failure(denied));
+        }
+
+        let span = info_span!(
+            "agent.tool",
+            otel.name = %format!("agent.tool.{}", call.name),
+            tool.name = %call.name,
+            success = field::Empty,
+            duration_ms = field::Empty,
+        );
+        let _permit = self.rate_limiter.acquire(&call.name).await;
+        let start = Instant::now();
+        let result = tool
+            .execute(call.arguments.clone())
+            .instrument(span.clone())
+            .await;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        let success = matches!(&result, Ok(r) if r.success);
+        span.record("duration_ms", duration_ms);
+        span.record("success", success);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_call(&call.name, duration_ms, success);
+            if let Err(e) = &result {
+                metrics.record_error(&call.name, e.category());
+            }
+        }
+        result
+    }
+
+    /// Check that `call` would dispatch to a tool with schema-valid arguments,
+    /// without executing it
+    pub fn dry_run(&self, call: &ToolCall) -> Result<ToolDryRunResult> {
+        let Some(tool) = self.get(&call.name) else {
+            return Ok(ToolDryRunResult {
+                tool_found: false,
+                schema_valid: false,
+                validation_errors: vec![format!("Unknown tool: {}", call.name)],
+            });
+        };
+
+        let mut validation_errors = validate_arguments(&tool.parameters_schema(), &call.arguments);
+        let schema_valid = validation_errors.is_empty();
+        validation_errors.extend(self.permission_error(tool));
+        Ok(ToolDryRunResult {
+            tool_found: true,
+            schema_valid,
+            validation_errors,
+        })
+    }
+
+    /// Execute several tool calls concurrently, returning results in input order
+    ///
+    /// At most `max_concurrency` calls are in flight at once (minimum 1);
+    /// per-tool rate limits still apply on top of that.
+    pub async fn execute_batch(
+        &self,
+        calls: &[ToolCall],
+        max_concurrency: usize,
+    ) -> Vec<Result<ToolResult>> {
+        self.execute_batch_timed(calls, max_concurrency)
+            .await
+            .into_iter()
+            .map(|(result, _)| result)
+            .collect()
+    }
+
+    /// Like [`execute_batch`](Self::execute_batch), also returning how long
+    /// each call took (including any wait for its per-tool limit)
+    pub async fn execute_batch_timed(
+        &self,
+        calls: &[ToolCall],
+        max_concurrency: usize,
+    ) -> Vec<(Result<ToolResult>, Duration)> {
+        let run = |index: usize| async move {
+            let start = Instant::now();
+            let result = self.execute(&calls[index]).await;
+            (index, (result, start.elapsed()))
+        };
+
+        let mut pending = 0..calls.len();
+        let mut in_flight: FuturesUnordered<_> = pending
+            .by_ref()
+            .take(max_concurrency.max(1))
+            .map(run)
+            .collect();
+
+        let mut results: Vec<Option<(Result<ToolResult>, Duration)>> =
+            calls.iter().map(|_| None).collect();
+        while let Some((index, result)) = in_flight.next().await {
+            results[index] = Some(result);
+            if let Some(next) = pending.next() {
+                in_flight.push(run(next));
+            }
         }
+
+        results
+            .into_iter()
+            .map(|r| r.expect("every batched call completes"))
+            .collect()
     }
 
     /// Get tool count
@@ -66,7 +379,254 @@ impl ToolRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::permissions::Permission;
     use super::super::traits::ToolResult;
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+
+    struct NamedTool(&'static str);
+
+    #[async_trait]
+    impl Tool for NamedTool {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "test tool"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object", "properties": {} })
+        }
+
+        async fn execute(&self, _args: Value) -> Result<ToolResult> {
+            Ok(ToolResult::success(self.0))
+        }
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() {
+        let mut registry = ToolRegistry::new();
+        registry.register(NamedTool("zeta"));
+        registry.register(NamedTool("alpha"));
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.names(), vec!["alpha", "zeta"]);
+        assert_eq!(snapshot.tools[0].parameters["type"], "object");
+        for name in snapshot.names() {
+            let check = registry.dry_run(&call(name, json!({}))).unwrap();
+            assert!(check.tool_found && check.schema_valid, "{}: {:?}", name, check);
+        }
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: ToolRegistrySnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+    }
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            id: "1".to_string(),
+            name: name.to_string(),
+            arguments,
+            parent_message_id: None,
+        }
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let mut registry = ToolRegistry::new();
+        registry.register(NamedTool("alpha"));
+
+        let missing = registry.dry_run(&call("beta", json!({}))).unwrap();
+        assert!(!missing.tool_found);
+        assert_eq!(missing.validation_errors, vec!["Unknown tool: beta"]);
+
+        let invalid = registry.dry_run(&call("alpha", json!("not an object"))).unwrap();
+        assert!(invalid.tool_found);
+        assert!(!invalid.schema_valid);
+        assert_eq!(invalid.validation_errors, vec!["arguments: expected object, got string"]);
+    }
+
+    struct NetworkTool;
+
+    #[async_trait]
+    impl Tool for NetworkTool {
+        fn name(&self) -> &str {
+            "fetch"
+        }
+
+        fn description(&self) -> &str {
+            "test tool"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object", "properties": {} })
+        }
+
+        async fn execute(&self, _args: Value) -> Result<ToolResult> {
+            Ok(ToolResult::success("fetched"))
+        }
+
+        fn required_permissions(&self) -> Vec<Permission> {
+            vec![Permission::Network]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permissions_checked_before_execute() {
+        let mut registry = ToolRegistry::new().with_permissions(PermissionSet::none());
+        registry.register(NetworkTool);
+        registry.register(NamedTool("plain"));
+
+        let denied = registry.execute(&call("fetch", json!({}))).await.unwrap();
+        assert!(!denied.success);
+        assert_eq!(denied.error.as_deref(), Some("Permission denied: tool 'fetch' requires network"));
+        let check = registry.dry_run(&call("fetch", json!({}))).unwrap();
+        assert!(check.schema_valid);
+        assert_eq!(check.validation_errors.len(), 1);
+        // Tools without requirements run under any permission set
+        assert!(registry.execute(&call("plain", json!({}))).await.unwrap().success);
+
+        // Shared handles keep their requirements
+        let mut restored = ToolRegistry::restore(&registry.snapshot(), |name| {
+            registry.shared(name).map(|t| Box::new(t) as Box<dyn Tool>)
+        });
+        restored.set_permissions(PermissionSet::none().with(Permission::Network));
+        assert!(restored.execute(&call("fetch", json!({}))).await.unwrap().success);
+    }
+
+    #[tokio::test]
+    async fn test_restore_uses_builder() {
+        let mut registry = ToolRegistry::new();
+        registry.register(NamedTool("alpha"));
+        registry.register(NamedTool("gone"));
+
+        let restored = ToolRegistry::restore(&registry.snapshot(), |name| match name {
+            "alpha" => Some(Box::new(NamedTool("alpha")) as Box<dyn Tool>),
+            _ => None,
+        });
+        assert_eq!(restored.names(), vec!["alpha"]);
+
+        let call = ToolCall {
+            id: "1".to_string(),
+            name: "alpha".to_string(),
+            arguments: json!({}),
+            parent_message_id: None,
+        };
+        assert!(restored.execute(&call).await.unwrap().success);
+    }
+
+    /// Sleeps for `delay_ms` and tracks the peak number of concurrent calls
+    struct SlowTool {
+        running: Arc<std::sync::atomic::AtomicUsize>,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for SlowTool {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "test tool"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object", "properties": { "delay_ms": { "type": "integer" } } })
+        }
+
+        async fn execute(&self, args: Value) -> Result<ToolResult> {
+            use std::sync::atomic::Ordering;
+
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            let delay = args["delay_ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(ToolResult::success(delay.to_string()))
+        }
+    }
+
+    fn slow_call(delay_ms: u64) -> ToolCall {
+        ToolCall {
+            id: delay_ms.to_string(),
+            name: "slow".to_string(),
+            arguments: json!({ "delay_ms": delay_ms }),
+            parent_message_id: None,
+        }
+    }
+
+    fn slow_registry() -> (ToolRegistry, Arc<std::sync::atomic::AtomicUsize>) {
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut registry = ToolRegistry::new();
+        registry.register(SlowTool {
+            running: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            peak: peak.clone(),
+        });
+        (registry, peak)
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_preserves_order() {
+        let (registry, peak) = slow_registry();
+        let calls = vec![slow_call(40), slow_call(0), slow_call(20), slow_call(10)];
+
+        let results = registry.execute_batch(&calls, 4).await;
+        let contents: Vec<String> = results
+            .into_iter()
+            .map(|r| r.unwrap().content.unwrap())
+            .collect();
+        assert_eq!(contents, vec!["40", "0", "20", "10"]);
+        assert!(peak.load(std::sync::atomic::Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_timed_per_call() {
+        let (registry, _) = slow_registry();
+        let calls = vec![slow_call(60), slow_call(0)];
+
+        let results = registry.execute_batch_timed(&calls, 2).await;
+        assert!(results[0].1 >= Duration::from_millis(60));
+        assert!(results[1].1 < Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn test_execute_batch_respects_limits() {
+        let (registry, peak) = slow_registry();
+        let calls: Vec<ToolCall> = (0..6).map(|_| slow_call(5)).collect();
+        assert_eq!(registry.execute_batch(&calls, 2).await.len(), 6);
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let (registry, peak) = slow_registry();
+        let registry = registry.with_rate_limiter(RateLimiter::new().with_limit("slow", 1));
+        assert_eq!(registry.rate_limiter.limit("slow"), Some(1));
+        let results = registry.execute_batch(&calls, 6).await;
+        assert!(results.iter().all(|r| r.as_ref().unwrap().success));
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_records_metrics() {
+        let recorder = Arc::new(super::super::PrometheusMetricsRecorder::new());
+        let mut registry = ToolRegistry::new();
+        registry.register(NamedTool("alpha"));
+        registry.record_metrics(recorder.clone());
+
+        let call = |name: &str| ToolCall {
+            id: "1".to_string(),
+            name: name.to_string(),
+            arguments: json!({}),
+            parent_message_id: None,
+        };
+        registry.execute(&call("alpha")).await.unwrap();
+        registry.execute(&call("missing")).await.unwrap();
+
+        let text = recorder.render();
+        assert!(text.contains("openagent_tool_calls_total{status=\"success\",tool=\"alpha\"} 1\n"));
+        assert!(!text.contains("missing"));
+    }
 
     #[test]
     fn test_tool_result() {
@@ -78,4 +638,57 @@ mod tests {
         assert!(!failure.success);
         assert_eq!(failure.error.as_deref(), Some("Oops!"));
     }
+
+    #[test]
+    fn test_tool_result_metadata() {
+        let result = ToolResult::success("ok")
+            .with_metadata("exit_code", serde_json::json!(0))
+            .with_metadata("file_size", serde_json::json!(42));
+        assert_eq!(result.metadata("exit_code"), Some(&serde_json::json!(0)));
+        assert_eq!(result.metadata("file_size"), Some(&serde_json::json!(42)));
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["metadata"]["file_size"], 42);
+        assert!(serde_json::to_value(ToolResult::success("x")).unwrap().get("metadata").is_none());
+    }
+
+    /// Records its hooks in `log` and blocks calls to `blocked`
+    struct Recorder {
+        label: &'static str,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl ToolInterceptor for Recorder {
+        fn before_execute(&self, call: &mut ToolCall) -> Result<()> {
+            self.log.lock().unwrap().push(format!("before {}", self.label));
+            if call.name == "blocked" {
+                return Err(crate::Error::Unauthorized("not today".to_string()));
+            }
+            call.arguments["seen_by"] = json!(self.label);
+            Ok(())
+        }
+
+        fn after_execute(&self, _call: &ToolCall, result: &mut ToolResult) {
+            self.log.lock().unwrap().push(format!("after {}", self.label));
+            result.content = result.content.take().map(|c| format!("{}+{}", c, self.label));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_interceptor_chain() {
+        let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = |label| Arc::new(Recorder { label, log: log.clone() });
+        let mut registry = ToolRegistry::new()
+            .with_interceptor(recorder("outer"))
+            .with_interceptor(recorder("inner"));
+        registry.register(NamedTool("alpha"));
+
+        let result = registry.execute(&call("alpha", json!({}))).await.unwrap();
+        assert_eq!(result.content.as_deref(), Some("alpha+inner+outer"));
+        assert_eq!(*log.lock().unwrap(), vec!["before outer", "before inner", "after inner", "after outer"]);
+
+        let blocked = registry.execute(&call("blocked", json!({}))).await.unwrap();
+        assert!(!blocked.success);
+        assert!(blocked.error.unwrap().starts_with("Tool call blocked:"));
+    }
 }
@@ -0,0 +1,191 @@
+//! Argument checks against tool parameter schemas
+//!
+//! Covers the JSON Schema keywords tools use in `parameters_schema`:
+//! `type` (a name or a list of names), `enum`, `required`, `properties` and
+//! `items`. Unknown keywords are ignored.
+//!
+//! [`example_arguments`] builds placeholder arguments from the same keywords
+//! for tools that don't provide their own example.
+
+use serde_json::Value;
+
+/// Check `args` against `schema`, returning one message per violation
+pub(crate) fn validate_arguments(schema: &Value, args: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate(schema, args, "arguments", &mut errors);
+    errors
+}
+
+fn validate(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type") {
+        let names: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| has_type(value, name)) {
+            errors.push(format!("{}: expected {}, got {}", path, names.join(" or "), type_name(value)));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} is not one of {}", path, value, Value::Array(allowed.clone())));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !fields.contains_key(name) {
+                        errors.push(format!("{}: missing required property '{}'", path, name));
+                    }
+                }
+            }
+            if let Some(Value::Object(properties)) = schema.get("properties") {
+                for (name, field) in fields {
+                    if let Some(property) = properties.get(name) {
+                        validate(property, field, &format!("{}.{}", path, name), errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(item_schema, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Placeholder arguments with every required property of `schema` set:
+/// the first `enum` value, or a stand-in for the property's type
+pub(crate) fn example_arguments(schema: &Value) -> Value {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    let args = required
+        .into_iter()
+        .map(|name| {
+            let property = properties.and_then(|p| p.get(name)).unwrap_or(&Value::Null);
+            (name.to_string(), example_value(name, property))
+        })
+        .collect();
+    Value::Object(args)
+}
+
+fn example_value(name: &str, property: &Value) -> Value {
+    if let Some(first) = property.get("enum").and_then(Value::as_array).and_then(|v| v.first()) {
+        return first.clone();
+    }
+    let type_name = match property.get("type") {
+        Some(Value::Array(names)) => names.first().and_then(Value::as_str),
+        Some(name) => name.as_str(),
+        None => None,
+    };
+    match type_name {
+        Some("object") => example_arguments(property),
+        Some("array") => Value::Array(Vec::new()),
+        Some("integer") => Value::from(1),
+        Some("number") => Value::from(1.0),
+        Some("boolean") => Value::Bool(true),
+        Some("null") => Value::Null,
+        _ => Value::String(format!("<{}>", name)),
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": { "type": "string", "enum": ["status", "log"] },
+                "limit": { "type": "integer" },
+                "paths": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        let args = json!({ "operation": "log", "limit": 5, "paths": ["a"], "extra": true });
+        assert!(validate_arguments(&schema(), &args).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_violation() {
+        let args = json!({ "operation": "push", "limit": 2.5, "paths": ["a", 3] });
+        let mut errors = validate_arguments(&schema(), &args);
+        errors.sort();
+        assert_eq!(
+            errors,
+            vec![
+                "arguments.limit: expected integer, got number",
+                "arguments.operation: \"push\" is not one of [\"status\",\"log\"]",
+                "arguments.paths[1]: expected string, got integer",
+            ]
+        );
+
+        assert_eq!(
+            validate_arguments(&schema(), &json!({})),
+            vec!["arguments: missing required property 'operation'"]
+        );
+        assert_eq!(
+            validate_arguments(&schema(), &json!("status")),
+            vec!["arguments: expected object, got string"]
+        );
+    }
+
+    #[test]
+    fn test_example_arguments() {
+        assert_eq!(example_arguments(&schema()), json!({ "operation": "status" }));
+
+        let schema = json!({
+            "type": "object",
+            "properties": { "path": { "type": "string" }, "limit": { "type": "integer" } },
+            "required": ["path", "limit"]
+        });
+        let args = example_arguments(&schema);
+        assert_eq!(args, json!({ "path": "<path>", "limit": 1 }));
+        assert!(validate_arguments(&schema, &args).is_empty());
+    }
+}
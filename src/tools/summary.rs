@@ -0,0 +1,212 @@
+//! Summarizing tool wrapper
+//!
+//! Some tools can return far more text than the agent needs, e.g. `read_file`
+//! on a large log. [`SummaryTool`] wraps such a tool and, when a successful
+//! result is longer than `max_chars`, asks the LLM to condense it to three
+//! sentences before it is added to the context. Attach it with
+//! [`ToolRegistry::wrap_with_summarizer`](super::ToolRegistry::wrap_with_summarizer).
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use super::permissions::Permission;
+use super::traits::{Tool, ToolResult};
+use crate::agent::{GenerationOptions, Message, OpenRouterClient};
+use crate::Result;
+
+/// Default length above which results are summarized
+pub const DEFAULT_SUMMARY_MAX_CHARS: usize = 2000;
+
+/// Wraps a tool and summarizes its long results with the LLM
+///
+/// The wrapper keeps the inner tool's name, description, schema and
+/// permissions, so the agent sees no difference. Failed results are passed
+/// through unchanged. If summarizing fails, the result is truncated to
+/// `max_chars` instead.
+pub struct SummaryTool {
+    inner: Arc<dyn Tool>,
+    client: Arc<OpenRouterClient>,
+    max_chars: usize,
+}
+
+impl SummaryTool {
+    /// Summarize results of `inner` longer than [`DEFAULT_SUMMARY_MAX_CHARS`]
+    pub fn new(inner: Arc<dyn Tool>, client: Arc<OpenRouterClient>) -> Self {
+        SummaryTool {
+            inner,
+            client,
+            max_chars: DEFAULT_SUMMARY_MAX_CHARS,
+        }
+    }
+
+    /// Summarize results longer than `max_chars` characters
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Ask the LLM for a three sentence summary of `output`
+    async fn summarize(&self, output: &str) -> Result<String> {
+        let prompt = format!(
+            "Summarize the following output of the `{}` tool in 3 sentences. \
+             Keep names, numbers and error messages that matter; reply with the summary only.\n\n{}",
+            self.inner.name(),
+            output
+        );
+        let response = self
+            .client
+            .chat(vec![Message::user(prompt)], GenerationOptions::precise())
+            .await?;
+        Ok(response
+            .choices
+            .first()
+            .map(|c| c.message.content.trim().to_string())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Tool for SummaryTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.inner.parameters_schema()
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        self.inner.required_permissions()
+    }
+
+    fn example(&self) -> (Value, String) {
+        self.inner.example()
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        let mut result = self.inner.execute(args).await?;
+        let Some(content) = result.content.as_deref().filter(|_| result.success) else {
+            return Ok(result);
+        };
+        let chars = content.chars().count();
+        if chars <= self.max_chars {
+            return Ok(result);
+        }
+
+        let summary = match self.summarize(content).await {
+            Ok(summary) if !summary.is_empty() => {
+                debug!("Summarized {} characters of {} output", chars, self.name());
+                format!("[Summary of {} characters of output]\n{}", chars, summary)
+            }
+            Ok(_) | Err(_) => {
+                warn!("Failed to summarize {} output, truncating it", self.name());
+                let end = content.char_indices().nth(self.max_chars).map_or(content.len(), |(i, _)| i);
+                format!("{}\n\n[truncated after {} of {} characters]", &content[..end], self.max_chars, chars)
+            }
+        };
+        result.content = Some(summary);
+        Ok(result
+            .with_metadata("summarized", Value::Bool(true))
+            .with_metadata("original_chars", chars.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{ToolCall, ToolRegistry};
+    use serde_json::json;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Returns `args.len` characters of log output
+    struct LogTool;
+
+    #[async_trait]
+    impl Tool for LogTool {
+        fn name(&self) -> &str {
+            "read_log"
+        }
+
+        fn description(&self) -> &str {
+            "Read the log"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            json!({ "type": "object", "properties": { "len": { "type": "integer" } } })
+        }
+
+        fn required_permissions(&self) -> Vec<Permission> {
+            vec![Permission::FileSystem]
+        }
+
+        async fn execute(&self, args: Value) -> Result<ToolResult> {
+            let len = args["len"].as_u64().unwrap_or(0) as usize;
+            Ok(ToolResult::success("x".repeat(len)))
+        }
+    }
+
+    fn call(len: usize) -> ToolCall {
+        ToolCall {
+            id: "call_1".to_string(),
+            name: "read_log".to_string(),
+            arguments: json!({ "len": len }),
+            parent_message_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_results_are_summarized() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("in 3 sentences"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": "gen-1",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "test/model",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "The log is all x." },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = Arc::new(OpenRouterClient::new(crate::config::OpenRouterConfig {
+            api_key: secrecy::SecretString::from("test-key"),
+            default_model: "test/model".to_string(),
+            site_url: None,
+            site_name: None,
+            base_url: server.uri(),
+            timeout_secs: 5,
+            max_retries: 0,
+        })
+        .unwrap());
+        let mut registry = ToolRegistry::new();
+        registry.register(LogTool);
+        assert!(registry.wrap_with_summarizer("read_log", client.clone(), 100));
+        assert!(!registry.wrap_with_summarizer("missing", client, 100));
+        assert_eq!(registry.get("read_log").unwrap().required_permissions(), vec![Permission::FileSystem]);
+
+        let short = registry.execute(&call(100)).await.unwrap();
+        assert_eq!(short.content.as_deref().map(str::len), Some(100));
+        assert!(short.metadata("summarized").is_none());
+
+        let long = registry.execute(&call(5000)).await.unwrap();
+        assert_eq!(
+            long.content.as_deref(),
+            Some("[Summary of 5000 characters of output]\nThe log is all x.")
+        );
+        assert_eq!(long.metadata("original_chars"), Some(&json!(5000)));
+    }
+}
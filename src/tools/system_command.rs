@@ -2,6 +2,11 @@
 //!
 //! Allows the agent to execute OS commands like `apt update`, `mv a b`, etc.
 //! Supports allowlist/denylist for security control.
+//!
+//! Outside full-access mode, commands start with an empty environment plus
+//! [`BASE_ENV_VARS`] and any variables named via `with_env_passthrough`, so
+//! secrets in the agent's own environment (API keys, database URLs) are not
+//! visible to them.
 
 use async_trait::async_trait;
 use serde_json::Value;
@@ -10,9 +15,13 @@ use std::path::PathBuf;
 use std::time::Duration;
 use tokio::process::Command;
 
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
 use crate::error::Result;
 
+/// Variables kept for every command when the environment is stripped
+pub const BASE_ENV_VARS: &[&str] = &["PATH", "HOME", "LANG", "LC_ALL", "TERM", "TMPDIR"];
+
 /// Tool for executing system commands
 ///
 /// This tool allows the agent to run OS commands with optional
@@ -31,6 +40,10 @@ pub struct SystemCommandTool {
     /// Allow shell metacharacters in arguments (&&, |, ;, etc.)
     /// When true, shell injection checks are skipped (for full OS access mode)
     allow_shell_metacharacters: bool,
+    /// Start commands with a stripped environment (false in full OS access mode)
+    strip_env: bool,
+    /// Extra variables copied from the agent's environment when stripping
+    env_passthrough: Vec<String>,
 }
 
 impl Default for SystemCommandTool {
@@ -57,6 +70,8 @@ impl SystemCommandTool {
             denied_commands: denied,
             run_as_user: None,
             allow_shell_metacharacters: false,
+            strip_env: true,
+            env_passthrough: Vec::new(),
         }
     }
 
@@ -93,6 +108,8 @@ impl SystemCommandTool {
             denied_commands: HashSet::new(), // No denied commands
             run_as_user: None,
             allow_shell_metacharacters: true, // Allow pipes, &&, etc.
+            strip_env: false, // Inherit the full environment
+            env_passthrough: Vec::new(),
         }
     }
 
@@ -117,6 +134,36 @@ impl SystemCommandTool {
         self
     }
 
+    /// Pass these variables from the agent's environment through to commands
+    /// (e.g. `CARGO_HOME`, `GOPATH`); everything else beyond
+    /// [`BASE_ENV_VARS`] stays stripped. No effect in full access mode, where
+    /// the whole environment is inherited.
+    pub fn with_env_passthrough(mut self, vars: &[&str]) -> Self {
+        for var in vars {
+            if !self.env_passthrough.iter().any(|v| v == var) {
+                self.env_passthrough.push(var.to_string());
+            }
+        }
+        self
+    }
+
+    /// Variables a command will see, read from the current environment
+    fn command_env(&self) -> Vec<(String, String)> {
+        BASE_ENV_VARS
+            .iter()
+            .copied()
+            .chain(self.env_passthrough.iter().map(String::as_str))
+            .filter_map(|name| std::env::var(name).ok().map(|value| (name.to_string(), value)))
+            .collect()
+    }
+
+    /// Skip the shell metacharacter check for callers that quote every
+    /// argument themselves, so `;` or `|` inside an argument stay literal
+    pub(crate) fn with_quoted_args(mut self) -> Self {
+        self.allow_shell_metacharacters = true;
+        self
+    }
+
     /// Set allowed commands (whitelist)
     /// When set, only these commands can be executed
     pub fn with_allowed_commands(mut self, commands: Vec<String>) -> Self {
@@ -211,6 +258,17 @@ impl Tool for SystemCommandTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::SystemCommand]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "command": "ls", "args": ["-1", "src"] }),
+            "STDOUT:\nlib.rs\nmain.rs\n".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         // Parse command
         let command = args
@@ -279,6 +337,10 @@ impl Tool for SystemCommandTool {
         let mut cmd = Command::new(shell_cmd);
         cmd.args(&shell_args);
 
+        if self.strip_env {
+            cmd.env_clear().envs(self.command_env());
+        }
+
         // Set working directory if specified
         if let Some(ref dir) = working_dir {
             cmd.current_dir(dir);
@@ -316,25 +378,22 @@ impl Tool for SystemCommandTool {
                     content = format!("Command completed with exit code {}", exit_code);
                 }
 
-                // Include metadata with exit code
-                let metadata = serde_json::json!({
-                    "exit_code": exit_code,
-                    "success": success,
-                    "command": command,
-                    "args": cmd_args,
-                });
-
-                if success {
-                    Ok(ToolResult::success_with_metadata(content, metadata))
+                let result = if success {
+                    ToolResult::success(content)
                 } else {
                     // Command failed but executed - return as failure with details
-                    Ok(ToolResult {
-                        success: false,
+                    ToolResult {
                         content: Some(content),
-                        error: Some(format!("Command exited with code {}", exit_code)),
-                        metadata: Some(metadata),
-                    })
-                }
+                        ..ToolResult::failure(format!("Command exited with code {}", exit_code))
+                    }
+                };
+
+                Ok(result
+                    .with_metadata("exit_code", serde_json::json!(exit_code))
+                    .with_metadata("command", serde_json::json!(command))
+                    .with_metadata("args", serde_json::json!(cmd_args))
+                    .with_metadata("stdout", serde_json::json!(stdout))
+                    .with_metadata("stderr", serde_json::json!(stderr)))
             }
             Ok(Err(e)) => {
                 // Failed to execute command (e.g., command not found)
@@ -368,6 +427,29 @@ mod tests {
         assert!(result.content.unwrap().contains("hello world"));
     }
 
+    #[test]
+    fn test_env_passthrough() {
+        // Set before any runtime threads exist; no other test reads these names
+        std::env::set_var("OPENAGENT_TEST_PASSTHROUGH", "visible");
+        std::env::set_var("OPENAGENT_TEST_SECRET", "hidden");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let args = serde_json::json!({ "command": "env" });
+
+        let tool = SystemCommandTool::new().with_env_passthrough(&["OPENAGENT_TEST_PASSTHROUGH"]);
+        let output = runtime.block_on(tool.execute(args.clone())).unwrap().content.unwrap();
+        assert!(output.contains("OPENAGENT_TEST_PASSTHROUGH=visible"));
+        assert!(!output.contains("OPENAGENT_TEST_SECRET"));
+        assert!(output.contains("PATH="));
+
+        // Full access inherits everything
+        let tool = SystemCommandTool::with_full_access(None);
+        let output = runtime.block_on(tool.execute(args)).unwrap().content.unwrap();
+        assert!(output.contains("OPENAGENT_TEST_SECRET=hidden"));
+    }
+
     #[tokio::test]
     async fn test_system_command_ls() {
         let tool = SystemCommandTool::new();
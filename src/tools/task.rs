@@ -56,6 +56,13 @@ impl Tool for TaskCreateTool {
         })
     }
 
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "title": "Weekly report", "description": "Summarize this week's merged pull requests", "priority": 1 }),
+            "Task created successfully.\nID: 0d9e8f7a-6b5c-4d3e-8f2a-1b0c9d8e7f6a\nTitle: Weekly report\nPriority: 1\nStatus: pending".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let user_id = args
             .get("_user_id")
@@ -133,6 +140,13 @@ impl Tool for TaskListTool {
         })
     }
 
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "status": "pending", "limit": 5 }),
+            "Found 1 task(s):\n\n- [pending] Weekly report (priority: 1, id: 0d9e8f7a)\n  Summarize this week's merged pull requests".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let user_id = args
             .get("_user_id")
@@ -232,6 +246,13 @@ impl Tool for TaskUpdateTool {
         })
     }
 
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "task_id": "0d9e8f7a", "action": "finish", "result": "Report sent" }),
+            "Task 'Weekly report' marked as finished.".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let task_id_str = args
             .get("task_id")
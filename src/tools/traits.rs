@@ -1,5 +1,8 @@
 //! Core tool trait and result types
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -7,6 +10,8 @@ use serde_json::Value;
 use crate::agent::types::{ToolDefinition, FunctionDefinition};
 use crate::error::Result;
 
+use super::permissions::Permission;
+
 /// A tool that can be called by the LLM
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -22,6 +27,19 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: Value) -> Result<ToolResult>;
 
+    /// Capabilities the caller must hold for the registry to run this tool
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![]
+    }
+
+    /// Example arguments and the output they produce, for documentation
+    /// (see `ToolRegistry::explain`)
+    ///
+    /// Defaults to placeholders for the required parameters and no output.
+    fn example(&self) -> (Value, String) {
+        (super::schema::example_arguments(&self.parameters_schema()), String::new())
+    }
+
     /// Convert to OpenRouter tool definition
     fn to_definition(&self) -> ToolDefinition {
         ToolDefinition {
@@ -35,6 +53,34 @@ pub trait Tool: Send + Sync {
     }
 }
 
+/// Shared tools can be re-registered, e.g. when restoring a registry snapshot
+#[async_trait]
+impl Tool for Arc<dyn Tool> {
+    fn name(&self) -> &str {
+        self.as_ref().name()
+    }
+
+    fn description(&self) -> &str {
+        self.as_ref().description()
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.as_ref().parameters_schema()
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolResult> {
+        self.as_ref().execute(args).await
+    }
+
+    fn required_permissions(&self) -> Vec<Permission> {
+        self.as_ref().required_permissions()
+    }
+
+    fn example(&self) -> (Value, String) {
+        self.as_ref().example()
+    }
+}
+
 /// Result of a tool execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResult {
@@ -44,9 +90,9 @@ pub struct ToolResult {
     pub content: Option<String>,
     /// Error message (for failed execution)
     pub error: Option<String>,
-    /// Additional metadata
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub metadata: Option<Value>,
+    /// Structured metadata for the agent loop (`exit_code`, `file_size`, `tokens_used`, ...)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metadata: HashMap<String, Value>,
 }
 
 impl ToolResult {
@@ -56,17 +102,7 @@ impl ToolResult {
             success: true,
             content: Some(content.into()),
             error: None,
-            metadata: None,
-        }
-    }
-
-    /// Create a successful result with metadata
-    pub fn success_with_metadata(content: impl Into<String>, metadata: Value) -> Self {
-        ToolResult {
-            success: true,
-            content: Some(content.into()),
-            error: None,
-            metadata: Some(metadata),
+            metadata: HashMap::new(),
         }
     }
 
@@ -76,10 +112,37 @@ impl ToolResult {
             success: false,
             content: None,
             error: Some(error.into()),
-            metadata: None,
+            metadata: HashMap::new(),
         }
     }
 
+    /// Create a successful result with metadata
+    ///
+    /// The fields of an object become metadata entries; any other value is
+    /// stored under `metadata`.
+    #[deprecated(note = "use `ToolResult::success(content).with_metadata(key, value)`")]
+    pub fn success_with_metadata(content: impl Into<String>, metadata: Value) -> Self {
+        let result = ToolResult::success(content);
+        match metadata {
+            Value::Object(entries) => entries
+                .into_iter()
+                .fold(result, |result, (key, value)| result.with_metadata(&key, value)),
+            Value::Null => result,
+            other => result.with_metadata("metadata", other),
+        }
+    }
+
+    /// Attach a metadata entry
+    pub fn with_metadata(mut self, key: &str, value: Value) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    /// Look up a metadata entry
+    pub fn metadata(&self, key: &str) -> Option<&Value> {
+        self.metadata.get(key)
+    }
+
     /// Convert to a string for the LLM
     pub fn to_string(&self) -> String {
         if self.success {
@@ -99,6 +162,9 @@ pub struct ToolCall {
     pub name: String,
     /// Tool arguments as JSON
     pub arguments: Value,
+    /// ID of the user message whose turn made this call, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_message_id: Option<String>,
 }
 
 impl ToolCall {
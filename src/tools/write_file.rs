@@ -6,6 +6,7 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::path::PathBuf;
 
+use super::permissions::Permission;
 use super::traits::{Tool, ToolResult};
 use crate::error::Result;
 
@@ -47,6 +48,17 @@ impl Tool for WriteFileTool {
         })
     }
 
+    fn required_permissions(&self) -> Vec<Permission> {
+        vec![Permission::FileSystem]
+    }
+
+    fn example(&self) -> (Value, String) {
+        (
+            serde_json::json!({ "path": "notes/todo.md", "content": "- [ ] Renew TLS certificate\n" }),
+            "Successfully wrote 28 bytes to notes/todo.md".to_string(),
+        )
+    }
+
     async fn execute(&self, args: Value) -> Result<ToolResult> {
         let path = args
             .get("path")
@@ -82,7 +94,8 @@ impl Tool for WriteFileTool {
                 "Successfully wrote {} bytes to {}",
                 content.len(),
                 path
-            ))),
+            ))
+            .with_metadata("file_size", serde_json::json!(content.len()))),
             Err(e) => Ok(ToolResult::failure(format!("Failed to write file: {}", e))),
         }
     }